@@ -114,6 +114,30 @@ impl AuditLogger {
         self.log_event(event).await
     }
 
+    /// An operator's input tripped a rate or sanity limit.
+    pub async fn log_input_limited(
+        &self,
+        operator_id: &[u8; 32],
+        session_id: &[u8],
+        reason: &str,
+    ) -> Result<(), AuditError> {
+        let event = AuditEvent {
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            event_type: "input_limited".to_string(),
+            operator_id: Some(operator_id.to_vec()),
+            session_id: Some(session_id.to_vec()),
+            details: serde_json::json!({
+                "reason": reason,
+            }),
+            signature: Vec::new(),
+        };
+
+        self.log_event(event).await
+    }
+
     async fn log_event(&self, mut event: AuditEvent) -> Result<(), AuditError> {
         // Sign the event
         let event_json = serde_json::to_string(&event)
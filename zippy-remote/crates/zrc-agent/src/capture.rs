@@ -3,6 +3,8 @@ use std::sync::Arc;
 use bytes::Bytes;
 use thiserror::Error;
 use tracing::{debug, info, warn};
+use zrc_core::cursor::CursorShape;
+use zrc_core::platform::MonitorBounds;
 
 #[cfg(windows)]
 use zrc_platform_win::capturer::WinCapturer;
@@ -41,6 +43,8 @@ pub struct CaptureFrame {
     pub data: Bytes,
     pub width: u32,
     pub height: u32,
+    /// Bytes per row, including any padding
+    pub stride: u32,
     pub format: CaptureFormat,
     pub timestamp: std::time::Instant,
 }
@@ -73,7 +77,7 @@ impl WindowsCapturer {
         self.monitor_manager.monitors()
             .iter()
             .map(|m| MonitorInfo {
-                id: m.handle as u32,
+                id: zrc_platform_win::platform::monitor_id(&m.device_name),
                 name: m.device_name.clone(),
                 is_primary: m.is_primary,
                 width: (m.bounds.right - m.bounds.left) as u32,
@@ -105,6 +109,7 @@ impl PlatformCapturer for WindowsCapturer {
             data: Bytes::from(frame.bgra.clone()),
             width: frame.width,
             height: frame.height,
+            stride: frame.stride,
             format: CaptureFormat::Bgra8888,
             timestamp: std::time::Instant::now(),
         })
@@ -125,6 +130,99 @@ impl PlatformCapturer for WindowsCapturer {
     }
 }
 
+/// Capture the primary monitor once.
+///
+/// Blocking and self-contained so the session frame pump can call it from a
+/// `Send` closure; `PlatformCapturer` backends cannot move between threads.
+#[cfg(windows)]
+pub fn capture_primary() -> Result<CaptureFrame, CaptureError> {
+    let frame = zrc_platform_win::capture_gdi::capture_primary_bgra()
+        .map_err(|e| CaptureError::CaptureFailed(e.to_string()))?;
+
+    Ok(CaptureFrame {
+        data: Bytes::from(frame.bgra),
+        width: frame.width,
+        height: frame.height,
+        stride: frame.stride,
+        format: CaptureFormat::Bgra8888,
+        timestamp: std::time::Instant::now(),
+    })
+}
+
+#[cfg(not(windows))]
+pub fn capture_primary() -> Result<CaptureFrame, CaptureError> {
+    Err(CaptureError::CaptureFailed(
+        "screen capture not implemented for this platform".to_string(),
+    ))
+}
+
+/// Displays available for capture. The session frame pump asks every frame,
+/// so one enumeration is reused for `MONITOR_REFRESH_INTERVAL`; unplugged
+/// monitors disappear after at most that long.
+#[cfg(windows)]
+pub fn enumerate_monitors() -> Vec<zrc_core::platform::MonitorInfo> {
+    use std::sync::Mutex;
+    use std::time::Instant;
+    use zrc_platform_win::platform::MONITOR_REFRESH_INTERVAL;
+
+    static CACHE: Mutex<Option<(Instant, Vec<zrc_core::platform::MonitorInfo>)>> = Mutex::new(None);
+
+    let mut cache = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((at, monitors)) = cache.as_ref() {
+        if at.elapsed() < MONITOR_REFRESH_INTERVAL {
+            return monitors.clone();
+        }
+    }
+    let monitors = MonitorManager::new()
+        .map(|manager| zrc_platform_win::platform::monitor_infos(manager.monitors()))
+        .unwrap_or_else(|e| {
+            debug!("Monitor enumeration failed: {}", e);
+            Vec::new()
+        });
+    *cache = Some((Instant::now(), monitors.clone()));
+    monitors
+}
+
+#[cfg(not(windows))]
+pub fn enumerate_monitors() -> Vec<zrc_core::platform::MonitorInfo> {
+    Vec::new()
+}
+
+/// Capture one monitor, by its virtual desktop bounds, once.
+#[cfg(windows)]
+pub fn capture_monitor(bounds: &MonitorBounds) -> Result<CaptureFrame, CaptureError> {
+    let frame = zrc_platform_win::capture_gdi::capture_rect_bgra(bounds.x, bounds.y, bounds.width, bounds.height)
+        .map_err(|e| CaptureError::CaptureFailed(e.to_string()))?;
+
+    Ok(CaptureFrame {
+        data: Bytes::from(frame.bgra),
+        width: frame.width,
+        height: frame.height,
+        stride: frame.stride,
+        format: CaptureFormat::Bgra8888,
+        timestamp: std::time::Instant::now(),
+    })
+}
+
+#[cfg(not(windows))]
+pub fn capture_monitor(_bounds: &MonitorBounds) -> Result<CaptureFrame, CaptureError> {
+    Err(CaptureError::MonitorNotFound)
+}
+
+/// Shape of the host cursor, or the arrow if it cannot be read.
+#[cfg(windows)]
+pub fn cursor_shape() -> CursorShape {
+    zrc_platform_win::cursor::current_cursor().unwrap_or_else(|e| {
+        debug!("Cursor read failed: {}", e);
+        CursorShape::default()
+    })
+}
+
+#[cfg(not(windows))]
+pub fn cursor_shape() -> CursorShape {
+    CursorShape::default()
+}
+
 #[derive(Debug, Clone)]
 pub struct MonitorInfo {
     pub id: u32,
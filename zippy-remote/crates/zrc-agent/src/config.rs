@@ -1,6 +1,11 @@
 use std::path::PathBuf;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use zrc_core::policy::ConsentMode;
+use zrc_transport::quic::QuicConfig;
+use crate::input::InputLimits;
+use crate::policy::{PolicyEngine, PolicyRule};
+use crate::service::StatusEndpointConfig;
 use tracing::{error, info};
 
 #[derive(Debug, Error)]
@@ -16,7 +21,14 @@ pub enum ConfigError {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AgentConfig {
     pub bind_addr: String,
+    /// Address operators are told to dial for QUIC sessions; defaults to
+    /// `bind_addr`, which only works when that is not a wildcard address.
+    #[serde(default)]
+    pub advertise_addr: Option<String>,
     pub rendezvous_url: Option<String>,
+    /// QUIC congestion controller: "cubic", "newreno" or "bbr"
+    #[serde(default = "default_congestion_control")]
+    pub congestion_control: String,
     
     // ICE/TURN configuration
     pub stun_servers: Vec<String>,
@@ -33,11 +45,57 @@ pub struct AgentConfig {
     // Policy settings
     pub consent_mode: String, // "always_require", "unattended_allowed", "trusted_only"
     pub allow_unattended: bool,
+    /// Per-operator consent rules. When any are configured, pair and session
+    /// requests no rule covers are denied; when none are, every request goes
+    /// to the consent handler as before.
+    #[serde(default)]
+    pub policy_rules: Vec<PolicyRule>,
+    /// Rate and sanity limits on operator input
+    #[serde(default)]
+    pub input_limits: InputLimits,
     
     // Logging
     pub log_level: String,
     pub log_file: Option<PathBuf>,
     pub audit_log: Option<PathBuf>,
+
+    /// Directory for the encrypted file key store, used only when the agent
+    /// runs with `--file-keystore` and no OS secret store is available
+    #[serde(default)]
+    pub keystore_dir: Option<PathBuf>,
+
+    /// SQLite database holding pairings, invites and session tickets;
+    /// shared by the running agent and `zrc-agent pair`
+    #[serde(default = "default_store_path")]
+    pub store_path: PathBuf,
+
+    /// Where files sent by operators are saved; transfers are refused when
+    /// unset
+    #[serde(default)]
+    pub download_dir: Option<PathBuf>,
+    #[serde(default = "default_max_transfer_bytes")]
+    pub max_transfer_bytes: u64,
+
+    /// Record sessions into this directory, encrypted to the device key.
+    /// Operators are always told a session is being recorded.
+    #[serde(default)]
+    pub recording_dir: Option<PathBuf>,
+
+    // Local status endpoint
+    #[serde(default)]
+    pub status_endpoint: StatusEndpointConfig,
+}
+
+fn default_congestion_control() -> String {
+    "cubic".to_string()
+}
+
+fn default_store_path() -> PathBuf {
+    PathBuf::from("zrc-agent.db")
+}
+
+fn default_max_transfer_bytes() -> u64 {
+    4 * 1024 * 1024 * 1024 // 4 GiB
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -51,7 +109,9 @@ impl Default for AgentConfig {
     fn default() -> Self {
         Self {
             bind_addr: "0.0.0.0:8080".to_string(),
+            advertise_addr: None,
             rendezvous_url: None,
+            congestion_control: default_congestion_control(),
             stun_servers: vec!["stun:stun.l.google.com:19302".to_string()],
             turn_servers: Vec::new(),
             capture_fps: 30,
@@ -60,9 +120,17 @@ impl Default for AgentConfig {
             session_timeout_secs: 28800, // 8 hours
             consent_mode: "always_require".to_string(),
             allow_unattended: false,
+            policy_rules: Vec::new(),
+            input_limits: InputLimits::default(),
             log_level: "info".to_string(),
             log_file: None,
             audit_log: None,
+            keystore_dir: None,
+            store_path: default_store_path(),
+            download_dir: None,
+            max_transfer_bytes: default_max_transfer_bytes(),
+            recording_dir: None,
+            status_endpoint: StatusEndpointConfig::default(),
         }
     }
 }
@@ -85,14 +153,38 @@ impl AgentConfig {
         if let Ok(addr) = std::env::var("ZRC_BIND_ADDR") {
             config.bind_addr = addr;
         }
+        if let Ok(addr) = std::env::var("ZRC_ADVERTISE_ADDR") {
+            config.advertise_addr = Some(addr);
+        }
         if let Ok(url) = std::env::var("ZRC_RENDEZVOUS_URL") {
             config.rendezvous_url = Some(url);
         }
+        if let Ok(cc) = std::env::var("ZRC_CONGESTION_CONTROL") {
+            config.congestion_control = cc;
+        }
         if let Ok(fps) = std::env::var("ZRC_CAPTURE_FPS") {
             if let Ok(fps_val) = fps.parse::<u32>() {
                 config.capture_fps = fps_val;
             }
         }
+        if let Ok(dir) = std::env::var("ZRC_KEYSTORE_DIR") {
+            config.keystore_dir = Some(PathBuf::from(dir));
+        }
+        if let Ok(path) = std::env::var("ZRC_STORE_PATH") {
+            config.store_path = PathBuf::from(path);
+        }
+        if let Ok(dir) = std::env::var("ZRC_DOWNLOAD_DIR") {
+            config.download_dir = Some(PathBuf::from(dir));
+        }
+        if let Ok(dir) = std::env::var("ZRC_RECORDING_DIR") {
+            config.recording_dir = Some(PathBuf::from(dir));
+        }
+        if let Ok(addr) = std::env::var("ZRC_STATUS_ADDR") {
+            config.status_endpoint.bind_addr = addr;
+        }
+        if let Ok(token) = std::env::var("ZRC_STATUS_TOKEN") {
+            config.status_endpoint.token = Some(token);
+        }
         if let Ok(level) = std::env::var("RUST_LOG") {
             config.log_level = level;
         }
@@ -116,6 +208,54 @@ impl AgentConfig {
                 "max_concurrent_sessions must be at least 1".to_string()
             ));
         }
+        if self.input_limits.max_scroll_delta < 0 {
+            return Err(ConfigError::ValidationError(
+                "input_limits.max_scroll_delta must not be negative".to_string()
+            ));
+        }
+        if self.status_endpoint.enabled {
+            self.status_endpoint
+                .socket_addr()
+                .map_err(|e| ConfigError::ValidationError(e.to_string()))?;
+        }
+        self.consent_mode()?;
+        self.policy_engine()?;
+        self.quic_config()?;
         Ok(())
     }
+
+    /// QUIC transport settings for the session listener.
+    pub fn quic_config(&self) -> Result<QuicConfig, ConfigError> {
+        let congestion_control = self
+            .congestion_control
+            .parse()
+            .map_err(ConfigError::ValidationError)?;
+        Ok(QuicConfig {
+            congestion_control,
+            ..QuicConfig::default()
+        })
+    }
+
+    /// Build the per-operator rule engine, or `None` if no rules are set.
+    pub fn policy_engine(&self) -> Result<Option<PolicyEngine>, ConfigError> {
+        if self.policy_rules.is_empty() {
+            return Ok(None);
+        }
+        PolicyEngine::new(self.consent_mode()?)
+            .with_rules(&self.policy_rules)
+            .map(Some)
+            .map_err(|e| ConfigError::ValidationError(e.to_string()))
+    }
+
+    /// Parse `consent_mode` into the core policy mode.
+    pub fn consent_mode(&self) -> Result<ConsentMode, ConfigError> {
+        match self.consent_mode.as_str() {
+            "always_require" => Ok(ConsentMode::AlwaysRequire),
+            "unattended_allowed" => Ok(ConsentMode::UnattendedAllowed),
+            "trusted_only" => Ok(ConsentMode::TrustedOperatorsOnly),
+            other => Err(ConfigError::ValidationError(format!(
+                "unknown consent_mode {:?}", other
+            ))),
+        }
+    }
 }
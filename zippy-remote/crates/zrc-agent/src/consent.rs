@@ -1,6 +1,9 @@
 use async_trait::async_trait;
 use std::sync::Arc;
-use zrc_proto::v1::{PermissionV1, UserIdV1};
+use zrc_core::pairing::{ConsentHandler as CorePairingConsent, PairDecision, PairingError};
+use zrc_core::session::{SessionConsentDecision, SessionConsentHandler, SessionError};
+use zrc_proto::v1::{PermissionV1, PermissionsV1};
+use crate::policy::{Decision, PolicyEngine};
 use thiserror::Error;
 use tracing::{info, warn};
 
@@ -138,3 +141,162 @@ impl ConsentHandler for HeadlessConsentHandler {
         Ok(())
     }
 }
+
+/// `PermissionsV1` bitmask for a list of permissions.
+pub fn permission_bits(permissions: &[PermissionV1]) -> u32 {
+    permissions.iter().fold(0, |acc, perm| {
+        acc | match perm {
+            PermissionV1::View => PermissionsV1::View as u32,
+            PermissionV1::Control => PermissionsV1::Control as u32,
+            PermissionV1::Clipboard => PermissionsV1::Clipboard as u32,
+            PermissionV1::Files => PermissionsV1::FileTransfer as u32,
+            PermissionV1::Audio => PermissionsV1::Audio as u32,
+            _ => 0,
+        }
+    })
+}
+
+/// Permissions named by a `PermissionsV1` bitmask.
+fn permissions_from_bits(bits: u32) -> Vec<PermissionV1> {
+    [
+        PermissionV1::View,
+        PermissionV1::Control,
+        PermissionV1::Clipboard,
+        PermissionV1::Files,
+        PermissionV1::Audio,
+    ]
+    .into_iter()
+    .filter(|perm| bits & permission_bits(&[*perm]) != 0)
+    .collect()
+}
+
+fn operator_id32(operator_id: &[u8]) -> [u8; 32] {
+    let mut id = [0u8; 32];
+    let len = operator_id.len().min(32);
+    id[..len].copy_from_slice(&operator_id[..len]);
+    id
+}
+
+/// Routes zrc-core pairing and session consent prompts to the agent's
+/// `ConsentHandler`, treating handler errors (denied, timed out, UI failure)
+/// as a rejection.
+///
+/// With a rule engine attached, each request is decided by the rules first:
+/// denied requests never reach the handler, unattended ones are approved
+/// without a prompt, and prompted ones are capped at the rule's permissions.
+pub struct ConsentBridge {
+    handler: Arc<dyn ConsentHandler>,
+    policy: Option<Arc<PolicyEngine>>,
+}
+
+impl ConsentBridge {
+    pub fn new(handler: Arc<dyn ConsentHandler>) -> Self {
+        Self { handler, policy: None }
+    }
+
+    pub fn with_policy(mut self, policy: Arc<PolicyEngine>) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    /// Rule decision for a request, or a plain prompt when no rules are set.
+    fn decide(&self, operator_id: &[u8; 32], requested_perms: u32) -> Decision {
+        match &self.policy {
+            Some(policy) => policy.evaluate(operator_id, requested_perms),
+            None => Decision::Prompt { permissions: requested_perms },
+        }
+    }
+}
+
+#[async_trait]
+impl CorePairingConsent for ConsentBridge {
+    async fn request_consent(
+        &self,
+        operator_id: &[u8],
+        _sas: Option<&str>,
+    ) -> Result<PairDecision, PairingError> {
+        let operator_id = operator_id32(operator_id);
+        let requested = vec![PermissionV1::View, PermissionV1::Control];
+        let allowed = match self.decide(&operator_id, permission_bits(&requested)) {
+            Decision::Deny { reason } => {
+                info!("Pairing with {} denied: {}", hex::encode(operator_id), reason);
+                return Ok(PairDecision {
+                    approved: false,
+                    granted_perms: Vec::new(),
+                    unattended_enabled: false,
+                    require_consent_each_time: true,
+                });
+            }
+            Decision::Unattended { permissions } => {
+                info!("Pairing with {} approved by policy", hex::encode(operator_id));
+                return Ok(PairDecision {
+                    approved: true,
+                    granted_perms: permissions_from_bits(permissions),
+                    unattended_enabled: true,
+                    require_consent_each_time: false,
+                });
+            }
+            Decision::Prompt { permissions } => permissions,
+        };
+
+        let request = PairingConsentRequest {
+            operator_id,
+            operator_name: None,
+            requested_permissions: permissions_from_bits(allowed),
+        };
+        let decision = match self.handler.request_pairing_consent(request).await {
+            Ok(decision) => decision,
+            Err(e) => {
+                info!("Pairing with {} not approved: {}", hex::encode(operator_id), e);
+                ConsentDecision { approved: false, granted_permissions: Vec::new() }
+            }
+        };
+
+        Ok(PairDecision {
+            approved: decision.approved,
+            granted_perms: permissions_from_bits(permission_bits(&decision.granted_permissions) & allowed),
+            unattended_enabled: false,
+            require_consent_each_time: true,
+        })
+    }
+}
+
+#[async_trait]
+impl SessionConsentHandler for ConsentBridge {
+    async fn request_consent(
+        &self,
+        operator_id: &[u8],
+        requested_permissions: u32,
+        paired_permissions: u32,
+    ) -> Result<SessionConsentDecision, SessionError> {
+        let operator_id = operator_id32(operator_id);
+        let allowed = match self.decide(&operator_id, requested_permissions & paired_permissions) {
+            Decision::Deny { reason } => {
+                info!("Session for {} denied: {}", hex::encode(operator_id), reason);
+                return Ok(SessionConsentDecision { approved: false, granted_permissions: 0 });
+            }
+            Decision::Unattended { permissions } => {
+                return Ok(SessionConsentDecision { approved: true, granted_permissions: permissions });
+            }
+            Decision::Prompt { permissions } => permissions,
+        };
+
+        let request = SessionConsentRequest {
+            operator_id,
+            operator_name: None,
+            requested_permissions: permissions_from_bits(allowed),
+            session_id: Vec::new(),
+        };
+        match self.handler.request_session_consent(request).await {
+            Ok(decision) if decision.approved => Ok(SessionConsentDecision {
+                approved: true,
+                granted_permissions: permission_bits(&decision.granted_permissions) & allowed,
+            }),
+            Ok(_) => Ok(SessionConsentDecision { approved: false, granted_permissions: 0 }),
+            Err(e) => {
+                info!("Session for {} not approved: {}", hex::encode(operator_id), e);
+                Ok(SessionConsentDecision { approved: false, granted_permissions: 0 })
+            }
+        }
+    }
+}
@@ -1,10 +1,28 @@
-//! File transfer via WebRTC DataChannel.
+//! Chunked file transfer over the session control channel.
+//!
+//! Transfers are driven by `FileTransferControlV1` messages: START carries the
+//! file name, size and whole-file SHA-256, each DATA chunk carries its offset
+//! and its own SHA-256, and the receiver ACKs the number of bytes it has
+//! durably written. Partial data lives in a `.part` file named after the
+//! transfer id, so a sender that reconnects (or a restarted agent) resumes
+//! from the last acknowledged offset instead of starting over.
 
-use std::path::PathBuf;
-use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::collections::HashMap;
+use std::io::{ErrorKind, SeekFrom};
+use std::path::{Component, Path, PathBuf};
+use sha2::{Digest, Sha256};
+use tokio::fs::{self, File, OpenOptions};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+use tokio::sync::Mutex;
 use thiserror::Error;
 use tracing::{debug, info, warn};
+use zrc_proto::v1::{FileActionV1, FileTransferControlV1, PermissionsV1};
+
+/// DATA payload size used by `OutgoingTransfer`.
+pub const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Largest DATA payload the receiver accepts.
+const MAX_CHUNK_SIZE: usize = 1024 * 1024;
 
 #[derive(Debug, Error)]
 pub enum FileTransferError {
@@ -16,11 +34,113 @@ pub enum FileTransferError {
     TransferFailed(String),
     #[error("integrity check failed")]
     IntegrityCheckFailed,
+    #[error("invalid file name: {0:?}")]
+    InvalidFileName(String),
+    #[error("unknown transfer {0}")]
+    UnknownTransfer(String),
+    #[error("not enough disk space")]
+    DiskFull,
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+impl FileTransferError {
+    /// ERROR reply telling the sender why the transfer stopped.
+    pub fn to_message(&self, transfer_id: &[u8]) -> FileTransferControlV1 {
+        FileTransferControlV1 {
+            transfer_id: transfer_id.to_vec(),
+            action: FileActionV1::Error as i32,
+            error_message: self.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+fn io_error(e: std::io::Error) -> FileTransferError {
+    if is_enospc(&e) {
+        FileTransferError::DiskFull
+    } else {
+        FileTransferError::Io(e)
+    }
+}
+
+#[cfg(unix)]
+fn is_enospc(e: &std::io::Error) -> bool {
+    e.raw_os_error() == Some(28)
+}
+
+#[cfg(windows)]
+fn is_enospc(e: &std::io::Error) -> bool {
+    // ERROR_HANDLE_DISK_FULL, ERROR_DISK_FULL
+    matches!(e.raw_os_error(), Some(39) | Some(112))
+}
+
+#[cfg(not(any(unix, windows)))]
+fn is_enospc(_e: &std::io::Error) -> bool {
+    false
+}
+
+/// Check that `name` is a single plain file name, so joining it onto the
+/// download directory cannot escape it.
+pub fn validate_file_name(name: &str) -> Result<&str, FileTransferError> {
+    let invalid = || FileTransferError::InvalidFileName(name.to_string());
+    if name.is_empty() || name.len() > 255 || name.contains(['/', '\\', '\0', ':']) {
+        return Err(invalid());
+    }
+    let mut components = Path::new(name).components();
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(part)), None) if part == name => Ok(name),
+        _ => Err(invalid()),
+    }
+}
+
+fn transfer_key(transfer_id: &[u8]) -> Result<String, FileTransferError> {
+    if transfer_id.len() != 16 {
+        return Err(FileTransferError::TransferFailed(
+            "transfer_id must be 16 bytes".to_string(),
+        ));
+    }
+    Ok(hex::encode(transfer_id))
+}
+
+fn ack(transfer_id: &[u8], action: FileActionV1, progress: u64) -> FileTransferControlV1 {
+    FileTransferControlV1 {
+        transfer_id: transfer_id.to_vec(),
+        action: action as i32,
+        progress,
+        ..Default::default()
+    }
 }
 
+async fn sha256_file(path: &Path) -> Result<[u8; 32], FileTransferError> {
+    let mut file = File::open(path).await?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; CHUNK_SIZE];
+    loop {
+        let n = file.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hasher.finalize().into())
+}
+
+/// A file being received into `download_dir`.
+struct IncomingTransfer {
+    file_name: String,
+    file_size: u64,
+    file_sha256: [u8; 32],
+    part_path: PathBuf,
+    /// Bytes written and synced to `part_path`
+    committed: u64,
+}
+
+/// Receiving end of file transfers for one agent.
 pub struct FileTransfer {
     download_dir: PathBuf,
     max_file_size: u64,
+    incoming: Mutex<HashMap<String, IncomingTransfer>>,
 }
 
 impl FileTransfer {
@@ -28,30 +148,452 @@ impl FileTransfer {
         Self {
             download_dir,
             max_file_size,
+            incoming: Mutex::new(HashMap::new()),
         }
     }
 
-    pub async fn handle_download(&self, file_path: PathBuf) -> Result<Vec<u8>, FileTransferError> {
-        // TODO: Implement file download
-        warn!("File download not yet implemented");
-        Err(FileTransferError::TransferFailed("Not implemented".to_string()))
+    /// Handle one control message from a session holding `granted_permissions`
+    /// and return the reply to send back, if any. Errors should be reported
+    /// to the sender with `FileTransferError::to_message`.
+    pub async fn handle_control(
+        &self,
+        msg: &FileTransferControlV1,
+        granted_permissions: u32,
+    ) -> Result<Option<FileTransferControlV1>, FileTransferError> {
+        if granted_permissions & PermissionsV1::FileTransfer as u32 == 0 {
+            return Err(FileTransferError::PermissionDenied(
+                "session does not hold the file_transfer permission".to_string(),
+            ));
+        }
+        let key = transfer_key(&msg.transfer_id)?;
+
+        match FileActionV1::try_from(msg.action).unwrap_or(FileActionV1::Unspecified) {
+            FileActionV1::Start => self.start(key, msg).await.map(Some),
+            FileActionV1::Resume => {
+                let incoming = self.incoming.lock().await;
+                let transfer = incoming
+                    .get(&key)
+                    .ok_or(FileTransferError::UnknownTransfer(key.clone()))?;
+                Ok(Some(ack(&msg.transfer_id, FileActionV1::Ack, transfer.committed)))
+            }
+            FileActionV1::Data => self.write_chunk(key, msg).await.map(Some),
+            FileActionV1::Complete => self.complete(key, msg).await.map(Some),
+            FileActionV1::Cancel => {
+                if let Some(transfer) = self.incoming.lock().await.remove(&key) {
+                    let _ = fs::remove_file(&transfer.part_path).await;
+                    info!("Transfer {} of {} cancelled", key, transfer.file_name);
+                }
+                Ok(None)
+            }
+            FileActionV1::Error => {
+                // Sender gave up; keep the partial file in case it resumes
+                warn!("Sender aborted transfer {}: {}", key, msg.error_message);
+                Ok(None)
+            }
+            other => {
+                debug!("Ignoring file action {:?} for transfer {}", other, key);
+                Ok(None)
+            }
+        }
     }
 
-    pub async fn handle_upload(&self, file_name: String, data: Vec<u8>) -> Result<(), FileTransferError> {
-        if data.len() as u64 > self.max_file_size {
+    async fn start(
+        &self,
+        key: String,
+        msg: &FileTransferControlV1,
+    ) -> Result<FileTransferControlV1, FileTransferError> {
+        let file_name = validate_file_name(&msg.file_name)?.to_string();
+        if msg.file_size > self.max_file_size {
+            return Err(FileTransferError::TransferFailed(format!(
+                "file size {} exceeds limit {}",
+                msg.file_size, self.max_file_size
+            )));
+        }
+        let file_sha256: [u8; 32] = msg.file_sha256.as_slice().try_into().map_err(|_| {
+            FileTransferError::TransferFailed("file_sha256 must be 32 bytes".to_string())
+        })?;
+
+        let mut incoming = self.incoming.lock().await;
+        if let Some(transfer) = incoming.get(&key) {
+            if transfer.file_name != file_name
+                || transfer.file_size != msg.file_size
+                || transfer.file_sha256 != file_sha256
+            {
+                return Err(FileTransferError::TransferFailed(
+                    "transfer id reused for a different file".to_string(),
+                ));
+            }
+            return Ok(ack(&msg.transfer_id, FileActionV1::Ack, transfer.committed));
+        }
+
+        fs::create_dir_all(&self.download_dir).await.map_err(io_error)?;
+        let part_path = self.download_dir.join(format!(".{}.part", key));
+        // A part file left by an earlier run of this transfer is resumed; it
+        // only ever holds chunks that passed their hash check
+        let committed = match fs::metadata(&part_path).await {
+            Ok(meta) if meta.len() <= msg.file_size => meta.len(),
+            Ok(_) => {
+                fs::remove_file(&part_path).await?;
+                0
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => 0,
+            Err(e) => return Err(e.into()),
+        };
+        if committed == 0 {
+            File::create(&part_path).await.map_err(io_error)?;
+        }
+
+        info!(
+            "Receiving {} ({} bytes) as transfer {}, starting at {}",
+            file_name, msg.file_size, key, committed
+        );
+        incoming.insert(
+            key,
+            IncomingTransfer {
+                file_name,
+                file_size: msg.file_size,
+                file_sha256,
+                part_path,
+                committed,
+            },
+        );
+        Ok(ack(&msg.transfer_id, FileActionV1::Ack, committed))
+    }
+
+    async fn write_chunk(
+        &self,
+        key: String,
+        msg: &FileTransferControlV1,
+    ) -> Result<FileTransferControlV1, FileTransferError> {
+        let mut incoming = self.incoming.lock().await;
+        let transfer = incoming
+            .get_mut(&key)
+            .ok_or(FileTransferError::UnknownTransfer(key.clone()))?;
+
+        // Duplicates and chunks past a gap are answered with the committed
+        // offset so the sender rewinds or skips ahead
+        if msg.progress != transfer.committed {
+            return Ok(ack(&msg.transfer_id, FileActionV1::Ack, transfer.committed));
+        }
+        if msg.data.is_empty() || msg.data.len() > MAX_CHUNK_SIZE {
+            return Err(FileTransferError::TransferFailed(format!(
+                "chunk of {} bytes", msg.data.len()
+            )));
+        }
+        if transfer.committed + msg.data.len() as u64 > transfer.file_size {
             return Err(FileTransferError::TransferFailed(
-                format!("File size {} exceeds limit {}", data.len(), self.max_file_size)
+                "chunk runs past the announced file size".to_string(),
             ));
         }
+        if Sha256::digest(&msg.data).as_slice() != msg.chunk_sha256.as_slice() {
+            return Err(FileTransferError::IntegrityCheckFailed);
+        }
+
+        let mut file = OpenOptions::new()
+            .write(true)
+            .open(&transfer.part_path)
+            .await
+            .map_err(io_error)?;
+        file.seek(SeekFrom::Start(transfer.committed)).await?;
+        let written = async {
+            file.write_all(&msg.data).await?;
+            file.sync_data().await
+        }
+        .await;
+        if let Err(e) = written {
+            // Drop whatever part of the chunk made it to disk so the part
+            // file stays a prefix of verified chunks
+            let _ = file.set_len(transfer.committed).await;
+            return Err(io_error(e));
+        }
+
+        transfer.committed += msg.data.len() as u64;
+        Ok(ack(&msg.transfer_id, FileActionV1::Ack, transfer.committed))
+    }
+
+    async fn complete(
+        &self,
+        key: String,
+        msg: &FileTransferControlV1,
+    ) -> Result<FileTransferControlV1, FileTransferError> {
+        let mut incoming = self.incoming.lock().await;
+        let transfer = incoming
+            .get(&key)
+            .ok_or(FileTransferError::UnknownTransfer(key.clone()))?;
+        if transfer.committed != transfer.file_size {
+            return Err(FileTransferError::TransferFailed(format!(
+                "complete after {} of {} bytes",
+                transfer.committed, transfer.file_size
+            )));
+        }
+
+        let transfer = incoming.remove(&key).expect("transfer present");
+        if sha256_file(&transfer.part_path).await? != transfer.file_sha256 {
+            let _ = fs::remove_file(&transfer.part_path).await;
+            return Err(FileTransferError::IntegrityCheckFailed);
+        }
+
+        let dest = self.unused_destination(&transfer.file_name).await;
+        fs::rename(&transfer.part_path, &dest).await?;
+        info!("Received {} into {}", transfer.file_name, dest.display());
+        Ok(ack(&msg.transfer_id, FileActionV1::Complete, transfer.file_size))
+    }
+
+    /// `name` in the download directory, suffixed with a counter if a file
+    /// of that name already exists.
+    async fn unused_destination(&self, name: &str) -> PathBuf {
+        let candidate = self.download_dir.join(name);
+        if fs::metadata(&candidate).await.is_err() {
+            return candidate;
+        }
+        let path = Path::new(name);
+        let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or(name);
+        let ext = path.extension().and_then(|e| e.to_str());
+        for n in 1.. {
+            let numbered = match ext {
+                Some(ext) => format!("{} ({}).{}", stem, n, ext),
+                None => format!("{} ({})", stem, n),
+            };
+            let candidate = self.download_dir.join(numbered);
+            if fs::metadata(&candidate).await.is_err() {
+                return candidate;
+            }
+        }
+        unreachable!()
+    }
+}
+
+/// Sending end of one transfer, reading from a local file.
+pub struct OutgoingTransfer {
+    transfer_id: [u8; 16],
+    path: PathBuf,
+    file_name: String,
+    file_size: u64,
+    file_sha256: [u8; 32],
+    acked: u64,
+}
+
+impl OutgoingTransfer {
+    /// Prepare to send `path`, hashing it up front for the START message.
+    pub async fn open(path: PathBuf) -> Result<Self, FileTransferError> {
+        let meta = fs::metadata(&path)
+            .await
+            .map_err(|_| FileTransferError::FileNotFound(path.display().to_string()))?;
+        if !meta.is_file() {
+            return Err(FileTransferError::FileNotFound(path.display().to_string()));
+        }
+        let file_name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .ok_or_else(|| FileTransferError::InvalidFileName(path.display().to_string()))?
+            .to_string();
+        let mut transfer_id = [0u8; 16];
+        getrandom::getrandom(&mut transfer_id)
+            .map_err(|e| FileTransferError::TransferFailed(e.to_string()))?;
+
+        Ok(Self {
+            transfer_id,
+            file_sha256: sha256_file(&path).await?,
+            path,
+            file_name,
+            file_size: meta.len(),
+            acked: 0,
+        })
+    }
+
+    pub fn transfer_id(&self) -> &[u8; 16] {
+        &self.transfer_id
+    }
+
+    /// Bytes the receiver has acknowledged.
+    pub fn acked(&self) -> u64 {
+        self.acked
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.acked == self.file_size
+    }
+
+    pub fn start_msg(&self) -> FileTransferControlV1 {
+        FileTransferControlV1 {
+            transfer_id: self.transfer_id.to_vec(),
+            action: FileActionV1::Start as i32,
+            file_name: self.file_name.clone(),
+            file_size: self.file_size,
+            file_sha256: self.file_sha256.to_vec(),
+            ..Default::default()
+        }
+    }
 
-        // TODO: Implement file upload with integrity check
-        warn!("File upload not yet implemented");
-        Err(FileTransferError::TransferFailed("Not implemented".to_string()))
+    /// Sent after a reconnect; the receiver answers with its committed offset.
+    pub fn resume_msg(&self) -> FileTransferControlV1 {
+        ack(&self.transfer_id, FileActionV1::Resume, self.acked)
     }
 
-    pub async fn resume_transfer(&self, transfer_id: &str) -> Result<(), FileTransferError> {
-        // TODO: Implement transfer resume
-        warn!("Transfer resume not yet implemented");
-        Err(FileTransferError::TransferFailed("Not implemented".to_string()))
+    pub fn complete_msg(&self) -> FileTransferControlV1 {
+        FileTransferControlV1 {
+            file_sha256: self.file_sha256.to_vec(),
+            ..ack(&self.transfer_id, FileActionV1::Complete, self.file_size)
+        }
+    }
+
+    /// Record the receiver's ACK; the next chunk starts at its offset.
+    pub fn on_ack(&mut self, msg: &FileTransferControlV1) -> Result<(), FileTransferError> {
+        if msg.transfer_id != self.transfer_id {
+            return Err(FileTransferError::UnknownTransfer(hex::encode(&msg.transfer_id)));
+        }
+        match FileActionV1::try_from(msg.action).unwrap_or(FileActionV1::Unspecified) {
+            FileActionV1::Ack if msg.progress <= self.file_size => {
+                self.acked = msg.progress;
+                Ok(())
+            }
+            FileActionV1::Ack => Err(FileTransferError::TransferFailed(
+                "receiver acknowledged more than the file size".to_string(),
+            )),
+            FileActionV1::Error => Err(FileTransferError::TransferFailed(msg.error_message.clone())),
+            other => Err(FileTransferError::TransferFailed(format!(
+                "unexpected {:?} from receiver", other
+            ))),
+        }
+    }
+
+    /// DATA message for the chunk after the last ACK, or `None` once the
+    /// whole file is acknowledged.
+    pub async fn next_chunk(&self) -> Result<Option<FileTransferControlV1>, FileTransferError> {
+        if self.is_done() {
+            return Ok(None);
+        }
+        let len = (self.file_size - self.acked).min(CHUNK_SIZE as u64) as usize;
+        let mut data = vec![0u8; len];
+        let mut file = File::open(&self.path).await?;
+        file.seek(SeekFrom::Start(self.acked)).await?;
+        file.read_exact(&mut data).await?;
+
+        Ok(Some(FileTransferControlV1 {
+            chunk_sha256: Sha256::digest(&data).to_vec(),
+            data,
+            ..ack(&self.transfer_id, FileActionV1::Data, self.acked)
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILE_TRANSFER: u32 = PermissionsV1::FileTransfer as u32;
+
+    fn sample(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 31 % 251) as u8).collect()
+    }
+
+    async fn reply(receiver: &FileTransfer, msg: FileTransferControlV1) -> FileTransferControlV1 {
+        receiver
+            .handle_control(&msg, FILE_TRANSFER)
+            .await
+            .unwrap()
+            .expect("reply")
+    }
+
+    #[tokio::test]
+    async fn resume_after_disconnect_rebuilds_identical_file() {
+        let src_dir = tempfile::tempdir().unwrap();
+        let dst_dir = tempfile::tempdir().unwrap();
+        let contents = sample(CHUNK_SIZE * 3 + 1234);
+        let src = src_dir.path().join("report.bin");
+        std::fs::write(&src, &contents).unwrap();
+
+        let mut sender = OutgoingTransfer::open(src).await.unwrap();
+        let receiver = FileTransfer::new(dst_dir.path().to_path_buf(), 1 << 30);
+        let ack = reply(&receiver, sender.start_msg()).await;
+        sender.on_ack(&ack).unwrap();
+
+        // One chunk lands, then the link drops with the next chunk written
+        // but its ACK lost
+        let chunk = sender.next_chunk().await.unwrap().unwrap();
+        sender.on_ack(&reply(&receiver, chunk).await).unwrap();
+        let chunk = sender.next_chunk().await.unwrap().unwrap();
+        reply(&receiver, chunk).await;
+        assert_eq!(sender.acked(), CHUNK_SIZE as u64);
+
+        // The agent restarts too; its state is rebuilt from the part file
+        let receiver = FileTransfer::new(dst_dir.path().to_path_buf(), 1 << 30);
+        let ack = reply(&receiver, sender.start_msg()).await;
+        assert_eq!(ack.progress, 2 * CHUNK_SIZE as u64);
+        sender.on_ack(&reply(&receiver, sender.resume_msg()).await).unwrap();
+        assert_eq!(sender.acked(), 2 * CHUNK_SIZE as u64);
+
+        while let Some(chunk) = sender.next_chunk().await.unwrap() {
+            sender.on_ack(&reply(&receiver, chunk).await).unwrap();
+        }
+        let done = reply(&receiver, sender.complete_msg()).await;
+        assert_eq!(done.action, FileActionV1::Complete as i32);
+
+        let received = std::fs::read(dst_dir.path().join("report.bin")).unwrap();
+        assert_eq!(received, contents);
+        assert_eq!(std::fs::read_dir(dst_dir.path()).unwrap().count(), 1);
+    }
+
+    #[tokio::test]
+    async fn corrupted_chunk_and_whole_file_are_rejected() {
+        let src_dir = tempfile::tempdir().unwrap();
+        let dst_dir = tempfile::tempdir().unwrap();
+        let src = src_dir.path().join("a.txt");
+        std::fs::write(&src, sample(100)).unwrap();
+        let mut sender = OutgoingTransfer::open(src).await.unwrap();
+        let receiver = FileTransfer::new(dst_dir.path().to_path_buf(), 1 << 20);
+        sender.on_ack(&reply(&receiver, sender.start_msg()).await).unwrap();
+
+        let mut chunk = sender.next_chunk().await.unwrap().unwrap();
+        chunk.data[0] ^= 0xff;
+        assert!(matches!(
+            receiver.handle_control(&chunk, FILE_TRANSFER).await,
+            Err(FileTransferError::IntegrityCheckFailed)
+        ));
+
+        // A chunk whose own hash matches but differs from the announced file
+        let mut chunk = sender.next_chunk().await.unwrap().unwrap();
+        chunk.data[0] ^= 0xff;
+        chunk.chunk_sha256 = Sha256::digest(&chunk.data).to_vec();
+        sender.on_ack(&reply(&receiver, chunk).await).unwrap();
+        assert!(matches!(
+            receiver.handle_control(&sender.complete_msg(), FILE_TRANSFER).await,
+            Err(FileTransferError::IntegrityCheckFailed)
+        ));
+        assert_eq!(std::fs::read_dir(dst_dir.path()).unwrap().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn start_is_gated_on_permission_name_and_size() {
+        let dst_dir = tempfile::tempdir().unwrap();
+        let receiver = FileTransfer::new(dst_dir.path().to_path_buf(), 1000);
+        let start = |name: &str, size: u64| FileTransferControlV1 {
+            transfer_id: vec![7; 16],
+            action: FileActionV1::Start as i32,
+            file_name: name.to_string(),
+            file_size: size,
+            file_sha256: vec![0; 32],
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            receiver.handle_control(&start("ok.txt", 10), PermissionsV1::View as u32).await,
+            Err(FileTransferError::PermissionDenied(_))
+        ));
+        for name in ["../evil", "..", ".", "dir/x", "dir\\x", "/etc/passwd", "C:x", ""] {
+            assert!(
+                matches!(
+                    receiver.handle_control(&start(name, 10), FILE_TRANSFER).await,
+                    Err(FileTransferError::InvalidFileName(_))
+                ),
+                "{:?} accepted",
+                name
+            );
+        }
+        assert!(matches!(
+            receiver.handle_control(&start("big.bin", 1001), FILE_TRANSFER).await,
+            Err(FileTransferError::TransferFailed(_))
+        ));
+        assert!(receiver.handle_control(&start("ok.txt", 10), FILE_TRANSFER).await.is_ok());
     }
 }
@@ -2,7 +2,8 @@ use std::sync::Arc;
 use zrc_crypto::identity::Identity;
 use zrc_crypto::cert_binding::{sign_cert_fingerprint, verify_cert_binding, CertBinding, CertBindingError};
 use zrc_crypto::hash::sha256;
-use zrc_proto::v1::PublicKeyBundleV1;
+use zrc_core::types::IdentityKeys;
+use zrc_proto::v1::{KeyTypeV1, PublicKeyBundleV1, PublicKeyV1};
 use async_trait::async_trait;
 use thiserror::Error;
 use tracing::{error, info, warn};
@@ -115,6 +116,24 @@ impl IdentityManager {
         self.identity.public_bundle()
     }
 
+    /// The identity as the key bundle the zrc-core pairing and session
+    /// state machines expect.
+    pub fn identity_keys(&self) -> IdentityKeys {
+        IdentityKeys {
+            sign: self.identity.sign_key().clone(),
+            sign_pub: PublicKeyV1 {
+                key_type: KeyTypeV1::Ed25519 as i32,
+                key_bytes: self.identity.sign_pub().to_vec(),
+            },
+            kex_priv: self.identity.kex_secret().clone(),
+            kex_pub: PublicKeyV1 {
+                key_type: KeyTypeV1::X25519 as i32,
+                key_bytes: self.identity.kex_pub().to_vec(),
+            },
+            id32: self.device_id,
+        }
+    }
+
     /// Generate a DTLS certificate binding.
     /// This signs the DTLS certificate fingerprint with the device's Ed25519 identity key.
     pub fn bind_dtls_cert(&self, dtls_fingerprint: &[u8; 32]) -> CertBinding {
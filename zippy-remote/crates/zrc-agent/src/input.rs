@@ -1,7 +1,13 @@
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::Instant;
 use thiserror::Error;
-use tracing::{debug, info, warn};
+#[cfg(windows)]
+use tracing::info;
+use tracing::warn;
+use zrc_proto::v1::{InputEventTypeV1, InputEventV1};
 
 #[cfg(windows)]
 use zrc_platform_win::injector::WinInjector;
@@ -14,11 +20,162 @@ pub enum InputError {
     CoordinateOutOfBounds,
     #[error("key not found")]
     KeyNotFound,
+    #[error("input rate limit exceeded")]
+    RateLimited,
+    #[error("input rejected: {0}")]
+    Rejected(String),
+}
+
+/// Coordinates beyond this are rejected even before the first frame has
+/// told us the screen size.
+const MAX_COORDINATE: i32 = 32768;
+
+/// Per-session limits on injected input, set under `[input_limits]` in the
+/// agent config.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InputLimits {
+    /// Sustained events per second; 0 disables rate limiting
+    pub max_events_per_sec: u32,
+    /// Events allowed in a burst above the sustained rate
+    pub burst: u32,
+    /// Scroll deltas are clamped to +/- this
+    pub max_scroll_delta: i32,
+    /// Longest `KeyChar` text accepted, in characters
+    pub max_text_len: usize,
+}
+
+impl Default for InputLimits {
+    fn default() -> Self {
+        Self {
+            max_events_per_sec: 500,
+            burst: 1000,
+            max_scroll_delta: 1200, // ten wheel notches
+            max_text_len: 256,
+        }
+    }
+}
+
+/// Size of the frames a session is streaming, shared between the capture
+/// path (which sets it) and the input filter (which checks against it), and
+/// where the streamed monitor sits on the virtual desktop.
+#[derive(Debug, Default)]
+pub struct ScreenBounds {
+    size: AtomicU64,
+    origin: AtomicU64,
+}
+
+impl ScreenBounds {
+    pub fn set(&self, width: u32, height: u32) {
+        self.size.store(((width as u64) << 32) | height as u64, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> Option<(u32, u32)> {
+        match self.size.load(Ordering::Relaxed) {
+            0 => None,
+            packed => Some(((packed >> 32) as u32, packed as u32)),
+        }
+    }
+
+    /// Top-left corner of the streamed monitor; pointer positions arrive
+    /// relative to it.
+    pub fn set_origin(&self, x: i32, y: i32) {
+        self.origin.store(((x as u32 as u64) << 32) | y as u32 as u64, Ordering::Relaxed);
+    }
+
+    pub fn origin(&self) -> (i32, i32) {
+        let packed = self.origin.load(Ordering::Relaxed);
+        ((packed >> 32) as u32 as i32, packed as u32 as i32)
+    }
+}
+
+/// Token bucket plus sanity checks applied before an event reaches the
+/// injector.
+pub struct InputFilter {
+    limits: InputLimits,
+    bounds: Arc<ScreenBounds>,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl InputFilter {
+    pub fn new(limits: InputLimits, bounds: Arc<ScreenBounds>) -> Self {
+        Self {
+            tokens: limits.burst.max(1) as f64,
+            limits,
+            bounds,
+            last_refill: Instant::now(),
+        }
+    }
+
+    /// Check `event` at time `now`, clamping what can be clamped.
+    ///
+    /// `releases_held` says the event releases a key or button the session
+    /// holds down; such releases bypass the rate limit so a throttled session
+    /// cannot leave inputs held. Any other release is limited like the rest.
+    pub fn check(
+        &mut self,
+        event: &mut InputEventV1,
+        releases_held: bool,
+        now: Instant,
+    ) -> Result<(), InputError> {
+        if releases_held {
+            return Ok(());
+        }
+        match event.event_type() {
+            InputEventTypeV1::MouseMove => {
+                let (width, height) = self
+                    .bounds
+                    .get()
+                    .map(|(w, h)| (w as i32, h as i32))
+                    .unwrap_or((MAX_COORDINATE, MAX_COORDINATE));
+                if !(0..width).contains(&event.mouse_x) || !(0..height).contains(&event.mouse_y) {
+                    return Err(InputError::Rejected(format!(
+                        "mouse position ({}, {}) outside {}x{}",
+                        event.mouse_x, event.mouse_y, width, height
+                    )));
+                }
+            }
+            InputEventTypeV1::MouseMoveRelative => {
+                event.mouse_x = event.mouse_x.clamp(-MAX_COORDINATE, MAX_COORDINATE);
+                event.mouse_y = event.mouse_y.clamp(-MAX_COORDINATE, MAX_COORDINATE);
+            }
+            InputEventTypeV1::Scroll => {
+                let max = self.limits.max_scroll_delta;
+                event.scroll_delta_x = event.scroll_delta_x.clamp(-max, max);
+                event.scroll_delta_y = event.scroll_delta_y.clamp(-max, max);
+            }
+            InputEventTypeV1::KeyChar if event.text.chars().count() > self.limits.max_text_len => {
+                return Err(InputError::Rejected(format!(
+                    "{} characters of text", event.text.chars().count()
+                )));
+            }
+            _ => {}
+        }
+        self.take_token(now)
+    }
+
+    fn take_token(&mut self, now: Instant) -> Result<(), InputError> {
+        if self.limits.max_events_per_sec == 0 {
+            return Ok(());
+        }
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        self.last_refill = now.max(self.last_refill);
+        let capacity = self.limits.burst.max(1) as f64;
+        self.tokens = (self.tokens + elapsed * self.limits.max_events_per_sec as f64).min(capacity);
+        if self.tokens < 1.0 {
+            return Err(InputError::RateLimited);
+        }
+        self.tokens -= 1.0;
+        Ok(())
+    }
 }
 
 #[async_trait]
 pub trait PlatformInjector: Send + Sync {
     async fn inject_mouse_move(&mut self, x: i32, y: i32) -> Result<(), InputError>;
+    /// Move the pointer by a delta from where it is now.
+    async fn inject_mouse_move_relative(&mut self, dx: i32, dy: i32) -> Result<(), InputError>;
     async fn inject_mouse_button(&mut self, button: MouseButton, pressed: bool) -> Result<(), InputError>;
     async fn inject_mouse_scroll(&mut self, delta_x: i32, delta_y: i32) -> Result<(), InputError>;
     async fn inject_key(&mut self, key: u32, pressed: bool) -> Result<(), InputError>;
@@ -35,6 +192,146 @@ pub enum MouseButton {
     X2,
 }
 
+impl MouseButton {
+    /// Map a wire button number (1=left, 2=right, 3=middle, 4=x1, 5=x2).
+    pub fn from_wire(button: u32) -> Option<Self> {
+        match button {
+            1 => Some(MouseButton::Left),
+            2 => Some(MouseButton::Right),
+            3 => Some(MouseButton::Middle),
+            4 => Some(MouseButton::X1),
+            5 => Some(MouseButton::X2),
+            _ => None,
+        }
+    }
+}
+
+/// Apply one input event received on a session's control channel.
+pub async fn apply_input_event(
+    injector: &mut dyn PlatformInjector,
+    event: &InputEventV1,
+) -> Result<(), InputError> {
+    let button = || {
+        MouseButton::from_wire(event.button)
+            .ok_or_else(|| InputError::InjectionFailed(format!("unknown mouse button {}", event.button)))
+    };
+
+    match event.event_type() {
+        InputEventTypeV1::MouseMove => injector.inject_mouse_move(event.mouse_x, event.mouse_y).await,
+        InputEventTypeV1::MouseDown => injector.inject_mouse_button(button()?, true).await,
+        InputEventTypeV1::MouseUp => injector.inject_mouse_button(button()?, false).await,
+        InputEventTypeV1::KeyDown => injector.inject_key(event.key_code, true).await,
+        InputEventTypeV1::KeyUp => injector.inject_key(event.key_code, false).await,
+        InputEventTypeV1::KeyChar => injector.inject_text(&event.text).await,
+        InputEventTypeV1::Scroll => {
+            injector.inject_mouse_scroll(event.scroll_delta_x, event.scroll_delta_y).await
+        }
+        InputEventTypeV1::MouseMoveRelative => {
+            injector.inject_mouse_move_relative(event.mouse_x, event.mouse_y).await
+        }
+        InputEventTypeV1::Unspecified => Ok(()),
+    }
+}
+
+/// Injects one session's input and tracks which keys and mouse buttons it
+/// holds down, so they can be released when the session ends however it
+/// ends. Without this a dropped connection mid-shortcut leaves the host with
+/// a stuck modifier.
+pub struct SessionInput {
+    injector: Box<dyn PlatformInjector>,
+    filter: Option<InputFilter>,
+    screen: Option<Arc<ScreenBounds>>,
+    /// In press order
+    held_keys: Vec<u32>,
+    held_buttons: Vec<MouseButton>,
+}
+
+impl SessionInput {
+    pub fn new(injector: Box<dyn PlatformInjector>) -> Self {
+        Self {
+            injector,
+            filter: None,
+            screen: None,
+            held_keys: Vec::new(),
+            held_buttons: Vec::new(),
+        }
+    }
+
+    /// Rate-limit and sanity-check events before they are injected.
+    pub fn with_filter(mut self, filter: InputFilter) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    /// Offset pointer positions by the streamed monitor's origin.
+    pub fn with_screen(mut self, screen: Arc<ScreenBounds>) -> Self {
+        self.screen = Some(screen);
+        self
+    }
+
+    pub async fn apply(&mut self, event: &InputEventV1) -> Result<(), InputError> {
+        let mut event = event.clone();
+        if let Some(filter) = self.filter.as_mut() {
+            let releases_held = match event.event_type() {
+                InputEventTypeV1::KeyUp => self.held_keys.contains(&event.key_code),
+                InputEventTypeV1::MouseUp => MouseButton::from_wire(event.button)
+                    .is_some_and(|button| self.held_buttons.contains(&button)),
+                _ => false,
+            };
+            filter.check(&mut event, releases_held, Instant::now())?;
+        }
+        if let (InputEventTypeV1::MouseMove, Some(screen)) = (event.event_type(), &self.screen) {
+            let (x, y) = screen.origin();
+            event.mouse_x = event.mouse_x.saturating_add(x);
+            event.mouse_y = event.mouse_y.saturating_add(y);
+        }
+        let event = &event;
+
+        // A failed release leaves the input held, so it is retried on close
+        apply_input_event(self.injector.as_mut(), event).await?;
+
+        match event.event_type() {
+            InputEventTypeV1::KeyDown if !self.held_keys.contains(&event.key_code) => {
+                self.held_keys.push(event.key_code);
+            }
+            InputEventTypeV1::KeyUp => self.held_keys.retain(|k| *k != event.key_code),
+            InputEventTypeV1::MouseDown | InputEventTypeV1::MouseUp => {
+                if let Some(button) = MouseButton::from_wire(event.button) {
+                    self.held_buttons.retain(|b| *b != button);
+                    if event.event_type() == InputEventTypeV1::MouseDown {
+                        self.held_buttons.push(button);
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    pub fn is_holding_input(&self) -> bool {
+        !self.held_keys.is_empty() || !self.held_buttons.is_empty()
+    }
+
+    /// Release every held key and button, most recently pressed first.
+    /// Every release is attempted; the first failure is returned.
+    pub async fn release_all(&mut self) -> Result<(), InputError> {
+        let mut result = Ok(());
+        for key in std::mem::take(&mut self.held_keys).into_iter().rev() {
+            if let Err(e) = self.injector.inject_key(key, false).await {
+                warn!("Failed to release key {:#x}: {}", key, e);
+                result = result.and(Err(e));
+            }
+        }
+        for button in std::mem::take(&mut self.held_buttons).into_iter().rev() {
+            if let Err(e) = self.injector.inject_mouse_button(button, false).await {
+                warn!("Failed to release {:?} mouse button: {}", button, e);
+                result = result.and(Err(e));
+            }
+        }
+        result.and(self.injector.release_all_keys().await)
+    }
+}
+
 #[cfg(windows)]
 pub struct WindowsInjector {
     injector: WinInjector,
@@ -61,6 +358,11 @@ impl PlatformInjector for WindowsInjector {
             .map_err(|e| InputError::InjectionFailed(e.to_string()))
     }
 
+    async fn inject_mouse_move_relative(&mut self, dx: i32, dy: i32) -> Result<(), InputError> {
+        self.injector.inject_mouse_move_relative(dx, dy)
+            .map_err(|e| InputError::InjectionFailed(e.to_string()))
+    }
+
     async fn inject_mouse_button(&mut self, button: MouseButton, pressed: bool) -> Result<(), InputError> {
         // WinInjector uses u32 for button: 1=Left, 2=Right, 3=Middle, 4=X1, 5=X2
         let win_button = match button {
@@ -117,3 +419,263 @@ impl PlatformInjector for WindowsInjector {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    const VK_CONTROL: u32 = 0x11;
+    const VK_C: u32 = 0x43;
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Injected {
+        Key(u32, bool),
+        Button(MouseButton, bool),
+        Move(i32, i32),
+        MoveBy(i32, i32),
+        Other,
+    }
+
+    #[derive(Default)]
+    struct RecordingInjector {
+        log: Arc<Mutex<Vec<Injected>>>,
+    }
+
+    #[async_trait]
+    impl PlatformInjector for RecordingInjector {
+        async fn inject_mouse_move(&mut self, x: i32, y: i32) -> Result<(), InputError> {
+            self.log.lock().unwrap().push(Injected::Move(x, y));
+            Ok(())
+        }
+        async fn inject_mouse_move_relative(&mut self, dx: i32, dy: i32) -> Result<(), InputError> {
+            self.log.lock().unwrap().push(Injected::MoveBy(dx, dy));
+            Ok(())
+        }
+        async fn inject_mouse_button(&mut self, button: MouseButton, pressed: bool) -> Result<(), InputError> {
+            self.log.lock().unwrap().push(Injected::Button(button, pressed));
+            Ok(())
+        }
+        async fn inject_mouse_scroll(&mut self, _dx: i32, _dy: i32) -> Result<(), InputError> {
+            self.log.lock().unwrap().push(Injected::Other);
+            Ok(())
+        }
+        async fn inject_key(&mut self, key: u32, pressed: bool) -> Result<(), InputError> {
+            self.log.lock().unwrap().push(Injected::Key(key, pressed));
+            Ok(())
+        }
+        async fn inject_text(&mut self, _text: &str) -> Result<(), InputError> {
+            self.log.lock().unwrap().push(Injected::Other);
+            Ok(())
+        }
+        async fn release_all_keys(&mut self) -> Result<(), InputError> {
+            Ok(())
+        }
+    }
+
+    fn key(event_type: InputEventTypeV1, key_code: u32) -> InputEventV1 {
+        InputEventV1 { event_type: event_type as i32, key_code, ..Default::default() }
+    }
+
+    fn button(event_type: InputEventTypeV1, button: u32) -> InputEventV1 {
+        InputEventV1 { event_type: event_type as i32, button, ..Default::default() }
+    }
+
+    fn mouse_move(x: i32, y: i32) -> InputEventV1 {
+        InputEventV1 {
+            event_type: InputEventTypeV1::MouseMove as i32,
+            mouse_x: x,
+            mouse_y: y,
+            ..Default::default()
+        }
+    }
+
+    fn limits(max_events_per_sec: u32, burst: u32) -> InputLimits {
+        InputLimits { max_events_per_sec, burst, ..InputLimits::default() }
+    }
+
+    #[test]
+    fn burst_beyond_cap_is_throttled_then_refills() {
+        let mut filter = InputFilter::new(limits(100, 10), Arc::new(ScreenBounds::default()));
+        let start = Instant::now();
+
+        let allowed = (0..50)
+            .filter(|_| filter.check(&mut mouse_move(5, 5), false, start).is_ok())
+            .count();
+        assert_eq!(allowed, 10);
+        assert!(matches!(
+            filter.check(&mut mouse_move(5, 5), false, start),
+            Err(InputError::RateLimited)
+        ));
+        // Releasing something held is never throttled; other releases are
+        assert!(filter.check(&mut key(InputEventTypeV1::KeyUp, VK_CONTROL), true, start).is_ok());
+        assert!(matches!(
+            filter.check(&mut key(InputEventTypeV1::KeyUp, VK_CONTROL), false, start),
+            Err(InputError::RateLimited)
+        ));
+
+        // 55ms at 100/s buys five more events
+        let later = start + std::time::Duration::from_millis(55);
+        let allowed = (0..50)
+            .filter(|_| filter.check(&mut mouse_move(5, 5), false, later).is_ok())
+            .count();
+        assert_eq!(allowed, 5);
+    }
+
+    #[tokio::test]
+    async fn out_of_bounds_mouse_is_not_forwarded() {
+        let injector = RecordingInjector::default();
+        let log = injector.log.clone();
+        let bounds = Arc::new(ScreenBounds::default());
+        bounds.set(1920, 1080);
+        let mut input = SessionInput::new(Box::new(injector))
+            .with_filter(InputFilter::new(InputLimits::default(), bounds));
+
+        for (x, y) in [(1920, 10), (10, 1080), (-1, 10), (10, i32::MIN)] {
+            assert!(matches!(
+                input.apply(&mouse_move(x, y)).await,
+                Err(InputError::Rejected(_))
+            ));
+        }
+        assert!(log.lock().unwrap().is_empty());
+
+        input.apply(&mouse_move(1919, 1079)).await.unwrap();
+        assert_eq!(*log.lock().unwrap(), vec![Injected::Move(1919, 1079)]);
+    }
+
+    #[tokio::test]
+    async fn mouse_moves_follow_the_streamed_monitor() {
+        let injector = RecordingInjector::default();
+        let log = injector.log.clone();
+        let bounds = Arc::new(ScreenBounds::default());
+        bounds.set(1280, 1024);
+        bounds.set_origin(-1280, 56);
+        let mut input = SessionInput::new(Box::new(injector))
+            .with_screen(bounds.clone())
+            .with_filter(InputFilter::new(InputLimits::default(), bounds));
+
+        // Checked against the monitor's size, injected on the desktop
+        input.apply(&mouse_move(10, 20)).await.unwrap();
+        assert!(input.apply(&mouse_move(1280, 20)).await.is_err());
+        assert_eq!(*log.lock().unwrap(), vec![Injected::Move(-1270, 76)]);
+    }
+
+    #[tokio::test]
+    async fn relative_moves_are_injected_as_deltas() {
+        let injector = RecordingInjector::default();
+        let log = injector.log.clone();
+        let bounds = Arc::new(ScreenBounds::default());
+        bounds.set(1280, 1024);
+        bounds.set_origin(-1280, 56);
+        let mut input = SessionInput::new(Box::new(injector))
+            .with_screen(bounds.clone())
+            .with_filter(InputFilter::new(InputLimits::default(), bounds));
+
+        // Deltas are not offset by the monitor origin; huge ones are clamped
+        let relative = |dx, dy| InputEventV1 {
+            event_type: InputEventTypeV1::MouseMoveRelative as i32,
+            mouse_x: dx,
+            mouse_y: dy,
+            ..Default::default()
+        };
+        input.apply(&relative(-5, 12)).await.unwrap();
+        input.apply(&relative(i32::MAX, 0)).await.unwrap();
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec![Injected::MoveBy(-5, 12), Injected::MoveBy(MAX_COORDINATE, 0)]
+        );
+    }
+
+    #[tokio::test]
+    async fn only_held_inputs_are_released_past_the_limit() {
+        let injector = RecordingInjector::default();
+        let log = injector.log.clone();
+        let mut input = SessionInput::new(Box::new(injector))
+            .with_filter(InputFilter::new(limits(1, 1), Arc::new(ScreenBounds::default())));
+
+        input.apply(&key(InputEventTypeV1::KeyDown, VK_CONTROL)).await.unwrap();
+        // Out of tokens: a flood of releases for keys never pressed is throttled
+        assert!(matches!(
+            input.apply(&key(InputEventTypeV1::KeyUp, VK_C)).await,
+            Err(InputError::RateLimited)
+        ));
+        // but the held Ctrl still gets released
+        input.apply(&key(InputEventTypeV1::KeyUp, VK_CONTROL)).await.unwrap();
+
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec![Injected::Key(VK_CONTROL, true), Injected::Key(VK_CONTROL, false)]
+        );
+    }
+
+    #[test]
+    fn scroll_is_clamped_and_long_text_rejected() {
+        let mut filter = InputFilter::new(InputLimits::default(), Arc::new(ScreenBounds::default()));
+        let mut scroll = InputEventV1 {
+            event_type: InputEventTypeV1::Scroll as i32,
+            scroll_delta_x: -1_000_000,
+            scroll_delta_y: 240,
+            ..Default::default()
+        };
+        filter.check(&mut scroll, false, Instant::now()).unwrap();
+        assert_eq!((scroll.scroll_delta_x, scroll.scroll_delta_y), (-1200, 240));
+
+        let mut text = InputEventV1 {
+            event_type: InputEventTypeV1::KeyChar as i32,
+            text: "x".repeat(257),
+            ..Default::default()
+        };
+        assert!(matches!(
+            filter.check(&mut text, false, Instant::now()),
+            Err(InputError::Rejected(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn held_ctrl_is_released_after_disconnect() {
+        let injector = RecordingInjector::default();
+        let log = injector.log.clone();
+        let mut input = SessionInput::new(Box::new(injector));
+
+        input.apply(&key(InputEventTypeV1::KeyDown, VK_CONTROL)).await.unwrap();
+        // The connection drops here; closing the session releases what is held
+        assert!(input.is_holding_input());
+        input.release_all().await.unwrap();
+
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec![Injected::Key(VK_CONTROL, true), Injected::Key(VK_CONTROL, false)]
+        );
+        assert!(!input.is_holding_input());
+    }
+
+    #[tokio::test]
+    async fn only_inputs_still_held_are_released_in_reverse_order() {
+        let injector = RecordingInjector::default();
+        let log = injector.log.clone();
+        let mut input = SessionInput::new(Box::new(injector));
+
+        input.apply(&key(InputEventTypeV1::KeyDown, VK_CONTROL)).await.unwrap();
+        input.apply(&key(InputEventTypeV1::KeyDown, VK_C)).await.unwrap();
+        input.apply(&key(InputEventTypeV1::KeyDown, VK_C)).await.unwrap(); // auto-repeat
+        input.apply(&button(InputEventTypeV1::MouseDown, 1)).await.unwrap();
+        input.apply(&button(InputEventTypeV1::MouseDown, 2)).await.unwrap();
+        input.apply(&button(InputEventTypeV1::MouseUp, 2)).await.unwrap();
+        log.lock().unwrap().clear();
+
+        input.release_all().await.unwrap();
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec![
+                Injected::Key(VK_C, false),
+                Injected::Key(VK_CONTROL, false),
+                Injected::Button(MouseButton::Left, false),
+            ]
+        );
+
+        // Nothing is held any more, so a second close injects nothing
+        log.lock().unwrap().clear();
+        input.release_all().await.unwrap();
+        assert!(log.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,403 @@
+//! Platform key stores for the agent identity.
+//!
+//! Each OS secret store implements `identity::KeyStore`: DPAPI on Windows
+//! (see `identity`), the Secret Service on Linux and the Keychain on macOS.
+//! Hosts without one can opt into `EncryptedFileKeyStore`, which seals each
+//! key under a passphrase.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+use zeroize::Zeroizing;
+
+use crate::identity::{IdentityError, KeyStore};
+
+/// Service / collection name keys are filed under in OS secret stores
+pub const KEYSTORE_SERVICE: &str = "zrc-agent";
+
+/// Minimum passphrase length for the encrypted file key store
+pub const MIN_PASSPHRASE_LEN: usize = 8;
+
+/// Secret Service (libsecret / GNOME Keyring / KWallet) backed key store.
+#[cfg(all(target_os = "linux", feature = "secret-service"))]
+pub struct SecretServiceKeyStore {
+    store: zrc_platform_linux::secret_store::SecretStore,
+}
+
+#[cfg(all(target_os = "linux", feature = "secret-service"))]
+impl SecretServiceKeyStore {
+    /// Connect to the session's Secret Service, unlocking the default
+    /// collection if needed.
+    pub async fn connect() -> Result<Self, IdentityError> {
+        let store = zrc_platform_linux::secret_store::SecretStore::new(KEYSTORE_SERVICE.to_string())
+            .await
+            .map_err(|e| IdentityError::KeyStorageFailed(e.to_string()))?;
+        Ok(Self { store })
+    }
+}
+
+#[cfg(all(target_os = "linux", feature = "secret-service"))]
+#[async_trait]
+impl KeyStore for SecretServiceKeyStore {
+    async fn store_key(&self, key_id: &str, key_data: &[u8]) -> Result<(), IdentityError> {
+        self.store
+            .store_key(key_id, key_data)
+            .await
+            .map_err(|e| IdentityError::KeyStorageFailed(e.to_string()))
+    }
+
+    async fn load_key(&self, key_id: &str) -> Result<Vec<u8>, IdentityError> {
+        let key = self
+            .store
+            .load_key(key_id)
+            .await
+            .map_err(|e| IdentityError::KeyLoadingFailed(e.to_string()))?;
+        Ok(key.as_bytes().to_vec())
+    }
+
+    async fn key_exists(&self, key_id: &str) -> bool {
+        self.store.load_key(key_id).await.is_ok()
+    }
+}
+
+/// macOS Keychain backed key store.
+#[cfg(target_os = "macos")]
+pub struct KeychainKeyStore {
+    store: zrc_platform_mac::keychain::KeychainStore,
+}
+
+#[cfg(target_os = "macos")]
+impl KeychainKeyStore {
+    pub fn new() -> Self {
+        Self {
+            store: zrc_platform_mac::keychain::KeychainStore::new(KEYSTORE_SERVICE.to_string(), None),
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+#[async_trait]
+impl KeyStore for KeychainKeyStore {
+    async fn store_key(&self, key_id: &str, key_data: &[u8]) -> Result<(), IdentityError> {
+        self.store
+            .store_key(key_id, key_data)
+            .map_err(|e| IdentityError::KeyStorageFailed(e.to_string()))
+    }
+
+    async fn load_key(&self, key_id: &str) -> Result<Vec<u8>, IdentityError> {
+        let key = self
+            .store
+            .load_key(key_id)
+            .map_err(|e| IdentityError::KeyLoadingFailed(e.to_string()))?;
+        Ok(key.as_bytes().to_vec())
+    }
+
+    async fn key_exists(&self, key_id: &str) -> bool {
+        self.store.load_key(key_id).is_ok()
+    }
+}
+
+/// On-disk format of one sealed key.
+#[derive(Serialize, Deserialize)]
+struct SealedKeyFile {
+    version: u32,
+    kdf: String,
+    m_cost: u32,
+    t_cost: u32,
+    p_cost: u32,
+    /// KDF salt (hex)
+    salt: String,
+    /// XChaCha20-Poly1305 nonce, 24 bytes (hex)
+    nonce: String,
+    /// Sealed key bytes (hex)
+    ciphertext: String,
+}
+
+impl SealedKeyFile {
+    const CURRENT_VERSION: u32 = 1;
+    const KDF_ARGON2ID: &'static str = "argon2id";
+    const DEFAULT_M_COST: u32 = 19 * 1024;
+    const DEFAULT_T_COST: u32 = 2;
+    const DEFAULT_P_COST: u32 = 1;
+}
+
+/// Passphrase-encrypted key files, one per key id.
+///
+/// Each key is sealed with XChaCha20-Poly1305 under an Argon2id key derived
+/// from the passphrase and a per-file salt, with the key id as associated
+/// data so files cannot be swapped. Only used when no OS secret store is
+/// available and the operator explicitly enabled it.
+pub struct EncryptedFileKeyStore {
+    dir: PathBuf,
+    passphrase: Zeroizing<String>,
+}
+
+impl EncryptedFileKeyStore {
+    pub fn new(dir: impl Into<PathBuf>, passphrase: String) -> Result<Self, IdentityError> {
+        let passphrase = Zeroizing::new(passphrase);
+        if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+            return Err(IdentityError::KeyStorageFailed(format!(
+                "keystore passphrase must be at least {} characters",
+                MIN_PASSPHRASE_LEN
+            )));
+        }
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)
+            .map_err(|e| IdentityError::KeyStorageFailed(format!("{}: {}", dir.display(), e)))?;
+        Ok(Self { dir, passphrase })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn key_path(&self, key_id: &str) -> Result<PathBuf, IdentityError> {
+        let valid = !key_id.is_empty()
+            && key_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+        if !valid {
+            return Err(IdentityError::KeyStorageFailed(format!("invalid key id {:?}", key_id)));
+        }
+        Ok(self.dir.join(format!("{}.key", key_id)))
+    }
+
+    fn derive_key(
+        &self,
+        salt: &[u8],
+        m_cost: u32,
+        t_cost: u32,
+        p_cost: u32,
+    ) -> Result<Zeroizing<[u8; 32]>, String> {
+        let params = argon2::Params::new(m_cost, t_cost, p_cost, Some(32))
+            .map_err(|e| format!("invalid KDF parameters: {}", e))?;
+        let argon = argon2::Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params);
+
+        let mut key = Zeroizing::new([0u8; 32]);
+        argon
+            .hash_password_into(self.passphrase.as_bytes(), salt, &mut key[..])
+            .map_err(|e| format!("key derivation failed: {}", e))?;
+        Ok(key)
+    }
+
+    fn seal(&self, key_id: &str, key_data: &[u8]) -> Result<SealedKeyFile, String> {
+        let mut salt = [0u8; 16];
+        let mut nonce = [0u8; 24];
+        getrandom::getrandom(&mut salt).map_err(|e| e.to_string())?;
+        getrandom::getrandom(&mut nonce).map_err(|e| e.to_string())?;
+
+        let key = self.derive_key(
+            &salt,
+            SealedKeyFile::DEFAULT_M_COST,
+            SealedKeyFile::DEFAULT_T_COST,
+            SealedKeyFile::DEFAULT_P_COST,
+        )?;
+        let ciphertext = XChaCha20Poly1305::new(Key::from_slice(&key[..]))
+            .encrypt(XNonce::from_slice(&nonce), Payload { msg: key_data, aad: key_id.as_bytes() })
+            .map_err(|_| "encryption failed".to_string())?;
+
+        Ok(SealedKeyFile {
+            version: SealedKeyFile::CURRENT_VERSION,
+            kdf: SealedKeyFile::KDF_ARGON2ID.to_string(),
+            m_cost: SealedKeyFile::DEFAULT_M_COST,
+            t_cost: SealedKeyFile::DEFAULT_T_COST,
+            p_cost: SealedKeyFile::DEFAULT_P_COST,
+            salt: hex::encode(salt),
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(ciphertext),
+        })
+    }
+
+    fn open(&self, key_id: &str, file: &SealedKeyFile) -> Result<Vec<u8>, String> {
+        if file.version != SealedKeyFile::CURRENT_VERSION {
+            return Err(format!("unsupported key file version {}", file.version));
+        }
+        if file.kdf != SealedKeyFile::KDF_ARGON2ID {
+            return Err(format!("unsupported KDF {:?}", file.kdf));
+        }
+
+        let decode = |field: &str, value: &str| {
+            hex::decode(value).map_err(|e| format!("invalid {} hex: {}", field, e))
+        };
+        let salt = decode("salt", &file.salt)?;
+        let nonce = decode("nonce", &file.nonce)?;
+        let ciphertext = decode("ciphertext", &file.ciphertext)?;
+        if nonce.len() != 24 {
+            return Err(format!("invalid nonce length {}", nonce.len()));
+        }
+
+        let key = self.derive_key(&salt, file.m_cost, file.t_cost, file.p_cost)?;
+        XChaCha20Poly1305::new(Key::from_slice(&key[..]))
+            .decrypt(XNonce::from_slice(&nonce), Payload { msg: &ciphertext, aad: key_id.as_bytes() })
+            .map_err(|_| "wrong passphrase or corrupted key file".to_string())
+    }
+}
+
+/// Write `contents` to `path` via a temporary file, readable only by the
+/// owner on Unix.
+fn write_private(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let tmp = path.with_extension("key.tmp");
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&tmp)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    std::fs::rename(&tmp, path)
+}
+
+#[async_trait]
+impl KeyStore for EncryptedFileKeyStore {
+    async fn store_key(&self, key_id: &str, key_data: &[u8]) -> Result<(), IdentityError> {
+        let path = self.key_path(key_id)?;
+        let sealed = self.seal(key_id, key_data).map_err(IdentityError::KeyStorageFailed)?;
+        let json = serde_json::to_vec_pretty(&sealed)
+            .map_err(|e| IdentityError::KeyStorageFailed(e.to_string()))?;
+        write_private(&path, &json)
+            .map_err(|e| IdentityError::KeyStorageFailed(format!("{}: {}", path.display(), e)))
+    }
+
+    async fn load_key(&self, key_id: &str) -> Result<Vec<u8>, IdentityError> {
+        let path = self.key_path(key_id)?;
+        let json = std::fs::read(&path)
+            .map_err(|e| IdentityError::KeyLoadingFailed(format!("{}: {}", path.display(), e)))?;
+        let sealed: SealedKeyFile = serde_json::from_slice(&json)
+            .map_err(|e| IdentityError::KeyLoadingFailed(format!("{}: {}", path.display(), e)))?;
+        self.open(key_id, &sealed).map_err(IdentityError::KeyLoadingFailed)
+    }
+
+    async fn key_exists(&self, key_id: &str) -> bool {
+        self.key_path(key_id).map(|path| path.is_file()).unwrap_or(false)
+    }
+}
+
+/// Where the encrypted file key store lives and how it is unlocked.
+pub struct FileKeyStoreConfig {
+    pub dir: PathBuf,
+    pub passphrase: String,
+}
+
+/// Open the OS secret store for this platform.
+pub async fn platform_keystore() -> Result<Arc<dyn KeyStore>, IdentityError> {
+    #[cfg(windows)]
+    {
+        Ok(Arc::new(zrc_platform_win::keystore::DpapiKeyStore::new(
+            zrc_platform_win::keystore::DpapiScope::CurrentUser,
+        )))
+    }
+    #[cfg(all(target_os = "linux", feature = "secret-service"))]
+    {
+        Ok(Arc::new(SecretServiceKeyStore::connect().await?))
+    }
+    #[cfg(all(target_os = "linux", not(feature = "secret-service")))]
+    {
+        Err(IdentityError::KeyStorageFailed(
+            "built without Secret Service support (enable the secret-service feature)".to_string(),
+        ))
+    }
+    #[cfg(target_os = "macos")]
+    {
+        Ok(Arc::new(KeychainKeyStore::new()))
+    }
+    #[cfg(not(any(windows, target_os = "linux", target_os = "macos")))]
+    {
+        Err(IdentityError::KeyStorageFailed(
+            "no OS secret store on this platform".to_string(),
+        ))
+    }
+}
+
+/// Open the OS secret store, falling back to `file_fallback` only when one
+/// was explicitly configured.
+pub async fn open_keystore(
+    file_fallback: Option<FileKeyStoreConfig>,
+) -> Result<Arc<dyn KeyStore>, IdentityError> {
+    match platform_keystore().await {
+        Ok(store) => Ok(store),
+        Err(e) => {
+            let Some(fallback) = file_fallback else {
+                return Err(e);
+            };
+            warn!("OS secret store unavailable ({}); using encrypted file key store", e);
+            let store = EncryptedFileKeyStore::new(fallback.dir, fallback.passphrase)?;
+            info!("Key store directory: {}", store.dir().display());
+            Ok(Arc::new(store))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PASSPHRASE: &str = "correct horse battery";
+
+    #[tokio::test]
+    async fn test_file_keystore_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = EncryptedFileKeyStore::new(dir.path(), PASSPHRASE.to_string()).unwrap();
+
+        assert!(!store.key_exists("zrc_identity_sign_key").await);
+        store.store_key("zrc_identity_sign_key", &[7u8; 32]).await.unwrap();
+        assert!(store.key_exists("zrc_identity_sign_key").await);
+        assert_eq!(store.load_key("zrc_identity_sign_key").await.unwrap(), vec![7u8; 32]);
+
+        // The file holds ciphertext, not the key
+        let raw = std::fs::read_to_string(dir.path().join("zrc_identity_sign_key.key")).unwrap();
+        assert!(!raw.contains(&hex::encode([7u8; 32])));
+
+        // Reopening with the same passphrase reads the key back
+        let reopened = EncryptedFileKeyStore::new(dir.path(), PASSPHRASE.to_string()).unwrap();
+        assert_eq!(reopened.load_key("zrc_identity_sign_key").await.unwrap(), vec![7u8; 32]);
+    }
+
+    #[tokio::test]
+    async fn test_file_keystore_rejects_wrong_passphrase_and_swapped_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = EncryptedFileKeyStore::new(dir.path(), PASSPHRASE.to_string()).unwrap();
+        store.store_key("sign", &[1u8; 32]).await.unwrap();
+
+        let wrong = EncryptedFileKeyStore::new(dir.path(), "not the passphrase".to_string()).unwrap();
+        assert!(wrong.load_key("sign").await.is_err());
+
+        // A key file copied under another id fails authentication
+        std::fs::copy(dir.path().join("sign.key"), dir.path().join("kex.key")).unwrap();
+        assert!(store.load_key("kex").await.is_err());
+
+        assert!(store.store_key("../escape", &[0u8; 32]).await.is_err());
+        assert!(EncryptedFileKeyStore::new(dir.path(), "short".to_string()).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_identity_manager_uses_file_keystore() {
+        let dir = tempfile::tempdir().unwrap();
+        let store: Arc<dyn KeyStore> =
+            Arc::new(EncryptedFileKeyStore::new(dir.path(), PASSPHRASE.to_string()).unwrap());
+
+        let first = crate::identity::IdentityManager::new(store.clone()).await.unwrap();
+        let second = crate::identity::IdentityManager::new(store).await.unwrap();
+        assert_eq!(first.device_id(), second.device_id());
+    }
+
+    #[test]
+    fn test_platform_keystores_are_key_stores() {
+        fn assert_key_store<T: KeyStore + 'static>() {}
+
+        assert_key_store::<EncryptedFileKeyStore>();
+        #[cfg(windows)]
+        assert_key_store::<zrc_platform_win::keystore::DpapiKeyStore>();
+        #[cfg(all(target_os = "linux", feature = "secret-service"))]
+        assert_key_store::<SecretServiceKeyStore>();
+        #[cfg(target_os = "macos")]
+        assert_key_store::<KeychainKeyStore>();
+    }
+}
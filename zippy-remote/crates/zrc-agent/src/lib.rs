@@ -10,10 +10,13 @@ pub mod consent;
 pub mod file_transfer;
 pub mod identity;
 pub mod input;
+pub mod keystore;
 pub mod media_transport;
 pub mod pairing;
 pub mod policy;
+pub mod recording;
 pub mod replay;
+pub mod runtime;
 pub mod service;
 pub mod session;
 pub mod signaling;
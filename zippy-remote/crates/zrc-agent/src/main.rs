@@ -1,7 +1,7 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use std::path::PathBuf;
 use std::sync::Arc;
-use tracing::info;
+use tracing::{error, info};
 use zrc_agent::*;
 
 #[derive(Parser)]
@@ -19,6 +19,36 @@ struct Args {
     /// Log level
     #[arg(short, long, default_value = "info")]
     log_level: String,
+
+    /// Fall back to a passphrase-encrypted key file (keystore_dir, unlocked
+    /// with ZRC_KEYSTORE_PASSPHRASE) when no OS secret store is available
+    #[arg(long)]
+    file_keystore: bool,
+
+    /// Check that the running agent service is healthy and exit: it must
+    /// answer on the status endpoint, run this version and listen for QUIC
+    #[arg(long)]
+    healthcheck: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create a pairing invite and print it with its secret. Give both to
+    /// the operator (`zrc-controller pair --invite <invite> --secret <secret>`)
+    /// over separate channels; the secret is not logged anywhere.
+    Pair {
+        /// Seconds the invite stays valid
+        #[arg(long, default_value_t = 600)]
+        ttl: u32,
+    },
+    /// Decrypt a session recording made by this device and list its records
+    Replay {
+        /// Recording file (.zrcrec)
+        file: PathBuf,
+    },
 }
 
 #[tokio::main]
@@ -40,24 +70,40 @@ async fn main() -> anyhow::Result<()> {
         config::AgentConfig::load_from_env()
     };
 
-    // Initialize identity manager
-    #[cfg(windows)]
-    let keystore: Arc<dyn identity::KeyStore> = Arc::new(
-        zrc_platform_win::keystore::DpapiKeyStore::new(
-            zrc_platform_win::keystore::DpapiScope::CurrentUser
+    if args.healthcheck {
+        let report = tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            service::probe_health(&config.status_endpoint),
         )
-    );
+        .await
+        .map_err(|_| anyhow::anyhow!("health check timed out"))??;
+        println!("healthy: {} ({} active sessions)", report.version, report.active_sessions);
+        return Ok(());
+    }
 
-    #[cfg(not(windows))]
-    let keystore: Arc<dyn identity::KeyStore> = {
-        // TODO: Implement for Linux/macOS
-        error!("KeyStore not implemented for this platform");
-        return Err(anyhow::anyhow!("KeyStore not available"));
+    // Initialize identity manager
+    let file_keystore = if args.file_keystore {
+        let dir = config.keystore_dir.clone().ok_or_else(|| {
+            anyhow::anyhow!("--file-keystore requires keystore_dir (or ZRC_KEYSTORE_DIR)")
+        })?;
+        let passphrase = std::env::var("ZRC_KEYSTORE_PASSPHRASE").map_err(|_| {
+            anyhow::anyhow!("--file-keystore requires ZRC_KEYSTORE_PASSPHRASE")
+        })?;
+        Some(keystore::FileKeyStoreConfig { dir, passphrase })
+    } else {
+        None
     };
+    let keystore = keystore::open_keystore(file_keystore).await?;
 
     let identity_mgr = identity::IdentityManager::new(keystore).await?;
     info!("Identity loaded: {}", hex::encode(identity_mgr.device_id()));
 
+    match &args.command {
+        Some(Command::Replay { file }) => return replay(file, &identity_mgr).await,
+        Some(Command::Pair { ttl }) => return pair(&config, &identity_mgr, *ttl).await,
+        None => {}
+    }
+
     // Initialize service host
     let mut service: Box<dyn service::ServiceHost> = if args.foreground {
         let (service, _rx) = service::ForegroundService::new();
@@ -86,19 +132,79 @@ async fn main() -> anyhow::Result<()> {
     service.start().await?;
     info!("zrc-agent service started");
 
-    // TODO: Initialize and run main agent loop
-    // - Pairing manager
-    // - Session manager
-    // - WebRTC transport
-    // - Capture engine
-    // - Input injector
+    // Consent prompts need a desktop; unattended hosts approve headlessly
+    let consent_handler: Arc<dyn consent::ConsentHandler> = if config.allow_unattended {
+        Arc::new(consent::HeadlessConsentHandler::new(true))
+    } else {
+        Arc::new(consent::GuiConsentHandler::new()?)
+    };
 
-    // Wait for shutdown signal
-    tokio::signal::ctrl_c().await?;
-    info!("Shutdown signal received");
+    let agent = runtime::AgentRuntime::new(config, &identity_mgr, consent_handler);
+    let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+    let run = agent.run(shutdown_rx);
+    tokio::pin!(run);
+
+    // Run until the agent loop fails or a shutdown signal arrives
+    let result = tokio::select! {
+        result = &mut run => result,
+        signal = tokio::signal::ctrl_c() => {
+            signal?;
+            info!("Shutdown signal received");
+            let _ = shutdown_tx.send(true);
+            run.await
+        }
+    };
+    if let Err(e) = &result {
+        error!("Agent loop failed: {}", e);
+    }
 
     service.stop().await?;
     info!("zrc-agent stopped");
 
+    result.map_err(Into::into)
+}
+
+/// Create an invite in the agent's store and print it for the operator.
+async fn pair(
+    config: &config::AgentConfig,
+    identity_mgr: &identity::IdentityManager,
+    ttl: u32,
+) -> anyhow::Result<()> {
+    use base64::Engine as _;
+    use prost::Message as _;
+
+    let issued = runtime::create_invite(config, identity_mgr, ttl).await?;
+    println!(
+        "Invite: {}",
+        base64::engine::general_purpose::STANDARD.encode(issued.invite.encode_to_vec())
+    );
+    println!("Secret: {}", hex::encode(issued.secret));
+    println!("Expires in {} seconds. Share the secret separately from the invite.", ttl);
+    Ok(())
+}
+
+/// Print a recording's manifest and a one-line summary of each record.
+async fn replay(path: &std::path::Path, identity_mgr: &identity::IdentityManager) -> anyhow::Result<()> {
+    let mut reader = recording::RecordingReader::open(path, &identity_mgr.identity_keys()).await?;
+    let mut counts = (0usize, 0usize);
+    while let Some(record) = reader.next_record().await? {
+        match record {
+            recording::Record::Manifest(manifest) => {
+                println!("{}", serde_json::to_string_pretty(&manifest)?);
+            }
+            recording::Record::Input { at_ms, event } => {
+                counts.0 += 1;
+                println!("{:>10}ms input {:?}", at_ms, event.event_type_enum());
+            }
+            recording::Record::Frame { at_ms, width, height, .. } => {
+                counts.1 += 1;
+                println!("{:>10}ms frame {}x{}", at_ms, width, height);
+            }
+            recording::Record::End { at_ms } => {
+                println!("{:>10}ms end", at_ms);
+            }
+        }
+    }
+    println!("{} input events, {} frames", counts.0, counts.1);
     Ok(())
 }
@@ -1,7 +1,7 @@
 use std::sync::Arc;
 use std::time::SystemTime;
-use zrc_core::pairing::{PairingHost, PairingError as CorePairingError, ConsentHandler, PairDecision};
-use zrc_core::rate_limit::RateLimiter;
+use zrc_core::pairing::{PairingHost, PairingHostState, PairingError as CorePairingError, ConsentHandler, PairDecision};
+use zrc_core::rate_limit::{RateLimitError, RateLimiter};
 use zrc_core::store::Store;
 use zrc_core::types::IdentityKeys;
 use zrc_proto::v1::{InviteV1, PairRequestV1, PairReceiptV1, EndpointHintsV1, PermissionV1};
@@ -18,6 +18,8 @@ pub enum PairingError {
     RateLimited(u64),
     #[error("store error: {0}")]
     Store(String),
+    #[error("pairing denied by consent handler")]
+    Denied,
 }
 
 /// Simple consent handler that auto-approves pairings (for testing/unattended mode)
@@ -47,6 +49,14 @@ impl ConsentHandler for AutoApproveConsentHandler {
     }
 }
 
+/// A freshly generated invite and the secret the operator must present with
+/// it. The secret is shown to whoever runs `zrc-agent pair` and is never
+/// logged.
+pub struct IssuedInvite {
+    pub invite: InviteV1,
+    pub secret: [u8; 32],
+}
+
 pub struct PairingManager<S: Store + Send + Sync + 'static, C: ConsentHandler + 'static> {
     device_keys: IdentityKeys,
     store: Arc<S>,
@@ -79,7 +89,7 @@ impl<S: Store + Send + Sync + 'static, C: ConsentHandler + 'static> PairingManag
         &self,
         ttl_seconds: u32,
         transport_hints: Option<EndpointHintsV1>,
-    ) -> Result<InviteV1, PairingError> {
+    ) -> Result<IssuedInvite, PairingError> {
         // Create a new pairing host for this invite
         let mut host = PairingHost::new(
             self.device_keys.clone(),
@@ -92,12 +102,19 @@ impl<S: Store + Send + Sync + 'static, C: ConsentHandler + 'static> PairingManag
             .await
             .map_err(PairingError::Core)?;
 
+        let PairingHostState::InviteGenerated { secret, .. } = host.state() else {
+            return Err(PairingError::Core(CorePairingError::InvalidState(
+                "invite generated without a secret".into(),
+            )));
+        };
+        let secret = *secret;
+
         // Track active invite
         let invite_id = hex::encode(&invite.device_id);
         self.active_invites.insert(invite_id, SystemTime::now());
 
         info!("Generated invite for device: {}", hex::encode(&invite.device_id));
-        Ok(invite)
+        Ok(IssuedInvite { invite, secret })
     }
 
     pub async fn handle_pair_request(
@@ -105,12 +122,17 @@ impl<S: Store + Send + Sync + 'static, C: ConsentHandler + 'static> PairingManag
         request: PairRequestV1,
         source_ip: std::net::IpAddr,
     ) -> Result<PairReceiptV1, PairingError> {
-        // Rate limiting: 3 attempts per minute per source
-        match self.rate_limiter.check_rate_limit(&source_ip.to_string(), zrc_core::rate_limit::RequestType::Pairing).await {
-            Ok(()) => {},
-            Err(e) => {
-                warn!("Pairing rate limit exceeded from {}: {}", source_ip, e);
-                return Err(PairingError::RateLimited(60));
+        // Rate limiting: 3 attempts per minute per source address, and per
+        // operator id so rotating addresses doesn't buy more invite guesses
+        let operator = format!("operator:{}", hex::encode(&request.operator_id));
+        for key in [source_ip.to_string(), operator] {
+            if let Err(RateLimitError::RateLimited { retry_after_secs, .. }) = self
+                .rate_limiter
+                .check_rate_limit(&key, zrc_core::rate_limit::RequestType::Pairing)
+                .await
+            {
+                warn!("Pairing rate limit exceeded from {} ({})", source_ip, key);
+                return Err(PairingError::RateLimited(retry_after_secs));
             }
         }
 
@@ -127,11 +149,12 @@ impl<S: Store + Send + Sync + 'static, C: ConsentHandler + 'static> PairingManag
             .await
             .map_err(PairingError::Core)?;
 
-        // For now, auto-approve with default permissions
+        // Let the consent handler approve, narrow or reject the request
         let receipt = host
-            .approve(0x03) // VIEW | CONTROL
+            .request_consent()
             .await
-            .map_err(PairingError::Core)?;
+            .map_err(PairingError::Core)?
+            .ok_or(PairingError::Denied)?;
 
         info!("Pairing completed successfully");
         Ok(receipt)
@@ -146,3 +169,78 @@ impl<S: Store + Send + Sync + 'static, C: ConsentHandler + 'static> PairingManag
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zrc_core::keys::generate_identity_keys;
+    use zrc_core::rate_limit::RateLimitConfig;
+    use zrc_core::sqlite_store::SqliteStore;
+
+    #[tokio::test]
+    async fn test_issued_invite_secret_matches_stored_invite() {
+        let keys = generate_identity_keys();
+        let store = Arc::new(SqliteStore::new_in_memory().unwrap());
+        let manager = PairingManager::new(
+            keys.clone(),
+            store.clone(),
+            Arc::new(AutoApproveConsentHandler::new(Vec::new())),
+            Arc::new(RateLimiter::new(RateLimitConfig::default())),
+            1,
+        )
+        .unwrap();
+
+        let issued = manager.generate_invite(600, None).await.unwrap();
+        assert_eq!(
+            issued.invite.invite_secret_hash,
+            zrc_crypto::hash::sha256(&issued.secret).to_vec()
+        );
+
+        // A later process answering the pair request finds the same secret
+        let stored = store.load_invite(&keys.id32).await.unwrap().unwrap();
+        assert_eq!(stored.invite_secret, issued.secret);
+    }
+
+    #[tokio::test]
+    async fn test_pair_requests_limited_per_source_and_operator() {
+        use std::net::IpAddr;
+        use std::time::Duration;
+
+        let manager = PairingManager::new(
+            generate_identity_keys(),
+            Arc::new(SqliteStore::new_in_memory().unwrap()),
+            Arc::new(AutoApproveConsentHandler::new(Vec::new())),
+            Arc::new(RateLimiter::new(RateLimitConfig {
+                pairing_attempts_per_minute: 2,
+                base_backoff: Duration::from_secs(30),
+                ..Default::default()
+            })),
+            1,
+        )
+        .unwrap();
+        let request = |operator: u8| PairRequestV1 {
+            operator_id: vec![operator; 32],
+            ..Default::default()
+        };
+        let attacker: IpAddr = "198.51.100.7".parse().unwrap();
+        let is_limited =
+            |r: &Result<PairReceiptV1, PairingError>| matches!(r, Err(PairingError::RateLimited(_)));
+
+        // No invite exists, so allowed attempts fail in the core pairing host
+        for operator in 1..=2 {
+            let result = manager.handle_pair_request(request(operator), attacker).await;
+            assert!(!is_limited(&result));
+        }
+        // A third attempt from the same address is throttled, whatever the operator id
+        let result = manager.handle_pair_request(request(3), attacker).await;
+        assert!(matches!(result, Err(PairingError::RateLimited(60))));
+
+        // Another address still gets through, until operator 1 runs out too
+        let other: IpAddr = "203.0.113.9".parse().unwrap();
+        let result = manager.handle_pair_request(request(1), other).await;
+        assert!(!is_limited(&result));
+        let third: IpAddr = "192.0.2.1".parse().unwrap();
+        let result = manager.handle_pair_request(request(1), third).await;
+        assert!(is_limited(&result));
+    }
+}
@@ -1,6 +1,7 @@
 use std::time::{SystemTime, Duration};
+use serde::{Deserialize, Serialize};
 use zrc_core::policy::{PolicyEngine as CorePolicyEngine, ConsentMode, PolicyError as CorePolicyError};
-use zrc_proto::v1::PermissionV1;
+use zrc_proto::v1::{PermissionV1, PermissionsV1};
 use thiserror::Error;
 use tracing::{info, warn};
 
@@ -8,6 +9,159 @@ use tracing::{info, warn};
 pub enum PolicyError {
     #[error("core policy error: {0}")]
     Core(#[from] CorePolicyError),
+    #[error("invalid policy rule: {0}")]
+    InvalidRule(String),
+}
+
+/// Permissions a rule may grant when it does not list any.
+const ALL_PERMISSIONS: u32 = PermissionsV1::View as u32
+    | PermissionsV1::Control as u32
+    | PermissionsV1::Clipboard as u32
+    | PermissionsV1::FileTransfer as u32
+    | PermissionsV1::Audio as u32;
+
+/// What a matching rule does with a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleAction {
+    /// Approve without asking the local user
+    Unattended,
+    /// Ask the local user through the consent handler
+    Prompt,
+    /// Reject outright
+    Deny,
+}
+
+/// One entry of the `[[policy_rules]]` config section.
+///
+/// ```toml
+/// [[policy_rules]]
+/// operator = "<64 hex chars>"   # omit to match any operator
+/// action = "unattended"          # "unattended", "prompt" or "deny"
+/// permissions = ["view", "control"]
+/// hours = [9, 17]                # UTC, end exclusive; may wrap midnight
+/// days = [1, 2, 3, 4, 5]         # 0 = Sunday
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicyRule {
+    #[serde(default)]
+    pub operator: Option<String>,
+    pub action: RuleAction,
+    /// Upper bound on granted permissions; empty means all of them
+    #[serde(default)]
+    pub permissions: Vec<String>,
+    #[serde(default)]
+    pub hours: Option<(u8, u8)>,
+    #[serde(default)]
+    pub days: Option<Vec<u8>>,
+}
+
+/// Outcome of evaluating the ruleset for one request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Decision {
+    /// Approve with these `PermissionsV1` bits, no prompt
+    Unattended { permissions: u32 },
+    /// Prompt the user; grant at most these bits
+    Prompt { permissions: u32 },
+    Deny { reason: String },
+}
+
+/// A `PolicyRule` with its operator id and permission names resolved.
+#[derive(Debug, Clone)]
+struct CompiledRule {
+    operator: Option<[u8; 32]>,
+    action: RuleAction,
+    permissions: u32,
+    hours: Option<(u8, u8)>,
+    days: Option<Vec<u8>>,
+}
+
+impl CompiledRule {
+    fn compile(rule: &PolicyRule) -> Result<Self, PolicyError> {
+        let operator = match &rule.operator {
+            Some(hex_id) => {
+                let bytes = hex::decode(hex_id).map_err(|_| {
+                    PolicyError::InvalidRule(format!("operator {:?} is not hex", hex_id))
+                })?;
+                let id: [u8; 32] = bytes.try_into().map_err(|_| {
+                    PolicyError::InvalidRule(format!("operator {:?} must be 32 bytes", hex_id))
+                })?;
+                Some(id)
+            }
+            None => None,
+        };
+
+        let permissions = if rule.permissions.is_empty() {
+            ALL_PERMISSIONS
+        } else {
+            rule.permissions.iter().try_fold(0u32, |acc, name| {
+                permission_bit(name).map(|bit| acc | bit).ok_or_else(|| {
+                    PolicyError::InvalidRule(format!("unknown permission {:?}", name))
+                })
+            })?
+        };
+
+        if let Some((start, end)) = rule.hours {
+            if start > 23 || end > 24 || start == end {
+                return Err(PolicyError::InvalidRule(format!(
+                    "hours [{}, {}] is not a valid window", start, end
+                )));
+            }
+        }
+        if let Some(days) = &rule.days {
+            if days.iter().any(|d| *d > 6) {
+                return Err(PolicyError::InvalidRule("days must be 0-6".to_string()));
+            }
+        }
+
+        Ok(Self {
+            operator,
+            action: rule.action,
+            permissions,
+            hours: rule.hours,
+            days: rule.days.clone(),
+        })
+    }
+
+    fn applies_at(&self, secs: u64) -> bool {
+        self.hours.is_none_or(|(start, end)| hour_in_window(hour_of_day(secs), start, end))
+            && self.days.as_ref().is_none_or(|days| days.contains(&day_of_week(secs)))
+    }
+}
+
+fn permission_bit(name: &str) -> Option<u32> {
+    match name {
+        "view" => Some(PermissionsV1::View as u32),
+        "control" => Some(PermissionsV1::Control as u32),
+        "clipboard" => Some(PermissionsV1::Clipboard as u32),
+        "files" | "file_transfer" => Some(PermissionsV1::FileTransfer as u32),
+        "audio" => Some(PermissionsV1::Audio as u32),
+        _ => None,
+    }
+}
+
+fn unix_secs(now: SystemTime) -> u64 {
+    now.duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or(Duration::ZERO)
+        .as_secs()
+}
+
+fn hour_of_day(secs: u64) -> u8 {
+    ((secs % 86400) / 3600) as u8
+}
+
+fn day_of_week(secs: u64) -> u8 {
+    ((secs / 86400 + 4) % 7) as u8 // Jan 1, 1970 was a Thursday (4)
+}
+
+fn hour_in_window(hour: u8, start: u8, end: u8) -> bool {
+    if start <= end {
+        // Same day range
+        hour >= start && hour < end
+    } else {
+        // Overnight range
+        hour >= start || hour < end
+    }
 }
 
 pub struct PolicyEngine {
@@ -15,6 +169,7 @@ pub struct PolicyEngine {
     consent_mode: ConsentMode,
     allowed_hours: Option<(u8, u8)>, // (start_hour, end_hour) in 24-hour format
     allowed_days: Option<Vec<u8>>, // Days of week (0=Sunday, 6=Saturday)
+    rules: Vec<CompiledRule>,
 }
 
 impl PolicyEngine {
@@ -24,6 +179,46 @@ impl PolicyEngine {
             consent_mode,
             allowed_hours: None,
             allowed_days: None,
+            rules: Vec::new(),
+        }
+    }
+
+    /// Install a per-operator ruleset, consulted by `evaluate`.
+    pub fn with_rules(mut self, rules: &[PolicyRule]) -> Result<Self, PolicyError> {
+        self.rules = rules
+            .iter()
+            .map(CompiledRule::compile)
+            .collect::<Result<_, _>>()?;
+        Ok(self)
+    }
+
+    /// Decide a pair or session request against the ruleset.
+    ///
+    /// Rules naming the operator are tried before rules that match any
+    /// operator, each group in config order; rules outside their time
+    /// window are skipped. The first applicable rule decides, and a request
+    /// no rule covers is denied.
+    pub fn evaluate(&self, operator_id: &[u8; 32], requested_perms: u32) -> Decision {
+        self.evaluate_at(operator_id, requested_perms, SystemTime::now())
+    }
+
+    fn evaluate_at(&self, operator_id: &[u8; 32], requested_perms: u32, now: SystemTime) -> Decision {
+        let secs = unix_secs(now);
+        let specific = self.rules.iter().filter(|r| r.operator.as_ref() == Some(operator_id));
+        let default = self.rules.iter().filter(|r| r.operator.is_none());
+
+        let Some(rule) = specific.chain(default).find(|r| r.applies_at(secs)) else {
+            return Decision::Deny { reason: "no policy rule matches".to_string() };
+        };
+
+        let permissions = requested_perms & rule.permissions;
+        match rule.action {
+            RuleAction::Deny => Decision::Deny { reason: "denied by policy rule".to_string() },
+            _ if permissions == 0 => Decision::Deny {
+                reason: "requested permissions not allowed by policy".to_string(),
+            },
+            RuleAction::Unattended => Decision::Unattended { permissions },
+            RuleAction::Prompt => Decision::Prompt { permissions },
         }
     }
 
@@ -78,19 +273,7 @@ impl PolicyEngine {
 
     fn is_within_allowed_hours(&self) -> bool {
         if let Some((start_hour, end_hour)) = self.allowed_hours {
-            let now = SystemTime::now();
-            let duration_since_epoch = now.duration_since(std::time::UNIX_EPOCH)
-                .unwrap_or(Duration::ZERO);
-            let total_seconds = duration_since_epoch.as_secs();
-            let hours_since_midnight = (total_seconds % 86400) / 3600;
-            
-            if start_hour <= end_hour {
-                // Same day range
-                hours_since_midnight >= start_hour as u64 && hours_since_midnight < end_hour as u64
-            } else {
-                // Overnight range
-                hours_since_midnight >= start_hour as u64 || hours_since_midnight < end_hour as u64
-            }
+            hour_in_window(hour_of_day(unix_secs(SystemTime::now())), start_hour, end_hour)
         } else {
             true
         }
@@ -98,16 +281,110 @@ impl PolicyEngine {
 
     fn is_allowed_day(&self) -> bool {
         if let Some(ref allowed_days) = self.allowed_days {
-            let now = SystemTime::now();
-            let duration_since_epoch = now.duration_since(std::time::UNIX_EPOCH)
-                .unwrap_or(Duration::ZERO);
-            let total_seconds = duration_since_epoch.as_secs();
-            let days_since_epoch = total_seconds / 86400;
-            let day_of_week = (days_since_epoch + 4) % 7; // Jan 1, 1970 was a Thursday (4)
-            
-            allowed_days.contains(&(day_of_week as u8))
+            allowed_days.contains(&day_of_week(unix_secs(SystemTime::now())))
         } else {
             true
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const X: [u8; 32] = [0x11; 32];
+    const Y: [u8; 32] = [0x22; 32];
+    const VIEW: u32 = PermissionsV1::View as u32;
+    const CONTROL: u32 = PermissionsV1::Control as u32;
+
+    fn rule(operator: Option<[u8; 32]>, action: RuleAction) -> PolicyRule {
+        PolicyRule {
+            operator: operator.map(hex::encode),
+            action,
+            permissions: Vec::new(),
+            hours: None,
+            days: None,
+        }
+    }
+
+    fn engine(rules: &[PolicyRule]) -> PolicyEngine {
+        PolicyEngine::new(ConsentMode::AlwaysRequire).with_rules(rules).unwrap()
+    }
+
+    // Thursday 1970-01-01 10:00 UTC
+    fn thursday_10am() -> SystemTime {
+        std::time::UNIX_EPOCH + Duration::from_secs(10 * 3600)
+    }
+
+    #[test]
+    fn operator_rule_takes_precedence_over_default() {
+        // The default rule comes first in config order and still loses
+        let policy = engine(&[
+            rule(None, RuleAction::Deny),
+            rule(Some(X), RuleAction::Unattended),
+        ]);
+
+        assert_eq!(
+            policy.evaluate(&X, VIEW | CONTROL),
+            Decision::Unattended { permissions: VIEW | CONTROL }
+        );
+        assert!(matches!(policy.evaluate(&Y, VIEW), Decision::Deny { .. }));
+    }
+
+    #[test]
+    fn unattended_and_prompt_operators() {
+        let mut view_only = rule(Some(Y), RuleAction::Prompt);
+        view_only.permissions = vec!["view".to_string()];
+        let policy = engine(&[rule(Some(X), RuleAction::Unattended), view_only]);
+
+        assert_eq!(policy.evaluate(&X, VIEW), Decision::Unattended { permissions: VIEW });
+        // Control is masked off rather than failing the request
+        assert_eq!(policy.evaluate(&Y, VIEW | CONTROL), Decision::Prompt { permissions: VIEW });
+        assert!(matches!(policy.evaluate(&Y, CONTROL), Decision::Deny { .. }));
+    }
+
+    #[test]
+    fn unknown_operator_is_denied_without_rules() {
+        let policy = engine(&[rule(Some(X), RuleAction::Unattended)]);
+        assert!(matches!(policy.evaluate(&Y, VIEW), Decision::Deny { .. }));
+        assert!(matches!(engine(&[]).evaluate(&X, VIEW), Decision::Deny { .. }));
+    }
+
+    #[test]
+    fn rules_outside_their_window_fall_through() {
+        let mut office_hours = rule(Some(X), RuleAction::Unattended);
+        office_hours.hours = Some((9, 17));
+        office_hours.days = Some(vec![1, 2, 3, 4, 5]);
+        let mut night = rule(Some(X), RuleAction::Unattended);
+        night.hours = Some((22, 6));
+        let policy = engine(&[night, office_hours, rule(None, RuleAction::Prompt)]);
+
+        assert_eq!(
+            policy.evaluate_at(&X, VIEW, thursday_10am()),
+            Decision::Unattended { permissions: VIEW }
+        );
+        // Saturday 10:00 UTC: office hours do not apply, the default prompts
+        let saturday = thursday_10am() + Duration::from_secs(2 * 86400);
+        assert_eq!(policy.evaluate_at(&X, VIEW, saturday), Decision::Prompt { permissions: VIEW });
+        // Friday 02:00 UTC is inside the overnight window
+        let overnight = thursday_10am() + Duration::from_secs(16 * 3600);
+        assert_eq!(
+            policy.evaluate_at(&X, VIEW, overnight),
+            Decision::Unattended { permissions: VIEW }
+        );
+    }
+
+    #[test]
+    fn invalid_rules_are_rejected() {
+        let mut bad_operator = rule(None, RuleAction::Prompt);
+        bad_operator.operator = Some("abcd".to_string());
+        let mut bad_permission = rule(None, RuleAction::Prompt);
+        bad_permission.permissions = vec!["root".to_string()];
+        let mut bad_hours = rule(None, RuleAction::Prompt);
+        bad_hours.hours = Some((9, 9));
+
+        for bad in [bad_operator, bad_permission, bad_hours] {
+            assert!(PolicyEngine::new(ConsentMode::AlwaysRequire).with_rules(&[bad]).is_err());
+        }
+    }
+}
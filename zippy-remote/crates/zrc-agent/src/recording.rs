@@ -0,0 +1,527 @@
+//! Opt-in local recording of sessions for audit.
+//!
+//! A recording is an append-only file of encrypted records. The file starts
+//! with a magic number and a random salt; the record key is derived from the
+//! device's X25519 secret and that salt, so only this device can read it back.
+//! Each record is `len (u32 BE) || nonce (24) || XChaCha20-Poly1305 ciphertext`
+//! with the header and the record's index as associated data, so records
+//! cannot be reordered, dropped from the middle or moved between files.
+//!
+//! The first record is a JSON manifest of session metadata; after it come
+//! received input events and a downsampled frame every `frame_interval`, and
+//! an end marker when the session closes.
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use hkdf::Hkdf;
+use prost::Message;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use thiserror::Error;
+use tokio::fs::{File, OpenOptions};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
+use zeroize::Zeroizing;
+use zrc_core::types::IdentityKeys;
+use zrc_proto::v1::InputEventV1;
+
+use crate::capture::{CaptureFormat, CaptureFrame};
+
+const MAGIC: &[u8; 8] = b"ZRCREC\x00\x01";
+const SALT_LEN: usize = 16;
+const HEADER_LEN: usize = MAGIC.len() + SALT_LEN;
+const NONCE_LEN: usize = 24;
+const KEY_INFO: &[u8] = b"zrc-session-recording-v1";
+
+/// Records larger than this are treated as corruption when reading.
+const MAX_RECORD_LEN: u32 = 64 * 1024 * 1024;
+
+/// Default spacing of recorded frames.
+pub const DEFAULT_FRAME_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Recorded frames keep every `DOWNSAMPLE`th pixel in each direction.
+pub const DOWNSAMPLE: u32 = 4;
+
+/// Entries queued between the session and the writer task.
+const QUEUE_DEPTH: usize = 256;
+
+const KIND_MANIFEST: u8 = 1;
+const KIND_INPUT: u8 = 2;
+const KIND_FRAME: u8 = 3;
+const KIND_END: u8 = 4;
+
+#[derive(Debug, Error)]
+pub enum RecordingError {
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("not a session recording")]
+    BadMagic,
+    #[error("record {0} failed to decrypt")]
+    Decrypt(u64),
+    #[error("record {0} is malformed: {1}")]
+    Malformed(u64, String),
+    #[error("recording ends mid-record")]
+    Truncated,
+    #[error("encryption failed")]
+    Encrypt,
+}
+
+/// Session metadata written as the first record.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordingManifest {
+    pub version: u32,
+    /// Hex ticket id
+    pub session: String,
+    pub device_id: String,
+    pub operator_id: String,
+    /// `PermissionsV1` bits granted to the session
+    pub permissions: u32,
+    /// Unix seconds
+    pub started_at: u64,
+    pub frame_interval_ms: u32,
+    pub downsample: u32,
+}
+
+/// One decrypted record.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Record {
+    Manifest(RecordingManifest),
+    /// `at_ms` is milliseconds since the recording started
+    Input { at_ms: u64, event: InputEventV1 },
+    /// Tightly packed 4-byte pixels in the captured format
+    Frame { at_ms: u64, width: u32, height: u32, pixels: Vec<u8> },
+    End { at_ms: u64 },
+}
+
+/// Where recordings are written and the device key that protects them.
+#[derive(Clone)]
+pub struct RecordingConfig {
+    pub dir: PathBuf,
+    pub keys: IdentityKeys,
+    pub frame_interval: Duration,
+}
+
+fn record_cipher(keys: &IdentityKeys, salt: &[u8]) -> XChaCha20Poly1305 {
+    let ikm = Zeroizing::new(keys.kex_priv.to_bytes());
+    let mut key = Zeroizing::new([0u8; 32]);
+    Hkdf::<Sha256>::new(Some(salt), &ikm[..])
+        .expand(KEY_INFO, &mut key[..])
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    XChaCha20Poly1305::new(Key::from_slice(&key[..]))
+}
+
+fn record_aad(header: &[u8; HEADER_LEN], index: u64) -> Vec<u8> {
+    let mut aad = header.to_vec();
+    aad.extend_from_slice(&index.to_be_bytes());
+    aad
+}
+
+/// Keep every `factor`th pixel of a 4-byte-per-pixel frame. Returns `None`
+/// for formats that are not packed 4-byte pixels.
+fn downsample(frame: &CaptureFrame, factor: u32) -> Option<(u32, u32, Vec<u8>)> {
+    if !matches!(frame.format, CaptureFormat::Bgra8888 | CaptureFormat::Rgba8888) {
+        return None;
+    }
+    let width = frame.width.div_ceil(factor);
+    let height = frame.height.div_ceil(factor);
+    let mut pixels = Vec::with_capacity((width * height * 4) as usize);
+    for y in (0..frame.height).step_by(factor as usize) {
+        let row = (y * frame.stride) as usize;
+        for x in (0..frame.width).step_by(factor as usize) {
+            let at = row + (x * 4) as usize;
+            pixels.extend_from_slice(frame.data.get(at..at + 4)?);
+        }
+    }
+    Some((width, height, pixels))
+}
+
+/// Writes one session's recording.
+pub struct SessionRecorder {
+    file: File,
+    path: PathBuf,
+    cipher: XChaCha20Poly1305,
+    header: [u8; HEADER_LEN],
+    index: u64,
+    started: Instant,
+}
+
+impl SessionRecorder {
+    /// Create a new recording in `config.dir` and write its manifest.
+    pub async fn create(
+        config: &RecordingConfig,
+        manifest: &RecordingManifest,
+    ) -> Result<Self, RecordingError> {
+        tokio::fs::create_dir_all(&config.dir).await?;
+        let path = config
+            .dir
+            .join(format!("{}-{}.zrcrec", manifest.started_at, manifest.session));
+        let mut file = OpenOptions::new()
+            .append(true)
+            .create_new(true)
+            .open(&path)
+            .await?;
+
+        let mut header = [0u8; HEADER_LEN];
+        header[..MAGIC.len()].copy_from_slice(MAGIC);
+        getrandom::getrandom(&mut header[MAGIC.len()..]).map_err(|_| RecordingError::Encrypt)?;
+        file.write_all(&header).await?;
+
+        let mut recorder = Self {
+            file,
+            path,
+            cipher: record_cipher(&config.keys, &header[MAGIC.len()..]),
+            header,
+            index: 0,
+            started: Instant::now(),
+        };
+        let json = serde_json::to_vec(manifest)
+            .map_err(|e| RecordingError::Malformed(0, e.to_string()))?;
+        recorder.append(KIND_MANIFEST, &json).await?;
+        Ok(recorder)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn elapsed_ms(&self) -> u64 {
+        self.started.elapsed().as_millis() as u64
+    }
+
+    async fn append(&mut self, kind: u8, body: &[u8]) -> Result<(), RecordingError> {
+        let mut plaintext = Vec::with_capacity(1 + body.len());
+        plaintext.push(kind);
+        plaintext.extend_from_slice(body);
+
+        let mut nonce = [0u8; NONCE_LEN];
+        getrandom::getrandom(&mut nonce).map_err(|_| RecordingError::Encrypt)?;
+        let aad = record_aad(&self.header, self.index);
+        let ciphertext = self
+            .cipher
+            .encrypt(XNonce::from_slice(&nonce), Payload { msg: &plaintext, aad: &aad })
+            .map_err(|_| RecordingError::Encrypt)?;
+
+        // One write per record so a crash leaves at most one partial record
+        let mut record = Vec::with_capacity(4 + NONCE_LEN + ciphertext.len());
+        record.extend_from_slice(&(ciphertext.len() as u32).to_be_bytes());
+        record.extend_from_slice(&nonce);
+        record.extend_from_slice(&ciphertext);
+        self.file.write_all(&record).await?;
+        self.index += 1;
+        Ok(())
+    }
+
+    pub async fn record_input(&mut self, event: &InputEventV1) -> Result<(), RecordingError> {
+        let mut body = self.elapsed_ms().to_be_bytes().to_vec();
+        event.encode(&mut body).expect("Vec has unbounded capacity");
+        self.append(KIND_INPUT, &body).await
+    }
+
+    /// Record `frame` downsampled; frames in other formats are skipped.
+    pub async fn record_frame(&mut self, frame: &CaptureFrame) -> Result<(), RecordingError> {
+        let Some((width, height, pixels)) = downsample(frame, DOWNSAMPLE) else {
+            return Ok(());
+        };
+        self.record_pixels(width, height, &pixels).await
+    }
+
+    async fn record_pixels(&mut self, width: u32, height: u32, pixels: &[u8]) -> Result<(), RecordingError> {
+        let mut body = Vec::with_capacity(16 + pixels.len());
+        body.extend_from_slice(&self.elapsed_ms().to_be_bytes());
+        body.extend_from_slice(&width.to_be_bytes());
+        body.extend_from_slice(&height.to_be_bytes());
+        body.extend_from_slice(pixels);
+        self.append(KIND_FRAME, &body).await
+    }
+
+    /// Write the end marker and flush the file to disk.
+    pub async fn finish(mut self) -> Result<PathBuf, RecordingError> {
+        let at_ms = self.elapsed_ms();
+        self.append(KIND_END, &at_ms.to_be_bytes()).await?;
+        self.file.sync_all().await?;
+        Ok(self.path)
+    }
+}
+
+enum Entry {
+    Input(InputEventV1),
+    Frame { width: u32, height: u32, pixels: Vec<u8> },
+}
+
+/// Cheap handle the session's capture and control paths feed; the recorder
+/// runs on its own task so disk writes never stall the session. Dropping
+/// every handle finishes the recording.
+pub struct RecorderHandle {
+    tx: mpsc::Sender<Entry>,
+    frame_interval: Duration,
+    last_frame: Mutex<Option<Instant>>,
+}
+
+impl RecorderHandle {
+    /// Start the writer task for `recorder`.
+    pub fn spawn(mut recorder: SessionRecorder, frame_interval: Duration) -> Self {
+        let (tx, mut rx) = mpsc::channel(QUEUE_DEPTH);
+        tokio::spawn(async move {
+            while let Some(entry) = rx.recv().await {
+                let written = match entry {
+                    Entry::Input(event) => recorder.record_input(&event).await,
+                    Entry::Frame { width, height, pixels } => {
+                        recorder.record_pixels(width, height, &pixels).await
+                    }
+                };
+                if let Err(e) = written {
+                    warn!("Stopping session recording {}: {}", recorder.path().display(), e);
+                    return;
+                }
+            }
+            match recorder.finish().await {
+                Ok(path) => info!("Session recording saved to {}", path.display()),
+                Err(e) => warn!("Failed to finish session recording: {}", e),
+            }
+        });
+        Self {
+            tx,
+            frame_interval,
+            last_frame: Mutex::new(None),
+        }
+    }
+
+    pub fn input(&self, event: &InputEventV1) {
+        if self.tx.try_send(Entry::Input(event.clone())).is_err() {
+            debug!("Recording queue full, dropped an input event");
+        }
+    }
+
+    /// Offer a captured frame; it is recorded if `frame_interval` has passed
+    /// since the last recorded one.
+    pub fn frame(&self, frame: &CaptureFrame) {
+        {
+            let mut last = self.last_frame.lock().unwrap_or_else(|e| e.into_inner());
+            if last.is_some_and(|t| t.elapsed() < self.frame_interval) {
+                return;
+            }
+            *last = Some(Instant::now());
+        }
+        if let Some((width, height, pixels)) = downsample(frame, DOWNSAMPLE) {
+            if self.tx.try_send(Entry::Frame { width, height, pixels }).is_err() {
+                debug!("Recording queue full, dropped a frame");
+            }
+        }
+    }
+}
+
+/// Reads a recording back with the device key that wrote it.
+pub struct RecordingReader {
+    file: File,
+    cipher: XChaCha20Poly1305,
+    header: [u8; HEADER_LEN],
+    index: u64,
+}
+
+impl RecordingReader {
+    pub async fn open(path: &Path, keys: &IdentityKeys) -> Result<Self, RecordingError> {
+        let mut file = File::open(path).await?;
+        let mut header = [0u8; HEADER_LEN];
+        file.read_exact(&mut header).await.map_err(|e| match e.kind() {
+            std::io::ErrorKind::UnexpectedEof => RecordingError::BadMagic,
+            _ => RecordingError::Io(e),
+        })?;
+        if &header[..MAGIC.len()] != MAGIC {
+            return Err(RecordingError::BadMagic);
+        }
+        Ok(Self {
+            file,
+            cipher: record_cipher(keys, &header[MAGIC.len()..]),
+            header,
+            index: 0,
+        })
+    }
+
+    /// The next record, or `None` at a clean end of file.
+    pub async fn next_record(&mut self) -> Result<Option<Record>, RecordingError> {
+        let mut len = [0u8; 4];
+        match self.file.read(&mut len[..1]).await? {
+            0 => return Ok(None),
+            _ => self.read_exact(&mut len[1..]).await?,
+        }
+        let len = u32::from_be_bytes(len);
+        if len > MAX_RECORD_LEN {
+            return Err(RecordingError::Malformed(self.index, format!("{} byte record", len)));
+        }
+        let mut nonce = [0u8; NONCE_LEN];
+        self.read_exact(&mut nonce).await?;
+        let mut ciphertext = vec![0u8; len as usize];
+        self.read_exact(&mut ciphertext).await?;
+
+        let index = self.index;
+        let aad = record_aad(&self.header, index);
+        let plaintext = self
+            .cipher
+            .decrypt(XNonce::from_slice(&nonce), Payload { msg: &ciphertext, aad: &aad })
+            .map_err(|_| RecordingError::Decrypt(index))?;
+        self.index += 1;
+
+        let malformed = |what: &str| RecordingError::Malformed(index, what.to_string());
+        let (kind, body) = plaintext.split_first().ok_or_else(|| malformed("empty"))?;
+        let at_ms = || -> Result<u64, RecordingError> {
+            Ok(u64::from_be_bytes(body.get(..8).ok_or_else(|| malformed("short"))?.try_into().unwrap()))
+        };
+        let record = match *kind {
+            KIND_MANIFEST if index == 0 => Record::Manifest(
+                serde_json::from_slice(body).map_err(|e| malformed(&e.to_string()))?,
+            ),
+            _ if index == 0 => return Err(malformed("first record is not a manifest")),
+            KIND_INPUT => Record::Input {
+                at_ms: at_ms()?,
+                event: InputEventV1::decode(&body[8..]).map_err(|e| malformed(&e.to_string()))?,
+            },
+            KIND_FRAME => {
+                let dims = body.get(8..16).ok_or_else(|| malformed("short frame"))?;
+                let width = u32::from_be_bytes(dims[..4].try_into().unwrap());
+                let height = u32::from_be_bytes(dims[4..].try_into().unwrap());
+                let pixels = body[16..].to_vec();
+                if pixels.len() as u64 != width as u64 * height as u64 * 4 {
+                    return Err(malformed("frame size mismatch"));
+                }
+                Record::Frame { at_ms: at_ms()?, width, height, pixels }
+            }
+            KIND_END => Record::End { at_ms: at_ms()? },
+            other => return Err(malformed(&format!("unknown record kind {}", other))),
+        };
+        Ok(Some(record))
+    }
+
+    async fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), RecordingError> {
+        self.file.read_exact(buf).await.map(|_| ()).map_err(|e| match e.kind() {
+            std::io::ErrorKind::UnexpectedEof => RecordingError::Truncated,
+            _ => RecordingError::Io(e),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+    use zrc_core::keys::generate_identity_keys;
+    use zrc_proto::v1::InputEventTypeV1;
+
+    fn manifest() -> RecordingManifest {
+        RecordingManifest {
+            version: 1,
+            session: "00112233445566778899aabbccddeeff".to_string(),
+            device_id: hex::encode([1u8; 32]),
+            operator_id: hex::encode([2u8; 32]),
+            permissions: 3,
+            started_at: 1_700_000_000,
+            frame_interval_ms: 1000,
+            downsample: DOWNSAMPLE,
+        }
+    }
+
+    fn frame(width: u32, height: u32) -> CaptureFrame {
+        // Padded rows, with each pixel's first byte set to its x coordinate
+        let stride = width * 4 + 8;
+        let mut data = vec![0u8; (stride * height) as usize];
+        for y in 0..height {
+            for x in 0..width {
+                data[(y * stride + x * 4) as usize] = x as u8;
+            }
+        }
+        CaptureFrame {
+            data: Bytes::from(data),
+            width,
+            height,
+            stride,
+            format: CaptureFormat::Bgra8888,
+            timestamp: Instant::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn recording_decrypts_to_well_formed_records() {
+        let dir = tempfile::tempdir().unwrap();
+        let keys = generate_identity_keys();
+        let config = RecordingConfig {
+            dir: dir.path().to_path_buf(),
+            keys: keys.clone(),
+            frame_interval: DEFAULT_FRAME_INTERVAL,
+        };
+        let click = InputEventV1 {
+            event_type: InputEventTypeV1::MouseDown as i32,
+            mouse_x: 10,
+            mouse_y: 20,
+            button: 1,
+            ..Default::default()
+        };
+
+        let mut recorder = SessionRecorder::create(&config, &manifest()).await.unwrap();
+        recorder.record_input(&click).await.unwrap();
+        recorder.record_frame(&frame(10, 6)).await.unwrap();
+        let path = recorder.finish().await.unwrap();
+
+        let mut reader = RecordingReader::open(&path, &keys).await.unwrap();
+        assert_eq!(reader.next_record().await.unwrap(), Some(Record::Manifest(manifest())));
+        assert!(matches!(
+            reader.next_record().await.unwrap(),
+            Some(Record::Input { event, .. }) if event == click
+        ));
+        match reader.next_record().await.unwrap() {
+            Some(Record::Frame { width, height, pixels, .. }) => {
+                assert_eq!((width, height), (3, 2));
+                let xs: Vec<u8> = pixels.chunks(4).map(|p| p[0]).collect();
+                assert_eq!(xs, vec![0, 4, 8, 0, 4, 8]);
+            }
+            other => panic!("expected a frame, got {:?}", other),
+        }
+        assert!(matches!(reader.next_record().await.unwrap(), Some(Record::End { .. })));
+        assert_eq!(reader.next_record().await.unwrap(), None);
+
+        // Nothing in the file is readable without the device key
+        let raw = std::fs::read(&path).unwrap();
+        assert!(!raw.windows(10).any(|w| w == b"operator_i"));
+        let mut other = RecordingReader::open(&path, &generate_identity_keys()).await.unwrap();
+        assert!(matches!(other.next_record().await, Err(RecordingError::Decrypt(0))));
+    }
+
+    #[tokio::test]
+    async fn tampering_and_truncation_are_detected() {
+        let dir = tempfile::tempdir().unwrap();
+        let keys = generate_identity_keys();
+        let config = RecordingConfig {
+            dir: dir.path().to_path_buf(),
+            keys: keys.clone(),
+            frame_interval: DEFAULT_FRAME_INTERVAL,
+        };
+        let mut recorder = SessionRecorder::create(&config, &manifest()).await.unwrap();
+        recorder.record_input(&InputEventV1::default()).await.unwrap();
+        let path = recorder.finish().await.unwrap();
+        let raw = std::fs::read(&path).unwrap();
+
+        let truncated = dir.path().join("truncated.zrcrec");
+        std::fs::write(&truncated, &raw[..raw.len() - 5]).unwrap();
+        let mut reader = RecordingReader::open(&truncated, &keys).await.unwrap();
+        let mut last = Ok(None);
+        for _ in 0..4 {
+            last = reader.next_record().await;
+            if last.is_err() {
+                break;
+            }
+        }
+        assert!(matches!(last, Err(RecordingError::Truncated)));
+
+        let mut flipped = raw.clone();
+        let end = flipped.len() - 1;
+        flipped[end] ^= 1;
+        let tampered = dir.path().join("tampered.zrcrec");
+        std::fs::write(&tampered, &flipped).unwrap();
+        let mut reader = RecordingReader::open(&tampered, &keys).await.unwrap();
+        reader.next_record().await.unwrap();
+        reader.next_record().await.unwrap();
+        assert!(matches!(reader.next_record().await, Err(RecordingError::Decrypt(2))));
+    }
+}
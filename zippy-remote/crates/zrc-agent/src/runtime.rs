@@ -0,0 +1,1005 @@
+//! Agent main loop.
+//!
+//! Polls the rendezvous mailbox for pair and session requests, accepts QUIC
+//! connections for the sessions it issued tickets for, streams captured
+//! frames and applies the operator's input until shutdown is signalled.
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use prost::Message;
+use thiserror::Error;
+use tokio::sync::{mpsc, watch, Mutex};
+use tokio::task::JoinSet;
+use tracing::{debug, info, warn};
+use zrc_core::cursor::{CursorTracker, DEFAULT_CURSOR_POLL_INTERVAL};
+use zrc_core::http_mailbox::HttpMailboxClient;
+use zrc_core::platform::MonitorSelection;
+use zrc_core::policy::PolicyEngine;
+use zrc_core::quic::QuicServer;
+use zrc_core::quic_mux::{
+    host_accept_control_handshake, host_stream_frames_with_stats, ControlChannelV1, FramePacketV1,
+    FrameRateConfig,
+};
+use zrc_core::rate_limit::{RateLimitConfig, RateLimiter};
+use zrc_core::sqlite_store::SqliteStore;
+use zrc_core::store::Store;
+use zrc_core::transport::{QuicConfig, TransportNegotiator};
+use zrc_core::types::IdentityKeys;
+use zrc_crypto::envelope::{envelope_open_v1, envelope_seal_v1};
+use zrc_crypto::identity::Identity;
+use zrc_crypto::session_crypto::derive_session_crypto_v1;
+use zrc_proto::v1::{
+    control_msg_v1::Payload, session_close_v1::ReasonV1, ControlMsgV1, EndpointHintsV1, EnvelopeV1,
+    MsgTypeV1, PairRequestV1, PermissionsV1, RecordingStatusV1, SessionInitRequestV1, SessionTicketV1,
+};
+
+use crate::capture;
+use crate::config::{AgentConfig, ConfigError};
+use crate::audit::AuditLogger;
+use crate::consent::{ConsentBridge, ConsentHandler, HeadlessConsentHandler};
+use crate::file_transfer::{FileTransfer, FileTransferError};
+use crate::recording::{
+    self, RecorderHandle, RecordingConfig, RecordingManifest, SessionRecorder,
+    DEFAULT_FRAME_INTERVAL,
+};
+use crate::policy::{Decision, PolicyEngine as RulePolicy};
+use crate::identity::IdentityManager;
+use crate::input::{
+    InputError, InputFilter, InputLimits, PlatformInjector, ScreenBounds, SessionInput,
+};
+use crate::pairing::{IssuedInvite, PairingError, PairingManager};
+use crate::service::{AgentStatus, ServiceError, StatusServer};
+use crate::session::{SessionError, SessionManager};
+
+const ALPN: &[u8] = b"zrc/1";
+const MAILBOX_WAIT_MS: u64 = 25_000;
+const MAILBOX_RETRY: Duration = Duration::from_secs(5);
+const SESSION_SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+/// Time sessions get to send their close message before the listener closes
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(5);
+/// Time a control task gets to wind down after its frame stream ends
+const CONTROL_CLOSE_GRACE: Duration = Duration::from_secs(2);
+/// Minimum spacing of audit entries for one session's input limit trips.
+const INPUT_LIMIT_AUDIT_INTERVAL: Duration = Duration::from_secs(10);
+
+type Pairing = PairingManager<SqliteStore, ConsentBridge>;
+type Sessions = SessionManager<SqliteStore, ConsentBridge>;
+
+#[derive(Debug, Error)]
+pub enum RuntimeError {
+    #[error("config error: {0}")]
+    Config(#[from] ConfigError),
+    #[error("mailbox error: {0}")]
+    Mailbox(String),
+    #[error("QUIC error: {0}")]
+    Quic(String),
+    #[error("pairing error: {0}")]
+    Pairing(#[from] PairingError),
+    #[error("session error: {0}")]
+    Session(#[from] SessionError),
+    #[error("store error: {0}")]
+    Store(String),
+    #[error("malformed message: {0}")]
+    Malformed(String),
+    #[error("sender is not paired")]
+    NotPaired,
+    #[error("denied by policy: {0}")]
+    PolicyDenied(String),
+    #[error("service error: {0}")]
+    Service(#[from] ServiceError),
+}
+
+pub struct AgentRuntime {
+    config: AgentConfig,
+    identity: Arc<Identity>,
+    device_keys: IdentityKeys,
+    consent_handler: Arc<dyn ConsentHandler>,
+    status: Arc<AgentStatus>,
+}
+
+/// Agent-wide services each session uses.
+struct SessionEnv {
+    /// One receiver for all sessions, so a transfer resumes across reconnects
+    transfers: Option<Arc<FileTransfer>>,
+    recording: Option<RecordingConfig>,
+    input_limits: InputLimits,
+    audit: Option<Arc<AuditLogger>>,
+    capture_fps: u32,
+    status: Arc<AgentStatus>,
+    /// This device's signing key; tickets naming any other are refused
+    device_sign_pub: Vec<u8>,
+}
+
+impl AgentRuntime {
+    pub fn new(
+        config: AgentConfig,
+        identity: &IdentityManager,
+        consent_handler: Arc<dyn ConsentHandler>,
+    ) -> Self {
+        let device_keys = identity.identity_keys();
+        Self {
+            config,
+            identity: identity.identity(),
+            status: Arc::new(AgentStatus::new(&device_keys.id32)),
+            device_keys,
+            consent_handler,
+        }
+    }
+
+    /// Live state served by the status endpoint.
+    pub fn status(&self) -> Arc<AgentStatus> {
+        self.status.clone()
+    }
+
+    /// Run until `shutdown` flips to true (or its sender is dropped), then
+    /// close every session and the QUIC listener.
+    pub async fn run(self, mut shutdown: watch::Receiver<bool>) -> Result<(), RuntimeError> {
+        let rendezvous_url = self.config.rendezvous_url.clone().ok_or_else(|| {
+            ConfigError::ValidationError("rendezvous_url is required".to_string())
+        })?;
+        let bind_addr: SocketAddr = self.config.bind_addr.parse().map_err(|e| {
+            ConfigError::ValidationError(format!("invalid bind_addr: {}", e))
+        })?;
+        let advertise_addr: SocketAddr = match &self.config.advertise_addr {
+            Some(addr) => addr.parse().map_err(|e| {
+                ConfigError::ValidationError(format!("invalid advertise_addr: {}", e))
+            })?,
+            None => bind_addr,
+        };
+        if advertise_addr.ip().is_unspecified() {
+            warn!("Advertising wildcard address {}; set advertise_addr so operators can connect", advertise_addr);
+        }
+
+        let mailbox = HttpMailboxClient::new(rendezvous_url)
+            .map_err(|e| RuntimeError::Mailbox(e.to_string()))?;
+        let quic_config = self.config.quic_config()?;
+        let transport = quic_config
+            .transport_config()
+            .map_err(|e| RuntimeError::Quic(e.to_string()))?;
+        let quic = QuicServer::bind_with_transport(bind_addr, ALPN, Arc::new(transport), quic_config.allow_migration)
+            .await
+            .map_err(|e| RuntimeError::Quic(e.to_string()))?;
+        info!("QUIC listener on {} (advertised as {})", bind_addr, advertise_addr);
+        self.status.set_quic_listening(true);
+
+        if self.config.status_endpoint.enabled {
+            let server = StatusServer::bind(&self.config.status_endpoint, self.status.clone()).await?;
+            let shutdown = shutdown.clone();
+            tokio::spawn(async move {
+                if let Err(e) = server.serve(shutdown).await {
+                    warn!("Status endpoint failed: {}", e);
+                }
+            });
+        }
+
+        let store = open_store(&self.config)?;
+        let policy = Arc::new(PolicyEngine::new(self.config.consent_mode()?));
+        let rules = self.config.policy_engine()?.map(Arc::new);
+        let mut consent = ConsentBridge::new(self.consent_handler.clone());
+        if let Some(rules) = &rules {
+            consent = consent.with_policy(rules.clone());
+        }
+        let consent = Arc::new(consent);
+        let negotiator = TransportNegotiator::default().with_quic_config(QuicConfig {
+            certificate: quic.cert_der.clone(),
+            alpn_protocols: vec![String::from_utf8_lossy(ALPN).into_owned()],
+            server_addrs: vec![advertise_addr.to_string()],
+        });
+
+        let pairing = PairingManager::new(
+            self.device_keys.clone(),
+            store.clone(),
+            consent.clone(),
+            Arc::new(RateLimiter::new(RateLimitConfig::default())),
+            1,
+        )?;
+        let sessions = Arc::new(
+            SessionManager::new(
+                self.device_keys.clone(),
+                store.clone(),
+                policy,
+                consent.clone(),
+                self.config.max_concurrent_sessions,
+                Duration::from_secs(self.config.session_timeout_secs),
+            )?
+            .with_transport_negotiator(negotiator),
+        );
+
+        let audit = match &self.config.audit_log {
+            Some(path) => Some(Arc::new(
+                AuditLogger::new(path.clone(), self.identity.clone())
+                    .map_err(|e| ConfigError::ValidationError(format!("audit_log: {}", e)))?,
+            )),
+            None => None,
+        };
+        let env = Arc::new(SessionEnv {
+            transfers: self.config.download_dir.clone().map(|dir| {
+                Arc::new(FileTransfer::new(dir, self.config.max_transfer_bytes))
+            }),
+            recording: self.config.recording_dir.clone().map(|dir| RecordingConfig {
+                dir,
+                keys: self.device_keys.clone(),
+                frame_interval: DEFAULT_FRAME_INTERVAL,
+            }),
+            input_limits: self.config.input_limits.clone(),
+            audit,
+            capture_fps: self.config.capture_fps,
+            status: self.status.clone(),
+            device_sign_pub: self.device_keys.sign_pub.key_bytes.clone(),
+        });
+
+        let accept = tokio::spawn(accept_sessions(
+            quic.endpoint.clone(),
+            sessions.clone(),
+            env,
+            shutdown.clone(),
+        ));
+
+        let host = MailboxHost {
+            device_keys: self.device_keys.clone(),
+            mailbox,
+            store,
+            pairing,
+            sessions: sessions.clone(),
+            rules,
+            status: self.status.clone(),
+        };
+        let mut sweep = tokio::time::interval(SESSION_SWEEP_INTERVAL);
+
+        info!("Agent ready, polling mailbox {}", hex::encode(self.device_keys.id32));
+        info!("Run `zrc-agent pair` to create a pairing invite");
+        loop {
+            tokio::select! {
+                _ = shutdown.changed() => break,
+                _ = sweep.tick() => sessions.cleanup_expired_sessions().await,
+                polled = host.mailbox.poll_with_source(&self.device_keys.id32, MAILBOX_WAIT_MS) => {
+                    self.status.set_mailbox_connected(polled.is_ok());
+                    match polled {
+                        Ok(Some((bytes, source))) => {
+                            if let Err(e) = host.handle_message(&bytes, source).await {
+                                warn!("Dropped mailbox message: {}", e);
+                            }
+                        }
+                        Ok(None) => {}
+                        Err(e) => {
+                            warn!("Mailbox poll failed: {}", e);
+                            tokio::select! {
+                                _ = shutdown.changed() => break,
+                                _ = tokio::time::sleep(MAILBOX_RETRY) => {}
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        info!("Stopping agent, closing {} session(s)", sessions.active_session_count());
+        match tokio::time::timeout(SHUTDOWN_GRACE, accept).await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => warn!("Session listener task failed: {}", e),
+            Err(_) => warn!("Sessions did not close within {:?}", SHUTDOWN_GRACE),
+        }
+        quic.endpoint.close(0u32.into(), b"agent shutting down");
+        self.status.set_quic_listening(false);
+        sessions.terminate_all();
+        Ok(())
+    }
+}
+
+/// Open the agent's persistent store at `store_path`.
+fn open_store(config: &AgentConfig) -> Result<Arc<SqliteStore>, RuntimeError> {
+    if let Some(dir) = config.store_path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir).map_err(|e| RuntimeError::Store(e.to_string()))?;
+    }
+    SqliteStore::new(&config.store_path)
+        .map(Arc::new)
+        .map_err(|e| RuntimeError::Store(e.to_string()))
+}
+
+/// Create a pairing invite in the agent's store, valid for `ttl_secs`.
+///
+/// The running agent answers pair requests from the store, so the invite
+/// works whether or not it is running yet. The caller hands the secret to
+/// the operator; it is not logged.
+pub async fn create_invite(
+    config: &AgentConfig,
+    identity: &IdentityManager,
+    ttl_secs: u32,
+) -> Result<IssuedInvite, RuntimeError> {
+    // Consent is asked when the pair request arrives, not here
+    let consent = ConsentBridge::new(Arc::new(HeadlessConsentHandler::new(false)));
+    let pairing = PairingManager::new(
+        identity.identity_keys(),
+        open_store(config)?,
+        Arc::new(consent),
+        Arc::new(RateLimiter::new(RateLimitConfig::default())),
+        1,
+    )?;
+    let hints = config.rendezvous_url.clone().map(|url| EndpointHintsV1 {
+        rendezvous_urls: vec![url],
+        ..Default::default()
+    });
+    Ok(pairing.generate_invite(ttl_secs, hints).await?)
+}
+
+/// Handles pair and session requests delivered through the mailbox.
+struct MailboxHost {
+    device_keys: IdentityKeys,
+    mailbox: HttpMailboxClient,
+    store: Arc<SqliteStore>,
+    pairing: Pairing,
+    sessions: Arc<Sessions>,
+    rules: Option<Arc<RulePolicy>>,
+    status: Arc<AgentStatus>,
+}
+
+impl MailboxHost {
+    /// Pair requests arrive as bare `PairRequestV1` (the operator has no
+    /// pairing to sign with yet); everything else is a sealed `EnvelopeV1`.
+    /// `source` is the address the rendezvous server saw the message posted from.
+    async fn handle_message(&self, bytes: &[u8], source: Option<IpAddr>) -> Result<(), RuntimeError> {
+        match EnvelopeV1::decode(bytes) {
+            Ok(envelope) if is_envelope(&envelope) => self.handle_envelope(envelope).await,
+            _ => {
+                let request = PairRequestV1::decode(bytes)
+                    .map_err(|e| RuntimeError::Malformed(e.to_string()))?;
+                self.handle_pair_request(request, source).await
+            }
+        }
+    }
+
+    async fn handle_pair_request(
+        &self,
+        request: PairRequestV1,
+        source: Option<IpAddr>,
+    ) -> Result<(), RuntimeError> {
+        let operator_id = id32(&request.operator_id, "operator_id")?;
+
+        // Servers that don't report the sender share one rate limit bucket
+        let source = source.unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+        let receipt = self.pairing.handle_pair_request(request, source).await?;
+
+        self.mailbox
+            .post(&operator_id, &receipt.encode_to_vec())
+            .await
+            .map_err(|e| RuntimeError::Mailbox(e.to_string()))?;
+        self.status.record_pairing();
+        info!("Paired with operator {}", hex::encode(operator_id));
+        Ok(())
+    }
+
+    async fn handle_envelope(&self, envelope: EnvelopeV1) -> Result<(), RuntimeError> {
+        let header = envelope.header.clone().unwrap_or_default();
+        if header.msg_type != MsgTypeV1::SessionInitRequest as i32 {
+            return Err(RuntimeError::Malformed(format!(
+                "unexpected message type {}",
+                header.msg_type
+            )));
+        }
+        let operator_id = id32(&header.sender_id, "sender_id")?;
+
+        let pairing = self
+            .store
+            .load_pairing(&self.device_keys.id32, &operator_id)
+            .await
+            .map_err(|e| RuntimeError::Store(e.to_string()))?
+            .ok_or(RuntimeError::NotPaired)?;
+        let operator_sign_pub = id32(&pairing.operator_sign_pub.key_bytes, "operator_sign_pub")?;
+        let operator_kex_pub = id32(&pairing.operator_kex_pub.key_bytes, "operator_kex_pub")?;
+
+        let (plaintext, _) = envelope_open_v1(&envelope, &self.device_keys.kex_priv, &operator_sign_pub)
+            .map_err(|e| RuntimeError::Malformed(e.to_string()))?;
+        let request = SessionInitRequestV1::decode(plaintext.as_ref())
+            .map_err(|e| RuntimeError::Malformed(e.to_string()))?;
+
+        // Sessions the core policy lets through without consent never reach
+        // the consent bridge, so deny rules are enforced here as well
+        if let Some(rules) = &self.rules {
+            if let Decision::Deny { reason } =
+                rules.evaluate(&operator_id, request.requested_capabilities)
+            {
+                return Err(RuntimeError::PolicyDenied(reason));
+            }
+        }
+
+        let response = self.sessions.handle_session_request(request, true).await?;
+
+        let reply = envelope_seal_v1(
+            &self.device_keys.sign,
+            &self.device_keys.id32,
+            &operator_id,
+            &operator_kex_pub,
+            MsgTypeV1::SessionInitResponse,
+            &response.encode_to_vec(),
+            unix_now(),
+        )
+        .map_err(|e| RuntimeError::Malformed(e.to_string()))?;
+        self.mailbox
+            .post(&operator_id, &reply.encode_to_vec())
+            .await
+            .map_err(|e| RuntimeError::Mailbox(e.to_string()))?;
+        info!("Issued session ticket to operator {}", hex::encode(operator_id));
+        Ok(())
+    }
+}
+
+fn is_envelope(envelope: &EnvelopeV1) -> bool {
+    envelope.header.as_ref().is_some_and(|h| h.version == 1) && !envelope.signature.is_empty()
+}
+
+fn id32(bytes: &[u8], field: &str) -> Result<[u8; 32], RuntimeError> {
+    bytes
+        .try_into()
+        .map_err(|_| RuntimeError::Malformed(format!("{} must be 32 bytes", field)))
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Accept QUIC connections until shutdown, then wait for their sessions to
+/// close.
+async fn accept_sessions(
+    endpoint: Arc<quinn::Endpoint>,
+    sessions: Arc<Sessions>,
+    env: Arc<SessionEnv>,
+    mut shutdown: watch::Receiver<bool>,
+) {
+    let mut tasks = JoinSet::new();
+    loop {
+        let incoming = tokio::select! {
+            _ = shutdown.changed() => break,
+            incoming = endpoint.accept() => incoming,
+        };
+        let Some(incoming) = incoming else { break };
+
+        let sessions = sessions.clone();
+        let env = env.clone();
+        let shutdown = shutdown.clone();
+        tasks.spawn(async move {
+            let conn = match incoming.await {
+                Ok(conn) => conn,
+                Err(e) => {
+                    debug!("QUIC handshake failed: {}", e);
+                    return;
+                }
+            };
+            if let Err(e) = serve_session(conn, sessions, env, shutdown).await {
+                warn!("Session ended with error: {}", e);
+            }
+        });
+    }
+    while tasks.join_next().await.is_some() {}
+}
+
+/// Serve one operator connection: verify its ticket, apply its input and
+/// stream frames until either side closes or the agent shuts down.
+async fn serve_session(
+    conn: quinn::Connection,
+    sessions: Arc<Sessions>,
+    env: Arc<SessionEnv>,
+    mut shutdown: watch::Receiver<bool>,
+) -> anyhow::Result<()> {
+    let (ticket_packet, mut control) = host_accept_control_handshake(&conn, unix_now()).await?;
+    let presented = ticket_packet.ticket.unwrap_or_default();
+    let ticket_hex = hex::encode(&presented.ticket_id);
+
+    // The handshake only checks the ticket against the key it carries, so
+    // trust nothing in it beyond the id: permissions and binding come from
+    // the ticket this agent issued
+    let issued_by_us = presented
+        .device_sign_pub
+        .as_ref()
+        .is_some_and(|key| key.key_bytes == env.device_sign_pub);
+    let ticket = match sessions.active_ticket(&presented.ticket_id) {
+        Some(ticket) if issued_by_us && ticket.session_binding == presented.session_binding => ticket,
+        _ => {
+            conn.close(0u32.into(), b"unknown session");
+            anyhow::bail!("ticket {} was not issued by this agent", ticket_hex);
+        }
+    };
+    if ticket.permissions & PermissionsV1::View as u32 == 0 {
+        conn.close(0u32.into(), b"view not permitted");
+        let _ = sessions.terminate_session(&ticket.ticket_id).await;
+        anyhow::bail!("ticket {} does not grant view", ticket_hex);
+    }
+    info!("Session {} connected from {}", ticket_hex, conn.remote_address());
+    let _active = env.status.session_opened();
+
+    let crypto = derive_session_crypto_v1(&ticket.session_binding, &ticket.ticket_id);
+    let codec = control.frame_codec;
+    let quality = control.quality.clone();
+    let mut close_signal = control.close_signal();
+
+    let recorder = match &env.recording {
+        Some(recording) => start_recording(recording, &ticket, &mut control).await,
+        None => None,
+    };
+
+    let can_control = ticket.permissions & PermissionsV1::Control as u32 != 0;
+    let bounds = Arc::new(ScreenBounds::default());
+    let input = Arc::new(Mutex::new(if can_control {
+        platform_injector().map(|injector| {
+            SessionInput::new(injector)
+                .with_screen(bounds.clone())
+                .with_filter(InputFilter::new(env.input_limits.clone(), bounds.clone()))
+        })
+    } else {
+        None
+    }));
+
+    let shared = SessionShared {
+        recorder: recorder.clone(),
+        input: input.clone(),
+        monitors: Arc::new(std::sync::Mutex::new(MonitorSelection::new())),
+    };
+    let monitors = shared.monitors.clone();
+    let (notices, notices_rx) = mpsc::unbounded_channel();
+    let cursor_task = tokio::spawn(forward_cursor(notices.clone()));
+
+    let mut control_task = tokio::spawn(run_control(
+        control,
+        sessions.clone(),
+        env.clone(),
+        shared,
+        ticket.clone(),
+        notices_rx,
+        shutdown.clone(),
+    ));
+
+    let rate = FrameRateConfig {
+        max_fps: Some(env.capture_fps as f64),
+        ..FrameRateConfig::default()
+    };
+    let (_stats, frames) = host_stream_frames_with_stats(&conn, &crypto, codec, rate, quality, move || {
+        // Resolved every frame, against the cached enumeration, so an
+        // unplugged monitor falls back to the primary and the operator is told
+        let available = capture::enumerate_monitors();
+        let (selected, fallback) = monitors.lock().unwrap().resolve(&available);
+        if let Some(notice) = fallback {
+            let _ = notices.send(ControlMsgV1::new(0, Payload::MonitorSelect(notice)));
+        }
+        let frame = match selected.and_then(|id| available.iter().find(|m| m.id == id)) {
+            Some(monitor) => {
+                bounds.set_origin(monitor.bounds.x, monitor.bounds.y);
+                capture::capture_monitor(&monitor.bounds)
+            }
+            None => {
+                bounds.set_origin(0, 0);
+                capture::capture_primary()
+            }
+        }
+        .map_err(|e| anyhow::anyhow!("{e}"))?;
+        bounds.set(frame.width, frame.height);
+        if let Some(recorder) = &recorder {
+            recorder.frame(&frame);
+        }
+        Ok(FramePacketV1 {
+            width: frame.width,
+            height: frame.height,
+            stride: frame.stride,
+            format: 1,
+            pixels: frame.data.to_vec(),
+        })
+    });
+
+    tokio::select! {
+        result = frames => {
+            if let Err(e) = result {
+                debug!("Frame stream for session {} ended: {}", ticket_hex, e);
+            }
+        }
+        close = close_signal.closed() => {
+            info!("Session {} closed by operator ({:?})", ticket_hex, close.reason());
+        }
+        _ = shutdown.changed() => {}
+    }
+
+    // Stopping the frame stream stops capture; give the control task time to
+    // send its close message and release held keys before dropping the link
+    if tokio::time::timeout(CONTROL_CLOSE_GRACE, &mut control_task).await.is_err() {
+        control_task.abort();
+    }
+    cursor_task.abort();
+    // Covers the control task being aborted or failing mid-keystroke
+    release_held_input(&input).await;
+    conn.close(0u32.into(), b"session ended");
+    let _ = sessions.terminate_session(&ticket.ticket_id).await;
+    Ok(())
+}
+
+/// Session state the control task shares with the frame pump.
+struct SessionShared {
+    recorder: Option<Arc<RecorderHandle>>,
+    input: Arc<Mutex<Option<SessionInput>>>,
+    monitors: Arc<std::sync::Mutex<MonitorSelection>>,
+}
+
+/// Handle the operator's control messages and send the agent's own notices
+/// (`notices`) until the session ends.
+async fn run_control(
+    mut control: ControlChannelV1,
+    sessions: Arc<Sessions>,
+    env: Arc<SessionEnv>,
+    shared: SessionShared,
+    ticket: SessionTicketV1,
+    mut notices: mpsc::UnboundedReceiver<ControlMsgV1>,
+    mut shutdown: watch::Receiver<bool>,
+) {
+    let SessionShared { recorder, input, monitors } = shared;
+    let mut last_limit_audit: Option<Instant> = None;
+    loop {
+        let idle_deadline = sessions.idle_deadline(&ticket.ticket_id).await;
+        let received = tokio::select! {
+            _ = shutdown.changed() => None,
+            _ = sleep_until(idle_deadline) => {
+                match sessions.check_idle(&ticket.ticket_id).await {
+                    Ok(Some(close)) => {
+                        warn!("Closing idle session {}: {}", hex::encode(&ticket.ticket_id), close.detail);
+                        release_held_input(&input).await;
+                        if let Err(e) = control.send_close(close.reason(), &close.detail).await {
+                            debug!("Failed to send session close: {}", e);
+                        }
+                        break;
+                    }
+                    Ok(None) => {}
+                    Err(e) => debug!("Idle check failed: {}", e),
+                }
+                continue;
+            }
+            Some(notice) = notices.recv() => {
+                if let Err(e) = control.send_msg(&notice).await {
+                    debug!("Failed to send control notice: {}", e);
+                }
+                continue;
+            }
+            received = control.recv_msg() => Some(received),
+        };
+        let Some(received) = received else {
+            release_held_input(&input).await;
+            if let Err(e) = control.send_close(ReasonV1::UserDisconnect, "agent shutting down").await {
+                debug!("Failed to send session close: {}", e);
+            }
+            break;
+        };
+        let msg = match received {
+            Ok(Some(msg)) => msg,
+            Ok(None) => break,
+            Err(e) => {
+                debug!("Control channel closed: {}", e);
+                break;
+            }
+        };
+
+        sessions.touch(&ticket.ticket_id);
+        if let Some(pong) = sessions.handle_keepalive(&ticket.ticket_id, &msg).await {
+            if let Err(e) = control.send_msg(&pong).await {
+                debug!("Failed to send pong: {}", e);
+            }
+        }
+        match msg.payload {
+            Some(Payload::Input(event)) => {
+                let applied = match input.lock().await.as_mut() {
+                    Some(input) => input.apply(&event).await.map(|()| true),
+                    None => Ok(false),
+                };
+                match applied {
+                    // Only input that passed the filter and was injected
+                    // is recorded, so a throttled flood isn't
+                    Ok(true) => {
+                        if let Some(recorder) = &recorder {
+                            recorder.input(&event);
+                        }
+                    }
+                    Err(e @ (InputError::RateLimited | InputError::Rejected(_))) => {
+                        // A flood trips this on every event; audit once per interval
+                        if last_limit_audit.is_none_or(|t| t.elapsed() >= INPUT_LIMIT_AUDIT_INTERVAL) {
+                            last_limit_audit = Some(Instant::now());
+                            warn!("Session {} input limited: {}", hex::encode(&ticket.ticket_id), e);
+                            audit_input_limited(&env, &ticket, &e).await;
+                        }
+                    }
+                    Err(e) => debug!("Input event not applied: {}", e),
+                    Ok(false) => {}
+                }
+            }
+            Some(Payload::SessionControl(session_control)) => {
+                if let Err(e) = control.apply_session_control(&session_control).await {
+                    debug!("Session control not applied: {}", e);
+                }
+            }
+            Some(Payload::FileControl(file_control)) => {
+                let reply = match &env.transfers {
+                    Some(transfers) => transfers
+                        .handle_control(&file_control, ticket.permissions)
+                        .await
+                        .unwrap_or_else(|e| {
+                            debug!("File transfer message rejected: {}", e);
+                            Some(e.to_message(&file_control.transfer_id))
+                        }),
+                    None => Some(
+                        FileTransferError::PermissionDenied("file transfer is disabled".to_string())
+                            .to_message(&file_control.transfer_id),
+                    ),
+                };
+                if let Some(reply) = reply {
+                    let msg = ControlMsgV1::new(0, Payload::FileControl(reply));
+                    if let Err(e) = control.send_msg(&msg).await {
+                        debug!("Failed to send file transfer reply: {}", e);
+                    }
+                }
+            }
+            Some(Payload::MonitorSelect(request)) => {
+                let available = capture::enumerate_monitors();
+                let reply = monitors.lock().unwrap().handle_request(&request, &available);
+                if let Some(reply) = reply {
+                    let msg = ControlMsgV1::new(0, Payload::MonitorSelect(reply));
+                    if let Err(e) = control.send_msg(&msg).await {
+                        debug!("Failed to send monitor reply: {}", e);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    release_held_input(&input).await;
+}
+
+/// Sleep until `deadline`, or forever without one.
+async fn sleep_until(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline.into()).await,
+        None => std::future::pending().await,
+    }
+}
+
+async fn audit_input_limited(env: &SessionEnv, ticket: &SessionTicketV1, reason: &InputError) {
+    let (Some(audit), Ok(operator_id)) = (&env.audit, <[u8; 32]>::try_from(ticket.operator_id.as_slice()))
+    else {
+        return;
+    };
+    if let Err(e) = audit
+        .log_input_limited(&operator_id, &ticket.session_id, &reason.to_string())
+        .await
+    {
+        warn!("Failed to audit input limit: {}", e);
+    }
+}
+
+/// Send the host cursor shape to the operator whenever it changes, until the
+/// control task stops taking notices.
+async fn forward_cursor(notices: mpsc::UnboundedSender<ControlMsgV1>) {
+    let mut tracker = CursorTracker::default();
+    let mut poll = tokio::time::interval(DEFAULT_CURSOR_POLL_INTERVAL);
+    poll.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    loop {
+        poll.tick().await;
+        if let Some(shape) = tracker.observe(capture::cursor_shape(), Instant::now()) {
+            if notices.send(ControlMsgV1::new(0, Payload::CursorShape(shape))).is_err() {
+                break;
+            }
+        }
+    }
+}
+
+/// Release whatever keys and buttons the session still holds.
+async fn release_held_input(input: &Mutex<Option<SessionInput>>) {
+    if let Some(input) = input.lock().await.as_mut() {
+        if let Err(e) = input.release_all().await {
+            warn!("Failed to release held input: {}", e);
+        }
+    }
+}
+
+/// Tell the operator the session is recorded, then start recording it.
+/// Nothing is recorded if the notice cannot be delivered.
+async fn start_recording(
+    recording: &RecordingConfig,
+    ticket: &SessionTicketV1,
+    control: &mut ControlChannelV1,
+) -> Option<Arc<RecorderHandle>> {
+    let status = RecordingStatusV1 {
+        recording: true,
+        input_events: true,
+        frame_interval_ms: recording.frame_interval.as_millis() as u32,
+    };
+    let notice = ControlMsgV1::new(0, Payload::RecordingStatus(status));
+    if let Err(e) = control.send_msg(&notice).await {
+        warn!("Not recording session: operator could not be notified: {}", e);
+        return None;
+    }
+
+    let manifest = RecordingManifest {
+        version: 1,
+        session: hex::encode(&ticket.ticket_id),
+        device_id: hex::encode(&ticket.device_id),
+        operator_id: hex::encode(&ticket.operator_id),
+        permissions: ticket.permissions,
+        started_at: unix_now(),
+        frame_interval_ms: recording.frame_interval.as_millis() as u32,
+        downsample: recording::DOWNSAMPLE,
+    };
+    match SessionRecorder::create(recording, &manifest).await {
+        Ok(recorder) => {
+            info!("Recording session to {}", recorder.path().display());
+            Some(Arc::new(RecorderHandle::spawn(recorder, recording.frame_interval)))
+        }
+        Err(e) => {
+            warn!("Failed to start session recording: {}", e);
+            let stopped = ControlMsgV1::new(0, Payload::RecordingStatus(RecordingStatusV1::default()));
+            if let Err(e) = control.send_msg(&stopped).await {
+                debug!("Failed to withdraw recording notice: {}", e);
+            }
+            None
+        }
+    }
+}
+
+#[cfg(windows)]
+fn platform_injector() -> Option<Box<dyn PlatformInjector>> {
+    match crate::input::WindowsInjector::new() {
+        Ok(injector) => Some(Box::new(injector)),
+        Err(e) => {
+            warn!("Input injection unavailable: {}", e);
+            None
+        }
+    }
+}
+
+#[cfg(not(windows))]
+fn platform_injector() -> Option<Box<dyn PlatformInjector>> {
+    warn!("Input injection not implemented for this platform");
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zrc_core::keys::generate_identity_keys;
+    use zrc_core::policy::ConsentMode;
+    use zrc_core::quic::QuicClient;
+    use zrc_core::quic_mux::controller_control_handshake;
+    use zrc_core::store::PairingRecord;
+    use zrc_crypto::ticket::{compute_session_binding_v1, sign_ticket_v1};
+    use zrc_proto::v1::{
+        control_msg_v1, ControlTicketV1, DeviceIdV1, KeyTypeV1, PublicKeyV1, SessionIdV1, UserIdV1,
+    };
+
+    const IDLE_TIMEOUT: Duration = Duration::from_millis(300);
+
+    fn key(key_type: KeyTypeV1) -> PublicKeyV1 {
+        PublicKeyV1 { key_type: key_type as i32, key_bytes: vec![0u8; 32] }
+    }
+
+    #[tokio::test]
+    async fn test_control_loop_closes_session_once_pings_stop() {
+        // The server config needs a process-wide provider; another test may
+        // already have installed one
+        let _ = rustls::crypto::ring::default_provider().install_default();
+        let device_keys = generate_identity_keys();
+        let operator_keys = generate_identity_keys();
+
+        let store = Arc::new(SqliteStore::new_in_memory().unwrap());
+        store
+            .save_pairing(PairingRecord {
+                pairing_id: vec![1u8; 16],
+                device_id: device_keys.id32.to_vec(),
+                operator_id: operator_keys.id32.to_vec(),
+                device_sign_pub: key(KeyTypeV1::Ed25519),
+                device_kex_pub: key(KeyTypeV1::X25519),
+                operator_sign_pub: key(KeyTypeV1::Ed25519),
+                operator_kex_pub: key(KeyTypeV1::X25519),
+                granted_perms: vec![0],
+                unattended_enabled: true,
+                require_consent_each_time: false,
+                issued_at: unix_now(),
+                last_session: None,
+            })
+            .await
+            .unwrap();
+        let mut policy = PolicyEngine::new(ConsentMode::UnattendedAllowed);
+        policy.set_idle_timeout(Some(IDLE_TIMEOUT));
+        let sessions = Arc::new(
+            Sessions::new(
+                device_keys.clone(),
+                store,
+                Arc::new(policy),
+                Arc::new(ConsentBridge::new(Arc::new(HeadlessConsentHandler::new(true)))),
+                1,
+                Duration::from_secs(3600),
+            )
+            .unwrap(),
+        );
+        let session_id = vec![3u8; 32];
+        let response = sessions
+            .handle_session_request(
+                SessionInitRequestV1 {
+                    operator_id: operator_keys.id32.to_vec(),
+                    device_id: device_keys.id32.to_vec(),
+                    session_id: session_id.clone(),
+                    requested_capabilities: PermissionsV1::View as u32,
+                    ..Default::default()
+                },
+                false,
+            )
+            .await
+            .unwrap();
+        let ticket = response.issued_ticket.unwrap();
+
+        // Present the issued ticket id under a binding the handshake accepts
+        let nonce = vec![7u8; 16];
+        let mut presented = ticket.clone();
+        presented.session_binding =
+            compute_session_binding_v1(&session_id, &operator_keys.id32, &device_keys.id32, &nonce).to_vec();
+        sign_ticket_v1(&device_keys.sign, &mut presented).unwrap();
+        let ticket_packet = ControlTicketV1 {
+            session_id: Some(SessionIdV1 { id: session_id }),
+            device_id: Some(DeviceIdV1 { id: device_keys.id32.to_vec() }),
+            operator_id: Some(UserIdV1 { id: operator_keys.id32.to_vec() }),
+            ticket_binding_nonce: nonce,
+            ticket: Some(presented),
+            ..Default::default()
+        };
+
+        let server = QuicServer::bind("127.0.0.1:0".parse().unwrap(), ALPN).await.unwrap();
+        let addr = server.endpoint.local_addr().unwrap();
+        let client = QuicClient::new("127.0.0.1:0".parse().unwrap(), ALPN, &server.cert_der).unwrap();
+        let accept = async { server.endpoint.accept().await.unwrap().await.unwrap() };
+        let (host_conn, conn) = tokio::join!(accept, client.connect(addr, "zrc.local"));
+        let conn = conn.unwrap();
+        let (host, controller) = tokio::join!(
+            host_accept_control_handshake(&host_conn, unix_now()),
+            controller_control_handshake(&conn, &ticket_packet),
+        );
+        let (_, host_control) = host.unwrap();
+        let mut control = controller.unwrap();
+
+        let env = Arc::new(SessionEnv {
+            transfers: None,
+            recording: None,
+            input_limits: InputLimits::default(),
+            audit: None,
+            capture_fps: 30,
+            status: Arc::new(AgentStatus::new(&device_keys.id32)),
+            device_sign_pub: device_keys.sign_pub.key_bytes.clone(),
+        });
+        let shared = SessionShared {
+            recorder: None,
+            input: Arc::new(Mutex::new(None)),
+            monitors: Arc::new(std::sync::Mutex::new(MonitorSelection::new())),
+        };
+        let (_notices, notices_rx) = mpsc::unbounded_channel();
+        let (_shutdown, shutdown_rx) = watch::channel(false);
+        let loop_task = tokio::spawn(run_control(
+            host_control,
+            sessions.clone(),
+            env,
+            shared,
+            ticket.clone(),
+            notices_rx,
+            shutdown_rx,
+        ));
+
+        // Pinging keeps the session open well past the idle timeout
+        for sequence in 1..=8 {
+            control.send_msg(&ControlMsgV1::ping(sequence)).await.unwrap();
+            let pong = control.recv_msg().await.unwrap().expect("pong, not a close");
+            assert!(matches!(pong.payload, Some(control_msg_v1::Payload::Pong(_))));
+            tokio::time::sleep(IDLE_TIMEOUT / 3).await;
+        }
+        assert!(sessions.is_active(&ticket.ticket_id));
+
+        // Once the pings stop, the host closes the session for inactivity
+        let closed = tokio::time::timeout(IDLE_TIMEOUT * 10, control.recv_msg()).await.unwrap();
+        assert!(closed.unwrap().is_none());
+        assert_eq!(control.close_reason().unwrap().reason(), ReasonV1::Idle);
+        tokio::time::timeout(Duration::from_secs(5), loop_task).await.unwrap().unwrap();
+        assert!(!sessions.is_active(&ticket.ticket_id));
+    }
+}
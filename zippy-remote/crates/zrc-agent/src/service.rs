@@ -1,6 +1,18 @@
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
 use async_trait::async_trait;
+use axum::extract::{ConnectInfo, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::get;
+use axum::{Json, Router};
+use constant_time_eq::constant_time_eq;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
-use tracing::info;
+use tokio::net::TcpListener;
+use tracing::{info, warn};
 
 #[derive(Debug, Error)]
 pub enum ServiceError {
@@ -12,6 +24,8 @@ pub enum ServiceError {
     StopFailed(String),
     #[error("signal handling failed: {0}")]
     SignalFailed(String),
+    #[error("agent is unhealthy: {0}")]
+    Unhealthy(String),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -80,6 +94,272 @@ impl ServiceHost for ForegroundService {
     }
 }
 
+/// Local status endpoint settings (`[status_endpoint]` in the config file).
+///
+/// Requests from loopback are always answered; anything else needs
+/// `Authorization: Bearer <token>`, so binding beyond localhost requires a
+/// token.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StatusEndpointConfig {
+    pub enabled: bool,
+    pub bind_addr: String,
+    pub token: Option<String>,
+}
+
+impl Default for StatusEndpointConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            bind_addr: "127.0.0.1:9470".to_string(),
+            token: None,
+        }
+    }
+}
+
+impl StatusEndpointConfig {
+    /// Parse `bind_addr`, refusing a non-loopback address without a token.
+    pub fn socket_addr(&self) -> Result<SocketAddr, ServiceError> {
+        let addr: SocketAddr = self.bind_addr.parse().map_err(|e| {
+            ServiceError::InitFailed(format!("invalid status bind_addr: {}", e))
+        })?;
+        if !addr.ip().is_loopback() && self.token.as_deref().is_none_or(str::is_empty) {
+            return Err(ServiceError::InitFailed(
+                "status endpoint bound beyond localhost requires a token".to_string(),
+            ));
+        }
+        Ok(addr)
+    }
+}
+
+/// Live agent state reported by the status endpoint. The runtime updates it
+/// as sessions come and go; readers only ever see a snapshot.
+#[derive(Debug)]
+pub struct AgentStatus {
+    device_id: String,
+    started: Instant,
+    active_sessions: AtomicUsize,
+    /// Unix seconds of the last completed pairing, 0 if none yet
+    last_pairing_at: AtomicU64,
+    quic_listening: AtomicBool,
+    mailbox_connected: AtomicBool,
+}
+
+impl AgentStatus {
+    pub fn new(device_id: &[u8]) -> Self {
+        Self {
+            device_id: hex::encode(device_id),
+            started: Instant::now(),
+            active_sessions: AtomicUsize::new(0),
+            last_pairing_at: AtomicU64::new(0),
+            quic_listening: AtomicBool::new(false),
+            mailbox_connected: AtomicBool::new(false),
+        }
+    }
+
+    /// Count a session as active until the returned guard is dropped.
+    pub fn session_opened(self: &Arc<Self>) -> ActiveSessionGuard {
+        self.active_sessions.fetch_add(1, Ordering::Relaxed);
+        ActiveSessionGuard(self.clone())
+    }
+
+    pub fn record_pairing(&self) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        self.last_pairing_at.store(now, Ordering::Relaxed);
+    }
+
+    pub fn set_quic_listening(&self, listening: bool) {
+        self.quic_listening.store(listening, Ordering::Relaxed);
+    }
+
+    pub fn set_mailbox_connected(&self, connected: bool) {
+        self.mailbox_connected.store(connected, Ordering::Relaxed);
+    }
+
+    pub fn report(&self) -> StatusReport {
+        StatusReport {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            device_id: self.device_id.clone(),
+            active_sessions: self.active_sessions.load(Ordering::Relaxed),
+            last_pairing_at: match self.last_pairing_at.load(Ordering::Relaxed) {
+                0 => None,
+                at => Some(at),
+            },
+            transport: TransportStatus {
+                quic_listening: self.quic_listening.load(Ordering::Relaxed),
+                mailbox_connected: self.mailbox_connected.load(Ordering::Relaxed),
+            },
+            uptime_secs: self.started.elapsed().as_secs(),
+        }
+    }
+}
+
+/// Keeps one session counted in [`AgentStatus`] while alive.
+#[derive(Debug)]
+pub struct ActiveSessionGuard(Arc<AgentStatus>);
+
+impl Drop for ActiveSessionGuard {
+    fn drop(&mut self) {
+        self.0.active_sessions.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// JSON body of `GET /status`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusReport {
+    pub version: String,
+    /// Hex device id
+    pub device_id: String,
+    pub active_sessions: usize,
+    /// Unix seconds of the last completed pairing
+    pub last_pairing_at: Option<u64>,
+    pub transport: TransportStatus,
+    pub uptime_secs: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransportStatus {
+    pub quic_listening: bool,
+    /// Whether the last rendezvous mailbox poll succeeded
+    pub mailbox_connected: bool,
+}
+
+#[derive(Clone)]
+struct StatusState {
+    status: Arc<AgentStatus>,
+    token: Option<Arc<str>>,
+}
+
+/// HTTP server for the status endpoint.
+pub struct StatusServer {
+    listener: TcpListener,
+    state: StatusState,
+}
+
+impl StatusServer {
+    pub async fn bind(
+        config: &StatusEndpointConfig,
+        status: Arc<AgentStatus>,
+    ) -> Result<Self, ServiceError> {
+        let addr = config.socket_addr()?;
+        let listener = TcpListener::bind(addr)
+            .await
+            .map_err(|e| ServiceError::StartFailed(format!("status endpoint {}: {}", addr, e)))?;
+        Ok(Self {
+            listener,
+            state: StatusState {
+                status,
+                token: config.token.as_deref().filter(|t| !t.is_empty()).map(Arc::from),
+            },
+        })
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr, ServiceError> {
+        self.listener
+            .local_addr()
+            .map_err(|e| ServiceError::StartFailed(e.to_string()))
+    }
+
+    /// Serve until `shutdown` changes.
+    pub async fn serve(
+        self,
+        mut shutdown: tokio::sync::watch::Receiver<bool>,
+    ) -> Result<(), ServiceError> {
+        if let Ok(addr) = self.listener.local_addr() {
+            info!("Status endpoint listening on http://{}/status", addr);
+        }
+        let router = Router::new()
+            .route("/status", get(get_status))
+            .route("/health", get(get_status))
+            .with_state(self.state);
+        axum::serve(
+            self.listener,
+            router.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .with_graceful_shutdown(async move {
+            let _ = shutdown.changed().await;
+        })
+        .await
+        .map_err(|e| ServiceError::StopFailed(e.to_string()))
+    }
+}
+
+/// Ask the running agent's status endpoint whether it is healthy.
+///
+/// Used by `zrc-agent --healthcheck`, which the updater runs after restarting
+/// the service: the agent must answer, run this binary's version and have its
+/// QUIC listener up.
+pub async fn probe_health(config: &StatusEndpointConfig) -> Result<StatusReport, ServiceError> {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let fail = ServiceError::Unhealthy;
+    let mut addr = config.socket_addr()?;
+    if addr.ip().is_unspecified() {
+        addr.set_ip(std::net::Ipv4Addr::LOCALHOST.into());
+    }
+
+    let mut request = format!("GET /health HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n", addr);
+    if let Some(token) = config.token.as_deref().filter(|t| !t.is_empty()) {
+        request.push_str(&format!("Authorization: Bearer {}\r\n", token));
+    }
+    request.push_str("\r\n");
+
+    let mut stream = tokio::net::TcpStream::connect(addr)
+        .await
+        .map_err(|e| fail(format!("{}: {}", addr, e)))?;
+    stream.write_all(request.as_bytes()).await.map_err(|e| fail(e.to_string()))?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.map_err(|e| fail(e.to_string()))?;
+
+    let (head, body) = response
+        .split_once("\r\n\r\n")
+        .ok_or_else(|| fail("malformed response".to_string()))?;
+    let status_line = head.lines().next().unwrap_or_default();
+    if !status_line.starts_with("HTTP/1.1 200") {
+        return Err(fail(status_line.to_string()));
+    }
+    let report: StatusReport = serde_json::from_str(body).map_err(|e| fail(e.to_string()))?;
+
+    if report.version != env!("CARGO_PKG_VERSION") {
+        return Err(fail(format!(
+            "service runs {}, expected {}",
+            report.version,
+            env!("CARGO_PKG_VERSION")
+        )));
+    }
+    if !report.transport.quic_listening {
+        return Err(fail("QUIC listener is not up".to_string()));
+    }
+    Ok(report)
+}
+
+/// Loopback callers are trusted; remote ones must present the token.
+fn is_authorized(peer: &SocketAddr, headers: &HeaderMap, token: Option<&str>) -> bool {
+    if peer.ip().is_loopback() {
+        return true;
+    }
+    let presented = headers
+        .get("authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|s| s.strip_prefix("Bearer "));
+    matches!((presented, token), (Some(p), Some(t)) if constant_time_eq(p.as_bytes(), t.as_bytes()))
+}
+
+async fn get_status(
+    State(state): State<StatusState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+) -> Result<Json<StatusReport>, StatusCode> {
+    if !is_authorized(&peer, &headers, state.token.as_deref()) {
+        warn!("Unauthorized status request from {}", peer);
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+    Ok(Json(state.status.report()))
+}
+
 #[cfg(windows)]
 pub mod windows {
     use super::*;
@@ -174,7 +454,7 @@ pub mod linux {
         }
     }
 
-    #[async_trait]
+    #[async_trait(?Send)]
     impl ServiceHost for SystemdServiceHost {
         async fn start(&mut self) -> Result<(), ServiceError> {
             info!("Starting zrc-agent as systemd service");
@@ -216,7 +496,7 @@ pub mod macos {
         }
     }
 
-    #[async_trait]
+    #[async_trait(?Send)]
     impl ServiceHost for LaunchdServiceHost {
         async fn start(&mut self) -> Result<(), ServiceError> {
             info!("Starting zrc-agent as launchd daemon");
@@ -241,3 +521,114 @@ pub mod macos {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    fn state(token: Option<&str>) -> StatusState {
+        StatusState {
+            status: Arc::new(AgentStatus::new(&[0xab; 32])),
+            token: token.map(Arc::from),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_remote_requests_need_token() {
+        let remote: SocketAddr = "10.0.0.5:40000".parse().unwrap();
+        let mut auth = HeaderMap::new();
+        auth.insert("authorization", "Bearer secret".parse().unwrap());
+
+        let open = get_status(
+            State(state(None)),
+            ConnectInfo("127.0.0.1:40000".parse().unwrap()),
+            HeaderMap::new(),
+        )
+        .await;
+        assert!(open.is_ok());
+
+        let denied = get_status(State(state(None)), ConnectInfo(remote), auth.clone()).await;
+        assert_eq!(denied.unwrap_err(), StatusCode::UNAUTHORIZED);
+        let denied = get_status(State(state(Some("secret"))), ConnectInfo(remote), HeaderMap::new()).await;
+        assert_eq!(denied.unwrap_err(), StatusCode::UNAUTHORIZED);
+        let denied = get_status(State(state(Some("secret2"))), ConnectInfo(remote), auth.clone()).await;
+        assert_eq!(denied.unwrap_err(), StatusCode::UNAUTHORIZED);
+        let allowed = get_status(State(state(Some("secret"))), ConnectInfo(remote), auth).await;
+        assert!(allowed.is_ok());
+
+        let config = StatusEndpointConfig {
+            bind_addr: "0.0.0.0:9470".to_string(),
+            ..Default::default()
+        };
+        assert!(config.socket_addr().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_status_json_reflects_state() {
+        let status = Arc::new(AgentStatus::new(&[0xab; 32]));
+        status.set_quic_listening(true);
+        status.record_pairing();
+        let first = status.session_opened();
+        let _second = status.session_opened();
+        drop(first);
+
+        let config = StatusEndpointConfig {
+            bind_addr: "127.0.0.1:0".to_string(),
+            ..Default::default()
+        };
+        let server = StatusServer::bind(&config, status.clone()).await.unwrap();
+        let addr = server.local_addr().unwrap();
+        let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+        let serve = tokio::spawn(server.serve(shutdown_rx));
+
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(b"GET /status HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        assert!(head.starts_with("HTTP/1.1 200"));
+
+        let json: serde_json::Value = serde_json::from_str(body).unwrap();
+        assert_eq!(json["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(json["device_id"], hex::encode([0xab; 32]));
+        assert_eq!(json["active_sessions"], 1);
+        assert!(json["last_pairing_at"].as_u64().unwrap() > 0);
+        assert_eq!(json["transport"]["quic_listening"], true);
+        assert_eq!(json["transport"]["mailbox_connected"], false);
+        assert!(json["uptime_secs"].is_u64());
+
+        shutdown_tx.send(true).unwrap();
+        serve.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_probe_health_needs_quic_listener() {
+        let status = Arc::new(AgentStatus::new(&[0xab; 32]));
+        let config = StatusEndpointConfig {
+            bind_addr: "127.0.0.1:0".to_string(),
+            ..Default::default()
+        };
+        let server = StatusServer::bind(&config, status.clone()).await.unwrap();
+        let config = StatusEndpointConfig {
+            bind_addr: server.local_addr().unwrap().to_string(),
+            ..Default::default()
+        };
+        let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+        let serve = tokio::spawn(server.serve(shutdown_rx));
+
+        assert!(probe_health(&config).await.is_err());
+        status.set_quic_listening(true);
+        let report = probe_health(&config).await.unwrap();
+        assert_eq!(report.device_id, hex::encode([0xab; 32]));
+
+        shutdown_tx.send(true).unwrap();
+        serve.await.unwrap().unwrap();
+
+        // Nothing listening is unhealthy too
+        assert!(probe_health(&config).await.is_err());
+    }
+}
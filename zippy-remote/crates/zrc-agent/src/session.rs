@@ -1,14 +1,20 @@
 use std::sync::Arc;
-use std::time::{Duration, SystemTime};
+use std::time::{Duration, Instant, SystemTime};
 use zrc_core::session::{SessionHost, SessionError as CoreSessionError, SessionConsentHandler, SessionConsentDecision};
 use zrc_core::store::Store;
 use zrc_core::policy::PolicyEngine;
+use zrc_core::transport::TransportNegotiator;
 use zrc_core::types::IdentityKeys;
-use zrc_proto::v1::{SessionInitRequestV1, SessionInitResponseV1, SessionTicketV1};
+use zrc_proto::v1::{ControlMsgV1, SessionCloseV1, SessionInitRequestV1, SessionInitResponseV1, SessionTicketV1};
 use async_trait::async_trait;
 use thiserror::Error;
 use tracing::info;
 use dashmap::DashMap;
+use tokio::sync::Mutex;
+
+/// A session's host state machine, shared between the manager and its
+/// control loop.
+type SharedHost<S, C> = Arc<Mutex<SessionHost<S, C>>>;
 
 #[derive(Debug, Clone)]
 pub struct ActiveSession {
@@ -53,8 +59,12 @@ pub struct SessionManager<S: Store + Send + Sync + 'static, C: SessionConsentHan
     policy: Arc<PolicyEngine>,
     consent_handler: Arc<C>,
     active_sessions: Arc<DashMap<Vec<u8>, ActiveSession>>,
+    /// Host state machine of each active session, which enforces its idle
+    /// timeout
+    hosts: Arc<DashMap<Vec<u8>, SharedHost<S, C>>>,
     max_concurrent_sessions: usize,
     session_timeout: Duration,
+    transport_negotiator: TransportNegotiator,
 }
 
 impl<S: Store + Send + Sync + 'static, C: SessionConsentHandler + 'static> SessionManager<S, C> {
@@ -72,11 +82,20 @@ impl<S: Store + Send + Sync + 'static, C: SessionConsentHandler + 'static> Sessi
             policy,
             consent_handler,
             active_sessions: Arc::new(DashMap::new()),
+            hosts: Arc::new(DashMap::new()),
             max_concurrent_sessions,
             session_timeout,
+            transport_negotiator: TransportNegotiator::default(),
         })
     }
 
+    /// Offer the transports (e.g. the agent's QUIC listener) configured on
+    /// `negotiator` in session responses.
+    pub fn with_transport_negotiator(mut self, negotiator: TransportNegotiator) -> Self {
+        self.transport_negotiator = negotiator;
+        self
+    }
+
     pub async fn handle_session_request(
         &self,
         request: SessionInitRequestV1,
@@ -88,11 +107,12 @@ impl<S: Store + Send + Sync + 'static, C: SessionConsentHandler + 'static> Sessi
         }
 
         // Create a new session host to handle this request
-        let mut host = SessionHost::new(
+        let mut host = SessionHost::with_transport_negotiator(
             self.device_keys.clone(),
             self.store.clone(),
             self.policy.clone(),
             self.consent_handler.clone(),
+            self.transport_negotiator.clone(),
         );
 
         // Handle the request
@@ -127,6 +147,7 @@ impl<S: Store + Send + Sync + 'static, C: SessionConsentHandler + 'static> Sessi
                 started_at: SystemTime::now(),
                 last_activity: SystemTime::now(),
             };
+            self.hosts.insert(ticket_id.clone(), Arc::new(Mutex::new(host)));
             self.active_sessions.insert(ticket_id, session);
         }
 
@@ -135,6 +156,7 @@ impl<S: Store + Send + Sync + 'static, C: SessionConsentHandler + 'static> Sessi
     }
 
     pub async fn terminate_session(&self, ticket_id: &[u8]) -> Result<(), SessionError> {
+        self.hosts.remove(ticket_id);
         if self.active_sessions.remove(ticket_id).is_none() {
             return Err(SessionError::NotFound);
         }
@@ -161,4 +183,70 @@ impl<S: Store + Send + Sync + 'static, C: SessionConsentHandler + 'static> Sessi
     pub fn active_session_count(&self) -> usize {
         self.active_sessions.len()
     }
+
+    /// Whether `ticket_id` belongs to a session started by this manager.
+    pub fn is_active(&self, ticket_id: &[u8]) -> bool {
+        self.active_sessions.contains_key(ticket_id)
+    }
+
+    /// The ticket this manager issued for `ticket_id`, if its session is
+    /// still active. This, not whatever a client presents, is authoritative
+    /// for the session's permissions and binding.
+    pub fn active_ticket(&self, ticket_id: &[u8]) -> Option<SessionTicketV1> {
+        self.active_sessions.get(ticket_id).map(|session| session.ticket.clone())
+    }
+
+    /// Record control-channel activity on a session.
+    pub fn touch(&self, ticket_id: &[u8]) {
+        if let Some(mut session) = self.active_sessions.get_mut(ticket_id) {
+            session.last_activity = SystemTime::now();
+        }
+    }
+
+    /// Note a control message from a session's controller. Input and pings
+    /// restart its idle timer; returns the pong to send back for a ping.
+    pub async fn handle_keepalive(&self, ticket_id: &[u8], msg: &ControlMsgV1) -> Option<ControlMsgV1> {
+        let host = self.host(ticket_id)?;
+        let pong = host.lock().await.handle_keepalive(msg);
+        pong
+    }
+
+    /// When the session will be closed for inactivity, if it is active and
+    /// the policy sets an idle timeout.
+    pub async fn idle_deadline(&self, ticket_id: &[u8]) -> Option<Instant> {
+        let host = self.host(ticket_id)?;
+        let deadline = host.lock().await.idle_deadline();
+        deadline
+    }
+
+    /// End the session if it has been idle past the policy timeout.
+    ///
+    /// Returns the close message to send the controller; the session is no
+    /// longer active afterwards.
+    pub async fn check_idle(&self, ticket_id: &[u8]) -> Result<Option<SessionCloseV1>, SessionError> {
+        let Some(host) = self.host(ticket_id) else {
+            return Ok(None);
+        };
+        let close = host.lock().await.check_idle().await?;
+        if close.is_some() {
+            info!("Session {} idle, closing", hex::encode(ticket_id));
+            let _ = self.terminate_session(ticket_id).await;
+        }
+        Ok(close)
+    }
+
+    fn host(&self, ticket_id: &[u8]) -> Option<SharedHost<S, C>> {
+        self.hosts.get(ticket_id).map(|host| host.clone())
+    }
+
+    /// Drop every tracked session, e.g. on shutdown.
+    pub fn terminate_all(&self) -> usize {
+        let count = self.active_sessions.len();
+        self.hosts.clear();
+        self.active_sessions.clear();
+        if count > 0 {
+            info!("Terminated {} active session(s)", count);
+        }
+        count
+    }
 }
@@ -0,0 +1,370 @@
+//! Headless frame throughput benchmark
+//!
+//! Receives frames from a live session, or from a local synthetic source,
+//! without opening a window and measures:
+//! - Received frames per second and megapixels per second
+//! - Time spent in BGRA→RGBA conversion (the loop `zrc-viewer` runs per redraw),
+//!   reported as percentiles
+//!
+//! The report is serializable so CI can assert a regression threshold on it.
+//!
+//! Requirements: 6.1, 6.7
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tokio::sync::mpsc;
+
+use zrc_core::quic_mux::{controller_recv_frames_with_stats, FramePacketV1};
+use zrc_crypto::session_crypto::SessionCryptoV1;
+
+use crate::frames::{bgra_to_rgba, PACKET_FORMAT_BGRA};
+
+/// Frames buffered between the QUIC receive task and the benchmark loop
+const FRAME_QUEUE_DEPTH: usize = 64;
+
+/// Parse a duration such as `10s`, `500ms`, `2m` or a bare number of seconds
+pub fn parse_duration(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let amount: f64 = number
+        .parse()
+        .map_err(|_| format!("Invalid duration '{value}'"))?;
+    let secs = match unit {
+        "" | "s" => amount,
+        "ms" => amount / 1000.0,
+        "m" => amount * 60.0,
+        _ => return Err(format!("Unknown duration unit '{unit}' (use ms, s or m)")),
+    };
+    if secs <= 0.0 || !secs.is_finite() {
+        return Err(format!("Duration must be positive: '{value}'"));
+    }
+    Ok(Duration::from_secs_f64(secs))
+}
+
+/// Parse a frame size such as `1920x1080`
+pub fn parse_resolution(value: &str) -> Result<(u32, u32), String> {
+    let (width, height) = value
+        .trim()
+        .split_once(['x', 'X'])
+        .ok_or_else(|| format!("Invalid resolution '{value}' (use WIDTHxHEIGHT)"))?;
+    let parse = |n: &str| n.trim().parse::<u32>().ok().filter(|n| *n > 0);
+    match (parse(width), parse(height)) {
+        (Some(width), Some(height)) => Ok((width, height)),
+        _ => Err(format!("Invalid resolution '{value}' (use WIDTHxHEIGHT)")),
+    }
+}
+
+/// Conversion time percentiles in microseconds
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct LatencyPercentiles {
+    pub samples: usize,
+    pub mean_us: f64,
+    pub p50_us: f64,
+    pub p90_us: f64,
+    pub p99_us: f64,
+    pub max_us: f64,
+}
+
+impl LatencyPercentiles {
+    /// Compute nearest-rank percentiles over the samples
+    pub fn from_samples(samples: &[Duration]) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+        let mut us: Vec<f64> = samples.iter().map(|d| d.as_secs_f64() * 1e6).collect();
+        us.sort_by(f64::total_cmp);
+        let rank = |p: f64| {
+            let idx = ((p / 100.0) * us.len() as f64).ceil() as usize;
+            us[idx.clamp(1, us.len()) - 1]
+        };
+        Self {
+            samples: us.len(),
+            mean_us: us.iter().sum::<f64>() / us.len() as f64,
+            p50_us: rank(50.0),
+            p90_us: rank(90.0),
+            p99_us: rank(99.0),
+            max_us: us[us.len() - 1],
+        }
+    }
+}
+
+/// Result of a frame benchmark run
+#[derive(Debug, Clone, Serialize)]
+pub struct FrameBenchReport {
+    /// Wall-clock time spent receiving
+    pub duration_secs: f64,
+    /// Frames received
+    pub frames: u64,
+    /// Frames discarded because the benchmark loop fell behind
+    pub frames_dropped: u64,
+    /// Pixel payload bytes received
+    pub bytes: u64,
+    /// Received frames per second
+    pub fps: f64,
+    /// Received megapixels per second
+    pub megapixels_per_sec: f64,
+    /// BGRA→RGBA conversion time per frame
+    pub convert: LatencyPercentiles,
+    /// Regression threshold the run was checked against, if any
+    pub min_fps: Option<f64>,
+    /// Whether `fps` met `min_fps`
+    pub passed: bool,
+    /// Encrypted bytes received on the Frames stream (live sessions only)
+    pub wire_bytes: Option<u64>,
+    /// QUIC round-trip time at the end of the run (live sessions only)
+    pub rtt_ms: Option<f64>,
+}
+
+/// Accumulates per-frame measurements
+pub struct FrameBench {
+    frames: u64,
+    bytes: u64,
+    pixels: u64,
+    convert_times: Vec<Duration>,
+    scratch: Vec<u8>,
+}
+
+impl FrameBench {
+    /// Create an empty benchmark
+    pub fn new() -> Self {
+        Self {
+            frames: 0,
+            bytes: 0,
+            pixels: 0,
+            convert_times: Vec::new(),
+            scratch: Vec::new(),
+        }
+    }
+
+    /// Record a received frame, timing its BGRA→RGBA conversion
+    ///
+    /// Frames in other pixel formats are counted but not converted.
+    pub fn record(&mut self, pkt: &FramePacketV1) {
+        self.frames += 1;
+        self.bytes += pkt.pixels.len() as u64;
+        self.pixels += u64::from(pkt.width) * u64::from(pkt.height);
+
+        if pkt.format != PACKET_FORMAT_BGRA {
+            return;
+        }
+        self.scratch.resize(pkt.pixels.len(), 0);
+        let start = Instant::now();
+        bgra_to_rgba(&pkt.pixels, &mut self.scratch);
+        self.convert_times.push(start.elapsed());
+    }
+
+    /// Build the report for a run that lasted `elapsed`
+    pub fn report(&self, elapsed: Duration, min_fps: Option<f64>) -> FrameBenchReport {
+        let secs = elapsed.as_secs_f64().max(f64::EPSILON);
+        let fps = self.frames as f64 / secs;
+        FrameBenchReport {
+            duration_secs: elapsed.as_secs_f64(),
+            frames: self.frames,
+            frames_dropped: 0,
+            bytes: self.bytes,
+            fps,
+            megapixels_per_sec: self.pixels as f64 / 1e6 / secs,
+            convert: LatencyPercentiles::from_samples(&self.convert_times),
+            min_fps,
+            passed: !matches!(min_fps, Some(min) if fps < min),
+            wire_bytes: None,
+            rtt_ms: None,
+        }
+    }
+}
+
+impl Default for FrameBench {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Consume frames from `rx` for `duration` or until the sender goes away
+pub async fn run_frame_bench(
+    rx: &mut mpsc::Receiver<FramePacketV1>,
+    duration: Duration,
+    min_fps: Option<f64>,
+) -> FrameBenchReport {
+    let mut bench = FrameBench::new();
+    let started = Instant::now();
+    let deadline = tokio::time::Instant::now() + duration;
+
+    while let Ok(Some(pkt)) = tokio::time::timeout_at(deadline, rx.recv()).await {
+        bench.record(&pkt);
+    }
+
+    bench.report(started.elapsed(), min_fps)
+}
+
+/// Benchmark the local pipeline against a synthetic BGRA frame source
+///
+/// Frames are generated as fast as the benchmark loop takes them, so the
+/// result is the ceiling the receive side can reach without a network.
+pub async fn bench_synthetic_frames(
+    width: u32,
+    height: u32,
+    duration: Duration,
+    min_fps: Option<f64>,
+) -> FrameBenchReport {
+    let (tx, mut rx) = mpsc::channel(FRAME_QUEUE_DEPTH);
+    let frame = FramePacketV1 {
+        width,
+        height,
+        stride: width * 4,
+        format: PACKET_FORMAT_BGRA,
+        pixels: vec![0x80; width as usize * height as usize * 4],
+    };
+    let source = tokio::spawn(async move {
+        while tx.send(frame.clone()).await.is_ok() {}
+    });
+
+    let report = run_frame_bench(&mut rx, duration, min_fps).await;
+
+    drop(rx);
+    let _ = source.await;
+    report
+}
+
+/// Benchmark the frame stream of a connected QUIC session
+///
+/// The receive task is aborted and awaited, and the connection closed, before
+/// returning, so nothing outlives the benchmark.
+pub async fn bench_quic_frames(
+    conn: quinn::Connection,
+    crypto: SessionCryptoV1,
+    duration: Duration,
+    min_fps: Option<f64>,
+) -> FrameBenchReport {
+    let (tx, mut rx) = mpsc::channel(FRAME_QUEUE_DEPTH);
+    let dropped = Arc::new(AtomicU64::new(0));
+
+    let recv_conn = conn.clone();
+    let recv_dropped = Arc::clone(&dropped);
+    let (stats_tx, stats_rx) = tokio::sync::oneshot::channel();
+    let recv_task = tokio::spawn(async move {
+        let (stats, recv) = controller_recv_frames_with_stats(&recv_conn, &crypto, move |pkt| {
+            if tx.try_send(pkt).is_err() {
+                recv_dropped.fetch_add(1, Ordering::Relaxed);
+            }
+        });
+        let _ = stats_tx.send(stats);
+        recv.await
+    });
+    let stats = stats_rx.await.ok();
+
+    let mut report = run_frame_bench(&mut rx, duration, min_fps).await;
+
+    recv_task.abort();
+    if let Ok(Err(e)) = recv_task.await {
+        tracing::debug!("Frame receive task ended: {e}");
+    }
+    conn.close(0u32.into(), b"bench complete");
+
+    report.frames_dropped = dropped.load(Ordering::Relaxed);
+    if let Some(stats) = stats {
+        let stats = stats.read().unwrap_or_else(|e| e.into_inner()).clone();
+        report.wire_bytes = Some(stats.bytes);
+        report.rtt_ms = stats.rtt.map(|rtt| rtt.as_secs_f64() * 1e3);
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bgra_packet(width: u32, height: u32) -> FramePacketV1 {
+        FramePacketV1 {
+            width,
+            height,
+            stride: width * 4,
+            format: PACKET_FORMAT_BGRA,
+            pixels: vec![0; (width * height * 4) as usize],
+        }
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("10s").unwrap(), Duration::from_secs(10));
+        assert_eq!(parse_duration("250ms").unwrap(), Duration::from_millis(250));
+        assert_eq!(parse_duration("2m").unwrap(), Duration::from_secs(120));
+        assert_eq!(parse_duration("1.5").unwrap(), Duration::from_millis(1500));
+        assert!(parse_duration("0s").is_err());
+        assert!(parse_duration("10h").is_err());
+        assert!(parse_duration("fast").is_err());
+    }
+
+    #[test]
+    fn test_parse_resolution() {
+        assert_eq!(parse_resolution("1920x1080").unwrap(), (1920, 1080));
+        assert_eq!(parse_resolution("64X48").unwrap(), (64, 48));
+        assert!(parse_resolution("0x10").is_err());
+        assert!(parse_resolution("1920").is_err());
+        assert!(parse_resolution("wide").is_err());
+    }
+
+    #[test]
+    fn test_latency_percentiles() {
+        let samples: Vec<Duration> = (1..=100).map(Duration::from_micros).collect();
+        let p = LatencyPercentiles::from_samples(&samples);
+        assert_eq!(p.samples, 100);
+        assert!((p.p50_us - 50.0).abs() < 1e-6);
+        assert!((p.p90_us - 90.0).abs() < 1e-6);
+        assert!((p.p99_us - 99.0).abs() < 1e-6);
+        assert!((p.max_us - 100.0).abs() < 1e-6);
+        assert!((p.mean_us - 50.5).abs() < 1e-6);
+
+        assert_eq!(LatencyPercentiles::from_samples(&[]), LatencyPercentiles::default());
+    }
+
+    #[test]
+    fn test_frame_bench_report() {
+        let mut bench = FrameBench::new();
+        for _ in 0..10 {
+            bench.record(&bgra_packet(1000, 100));
+        }
+        // Non-BGRA frames are counted but not converted
+        let mut other = bgra_packet(1000, 100);
+        other.format = 2;
+        bench.record(&other);
+
+        let report = bench.report(Duration::from_secs(2), Some(5.0));
+        assert_eq!(report.frames, 11);
+        assert_eq!(report.bytes, 11 * 400_000);
+        assert!((report.fps - 5.5).abs() < 1e-9);
+        assert!((report.megapixels_per_sec - 0.55).abs() < 1e-9);
+        assert_eq!(report.convert.samples, 10);
+        assert!(report.passed);
+
+        assert!(!bench.report(Duration::from_secs(2), Some(6.0)).passed);
+        assert!(bench.report(Duration::from_secs(2), None).passed);
+    }
+
+    #[tokio::test]
+    async fn test_run_frame_bench_stops_when_stream_closes() {
+        let (tx, mut rx) = mpsc::channel(8);
+        for _ in 0..3 {
+            tx.send(bgra_packet(4, 4)).await.unwrap();
+        }
+        drop(tx);
+
+        let started = Instant::now();
+        let report = run_frame_bench(&mut rx, Duration::from_secs(30), None).await;
+        assert_eq!(report.frames, 3);
+        assert!(started.elapsed() < Duration::from_secs(30));
+    }
+
+    #[tokio::test]
+    async fn test_run_frame_bench_stops_at_deadline() {
+        let (_tx, mut rx) = mpsc::channel::<FramePacketV1>(8);
+        let report = run_frame_bench(&mut rx, Duration::from_millis(20), None).await;
+        assert_eq!(report.frames, 0);
+        assert!(report.duration_secs >= 0.02);
+    }
+}
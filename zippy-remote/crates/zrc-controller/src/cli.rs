@@ -32,6 +32,16 @@ pub struct Cli {
     #[arg(long, global = true)]
     pub config: Option<PathBuf>,
 
+    /// Named config profile to apply (from `[profiles.<name>]`)
+    /// Requirements: 10.9
+    #[arg(long, global = true)]
+    pub profile: Option<String>,
+
+    /// Override any config value, e.g. `--set logging.level=trace` (repeatable)
+    /// Requirements: 10.5
+    #[arg(long = "set", global = true, value_name = "KEY=VALUE", visible_alias = "config-override")]
+    pub set: Vec<crate::config::ConfigOverride>,
+
     /// Transport preference: auto, mesh, rendezvous, direct, relay
     /// Requirements: 8.1, 8.2
     #[arg(long, global = true)]
@@ -51,9 +61,54 @@ pub struct Cli {
     /// Requirements: 8.6
     #[arg(long = "mesh-node", global = true)]
     pub mesh_nodes: Vec<String>,
+
+    /// Abort the command after this many seconds (exit code 3)
+    #[arg(long, global = true, value_parser = clap::value_parser!(u64).range(1..))]
+    pub timeout: Option<u64>,
+}
+
+/// `--set` / `--config-override` values in `args`, up to a `--` separator
+fn raw_settings(args: &[std::ffi::OsString]) -> Result<Vec<crate::config::ConfigOverride>, clap::Error> {
+    let mut values = Vec::new();
+    let mut tokens = args.iter().skip(1).filter_map(|arg| arg.to_str());
+    while let Some(token) = tokens.next() {
+        if token == "--" {
+            break;
+        }
+        let value = match token {
+            "--set" | "--config-override" => tokens.next(),
+            _ => token
+                .strip_prefix("--set=")
+                .or_else(|| token.strip_prefix("--config-override=")),
+        };
+        if let Some(value) = value {
+            values.push(
+                value
+                    .parse()
+                    .map_err(|e: String| clap::Error::raw(clap::error::ErrorKind::ValueValidation, e))?,
+            );
+        }
+    }
+    Ok(values)
 }
 
 impl Cli {
+    /// Parse command-line arguments, keeping every `--set` override
+    ///
+    /// Clap replaces the values of a global argument given before the
+    /// subcommand with those given after it, so the overrides are collected
+    /// again from the raw arguments, in command-line order.
+    pub fn try_parse_args<I, T>(args: I) -> Result<Self, clap::Error>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<std::ffi::OsString>,
+    {
+        let args: Vec<std::ffi::OsString> = args.into_iter().map(Into::into).collect();
+        let mut cli = Self::try_parse_from(&args)?;
+        cli.set = raw_settings(&args)?;
+        Ok(cli)
+    }
+
     /// Execute the CLI command
     pub async fn execute(self) -> anyhow::Result<ExitCode> {
         // Load default config for backward compatibility
@@ -72,14 +127,38 @@ impl Cli {
             mesh_nodes: self.mesh_nodes.clone(),
         };
 
-        match self.command {
-            Commands::Pair(args) => args.execute(&self.output, self.verbose, &transport_opts).await,
-            Commands::Session(args) => args.execute(&self.output, self.verbose, &transport_opts).await,
-            Commands::Input(args) => args.execute(&self.output, self.verbose).await,
-            Commands::Pairings(args) => args.execute(&self.output, self.verbose).await,
-            Commands::Identity(args) => args.execute(&self.output, self.verbose).await,
-            Commands::Frames(args) => args.execute(&self.output, self.verbose).await,
-            Commands::Debug(args) => args.execute(&self.output, self.verbose, &transport_opts).await,
+        let deadline = self.timeout.map(std::time::Duration::from_secs);
+        let effects = crate::SideEffects::new();
+        let output = self.output;
+        let verbose = self.verbose;
+        let command = self.command.name();
+
+        let work = async {
+            match self.command {
+                Commands::Pair(args) => args.execute(&output, verbose, &config, &transport_opts, &effects).await,
+                Commands::Session(args) => args.execute(&output, verbose, &config, &transport_opts, &effects).await,
+                Commands::Input(args) => args.execute(&output, verbose).await,
+                Commands::Pairings(args) => args.execute(&output, verbose, &config).await,
+                Commands::Identity(args) => args.execute(&output, verbose, &config).await,
+                Commands::Frames(args) => args.execute(&output, verbose).await,
+                Commands::Bench(args) => args.execute(&output, verbose).await,
+                Commands::Debug(args) => args.execute(&output, verbose, &config, &transport_opts).await,
+            }
+        };
+
+        let formatter = crate::output::OutputFormatter::new(output, verbose);
+        match crate::run_with_deadline(deadline, &effects, work).await {
+            Ok(ExitCode::Timeout) if deadline.is_some() && output == OutputFormat::Json => {
+                formatter.report_failure(
+                    "Operation timed out",
+                    command,
+                    ExitCode::Timeout,
+                    crate::errors::TIMEOUT_CODE,
+                );
+                Ok(ExitCode::Timeout)
+            }
+            Ok(code) => Ok(code),
+            Err(e) => Ok(formatter.report_error(&e, command)),
         }
     }
 }
@@ -164,10 +243,28 @@ pub enum Commands {
     Identity(IdentityArgs),
     /// Receive and display frames
     Frames(FramesArgs),
+    /// Benchmark session throughput
+    Bench(BenchArgs),
     /// Debug and diagnostic tools
     Debug(DebugArgs),
 }
 
+impl Commands {
+    /// Top-level command name, as reported in JSON responses
+    pub fn name(&self) -> &'static str {
+        match self {
+            Commands::Pair(_) => "pair",
+            Commands::Session(_) => "session",
+            Commands::Input(_) => "input",
+            Commands::Pairings(_) => "pairings",
+            Commands::Identity(_) => "identity",
+            Commands::Frames(_) => "frames",
+            Commands::Bench(_) => "bench",
+            Commands::Debug(_) => "debug",
+        }
+    }
+}
+
 /// Arguments for the pair command
 #[derive(Parser, Debug)]
 pub struct PairArgs {
@@ -183,27 +280,44 @@ pub struct PairArgs {
     #[arg(long)]
     pub permissions: Option<String>,
 
-    /// Dry run (validate only, don't store)
+    /// Dry run: validate the invite and preview the pair request without sending it
     #[arg(long)]
     pub dry_run: bool,
 
+    /// Invite secret (64 hex chars), checked against the invite's secret hash
+    #[arg(long)]
+    pub secret: Option<String>,
+
+    /// Pair with each invite in a file, one `<base64-invite> [secret-hex]` per line
+    #[arg(long, conflicts_with_all = ["invite", "device", "dry_run"])]
+    pub batch: Option<PathBuf>,
+
+    /// Stop a --batch run at the first failed invite
+    #[arg(long, requires = "batch")]
+    pub fail_fast: bool,
+
     /// Transport preference
     #[arg(long, default_value = "auto")]
     pub transport: String,
 }
 
 impl PairArgs {
-    pub async fn execute(self, output: &OutputFormat, verbose: bool, transport_opts: &TransportOptions) -> anyhow::Result<ExitCode> {
-        use crate::config::Config;
+    pub async fn execute(
+        self,
+        output: &OutputFormat,
+        verbose: bool,
+        config: &crate::config::Config,
+        transport_opts: &TransportOptions,
+        effects: &crate::SideEffects,
+    ) -> anyhow::Result<ExitCode> {
         use crate::identity::IdentityManager;
         use crate::output::OutputFormatter;
-        use crate::pairing::{InviteSource, PairingClient, TransportClient, TransportPreference};
+        use crate::pairing::{InviteSource, PairingClient, PairingError, TransportClient, TransportPreference};
         use std::path::PathBuf;
 
         let formatter = OutputFormatter::new(*output, verbose);
         
-        // Load config and identity
-        let config = Config::load_default().unwrap_or_default();
+        // Load identity from the resolved config
         let identity = IdentityManager::init(&config.identity).await?;
         let identity = std::sync::Arc::new(identity);
 
@@ -217,8 +331,17 @@ impl PairArgs {
             resolved.mesh_nodes.clone(),
         );
 
+        // Record SAS mismatches and other security events
+        let audit = match crate::config::Config::audit_log_path() {
+            Some(path) => Some(std::sync::Arc::new(identity.audit_logger(path)?)),
+            None => None,
+        };
+
         // Create pairing client
         let mut client = PairingClient::with_config(identity, transport, None);
+        if let Some(audit) = audit {
+            client.set_audit_logger(audit);
+        }
 
         // Set transport preference from resolved config or command-specific override
         // Command-specific --transport flag takes precedence over global --transport
@@ -244,6 +367,13 @@ impl PairArgs {
             }
         }
 
+        if let Some(batch) = self.batch {
+            let permissions = parse_permission_list(self.permissions.as_deref());
+            let mask = PairingClient::permissions_to_mask(&permissions);
+            return Self::execute_batch(&mut client, &batch, mask, self.fail_fast, &formatter, effects)
+                .await;
+        }
+
         // Handle invite import
         if let Some(invite_str) = self.invite {
             formatter.progress("Importing invite...");
@@ -280,7 +410,33 @@ impl PairArgs {
                     }
 
                     if self.dry_run {
-                        return Ok(ExitCode::Success);
+                        let Some(secret_hex) = self.secret else {
+                            eprintln!("Note: Pass --secret to verify the invite secret and preview the pair request.");
+                            return Ok(ExitCode::Success);
+                        };
+                        let secret: [u8; 32] = match hex::decode(secret_hex.trim()).ok().and_then(|b| b.try_into().ok()) {
+                            Some(secret) => secret,
+                            None => {
+                                return Ok(formatter.error("Invalid --secret: expected 32 bytes as 64 hex characters", ExitCode::InvalidInput));
+                            }
+                        };
+
+                        let permissions = parse_permission_list(self.permissions.as_deref());
+                        let mask = PairingClient::permissions_to_mask(&permissions);
+
+                        return match client.preview_pair_request(&secret, mask) {
+                            Ok(request) => {
+                                formatter.success("Invite secret verified; pair request not sent (dry run)");
+                                println!("{}", formatter.format_pair_preview(&parsed, &request));
+                                Ok(ExitCode::Success)
+                            }
+                            Err(PairingError::InvalidProof) => {
+                                Ok(formatter.error("Invite secret does not match the invite's secret hash", ExitCode::InvalidInput))
+                            }
+                            Err(e) => {
+                                Ok(formatter.error(&format!("Failed to build pair request: {e}"), ExitCode::InvalidInput))
+                            }
+                        };
                     }
 
                     // Store invite for subsequent pairing
@@ -288,8 +444,7 @@ impl PairArgs {
                     Ok(ExitCode::Success)
                 }
                 Err(e) => {
-                    formatter.error(&format!("Failed to import invite: {e}"));
-                    Ok(ExitCode::InvalidInput)
+                    Ok(formatter.error(&format!("Failed to import invite: {e}"), ExitCode::InvalidInput))
                 }
             }
         } else if let Some(device_id) = self.device {
@@ -322,12 +477,46 @@ impl PairArgs {
         invite_secret: &[u8; 32],
         permissions: u32,
         formatter: &crate::output::OutputFormatter,
+        effects: &crate::SideEffects,
     ) -> anyhow::Result<ExitCode> {
+        match Self::run_pairing_flow(client, invite_secret, permissions, formatter, effects).await? {
+            Some(result) => {
+                formatter.success(&format!(
+                    "Pairing complete! Device: {}, Permissions: {:?}",
+                    result.device_id,
+                    result.permissions_granted
+                ));
+                Ok(ExitCode::Success)
+            }
+            None => {
+                Ok(formatter.error("SAS verification rejected - pairing cancelled", ExitCode::AuthenticationFailed))
+            }
+        }
+    }
+
+    /// Send the pair request, verify the SAS with the operator and store the pairing
+    ///
+    /// Returns `None` when the operator cancels or the SAS never matches.
+    async fn run_pairing_flow(
+        client: &mut crate::pairing::PairingClient,
+        invite_secret: &[u8; 32],
+        permissions: u32,
+        formatter: &crate::output::OutputFormatter,
+        effects: &crate::SideEffects,
+    ) -> anyhow::Result<Option<crate::pairing::PairingResult>> {
         use std::io::{self, Write};
+        use zrc_security::sas::SasOutcome;
 
         // Generate and send pair request
         formatter.progress("Sending pair request...");
-        let _request = client.send_pair_request(invite_secret, permissions).await?;
+        let send_result = client.send_pair_request(invite_secret, permissions).await;
+        if formatter.is_verbose() {
+            if let Some(ladder) = client.last_ladder() {
+                eprintln!("{}", formatter.format_ladder(ladder));
+            }
+        }
+        let _request = send_result?;
+        effects.record("Pair request sent");
         formatter.success("Pair request sent");
 
         // Wait for receipt
@@ -337,44 +526,195 @@ impl PairArgs {
 
         // Handle receipt and get SAS
         let sas = client.handle_receipt(receipt)?;
+        let sas_words = client.get_sas_words().unwrap_or_default().to_string();
         
         // Display SAS for verification
         println!("\n╔════════════════════════════════════════╗");
         println!("║     SAS Verification Code              ║");
         println!("║                                        ║");
         println!("║           {:^6}                       ║", sas);
+        println!("║  {:^36}  ║", sas_words);
         println!("║                                        ║");
         println!("║  Verify this code matches the device   ║");
         println!("╚════════════════════════════════════════╝\n");
 
-        // Prompt for confirmation
-        eprint!("Does the code match? [y/N] ");
-        io::stdout().flush()?;
+        // The operator types the code shown on the device; we compare it
+        loop {
+            eprint!("Enter the code shown on the device (blank to cancel): ");
+            io::stdout().flush()?;
 
-        let mut input = String::new();
-        io::stdin().read_line(&mut input)?;
+            let mut input = String::new();
+            io::stdin().read_line(&mut input)?;
+            if input.trim().is_empty() {
+                client.reject_sas()?;
+                return Ok(None);
+            }
 
-        if input.trim().eq_ignore_ascii_case("y") {
-            // Confirm SAS and complete pairing
-            formatter.progress("Confirming pairing...");
-            let result = client.confirm_sas().await?;
-            
-            formatter.success(&format!(
-                "Pairing complete! Device: {}, Permissions: {:?}",
-                result.device_id,
-                result.permissions_granted
+            match client.verify_sas(&input)? {
+                SasOutcome::Match => {
+                    formatter.progress("Confirming pairing...");
+                    let result = client.confirm_sas().await?;
+                    return Ok(Some(result));
+                }
+                SasOutcome::Mismatch { attempts_remaining } => {
+                    // Not a failure yet; the operator is asked again
+                    formatter.warning(&format!(
+                        "Code does not match ({} attempt(s) left)",
+                        attempts_remaining
+                    ));
+                }
+                SasOutcome::Exhausted => return Ok(None),
+            }
+        }
+    }
+
+    /// Pair with every invite in a batch file, one after another
+    ///
+    /// Individual failures are recorded and the batch continues unless
+    /// `fail_fast` is set. Returns `Success` only if every entry paired.
+    /// Requirements: 2.1-2.8
+    async fn execute_batch(
+        client: &mut crate::pairing::PairingClient,
+        path: &std::path::Path,
+        permissions: u32,
+        fail_fast: bool,
+        formatter: &crate::output::OutputFormatter,
+        effects: &crate::SideEffects,
+    ) -> anyhow::Result<ExitCode> {
+        use crate::output::PairBatchSummary;
+
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) => {
+                return Ok(formatter.error(&format!("Failed to read batch file {}: {}", path.display(), e), ExitCode::InvalidInput));
+            }
+        };
+        let entries = parse_pair_batch(&contents);
+        if entries.is_empty() {
+            return Ok(formatter.error(&format!("No invites found in {}", path.display()), ExitCode::InvalidInput));
+        }
+
+        let mut results = Vec::with_capacity(entries.len());
+        for (i, entry) in entries.iter().enumerate() {
+            formatter.progress(&format!(
+                "[{}/{}] Pairing invite from line {}...",
+                i + 1,
+                entries.len(),
+                entry.line
             ));
-            
+
+            // Each entry starts from a clean client state
+            client.reset();
+            let outcome = Self::pair_batch_entry(client, entry, permissions, formatter, effects).await;
+            if !outcome.paired {
+                formatter.warning(&format!(
+                    "Line {}: {}",
+                    entry.line,
+                    outcome.error.as_deref().unwrap_or("pairing failed")
+                ));
+            }
+            let failed = !outcome.paired;
+            results.push(outcome);
+            if failed && fail_fast {
+                break;
+            }
+        }
+        client.reset();
+
+        let summary = PairBatchSummary::new(entries.len(), results);
+        println!("{}", formatter.format_pair_batch(&summary));
+
+        if summary.succeeded == summary.total {
             Ok(ExitCode::Success)
         } else {
-            // Reject SAS
-            client.reject_sas()?;
-            formatter.error("SAS verification rejected - pairing cancelled");
-            Ok(ExitCode::AuthenticationFailed)
+            Ok(ExitCode::GeneralError)
+        }
+    }
+
+    /// Pair a single batch entry, capturing any failure as the outcome
+    async fn pair_batch_entry(
+        client: &mut crate::pairing::PairingClient,
+        entry: &PairBatchLine,
+        permissions: u32,
+        formatter: &crate::output::OutputFormatter,
+        effects: &crate::SideEffects,
+    ) -> crate::output::PairBatchOutcome {
+        use crate::output::PairBatchOutcome;
+        use crate::pairing::InviteSource;
+
+        let source = InviteSource::Base64(entry.invite.clone());
+        let Some(secret_hex) = &entry.secret else {
+            let device_id = client.import_invite(source).ok().map(|p| p.device_id);
+            return PairBatchOutcome::failed(entry.line, device_id, "No invite secret on this line");
+        };
+        let secret: [u8; 32] = match hex::decode(secret_hex).ok().and_then(|b| b.try_into().ok()) {
+            Some(secret) => secret,
+            None => {
+                return PairBatchOutcome::failed(
+                    entry.line,
+                    None,
+                    "Invalid secret: expected 32 bytes as 64 hex characters",
+                )
+            }
+        };
+
+        let parsed = match client.import_invite_with_secret(source, secret) {
+            Ok(parsed) => parsed,
+            Err(e) => return PairBatchOutcome::failed(entry.line, None, e.to_string()),
+        };
+
+        match Self::run_pairing_flow(client, &secret, permissions, formatter, effects).await {
+            Ok(Some(result)) => PairBatchOutcome::paired(entry.line, &result),
+            Ok(None) => {
+                PairBatchOutcome::failed(entry.line, Some(parsed.device_id), "SAS verification rejected")
+            }
+            Err(e) => PairBatchOutcome::failed(entry.line, Some(parsed.device_id), format!("{e:#}")),
         }
     }
 }
 
+/// Split a comma-separated permission list, defaulting to `view`
+fn parse_permission_list(list: Option<&str>) -> Vec<String> {
+    list.unwrap_or("view")
+        .split(',')
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty())
+        .collect()
+}
+
+/// One invite line from a `pair --batch` file
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PairBatchLine {
+    /// 1-based line number in the file
+    line: usize,
+    /// Base64-encoded invite
+    invite: String,
+    /// Invite secret (hex), if given after the invite
+    secret: Option<String>,
+}
+
+/// Parse a batch file: one `<base64-invite> [secret-hex]` per line
+///
+/// Blank lines and lines starting with `#` are skipped.
+fn parse_pair_batch(contents: &str) -> Vec<PairBatchLine> {
+    contents
+        .lines()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let line_text = line.trim();
+            if line_text.is_empty() || line_text.starts_with('#') {
+                return None;
+            }
+            let mut fields = line_text.split_whitespace();
+            Some(PairBatchLine {
+                line: i + 1,
+                invite: fields.next()?.to_string(),
+                secret: fields.next().map(str::to_string),
+            })
+        })
+        .collect()
+}
+
 /// Arguments for the session command
 #[derive(Parser, Debug)]
 pub struct SessionArgs {
@@ -383,8 +723,14 @@ pub struct SessionArgs {
 }
 
 impl SessionArgs {
-    pub async fn execute(self, output: &OutputFormat, verbose: bool, transport_opts: &TransportOptions) -> anyhow::Result<ExitCode> {
-        use crate::config::Config;
+    pub async fn execute(
+        self,
+        output: &OutputFormat,
+        verbose: bool,
+        config: &crate::config::Config,
+        transport_opts: &TransportOptions,
+        effects: &crate::SideEffects,
+    ) -> anyhow::Result<ExitCode> {
         use crate::identity::IdentityManager;
         use crate::output::OutputFormatter;
         use crate::pairing::{TransportClient, TransportPreference};
@@ -395,8 +741,7 @@ impl SessionArgs {
 
         match self.action {
             SessionAction::Start { device, capabilities, transport } => {
-                // Load config and identity
-                let config = Config::load_default().unwrap_or_default();
+                // Load identity from the resolved config
                 let identity = IdentityManager::init(&config.identity).await?;
                 let identity = std::sync::Arc::new(identity);
 
@@ -469,8 +814,16 @@ impl SessionArgs {
                         let device_id_bytes = hex::decode(&device)
                             .map_err(|e| anyhow::anyhow!("Invalid device ID: {}", e))?;
                         
-                        match client.send_session_request(&device_id_bytes, &request).await {
+                        let send_result = client.send_session_request(&device_id_bytes, &request).await;
+                        if verbose {
+                            if let Some(ladder) = client.last_ladder() {
+                                eprintln!("{}", formatter.format_ladder(ladder));
+                            }
+                        }
+
+                        match send_result {
                             Ok(()) => {
+                                effects.record(format!("Session request {} sent to device {}", session_id, device));
                                 formatter.success("Session request sent");
                                 formatter.progress("Waiting for device response...");
                                 
@@ -481,37 +834,124 @@ impl SessionArgs {
                                 Ok(ExitCode::Success)
                             }
                             Err(e) => {
-                                formatter.error(&format!("Failed to send session request: {}", e));
-                                Ok(ExitCode::ConnectionFailed)
+                                Ok(formatter.error(&format!("Failed to send session request: {}", e), ExitCode::ConnectionFailed))
                             }
                         }
                     }
                     Err(crate::session::SessionError::NotPaired(id)) => {
-                        formatter.error(&format!("Device {} is not paired", id));
+                        let code = formatter.error(&format!("Device {} is not paired", id), ExitCode::NotPaired);
                         eprintln!("Use 'zrc-controller pair --device {}' to pair first.", id);
-                        Ok(ExitCode::NotPaired)
+                        Ok(code)
                     }
                     Err(crate::session::SessionError::PermissionDenied(msg)) => {
-                        formatter.error(&format!("Permission denied: {}", msg));
-                        Ok(ExitCode::PermissionDenied)
+                        Ok(formatter.error(&format!("Permission denied: {}", msg), ExitCode::PermissionDenied))
                     }
                     Err(e) => {
-                        formatter.error(&format!("Failed to start session: {}", e));
-                        Ok(ExitCode::GeneralError)
+                        Ok(formatter.error(&format!("Failed to start session: {}", e), ExitCode::GeneralError))
                     }
                 }
             }
-            SessionAction::Connect { quic, cert, ticket, relay } => {
-                // TODO: Implement in task 7.3
+            SessionAction::Connect {
+                quic,
+                cert,
+                ticket,
+                relay,
+                device,
+                max_reconnects,
+                reconnect_base_ms,
+                reconnect_max_ms,
+            } => {
+                use crate::session::{
+                    parse_cert_pin, verify_cert_pin as check_cert_pin, wait_until_closed,
+                    QuicConnectParams, ReconnectPolicy,
+                };
+                use std::time::Duration;
+
+                let cert_fingerprint = match parse_cert_pin(&cert) {
+                    Ok(fingerprint) => fingerprint,
+                    Err(e) => {
+                        return Ok(formatter.error(&format!("Invalid certificate fingerprint: {}", e), ExitCode::InvalidInput));
+                    }
+                };
+
+                // The pin comes from the pairing record, not the command line
+                let pinned = match &device {
+                    Some(device) => {
+                        let db_path = config.pairings.db_path.clone()
+                            .or_else(PairingsStore::default_path)
+                            .ok_or_else(|| anyhow::anyhow!("Could not determine pairings database path"))?;
+                        let store = PairingsStore::open(&db_path)?;
+                        let pairing = match store.resolve_prefix(device) {
+                            Ok(pairing) => pairing,
+                            Err(crate::pairing::PairingError::NotPaired(id)) => {
+                                return Ok(formatter.error(&format!("No unique pairing matches: {}", id), ExitCode::NotPaired));
+                            }
+                            Err(e) => return Err(e.into()),
+                        };
+                        match pairing.cert_pin {
+                            Some(pin) => Some(pin),
+                            None => {
+                                let message = format!(
+                                    "No certificate pinned for device {}; run 'zrc-controller pairings pin-cert' first",
+                                    pairing.device_id
+                                );
+                                return Ok(formatter.error(&message, ExitCode::AuthenticationFailed));
+                            }
+                        }
+                    }
+                    None => None,
+                };
+
+                // Reject an unexpected server certificate before connecting
+                if let Some(pinned) = &pinned {
+                    if let Err(e) = check_cert_pin(&cert_fingerprint, pinned) {
+                        return Ok(formatter.error(&e.to_string(), ExitCode::AuthenticationFailed));
+                    }
+                    formatter.success("Server certificate matches pinned SHA-256");
+                }
+
+                let (host, port, ticket) = match parse_connect_target(&quic, &ticket) {
+                    Ok(target) => target,
+                    Err(e) => {
+                        return Ok(formatter.error(&e, ExitCode::InvalidInput));
+                    }
+                };
+
+                let policy = ReconnectPolicy {
+                    base_delay: Duration::from_millis(reconnect_base_ms),
+                    max_delay: Duration::from_millis(reconnect_max_ms),
+                    max_attempts: max_reconnects,
+                };
+                let params = QuicConnectParams {
+                    host,
+                    port,
+                    cert_fingerprint,
+                    ticket,
+                    relay_url: relay,
+                    pinned_cert_sha256: pinned,
+                };
+
+                // Transport drops, on the first dial or mid-session, are retried with
+                // backoff while the ticket is valid; CONNECTION_FAILED is only
+                // reported once attempts are exhausted
                 formatter.progress(&format!("Connecting to {}...", quic));
-                eprintln!("Session connect not yet implemented (task 7.3)");
-                eprintln!("Parameters: quic={}, cert={}, ticket_len={}, relay={:?}", 
-                    quic, cert, ticket.len(), relay);
+                let client = SessionClient::new();
+                client
+                    .run_quic_session(
+                        params,
+                        &policy,
+                        |session| {
+                            formatter.success(&format!("Connected to session {}", session.session_id));
+                            wait_until_closed(session)
+                        },
+                        |event| formatter.reconnecting(event),
+                    )
+                    .await?;
+                formatter.success("Session ended");
                 Ok(ExitCode::Success)
             }
             SessionAction::List => {
-                // Load config and identity
-                let config = Config::load_default().unwrap_or_default();
+                // Load identity from the resolved config
                 let identity = IdentityManager::init(&config.identity).await?;
                 let identity = std::sync::Arc::new(identity);
 
@@ -529,8 +969,7 @@ impl SessionArgs {
                 Ok(ExitCode::Success)
             }
             SessionAction::End { session } => {
-                // Load config and identity
-                let config = Config::load_default().unwrap_or_default();
+                // Load identity from the resolved config
                 let identity = IdentityManager::init(&config.identity).await?;
                 let identity = std::sync::Arc::new(identity);
 
@@ -543,12 +982,10 @@ impl SessionArgs {
                         Ok(ExitCode::Success)
                     }
                     Err(crate::session::SessionError::NotFound(id)) => {
-                        formatter.error(&format!("Session {} not found", id));
-                        Ok(ExitCode::GeneralError)
+                        Ok(formatter.error(&format!("Session {} not found", id), ExitCode::GeneralError))
                     }
                     Err(e) => {
-                        formatter.error(&format!("Failed to end session: {}", e));
-                        Ok(ExitCode::GeneralError)
+                        Ok(formatter.error(&format!("Failed to end session: {}", e), ExitCode::GeneralError))
                     }
                 }
             }
@@ -585,6 +1022,19 @@ pub enum SessionAction {
         /// Relay server URL (optional)
         #[arg(long)]
         relay: Option<String>,
+        /// Paired device whose pinned certificate (`pairings pin-cert`) --cert
+        /// must match; the connect fails with AUTH_FAILED otherwise
+        #[arg(long)]
+        device: Option<String>,
+        /// Reconnect attempts after a dropped connection (0 disables reconnecting)
+        #[arg(long, default_value = "5")]
+        max_reconnects: u32,
+        /// Initial reconnect backoff in milliseconds, doubled per attempt
+        #[arg(long, default_value = "500")]
+        reconnect_base_ms: u64,
+        /// Maximum reconnect backoff in milliseconds
+        #[arg(long, default_value = "30000")]
+        reconnect_max_ms: u64,
     },
     /// List active sessions
     List,
@@ -662,6 +1112,15 @@ impl InputArgs {
                 formatter.progress(&format!("Sending text input: {} chars", string.len()));
                 cmds.text(self.session.as_deref(), &string).await
             }
+            InputAction::Type { text, delay_ms } => {
+                formatter.progress(&format!(
+                    "Typing {} chars ({} ms between chars)",
+                    text.chars().count(),
+                    delay_ms
+                ));
+                cmds.type_text(self.session.as_deref(), &text, std::time::Duration::from_millis(delay_ms))
+                    .await
+            }
             InputAction::Scroll { delta } => {
                 formatter.progress(&format!("Sending scroll delta {}", delta));
                 cmds.scroll(self.session.as_deref(), delta).await
@@ -680,20 +1139,16 @@ impl InputArgs {
                 Ok(ExitCode::Success)
             }
             Err(crate::input::InputError::NoSession) => {
-                formatter.error("No active session. Use --session to specify a session ID.");
-                Ok(ExitCode::InvalidInput)
+                Ok(formatter.error("No active session. Use --session to specify a session ID.", ExitCode::InvalidInput))
             }
             Err(crate::input::InputError::InvalidInput(msg)) => {
-                formatter.error(&format!("Invalid input: {}", msg));
-                Ok(ExitCode::InvalidInput)
+                Ok(formatter.error(&format!("Invalid input: {}", msg), ExitCode::InvalidInput))
             }
             Err(crate::input::InputError::PermissionDenied(msg)) => {
-                formatter.error(&format!("Permission denied: {}", msg));
-                Ok(ExitCode::PermissionDenied)
+                Ok(formatter.error(&format!("Permission denied: {}", msg), ExitCode::PermissionDenied))
             }
             Err(e) => {
-                formatter.error(&format!("Input command failed: {}", e));
-                Ok(ExitCode::GeneralError)
+                Ok(formatter.error(&format!("Input command failed: {}", e), ExitCode::GeneralError))
             }
         }
     }
@@ -732,6 +1187,14 @@ pub enum InputAction {
         #[arg(long)]
         string: String,
     },
+    /// Type a string as individual characters (newlines and tabs become key presses)
+    Type {
+        /// Text to type
+        text: String,
+        /// Delay between characters in milliseconds
+        #[arg(long, default_value_t = 0)]
+        delay_ms: u64,
+    },
     /// Send scroll input
     Scroll {
         /// Scroll delta
@@ -748,17 +1211,20 @@ pub struct PairingsArgs {
 }
 
 impl PairingsArgs {
-    pub async fn execute(self, output: &OutputFormat, verbose: bool) -> anyhow::Result<ExitCode> {
-        use crate::config::Config;
+    pub async fn execute(
+        self,
+        output: &OutputFormat,
+        verbose: bool,
+        config: &crate::config::Config,
+    ) -> anyhow::Result<ExitCode> {
         use crate::output::OutputFormatter;
         use crate::pairings::PairingsStore;
         use std::io::{self, Write};
 
         let formatter = OutputFormatter::new(*output, verbose);
-        let config = Config::load_default().unwrap_or_default();
 
         // Open pairings store
-        let db_path = config.pairings.db_path
+        let db_path = config.pairings.db_path.clone()
             .or_else(PairingsStore::default_path)
             .ok_or_else(|| anyhow::anyhow!("Could not determine pairings database path"))?;
 
@@ -781,8 +1247,7 @@ impl PairingsArgs {
                         Ok(ExitCode::Success)
                     }
                     None => {
-                        formatter.error(&format!("Pairing not found: {}", device_id));
-                        Ok(ExitCode::NotPaired)
+                        Ok(formatter.error(&format!("Pairing not found: {}", device_id), ExitCode::NotPaired))
                     }
                 }
             }
@@ -794,8 +1259,7 @@ impl PairingsArgs {
                 let pairing = match store.get(&device_id)? {
                     Some(p) => p,
                     None => {
-                        formatter.error(&format!("Pairing not found: {}", device_id));
-                        return Ok(ExitCode::NotPaired);
+                        return Ok(formatter.error(&format!("Pairing not found: {}", device_id), ExitCode::NotPaired));
                     }
                 };
 
@@ -820,6 +1284,68 @@ impl PairingsArgs {
                 formatter.success(&format!("Pairing revoked for device: {}", device_id));
                 Ok(ExitCode::Success)
             }
+            PairingsAction::Rename { device_id, name } => {
+                formatter.progress(&format!("Renaming pairing {}...", device_id));
+                match store.rename(&device_id, &name) {
+                    Ok(pairing) => {
+                        formatter.success(&format!(
+                            "Device {} renamed to '{}'",
+                            pairing.device_id, name
+                        ));
+                        Ok(ExitCode::Success)
+                    }
+                    Err(crate::pairing::PairingError::NotPaired(id)) => {
+                        Ok(formatter.error(&format!("No unique pairing matches: {}", id), ExitCode::NotPaired))
+                    }
+                    Err(e) => Err(e.into()),
+                }
+            }
+            PairingsAction::SetNote { device_id, note } => {
+                formatter.progress(&format!("Updating note for {}...", device_id));
+                match store.set_note(&device_id, note.as_deref()) {
+                    Ok(pairing) => {
+                        if note.is_some() {
+                            formatter.success(&format!("Note set for device {}", pairing.device_id));
+                        } else {
+                            formatter.success(&format!("Note cleared for device {}", pairing.device_id));
+                        }
+                        Ok(ExitCode::Success)
+                    }
+                    Err(crate::pairing::PairingError::NotPaired(id)) => {
+                        Ok(formatter.error(&format!("No unique pairing matches: {}", id), ExitCode::NotPaired))
+                    }
+                    Err(e) => Err(e.into()),
+                }
+            }
+            PairingsAction::PinCert { device_id, cert } => {
+                let pin = match cert.as_deref().map(crate::session::parse_cert_pin).transpose() {
+                    Ok(pin) => pin,
+                    Err(e) => {
+                        return Ok(formatter.error(&format!("Invalid certificate pin: {}", e), ExitCode::InvalidInput));
+                    }
+                };
+                formatter.progress(&format!("Updating certificate pin for {}...", device_id));
+                match store.set_cert_pin(&device_id, pin) {
+                    Ok(pairing) => {
+                        match pin {
+                            Some(pin) => formatter.success(&format!(
+                                "Device {} pinned to certificate {}",
+                                pairing.device_id,
+                                hex::encode(pin)
+                            )),
+                            None => formatter.success(&format!(
+                                "Certificate pin cleared for device {}",
+                                pairing.device_id
+                            )),
+                        }
+                        Ok(ExitCode::Success)
+                    }
+                    Err(crate::pairing::PairingError::NotPaired(id)) => {
+                        Ok(formatter.error(&format!("No unique pairing matches: {}", id), ExitCode::NotPaired))
+                    }
+                    Err(e) => Err(e.into()),
+                }
+            }
             PairingsAction::Export { output: output_path } => {
                 // Requirements: 7.5
                 formatter.progress(&format!("Exporting pairings to {}...", output_path.display()));
@@ -832,8 +1358,7 @@ impl PairingsArgs {
             PairingsAction::Import { input: input_path } => {
                 // Requirements: 7.6
                 if !input_path.exists() {
-                    formatter.error(&format!("File not found: {}", input_path.display()));
-                    return Ok(ExitCode::InvalidInput);
+                    return Ok(formatter.error(&format!("File not found: {}", input_path.display()), ExitCode::InvalidInput));
                 }
 
                 formatter.progress(&format!("Importing pairings from {}...", input_path.display()));
@@ -841,6 +1366,50 @@ impl PairingsArgs {
                 formatter.success(&format!("Imported {} pairings from {}", count, input_path.display()));
                 Ok(ExitCode::Success)
             }
+            PairingsAction::ExportQr { device_id, invite, out, terminal } => {
+                use crate::pairing::{render_invite_qr_terminal, write_invite_qr_png, Reinvite};
+
+                let Some(pairing) = store.get(&device_id)? else {
+                    return Ok(formatter.error(&format!("No pairing found for device {}", device_id), ExitCode::NotPaired));
+                };
+
+                let reinvite = match Reinvite::from_pairing(&pairing, &invite) {
+                    Ok(reinvite) => reinvite,
+                    Err(e) => {
+                        return Ok(formatter.error(&e.to_string(), ExitCode::InvalidInput));
+                    }
+                };
+                let invite = reinvite.to_base64();
+
+                if let Some(path) = &out {
+                    write_invite_qr_png(&invite, path)?;
+                    formatter.success(&format!("Wrote QR invite to {}", path.display()));
+                }
+                if terminal {
+                    println!("{}", render_invite_qr_terminal(&invite)?);
+                }
+
+                match output {
+                    OutputFormat::Json => println!(
+                        "{}",
+                        formatter.format_success(
+                            &serde_json::json!({
+                                "device_id": pairing.device_id,
+                                "invite": invite,
+                                "expires_at": reinvite.invite.expires_at,
+                                "out": out,
+                            }),
+                            "pairings export-qr",
+                        )
+                    ),
+                    OutputFormat::Table => {
+                        println!("Invite: {}", invite);
+                        println!("Give the operator the secret from `zrc-agent pair` separately from the QR code.");
+                    }
+                    OutputFormat::Quiet => {}
+                }
+                Ok(ExitCode::Success)
+            }
         }
     }
 }
@@ -863,6 +1432,27 @@ pub enum PairingsAction {
         #[arg(long)]
         force: bool,
     },
+    /// Set the display name of a pairing
+    Rename {
+        /// Device ID or unique hex prefix
+        device_id: String,
+        /// New display name
+        name: String,
+    },
+    /// Set or clear the note attached to a pairing
+    SetNote {
+        /// Device ID or unique hex prefix
+        device_id: String,
+        /// Note text (omit to clear the note)
+        note: Option<String>,
+    },
+    /// Pin or unpin the device's QUIC server certificate
+    PinCert {
+        /// Device ID or unique hex prefix
+        device_id: String,
+        /// Certificate SHA-256 hex or path to the DER file (omit to clear the pin)
+        cert: Option<String>,
+    },
     /// Export pairings to file
     Export {
         /// Output file path
@@ -875,6 +1465,20 @@ pub enum PairingsAction {
         #[arg(long)]
         input: PathBuf,
     },
+    /// Render an invite for a paired device as a QR code (requires feature `qr`)
+    ExportQr {
+        /// Device ID
+        device_id: String,
+        /// Invite printed by `zrc-agent pair` on the device
+        #[arg(long)]
+        invite: String,
+        /// Write the QR code to this PNG file
+        #[arg(long, required_unless_present = "terminal")]
+        out: Option<PathBuf>,
+        /// Print the QR code to the terminal as Unicode blocks
+        #[arg(long)]
+        terminal: bool,
+    },
 }
 
 /// Arguments for the identity command
@@ -889,16 +1493,20 @@ pub struct IdentityArgs {
 }
 
 impl IdentityArgs {
-    pub async fn execute(self, output: &OutputFormat, verbose: bool) -> anyhow::Result<ExitCode> {
-        use crate::config::Config;
-        use crate::identity::IdentityManager;
+    pub async fn execute(
+        self,
+        output: &OutputFormat,
+        verbose: bool,
+        config: &crate::config::Config,
+    ) -> anyhow::Result<ExitCode> {
+        use crate::identity::{IdentityError, IdentityManager};
         use crate::output::OutputFormatter;
         use std::io::{self, Write};
 
         let formatter = OutputFormatter::new(*output, verbose);
 
         // Build identity config, potentially with override
-        let mut config = Config::load_default().unwrap_or_default();
+        let mut config = config.clone();
         if let Some(path) = self.identity_file {
             config.identity.key_path = Some(path);
         }
@@ -911,16 +1519,57 @@ impl IdentityArgs {
                 println!("{}", formatter.format_identity(&info));
                 Ok(ExitCode::Success)
             }
-            IdentityAction::Export { output_file } => {
+            IdentityAction::Export { output_file, out } => {
                 formatter.progress("Loading identity...");
                 let identity = IdentityManager::init(&config.identity).await?;
-                
+
+                if let Some(bundle_path) = out {
+                    let passphrase = read_passphrase(true)?;
+                    formatter.progress(&format!("Encrypting identity to {}...", bundle_path.display()));
+                    match identity.export_encrypted(&bundle_path, &passphrase) {
+                        Ok(()) => {}
+                        Err(IdentityError::InvalidPassphrase(msg)) => {
+                            return Ok(formatter.error(&format!("Invalid passphrase: {}", msg), ExitCode::InvalidInput));
+                        }
+                        Err(e) => return Err(e.into()),
+                    }
+                    formatter.success(&format!(
+                        "Encrypted identity exported to {} (keep this file and passphrase safe)",
+                        bundle_path.display()
+                    ));
+                    return Ok(ExitCode::Success);
+                }
+
+                let output_file = output_file.expect("clap requires --file or --out");
                 formatter.progress(&format!("Exporting to {}...", output_file.display()));
                 identity.export_to_file(&output_file)?;
                 
                 formatter.success(&format!("Identity exported to {}", output_file.display()));
                 Ok(ExitCode::Success)
             }
+            IdentityAction::Import { file, force } => {
+                if IdentityManager::identity_exists(&config.identity) && !force {
+                    return Ok(formatter.error("An identity already exists. Use --force to overwrite it.", ExitCode::InvalidInput));
+                }
+
+                let passphrase = read_passphrase(false)?;
+                formatter.progress(&format!("Importing identity from {}...", file.display()));
+                let identity = match IdentityManager::import_encrypted(&config.identity, &file, &passphrase, force).await {
+                    Ok(identity) => identity,
+                    Err(IdentityError::Decryption) => {
+                        return Ok(formatter.error("Wrong passphrase or corrupted bundle", ExitCode::AuthenticationFailed));
+                    }
+                    Err(IdentityError::AlreadyExists) => {
+                        return Ok(formatter.error("An identity already exists. Use --force to overwrite it.", ExitCode::InvalidInput));
+                    }
+                    Err(e) => return Err(e.into()),
+                };
+
+                let info = identity.display_info();
+                formatter.success(&format!("Identity {} imported", info.operator_id));
+                println!("{}", formatter.format_identity(&info));
+                Ok(ExitCode::Success)
+            }
             IdentityAction::Rotate { force } => {
                 // Warn user about consequences
                 if !force {
@@ -959,16 +1608,63 @@ impl IdentityArgs {
     }
 }
 
+/// Environment variable supplying the identity bundle passphrase non-interactively
+pub const IDENTITY_PASSPHRASE_ENV: &str = "ZRC_IDENTITY_PASSPHRASE";
+
+/// Read the identity bundle passphrase from the environment or stdin
+///
+/// On a terminal the passphrase is read without echo; piped input is read
+/// as a plain line.
+fn read_passphrase(confirm: bool) -> anyhow::Result<zeroize::Zeroizing<String>> {
+    use std::io::{self, IsTerminal, Write};
+    use zeroize::Zeroizing;
+
+    if let Ok(passphrase) = std::env::var(IDENTITY_PASSPHRASE_ENV) {
+        return Ok(Zeroizing::new(passphrase));
+    }
+
+    let prompt = |label: &str| -> anyhow::Result<Zeroizing<String>> {
+        if io::stdin().is_terminal() {
+            return Ok(Zeroizing::new(rpassword::prompt_password(format!("{}: ", label))?));
+        }
+        eprint!("{}: ", label);
+        io::stderr().flush()?;
+        let mut input = Zeroizing::new(String::new());
+        io::stdin().read_line(&mut input)?;
+        let len = input.trim_end_matches(['\r', '\n']).len();
+        input.truncate(len);
+        Ok(input)
+    };
+
+    let passphrase = prompt("Passphrase")?;
+    if confirm && *prompt("Confirm passphrase")? != *passphrase {
+        anyhow::bail!("Passphrases do not match");
+    }
+    Ok(passphrase)
+}
+
 /// Identity subcommands
 #[derive(Subcommand, Debug)]
 pub enum IdentityAction {
     /// Show current identity
     Show,
-    /// Export identity (public info only)
+    /// Export identity: public info with --file, or a passphrase-encrypted
+    /// private bundle with --out
     Export {
-        /// Output file path
-        #[arg(long = "file", short = 'f')]
-        output_file: PathBuf,
+        /// Output file path for the public identity
+        #[arg(long = "file", short = 'f', required_unless_present = "out", conflicts_with = "out")]
+        output_file: Option<PathBuf>,
+        /// Output file path for the encrypted private identity bundle
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+    /// Import an encrypted identity bundle created with `identity export --out`
+    Import {
+        /// Encrypted identity bundle
+        file: PathBuf,
+        /// Overwrite an existing identity
+        #[arg(long)]
+        force: bool,
     },
     /// Rotate identity (warning: breaks existing pairings)
     Rotate {
@@ -1007,10 +1703,10 @@ impl FramesArgs {
 
                 // Check if we have a session
                 if self.session.is_none() {
-                    formatter.error("No session specified. Use --session to specify a session ID.");
+                    let code = formatter.error("No session specified. Use --session to specify a session ID.", ExitCode::InvalidInput);
                     eprintln!("Note: Frame reception requires an active QUIC session.");
                     eprintln!("Use 'zrc-controller session connect' to establish a session first.");
-                    return Ok(ExitCode::InvalidInput);
+                    return Ok(code);
                 }
 
                 // Create frame saver
@@ -1058,6 +1754,17 @@ impl FramesArgs {
     }
 }
 
+/// Split a `host:port` QUIC endpoint and decode a base64 session ticket
+fn parse_connect_target(quic: &str, ticket: &str) -> Result<(String, u16, Vec<u8>), String> {
+    let (host, port) = quic
+        .rsplit_once(':')
+        .and_then(|(host, port)| Some((host.to_string(), port.parse::<u16>().ok()?)))
+        .ok_or_else(|| format!("Invalid QUIC endpoint '{}': expected host:port", quic))?;
+    let ticket = base64::Engine::decode(&base64::engine::general_purpose::STANDARD, ticket.trim())
+        .map_err(|e| format!("Invalid session ticket: {}", e))?;
+    Ok((host, port, ticket))
+}
+
 /// Format frame statistics for output
 fn format_frame_stats(stats: &crate::frames::FrameStats, format: OutputFormat) -> String {
     match format {
@@ -1167,6 +1874,116 @@ pub enum FramesAction {
     Stats,
 }
 
+/// Arguments for the bench command
+#[derive(Parser, Debug)]
+pub struct BenchArgs {
+    #[command(subcommand)]
+    pub action: BenchAction,
+}
+
+impl BenchArgs {
+    /// Execute benchmark commands
+    /// Requirements: 6.1, 6.7
+    pub async fn execute(self, output: &OutputFormat, verbose: bool) -> anyhow::Result<ExitCode> {
+        use crate::output::OutputFormatter;
+        use crate::session::{
+            parse_cert_pin, QuicConnectParams, ReconnectPolicy, SessionClient, SessionError,
+        };
+
+        let formatter = OutputFormatter::new(*output, verbose);
+
+        match self.action {
+            BenchAction::Frames { quic, cert, ticket, synthetic, duration, min_fps } => {
+                let report = if let Some((width, height)) = synthetic {
+                    formatter.progress(&format!(
+                        "Benchmarking synthetic {}x{} frames for {:?}...",
+                        width, height, duration
+                    ));
+                    crate::bench::bench_synthetic_frames(width, height, duration, min_fps).await
+                } else {
+                    let (Some(quic), Some(cert), Some(ticket)) = (quic, cert, ticket) else {
+                        return Ok(formatter.error("--quic, --cert and --ticket are required without --synthetic", ExitCode::InvalidInput));
+                    };
+                    let cert_fingerprint = match parse_cert_pin(&cert) {
+                        Ok(fingerprint) => fingerprint,
+                        Err(e) => {
+                            return Ok(formatter.error(&format!("Invalid certificate fingerprint: {}", e), ExitCode::InvalidInput));
+                        }
+                    };
+                    let (host, port, ticket) = match parse_connect_target(&quic, &ticket) {
+                        Ok(target) => target,
+                        Err(e) => {
+                            return Ok(formatter.error(&e, ExitCode::InvalidInput));
+                        }
+                    };
+                    let params = QuicConnectParams {
+                        host,
+                        port,
+                        cert_fingerprint,
+                        ticket,
+                        relay_url: None,
+                        pinned_cert_sha256: None,
+                    };
+
+                    // Measure a single connection; a reconnect would skew the numbers
+                    let policy = ReconnectPolicy { max_attempts: 0, ..ReconnectPolicy::default() };
+                    formatter.progress(&format!("Connecting to {}...", quic));
+                    let session = SessionClient::new()
+                        .connect_quic_with_reconnect(params, &policy, |_| {})
+                        .await?;
+                    let (Some(conn), Some(crypto)) = (session.connection, session.crypto) else {
+                        return Err(SessionError::ConnectionFailed(
+                            "Session has no QUIC frame stream".to_string(),
+                        )
+                        .into());
+                    };
+
+                    formatter.progress(&format!("Receiving frames for {:?}...", duration));
+                    crate::bench::bench_quic_frames(conn, crypto, duration, min_fps).await
+                };
+                println!("{}", formatter.format_frame_bench(&report));
+
+                if report.passed {
+                    Ok(ExitCode::Success)
+                } else {
+                    let message = format!(
+                        "{:.1} fps is below the --min-fps threshold of {:.1}",
+                        report.fps,
+                        min_fps.unwrap_or_default()
+                    );
+                    Ok(formatter.error(&message, ExitCode::GeneralError))
+                }
+            }
+        }
+    }
+}
+
+/// Bench subcommands
+#[derive(Subcommand, Debug)]
+pub enum BenchAction {
+    /// Measure received FPS, megapixels/s and BGRA→RGBA conversion time
+    Frames {
+        /// QUIC endpoint (host:port)
+        #[arg(long, required_unless_present = "synthetic")]
+        quic: Option<String>,
+        /// Server certificate fingerprint
+        #[arg(long, required_unless_present = "synthetic")]
+        cert: Option<String>,
+        /// Session ticket (base64)
+        #[arg(long, required_unless_present = "synthetic")]
+        ticket: Option<String>,
+        /// Benchmark locally generated WIDTHxHEIGHT frames instead of a session
+        #[arg(long, conflicts_with_all = ["quic", "cert", "ticket"], value_parser = crate::bench::parse_resolution)]
+        synthetic: Option<(u32, u32)>,
+        /// How long to receive frames (e.g. 10s, 500ms, 2m)
+        #[arg(long, default_value = "10s", value_parser = crate::bench::parse_duration)]
+        duration: std::time::Duration,
+        /// Exit with GENERAL_ERROR when the received FPS is below this threshold
+        #[arg(long)]
+        min_fps: Option<f64>,
+    },
+}
+
 /// Arguments for the debug command
 #[derive(Parser, Debug)]
 pub struct DebugArgs {
@@ -1177,7 +1994,13 @@ pub struct DebugArgs {
 impl DebugArgs {
     /// Execute debug commands
     /// Requirements: 12.1, 12.2, 12.3, 12.4, 12.6
-    pub async fn execute(self, output: &OutputFormat, verbose: bool, _transport_opts: &TransportOptions) -> anyhow::Result<ExitCode> {
+    pub async fn execute(
+        self,
+        output: &OutputFormat,
+        verbose: bool,
+        config: &crate::config::Config,
+        transport_opts: &TransportOptions,
+    ) -> anyhow::Result<ExitCode> {
         use crate::debug::DebugTools;
         use crate::output::OutputFormatter;
         use std::time::Duration;
@@ -1229,8 +2052,7 @@ impl DebugArgs {
                         Ok(ExitCode::Success)
                     }
                     Err(e) => {
-                        formatter.error(&format!("Failed to decode envelope: {}", e));
-                        Ok(ExitCode::InvalidInput)
+                        Ok(formatter.error(&format!("Failed to decode envelope: {}", e), ExitCode::InvalidInput))
                     }
                 }
             }
@@ -1262,8 +2084,7 @@ impl DebugArgs {
                         Ok(ExitCode::Success)
                     }
                     Err(e) => {
-                        formatter.error(&format!("Failed to compute transcript: {}", e));
-                        Ok(ExitCode::InvalidInput)
+                        Ok(formatter.error(&format!("Failed to compute transcript: {}", e), ExitCode::InvalidInput))
                     }
                 }
             }
@@ -1298,8 +2119,7 @@ impl DebugArgs {
                         Ok(ExitCode::Success)
                     }
                     Err(e) => {
-                        formatter.error(&format!("Failed to compute SAS: {}", e));
-                        Ok(ExitCode::InvalidInput)
+                        Ok(formatter.error(&format!("Failed to compute SAS: {}", e), ExitCode::InvalidInput))
                     }
                 }
             }
@@ -1355,14 +2175,52 @@ impl DebugArgs {
                             formatter.success("Transport test passed");
                             Ok(ExitCode::Success)
                         } else {
-                            formatter.error("Transport test failed");
-                            Ok(ExitCode::ConnectionFailed)
+                            Ok(formatter.error("Transport test failed", ExitCode::ConnectionFailed))
                         }
                     }
                     Err(e) => {
-                        formatter.error(&format!("Transport test error: {}", e));
-                        Ok(ExitCode::ConnectionFailed)
+                        Ok(formatter.error(&format!("Transport test error: {}", e), ExitCode::ConnectionFailed))
+                    }
+                }
+            }
+
+            DebugAction::TransportLadder { device_id, direct_addrs } => {
+                // Requirements: 8.7 - Probe each transport rung
+                use crate::pairing::TransportClient;
+
+                let device_id_bytes = match hex::decode(device_id.trim()) {
+                    Ok(bytes) => bytes,
+                    Err(e) => {
+                        return Ok(formatter.error(&format!("Invalid device ID: {}", e), ExitCode::InvalidInput));
+                    }
+                };
+
+                let resolved = transport_opts.merge_with_config(&config.transport);
+                let client = TransportClient::with_urls(
+                    resolved.rendezvous_urls,
+                    resolved.relay_urls,
+                    resolved.mesh_nodes,
+                );
+
+                formatter.progress(&format!("Probing transport ladder for {}...", device_id));
+                let ladder = client.probe_ladder(&device_id_bytes, &direct_addrs).await;
+
+                match output {
+                    OutputFormat::Quiet => {
+                        let summary = match ladder.selected() {
+                            Some(transport) => format!("OK {}", transport),
+                            None => "FAIL".to_string(),
+                        };
+                        println!("{}", summary);
                     }
+                    _ => println!("{}", formatter.format_ladder(&ladder)),
+                }
+
+                if ladder.selected().is_some() {
+                    formatter.success("At least one transport is reachable");
+                    Ok(ExitCode::Success)
+                } else {
+                    Ok(formatter.error("No transport is reachable", ExitCode::ConnectionFailed))
                 }
             }
 
@@ -1407,8 +2265,7 @@ impl DebugArgs {
                         Ok(ExitCode::Success)
                     }
                     Err(e) => {
-                        formatter.error(&format!("Capture failed: {}", e));
-                        Ok(ExitCode::GeneralError)
+                        Ok(formatter.error(&format!("Capture failed: {}", e), ExitCode::GeneralError))
                     }
                 }
             }
@@ -1443,6 +2300,14 @@ pub enum DebugAction {
         #[arg(long)]
         test: String,
     },
+    /// Probe reachability of each transport rung for a device
+    TransportLadder {
+        /// Device ID (hex)
+        device_id: String,
+        /// Direct endpoint hint for the device (host:port, repeatable)
+        #[arg(long = "direct-addr")]
+        direct_addrs: Vec<String>,
+    },
     /// Capture packets to file
     Capture {
         /// Output file path
@@ -1599,4 +2464,372 @@ mod tests {
         assert!(cli.relay_urls.is_empty());
         assert!(cli.mesh_nodes.is_empty());
     }
+
+    #[test]
+    fn test_cli_parse_pairings_rename_and_set_note() {
+        use clap::Parser;
+
+        let cli = Cli::try_parse_from(["zrc-controller", "pairings", "rename", "ab12", "Front Desk"]).unwrap();
+        match cli.command {
+            Commands::Pairings(PairingsArgs { action: PairingsAction::Rename { device_id, name } }) => {
+                assert_eq!(device_id, "ab12");
+                assert_eq!(name, "Front Desk");
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+
+        let cli = Cli::try_parse_from(["zrc-controller", "pairings", "set-note", "ab12"]).unwrap();
+        match cli.command {
+            Commands::Pairings(PairingsArgs { action: PairingsAction::SetNote { device_id, note } }) => {
+                assert_eq!(device_id, "ab12");
+                assert!(note.is_none());
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_cli_parse_global_timeout() {
+        use clap::Parser;
+
+        let cli = Cli::try_parse_from(["zrc-controller", "--timeout", "15", "identity", "show"]).unwrap();
+        assert_eq!(cli.timeout, Some(15));
+
+        let cli = Cli::try_parse_from(["zrc-controller", "pairings", "list", "--timeout", "2"]).unwrap();
+        assert_eq!(cli.timeout, Some(2));
+
+        assert!(Cli::try_parse_from(["zrc-controller", "--timeout", "0", "identity", "show"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_parse_input_type() {
+        use clap::Parser;
+
+        let cli = Cli::try_parse_from([
+            "zrc-controller", "input", "--session", "abc", "type", "hello world", "--delay-ms", "25",
+        ])
+        .unwrap();
+        match cli.command {
+            Commands::Input(args) => match args.action {
+                InputAction::Type { text, delay_ms } => {
+                    assert_eq!(text, "hello world");
+                    assert_eq!(delay_ms, 25);
+                }
+                other => panic!("unexpected action: {:?}", other),
+            },
+            other => panic!("unexpected command: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_cli_parse_profile() {
+        use clap::Parser;
+
+        let cli = Cli::try_parse_from(["zrc-controller", "--profile", "work", "identity", "show"]).unwrap();
+        assert_eq!(cli.profile.as_deref(), Some("work"));
+
+        let cli = Cli::try_parse_from(["zrc-controller", "identity", "show"]).unwrap();
+        assert!(cli.profile.is_none());
+    }
+
+    #[test]
+    fn test_cli_parse_set_overrides() {
+        // Overrides on both sides of the subcommand are all kept, in order
+        let cli = Cli::try_parse_args([
+            "zrc-controller", "--set", "logging.level=trace", "identity", "show",
+            "--config-override", "transport.timeout_seconds=5", "--set=output.colors=false",
+        ])
+        .unwrap();
+        let keys: Vec<&str> = cli.set.iter().map(|o| o.key.as_str()).collect();
+        assert_eq!(keys, vec!["logging.level", "transport.timeout_seconds", "output.colors"]);
+
+        assert!(Cli::try_parse_args(["zrc-controller", "--set", "logging.level", "identity", "show"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_parse_identity_export_import() {
+        use clap::Parser;
+
+        let cli = Cli::try_parse_from(["zrc-controller", "identity", "export", "--out", "id.bundle"]).unwrap();
+        match cli.command {
+            Commands::Identity(args) => match args.action {
+                IdentityAction::Export { output_file, out } => {
+                    assert!(output_file.is_none());
+                    assert_eq!(out, Some(PathBuf::from("id.bundle")));
+                }
+                other => panic!("unexpected action: {:?}", other),
+            },
+            other => panic!("unexpected command: {:?}", other),
+        }
+
+        let cli = Cli::try_parse_from(["zrc-controller", "identity", "import", "id.bundle", "--force"]).unwrap();
+        match cli.command {
+            Commands::Identity(args) => match args.action {
+                IdentityAction::Import { file, force } => {
+                    assert_eq!(file, PathBuf::from("id.bundle"));
+                    assert!(force);
+                }
+                other => panic!("unexpected action: {:?}", other),
+            },
+            other => panic!("unexpected command: {:?}", other),
+        }
+
+        assert!(Cli::try_parse_from(["zrc-controller", "identity", "export"]).is_err());
+        assert!(Cli::try_parse_from(["zrc-controller", "identity", "export", "-f", "a", "--out", "b"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_parse_pair_dry_run() {
+        use clap::Parser;
+
+        let secret = "ab".repeat(32);
+        let cli = Cli::try_parse_from([
+            "zrc-controller", "pair", "--invite", "AAAA", "--dry-run", "--secret", &secret,
+            "--permissions", "view,control",
+        ])
+        .unwrap();
+        match cli.command {
+            Commands::Pair(args) => {
+                assert!(args.dry_run);
+                assert_eq!(args.secret.as_deref(), Some(secret.as_str()));
+                assert_eq!(args.permissions.as_deref(), Some("view,control"));
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_cli_parse_session_connect_device_pin() {
+        use clap::Parser;
+
+        let pin = "cd".repeat(32);
+        let cli = Cli::try_parse_from([
+            "zrc-controller", "session", "connect", "--quic", "127.0.0.1:4433", "--cert", &pin,
+            "--ticket", "AAAA", "--device", "ab12",
+        ])
+        .unwrap();
+        match cli.command {
+            Commands::Session(args) => match args.action {
+                SessionAction::Connect { device, .. } => {
+                    assert_eq!(device.as_deref(), Some("ab12"));
+                }
+                other => panic!("unexpected action: {:?}", other),
+            },
+            other => panic!("unexpected command: {:?}", other),
+        }
+
+        let cli = Cli::try_parse_from(["zrc-controller", "pairings", "pin-cert", "ab12", &pin]).unwrap();
+        match cli.command {
+            Commands::Pairings(PairingsArgs { action: PairingsAction::PinCert { device_id, cert } }) => {
+                assert_eq!(device_id, "ab12");
+                assert_eq!(cert.as_deref(), Some(pin.as_str()));
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_cli_parse_session_connect_reconnect() {
+        use clap::Parser;
+
+        let cli = Cli::try_parse_from([
+            "zrc-controller", "session", "connect", "--quic", "127.0.0.1:4433", "--cert", "ab",
+            "--ticket", "AAAA", "--max-reconnects", "8", "--reconnect-base-ms", "250",
+        ])
+        .unwrap();
+        match cli.command {
+            Commands::Session(args) => match args.action {
+                SessionAction::Connect { max_reconnects, reconnect_base_ms, reconnect_max_ms, .. } => {
+                    assert_eq!(max_reconnects, 8);
+                    assert_eq!(reconnect_base_ms, 250);
+                    assert_eq!(reconnect_max_ms, 30000);
+                }
+                other => panic!("unexpected action: {:?}", other),
+            },
+            other => panic!("unexpected command: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_cli_parse_bench_frames() {
+        use clap::Parser;
+
+        let cli = Cli::try_parse_from([
+            "zrc-controller", "bench", "frames", "--quic", "127.0.0.1:4433", "--cert", "ab",
+            "--ticket", "AAAA", "--duration", "2s", "--min-fps", "30",
+        ])
+        .unwrap();
+        match cli.command {
+            Commands::Bench(BenchArgs { action: BenchAction::Frames { duration, min_fps, .. } }) => {
+                assert_eq!(duration, std::time::Duration::from_secs(2));
+                assert_eq!(min_fps, Some(30.0));
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+
+        assert!(Cli::try_parse_from([
+            "zrc-controller", "bench", "frames", "--quic", "127.0.0.1:4433", "--cert", "ab",
+            "--ticket", "AAAA", "--duration", "soon",
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_cli_parse_bench_frames_synthetic() {
+        use clap::Parser;
+
+        let cli = Cli::try_parse_from([
+            "zrc-controller", "bench", "frames", "--synthetic", "64x48",
+        ])
+        .unwrap();
+        match cli.command {
+            Commands::Bench(BenchArgs { action: BenchAction::Frames { synthetic, quic, .. } }) => {
+                assert_eq!(synthetic, Some((64, 48)));
+                assert_eq!(quic, None);
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+
+        // A session target is required without --synthetic, and excluded with it
+        assert!(Cli::try_parse_from(["zrc-controller", "bench", "frames"]).is_err());
+        assert!(Cli::try_parse_from([
+            "zrc-controller", "bench", "frames", "--synthetic", "64x48", "--quic", "127.0.0.1:4433",
+        ])
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_bench_frames_command_measures_synthetic_source() {
+        let bench = |min_fps| BenchArgs {
+            action: BenchAction::Frames {
+                quic: None,
+                cert: None,
+                ticket: None,
+                synthetic: Some((64, 48)),
+                duration: std::time::Duration::from_millis(100),
+                min_fps: Some(min_fps),
+            },
+        };
+
+        // Small frames easily clear 1 fps; no local pipeline reaches 1e9
+        let code = bench(1.0).execute(&OutputFormat::Quiet, false).await.unwrap();
+        assert_eq!(code, ExitCode::Success);
+        let code = bench(1e9).execute(&OutputFormat::Quiet, false).await.unwrap();
+        assert_eq!(code, ExitCode::GeneralError);
+    }
+
+    #[test]
+    fn test_parse_connect_target() {
+        let (host, port, ticket) = parse_connect_target("example.com:4433", "AQID").unwrap();
+        assert_eq!(host, "example.com");
+        assert_eq!(port, 4433);
+        assert_eq!(ticket, vec![1, 2, 3]);
+
+        assert!(parse_connect_target("example.com", "AQID").is_err());
+        assert!(parse_connect_target("example.com:4433", "not base64!").is_err());
+    }
+
+    #[test]
+    fn test_cli_parse_pair_batch() {
+        use clap::Parser;
+
+        let cli = Cli::try_parse_from(["zrc-controller", "pair", "--batch", "invites.txt", "--fail-fast"])
+            .unwrap();
+        match cli.command {
+            Commands::Pair(args) => {
+                assert_eq!(args.batch, Some(PathBuf::from("invites.txt")));
+                assert!(args.fail_fast);
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+
+        assert!(Cli::try_parse_from(["zrc-controller", "pair", "--fail-fast"]).is_err());
+        assert!(Cli::try_parse_from([
+            "zrc-controller", "pair", "--batch", "invites.txt", "--invite", "AAAA",
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_parse_pair_batch() {
+        let secret = "ab".repeat(32);
+        let contents = format!("# fleet A\nINVITE1 {secret}\n\n  INVITE2  \n");
+        let entries = parse_pair_batch(&contents);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].line, 2);
+        assert_eq!(entries[0].invite, "INVITE1");
+        assert_eq!(entries[0].secret.as_deref(), Some(secret.as_str()));
+        assert_eq!(entries[1].line, 4);
+        assert_eq!(entries[1].secret, None);
+
+        assert_eq!(parse_permission_list(None), vec!["view".to_string()]);
+        assert_eq!(parse_permission_list(Some("view, control,")), vec!["view", "control"]);
+    }
+
+    #[tokio::test]
+    async fn test_pair_batch_continues_past_failures() {
+        use crate::output::{OutputFormat, OutputFormatter};
+        use crate::pairing::PairingClient;
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("invites.txt");
+        std::fs::write(&path, "not-an-invite\nalso-bad deadbeef\n").unwrap();
+
+        let mut client = PairingClient::new();
+        let formatter = OutputFormatter::new(OutputFormat::Quiet, false);
+        let effects = crate::SideEffects::new();
+        let code = PairArgs::execute_batch(&mut client, &path, 1, false, &formatter, &effects)
+            .await
+            .unwrap();
+        assert_eq!(code, ExitCode::GeneralError);
+        assert!(matches!(client.state(), crate::pairing::PairingState::Idle));
+
+        let missing = temp_dir.path().join("missing.txt");
+        let code = PairArgs::execute_batch(&mut client, &missing, 1, true, &formatter, &effects)
+            .await
+            .unwrap();
+        assert_eq!(code, ExitCode::InvalidInput);
+    }
+
+    #[tokio::test]
+    async fn test_pairings_uses_resolved_config() {
+        use crate::output::OutputFormat;
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db_path = temp_dir.path().join("profile-pairings.db");
+        let mut config = crate::config::Config::default();
+        config.pairings.db_path = Some(db_path.clone());
+
+        let args = PairingsArgs { action: PairingsAction::List };
+        let code = args.execute(&OutputFormat::Quiet, false, &config).await.unwrap();
+        assert_eq!(code, ExitCode::Success);
+        assert!(db_path.exists(), "handler must open the store from the config it was given");
+    }
+
+    #[test]
+    fn test_cli_parse_pairings_export_qr() {
+        use clap::Parser;
+
+        let cli = Cli::try_parse_from([
+            "zrc-controller", "pairings", "export-qr", "ab12", "--invite", "AAAA", "--terminal",
+        ])
+        .unwrap();
+        match cli.command {
+            Commands::Pairings(PairingsArgs { action: PairingsAction::ExportQr { device_id, invite, out, terminal } }) => {
+                assert_eq!(device_id, "ab12");
+                assert_eq!(invite, "AAAA");
+                assert!(out.is_none());
+                assert!(terminal);
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+
+        // Either --out or --terminal is required
+        assert!(
+            Cli::try_parse_from(["zrc-controller", "pairings", "export-qr", "ab12", "--invite", "AAAA"])
+                .is_err()
+        );
+        // The invite comes from the device
+        assert!(Cli::try_parse_from(["zrc-controller", "pairings", "export-qr", "ab12", "--terminal"]).is_err());
+    }
 }
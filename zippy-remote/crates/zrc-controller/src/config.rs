@@ -18,7 +18,10 @@
 //! - 10.6: --config flag support
 //! - 10.7: Default config creation
 //! - 10.8: Config validation
+//! - 10.9: Named transport profiles (`[profiles.<name>]`, `--profile`)
+//! - 10.5: Ad-hoc `--set <dotted.key>=<value>` overrides
 
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
 use serde::{Deserialize, Serialize};
@@ -42,6 +45,24 @@ pub enum ConfigError {
     /// Invalid configuration value
     #[error("Invalid configuration: {0}")]
     ValidationError(String),
+
+    /// Selected profile is not defined
+    #[error("Profile '{name}' not found (available: {available})")]
+    ProfileNotFound {
+        /// Requested profile name
+        name: String,
+        /// Comma-separated list of defined profiles
+        available: String,
+    },
+
+    /// A `--set` override names an unknown key or has the wrong type
+    #[error("Invalid override for '{key}': {reason}")]
+    InvalidOverride {
+        /// Dotted key from the override
+        key: String,
+        /// Why the override was rejected
+        reason: String,
+    },
 }
 
 /// Controller configuration
@@ -72,8 +93,12 @@ pub enum ConfigError {
 /// [logging]
 /// level = "warn"
 /// file = ""
+///
+/// [profiles.work]
+/// rendezvous_urls = ["https://rendezvous.work.example"]
+/// relay_urls = ["https://relay.work.example"]
 /// ```
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Config {
     /// Identity configuration
     #[serde(default)]
@@ -94,18 +119,10 @@ pub struct Config {
     /// Logging configuration
     #[serde(default)]
     pub logging: LoggingConfig,
-}
 
-impl Default for Config {
-    fn default() -> Self {
-        Self {
-            identity: IdentityConfig::default(),
-            transport: TransportConfig::default(),
-            output: OutputConfig::default(),
-            pairings: PairingsConfig::default(),
-            logging: LoggingConfig::default(),
-        }
-    }
+    /// Named transport profiles selectable with `--profile`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, ProfileConfig>,
 }
 
 /// Identity configuration
@@ -177,6 +194,54 @@ impl Default for TransportConfig {
     }
 }
 
+/// Transport settings overridden by a named profile
+/// Requirements: 10.9
+///
+/// Unset fields fall back to the top-level `[transport]` values.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfileConfig {
+    /// Default transport override
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+
+    /// Rendezvous server URLs override
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rendezvous_urls: Option<Vec<String>>,
+
+    /// Relay server URLs override
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relay_urls: Option<Vec<String>>,
+
+    /// Mesh node addresses override
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mesh_nodes: Option<Vec<String>>,
+
+    /// Connection timeout override in seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_seconds: Option<u64>,
+}
+
+impl ProfileConfig {
+    /// Apply this profile on top of transport settings
+    pub fn apply(&self, transport: &mut TransportConfig) {
+        if let Some(ref default) = self.default {
+            transport.default = default.clone();
+        }
+        if let Some(ref urls) = self.rendezvous_urls {
+            transport.rendezvous_urls = urls.clone();
+        }
+        if let Some(ref urls) = self.relay_urls {
+            transport.relay_urls = urls.clone();
+        }
+        if let Some(ref nodes) = self.mesh_nodes {
+            transport.mesh_nodes = nodes.clone();
+        }
+        if let Some(timeout) = self.timeout_seconds {
+            transport.timeout_seconds = timeout;
+        }
+    }
+}
+
 /// Output configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OutputConfig {
@@ -212,19 +277,13 @@ impl Default for OutputConfig {
 }
 
 /// Pairings configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct PairingsConfig {
     /// Path to pairings database (empty = default location)
     #[serde(default)]
     pub db_path: Option<PathBuf>,
 }
 
-impl Default for PairingsConfig {
-    fn default() -> Self {
-        Self { db_path: None }
-    }
-}
-
 /// Logging configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LoggingConfig {
@@ -304,6 +363,11 @@ impl Config {
             .map(|dirs| dirs.data_dir().to_path_buf())
     }
 
+    /// Get the security audit log path
+    pub fn audit_log_path() -> Option<PathBuf> {
+        Self::data_dir().map(|dir| dir.join("audit.log"))
+    }
+
     /// Save configuration to file
     pub fn save(&self, path: &Path) -> Result<(), ConfigError> {
         let content = toml::to_string_pretty(self)?;
@@ -330,14 +394,7 @@ impl Config {
     /// Validate configuration values
     /// Requirements: 10.8
     pub fn validate(&self) -> Result<(), ConfigError> {
-        // Validate transport preference
-        let valid_transports = ["auto", "mesh", "rendezvous", "direct", "relay"];
-        if !valid_transports.contains(&self.transport.default.as_str()) {
-            return Err(ConfigError::ValidationError(format!(
-                "Invalid transport '{}'. Valid values: {:?}",
-                self.transport.default, valid_transports
-            )));
-        }
+        Self::validate_transport(&self.transport)?;
 
         // Validate output format
         let valid_formats = ["table", "json", "quiet"];
@@ -366,15 +423,42 @@ impl Config {
             )));
         }
 
+        // Validate each profile as merged over the top-level transport settings
+        for (name, profile) in &self.profiles {
+            let mut transport = self.transport.clone();
+            profile.apply(&mut transport);
+            Self::validate_transport(&transport).map_err(|e| match e {
+                ConfigError::ValidationError(msg) => {
+                    ConfigError::ValidationError(format!("profile '{}': {}", name, msg))
+                }
+                other => other,
+            })?;
+        }
+
+        Ok(())
+    }
+
+    /// Validate transport settings
+    /// Requirements: 10.8
+    fn validate_transport(transport: &TransportConfig) -> Result<(), ConfigError> {
+        // Validate transport preference
+        let valid_transports = ["auto", "mesh", "rendezvous", "direct", "relay"];
+        if !valid_transports.contains(&transport.default.as_str()) {
+            return Err(ConfigError::ValidationError(format!(
+                "Invalid transport '{}'. Valid values: {:?}",
+                transport.default, valid_transports
+            )));
+        }
+
         // Validate timeout
-        if self.transport.timeout_seconds == 0 {
+        if transport.timeout_seconds == 0 {
             return Err(ConfigError::ValidationError(
                 "timeout_seconds must be greater than 0".to_string(),
             ));
         }
 
         // Validate URLs (basic check)
-        for url in &self.transport.rendezvous_urls {
+        for url in &transport.rendezvous_urls {
             if !url.starts_with("http://") && !url.starts_with("https://") {
                 return Err(ConfigError::ValidationError(format!(
                     "Invalid rendezvous URL '{}': must start with http:// or https://",
@@ -383,7 +467,7 @@ impl Config {
             }
         }
 
-        for url in &self.transport.relay_urls {
+        for url in &transport.relay_urls {
             if !url.starts_with("http://") && !url.starts_with("https://") {
                 return Err(ConfigError::ValidationError(format!(
                     "Invalid relay URL '{}': must start with http:// or https://",
@@ -435,11 +519,15 @@ colors = true
 level = "warn"
 # Log file path (empty = stderr only)
 # file = ""
+
+# Named profiles override [transport] settings; select with --profile <name>
+# [profiles.work]
+# rendezvous_urls = ["https://rendezvous.work.example"]
+# relay_urls = ["https://relay.work.example"]
 "#
     }
 }
 
-
 /// CLI configuration overrides
 /// Requirements: 10.5
 ///
@@ -461,14 +549,28 @@ pub struct CliOverrides {
     pub relay_urls: Option<Vec<String>>,
     /// Mesh nodes override
     pub mesh_nodes: Option<Vec<String>>,
+    /// Named profile to apply
+    pub profile: Option<String>,
 }
 
 impl Config {
-    /// Apply CLI overrides to configuration
-    /// Requirements: 10.5
+    /// Apply the selected profile and CLI overrides to configuration
+    /// Requirements: 10.5, 10.9
     ///
-    /// CLI arguments take precedence over config file values.
-    pub fn with_overrides(mut self, overrides: &CliOverrides) -> Self {
+    /// Precedence, lowest to highest: defaults/config file, selected profile,
+    /// CLI arguments. Fails if the selected profile is not defined.
+    pub fn with_overrides(mut self, overrides: &CliOverrides) -> Result<Self, ConfigError> {
+        if let Some(ref name) = overrides.profile {
+            let profile = self.profiles.get(name).ok_or_else(|| ConfigError::ProfileNotFound {
+                name: name.clone(),
+                available: if self.profiles.is_empty() {
+                    "none".to_string()
+                } else {
+                    self.profiles.keys().cloned().collect::<Vec<_>>().join(", ")
+                },
+            })?;
+            profile.apply(&mut self.transport);
+        }
         if let Some(ref format) = overrides.output_format {
             self.output.format = format.clone();
         }
@@ -498,7 +600,157 @@ impl Config {
                 self.transport.mesh_nodes = nodes.clone();
             }
         }
-        self
+        Ok(self)
+    }
+}
+
+/// A single `--set <dotted.key>=<value>` override
+/// Requirements: 10.5
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigOverride {
+    /// Dotted path into the config, e.g. `logging.level`
+    pub key: String,
+    /// Raw value, converted to the type of the target field
+    pub value: String,
+}
+
+impl std::str::FromStr for ConfigOverride {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (key, value) = s
+            .split_once('=')
+            .ok_or_else(|| format!("Expected <dotted.key>=<value>, got '{s}'"))?;
+        let key = key.trim();
+        if key.is_empty() || key.split('.').any(str::is_empty) {
+            return Err(format!("Invalid config key '{key}'"));
+        }
+        Ok(Self {
+            key: key.to_string(),
+            value: value.trim().to_string(),
+        })
+    }
+}
+
+impl Config {
+    /// Apply `--set` overrides on top of the resolved configuration
+    ///
+    /// Overrides are applied in order, so a later one for the same key wins.
+    /// The result is validated like a loaded config file.
+    /// Requirements: 10.5, 10.8
+    pub fn with_settings(mut self, settings: &[ConfigOverride]) -> Result<Self, ConfigError> {
+        if settings.is_empty() {
+            return Ok(self);
+        }
+        for setting in settings {
+            self.set_path(&setting.key, &setting.value)?;
+        }
+        self.validate()?;
+        Ok(self)
+    }
+
+    /// Set a single value by dotted path, e.g. `transport.timeout_seconds`
+    ///
+    /// The value is converted to the type of the existing field: strings are
+    /// taken verbatim, numbers and booleans are parsed, and lists are
+    /// comma-separated. Unknown keys and type mismatches are rejected.
+    /// Requirements: 10.5
+    pub fn set_path(&mut self, key: &str, value: &str) -> Result<(), ConfigError> {
+        let invalid = |reason: String| ConfigError::InvalidOverride {
+            key: key.to_string(),
+            reason,
+        };
+
+        let root = toml::Value::try_from(&*self).map_err(ConfigError::from)?;
+        let segments: Vec<&str> = key.split('.').collect();
+        let (leaf, parents) = segments
+            .split_last()
+            .ok_or_else(|| invalid("empty key".to_string()))?;
+        let parent = parents
+            .iter()
+            .try_fold(&root, |v, segment| v.get(*segment))
+            .and_then(toml::Value::as_table)
+            .ok_or_else(|| invalid("unknown key".to_string()))?;
+
+        let current = parent.get(*leaf);
+        let candidates = match current {
+            Some(current) => vec![convert_override(current, value).map_err(invalid)?],
+            // Unset optional fields are absent from the serialized form, so
+            // their type is unknown: try a TOML literal first, then a string
+            None => guess_override(value),
+        };
+
+        let mut reason = "unknown key".to_string();
+        for candidate in candidates {
+            let mut attempt = root.clone();
+            if let Some(table) = parents
+                .iter()
+                .try_fold(&mut attempt, |v, segment| v.get_mut(*segment))
+                .and_then(toml::Value::as_table_mut)
+            {
+                table.insert(leaf.to_string(), candidate);
+            }
+
+            let updated: Config = match attempt.try_into() {
+                Ok(updated) => updated,
+                Err(e) => {
+                    reason = e.message().to_string();
+                    continue;
+                }
+            };
+
+            // Serde ignores unknown fields, so make sure a newly added key stuck
+            if current.is_none() {
+                let check = toml::Value::try_from(&updated).map_err(ConfigError::from)?;
+                if segments.iter().try_fold(&check, |v, segment| v.get(*segment)).is_none() {
+                    return Err(invalid("unknown key".to_string()));
+                }
+            }
+
+            *self = updated;
+            return Ok(());
+        }
+        Err(invalid(reason))
+    }
+}
+
+/// Candidate values for an override whose target field is currently unset
+fn guess_override(raw: &str) -> Vec<toml::Value> {
+    let literal = toml::from_str::<toml::Table>(&format!("v = {raw}"))
+        .ok()
+        .and_then(|mut table| table.remove("v"))
+        .filter(|v| !v.is_str());
+    literal
+        .into_iter()
+        .chain(std::iter::once(toml::Value::String(raw.to_string())))
+        .collect()
+}
+
+/// Convert a raw override to the TOML type of the value it replaces
+fn convert_override(current: &toml::Value, raw: &str) -> Result<toml::Value, String> {
+    match current {
+        toml::Value::String(_) => Ok(toml::Value::String(raw.to_string())),
+        toml::Value::Integer(_) => raw
+            .parse()
+            .map(toml::Value::Integer)
+            .map_err(|_| format!("expected an integer, got '{raw}'")),
+        toml::Value::Float(_) => raw
+            .parse()
+            .map(toml::Value::Float)
+            .map_err(|_| format!("expected a number, got '{raw}'")),
+        toml::Value::Boolean(_) => raw
+            .parse()
+            .map(toml::Value::Boolean)
+            .map_err(|_| format!("expected true or false, got '{raw}'")),
+        toml::Value::Array(_) => Ok(toml::Value::Array(
+            raw.split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(|item| toml::Value::String(item.to_string()))
+                .collect(),
+        )),
+        toml::Value::Table(_) => Err("is a section; set one of its keys instead".to_string()),
+        toml::Value::Datetime(_) => Err("datetime values cannot be overridden".to_string()),
     }
 }
 
@@ -639,9 +891,10 @@ mod tests {
             rendezvous_urls: Some(vec!["https://custom.example.com".to_string()]),
             relay_urls: None,
             mesh_nodes: None,
+            profile: None,
         };
         
-        let config = config.with_overrides(&overrides);
+        let config = config.with_overrides(&overrides).unwrap();
         
         assert_eq!(config.output.format, "json");
         assert!(config.output.verbose);
@@ -663,7 +916,7 @@ mod tests {
             ..Default::default()
         };
         
-        let config = config.with_overrides(&overrides);
+        let config = config.with_overrides(&overrides).unwrap();
         
         // Empty vector should not override
         assert_eq!(config.transport.rendezvous_urls, original_urls);
@@ -761,4 +1014,179 @@ level = "debug"
         let config = Config::load_from(None);
         assert!(config.is_ok());
     }
+
+    const PROFILES_TOML: &str = r#"
+[transport]
+default = "auto"
+rendezvous_urls = ["https://rendezvous.home.example"]
+relay_urls = ["https://relay.home.example"]
+
+[profiles.work]
+rendezvous_urls = ["https://rendezvous.work.example"]
+timeout_seconds = 10
+
+[profiles.lab]
+default = "direct"
+mesh_nodes = ["10.0.0.5:4000"]
+"#;
+
+    /// Test parsing and applying a named profile
+    /// Requirements: 10.9
+    #[test]
+    fn test_profile_overrides_transport() {
+        let config: Config = toml::from_str(PROFILES_TOML).unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.profiles.len(), 2);
+
+        let overrides = CliOverrides {
+            profile: Some("work".to_string()),
+            ..Default::default()
+        };
+        let config = config.with_overrides(&overrides).unwrap();
+
+        assert_eq!(config.transport.rendezvous_urls, vec!["https://rendezvous.work.example"]);
+        assert_eq!(config.transport.timeout_seconds, 10);
+        // Unset profile fields keep the top-level values
+        assert_eq!(config.transport.relay_urls, vec!["https://relay.home.example"]);
+        assert_eq!(config.transport.default, "auto");
+    }
+
+    /// Test CLI flags take precedence over the selected profile
+    /// Requirements: 10.5, 10.9
+    #[test]
+    fn test_cli_overrides_beat_profile() {
+        let config: Config = toml::from_str(PROFILES_TOML).unwrap();
+
+        let overrides = CliOverrides {
+            profile: Some("lab".to_string()),
+            transport: Some("relay".to_string()),
+            ..Default::default()
+        };
+        let config = config.with_overrides(&overrides).unwrap();
+
+        assert_eq!(config.transport.default, "relay");
+        assert_eq!(config.transport.mesh_nodes, vec!["10.0.0.5:4000"]);
+    }
+
+    /// Test selecting an undefined profile fails
+    #[test]
+    fn test_unknown_profile() {
+        let config: Config = toml::from_str(PROFILES_TOML).unwrap();
+
+        let overrides = CliOverrides {
+            profile: Some("cafe".to_string()),
+            ..Default::default()
+        };
+        let err = config.with_overrides(&overrides).unwrap_err();
+
+        assert!(matches!(err, ConfigError::ProfileNotFound { .. }));
+        assert!(err.to_string().contains("'cafe'"));
+        assert!(err.to_string().contains("lab, work"));
+    }
+
+    /// Test invalid values inside a profile are caught by validation
+    #[test]
+    fn test_validate_invalid_profile() {
+        let mut config = Config::default();
+        config.profiles.insert(
+            "broken".to_string(),
+            ProfileConfig {
+                relay_urls: Some(vec!["relay.example".to_string()]),
+                ..Default::default()
+            },
+        );
+
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("profile 'broken'"));
+        assert!(err.contains("Invalid relay URL"));
+    }
+
+    #[test]
+    fn test_config_override_parsing() {
+        let o: ConfigOverride = "logging.level=trace".parse().unwrap();
+        assert_eq!(o.key, "logging.level");
+        assert_eq!(o.value, "trace");
+
+        // Only the first '=' separates key from value
+        let o: ConfigOverride = "transport.relay_urls=https://r.example/?a=b".parse().unwrap();
+        assert_eq!(o.value, "https://r.example/?a=b");
+
+        assert!("logging.level".parse::<ConfigOverride>().is_err());
+        assert!("logging..level=x".parse::<ConfigOverride>().is_err());
+        assert!("=x".parse::<ConfigOverride>().is_err());
+    }
+
+    #[test]
+    fn test_set_path_typed_leaves() {
+        let mut config = Config::default();
+        config.set_path("logging.level", "trace").unwrap();
+        config.set_path("transport.timeout_seconds", "90").unwrap();
+        config.set_path("output.colors", "false").unwrap();
+        config
+            .set_path("transport.rendezvous_urls", "https://a.example, https://b.example")
+            .unwrap();
+        config.set_path("pairings.db_path", "/tmp/pairings.db").unwrap();
+        config.set_path("identity.key_path", "42").unwrap();
+
+        assert_eq!(config.logging.level, "trace");
+        assert_eq!(config.transport.timeout_seconds, 90);
+        assert!(!config.output.colors);
+        assert_eq!(
+            config.transport.rendezvous_urls,
+            vec!["https://a.example".to_string(), "https://b.example".to_string()]
+        );
+        assert_eq!(config.pairings.db_path, Some(PathBuf::from("/tmp/pairings.db")));
+        // An unset path that looks like a number still lands as a string
+        assert_eq!(config.identity.key_path, Some(PathBuf::from("42")));
+    }
+
+    #[test]
+    fn test_set_path_unset_profile_field() {
+        let mut config = Config::default();
+        config.profiles.insert("lab".to_string(), ProfileConfig::default());
+        config.set_path("profiles.lab.timeout_seconds", "5").unwrap();
+        assert_eq!(config.profiles["lab"].timeout_seconds, Some(5));
+
+        assert!(matches!(
+            config.set_path("profiles.lab.timeout_seconds", "soon"),
+            Err(ConfigError::InvalidOverride { .. })
+        ));
+    }
+
+    #[test]
+    fn test_set_path_rejects_bad_keys_and_types() {
+        let mut config = Config::default();
+
+        for (key, value) in [
+            ("logging.nope", "x"),
+            ("nope.level", "x"),
+            ("transport.timeout_seconds", "soon"),
+            ("output.colors", "maybe"),
+            ("transport", "x"),
+        ] {
+            match config.set_path(key, value) {
+                Err(ConfigError::InvalidOverride { key: k, .. }) => assert_eq!(k, key),
+                other => panic!("expected InvalidOverride for {key}, got {other:?}"),
+            }
+        }
+
+        // A failed override leaves the config untouched
+        assert_eq!(config.transport.timeout_seconds, 30);
+    }
+
+    #[test]
+    fn test_with_settings_applies_in_order_and_validates() {
+        let settings: Vec<ConfigOverride> = ["logging.level=info", "logging.level=trace"]
+            .iter()
+            .map(|s| s.parse().unwrap())
+            .collect();
+        let config = Config::default().with_settings(&settings).unwrap();
+        assert_eq!(config.logging.level, "trace");
+
+        let settings = vec!["transport.timeout_seconds=0".parse().unwrap()];
+        assert!(matches!(
+            Config::default().with_settings(&settings),
+            Err(ConfigError::ValidationError(_))
+        ));
+    }
 }
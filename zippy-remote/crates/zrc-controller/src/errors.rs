@@ -0,0 +1,241 @@
+//! Machine-readable error classification for CLI failures
+//!
+//! Maps command errors to an [`ExitCode`] plus a stable error code string so
+//! automation can branch on failures without scraping stderr. The codes are
+//! part of the JSON output contract and must not change once released.
+//!
+//! | Code                          | Exit code             |
+//! |-------------------------------|-----------------------|
+//! | `PAIRING_INVALID_INVITE`      | INVALID_INPUT         |
+//! | `PAIRING_INVITE_EXPIRED`      | INVALID_INPUT         |
+//! | `PAIRING_DECODE_FAILED`       | INVALID_INPUT         |
+//! | `PAIRING_QR_FAILED`           | INVALID_INPUT         |
+//! | `PAIRING_INVALID_PROOF`       | AUTH_FAILED           |
+//! | `PAIRING_SIGNATURE_INVALID`   | AUTH_FAILED           |
+//! | `PAIRING_SAS_FAILED`          | AUTH_FAILED           |
+//! | `PAIRING_REJECTED`            | PERMISSION_DENIED     |
+//! | `PAIRING_TRANSPORT_FAILED`    | CONNECTION_FAILED     |
+//! | `PAIRING_TIMEOUT`             | TIMEOUT               |
+//! | `PAIRING_NOT_PAIRED`          | NOT_PAIRED            |
+//! | `PAIRING_INVALID_STATE`       | GENERAL_ERROR         |
+//! | `PAIRING_STORAGE_FAILED`      | GENERAL_ERROR         |
+//! | `PAIRING_IO_FAILED`           | GENERAL_ERROR         |
+//! | `PAIRING_IDENTITY_FAILED`     | GENERAL_ERROR         |
+//! | `SESSION_NOT_PAIRED`          | NOT_PAIRED            |
+//! | `SESSION_DENIED`              | PERMISSION_DENIED     |
+//! | `SESSION_PERMISSION_DENIED`   | PERMISSION_DENIED     |
+//! | `SESSION_CONNECTION_FAILED`   | CONNECTION_FAILED     |
+//! | `SESSION_TRANSPORT_FAILED`    | CONNECTION_FAILED     |
+//! | `SESSION_AUTH_FAILED`         | AUTH_FAILED           |
+//! | `SESSION_SIGNATURE_INVALID`   | AUTH_FAILED           |
+//! | `SESSION_TICKET_EXPIRED`      | AUTH_FAILED           |
+//! | `SESSION_TIMEOUT`             | TIMEOUT               |
+//! | `SESSION_NOT_FOUND`           | INVALID_INPUT         |
+//! | `SESSION_MISSING_FIELD`       | INVALID_INPUT         |
+//! | `SESSION_INVALID_STATE`       | GENERAL_ERROR         |
+//! | `SESSION_CRYPTO_FAILED`       | GENERAL_ERROR         |
+//! | `SESSION_STORE_FAILED`        | GENERAL_ERROR         |
+//! | `SESSION_UNSUPPORTED`         | GENERAL_ERROR         |
+//! | `CONFIG_PROFILE_NOT_FOUND`    | INVALID_INPUT         |
+//! | `CONFIG_INVALID`              | INVALID_INPUT         |
+//! | `CONFIG_INVALID_OVERRIDE`     | INVALID_INPUT         |
+//! | `CONFIG_IO_FAILED`            | GENERAL_ERROR         |
+//! | `TIMEOUT`                     | TIMEOUT               |
+//! | `INTERNAL`                    | GENERAL_ERROR         |
+//!
+//! Failures a command reports itself through `OutputFormatter::error` use
+//! the exit code name as their code (e.g. `INVALID_INPUT`).
+//!
+//! Requirements: 9.4, 9.6
+
+use crate::config::ConfigError;
+use crate::pairing::PairingError;
+use crate::session::SessionError;
+use crate::ExitCode;
+
+/// Error code for a command that exceeded its `--timeout`
+pub const TIMEOUT_CODE: &str = "TIMEOUT";
+
+/// Error code for failures without a more specific classification
+pub const INTERNAL_CODE: &str = "INTERNAL";
+
+/// An error that knows its CLI exit code and stable machine code
+pub trait CommandError {
+    /// Exit code the process should return
+    fn exit_code(&self) -> ExitCode;
+    /// Stable machine-readable error code
+    fn error_code(&self) -> &'static str;
+}
+
+impl CommandError for PairingError {
+    fn exit_code(&self) -> ExitCode {
+        match self {
+            Self::InvalidInvite(_)
+            | Self::InviteExpired(_)
+            | Self::Base64Decode(_)
+            | Self::ProtobufDecode(_)
+            | Self::JsonParse(_)
+            | Self::QrCode(_) => ExitCode::InvalidInput,
+            Self::InvalidProof | Self::SignatureInvalid(_) | Self::SasVerificationFailed => {
+                ExitCode::AuthenticationFailed
+            }
+            Self::Rejected(_) => ExitCode::PermissionDenied,
+            Self::Transport(_) => ExitCode::ConnectionFailed,
+            Self::Timeout(_) => ExitCode::Timeout,
+            Self::NotPaired(_) => ExitCode::NotPaired,
+            Self::InvalidState(_) | Self::Storage(_) | Self::Io(_) | Self::Identity(_) => {
+                ExitCode::GeneralError
+            }
+        }
+    }
+
+    fn error_code(&self) -> &'static str {
+        match self {
+            Self::InvalidInvite(_) => "PAIRING_INVALID_INVITE",
+            Self::InviteExpired(_) => "PAIRING_INVITE_EXPIRED",
+            Self::Base64Decode(_) | Self::ProtobufDecode(_) | Self::JsonParse(_) => {
+                "PAIRING_DECODE_FAILED"
+            }
+            Self::QrCode(_) => "PAIRING_QR_FAILED",
+            Self::InvalidProof => "PAIRING_INVALID_PROOF",
+            Self::SignatureInvalid(_) => "PAIRING_SIGNATURE_INVALID",
+            Self::SasVerificationFailed => "PAIRING_SAS_FAILED",
+            Self::Rejected(_) => "PAIRING_REJECTED",
+            Self::Transport(_) => "PAIRING_TRANSPORT_FAILED",
+            Self::Timeout(_) => "PAIRING_TIMEOUT",
+            Self::NotPaired(_) => "PAIRING_NOT_PAIRED",
+            Self::InvalidState(_) => "PAIRING_INVALID_STATE",
+            Self::Storage(_) => "PAIRING_STORAGE_FAILED",
+            Self::Io(_) => "PAIRING_IO_FAILED",
+            Self::Identity(_) => "PAIRING_IDENTITY_FAILED",
+        }
+    }
+}
+
+impl CommandError for SessionError {
+    fn exit_code(&self) -> ExitCode {
+        match self {
+            Self::NotPaired(_) => ExitCode::NotPaired,
+            Self::Denied(_) | Self::PermissionDenied(_) => ExitCode::PermissionDenied,
+            Self::ConnectionFailed(_) | Self::Transport(_) => ExitCode::ConnectionFailed,
+            Self::AuthenticationFailed(_) | Self::SignatureInvalid | Self::TicketExpired => {
+                ExitCode::AuthenticationFailed
+            }
+            Self::Timeout(_) => ExitCode::Timeout,
+            Self::NotFound(_) | Self::MissingField(_) => ExitCode::InvalidInput,
+            Self::InvalidState(_) | Self::Crypto(_) | Self::Store(_) | Self::Unsupported(_) => {
+                ExitCode::GeneralError
+            }
+        }
+    }
+
+    fn error_code(&self) -> &'static str {
+        match self {
+            Self::NotPaired(_) => "SESSION_NOT_PAIRED",
+            Self::Denied(_) => "SESSION_DENIED",
+            Self::PermissionDenied(_) => "SESSION_PERMISSION_DENIED",
+            Self::ConnectionFailed(_) => "SESSION_CONNECTION_FAILED",
+            Self::Transport(_) => "SESSION_TRANSPORT_FAILED",
+            Self::AuthenticationFailed(_) => "SESSION_AUTH_FAILED",
+            Self::SignatureInvalid => "SESSION_SIGNATURE_INVALID",
+            Self::TicketExpired => "SESSION_TICKET_EXPIRED",
+            Self::Timeout(_) => "SESSION_TIMEOUT",
+            Self::NotFound(_) => "SESSION_NOT_FOUND",
+            Self::MissingField(_) => "SESSION_MISSING_FIELD",
+            Self::InvalidState(_) => "SESSION_INVALID_STATE",
+            Self::Crypto(_) => "SESSION_CRYPTO_FAILED",
+            Self::Store(_) => "SESSION_STORE_FAILED",
+            Self::Unsupported(_) => "SESSION_UNSUPPORTED",
+        }
+    }
+}
+
+impl CommandError for ConfigError {
+    fn exit_code(&self) -> ExitCode {
+        match self {
+            Self::ReadError(_) => ExitCode::GeneralError,
+            _ => ExitCode::InvalidInput,
+        }
+    }
+
+    fn error_code(&self) -> &'static str {
+        match self {
+            Self::ProfileNotFound { .. } => "CONFIG_PROFILE_NOT_FOUND",
+            Self::InvalidOverride { .. } => "CONFIG_INVALID_OVERRIDE",
+            Self::ReadError(_) => "CONFIG_IO_FAILED",
+            Self::ParseError(_) | Self::SerializeError(_) | Self::ValidationError(_) => {
+                "CONFIG_INVALID"
+            }
+        }
+    }
+}
+
+/// Classify an arbitrary command failure
+///
+/// Walks the error chain looking for a known error type; anything else is
+/// reported as `GENERAL_ERROR` / `INTERNAL`.
+pub fn classify(err: &anyhow::Error) -> (ExitCode, &'static str) {
+    for cause in err.chain() {
+        if let Some(e) = cause.downcast_ref::<PairingError>() {
+            return (e.exit_code(), e.error_code());
+        }
+        if let Some(e) = cause.downcast_ref::<SessionError>() {
+            return (e.exit_code(), e.error_code());
+        }
+        if let Some(e) = cause.downcast_ref::<ConfigError>() {
+            return (e.exit_code(), e.error_code());
+        }
+    }
+    (ExitCode::GeneralError, INTERNAL_CODE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_pairing_error_mapping() {
+        let e = PairingError::InvalidProof;
+        assert_eq!(e.exit_code(), ExitCode::AuthenticationFailed);
+        assert_eq!(e.error_code(), "PAIRING_INVALID_PROOF");
+
+        let e = PairingError::Transport("refused".to_string());
+        assert_eq!(e.exit_code(), ExitCode::ConnectionFailed);
+
+        let e = PairingError::NotPaired("abc".to_string());
+        assert_eq!(e.exit_code(), ExitCode::NotPaired);
+    }
+
+    #[test]
+    fn test_session_error_mapping() {
+        let e = SessionError::Timeout(Duration::from_secs(5));
+        assert_eq!(e.exit_code(), ExitCode::Timeout);
+        assert_eq!(e.error_code(), "SESSION_TIMEOUT");
+
+        let e = SessionError::Denied("busy".to_string());
+        assert_eq!(e.exit_code(), ExitCode::PermissionDenied);
+    }
+
+    #[test]
+    fn test_classify_walks_context_chain() {
+        let err = anyhow::Error::from(SessionError::NotPaired("abc".to_string()))
+            .context("starting session");
+        assert_eq!(classify(&err), (ExitCode::NotPaired, "SESSION_NOT_PAIRED"));
+
+        let err = anyhow::Error::from(ConfigError::ProfileNotFound {
+            name: "lab".to_string(),
+            available: "none".to_string(),
+        });
+        assert_eq!(classify(&err), (ExitCode::InvalidInput, "CONFIG_PROFILE_NOT_FOUND"));
+
+        let err = anyhow::Error::from(ConfigError::InvalidOverride {
+            key: "logging.nope".to_string(),
+            reason: "unknown key".to_string(),
+        });
+        assert_eq!(classify(&err), (ExitCode::InvalidInput, "CONFIG_INVALID_OVERRIDE"));
+
+        let err = anyhow::anyhow!("something odd");
+        assert_eq!(classify(&err), (ExitCode::GeneralError, INTERNAL_CODE));
+    }
+}
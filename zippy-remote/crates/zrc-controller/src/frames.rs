@@ -4,21 +4,24 @@
 //! - Receive frame data over dedicated QUIC stream
 //! - Decode frame metadata (dimensions, format, timestamp)
 //! - Save frames to file
+//! - Dump sampled frames as images with a manifest
 //! - Display frame statistics
 //!
 //! Requirements: 6.1-6.7
 
 use std::collections::VecDeque;
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use prost::Message;
+use serde::Serialize;
 use thiserror::Error;
 use tokio::sync::{mpsc, RwLock};
 
+use zrc_core::quic_mux::FramePacketV1;
 use zrc_proto::v1::{FrameFormatV1, FrameMetadataV1};
 
 /// Frame reception errors
@@ -289,13 +292,8 @@ impl FrameReceiver {
 
     /// Try to receive a frame without blocking
     pub fn try_recv(&mut self) -> Option<ReceivedFrame> {
-        match self.rx.try_recv() {
-            Ok(frame) => {
-                // We can't update stats synchronously here without blocking
-                Some(frame)
-            }
-            Err(_) => None,
-        }
+        // We can't update stats synchronously here without blocking
+        self.rx.try_recv().ok()
     }
 
     /// Get current statistics
@@ -412,11 +410,10 @@ impl FrameStatsCollector {
         let bandwidth: f64 = recent.iter().map(|(_, size)| *size as f64).sum::<f64>()
             / self.rate_window.as_secs_f64();
 
-        let avg_frame_size = if self.frames_received > 0 {
-            self.bytes_received / self.frames_received
-        } else {
-            0
-        };
+        let avg_frame_size = self
+            .bytes_received
+            .checked_div(self.frames_received)
+            .unwrap_or(0);
 
         FrameStats {
             frames_received: self.frames_received,
@@ -572,6 +569,231 @@ impl FrameHeader {
     }
 }
 
+/// `FramePacketV1::format` value for BGRA pixels
+pub const PACKET_FORMAT_BGRA: u8 = 1;
+
+/// Image format for dumped frames
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DumpFormat {
+    /// Binary PPM (P6), no extra dependencies
+    #[default]
+    Ppm,
+    /// PNG (requires the `png` feature)
+    Png,
+}
+
+impl DumpFormat {
+    /// File extension for this format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Ppm => "ppm",
+            Self::Png => "png",
+        }
+    }
+}
+
+impl std::str::FromStr for DumpFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "ppm" => Ok(Self::Ppm),
+            "png" => Ok(Self::Png),
+            _ => Err(format!("Unknown format: {s}. Use 'ppm' or 'png'")),
+        }
+    }
+}
+
+/// Manifest entry describing one dumped frame
+#[derive(Debug, Clone, Serialize)]
+pub struct DumpedFrame {
+    /// Index of the frame in the received stream (0-based)
+    pub index: u64,
+    /// File name relative to the dump directory
+    pub file: String,
+    /// Frame width in pixels
+    pub width: u32,
+    /// Frame height in pixels
+    pub height: u32,
+    /// Row stride in bytes as received
+    pub stride: u32,
+    /// Packet pixel format as received (1 = BGRA)
+    pub format: u8,
+    /// When the frame was received (RFC 3339)
+    pub timestamp: String,
+}
+
+/// Writes sampled `FramePacketV1`s to numbered image files plus a `manifest.json`
+/// Requirements: 6.3
+pub struct FrameDumper {
+    /// Output directory
+    dir: PathBuf,
+    /// Image format
+    format: DumpFormat,
+    /// Save every K-th received frame
+    every: u64,
+    /// Stop after this many frames have been saved
+    max_frames: Option<u64>,
+    /// Frames received so far
+    received: u64,
+    /// Manifest entries for saved frames
+    entries: Vec<DumpedFrame>,
+}
+
+impl FrameDumper {
+    /// Create a dumper writing into `dir`, creating it if needed
+    pub fn new(
+        dir: impl AsRef<Path>,
+        format: DumpFormat,
+        every: u64,
+        max_frames: Option<u64>,
+    ) -> Result<Self, FrameError> {
+        if every == 0 {
+            return Err(FrameError::InvalidFrame("--every must be at least 1".to_string()));
+        }
+        #[cfg(not(feature = "png"))]
+        if format == DumpFormat::Png {
+            return Err(FrameError::InvalidFrame(
+                "PNG support not enabled. Rebuild with --features png".to_string(),
+            ));
+        }
+
+        let dir = dir.as_ref().to_path_buf();
+        std::fs::create_dir_all(&dir)?;
+
+        Ok(Self {
+            dir,
+            format,
+            every,
+            max_frames,
+            received: 0,
+            entries: Vec::new(),
+        })
+    }
+
+    /// Whether the frame limit has been reached
+    pub fn is_done(&self) -> bool {
+        self.max_frames
+            .is_some_and(|max| self.entries.len() as u64 >= max)
+    }
+
+    /// Number of frames written so far
+    pub fn frames_saved(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Offer a received frame; returns whether it was written
+    pub fn push(&mut self, pkt: &FramePacketV1) -> Result<bool, FrameError> {
+        let index = self.received;
+        self.received += 1;
+
+        if self.is_done() || !index.is_multiple_of(self.every) {
+            return Ok(false);
+        }
+
+        let rgb = bgra_to_rgb(pkt)?;
+        let file = format!("frame_{:06}.{}", index, self.format.extension());
+        let path = self.dir.join(&file);
+
+        match self.format {
+            DumpFormat::Ppm => write_ppm(&path, pkt.width, pkt.height, &rgb)?,
+            DumpFormat::Png => write_png(&path, pkt.width, pkt.height, &rgb)?,
+        }
+
+        self.entries.push(DumpedFrame {
+            index,
+            file,
+            width: pkt.width,
+            height: pkt.height,
+            stride: pkt.stride,
+            format: pkt.format,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        });
+        Ok(true)
+    }
+
+    /// Write `manifest.json` and return the number of frames saved
+    pub fn finish(self) -> Result<usize, FrameError> {
+        let manifest = serde_json::to_string_pretty(&self.entries)
+            .map_err(|e| FrameError::InvalidFrame(format!("Manifest serialization failed: {e}")))?;
+        std::fs::write(self.dir.join("manifest.json"), manifest)?;
+        Ok(self.entries.len())
+    }
+}
+
+/// Convert a BGRA `FramePacketV1` to tightly packed RGB, honoring the row stride
+pub fn bgra_to_rgb(pkt: &FramePacketV1) -> Result<Vec<u8>, FrameError> {
+    if pkt.format != PACKET_FORMAT_BGRA {
+        return Err(FrameError::InvalidFrame(format!(
+            "Unsupported packet format {}; only BGRA (1) can be converted",
+            pkt.format
+        )));
+    }
+
+    let width = pkt.width as usize;
+    let height = pkt.height as usize;
+    let row_bytes = width * 4;
+    let stride = pkt.stride as usize;
+    if stride < row_bytes {
+        return Err(FrameError::InvalidFrame(format!(
+            "Stride {stride} is smaller than row size {row_bytes}"
+        )));
+    }
+    let needed = if height == 0 { 0 } else { stride * (height - 1) + row_bytes };
+    if pkt.pixels.len() < needed {
+        return Err(FrameError::InvalidFrame(format!(
+            "Frame has {} bytes, expected at least {needed}",
+            pkt.pixels.len()
+        )));
+    }
+
+    let mut rgb = Vec::with_capacity(width * height * 3);
+    for row in 0..height {
+        let start = row * stride;
+        for px in pkt.pixels[start..start + row_bytes].chunks_exact(4) {
+            rgb.extend_from_slice(&[px[2], px[1], px[0]]);
+        }
+    }
+    Ok(rgb)
+}
+
+/// Swizzle BGRA pixels into RGBA, the conversion `zrc-viewer` runs before presenting
+///
+/// Converts as many whole pixels as fit in both buffers. Row padding is treated
+/// like pixel data, so `dst` keeps the source stride.
+pub fn bgra_to_rgba(src: &[u8], dst: &mut [u8]) {
+    for (s, d) in src.chunks_exact(4).zip(dst.chunks_exact_mut(4)) {
+        d[0] = s[2];
+        d[1] = s[1];
+        d[2] = s[0];
+        d[3] = s[3];
+    }
+}
+
+/// Write packed RGB as a binary PPM (P6) image
+fn write_ppm(path: &Path, width: u32, height: u32, rgb: &[u8]) -> Result<(), FrameError> {
+    let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    write!(file, "P6\n{} {}\n255\n", width, height)?;
+    file.write_all(rgb)?;
+    file.flush()?;
+    Ok(())
+}
+
+/// Write packed RGB as a PNG image
+#[cfg(feature = "png")]
+fn write_png(path: &Path, width: u32, height: u32, rgb: &[u8]) -> Result<(), FrameError> {
+    image::save_buffer(path, rgb, width, height, image::ColorType::Rgb8)
+        .map_err(|e| FrameError::InvalidFrame(format!("PNG encoding failed: {e}")))
+}
+
+/// Write packed RGB as a PNG image (stub when feature not enabled)
+#[cfg(not(feature = "png"))]
+fn write_png(_path: &Path, _width: u32, _height: u32, _rgb: &[u8]) -> Result<(), FrameError> {
+    Err(FrameError::InvalidFrame(
+        "PNG support not enabled. Rebuild with --features png".to_string(),
+    ))
+}
+
 /// Parse frame metadata from protobuf bytes
 /// Requirements: 6.2
 pub fn parse_frame_metadata(data: &[u8]) -> Result<FrameMetadata, FrameError> {
@@ -773,4 +995,80 @@ mod tests {
         assert_eq!(stats.keyframes, 1);
         assert_eq!(stats.resolution, Some((1920, 1080)));
     }
+
+    fn bgra_packet(width: u32, height: u32, stride: u32) -> FramePacketV1 {
+        let mut pixels = vec![0xEEu8; (stride * height) as usize];
+        for row in 0..height as usize {
+            for col in 0..width as usize {
+                let i = row * stride as usize + col * 4;
+                pixels[i..i + 4].copy_from_slice(&[10, 20, 30, 255]); // B, G, R, A
+            }
+        }
+        FramePacketV1 { width, height, stride, format: PACKET_FORMAT_BGRA, pixels }
+    }
+
+    #[test]
+    fn test_bgra_to_rgb_honors_stride() {
+        // 2x2 frame with 4 bytes of row padding
+        let pkt = bgra_packet(2, 2, 12);
+        let rgb = bgra_to_rgb(&pkt).unwrap();
+        assert_eq!(rgb, vec![30, 20, 10, 30, 20, 10, 30, 20, 10, 30, 20, 10]);
+    }
+
+    #[test]
+    fn test_bgra_to_rgba() {
+        let src = [10, 20, 30, 255, 1, 2, 3, 4];
+        let mut dst = [0u8; 8];
+        bgra_to_rgba(&src, &mut dst);
+        assert_eq!(dst, [30, 20, 10, 255, 3, 2, 1, 4]);
+
+        // A short destination converts only the pixels that fit
+        let mut short = [0u8; 6];
+        bgra_to_rgba(&src, &mut short);
+        assert_eq!(short, [30, 20, 10, 255, 0, 0]);
+    }
+
+    #[test]
+    fn test_bgra_to_rgb_rejects_bad_input() {
+        let mut pkt = bgra_packet(2, 2, 8);
+        pkt.format = 2;
+        assert!(bgra_to_rgb(&pkt).is_err());
+
+        let pkt = FramePacketV1 { width: 2, height: 2, stride: 4, format: 1, pixels: vec![0; 16] };
+        assert!(bgra_to_rgb(&pkt).is_err());
+
+        let pkt = FramePacketV1 { width: 2, height: 2, stride: 8, format: 1, pixels: vec![0; 10] };
+        assert!(bgra_to_rgb(&pkt).is_err());
+    }
+
+    #[test]
+    fn test_frame_dumper_sampling_and_manifest() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let out = temp_dir.path().join("dump");
+        let mut dumper = FrameDumper::new(&out, DumpFormat::Ppm, 2, Some(2)).unwrap();
+
+        let pkt = bgra_packet(2, 1, 8);
+        let saved: Vec<bool> = (0..6).map(|_| dumper.push(&pkt).unwrap()).collect();
+        assert_eq!(saved, vec![true, false, true, false, false, false]);
+        assert!(dumper.is_done());
+        assert_eq!(dumper.finish().unwrap(), 2);
+
+        let ppm = std::fs::read(out.join("frame_000002.ppm")).unwrap();
+        assert_eq!(ppm, b"P6\n2 1\n255\n\x1e\x14\x0a\x1e\x14\x0a".to_vec());
+
+        let manifest: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(out.join("manifest.json")).unwrap()).unwrap();
+        let entries = manifest.as_array().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1]["index"], 2);
+        assert_eq!(entries[1]["file"], "frame_000002.ppm");
+        assert_eq!(entries[1]["stride"], 8);
+        assert_eq!(entries[1]["format"], 1);
+    }
+
+    #[test]
+    fn test_frame_dumper_rejects_zero_every() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        assert!(FrameDumper::new(temp_dir.path(), DumpFormat::Ppm, 0, None).is_err());
+    }
 }
@@ -5,15 +5,19 @@
 //! - X25519 key exchange key generation and management
 //! - Secure key storage (OS keystore or file-based)
 //! - Identity persistence across restarts
+//! - Passphrase-protected export/import of the private keys
 
 use std::fs;
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 use std::time::SystemTime;
 
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use thiserror::Error;
+use zeroize::{Zeroize, Zeroizing};
 
 use crate::config::IdentityConfig;
 
@@ -43,6 +47,15 @@ pub enum IdentityError {
 
     #[error("Invalid key data: {0}")]
     InvalidKeyData(String),
+
+    #[error("Identity already exists (use --force to overwrite)")]
+    AlreadyExists,
+
+    #[error("Decryption failed: wrong passphrase or corrupted bundle")]
+    Decryption,
+
+    #[error("Invalid passphrase: {0}")]
+    InvalidPassphrase(String),
 }
 
 /// Operator identity information for display
@@ -71,6 +84,167 @@ pub struct IdentityExport {
     pub created_at: String,
 }
 
+/// Minimum passphrase length for encrypted identity bundles
+pub const MIN_PASSPHRASE_LEN: usize = 8;
+
+/// Passphrase-encrypted private identity bundle
+///
+/// The bundle key is derived from the passphrase with Argon2id and the
+/// serialized private keys are sealed with XChaCha20-Poly1305, using the
+/// operator ID as associated data.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptedIdentityBundle {
+    /// Bundle format version
+    pub version: u32,
+    /// Operator ID of the contained identity (public)
+    pub operator_id: String,
+    /// Key derivation function ("argon2id")
+    pub kdf: String,
+    /// Argon2 memory cost in KiB
+    pub m_cost: u32,
+    /// Argon2 iterations
+    pub t_cost: u32,
+    /// Argon2 parallelism
+    pub p_cost: u32,
+    /// KDF salt (hex encoded)
+    pub salt: String,
+    /// XChaCha20-Poly1305 nonce (24 bytes, hex encoded)
+    pub nonce: String,
+    /// Encrypted identity (hex encoded)
+    pub ciphertext: String,
+}
+
+impl EncryptedIdentityBundle {
+    const CURRENT_VERSION: u32 = 1;
+    const KDF_ARGON2ID: &'static str = "argon2id";
+    const DEFAULT_M_COST: u32 = 19 * 1024;
+    const DEFAULT_T_COST: u32 = 2;
+    const DEFAULT_P_COST: u32 = 1;
+    /// Accepted Argon2 costs when opening a bundle. The floor keeps a forged
+    /// bundle from weakening the KDF; the ceiling keeps it from exhausting
+    /// memory or CPU before the passphrase is even checked.
+    const M_COST_RANGE: std::ops::RangeInclusive<u32> = Self::DEFAULT_M_COST..=1024 * 1024;
+    const T_COST_RANGE: std::ops::RangeInclusive<u32> = Self::DEFAULT_T_COST..=16;
+    const P_COST_RANGE: std::ops::RangeInclusive<u32> = Self::DEFAULT_P_COST..=16;
+
+    /// Derive the bundle encryption key from a passphrase
+    fn derive_key(
+        passphrase: &str,
+        salt: &[u8],
+        m_cost: u32,
+        t_cost: u32,
+        p_cost: u32,
+    ) -> Result<Zeroizing<[u8; 32]>, IdentityError> {
+        let params = argon2::Params::new(m_cost, t_cost, p_cost, Some(32))
+            .map_err(|e| IdentityError::InvalidKeyData(format!("Invalid KDF parameters: {e}")))?;
+        let argon = argon2::Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params);
+
+        let mut key = Zeroizing::new([0u8; 32]);
+        argon
+            .hash_password_into(passphrase.as_bytes(), salt, &mut key[..])
+            .map_err(|e| IdentityError::KeyGeneration(format!("Key derivation failed: {e}")))?;
+        Ok(key)
+    }
+
+    /// Encrypt a stored identity under a passphrase
+    fn seal(stored: &StoredIdentity, operator_id: &str, passphrase: &str) -> Result<Self, IdentityError> {
+        if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+            return Err(IdentityError::InvalidPassphrase(format!(
+                "must be at least {MIN_PASSPHRASE_LEN} characters"
+            )));
+        }
+
+        let mut salt = [0u8; 16];
+        let mut nonce = [0u8; 24];
+        getrandom::getrandom(&mut salt).map_err(|e| IdentityError::KeyGeneration(e.to_string()))?;
+        getrandom::getrandom(&mut nonce).map_err(|e| IdentityError::KeyGeneration(e.to_string()))?;
+
+        let key = Self::derive_key(
+            passphrase,
+            &salt,
+            Self::DEFAULT_M_COST,
+            Self::DEFAULT_T_COST,
+            Self::DEFAULT_P_COST,
+        )?;
+        let plaintext = Zeroizing::new(
+            serde_json::to_vec(stored).map_err(|e| IdentityError::Serialization(e.to_string()))?,
+        );
+
+        let cipher = XChaCha20Poly1305::new(Key::from_slice(&key[..]));
+        let ciphertext = cipher
+            .encrypt(
+                XNonce::from_slice(&nonce),
+                Payload { msg: &plaintext, aad: operator_id.as_bytes() },
+            )
+            .map_err(|_| IdentityError::Save("Encryption failed".to_string()))?;
+
+        Ok(Self {
+            version: Self::CURRENT_VERSION,
+            operator_id: operator_id.to_string(),
+            kdf: Self::KDF_ARGON2ID.to_string(),
+            m_cost: Self::DEFAULT_M_COST,
+            t_cost: Self::DEFAULT_T_COST,
+            p_cost: Self::DEFAULT_P_COST,
+            salt: hex::encode(salt),
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(ciphertext),
+        })
+    }
+
+    /// Decrypt the stored identity with a passphrase
+    fn open(&self, passphrase: &str) -> Result<StoredIdentity, IdentityError> {
+        if self.version != Self::CURRENT_VERSION {
+            return Err(IdentityError::InvalidKeyData(format!(
+                "Unsupported bundle version {}",
+                self.version
+            )));
+        }
+        if self.kdf != Self::KDF_ARGON2ID {
+            return Err(IdentityError::InvalidKeyData(format!("Unsupported KDF '{}'", self.kdf)));
+        }
+        for (name, value, range) in [
+            ("m_cost", self.m_cost, Self::M_COST_RANGE),
+            ("t_cost", self.t_cost, Self::T_COST_RANGE),
+            ("p_cost", self.p_cost, Self::P_COST_RANGE),
+        ] {
+            if !range.contains(&value) {
+                return Err(IdentityError::InvalidKeyData(format!(
+                    "KDF {name} {value} outside {}..={}",
+                    range.start(),
+                    range.end()
+                )));
+            }
+        }
+
+        let decode = |field: &str, value: &str| {
+            hex::decode(value)
+                .map_err(|e| IdentityError::InvalidKeyData(format!("Invalid {field} hex: {e}")))
+        };
+        let salt = decode("salt", &self.salt)?;
+        let nonce = decode("nonce", &self.nonce)?;
+        let ciphertext = decode("ciphertext", &self.ciphertext)?;
+        if nonce.len() != 24 {
+            return Err(IdentityError::InvalidKeyData(format!(
+                "Invalid nonce length: expected 24, got {}",
+                nonce.len()
+            )));
+        }
+
+        let key = Self::derive_key(passphrase, &salt, self.m_cost, self.t_cost, self.p_cost)?;
+        let cipher = XChaCha20Poly1305::new(Key::from_slice(&key[..]));
+        let plaintext = Zeroizing::new(
+            cipher
+                .decrypt(
+                    XNonce::from_slice(&nonce),
+                    Payload { msg: &ciphertext, aad: self.operator_id.as_bytes() },
+                )
+                .map_err(|_| IdentityError::Decryption)?,
+        );
+
+        serde_json::from_slice(&plaintext).map_err(|e| IdentityError::Serialization(e.to_string()))
+    }
+}
+
 /// Serializable identity data for file storage
 #[derive(Serialize, Deserialize)]
 struct StoredIdentity {
@@ -105,6 +279,13 @@ impl StoredIdentity {
     }
 }
 
+impl Drop for StoredIdentity {
+    fn drop(&mut self) {
+        self.sign_seed.zeroize();
+        self.kex_secret.zeroize();
+    }
+}
+
 /// Key storage backend trait (internal)
 trait KeyStore: Send + Sync {
     /// Store identity keys
@@ -328,7 +509,7 @@ impl IdentityManager {
         let signing_key = ed25519_dalek::SigningKey::generate(&mut rng);
         
         // Generate X25519 key exchange key
-        let kex_secret = x25519_dalek::StaticSecret::random_from_rng(&mut rng);
+        let kex_secret = x25519_dalek::StaticSecret::random_from_rng(rng);
         
         let created_at = SystemTime::now();
         let operator_id = Self::compute_operator_id(&signing_key);
@@ -353,7 +534,7 @@ impl IdentityManager {
 
         match config.key_store.as_str() {
             "os" => Box::new(OsKeyStore::new(path)),
-            "file" | _ => Box::new(FileKeyStore::new(path)),
+            _ => Box::new(FileKeyStore::new(path)),
         }
     }
 
@@ -365,7 +546,7 @@ impl IdentityManager {
         let signing_key = ed25519_dalek::SigningKey::generate(&mut rng);
         
         // Generate X25519 key exchange key
-        let kex_secret = x25519_dalek::StaticSecret::random_from_rng(&mut rng);
+        let kex_secret = x25519_dalek::StaticSecret::random_from_rng(rng);
         
         let created_at = SystemTime::now();
         let operator_id = Self::compute_operator_id(&signing_key);
@@ -400,7 +581,8 @@ impl IdentityManager {
             )));
         }
         
-        let mut sign_seed = [0u8; 32];
+        let sign_seed_bytes = Zeroizing::new(sign_seed_bytes);
+        let mut sign_seed = Zeroizing::new([0u8; 32]);
         sign_seed.copy_from_slice(&sign_seed_bytes);
         let signing_key = ed25519_dalek::SigningKey::from_bytes(&sign_seed);
 
@@ -415,9 +597,10 @@ impl IdentityManager {
             )));
         }
         
-        let mut kex_arr = [0u8; 32];
+        let kex_bytes = Zeroizing::new(kex_bytes);
+        let mut kex_arr = Zeroizing::new([0u8; 32]);
         kex_arr.copy_from_slice(&kex_bytes);
-        let kex_secret = x25519_dalek::StaticSecret::from(kex_arr);
+        let kex_secret = x25519_dalek::StaticSecret::from(*kex_arr);
 
         let created_at = stored.parse_created_at()?;
         let operator_id = Self::compute_operator_id(&signing_key);
@@ -469,6 +652,17 @@ impl IdentityManager {
         self.signing_key.sign(data).to_bytes()
     }
 
+    /// Open the audit log at `path`, signing entries with the operator key
+    pub fn audit_logger(
+        &self,
+        path: PathBuf,
+    ) -> Result<zrc_security::audit::AuditLogger, zrc_security::SecurityError> {
+        zrc_security::audit::AuditLogger::new(
+            self.signing_key.clone(),
+            Box::new(zrc_security::audit::FileAuditLogWriter::new(path)),
+        )
+    }
+
     /// Perform X25519 Diffie-Hellman key exchange
     pub fn key_exchange(&self, peer_kex_pub: &[u8; 32]) -> [u8; 32] {
         let peer_pub = x25519_dalek::PublicKey::from(*peer_kex_pub);
@@ -496,6 +690,71 @@ impl IdentityManager {
         Ok(())
     }
 
+    /// Export the private identity as a passphrase-encrypted bundle
+    ///
+    /// The bundle can be restored on another machine with [`Self::import_encrypted`].
+    pub fn export_encrypted(&self, path: &Path, passphrase: &str) -> Result<(), IdentityError> {
+        let stored = StoredIdentity::new(
+            &self.signing_key.to_bytes(),
+            self.kex_secret.as_bytes(),
+            self.created_at,
+        );
+        let bundle = EncryptedIdentityBundle::seal(&stored, &self.operator_id, passphrase)?;
+        let json = serde_json::to_string_pretty(&bundle)
+            .map_err(|e| IdentityError::Serialization(e.to_string()))?;
+
+        fs::write(path, json)?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+        }
+
+        Ok(())
+    }
+
+    /// Import a passphrase-encrypted bundle into the configured key store
+    ///
+    /// Refuses to replace an existing identity unless `force` is set.
+    pub async fn import_encrypted(
+        config: &IdentityConfig,
+        path: &Path,
+        passphrase: &str,
+        force: bool,
+    ) -> Result<Self, IdentityError> {
+        let key_store = Self::create_key_store(config);
+        if key_store.exists() && !force {
+            return Err(IdentityError::AlreadyExists);
+        }
+
+        let contents = fs::read_to_string(path)?;
+        let bundle: EncryptedIdentityBundle = serde_json::from_str(&contents)
+            .map_err(|e| IdentityError::Serialization(e.to_string()))?;
+        let stored = bundle.open(passphrase)?;
+        let identity = Self::from_stored(stored, key_store)?;
+
+        // Check before persisting so a bad bundle never replaces the current identity
+        if identity.operator_id != bundle.operator_id {
+            return Err(IdentityError::InvalidKeyData(format!(
+                "Operator ID mismatch: bundle says {}, keys derive {}",
+                bundle.operator_id, identity.operator_id
+            )));
+        }
+        identity.key_store.store(&StoredIdentity::new(
+            &identity.signing_key.to_bytes(),
+            identity.kex_secret.as_bytes(),
+            identity.created_at,
+        ))?;
+
+        tracing::info!(
+            operator_id = %identity.operator_id,
+            "Imported operator identity"
+        );
+
+        Ok(identity)
+    }
+
     /// Rotate identity (warning: breaks existing pairings)
     ///
     /// This generates a completely new identity, invalidating all existing
@@ -505,7 +764,7 @@ impl IdentityManager {
         
         // Generate new keys
         self.signing_key = ed25519_dalek::SigningKey::generate(&mut rng);
-        self.kex_secret = x25519_dalek::StaticSecret::random_from_rng(&mut rng);
+        self.kex_secret = x25519_dalek::StaticSecret::random_from_rng(rng);
         self.operator_id = Self::compute_operator_id(&self.signing_key);
         self.created_at = SystemTime::now();
 
@@ -714,4 +973,152 @@ mod tests {
         store.delete().unwrap();
         assert!(!store.exists());
     }
+
+    #[tokio::test]
+    async fn test_encrypted_export_import_round_trip() {
+        let source_dir = TempDir::new().unwrap();
+        let identity = IdentityManager::init(&test_config(&source_dir)).await.unwrap();
+        let bundle_path = source_dir.path().join("identity.bundle");
+        identity.export_encrypted(&bundle_path, "correct horse battery").unwrap();
+
+        // The bundle must not contain the raw keys
+        let bundle = fs::read_to_string(&bundle_path).unwrap();
+        assert!(!bundle.contains(&hex::encode(identity.signing_key.to_bytes())));
+
+        let target_dir = TempDir::new().unwrap();
+        let imported = IdentityManager::import_encrypted(
+            &test_config(&target_dir),
+            &bundle_path,
+            "correct horse battery",
+            false,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(imported.operator_id(), identity.operator_id());
+        assert_eq!(imported.sign_pub(), identity.sign_pub());
+        assert_eq!(imported.kex_pub(), identity.kex_pub());
+
+        // The imported identity is persisted in the new key store
+        let reloaded = IdentityManager::init(&test_config(&target_dir)).await.unwrap();
+        assert_eq!(reloaded.operator_id(), identity.operator_id());
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_import_wrong_passphrase() {
+        let temp_dir = TempDir::new().unwrap();
+        let identity = IdentityManager::new_ephemeral();
+        let bundle_path = temp_dir.path().join("identity.bundle");
+        identity.export_encrypted(&bundle_path, "correct horse battery").unwrap();
+
+        let result = IdentityManager::import_encrypted(
+            &test_config(&temp_dir),
+            &bundle_path,
+            "wrong horse battery",
+            false,
+        )
+        .await;
+        assert!(matches!(result, Err(IdentityError::Decryption)));
+        assert!(!temp_dir.path().join("identity.json").exists());
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_import_requires_force() {
+        let temp_dir = TempDir::new().unwrap();
+        let existing = IdentityManager::init(&test_config(&temp_dir)).await.unwrap();
+
+        let other = IdentityManager::new_ephemeral();
+        let bundle_path = temp_dir.path().join("identity.bundle");
+        other.export_encrypted(&bundle_path, "correct horse battery").unwrap();
+
+        let result = IdentityManager::import_encrypted(
+            &test_config(&temp_dir),
+            &bundle_path,
+            "correct horse battery",
+            false,
+        )
+        .await;
+        assert!(matches!(result, Err(IdentityError::AlreadyExists)));
+        let reloaded = IdentityManager::init(&test_config(&temp_dir)).await.unwrap();
+        assert_eq!(reloaded.operator_id(), existing.operator_id());
+
+        let imported = IdentityManager::import_encrypted(
+            &test_config(&temp_dir),
+            &bundle_path,
+            "correct horse battery",
+            true,
+        )
+        .await
+        .unwrap();
+        assert_eq!(imported.operator_id(), other.operator_id());
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_import_operator_mismatch_keeps_existing_identity() {
+        let temp_dir = TempDir::new().unwrap();
+        let existing = IdentityManager::init(&test_config(&temp_dir)).await.unwrap();
+
+        // Keys sealed under an operator ID they don't derive
+        let other = IdentityManager::new_ephemeral();
+        let stored = StoredIdentity::new(
+            &other.signing_key.to_bytes(),
+            other.kex_secret.as_bytes(),
+            other.created_at,
+        );
+        let bundle =
+            EncryptedIdentityBundle::seal(&stored, existing.operator_id(), "correct horse battery")
+                .unwrap();
+        let bundle_path = temp_dir.path().join("identity.bundle");
+        fs::write(&bundle_path, serde_json::to_string(&bundle).unwrap()).unwrap();
+
+        let result = IdentityManager::import_encrypted(
+            &test_config(&temp_dir),
+            &bundle_path,
+            "correct horse battery",
+            true,
+        )
+        .await;
+        assert!(matches!(result, Err(IdentityError::InvalidKeyData(_))));
+        let reloaded = IdentityManager::init(&test_config(&temp_dir)).await.unwrap();
+        assert_eq!(reloaded.operator_id(), existing.operator_id());
+    }
+
+    #[test]
+    fn test_encrypted_bundle_rejects_out_of_range_kdf_costs() {
+        let identity = IdentityManager::new_ephemeral();
+        let stored = StoredIdentity::new(
+            &identity.signing_key.to_bytes(),
+            identity.kex_secret.as_bytes(),
+            identity.created_at,
+        );
+        let bundle =
+            EncryptedIdentityBundle::seal(&stored, identity.operator_id(), "correct horse battery")
+                .unwrap();
+
+        // Rejected before the KDF runs: a huge m_cost would otherwise allocate it
+        for tamper in [
+            |b: &mut EncryptedIdentityBundle| b.m_cost = u32::MAX,
+            |b: &mut EncryptedIdentityBundle| b.m_cost = 8,
+            |b: &mut EncryptedIdentityBundle| b.t_cost = 1,
+            |b: &mut EncryptedIdentityBundle| b.t_cost = 1000,
+            |b: &mut EncryptedIdentityBundle| b.p_cost = 0,
+            |b: &mut EncryptedIdentityBundle| b.p_cost = 64,
+        ] {
+            let mut tampered = bundle.clone();
+            tamper(&mut tampered);
+            assert!(matches!(
+                tampered.open("correct horse battery"),
+                Err(IdentityError::InvalidKeyData(_))
+            ));
+        }
+        assert!(bundle.open("correct horse battery").is_ok());
+    }
+
+    #[test]
+    fn test_encrypted_export_rejects_short_passphrase() {
+        let temp_dir = TempDir::new().unwrap();
+        let identity = IdentityManager::new_ephemeral();
+        let result = identity.export_encrypted(&temp_dir.path().join("identity.bundle"), "short");
+        assert!(matches!(result, Err(IdentityError::InvalidPassphrase(_))));
+    }
 }
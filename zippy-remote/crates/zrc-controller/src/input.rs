@@ -4,12 +4,16 @@
 //! - Mouse movement, clicks, and scrolling
 //! - Keyboard key events (down/up)
 //! - Text string input
+//! - Typing a string as a paced sequence of character/key events
 //!
 //! Requirements: 5.1-5.7
 
+use std::time::Duration;
+
 use prost::Message;
 use serde::Serialize;
 use thiserror::Error;
+use tokio::sync::mpsc;
 
 use zrc_proto::v1::{InputEventTypeV1, InputEventV1};
 
@@ -85,6 +89,17 @@ impl KeyCode {
     }
 }
 
+impl KeyCode {
+    /// Enter/Return virtual key code
+    pub const ENTER: KeyCode = KeyCode(0x0D);
+    /// Tab virtual key code
+    pub const TAB: KeyCode = KeyCode(0x09);
+}
+
+/// Maximum encoded bytes queued on the control stream per batch while typing
+/// Requirements: 5.3
+pub const TYPE_BATCH_MAX_BYTES: usize = 1024;
+
 /// Input modifier keys bitmask
 /// Requirements: 5.2
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
@@ -157,13 +172,13 @@ impl InputValidator {
     pub fn validate_mouse_coords(x: i32, y: i32) -> Result<(), InputError> {
         // Coordinates should be non-negative for absolute positioning
         // Allow negative for relative movement
-        if x < -32768 || x > 32767 {
+        if !(-32768..=32767).contains(&x) {
             return Err(InputError::InvalidInput(format!(
                 "X coordinate {} out of range [-32768, 32767]",
                 x
             )));
         }
-        if y < -32768 || y > 32767 {
+        if !(-32768..=32767).contains(&y) {
             return Err(InputError::InvalidInput(format!(
                 "Y coordinate {} out of range [-32768, 32767]",
                 y
@@ -176,7 +191,7 @@ impl InputValidator {
     /// Requirements: 5.6
     pub fn validate_scroll_delta(delta: i32) -> Result<(), InputError> {
         // Scroll delta should be within reasonable bounds
-        if delta < -10000 || delta > 10000 {
+        if !(-10000..=10000).contains(&delta) {
             return Err(InputError::InvalidInput(format!(
                 "Scroll delta {} out of range [-10000, 10000]",
                 delta
@@ -344,6 +359,68 @@ impl InputEventBuilder {
             scroll_delta_y: 0,
         }
     }
+
+    /// Build the events for typing a string, one group per typed character
+    ///
+    /// Printable characters (including those outside the BMP) are sent whole
+    /// as KEY_CHAR text events. Newlines (`\n`, `\r`, `\r\n`) and tabs fall back
+    /// to Enter/Tab key presses; other control characters are rejected.
+    /// Requirements: 5.3
+    pub fn type_text(text: &str) -> Result<Vec<Vec<InputEventV1>>, InputError> {
+        let mut groups = Vec::new();
+        let mut chars = text.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            let group = match c {
+                '\r' | '\n' => {
+                    if c == '\r' && chars.peek() == Some(&'\n') {
+                        chars.next();
+                    }
+                    Self::key_press(KeyCode::ENTER)
+                }
+                '\t' => Self::key_press(KeyCode::TAB),
+                c if c.is_control() => {
+                    return Err(InputError::InvalidInput(format!(
+                        "Unsupported control character U+{:04X}",
+                        c as u32
+                    )));
+                }
+                c => vec![Self::text_input(c.encode_utf8(&mut [0u8; 4]))],
+            };
+            groups.push(group);
+        }
+
+        Ok(groups)
+    }
+
+    /// Build a key press (down + up) without modifiers
+    fn key_press(code: KeyCode) -> Vec<InputEventV1> {
+        vec![
+            Self::key_down(code, Modifiers::none()),
+            Self::key_up(code, Modifiers::none()),
+        ]
+    }
+}
+
+/// Encode events as length-delimited frames, split into batches of at most
+/// `max_bytes` (a single oversized event still gets its own batch)
+/// Requirements: 5.3
+pub fn batch_events(events: &[InputEventV1], max_bytes: usize) -> Vec<Vec<u8>> {
+    let mut batches = Vec::new();
+    let mut current = Vec::new();
+
+    for event in events {
+        let encoded = event.encode_length_delimited_to_vec();
+        if !current.is_empty() && current.len() + encoded.len() > max_bytes {
+            batches.push(std::mem::take(&mut current));
+        }
+        current.extend_from_slice(&encoded);
+    }
+    if !current.is_empty() {
+        batches.push(current);
+    }
+
+    batches
 }
 
 /// Input command builder and sender
@@ -353,6 +430,9 @@ pub struct InputCommands {
     session_id: Option<String>,
     /// Whether we have control permission
     has_control_permission: bool,
+    /// The session's input channel, carrying batches of length-delimited
+    /// events to the control stream
+    input_tx: Option<mpsc::UnboundedSender<Vec<u8>>>,
 }
 
 impl InputCommands {
@@ -361,6 +441,7 @@ impl InputCommands {
         Self {
             session_id: None,
             has_control_permission: false,
+            input_tx: None,
         }
     }
 
@@ -369,9 +450,16 @@ impl InputCommands {
         Self {
             session_id: Some(session_id),
             has_control_permission: true,
+            input_tx: None,
         }
     }
 
+    /// Send event batches over the session's input channel
+    pub fn with_input_channel(mut self, input_tx: mpsc::UnboundedSender<Vec<u8>>) -> Self {
+        self.input_tx = Some(input_tx);
+        self
+    }
+
     /// Set the current session
     pub fn set_session(&mut self, session_id: String, has_control: bool) {
         self.session_id = Some(session_id);
@@ -412,6 +500,16 @@ impl InputCommands {
         event.encode_to_vec()
     }
 
+    /// Queue a batch on the session's input channel, if one is attached
+    fn send_batch(&self, batch: Vec<u8>) -> Result<(), InputError> {
+        let Some(input_tx) = &self.input_tx else {
+            return Ok(());
+        };
+        input_tx
+            .send(batch)
+            .map_err(|_| InputError::SendFailed("session input channel closed".to_string()))
+    }
+
     /// Send mouse move
     /// Requirements: 5.1
     pub async fn mouse_move(
@@ -640,6 +738,54 @@ impl InputCommands {
             details: format!("Text input: {} chars", text.len()),
         })
     }
+
+    /// Type a string as a sequence of character/key events
+    ///
+    /// With a non-zero `delay`, each character is sent on its own and followed
+    /// by the delay. Otherwise events are batched up to `TYPE_BATCH_MAX_BYTES`,
+    /// yielding between batches so the control stream can drain.
+    /// Requirements: 5.3, 5.5
+    pub async fn type_text(
+        &self,
+        session_id: Option<&str>,
+        text: &str,
+        delay: Duration,
+    ) -> Result<InputResult, InputError> {
+        let session = self.require_session(session_id)?;
+        self.require_control_permission()?;
+        InputValidator::validate_text(text)?;
+
+        let groups = InputEventBuilder::type_text(text)?;
+        let event_count: usize = groups.iter().map(Vec::len).sum();
+
+        if delay.is_zero() {
+            let events: Vec<InputEventV1> = groups.into_iter().flatten().collect();
+            for batch in batch_events(&events, TYPE_BATCH_MAX_BYTES) {
+                self.send_batch(batch)?;
+                tokio::task::yield_now().await;
+            }
+        } else {
+            for (i, group) in groups.iter().enumerate() {
+                for batch in batch_events(group, TYPE_BATCH_MAX_BYTES) {
+                    self.send_batch(batch)?;
+                }
+                if i + 1 < groups.len() {
+                    tokio::time::sleep(delay).await;
+                }
+            }
+        }
+
+        Ok(InputResult {
+            success: true,
+            session_id: session,
+            input_type: "type".to_string(),
+            details: format!(
+                "Typed {} chars ({} events)",
+                text.chars().count(),
+                event_count
+            ),
+        })
+    }
 }
 
 impl Default for InputCommands {
@@ -815,4 +961,109 @@ mod tests {
         assert!(result.success);
         assert_eq!(result.input_type, "text");
     }
+
+    #[test]
+    fn test_type_text_plain_and_non_bmp() {
+        let groups = InputEventBuilder::type_text("a\u{e9}\u{1F600}").unwrap();
+        assert_eq!(groups.len(), 3);
+        for group in &groups {
+            assert_eq!(group.len(), 1);
+            assert_eq!(group[0].event_type, InputEventTypeV1::KeyChar as i32);
+        }
+        assert_eq!(groups[0][0].text, "a");
+        assert_eq!(groups[1][0].text, "\u{e9}");
+        // Characters outside the BMP are sent whole, not as surrogate halves
+        assert_eq!(groups[2][0].text, "\u{1F600}");
+    }
+
+    #[test]
+    fn test_type_text_newlines_and_tabs() {
+        let groups = InputEventBuilder::type_text("a\r\nb\n\t").unwrap();
+        assert_eq!(groups.len(), 5);
+
+        let enter = &groups[1];
+        assert_eq!(enter.len(), 2);
+        assert_eq!(enter[0].event_type, InputEventTypeV1::KeyDown as i32);
+        assert_eq!(enter[0].key_code, KeyCode::ENTER.value());
+        assert_eq!(enter[1].event_type, InputEventTypeV1::KeyUp as i32);
+
+        assert_eq!(groups[3][0].key_code, KeyCode::ENTER.value());
+        assert_eq!(groups[4][0].key_code, KeyCode::TAB.value());
+    }
+
+    #[test]
+    fn test_type_text_rejects_control_chars() {
+        assert!(matches!(
+            InputEventBuilder::type_text("a\u{7}b"),
+            Err(InputError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_batch_events_respects_limit() {
+        let events: Vec<InputEventV1> = (0..100)
+            .map(|_| InputEventBuilder::text_input("x"))
+            .collect();
+        let frame_len = events[0].encode_length_delimited_to_vec().len();
+        let batches = batch_events(&events, frame_len * 10);
+
+        assert_eq!(batches.len(), 10);
+        assert!(batches.iter().all(|b| b.len() <= frame_len * 10));
+        assert_eq!(batches.iter().map(Vec::len).sum::<usize>(), frame_len * 100);
+
+        // An event larger than the limit still gets sent
+        assert_eq!(batch_events(&events[..1], 1).len(), 1);
+        assert!(batch_events(&[], 10).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_type_text_command() {
+        let cmds = InputCommands::with_session("session-1".to_string());
+        let result = cmds
+            .type_text(None, "hi\n", Duration::from_millis(1))
+            .await
+            .unwrap();
+        assert_eq!(result.input_type, "type");
+        assert_eq!(result.details, "Typed 3 chars (4 events)");
+
+        let cmds = InputCommands::new();
+        assert!(matches!(
+            cmds.type_text(None, "hi", Duration::ZERO).await,
+            Err(InputError::NoSession)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_type_text_sends_batches_over_input_channel() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let cmds = InputCommands::with_session("session-1".to_string()).with_input_channel(tx);
+
+        // Batched: everything fits in one batch
+        cmds.type_text(None, "hi\n", Duration::ZERO).await.unwrap();
+        let batch = rx.try_recv().unwrap();
+        assert!(rx.try_recv().is_err());
+        let expected: Vec<InputEventV1> =
+            InputEventBuilder::type_text("hi\n").unwrap().into_iter().flatten().collect();
+        let mut buf = batch.as_slice();
+        let mut sent = Vec::new();
+        while !buf.is_empty() {
+            sent.push(InputEventV1::decode_length_delimited(&mut buf).unwrap());
+        }
+        assert_eq!(sent, expected);
+
+        // Paced: one batch per typed character
+        cmds.type_text(None, "hi\n", Duration::from_millis(1)).await.unwrap();
+        let mut batches = 0;
+        while rx.try_recv().is_ok() {
+            batches += 1;
+        }
+        assert_eq!(batches, 3);
+
+        // A closed channel fails the command instead of dropping the text
+        drop(rx);
+        assert!(matches!(
+            cmds.type_text(None, "hi", Duration::ZERO).await,
+            Err(InputError::SendFailed(_))
+        ));
+    }
 }
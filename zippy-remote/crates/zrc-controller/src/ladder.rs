@@ -0,0 +1,207 @@
+//! Transport ladder decision log
+//!
+//! Records what each transport did while the controller tried to deliver a
+//! message, so that "why did it use relay?" can be answered from the CLI
+//! (`--verbose`/`--debug`) or rendered by a UI as connection info.
+//!
+//! Requirements: 8.3, 8.7
+
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use serde::{Serialize, Serializer};
+
+use crate::pairing::{PairingError, TransportPreference};
+
+/// Outcome of a single transport attempt
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AttemptOutcome {
+    /// The transport delivered the message
+    Success,
+    /// The transport was tried and failed
+    Failed,
+    /// The transport was not tried (e.g. not configured)
+    Skipped,
+}
+
+impl std::fmt::Display for AttemptOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Success => write!(f, "success"),
+            Self::Failed => write!(f, "failed"),
+            Self::Skipped => write!(f, "skipped"),
+        }
+    }
+}
+
+/// Record of one rung of the transport ladder
+#[derive(Debug, Clone, Serialize)]
+pub struct LadderAttempt {
+    /// Transport that was considered
+    #[serde(serialize_with = "serialize_display")]
+    pub transport: TransportPreference,
+    /// What happened
+    pub outcome: AttemptOutcome,
+    /// Time spent on the attempt (zero when skipped)
+    #[serde(rename = "latency_ms", serialize_with = "serialize_millis")]
+    pub latency: Duration,
+    /// Error or skip reason, if any
+    pub error: Option<String>,
+}
+
+/// Structured result of a transport ladder run
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LadderResult {
+    /// Attempts in the order they were made
+    pub attempts: Vec<LadderAttempt>,
+}
+
+impl LadderResult {
+    /// Create an empty ladder result
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a transport that was not attempted
+    pub fn record_skipped(&mut self, transport: TransportPreference, reason: impl Into<String>) {
+        self.attempts.push(LadderAttempt {
+            transport,
+            outcome: AttemptOutcome::Skipped,
+            latency: Duration::ZERO,
+            error: Some(reason.into()),
+        });
+    }
+
+    /// Run a transport attempt, timing it and recording its outcome
+    pub async fn attempt<F>(
+        &mut self,
+        transport: TransportPreference,
+        send: F,
+    ) -> Result<(), PairingError>
+    where
+        F: Future<Output = Result<(), PairingError>>,
+    {
+        tracing::debug!("Transport ladder: trying {}...", transport);
+        let start = Instant::now();
+        let result = send.await;
+        let latency = start.elapsed();
+
+        let (outcome, error) = match &result {
+            Ok(()) => (AttemptOutcome::Success, None),
+            Err(e) => {
+                tracing::debug!("{} transport failed: {}", transport, e);
+                (AttemptOutcome::Failed, Some(e.to_string()))
+            }
+        };
+        self.attempts.push(LadderAttempt {
+            transport,
+            outcome,
+            latency,
+            error,
+        });
+
+        result
+    }
+
+    /// Transport that succeeded, if any
+    pub fn selected(&self) -> Option<TransportPreference> {
+        self.attempts
+            .iter()
+            .find(|a| a.outcome == AttemptOutcome::Success)
+            .map(|a| a.transport)
+    }
+
+    /// Error combining every failed attempt, as reported when the ladder is exhausted
+    pub fn exhausted_error(&self) -> PairingError {
+        let errors: Vec<String> = self
+            .attempts
+            .iter()
+            .filter(|a| a.outcome == AttemptOutcome::Failed)
+            .map(|a| format!("{}: {}", a.transport, a.error.as_deref().unwrap_or("unknown error")))
+            .collect();
+        PairingError::Transport(format!("All transports failed: {}", errors.join("; ")))
+    }
+
+    /// One line per attempt, for verbose CLI output
+    pub fn summary(&self) -> String {
+        let mut out = String::from("Transport ladder:\n");
+        for (i, a) in self.attempts.iter().enumerate() {
+            out.push_str(&format!("  {}. {} - {}", i + 1, a.transport, a.outcome));
+            if a.outcome != AttemptOutcome::Skipped {
+                out.push_str(&format!(" ({} ms)", a.latency.as_millis()));
+            }
+            if let Some(err) = &a.error {
+                out.push_str(&format!(": {}", err));
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+fn serialize_display<S: Serializer>(value: &TransportPreference, s: S) -> Result<S::Ok, S::Error> {
+    s.collect_str(value)
+}
+
+fn serialize_millis<S: Serializer>(value: &Duration, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_u64(value.as_millis() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fail(msg: &str) -> Result<(), PairingError> {
+        Err(PairingError::Transport(msg.to_string()))
+    }
+
+    #[tokio::test]
+    async fn test_attempt_records_success_and_failure() {
+        let mut ladder = LadderResult::new();
+
+        assert!(ladder
+            .attempt(TransportPreference::Direct, async { fail("unreachable") })
+            .await
+            .is_err());
+        assert!(ladder
+            .attempt(TransportPreference::Relay, async { Ok(()) })
+            .await
+            .is_ok());
+
+        assert_eq!(ladder.attempts.len(), 2);
+        assert_eq!(ladder.attempts[0].outcome, AttemptOutcome::Failed);
+        assert!(ladder.attempts[0].error.as_deref().unwrap().contains("unreachable"));
+        assert_eq!(ladder.attempts[1].outcome, AttemptOutcome::Success);
+        assert!(ladder.attempts[1].error.is_none());
+        assert_eq!(ladder.selected(), Some(TransportPreference::Relay));
+    }
+
+    #[test]
+    fn test_exhausted_error_lists_only_failures() {
+        let mut ladder = LadderResult::new();
+        ladder.record_skipped(TransportPreference::Mesh, "not configured");
+        ladder.attempts.push(LadderAttempt {
+            transport: TransportPreference::Direct,
+            outcome: AttemptOutcome::Failed,
+            latency: Duration::from_millis(5),
+            error: Some("refused".to_string()),
+        });
+
+        let msg = ladder.exhausted_error().to_string();
+        assert!(msg.contains("direct: refused"));
+        assert!(!msg.contains("mesh"));
+        assert_eq!(ladder.selected(), None);
+    }
+
+    #[test]
+    fn test_serializes_transport_and_latency() {
+        let mut ladder = LadderResult::new();
+        ladder.record_skipped(TransportPreference::Mesh, "not configured");
+        let json = serde_json::to_value(&ladder).unwrap();
+
+        assert_eq!(json["attempts"][0]["transport"], "mesh");
+        assert_eq!(json["attempts"][0]["outcome"], "skipped");
+        assert_eq!(json["attempts"][0]["latency_ms"], 0);
+    }
+}
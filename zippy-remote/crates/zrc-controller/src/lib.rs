@@ -4,22 +4,31 @@
 //! - Pairing with remote devices
 //! - Initiating and managing sessions
 //! - Sending input commands
+//! - Benchmarking frame throughput
 //! - Debugging transport and cryptography
 
+pub mod bench;
 pub mod cli;
 pub mod config;
 pub mod debug;
+pub mod errors;
 pub mod frames;
 pub mod identity;
 pub mod input;
+pub mod ladder;
 pub mod output;
 pub mod pairing;
 pub mod pairings;
+pub mod sas;
 pub mod session;
 
 #[cfg(test)]
 mod proptests;
 
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
 pub use cli::Cli;
 pub use config::{Config, CliOverrides};
 pub use output::{OutputFormat, OutputFormatter, JsonResponse, SuccessMessage};
@@ -98,6 +107,70 @@ impl ExitCode {
     }
 }
 
+/// Side effects performed by an in-flight command
+///
+/// Commands record externally visible actions (e.g. a sent pair request) here
+/// so they can still be reported if the command is aborted by its deadline.
+#[derive(Debug, Clone, Default)]
+pub struct SideEffects {
+    entries: Arc<Mutex<Vec<String>>>,
+}
+
+impl SideEffects {
+    /// Create an empty side-effect log
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a completed side effect
+    pub fn record(&self, effect: impl Into<String>) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.push(effect.into());
+        }
+    }
+
+    /// Take all recorded side effects, leaving the log empty
+    pub fn take(&self) -> Vec<String> {
+        self.entries
+            .lock()
+            .map(|mut entries| std::mem::take(&mut *entries))
+            .unwrap_or_default()
+    }
+}
+
+/// Run command work under an optional deadline
+///
+/// When the deadline elapses the in-flight future is dropped, any recorded
+/// side effects are reported on stderr, and `ExitCode::Timeout` is returned.
+/// Requirements: 9.6
+pub async fn run_with_deadline<F>(
+    deadline: Option<Duration>,
+    effects: &SideEffects,
+    work: F,
+) -> anyhow::Result<ExitCode>
+where
+    F: Future<Output = anyhow::Result<ExitCode>>,
+{
+    let Some(limit) = deadline else {
+        return work.await;
+    };
+
+    match tokio::time::timeout(limit, work).await {
+        Ok(result) => result,
+        Err(_) => {
+            eprintln!("Error: Operation timed out after {:?}", limit);
+            let done = effects.take();
+            if !done.is_empty() {
+                eprintln!("The following actions completed before the timeout:");
+                for effect in done {
+                    eprintln!("  - {effect}");
+                }
+            }
+            Ok(ExitCode::Timeout)
+        }
+    }
+}
+
 
 #[cfg(test)]
 mod exit_code_tests {
@@ -159,4 +232,46 @@ mod exit_code_tests {
         let _ = ExitCode::AuthenticationFailed.to_exit_code();
         let _ = ExitCode::Timeout.to_exit_code();
     }
+
+    #[tokio::test]
+    async fn test_run_with_deadline_passes_through() {
+        let effects = SideEffects::new();
+        let code = run_with_deadline(Some(Duration::from_secs(5)), &effects, async {
+            Ok(ExitCode::NotPaired)
+        })
+        .await
+        .unwrap();
+        assert_eq!(code, ExitCode::NotPaired);
+
+        let code = run_with_deadline(None, &effects, async { Ok(ExitCode::Success) })
+            .await
+            .unwrap();
+        assert_eq!(code, ExitCode::Success);
+    }
+
+    #[tokio::test]
+    async fn test_run_with_deadline_times_out() {
+        let effects = SideEffects::new();
+        let in_flight = effects.clone();
+        let code = run_with_deadline(Some(Duration::from_millis(10)), &effects, async move {
+            in_flight.record("pair request sent");
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok(ExitCode::Success)
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(code, ExitCode::Timeout);
+        // Effects are drained when reported
+        assert!(effects.take().is_empty());
+    }
+
+    #[test]
+    fn test_side_effects_record_and_take() {
+        let effects = SideEffects::new();
+        effects.record("first");
+        effects.clone().record("second");
+        assert_eq!(effects.take(), vec!["first", "second"]);
+        assert!(effects.take().is_empty());
+    }
 }
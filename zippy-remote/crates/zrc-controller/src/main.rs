@@ -1,14 +1,13 @@
 //! ZRC Controller CLI entry point
 
-use clap::Parser;
 use tracing_subscriber::{fmt, prelude::*, EnvFilter};
-use zrc_controller::{Cli, Config, ExitCode};
+use zrc_controller::{Cli, Config, OutputFormatter};
 use zrc_controller::config::CliOverrides;
 
 #[tokio::main]
 async fn main() -> std::process::ExitCode {
     // Parse CLI arguments
-    let cli = Cli::parse();
+    let cli = Cli::try_parse_args(std::env::args_os()).unwrap_or_else(|e| e.exit());
 
     // Create default config on first run (Requirement 10.7)
     if let Err(e) = Config::create_default_if_missing() {
@@ -46,10 +45,20 @@ async fn main() -> std::process::ExitCode {
         } else {
             Some(cli.mesh_nodes.clone())
         },
+        profile: cli.profile.clone(),
     };
 
-    // Apply CLI overrides to config
-    let config = config.with_overrides(&overrides);
+    // Apply selected profile, CLI overrides, then ad-hoc --set overrides
+    let config = match config
+        .with_overrides(&overrides)
+        .and_then(|config| config.with_settings(&cli.set))
+    {
+        Ok(config) => config,
+        Err(e) => {
+            let formatter = OutputFormatter::new(cli.output, cli.verbose);
+            return formatter.report_error(&e.into(), cli.command.name()).to_exit_code();
+        }
+    };
 
     // Initialize logging based on config (with CLI override)
     let filter = if cli.debug {
@@ -66,11 +75,11 @@ async fn main() -> std::process::ExitCode {
         .init();
 
     // Execute command with resolved config
+    // Command failures are reported (as JSON in --output json) inside execute_with_config
+    let formatter = OutputFormatter::new(cli.output, cli.verbose);
+    let command = cli.command.name();
     match cli.execute_with_config(config).await {
         Ok(code) => code.to_exit_code(),
-        Err(e) => {
-            eprintln!("Error: {e}");
-            ExitCode::GeneralError.to_exit_code()
-        }
+        Err(e) => formatter.report_error(&e, command).to_exit_code(),
     }
 }
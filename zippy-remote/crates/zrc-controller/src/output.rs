@@ -13,10 +13,13 @@ use std::str::FromStr;
 use comfy_table::{presets::UTF8_FULL, Table};
 use serde::Serialize;
 
+use crate::bench::FrameBenchReport;
 use crate::identity::IdentityInfo;
-use crate::pairing::ParsedInvite;
+use crate::ladder::LadderResult;
+use crate::pairing::{PairingClient, PairingResult, ParsedInvite};
+use zrc_proto::v1::PairRequestV1;
 use crate::pairings::StoredPairing;
-use crate::session::SessionInitResult;
+use crate::session::{ReconnectEvent, SessionInitResult};
 use crate::ExitCode;
 
 /// Output format options
@@ -72,6 +75,35 @@ pub struct JsonResponse<T: Serialize> {
     /// Command that was executed
     #[serde(skip_serializing_if = "Option::is_none")]
     pub command: Option<String>,
+    /// Structured error (present on failure)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_details: Option<ErrorDetails>,
+}
+
+/// Machine-readable description of a command failure
+/// Requirements: 9.4, 9.6
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorDetails {
+    /// Stable error code (see `crate::errors`)
+    pub code: String,
+    /// Numeric process exit code
+    pub exit_code: i32,
+    /// Exit code name (e.g. "NOT_PAIRED")
+    pub exit_code_name: String,
+    /// Human-readable message
+    pub message: String,
+}
+
+impl ErrorDetails {
+    /// Describe a failure ending with `exit_code` under the error code `code`
+    pub fn new(message: &str, exit_code: ExitCode, code: &str) -> Self {
+        Self {
+            code: code.to_string(),
+            exit_code: exit_code as i32,
+            exit_code_name: exit_code.name().to_string(),
+            message: message.to_string(),
+        }
+    }
 }
 
 impl<T: Serialize> JsonResponse<T> {
@@ -83,6 +115,7 @@ impl<T: Serialize> JsonResponse<T> {
             error: None,
             timestamp: chrono::Utc::now().to_rfc3339(),
             command: None,
+            error_details: None,
         }
     }
 
@@ -94,6 +127,7 @@ impl<T: Serialize> JsonResponse<T> {
             error: None,
             timestamp: chrono::Utc::now().to_rfc3339(),
             command: Some(command.to_string()),
+            error_details: None,
         }
     }
 }
@@ -107,6 +141,7 @@ impl JsonResponse<()> {
             error: Some(message.to_string()),
             timestamp: chrono::Utc::now().to_rfc3339(),
             command: None,
+            error_details: None,
         }
     }
 
@@ -118,6 +153,15 @@ impl JsonResponse<()> {
             error: Some(message.to_string()),
             timestamp: chrono::Utc::now().to_rfc3339(),
             command: Some(command.to_string()),
+            error_details: None,
+        }
+    }
+
+    /// Create an error response carrying the exit code and stable error code
+    pub fn failure(message: &str, command: &str, exit_code: ExitCode, code: &str) -> JsonResponse<()> {
+        JsonResponse {
+            error_details: Some(ErrorDetails::new(message, exit_code, code)),
+            ..Self::error_with_command(message, command)
         }
     }
 }
@@ -200,6 +244,47 @@ impl OutputFormatter {
         }
     }
 
+    /// Format a dry-run preview of the pair request that would be sent
+    /// Requirements: 2.1, 2.2, 9.1
+    pub fn format_pair_preview(&self, invite: &ParsedInvite, request: &PairRequestV1) -> String {
+        let preview = PairPreviewOutput::new(invite, request);
+        match self.format {
+            OutputFormat::Table => self.pair_preview_table(&preview),
+            OutputFormat::Json => self.to_json_response(&preview, "pair dry-run"),
+            OutputFormat::Quiet => String::new(),
+        }
+    }
+
+    /// Format the summary of a `pair --batch` run
+    /// Requirements: 2.1-2.8, 9.1
+    pub fn format_pair_batch(&self, summary: &PairBatchSummary) -> String {
+        match self.format {
+            OutputFormat::Table => self.pair_batch_table(summary),
+            OutputFormat::Json => self.to_json_response(summary, "pair batch"),
+            OutputFormat::Quiet => String::new(),
+        }
+    }
+
+    /// Format transport ladder decision log
+    /// Requirements: 8.7
+    pub fn format_ladder(&self, ladder: &LadderResult) -> String {
+        match self.format {
+            OutputFormat::Table => self.ladder_table(ladder),
+            OutputFormat::Json => self.to_json_response(ladder, "transport ladder"),
+            OutputFormat::Quiet => String::new(),
+        }
+    }
+
+    /// Format a frame benchmark report
+    /// Requirements: 6.7, 9.1
+    pub fn format_frame_bench(&self, report: &FrameBenchReport) -> String {
+        match self.format {
+            OutputFormat::Table => self.frame_bench_table(report),
+            OutputFormat::Json => self.to_json_response(report, "bench frames"),
+            OutputFormat::Quiet => String::new(),
+        }
+    }
+
     /// Format a generic success result
     /// Requirements: 9.1, 9.4
     pub fn format_success<T: Serialize>(&self, data: &T, command: &str) -> String {
@@ -210,6 +295,29 @@ impl OutputFormatter {
         }
     }
 
+    /// Report a failed command and return the exit code it maps to
+    ///
+    /// JSON mode prints a failure `JsonResponse` with structured error details
+    /// to stdout; table mode prints the message to stderr.
+    /// Requirements: 9.4, 9.6
+    pub fn report_error(&self, error: &anyhow::Error, command: &str) -> ExitCode {
+        let (exit_code, code) = crate::errors::classify(error);
+        self.report_failure(&format!("{error:#}"), command, exit_code, code);
+        exit_code
+    }
+
+    /// Report a failure that has already been classified
+    pub fn report_failure(&self, message: &str, command: &str, exit_code: ExitCode, code: &str) {
+        match self.format {
+            OutputFormat::Table => eprintln!("Error: {message}"),
+            OutputFormat::Json => {
+                let response = JsonResponse::failure(message, command, exit_code, code);
+                println!("{}", self.to_json(&response));
+            }
+            OutputFormat::Quiet => {}
+        }
+    }
+
     /// Format error with exit code context
     /// Requirements: 9.1, 9.6
     pub fn format_error_with_code(&self, error: &dyn std::error::Error, code: ExitCode) -> String {
@@ -222,6 +330,7 @@ impl OutputFormatter {
                     error: Some(error.to_string()),
                     timestamp: chrono::Utc::now().to_rfc3339(),
                     command: None,
+                    error_details: None,
                 };
                 let mut output: serde_json::Value = serde_json::to_value(&response).unwrap();
                 output["exit_code"] = serde_json::json!(code as i32);
@@ -259,17 +368,26 @@ impl OutputFormatter {
         }
     }
 
-    /// Format error message
-    pub fn error(&self, message: &str) {
-        if self.format == OutputFormat::Table {
-            eprintln!("✗ {message}");
-        } else if self.format == OutputFormat::Json {
-            println!("{}", self.to_json(&ErrorOutput {
-                error: message.to_string(),
-                timestamp: chrono::Utc::now().to_rfc3339(),
-            }));
+    /// Report a command failure and return the exit code it ends with
+    ///
+    /// JSON mode prints the same failure `JsonResponse` as
+    /// [`report_failure`](Self::report_failure), with the exit code name as
+    /// the error code.
+    /// Requirements: 9.4, 9.6
+    pub fn error(&self, message: &str, exit_code: ExitCode) -> ExitCode {
+        match self.format {
+            OutputFormat::Table => eprintln!("✗ {message}"),
+            OutputFormat::Json => {
+                let response = JsonResponse {
+                    error_details: Some(ErrorDetails::new(message, exit_code, exit_code.name())),
+                    ..JsonResponse::error(message)
+                };
+                println!("{}", self.to_json(&response));
+            }
+            // Quiet mode: no output, rely on exit code
+            OutputFormat::Quiet => {}
         }
-        // Quiet mode: no output, rely on exit code
+        exit_code
     }
 
     /// Format warning message
@@ -302,6 +420,29 @@ impl OutputFormatter {
         }
     }
 
+    /// Report a reconnect attempt after a dropped session connection
+    ///
+    /// Shown in every non-quiet mode; JSON mode emits one event object per line
+    /// so scripts can follow the session while the command is still running.
+    /// Requirements: 4.7, 9.1
+    pub fn reconnecting(&self, event: &ReconnectEvent) {
+        match self.format {
+            OutputFormat::Table => eprintln!(
+                "⟳ Reconnecting (attempt {}/{}) in {}ms: {}",
+                event.attempt, event.max_attempts, event.delay_ms, event.reason
+            ),
+            OutputFormat::Json => {
+                let output = ReconnectingOutput {
+                    event: "reconnecting",
+                    details: event,
+                    timestamp: chrono::Utc::now().to_rfc3339(),
+                };
+                println!("{}", serde_json::to_string(&output).unwrap_or_default());
+            }
+            OutputFormat::Quiet => {}
+        }
+    }
+
     /// Format debug message (only in debug mode, handled by tracing)
     /// Requirements: 9.8
     pub fn debug(&self, message: &str) {
@@ -386,6 +527,106 @@ impl OutputFormatter {
         table.to_string()
     }
 
+    fn pair_batch_table(&self, summary: &PairBatchSummary) -> String {
+        let mut table = Table::new();
+        table.load_preset(UTF8_FULL);
+        table.set_header(vec!["Line", "Device ID", "Result", "Details"]);
+
+        for r in &summary.results {
+            let details = if r.paired {
+                r.permissions.join(", ")
+            } else {
+                r.error.clone().unwrap_or_default()
+            };
+            table.add_row(vec![
+                r.line.to_string(),
+                r.device_id.clone().unwrap_or_else(|| "-".to_string()),
+                if r.paired { "paired" } else { "failed" }.to_string(),
+                details,
+            ]);
+        }
+
+        format!(
+            "{table}\n{} of {} paired, {} failed, {} skipped",
+            summary.succeeded, summary.total, summary.failed, summary.skipped
+        )
+    }
+
+    fn pair_preview_table(&self, preview: &PairPreviewOutput) -> String {
+        let mut table = Table::new();
+        table.load_preset(UTF8_FULL);
+        table.set_header(vec!["Property", "Value"]);
+        table.add_row(vec!["Device ID", &preview.device_id]);
+        table.add_row(vec!["Expires At", &preview.expires_at]);
+        table.add_row(vec!["Transport Hints", &preview.transport_hints.join("\n")]);
+        table.add_row(vec!["Operator ID", &preview.operator_id]);
+        table.add_row(vec!["Operator Sign Key", &preview.operator_sign_pub]);
+        table.add_row(vec!["Operator Kex Key", &preview.operator_kex_pub]);
+        table.add_row(vec![
+            "Requested Permissions",
+            &format!(
+                "{} (0x{:02x})",
+                preview.requested_permissions.join(", "),
+                preview.requested_permissions_mask
+            ),
+        ]);
+        table.add_row(vec!["Invite Proof", &preview.invite_proof]);
+        table.add_row(vec!["Nonce", &preview.nonce]);
+        table.add_row(vec!["Timestamp", &preview.timestamp.to_string()]);
+        table.add_row(vec!["Encoded Size", &format!("{} bytes", preview.encoded_size)]);
+        table.to_string()
+    }
+
+    fn ladder_table(&self, ladder: &LadderResult) -> String {
+        if ladder.attempts.is_empty() {
+            return "No transports attempted.".to_string();
+        }
+
+        let mut table = Table::new();
+        table.load_preset(UTF8_FULL);
+        table.set_header(vec!["#", "Transport", "Outcome", "Latency", "Error"]);
+
+        for (i, a) in ladder.attempts.iter().enumerate() {
+            table.add_row(vec![
+                &(i + 1).to_string(),
+                &a.transport.to_string(),
+                &a.outcome.to_string(),
+                &format!("{} ms", a.latency.as_millis()),
+                a.error.as_deref().unwrap_or("-"),
+            ]);
+        }
+
+        table.to_string()
+    }
+
+    fn frame_bench_table(&self, report: &FrameBenchReport) -> String {
+        let mut table = Table::new();
+        table.load_preset(UTF8_FULL);
+        table.set_header(vec!["Metric", "Value"]);
+        table.add_row(vec!["Duration", &format!("{:.2} s", report.duration_secs)]);
+        table.add_row(vec!["Frames", &report.frames.to_string()]);
+        table.add_row(vec!["Frames Dropped", &report.frames_dropped.to_string()]);
+        table.add_row(vec!["FPS", &format!("{:.1}", report.fps)]);
+        table.add_row(vec!["Megapixels/s", &format!("{:.2}", report.megapixels_per_sec)]);
+        if let Some(wire_bytes) = report.wire_bytes {
+            table.add_row(vec!["Wire Bytes", &wire_bytes.to_string()]);
+        }
+        if let Some(rtt_ms) = report.rtt_ms {
+            table.add_row(vec!["RTT", &format!("{rtt_ms:.1} ms")]);
+        }
+        let c = &report.convert;
+        table.add_row(vec!["Convert mean", &format!("{:.1} µs", c.mean_us)]);
+        table.add_row(vec!["Convert p50", &format!("{:.1} µs", c.p50_us)]);
+        table.add_row(vec!["Convert p90", &format!("{:.1} µs", c.p90_us)]);
+        table.add_row(vec!["Convert p99", &format!("{:.1} µs", c.p99_us)]);
+        table.add_row(vec!["Convert max", &format!("{:.1} µs", c.max_us)]);
+        if let Some(min_fps) = report.min_fps {
+            let verdict = if report.passed { "pass" } else { "FAIL" };
+            table.add_row(vec!["Min FPS", &format!("{min_fps:.1} ({verdict})")]);
+        }
+        table.to_string()
+    }
+
     fn pairing_detail_table(&self, pairing: &StoredPairing) -> String {
         let mut table = Table::new();
         table.load_preset(UTF8_FULL);
@@ -398,6 +639,8 @@ impl OutputFormatter {
         table.add_row(vec!["Paired At", &format_time(pairing.paired_at)]);
         table.add_row(vec!["Last Session", &pairing.last_session.map(format_time).unwrap_or_else(|| "Never".to_string())]);
         table.add_row(vec!["Session Count", &pairing.session_count.to_string()]);
+        table.add_row(vec!["Note", pairing.note.as_deref().unwrap_or("-")]);
+        table.add_row(vec!["Cert Pin", &pairing.cert_pin.map(hex::encode).unwrap_or_else(|| "-".to_string())]);
         table.to_string()
     }
 }
@@ -524,6 +767,111 @@ impl From<&ParsedInvite> for InviteOutput {
     }
 }
 
+/// Outcome of pairing one invite from a `pair --batch` file
+#[derive(Debug, Clone, Serialize)]
+pub struct PairBatchOutcome {
+    /// Line number in the batch file
+    pub line: usize,
+    /// Device ID, when the invite could be decoded
+    pub device_id: Option<String>,
+    /// Whether pairing completed
+    pub paired: bool,
+    /// Permissions granted by the device
+    pub permissions: Vec<String>,
+    /// Why pairing failed
+    pub error: Option<String>,
+}
+
+impl PairBatchOutcome {
+    /// A successfully paired entry
+    pub fn paired(line: usize, result: &PairingResult) -> Self {
+        Self {
+            line,
+            device_id: Some(result.device_id.clone()),
+            paired: true,
+            permissions: result.permissions_granted.clone(),
+            error: None,
+        }
+    }
+
+    /// A failed entry
+    pub fn failed(line: usize, device_id: Option<String>, error: impl Into<String>) -> Self {
+        Self {
+            line,
+            device_id,
+            paired: false,
+            permissions: Vec::new(),
+            error: Some(error.into()),
+        }
+    }
+}
+
+/// Summary of a `pair --batch` run
+#[derive(Debug, Clone, Serialize)]
+pub struct PairBatchSummary {
+    /// Invites in the batch file
+    pub total: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    /// Invites not attempted because of `--fail-fast`
+    pub skipped: usize,
+    pub results: Vec<PairBatchOutcome>,
+}
+
+impl PairBatchSummary {
+    /// Summarize the outcomes of a batch of `total` invites
+    pub fn new(total: usize, results: Vec<PairBatchOutcome>) -> Self {
+        let succeeded = results.iter().filter(|r| r.paired).count();
+        let failed = results.len() - succeeded;
+        Self {
+            total,
+            succeeded,
+            failed,
+            skipped: total.saturating_sub(results.len()),
+            results,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct PairPreviewOutput {
+    device_id: String,
+    expires_at: String,
+    expires_at_iso: String,
+    transport_hints: Vec<String>,
+    operator_id: String,
+    operator_sign_pub: String,
+    operator_kex_pub: String,
+    requested_permissions: Vec<String>,
+    requested_permissions_mask: u32,
+    invite_proof: String,
+    nonce: String,
+    timestamp: u64,
+    encoded_size: usize,
+}
+
+impl PairPreviewOutput {
+    fn new(invite: &ParsedInvite, request: &PairRequestV1) -> Self {
+        use prost::Message;
+
+        Self {
+            device_id: invite.device_id.clone(),
+            expires_at: format_time(invite.expires_at),
+            expires_at_iso: format_time_iso(invite.expires_at),
+            transport_hints: invite.transport_hints.clone(),
+            operator_id: hex::encode(&request.operator_id),
+            operator_sign_pub: hex::encode(&request.operator_sign_pub),
+            operator_kex_pub: hex::encode(&request.operator_kex_pub),
+            requested_permissions: PairingClient::mask_to_permissions(request.requested_permissions),
+            requested_permissions_mask: request.requested_permissions,
+            invite_proof: hex::encode(&request.invite_proof),
+            nonce: hex::encode(&request.nonce),
+            timestamp: request.timestamp,
+            encoded_size: request.encoded_len(),
+        }
+    }
+}
+
 #[derive(Serialize)]
 struct ErrorOutput {
     error: String,
@@ -536,6 +884,14 @@ struct WarningOutput {
     timestamp: String,
 }
 
+#[derive(Serialize)]
+struct ReconnectingOutput<'a> {
+    event: &'static str,
+    #[serde(flatten)]
+    details: &'a ReconnectEvent,
+    timestamp: String,
+}
+
 #[derive(Serialize)]
 struct InfoOutput {
     info: String,
@@ -554,6 +910,8 @@ struct PairingDetailOutput {
     last_session: Option<String>,
     last_session_iso: Option<String>,
     session_count: u32,
+    note: Option<String>,
+    cert_pin: Option<String>,
 }
 
 impl From<&StoredPairing> for PairingDetailOutput {
@@ -569,6 +927,8 @@ impl From<&StoredPairing> for PairingDetailOutput {
             last_session: p.last_session.map(format_time),
             last_session_iso: p.last_session.map(format_time_iso),
             session_count: p.session_count,
+            note: p.note.clone(),
+            cert_pin: p.cert_pin.map(hex::encode),
         }
     }
 }
@@ -673,4 +1033,38 @@ mod tests {
         let output = formatter.format_pairings(&pairings);
         assert!(serde_json::from_str::<serde_json::Value>(&output).is_ok());
     }
+
+    #[test]
+    fn test_json_response_failure_details() {
+        let response = JsonResponse::failure("Device not paired: abc", "session start", ExitCode::NotPaired, "SESSION_NOT_PAIRED");
+        let json = serde_json::to_value(&response).unwrap();
+
+        assert_eq!(json["success"], false);
+        assert_eq!(json["command"], "session start");
+        assert_eq!(json["error"], "Device not paired: abc");
+        assert_eq!(json["error_details"]["code"], "SESSION_NOT_PAIRED");
+        assert_eq!(json["error_details"]["exit_code"], 6);
+        assert_eq!(json["error_details"]["exit_code_name"], "NOT_PAIRED");
+
+        // Success responses carry no error details
+        let json = serde_json::to_value(JsonResponse::success(1)).unwrap();
+        assert!(json.get("error_details").is_none());
+    }
+
+    #[test]
+    fn test_pair_batch_summary() {
+        let results = vec![
+            PairBatchOutcome::failed(1, None, "bad invite"),
+            PairBatchOutcome::failed(3, Some("ab".to_string()), "timeout"),
+        ];
+        let summary = PairBatchSummary::new(4, results);
+        assert_eq!(summary.succeeded, 0);
+        assert_eq!(summary.failed, 2);
+        assert_eq!(summary.skipped, 2);
+
+        let formatter = OutputFormatter::new(OutputFormat::Table, false);
+        let table = formatter.format_pair_batch(&summary);
+        assert!(table.contains("bad invite"));
+        assert!(table.ends_with("0 of 4 paired, 2 failed, 2 skipped"));
+    }
 }
@@ -12,7 +12,7 @@
 
 use std::fs;
 use std::io::Read;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
@@ -31,8 +31,11 @@ use zrc_proto::v1::{
     PublicKeyV1, TimestampV1, UserIdV1,
 };
 use zrc_proto::Validate;
+use zrc_security::audit::{AuditLogger, SecurityEvent};
+use zrc_security::sas::{SasOutcome, SasVerifier};
 
 use crate::identity::IdentityManager;
+use crate::ladder::LadderResult;
 use crate::pairings::{PairingsStore, StoredPairing};
 
 /// Transport client for sending pairing messages
@@ -83,32 +86,54 @@ impl TransportClient {
     }
 
     /// Send a pair request to the device via configured transport
+    ///
+    /// Returns the send result together with a structured record of every
+    /// transport that was considered.
     /// Requirements: 2.3, 8.3
     pub async fn send_pair_request(
         &self,
         device_id: &[u8],
         request: &PairRequestV1,
         preference: TransportPreference,
-    ) -> Result<(), PairingError> {
+    ) -> (Result<(), PairingError>, LadderResult) {
         let request_bytes = request.encode_to_vec();
+        let mut ladder = LadderResult::new();
 
-        match preference {
+        let result = match preference {
             TransportPreference::Auto => {
                 // Try transports in ladder order: mesh → direct → rendezvous → relay
                 // Requirements: 8.3
-                self.send_with_ladder(device_id, &request_bytes).await
+                self.send_with_ladder(device_id, &request_bytes, &mut ladder).await
             }
-            TransportPreference::Mesh => {
-                self.send_via_mesh(device_id, &request_bytes).await
+            single => {
+                self.send_via(single, device_id, &request_bytes, &mut ladder).await
             }
-            TransportPreference::Rendezvous => {
-                self.send_via_rendezvous(device_id, &request_bytes).await
+        };
+
+        (result, ladder)
+    }
+
+    /// Send via a single explicitly selected transport, recording the attempt
+    pub(crate) async fn send_via(
+        &self,
+        transport: TransportPreference,
+        device_id: &[u8],
+        data: &[u8],
+        ladder: &mut LadderResult,
+    ) -> Result<(), PairingError> {
+        match transport {
+            TransportPreference::Auto => self.send_with_ladder(device_id, data, ladder).await,
+            TransportPreference::Mesh => {
+                ladder.attempt(transport, self.send_via_mesh(device_id, data)).await
             }
             TransportPreference::Direct => {
-                self.send_via_direct(device_id, &request_bytes).await
+                ladder.attempt(transport, self.send_via_direct(device_id, data)).await
+            }
+            TransportPreference::Rendezvous => {
+                ladder.attempt(transport, self.send_via_rendezvous(device_id, data)).await
             }
             TransportPreference::Relay => {
-                self.send_via_relay(device_id, &request_bytes).await
+                ladder.attempt(transport, self.send_via_relay(device_id, data)).await
             }
         }
     }
@@ -119,60 +144,52 @@ impl TransportClient {
         &self,
         device_id: &[u8],
         data: &[u8],
+        ladder: &mut LadderResult,
     ) -> Result<(), PairingError> {
-        let mut errors = Vec::new();
-
         // 1. Try mesh first (if configured)
-        if !self.mesh_nodes.is_empty() {
-            tracing::debug!("Transport ladder: trying mesh...");
-            match self.send_via_mesh(device_id, data).await {
-                Ok(()) => return Ok(()),
-                Err(e) => {
-                    tracing::debug!("Mesh transport failed: {}", e);
-                    errors.push(format!("mesh: {}", e));
-                }
-            }
+        if self.mesh_nodes.is_empty() {
+            ladder.record_skipped(TransportPreference::Mesh, "no mesh nodes configured");
+        } else if ladder
+            .attempt(TransportPreference::Mesh, self.send_via_mesh(device_id, data))
+            .await
+            .is_ok()
+        {
+            return Ok(());
         }
 
         // 2. Try direct (if we have endpoint hints)
-        tracing::debug!("Transport ladder: trying direct...");
-        match self.send_via_direct(device_id, data).await {
-            Ok(()) => return Ok(()),
-            Err(e) => {
-                tracing::debug!("Direct transport failed: {}", e);
-                errors.push(format!("direct: {}", e));
-            }
+        if ladder
+            .attempt(TransportPreference::Direct, self.send_via_direct(device_id, data))
+            .await
+            .is_ok()
+        {
+            return Ok(());
         }
 
         // 3. Try rendezvous
-        if !self.rendezvous_urls.is_empty() {
-            tracing::debug!("Transport ladder: trying rendezvous...");
-            match self.send_via_rendezvous(device_id, data).await {
-                Ok(()) => return Ok(()),
-                Err(e) => {
-                    tracing::debug!("Rendezvous transport failed: {}", e);
-                    errors.push(format!("rendezvous: {}", e));
-                }
-            }
+        if self.rendezvous_urls.is_empty() {
+            ladder.record_skipped(TransportPreference::Rendezvous, "no rendezvous URLs configured");
+        } else if ladder
+            .attempt(TransportPreference::Rendezvous, self.send_via_rendezvous(device_id, data))
+            .await
+            .is_ok()
+        {
+            return Ok(());
         }
 
         // 4. Try relay as last resort
-        if !self.relay_urls.is_empty() {
-            tracing::debug!("Transport ladder: trying relay...");
-            match self.send_via_relay(device_id, data).await {
-                Ok(()) => return Ok(()),
-                Err(e) => {
-                    tracing::debug!("Relay transport failed: {}", e);
-                    errors.push(format!("relay: {}", e));
-                }
-            }
+        if self.relay_urls.is_empty() {
+            ladder.record_skipped(TransportPreference::Relay, "no relay URLs configured");
+        } else if ladder
+            .attempt(TransportPreference::Relay, self.send_via_relay(device_id, data))
+            .await
+            .is_ok()
+        {
+            return Ok(());
         }
 
         // All transports failed
-        Err(PairingError::Transport(format!(
-            "All transports failed: {}",
-            errors.join("; ")
-        )))
+        Err(ladder.exhausted_error())
     }
 
     /// Send via direct connection
@@ -258,18 +275,25 @@ impl TransportClient {
     /// Send via mesh network
     pub async fn send_via_mesh(
         &self,
-        _device_id: &[u8],
-        _data: &[u8],
+        device_id: &[u8],
+        data: &[u8],
     ) -> Result<(), PairingError> {
         if self.mesh_nodes.is_empty() {
             return Err(PairingError::Transport(
                 "No mesh nodes configured".to_string(),
             ));
         }
-        // TODO: Implement mesh transport
-        Err(PairingError::Transport(
-            "Mesh transport not yet implemented".to_string(),
-        ))
+
+        let device_id_32: [u8; 32] = device_id
+            .try_into()
+            .map_err(|_| PairingError::Transport("Invalid device ID length".to_string()))?;
+
+        let client = zrc_core::mesh_mailbox::MeshMailboxClient::from_urls(&self.mesh_nodes)
+            .map_err(|e| PairingError::Transport(format!("Mesh setup failed: {e}")))?;
+        client
+            .post(&device_id_32, data)
+            .await
+            .map_err(|e| PairingError::Transport(format!("Mesh send failed: {e}")))
     }
 
     /// Send via relay server
@@ -359,6 +383,150 @@ impl TransportClient {
         }
     }
 
+    /// Probe mesh reachability by connecting to the first responsive node
+    /// Requirements: 8.7
+    pub async fn probe_mesh(&self) -> Result<(), PairingError> {
+        if self.mesh_nodes.is_empty() {
+            return Err(PairingError::Transport("No mesh nodes configured".to_string()));
+        }
+        let mut errors = Vec::new();
+        for node in &self.mesh_nodes {
+            match tcp_probe(node).await {
+                Ok(()) => return Ok(()),
+                Err(e) => errors.push(format!("{node}: {e}")),
+            }
+        }
+        Err(PairingError::Transport(errors.join("; ")))
+    }
+
+    /// Probe direct reachability of the device's QUIC endpoint hints
+    /// Requirements: 8.7
+    pub async fn probe_direct(&self, direct_addrs: &[String]) -> Result<(), PairingError> {
+        if direct_addrs.is_empty() {
+            return Err(PairingError::Transport("No direct endpoint hints".to_string()));
+        }
+        let mut errors = Vec::new();
+        for addr in direct_addrs {
+            match quic_probe(addr).await {
+                Ok(()) => return Ok(()),
+                Err(e) => errors.push(format!("{addr}: {e}")),
+            }
+        }
+        Err(PairingError::Transport(errors.join("; ")))
+    }
+
+    /// Probe rendezvous reachability without posting to the device mailbox
+    /// Requirements: 8.7
+    pub async fn probe_rendezvous(&self, device_id: &[u8]) -> Result<(), PairingError> {
+        if self.rendezvous_urls.is_empty() {
+            return Err(PairingError::Transport(
+                "No rendezvous URLs configured".to_string(),
+            ));
+        }
+
+        let mut errors = Vec::new();
+        for url in &self.rendezvous_urls {
+            #[cfg(feature = "http-mailbox")]
+            let result = {
+                let mailbox_url = format!(
+                    "{}/v1/mailbox/{}",
+                    url.trim_end_matches('/'),
+                    hex::encode(device_id)
+                );
+                match &self.http_client {
+                    Some(client) => match tokio::time::timeout(
+                        PROBE_TIMEOUT,
+                        client.head(&mailbox_url).send(),
+                    )
+                    .await
+                    {
+                        Ok(Ok(resp)) if resp.status().is_server_error() => Err(
+                            PairingError::Transport(format!("Rendezvous returned status {}", resp.status())),
+                        ),
+                        Ok(Ok(_)) => Ok(()),
+                        Ok(Err(e)) => Err(PairingError::Transport(format!("Rendezvous request failed: {e}"))),
+                        Err(_) => Err(PairingError::Timeout(PROBE_TIMEOUT)),
+                    },
+                    None => Err(PairingError::Transport("HTTP client not available".to_string())),
+                }
+            };
+
+            #[cfg(not(feature = "http-mailbox"))]
+            let result = {
+                let _ = device_id;
+                match url_socket_addr(url) {
+                    Ok(addr) => tcp_probe(&addr).await,
+                    Err(e) => Err(e),
+                }
+            };
+
+            match result {
+                Ok(()) => return Ok(()),
+                Err(e) => errors.push(format!("{url}: {e}")),
+            }
+        }
+        Err(PairingError::Transport(errors.join("; ")))
+    }
+
+    /// Probe relay reachability until one relay's QUIC endpoint answers
+    /// Requirements: 8.7
+    pub async fn probe_relay(&self) -> Result<(), PairingError> {
+        if self.relay_urls.is_empty() {
+            return Err(PairingError::Transport("No relay URLs configured".to_string()));
+        }
+        let mut errors = Vec::new();
+        for url in &self.relay_urls {
+            let result = match url_socket_addr(url) {
+                Ok(addr) => quic_probe(&addr).await,
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(()) => return Ok(()),
+                Err(e) => errors.push(format!("{url}: {e}")),
+            }
+        }
+        Err(PairingError::Transport(errors.join("; ")))
+    }
+
+    /// Probe every rung of the transport ladder for a device
+    ///
+    /// Unlike a real send, every rung is checked even after one succeeds, and
+    /// unconfigured rungs are recorded as skipped.
+    /// Requirements: 8.7
+    pub async fn probe_ladder(&self, device_id: &[u8], direct_addrs: &[String]) -> LadderResult {
+        let mut ladder = LadderResult::new();
+
+        if self.mesh_nodes.is_empty() {
+            ladder.record_skipped(TransportPreference::Mesh, "no mesh nodes configured");
+        } else {
+            let _ = ladder.attempt(TransportPreference::Mesh, self.probe_mesh()).await;
+        }
+
+        if direct_addrs.is_empty() {
+            ladder.record_skipped(TransportPreference::Direct, "no endpoint hints for device");
+        } else {
+            let _ = ladder
+                .attempt(TransportPreference::Direct, self.probe_direct(direct_addrs))
+                .await;
+        }
+
+        if self.rendezvous_urls.is_empty() {
+            ladder.record_skipped(TransportPreference::Rendezvous, "no rendezvous URLs configured");
+        } else {
+            let _ = ladder
+                .attempt(TransportPreference::Rendezvous, self.probe_rendezvous(device_id))
+                .await;
+        }
+
+        if self.relay_urls.is_empty() {
+            ladder.record_skipped(TransportPreference::Relay, "no relay URLs configured");
+        } else {
+            let _ = ladder.attempt(TransportPreference::Relay, self.probe_relay()).await;
+        }
+
+        ladder
+    }
+
     /// Get configured rendezvous URLs
     pub fn rendezvous_urls(&self) -> &[String] {
         &self.rendezvous_urls
@@ -432,6 +600,85 @@ impl TransportClient {
     }
 }
 
+/// Timeout for a single reachability probe
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Check that a TCP connection to `addr` (host:port) can be opened
+async fn tcp_probe(addr: &str) -> Result<(), PairingError> {
+    match tokio::time::timeout(PROBE_TIMEOUT, tokio::net::TcpStream::connect(addr)).await {
+        Ok(Ok(_stream)) => Ok(()),
+        Ok(Err(e)) => Err(PairingError::Transport(format!("Connection failed: {e}"))),
+        Err(_) => Err(PairingError::Timeout(PROBE_TIMEOUT)),
+    }
+}
+
+/// Reserved QUIC version (RFC 9000 section 15) that no server implements
+const QUIC_PROBE_VERSION: u32 = 0x1a2a_3a4a;
+
+/// Servers ignore client Initial datagrams shorter than this
+const QUIC_MIN_INITIAL_SIZE: usize = 1200;
+
+/// Check that a QUIC endpoint answers at `addr` (host:port)
+///
+/// Sends a long-header datagram with a reserved version. A QUIC server
+/// answers it with a version negotiation packet, so no handshake or
+/// certificate is needed and the endpoint keeps no connection state.
+async fn quic_probe(addr: &str) -> Result<(), PairingError> {
+    let target = tokio::net::lookup_host(addr)
+        .await
+        .map_err(|e| PairingError::Transport(format!("Cannot resolve '{addr}': {e}")))?
+        .next()
+        .ok_or_else(|| PairingError::Transport(format!("No address for '{addr}'")))?;
+    let local = if target.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" };
+
+    let exchange = async {
+        let socket = tokio::net::UdpSocket::bind(local).await?;
+        socket.connect(target).await?;
+        let mut dcid = [0u8; 8];
+        getrandom::getrandom(&mut dcid).map_err(std::io::Error::other)?;
+        socket.send(&quic_probe_packet(&dcid)).await?;
+        let mut reply = [0u8; 1500];
+        let len = socket.recv(&mut reply).await?;
+        Ok::<_, std::io::Error>(is_version_negotiation(&reply[..len]))
+    };
+    match tokio::time::timeout(PROBE_TIMEOUT, exchange).await {
+        Ok(Ok(true)) => Ok(()),
+        Ok(Ok(false)) => Err(PairingError::Transport("Reply is not from a QUIC endpoint".to_string())),
+        Ok(Err(e)) => Err(PairingError::Transport(format!("Connection failed: {e}"))),
+        Err(_) => Err(PairingError::Timeout(PROBE_TIMEOUT)),
+    }
+}
+
+/// Padded long-header packet carrying `QUIC_PROBE_VERSION`
+fn quic_probe_packet(dcid: &[u8; 8]) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(QUIC_MIN_INITIAL_SIZE);
+    packet.push(0xc0); // long header, fixed bit
+    packet.extend_from_slice(&QUIC_PROBE_VERSION.to_be_bytes());
+    packet.push(dcid.len() as u8);
+    packet.extend_from_slice(dcid);
+    packet.push(0); // empty source connection id
+    packet.resize(QUIC_MIN_INITIAL_SIZE, 0);
+    packet
+}
+
+/// Whether `packet` is a version negotiation packet (long header, version 0)
+fn is_version_negotiation(packet: &[u8]) -> bool {
+    packet.len() >= 5 && packet[0] & 0x80 != 0 && packet[1..5] == [0; 4]
+}
+
+/// Resolve a server URL to a host:port string, using the scheme's default port
+fn url_socket_addr(url: &str) -> Result<String, PairingError> {
+    let parsed = url::Url::parse(url)
+        .map_err(|e| PairingError::Transport(format!("Invalid URL '{url}': {e}")))?;
+    let host = parsed
+        .host_str()
+        .ok_or_else(|| PairingError::Transport(format!("Missing host in '{url}'")))?;
+    let port = parsed
+        .port_or_known_default()
+        .ok_or_else(|| PairingError::Transport(format!("Missing port in '{url}'")))?;
+    Ok(format!("{host}:{port}"))
+}
+
 impl Default for TransportClient {
     fn default() -> Self {
         Self::new()
@@ -714,6 +961,92 @@ impl From<&InviteV1> for InviteJson {
     }
 }
 
+/// A device-issued invite for an already paired device, for sharing as a QR code
+///
+/// Only the device holds the secret behind an invite, so re-invites are
+/// created with `zrc-agent pair` on the device. The secret reaches the
+/// operator the same way as for a first pairing and never passes through
+/// the controller.
+#[derive(Debug, Clone)]
+pub struct Reinvite {
+    /// Invite to encode
+    pub invite: InviteV1,
+}
+
+impl Reinvite {
+    /// Accept `invite` (base64, as printed by `zrc-agent pair`) if it was
+    /// issued by the device of `pairing` and has not expired
+    /// Requirements: 2.1, 7.3
+    pub fn from_pairing(pairing: &StoredPairing, invite: &str) -> Result<Self, PairingError> {
+        let parsed = PairingClient::new().import_invite(InviteSource::Base64(invite.to_string()))?;
+        if !parsed.device_id.eq_ignore_ascii_case(&pairing.device_id)
+            || parsed.invite.device_sign_pub != pairing.device_sign_pub
+        {
+            return Err(PairingError::InvalidInvite(
+                "Invite was not issued by the paired device".to_string(),
+            ));
+        }
+        Ok(Self { invite: parsed.invite })
+    }
+
+    /// Standard base64 of the invite protobuf, the form `pair --invite` accepts
+    pub fn to_base64(&self) -> String {
+        base64::engine::general_purpose::STANDARD.encode(self.invite.encode_to_vec())
+    }
+}
+
+/// Encode invite data as a QR code
+///
+/// Level M (~15% recovery) tolerates glare and print smudges while keeping a
+/// base64 invite of a few hundred characters at a version phones scan easily.
+#[cfg(feature = "qr")]
+fn invite_qr_code(data: &str) -> Result<qrcode::QrCode, PairingError> {
+    qrcode::QrCode::with_error_correction_level(data.as_bytes(), qrcode::EcLevel::M)
+        .map_err(|e| PairingError::QrCode(format!("Failed to encode QR: {e}")))
+}
+
+/// Write invite data as a QR code PNG
+#[cfg(feature = "qr")]
+pub fn write_invite_qr_png(data: &str, path: &Path) -> Result<(), PairingError> {
+    let image = invite_qr_code(data)?
+        .render::<image::Luma<u8>>()
+        .min_dimensions(256, 256)
+        .quiet_zone(true)
+        .build();
+    image
+        .save(path)
+        .map_err(|e| PairingError::QrCode(format!("Failed to write QR image: {e}")))
+}
+
+/// Write invite data as a QR code PNG (stub when feature not enabled)
+#[cfg(not(feature = "qr"))]
+pub fn write_invite_qr_png(_data: &str, _path: &Path) -> Result<(), PairingError> {
+    Err(PairingError::QrCode(
+        "QR code support not enabled. Rebuild with --features qr".to_string(),
+    ))
+}
+
+/// Render invite data as a QR code of Unicode half blocks for headless terminals
+#[cfg(feature = "qr")]
+pub fn render_invite_qr_terminal(data: &str) -> Result<String, PairingError> {
+    use qrcode::render::unicode::Dense1x2;
+
+    Ok(invite_qr_code(data)?
+        .render::<Dense1x2>()
+        .dark_color(Dense1x2::Light)
+        .light_color(Dense1x2::Dark)
+        .quiet_zone(true)
+        .build())
+}
+
+/// Render invite data as a terminal QR code (stub when feature not enabled)
+#[cfg(not(feature = "qr"))]
+pub fn render_invite_qr_terminal(_data: &str) -> Result<String, PairingError> {
+    Err(PairingError::QrCode(
+        "QR code support not enabled. Rebuild with --features qr".to_string(),
+    ))
+}
+
 /// Handles pairing operations
 /// Requirements: 2.1-2.8
 pub struct PairingClient {
@@ -733,12 +1066,19 @@ pub struct PairingClient {
     started_at: Option<SystemTime>,
     /// Transport preference
     transport_preference: TransportPreference,
+    /// Decision log of the most recent transport send
+    last_ladder: Option<LadderResult>,
+    /// Checks user-entered SAS codes while in AwaitingSAS
+    sas_verifier: Option<SasVerifier>,
+    /// Audit log for security events
+    audit: Option<Arc<AuditLogger>>,
 }
 
 /// State of the pairing operation
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub enum PairingState {
     /// Initial state, ready to import invite
+    #[default]
     Idle,
     /// Invite has been imported and validated
     InviteImported {
@@ -753,6 +1093,7 @@ pub enum PairingState {
     /// Receipt received, awaiting SAS verification
     AwaitingSAS {
         sas: String,
+        sas_words: String,
         receipt: PairReceiptV1,
         invite: ParsedInvite,
     },
@@ -767,11 +1108,6 @@ pub enum PairingState {
     },
 }
 
-impl Default for PairingState {
-    fn default() -> Self {
-        Self::Idle
-    }
-}
 
 impl PairingClient {
     /// Create a new pairing client with identity manager
@@ -786,6 +1122,9 @@ impl PairingClient {
             timeout: Duration::from_secs(300), // 5 minutes
             started_at: None,
             transport_preference: TransportPreference::Auto,
+            last_ladder: None,
+            sas_verifier: None,
+            audit: None,
         }
     }
 
@@ -800,6 +1139,9 @@ impl PairingClient {
             timeout: Duration::from_secs(300),
             started_at: None,
             transport_preference: TransportPreference::Auto,
+            last_ladder: None,
+            sas_verifier: None,
+            audit: None,
         }
     }
 
@@ -818,6 +1160,9 @@ impl PairingClient {
             timeout: Duration::from_secs(300),
             started_at: None,
             transport_preference: TransportPreference::Auto,
+            last_ladder: None,
+            sas_verifier: None,
+            audit: None,
         }
     }
 
@@ -832,6 +1177,9 @@ impl PairingClient {
             timeout: Duration::from_secs(300),
             started_at: None,
             transport_preference: TransportPreference::Auto,
+            last_ladder: None,
+            sas_verifier: None,
+            audit: None,
         }
     }
 
@@ -850,6 +1198,16 @@ impl PairingClient {
         self.transport_preference = preference;
     }
 
+    /// Record security events (such as SAS mismatches) to an audit log
+    pub fn set_audit_logger(&mut self, audit: Arc<AuditLogger>) {
+        self.audit = Some(audit);
+    }
+
+    /// Get the transport decision log of the most recent send, if any
+    pub fn last_ladder(&self) -> Option<&LadderResult> {
+        self.last_ladder.as_ref()
+    }
+
     /// Import and validate an invite from various sources
     /// Requirements: 1.2, 1.3, 1.4, 1.5, 1.6
     pub fn import_invite(&mut self, source: InviteSource) -> Result<ParsedInvite, PairingError> {
@@ -917,7 +1275,6 @@ impl PairingClient {
         // Check timeout
         self.check_timeout()?;
 
-        // Validate state and extract invite
         let invite = match &self.state {
             PairingState::InviteImported { invite, .. } => invite.clone(),
             _ => {
@@ -927,20 +1284,56 @@ impl PairingClient {
             }
         };
 
+        let request = match self.preview_pair_request(invite_secret, requested_permissions) {
+            Ok(request) => request,
+            Err(e) => {
+                let reason = match &e {
+                    PairingError::InvalidProof => "Invite secret does not match",
+                    PairingError::InviteExpired(_) => "Invite has expired",
+                    _ => return Err(e),
+                };
+                self.state = PairingState::Failed {
+                    reason: reason.to_string(),
+                };
+                return Err(e);
+            }
+        };
+
+        // Transition to RequestSent state
+        self.state = PairingState::RequestSent {
+            request: request.clone(),
+            invite,
+        };
+
+        Ok(request)
+    }
+
+    /// Build the PairRequestV1 that `generate_pair_request` would produce,
+    /// without changing state or contacting the device (dry run)
+    /// Requirements: 2.1, 2.2
+    pub fn preview_pair_request(
+        &self,
+        invite_secret: &[u8; 32],
+        requested_permissions: u32,
+    ) -> Result<PairRequestV1, PairingError> {
+        // Validate state and extract invite
+        let invite = match &self.state {
+            PairingState::InviteImported { invite, .. } => invite,
+            _ => {
+                return Err(PairingError::InvalidState(
+                    "Must import invite before generating pair request".to_string(),
+                ));
+            }
+        };
+
         // Verify the secret matches the invite's hash
         let computed_hash = sha256(invite_secret);
         if computed_hash.to_vec() != invite.invite.invite_secret_hash {
-            self.state = PairingState::Failed {
-                reason: "Invite secret does not match".to_string(),
-            };
             return Err(PairingError::InvalidProof);
         }
 
         // Check invite hasn't expired
         if invite.is_expired() {
-            self.state = PairingState::Failed {
-                reason: "Invite has expired".to_string(),
-            };
             return Err(PairingError::InviteExpired(invite.expires_at));
         }
 
@@ -993,12 +1386,6 @@ impl PairingClient {
             timestamp: now,
         };
 
-        // Transition to RequestSent state
-        self.state = PairingState::RequestSent {
-            request: request.clone(),
-            invite,
-        };
-
         Ok(request)
     }
 
@@ -1028,10 +1415,13 @@ impl PairingClient {
             }
         };
 
-        // Send via transport
-        self.transport
+        // Send via transport, keeping the decision log even if every transport failed
+        let (result, ladder) = self
+            .transport
             .send_pair_request(&device_id, &request, self.transport_preference)
-            .await?;
+            .await;
+        self.last_ladder = Some(ladder);
+        result?;
 
         Ok(request)
     }
@@ -1243,7 +1633,7 @@ impl PairingClient {
     }
 
     /// Convert permission strings to bitmask
-    fn permissions_to_mask(permissions: &[String]) -> u32 {
+    pub fn permissions_to_mask(permissions: &[String]) -> u32 {
         let mut mask = 0u32;
         for perm in permissions {
             match perm.to_lowercase().as_str() {
@@ -1332,17 +1722,24 @@ impl PairingClient {
             invite.invite.expires_at,
         );
 
-        // Compute 6-digit SAS code
+        // Compute 6-digit SAS code and the equivalent word phrase
         let sas = compute_pairing_sas_6digit_v1(&sas_transcript);
+        let sas_words = crate::sas::render_wordlist(&sas_transcript);
+
+        self.await_sas(sas.clone(), sas_words, receipt, invite);
 
-        // Transition to AwaitingSAS state
+        Ok(sas)
+    }
+
+    /// Transition to AwaitingSAS with a fresh verifier for `sas`
+    fn await_sas(&mut self, sas: String, sas_words: String, receipt: PairReceiptV1, invite: ParsedInvite) {
+        self.sas_verifier = Some(SasVerifier::new(&sas));
         self.state = PairingState::AwaitingSAS {
-            sas: sas.clone(),
+            sas,
+            sas_words,
             receipt,
             invite,
         };
-
-        Ok(sas)
     }
 
     /// Verify the device signature on a PairReceiptV1
@@ -1391,20 +1788,50 @@ impl PairingClient {
         Ok(())
     }
 
-    /// Verify SAS code with user
+    /// Check the SAS the user read off the device against ours.
+    ///
+    /// Every mismatch is recorded in the audit log. Once the allowed attempts
+    /// are used up the pairing moves to `Failed` and `Exhausted` is returned.
     /// Requirements: 2.5
-    pub fn verify_sas(&self, _sas: &str) -> Result<bool, PairingError> {
-        // Get the expected SAS from state
-        match &self.state {
-            PairingState::AwaitingSAS { sas, .. } => {
-                // In a real implementation, this would compare with user input
-                // For now, we just return the SAS for display
-                Ok(!sas.is_empty())
+    pub fn verify_sas(&mut self, entered: &str) -> Result<SasOutcome, PairingError> {
+        let device_id = match &self.state {
+            PairingState::AwaitingSAS { receipt, .. } => hex::encode(&receipt.device_id),
+            _ => {
+                return Err(PairingError::InvalidState(
+                    "Not in SAS verification state".to_string(),
+                ));
             }
-            _ => Err(PairingError::InvalidState(
-                "Not in SAS verification state".to_string(),
-            )),
+        };
+        let verifier = self
+            .sas_verifier
+            .as_mut()
+            .ok_or_else(|| PairingError::InvalidState("No SAS to verify".to_string()))?;
+
+        let outcome = verifier.verify(entered);
+        if outcome == SasOutcome::Match {
+            return Ok(outcome);
+        }
+
+        let failures = verifier.failures();
+        let exhausted = outcome == SasOutcome::Exhausted;
+        if let Some(audit) = &self.audit {
+            let event = SecurityEvent::SasMismatch {
+                device_id,
+                failures,
+                exhausted,
+            };
+            if let Err(e) = audit.log(event) {
+                tracing::warn!("Failed to audit SAS mismatch: {}", e);
+            }
+        }
+        if exhausted {
+            self.state = PairingState::Failed {
+                reason: "SAS mismatch".to_string(),
+            };
+            self.sas_verifier = None;
+            self.started_at = None;
         }
+        Ok(outcome)
     }
 
     /// Get the current SAS code if in AwaitingSAS state
@@ -1415,6 +1842,14 @@ impl PairingClient {
         }
     }
 
+    /// Get the current SAS word phrase if in AwaitingSAS state
+    pub fn get_sas_words(&self) -> Option<&str> {
+        match &self.state {
+            PairingState::AwaitingSAS { sas_words, .. } => Some(sas_words),
+            _ => None,
+        }
+    }
+
     /// Confirm SAS verification and complete pairing
     /// Requirements: 2.5, 2.6
     pub async fn confirm_sas(&mut self) -> Result<PairingResult, PairingError> {
@@ -1461,6 +1896,7 @@ impl PairingClient {
                 self.state = PairingState::Failed {
                     reason: "SAS verification rejected by user".to_string(),
                 };
+                self.sas_verifier = None;
                 self.started_at = None;
                 Ok(())
             }
@@ -1527,6 +1963,8 @@ impl PairingClient {
                 paired_at: UNIX_EPOCH + Duration::from_secs(receipt.paired_at),
                 last_session: None,
                 session_count: 0,
+                note: None,
+                cert_pin: None,
             };
             store
                 .store(stored_pairing)
@@ -1537,7 +1975,7 @@ impl PairingClient {
     }
 
     /// Convert permission mask to string list
-    fn mask_to_permissions(mask: u32) -> Vec<String> {
+    pub fn mask_to_permissions(mask: u32) -> Vec<String> {
         let mut perms = Vec::new();
         if mask & 0x01 != 0 {
             perms.push("view".to_string());
@@ -1606,6 +2044,7 @@ impl Default for PairingClient {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use zrc_security::audit::MemoryAuditLogWriter;
 
     fn create_test_invite(expires_in_secs: u64) -> InviteV1 {
         let now = SystemTime::now()
@@ -1978,4 +2417,282 @@ mod tests {
         assert!(values.contains(&"direct"));
         assert!(values.contains(&"relay"));
     }
+
+    #[tokio::test]
+    async fn test_ladder_run_records_each_transport() {
+        use crate::ladder::AttemptOutcome;
+
+        // Unreachable rendezvous so the ladder is exhausted without network access
+        let client = TransportClient::with_urls(
+            vec!["http://127.0.0.1:1".to_string()],
+            vec!["https://relay.example.com".to_string()],
+            vec![],
+        );
+        let request = PairRequestV1::default();
+
+        let (result, ladder) = client
+            .send_pair_request(&[0u8; 32], &request, TransportPreference::Auto)
+            .await;
+
+        assert!(result.is_err());
+        let recorded: Vec<_> = ladder
+            .attempts
+            .iter()
+            .map(|a| (a.transport, a.outcome))
+            .collect();
+        assert_eq!(
+            recorded,
+            vec![
+                (TransportPreference::Mesh, AttemptOutcome::Skipped),
+                (TransportPreference::Direct, AttemptOutcome::Failed),
+                (TransportPreference::Rendezvous, AttemptOutcome::Failed),
+                (TransportPreference::Relay, AttemptOutcome::Failed),
+            ]
+        );
+        assert!(ladder.attempts.iter().skip(1).all(|a| a.error.is_some()));
+        assert_eq!(ladder.selected(), None);
+    }
+
+    #[tokio::test]
+    async fn test_single_transport_records_one_attempt() {
+        let client = TransportClient::with_urls(vec![], vec![], vec![]);
+        let request = PairRequestV1::default();
+
+        let (result, ladder) = client
+            .send_pair_request(&[0u8; 32], &request, TransportPreference::Relay)
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!(ladder.attempts.len(), 1);
+        assert_eq!(ladder.attempts[0].transport, TransportPreference::Relay);
+    }
+
+    #[test]
+    fn test_url_socket_addr() {
+        assert_eq!(url_socket_addr("https://relay.example.com").unwrap(), "relay.example.com:443");
+        assert_eq!(url_socket_addr("http://127.0.0.1:8080/path").unwrap(), "127.0.0.1:8080");
+        assert!(url_socket_addr("not a url").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_quic_probe_gets_version_negotiation() {
+        // The server config needs a process-wide provider; another test may
+        // already have installed one
+        let _ = rustls::crypto::ring::default_provider().install_default();
+        let server = zrc_core::quic::QuicServer::bind("127.0.0.1:0".parse().unwrap(), b"zrc/1")
+            .await
+            .unwrap();
+        let addr = server.endpoint.local_addr().unwrap().to_string();
+        quic_probe(&addr).await.unwrap();
+
+        // Nothing listening on UDP: the probe fails rather than reporting TCP
+        let closed = std::net::UdpSocket::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        assert!(quic_probe(&closed.to_string()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_probe_ladder_reports_every_rung() {
+        use crate::ladder::AttemptOutcome;
+
+        // Bind a listener so one rung is reachable; port 1 is refused
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let reachable = listener.local_addr().unwrap().to_string();
+
+        let client = TransportClient::with_urls(
+            vec![],
+            vec!["http://127.0.0.1:1".to_string()],
+            vec![reachable],
+        );
+        let ladder = client.probe_ladder(&[0u8; 32], &[]).await;
+
+        let outcomes: Vec<_> = ladder
+            .attempts
+            .iter()
+            .map(|a| (a.transport, a.outcome))
+            .collect();
+        assert_eq!(
+            outcomes,
+            vec![
+                (TransportPreference::Mesh, AttemptOutcome::Success),
+                (TransportPreference::Direct, AttemptOutcome::Skipped),
+                (TransportPreference::Rendezvous, AttemptOutcome::Skipped),
+                (TransportPreference::Relay, AttemptOutcome::Failed),
+            ]
+        );
+        assert!(ladder.attempts[3].error.is_some());
+    }
+
+    #[test]
+    fn test_preview_pair_request_does_not_change_state() {
+        let mut client = PairingClient::new();
+        let secret = [7u8; 32];
+        let mut invite = create_test_invite(3600);
+        invite.invite_secret_hash = sha256(&secret).to_vec();
+        let base64_str = base64::engine::general_purpose::STANDARD.encode(invite.encode_to_vec());
+        client.import_invite(InviteSource::Base64(base64_str)).unwrap();
+
+        let request = client.preview_pair_request(&secret, 0x03).unwrap();
+        assert_eq!(request.requested_permissions, 0x03);
+        assert_eq!(request.operator_sign_pub.len(), 32);
+        assert_eq!(request.invite_proof.len(), 32);
+        assert!(matches!(client.state(), PairingState::InviteImported { .. }));
+
+        // A wrong secret is rejected without failing the state machine
+        let result = client.preview_pair_request(&[8u8; 32], 0x03);
+        assert!(matches!(result, Err(PairingError::InvalidProof)));
+        assert!(matches!(client.state(), PairingState::InviteImported { .. }));
+
+        // The real request still transitions to RequestSent
+        client.generate_pair_request(&secret, 0x03).unwrap();
+        assert!(matches!(client.state(), PairingState::RequestSent { .. }));
+    }
+
+    #[test]
+    fn test_generate_pair_request_wrong_secret_fails_state() {
+        let mut client = PairingClient::new();
+        let base64_str =
+            base64::engine::general_purpose::STANDARD.encode(create_test_invite(3600).encode_to_vec());
+        client.import_invite(InviteSource::Base64(base64_str)).unwrap();
+
+        let result = client.generate_pair_request(&[7u8; 32], 0x01);
+        assert!(matches!(result, Err(PairingError::InvalidProof)));
+        assert!(matches!(client.state(), PairingState::Failed { .. }));
+    }
+
+    fn client_awaiting_sas(sas: &str) -> (PairingClient, MemoryAuditLogWriter) {
+        let mut client = PairingClient::new();
+        let base64_str =
+            base64::engine::general_purpose::STANDARD.encode(create_test_invite(3600).encode_to_vec());
+        let invite = client.import_invite(InviteSource::Base64(base64_str)).unwrap();
+        let receipt = PairReceiptV1 {
+            device_id: invite.invite.device_id.clone(),
+            ..Default::default()
+        };
+        client.await_sas(sas.to_string(), String::new(), receipt, invite);
+
+        let writer = MemoryAuditLogWriter::new();
+        let signing_key = ed25519_dalek::SigningKey::from_bytes(&[9u8; 32]);
+        client.set_audit_logger(Arc::new(
+            AuditLogger::new(signing_key, Box::new(writer.clone())).unwrap(),
+        ));
+        (client, writer)
+    }
+
+    #[test]
+    fn test_verify_sas_match() {
+        let (mut client, audit) = client_awaiting_sas("482913");
+        assert_eq!(client.verify_sas("482 913").unwrap(), SasOutcome::Match);
+        assert!(matches!(client.state(), PairingState::AwaitingSAS { .. }));
+        assert!(audit.entries().is_empty());
+    }
+
+    #[test]
+    fn test_verify_sas_mismatch_audits_and_fails() {
+        let (mut client, audit) = client_awaiting_sas("482913");
+
+        assert_eq!(
+            client.verify_sas("482914").unwrap(),
+            SasOutcome::Mismatch { attempts_remaining: 2 }
+        );
+        assert!(matches!(client.state(), PairingState::AwaitingSAS { .. }));
+        client.verify_sas("000000").unwrap();
+        assert_eq!(client.verify_sas("111111").unwrap(), SasOutcome::Exhausted);
+
+        assert!(matches!(client.state(), PairingState::Failed { .. }));
+        assert!(matches!(client.verify_sas("482913"), Err(PairingError::InvalidState(_))));
+
+        let entries = audit.entries();
+        assert_eq!(entries.len(), 3);
+        assert!(entries.iter().all(|e| e.event_type == "sas_mismatch"));
+        assert_eq!(entries[2].target.as_deref(), Some(hex::encode([0u8; 32]).as_str()));
+    }
+
+    fn stored_pairing() -> StoredPairing {
+        StoredPairing {
+            device_id: hex::encode([5u8; 32]),
+            device_name: Some("Front Desk".to_string()),
+            device_sign_pub: [6u8; 32],
+            device_kex_pub: [7u8; 32],
+            permissions: vec!["view".to_string()],
+            paired_at: SystemTime::now(),
+            last_session: None,
+            session_count: 0,
+            note: None,
+            cert_pin: None,
+        }
+    }
+
+    /// Invite as `zrc-agent pair` prints it for the device of `stored_pairing`
+    fn device_invite(device_sign_pub: [u8; 32]) -> String {
+        let expires_at = (SystemTime::now() + Duration::from_secs(600))
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let invite = InviteV1 {
+            device_id: vec![5u8; 32],
+            device_sign_pub: device_sign_pub.to_vec(),
+            invite_secret_hash: sha256(&[9u8; 32]).to_vec(),
+            expires_at,
+            transport_hints: None,
+        };
+        base64::engine::general_purpose::STANDARD.encode(invite.encode_to_vec())
+    }
+
+    #[test]
+    fn test_reinvite_round_trips_through_import() {
+        let reinvite = Reinvite::from_pairing(&stored_pairing(), &device_invite([6u8; 32])).unwrap();
+
+        // The device's secret, delivered separately, pairs with the QR invite
+        let mut client = PairingClient::new();
+        let parsed = client
+            .import_invite_with_secret(InviteSource::Base64(reinvite.to_base64()), [9u8; 32])
+            .unwrap();
+        assert_eq!(parsed.device_id, hex::encode([5u8; 32]));
+        assert_eq!(parsed.invite.device_sign_pub, vec![6u8; 32]);
+        assert!(parsed.time_until_expiry().unwrap() > Duration::from_secs(590));
+    }
+
+    #[test]
+    fn test_reinvite_rejects_invite_from_other_device() {
+        assert!(matches!(
+            Reinvite::from_pairing(&stored_pairing(), &device_invite([8u8; 32])),
+            Err(PairingError::InvalidInvite(_))
+        ));
+
+        let mut pairing = stored_pairing();
+        pairing.device_id = hex::encode([4u8; 32]);
+        assert!(matches!(
+            Reinvite::from_pairing(&pairing, &device_invite([6u8; 32])),
+            Err(PairingError::InvalidInvite(_))
+        ));
+    }
+
+    #[cfg(feature = "qr")]
+    #[test]
+    fn test_invite_qr_rendering() {
+        let reinvite = Reinvite::from_pairing(&stored_pairing(), &device_invite([6u8; 32])).unwrap();
+        let data = reinvite.to_base64();
+
+        let terminal = render_invite_qr_terminal(&data).unwrap();
+        assert!(terminal.lines().count() > 10);
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("qr.png");
+        write_invite_qr_png(&data, &path).unwrap();
+
+        // The rendered image decodes back to the same invite
+        let mut client = PairingClient::new();
+        let parsed = client.import_invite(InviteSource::QrImage(path)).unwrap();
+        assert_eq!(parsed.device_id, hex::encode([5u8; 32]));
+    }
+
+    #[cfg(not(feature = "qr"))]
+    #[test]
+    fn test_invite_qr_rendering_not_enabled() {
+        assert!(matches!(render_invite_qr_terminal("data"), Err(PairingError::QrCode(_))));
+        assert!(matches!(
+            write_invite_qr_png("data", Path::new("qr.png")),
+            Err(PairingError::QrCode(_))
+        ));
+    }
 }
@@ -9,6 +9,8 @@ use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use rusqlite::{params, Connection, OptionalExtension};
 use thiserror::Error;
 
+use crate::pairing::PairingError;
+
 /// Pairings store errors
 #[derive(Debug, Error)]
 pub enum StoreError {
@@ -47,6 +49,10 @@ pub struct StoredPairing {
     pub last_session: Option<SystemTime>,
     /// Total session count
     pub session_count: u32,
+    /// Operator-supplied note (optional)
+    pub note: Option<String>,
+    /// SHA-256 of the device's QUIC server certificate, checked on connect
+    pub cert_pin: Option<[u8; 32]>,
 }
 
 /// Persistent storage for pairings using SQLite
@@ -76,13 +82,23 @@ impl PairingsStore {
                 permissions TEXT NOT NULL,
                 paired_at INTEGER NOT NULL,
                 last_session INTEGER,
-                session_count INTEGER NOT NULL DEFAULT 0
+                session_count INTEGER NOT NULL DEFAULT 0,
+                note TEXT,
+                cert_pin BLOB
             );
             
             CREATE INDEX IF NOT EXISTS idx_pairings_paired_at ON pairings(paired_at);
             "#,
         )?;
 
+        // Databases created before notes were supported lack the column
+        if conn.prepare("SELECT note FROM pairings LIMIT 0").is_err() {
+            conn.execute("ALTER TABLE pairings ADD COLUMN note TEXT", [])?;
+        }
+        if conn.prepare("SELECT cert_pin FROM pairings LIMIT 0").is_err() {
+            conn.execute("ALTER TABLE pairings ADD COLUMN cert_pin BLOB", [])?;
+        }
+
         Ok(Self { conn })
     }
 
@@ -97,7 +113,7 @@ impl PairingsStore {
     pub fn list(&self) -> Result<Vec<StoredPairing>, StoreError> {
         let mut stmt = self.conn.prepare(
             "SELECT device_id, device_name, device_sign_pub, device_kex_pub, 
-                    permissions, paired_at, last_session, session_count 
+                    permissions, paired_at, last_session, session_count, note, cert_pin 
              FROM pairings ORDER BY paired_at DESC",
         )?;
 
@@ -111,6 +127,8 @@ impl PairingsStore {
                 let paired_at_unix: i64 = row.get(5)?;
                 let last_session_unix: Option<i64> = row.get(6)?;
                 let session_count: u32 = row.get(7)?;
+                let note: Option<String> = row.get(8)?;
+                let cert_pin: Option<Vec<u8>> = row.get(9)?;
 
                 Ok(StoredPairing {
                     device_id,
@@ -122,6 +140,8 @@ impl PairingsStore {
                     last_session: last_session_unix
                         .map(|ts| UNIX_EPOCH + Duration::from_secs(ts as u64)),
                     session_count,
+                    note,
+                    cert_pin: cert_pin.and_then(|pin| pin.try_into().ok()),
                 })
             })?
             .collect::<Result<Vec<_>, _>>()?;
@@ -134,7 +154,7 @@ impl PairingsStore {
     pub fn get(&self, device_id: &str) -> Result<Option<StoredPairing>, StoreError> {
         let mut stmt = self.conn.prepare(
             "SELECT device_id, device_name, device_sign_pub, device_kex_pub, 
-                    permissions, paired_at, last_session, session_count 
+                    permissions, paired_at, last_session, session_count, note, cert_pin 
              FROM pairings WHERE device_id = ?",
         )?;
 
@@ -148,6 +168,8 @@ impl PairingsStore {
                 let paired_at_unix: i64 = row.get(5)?;
                 let last_session_unix: Option<i64> = row.get(6)?;
                 let session_count: u32 = row.get(7)?;
+                let note: Option<String> = row.get(8)?;
+                let cert_pin: Option<Vec<u8>> = row.get(9)?;
 
                 Ok(StoredPairing {
                     device_id,
@@ -159,6 +181,8 @@ impl PairingsStore {
                     last_session: last_session_unix
                         .map(|ts| UNIX_EPOCH + Duration::from_secs(ts as u64)),
                     session_count,
+                    note,
+                    cert_pin: cert_pin.and_then(|pin| pin.try_into().ok()),
                 })
             })
             .optional()?;
@@ -166,6 +190,77 @@ impl PairingsStore {
         Ok(pairing)
     }
 
+    /// Resolve a pairing from a full device ID or a unique hex prefix
+    ///
+    /// Matching is case-insensitive. An exact match always wins; otherwise the
+    /// prefix must select exactly one stored pairing. Missing, empty, and
+    /// ambiguous prefixes all yield `PairingError::NotPaired`.
+    pub fn resolve_prefix(&self, prefix: &str) -> Result<StoredPairing, PairingError> {
+        let prefix = prefix.trim().to_ascii_lowercase();
+        if prefix.is_empty() {
+            return Err(PairingError::NotPaired("empty device ID".to_string()));
+        }
+
+        let pairings = self
+            .list()
+            .map_err(|e| PairingError::Storage(e.to_string()))?;
+
+        if let Some(exact) = pairings
+            .iter()
+            .find(|p| p.device_id.eq_ignore_ascii_case(&prefix))
+        {
+            return Ok(exact.clone());
+        }
+
+        let mut matches = pairings
+            .into_iter()
+            .filter(|p| p.device_id.to_ascii_lowercase().starts_with(&prefix));
+
+        match (matches.next(), matches.next()) {
+            (Some(pairing), None) => Ok(pairing),
+            (Some(_), Some(_)) => Err(PairingError::NotPaired(format!(
+                "ambiguous device ID prefix: {prefix}"
+            ))),
+            (None, _) => Err(PairingError::NotPaired(prefix)),
+        }
+    }
+
+    /// Set the display name of a pairing identified by ID or unique prefix
+    pub fn rename(&self, prefix: &str, name: &str) -> Result<StoredPairing, PairingError> {
+        let mut pairing = self.resolve_prefix(prefix)?;
+        pairing.device_name = Some(name.to_string());
+        self.update(&pairing)
+            .map_err(|e| PairingError::Storage(e.to_string()))?;
+        Ok(pairing)
+    }
+
+    /// Set or clear the note of a pairing identified by ID or unique prefix
+    pub fn set_note(
+        &self,
+        prefix: &str,
+        note: Option<&str>,
+    ) -> Result<StoredPairing, PairingError> {
+        let mut pairing = self.resolve_prefix(prefix)?;
+        pairing.note = note.map(|n| n.to_string());
+        self.update(&pairing)
+            .map_err(|e| PairingError::Storage(e.to_string()))?;
+        Ok(pairing)
+    }
+
+    /// Pin the certificate the device's QUIC server must present, or clear
+    /// the pin, for a pairing identified by ID or unique prefix
+    pub fn set_cert_pin(
+        &self,
+        prefix: &str,
+        cert_pin: Option<[u8; 32]>,
+    ) -> Result<StoredPairing, PairingError> {
+        let mut pairing = self.resolve_prefix(prefix)?;
+        pairing.cert_pin = cert_pin;
+        self.update(&pairing)
+            .map_err(|e| PairingError::Storage(e.to_string()))?;
+        Ok(pairing)
+    }
+
     /// Store new pairing
     /// Requirements: 7.2
     pub fn store(&self, pairing: StoredPairing) -> Result<(), StoreError> {
@@ -186,8 +281,8 @@ impl PairingsStore {
         self.conn.execute(
             "INSERT OR REPLACE INTO pairings 
              (device_id, device_name, device_sign_pub, device_kex_pub, 
-              permissions, paired_at, last_session, session_count)
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+              permissions, paired_at, last_session, session_count, note, cert_pin)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
             params![
                 pairing.device_id,
                 pairing.device_name,
@@ -197,6 +292,8 @@ impl PairingsStore {
                 paired_at_unix,
                 last_session_unix,
                 pairing.session_count,
+                pairing.note,
+                pairing.cert_pin.map(|pin| pin.to_vec()),
             ],
         )?;
 
@@ -222,7 +319,8 @@ impl PairingsStore {
         let rows = self.conn.execute(
             "UPDATE pairings SET 
              device_name = ?, device_sign_pub = ?, device_kex_pub = ?,
-             permissions = ?, paired_at = ?, last_session = ?, session_count = ?
+             permissions = ?, paired_at = ?, last_session = ?, session_count = ?,
+             note = ?, cert_pin = ?
              WHERE device_id = ?",
             params![
                 pairing.device_name,
@@ -232,6 +330,8 @@ impl PairingsStore {
                 paired_at_unix,
                 last_session_unix,
                 pairing.session_count,
+                pairing.note,
+                pairing.cert_pin.map(|pin| pin.to_vec()),
                 pairing.device_id,
             ],
         )?;
@@ -286,6 +386,8 @@ impl PairingsStore {
             paired_at: String,
             last_session: Option<String>,
             session_count: u32,
+            note: Option<String>,
+            cert_pin: Option<String>,
         }
 
         let exported: Vec<ExportedPairing> = pairings
@@ -306,6 +408,8 @@ impl PairingsStore {
                     paired_at: paired_at.to_rfc3339(),
                     last_session,
                     session_count: p.session_count,
+                    note: p.note,
+                    cert_pin: p.cert_pin.map(hex::encode),
                 }
             })
             .collect();
@@ -332,6 +436,10 @@ impl PairingsStore {
             paired_at: String,
             last_session: Option<String>,
             session_count: u32,
+            #[serde(default)]
+            note: Option<String>,
+            #[serde(default)]
+            cert_pin: Option<String>,
         }
 
         let imported: Vec<ImportedPairing> = serde_json::from_str(&contents)
@@ -349,15 +457,25 @@ impl PairingsStore {
                 .try_into()
                 .map_err(|_| StoreError::Serialization("Invalid key length".to_string()))?;
 
+            let cert_pin = p
+                .cert_pin
+                .map(|pin| {
+                    hex::decode(&pin)
+                        .map_err(|e| StoreError::Serialization(e.to_string()))?
+                        .try_into()
+                        .map_err(|_| StoreError::Serialization("Invalid certificate pin length".to_string()))
+                })
+                .transpose()?;
+
             let paired_at = chrono::DateTime::parse_from_rfc3339(&p.paired_at)
                 .map_err(|e| StoreError::Serialization(e.to_string()))?
                 .with_timezone(&chrono::Utc);
 
-            let last_session = p.last_session.map(|s| {
+            let last_session = p.last_session.and_then(|s| {
                 chrono::DateTime::parse_from_rfc3339(&s)
                     .map(|dt| dt.with_timezone(&chrono::Utc).into())
                     .ok()
-            }).flatten();
+            });
 
             let pairing = StoredPairing {
                 device_id: p.device_id,
@@ -368,6 +486,8 @@ impl PairingsStore {
                 paired_at: paired_at.into(),
                 last_session,
                 session_count: p.session_count,
+                note: p.note,
+                cert_pin,
             };
 
             self.store(pairing)?;
@@ -393,9 +513,15 @@ mod tests {
             paired_at: SystemTime::now(),
             last_session: None,
             session_count: 0,
+            note: None,
+            cert_pin: None,
         }
     }
 
+    fn open_test_store(temp_dir: &TempDir) -> PairingsStore {
+        PairingsStore::open(&temp_dir.path().join("pairings.db")).unwrap()
+    }
+
     #[test]
     fn test_store_and_get() {
         let temp_dir = TempDir::new().unwrap();
@@ -479,4 +605,162 @@ mod tests {
         assert!(store2.get("device1").unwrap().is_some());
         assert!(store2.get("device2").unwrap().is_some());
     }
+
+    #[test]
+    fn test_resolve_prefix_unique() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = open_test_store(&temp_dir);
+        store.store(create_test_pairing("abc123")).unwrap();
+        store.store(create_test_pairing("def456")).unwrap();
+
+        assert_eq!(store.resolve_prefix("ab").unwrap().device_id, "abc123");
+        assert_eq!(store.resolve_prefix("d").unwrap().device_id, "def456");
+    }
+
+    #[test]
+    fn test_resolve_prefix_is_case_insensitive() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = open_test_store(&temp_dir);
+        store.store(create_test_pairing("abc123")).unwrap();
+
+        assert_eq!(store.resolve_prefix("ABC").unwrap().device_id, "abc123");
+    }
+
+    #[test]
+    fn test_resolve_prefix_ambiguous() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = open_test_store(&temp_dir);
+        store.store(create_test_pairing("abc123")).unwrap();
+        store.store(create_test_pairing("abd456")).unwrap();
+
+        assert!(matches!(
+            store.resolve_prefix("ab"),
+            Err(PairingError::NotPaired(_))
+        ));
+        assert_eq!(store.resolve_prefix("abc").unwrap().device_id, "abc123");
+    }
+
+    #[test]
+    fn test_resolve_prefix_missing_or_empty() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = open_test_store(&temp_dir);
+        store.store(create_test_pairing("abc123")).unwrap();
+
+        assert!(matches!(
+            store.resolve_prefix("ff"),
+            Err(PairingError::NotPaired(_))
+        ));
+        assert!(matches!(
+            store.resolve_prefix(""),
+            Err(PairingError::NotPaired(_))
+        ));
+        assert!(matches!(
+            store.resolve_prefix("abc1234"),
+            Err(PairingError::NotPaired(_))
+        ));
+    }
+
+    #[test]
+    fn test_resolve_prefix_exact_match_wins() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = open_test_store(&temp_dir);
+        store.store(create_test_pairing("abc")).unwrap();
+        store.store(create_test_pairing("abc123")).unwrap();
+
+        assert_eq!(store.resolve_prefix("abc").unwrap().device_id, "abc");
+    }
+
+    #[test]
+    fn test_rename_persists() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = open_test_store(&temp_dir);
+        store.store(create_test_pairing("abc123")).unwrap();
+
+        let renamed = store.rename("abc", "Front Desk").unwrap();
+        assert_eq!(renamed.device_name.as_deref(), Some("Front Desk"));
+
+        let stored = store.get("abc123").unwrap().unwrap();
+        assert_eq!(stored.device_name.as_deref(), Some("Front Desk"));
+    }
+
+    #[test]
+    fn test_set_note_and_clear() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = open_test_store(&temp_dir);
+        store.store(create_test_pairing("abc123")).unwrap();
+
+        store.set_note("abc", Some("Rack 4, left")).unwrap();
+        let stored = store.get("abc123").unwrap().unwrap();
+        assert_eq!(stored.note.as_deref(), Some("Rack 4, left"));
+
+        store.set_note("abc123", None).unwrap();
+        let stored = store.get("abc123").unwrap().unwrap();
+        assert!(stored.note.is_none());
+    }
+
+    #[test]
+    fn test_cert_pin_persists_and_survives_export_import() {
+        let temp_dir = TempDir::new().unwrap();
+        let export_path = temp_dir.path().join("export.json");
+        let store1 = open_test_store(&temp_dir);
+        store1.store(create_test_pairing("abc123")).unwrap();
+        assert!(store1.get("abc123").unwrap().unwrap().cert_pin.is_none());
+
+        store1.set_cert_pin("abc", Some([9u8; 32])).unwrap();
+        assert_eq!(store1.get("abc123").unwrap().unwrap().cert_pin, Some([9u8; 32]));
+        store1.export(&export_path).unwrap();
+
+        let store2 = PairingsStore::open(&temp_dir.path().join("pairings2.db")).unwrap();
+        store2.import(&export_path).unwrap();
+        assert_eq!(store2.get("abc123").unwrap().unwrap().cert_pin, Some([9u8; 32]));
+
+        store1.set_cert_pin("abc123", None).unwrap();
+        assert!(store1.get("abc123").unwrap().unwrap().cert_pin.is_none());
+    }
+
+    #[test]
+    fn test_note_survives_export_import() {
+        let temp_dir = TempDir::new().unwrap();
+        let export_path = temp_dir.path().join("export.json");
+        let store1 = open_test_store(&temp_dir);
+        let mut pairing = create_test_pairing("abc123");
+        pairing.note = Some("Lab machine".to_string());
+        store1.store(pairing).unwrap();
+        store1.export(&export_path).unwrap();
+
+        let store2 = PairingsStore::open(&temp_dir.path().join("pairings2.db")).unwrap();
+        store2.import(&export_path).unwrap();
+        let imported = store2.get("abc123").unwrap().unwrap();
+        assert_eq!(imported.note.as_deref(), Some("Lab machine"));
+    }
+
+    #[test]
+    fn test_open_migrates_legacy_schema() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("pairings.db");
+        {
+            let conn = Connection::open(&db_path).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE pairings (
+                    device_id TEXT PRIMARY KEY,
+                    device_name TEXT,
+                    device_sign_pub BLOB NOT NULL,
+                    device_kex_pub BLOB NOT NULL,
+                    permissions TEXT NOT NULL,
+                    paired_at INTEGER NOT NULL,
+                    last_session INTEGER,
+                    session_count INTEGER NOT NULL DEFAULT 0
+                );",
+            )
+            .unwrap();
+        }
+
+        let store = PairingsStore::open(&db_path).unwrap();
+        store.store(create_test_pairing("abc123")).unwrap();
+        store.set_note("abc", Some("migrated")).unwrap();
+        assert_eq!(
+            store.get("abc123").unwrap().unwrap().note.as_deref(),
+            Some("migrated")
+        );
+    }
 }
@@ -330,7 +330,7 @@ mod tests {
                 ..Default::default()
             };
             
-            let result = config.with_overrides(&overrides);
+            let result = config.with_overrides(&overrides).unwrap();
             
             // Property: CLI override must take precedence
             prop_assert_eq!(
@@ -354,7 +354,7 @@ mod tests {
                 ..Default::default()
             };
             
-            let result = config.with_overrides(&overrides);
+            let result = config.with_overrides(&overrides).unwrap();
             
             // Property: CLI override must take precedence
             prop_assert_eq!(
@@ -378,7 +378,7 @@ mod tests {
                 ..Default::default()
             };
             
-            let result = config.with_overrides(&overrides);
+            let result = config.with_overrides(&overrides).unwrap();
             
             // Property: CLI override must take precedence
             prop_assert_eq!(
@@ -402,7 +402,7 @@ mod tests {
                 ..Default::default()
             };
             
-            let result = config.with_overrides(&overrides);
+            let result = config.with_overrides(&overrides).unwrap();
             
             if cli_debug {
                 // Property: When debug is true, log level must be "debug"
@@ -435,7 +435,7 @@ mod tests {
                 ..Default::default()
             };
             
-            let result = config.with_overrides(&overrides);
+            let result = config.with_overrides(&overrides).unwrap();
             
             // Property: CLI override must take precedence
             prop_assert_eq!(
@@ -459,7 +459,7 @@ mod tests {
                 ..Default::default()
             };
             
-            let result = config.with_overrides(&overrides);
+            let result = config.with_overrides(&overrides).unwrap();
             
             // Property: CLI override must take precedence
             prop_assert_eq!(
@@ -484,7 +484,7 @@ mod tests {
             // Apply empty overrides
             let overrides = CliOverrides::default();
             
-            let result = config.with_overrides(&overrides);
+            let result = config.with_overrides(&overrides).unwrap();
             
             // Property: All values must be preserved when no overrides
             prop_assert_eq!(
@@ -522,7 +522,7 @@ mod tests {
                 ..Default::default()
             };
             
-            let result = config.with_overrides(&overrides);
+            let result = config.with_overrides(&overrides).unwrap();
             
             // Property: Empty vectors must not override
             prop_assert_eq!(
@@ -563,7 +563,7 @@ mod tests {
                 ..Default::default()
             };
             
-            let result = config.with_overrides(&overrides);
+            let result = config.with_overrides(&overrides).unwrap();
             
             // Property: All CLI overrides must be applied
             prop_assert_eq!(
@@ -599,7 +599,7 @@ mod tests {
     /// Strategy to generate arbitrary device IDs (hex strings)
     fn device_id_strategy() -> impl Strategy<Value = String> {
         prop::collection::vec(any::<u8>(), 16..=32)
-            .prop_map(|bytes| hex::encode(bytes))
+            .prop_map(hex::encode)
     }
 
     /// Strategy to generate arbitrary device names
@@ -653,6 +653,8 @@ mod tests {
                 paired_at,
                 last_session,
                 session_count,
+                note: None,
+                cert_pin: None,
             }
         })
     }
@@ -1042,7 +1044,7 @@ mod tests {
             
             // Property: Table mode outputs must be non-empty for valid data
             prop_assert!(
-                !formatter.format_pairings(&[pairing.clone()]).is_empty(),
+                !formatter.format_pairings(std::slice::from_ref(&pairing)).is_empty(),
                 "Table mode pairings output must be non-empty"
             );
             prop_assert!(
@@ -1312,7 +1314,7 @@ mod tests {
             
             // Property: Exit code must be in valid range for process exit codes
             prop_assert!(
-                value >= 0 && value <= 255,
+                (0..=255).contains(&value),
                 "ExitCode::{:?} value {} must be in range 0-255",
                 code,
                 value
@@ -1332,7 +1334,7 @@ mod tests {
             // original value is in the valid u8 range
             let value = code as i32;
             prop_assert!(
-                value >= 0 && value <= 255,
+                (0..=255).contains(&value),
                 "ExitCode::{:?} must convert to valid u8 for process exit code",
                 code
             );
@@ -0,0 +1,140 @@
+//! SAS rendering for human verification
+//!
+//! The 6-digit SAS is easy to mishear over a noisy phone call, so the same
+//! SAS entropy can also be rendered as a short phrase drawn from a fixed
+//! 256-word list (one word per byte, PGP-wordlist style).
+//!
+//! Requirements: 2.5
+
+use zrc_crypto::hash::sha256;
+
+/// Number of SAS hash bytes rendered as words.
+///
+/// These are the same bytes `compute_pairing_sas_6digit_v1` reduces to six
+/// digits, so both representations carry the same entropy.
+pub const SAS_WORD_COUNT: usize = 4;
+
+/// Separator between words in a rendered phrase
+pub const SAS_WORD_SEPARATOR: char = '-';
+
+/// Fixed 256-entry wordlist; the index of each word is the byte it encodes.
+///
+/// The order is part of the protocol: changing it changes every phrase.
+pub const SAS_WORDLIST: [&str; 256] = [
+    "alpha", "bravo", "charlie", "delta", "echo", "foxtrot", "golf", "hotel",
+    "india", "juliet", "kilo", "lima", "mike", "november", "oscar", "papa",
+    "quebec", "romeo", "sierra", "tango", "uniform", "victor", "whiskey", "xray",
+    "yankee", "zulu", "acorn", "amber", "anchor", "anvil", "apple", "arrow",
+    "aspen", "atlas", "autumn", "badger", "bamboo", "banjo", "basil", "beacon",
+    "beaver", "beetle", "birch", "bishop", "blossom", "bonsai", "breeze", "bridge",
+    "bronze", "bucket", "buffalo", "cactus", "camel", "canary", "canyon", "carbon",
+    "castle", "cedar", "cello", "cherry", "cinder", "citrus", "clover", "cobalt",
+    "comet", "copper", "coral", "cosmos", "cotton", "coyote", "crater", "cricket",
+    "crystal", "cypress", "dagger", "daisy", "dolphin", "domino", "dragon", "dune",
+    "eagle", "ember", "emerald", "engine", "fable", "falcon", "fennel", "ferry",
+    "fiddle", "finch", "flint", "forest", "fossil", "fountain", "galaxy", "garnet",
+    "gazelle", "geyser", "ginger", "glacier", "glider", "goblet", "granite", "gravel",
+    "hammer", "harbor", "harvest", "hazel", "helmet", "heron", "hickory", "honey",
+    "horizon", "hornet", "husky", "iceberg", "igloo", "indigo", "iris", "island",
+    "ivory", "jaguar", "jasmine", "jester", "jewel", "jigsaw", "jungle", "kayak",
+    "kernel", "kestrel", "kettle", "kiwi", "koala", "ladder", "lagoon", "lantern",
+    "laser", "legend", "lemon", "lily", "lizard", "lobster", "lotus", "lunar",
+    "magnet", "mango", "mantle", "maple", "marble", "marlin", "meadow", "melon",
+    "meteor", "mint", "mirror", "monsoon", "mosaic", "muffin", "nectar", "needle",
+    "nickel", "nimbus", "noble", "nutmeg", "oasis", "ocean", "olive", "onyx",
+    "orbit", "orchid", "otter", "oyster", "paddle", "panda", "panther", "parrot",
+    "pebble", "pepper", "pigeon", "pilot", "pine", "pixel", "planet", "plaza",
+    "pluto", "poppy", "prism", "puffin", "pumpkin", "quartz", "quill", "quiver",
+    "rabbit", "radar", "radish", "rapid", "raptor", "raven", "reef", "ribbon",
+    "river", "robin", "rocket", "ruby", "saddle", "saffron", "salmon", "sapphire",
+    "satin", "season", "shadow", "shelter", "sherpa", "silver", "sketch", "socket",
+    "sonar", "spruce", "squid", "summit", "sunset", "swallow", "tablet", "tiger",
+    "timber", "topaz", "torch", "tornado", "tractor", "triton", "tulip", "tundra",
+    "turtle", "umbrella", "unicorn", "valley", "vapor", "velvet", "violet", "viper",
+    "volcano", "vortex", "wagon", "walnut", "walrus", "warden", "willow", "window",
+    "winter", "wizard", "yarrow", "yodel", "zebra", "zenith", "zephyr", "zinnia",
+];
+
+/// Render the SAS for a raw pairing SAS transcript as a word phrase
+///
+/// Takes the same transcript bytes passed to `compute_pairing_sas_6digit_v1`.
+pub fn render_wordlist(transcript: &[u8]) -> String {
+    let hash = sha256(transcript);
+    encode_words(&hash[..SAS_WORD_COUNT])
+}
+
+/// Encode bytes as a separator-joined word phrase
+pub fn encode_words(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|b| SAS_WORDLIST[*b as usize])
+        .collect::<Vec<_>>()
+        .join(&SAS_WORD_SEPARATOR.to_string())
+}
+
+/// Decode a word phrase back to bytes
+///
+/// Accepts `-`, whitespace, or commas as separators and ignores case.
+/// Returns `None` if any word is not in the list.
+pub fn decode_words(phrase: &str) -> Option<Vec<u8>> {
+    phrase
+        .split(|c: char| c == SAS_WORD_SEPARATOR || c == ',' || c.is_whitespace())
+        .filter(|w| !w.is_empty())
+        .map(|w| {
+            let w = w.to_ascii_lowercase();
+            SAS_WORDLIST
+                .iter()
+                .position(|candidate| *candidate == w)
+                .map(|i| i as u8)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_wordlist_is_unique_and_separator_free() {
+        let unique: HashSet<_> = SAS_WORDLIST.iter().collect();
+        assert_eq!(unique.len(), 256);
+        for word in SAS_WORDLIST {
+            assert!(!word.is_empty());
+            assert!(word.chars().all(|c| c.is_ascii_lowercase()), "bad word: {word}");
+        }
+    }
+
+    #[test]
+    fn test_every_byte_round_trips() {
+        let all: Vec<u8> = (0..=255u8).collect();
+        let phrase = encode_words(&all);
+        assert_eq!(decode_words(&phrase), Some(all));
+    }
+
+    #[test]
+    fn test_render_is_deterministic_and_matches_digits() {
+        let transcript = b"zrc_pair_sas_v1 test transcript";
+        let phrase = render_wordlist(transcript);
+        assert_eq!(phrase, render_wordlist(transcript));
+        assert_eq!(phrase.split(SAS_WORD_SEPARATOR).count(), SAS_WORD_COUNT);
+
+        // The phrase decodes to the bytes the 6-digit code is derived from
+        let bytes = decode_words(&phrase).unwrap();
+        let n = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) % 1_000_000;
+        assert_eq!(
+            format!("{:06}", n),
+            zrc_crypto::pairing::compute_pairing_sas_6digit_v1(transcript)
+        );
+    }
+
+    #[test]
+    fn test_decode_is_lenient_about_case_and_separators() {
+        assert_eq!(decode_words("Alpha bravo,CHARLIE-delta"), Some(vec![0, 1, 2, 3]));
+    }
+
+    #[test]
+    fn test_decode_rejects_unknown_words() {
+        assert_eq!(decode_words("alpha notaword"), None);
+    }
+}
@@ -5,7 +5,9 @@
 //! - Generate SessionInitRequestV1 with requested capabilities
 //! - Send session request via configured transport
 //! - Handle SessionInitResponseV1 with ticket and transport params
-//! - Establish QUIC connection with certificate verification
+//! - Establish QUIC connection with certificate verification (optionally pinned)
+//! - Re-establish a dropped QUIC connection with exponential backoff while the
+//!   ticket is still valid
 //!
 //! Requirements: 3.1-3.8, 4.1-4.8
 
@@ -14,14 +16,16 @@ use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use prost::Message;
+use serde::Serialize;
 use thiserror::Error;
 use tokio::sync::RwLock;
 
-use zrc_core::store::InMemoryStore;
 use zrc_crypto::hash::sha256;
-use zrc_proto::v1::{SessionInitRequestV1, SessionInitResponseV1};
+use zrc_crypto::session_crypto::{derive_session_crypto_v1, SessionCryptoV1};
+use zrc_proto::v1::{SessionInitRequestV1, SessionInitResponseV1, SessionTicketV1};
 
 use crate::identity::IdentityManager;
+use crate::ladder::LadderResult;
 use crate::pairing::{PairingError, TransportClient, TransportPreference};
 use crate::pairings::{PairingsStore, StoredPairing};
 
@@ -69,6 +73,19 @@ pub enum SessionError {
 
     #[error("Missing field: {0}")]
     MissingField(String),
+
+    #[error("Not supported: {0}")]
+    Unsupported(String),
+}
+
+impl SessionError {
+    /// Whether the failure is a transport drop worth reconnecting after
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            Self::ConnectionFailed(_) | Self::Transport(_) | Self::Timeout(_)
+        )
+    }
 }
 
 impl From<zrc_core::session::SessionError> for SessionError {
@@ -140,6 +157,205 @@ pub struct QuicConnectParams {
     pub ticket: Vec<u8>,
     /// Optional relay URL
     pub relay_url: Option<String>,
+    /// Expected SHA-256 of the server certificate DER; when set, a different
+    /// advertised certificate fails the connect with `AuthenticationFailed`
+    pub pinned_cert_sha256: Option<[u8; 32]>,
+}
+
+/// Parse a certificate pin: 64 hex characters (SHA-256) or a path to a DER file
+/// Requirements: 4.2
+pub fn parse_cert_pin(value: &str) -> Result<[u8; 32], SessionError> {
+    let trimmed = value.trim();
+    if trimmed.len() == 64 {
+        if let Ok(bytes) = hex::decode(trimmed) {
+            let mut pin = [0u8; 32];
+            pin.copy_from_slice(&bytes);
+            return Ok(pin);
+        }
+    }
+
+    let der = std::fs::read(trimmed).map_err(|e| {
+        SessionError::InvalidState(format!(
+            "Certificate pin must be a 64-char SHA-256 hex or a DER file path ({trimmed}: {e})"
+        ))
+    })?;
+    Ok(sha256(&der))
+}
+
+/// Check an advertised certificate fingerprint against a pinned SHA-256
+/// Requirements: 4.2
+pub fn verify_cert_pin(cert_fingerprint: &[u8; 32], pinned: &[u8; 32]) -> Result<(), SessionError> {
+    // Constant-time comparison
+    let diff = cert_fingerprint
+        .iter()
+        .zip(pinned.iter())
+        .fold(0u8, |acc, (a, b)| acc | (a ^ b));
+    if diff != 0 {
+        return Err(SessionError::AuthenticationFailed(format!(
+            "Server certificate {} does not match pinned {}",
+            hex::encode(cert_fingerprint),
+            hex::encode(pinned)
+        )));
+    }
+    Ok(())
+}
+
+/// Exponential backoff policy for re-establishing a dropped QUIC connection
+/// Requirements: 4.1, 4.7
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReconnectPolicy {
+    /// Delay before the first reconnect attempt
+    pub base_delay: Duration,
+    /// Upper bound on the delay between attempts
+    pub max_delay: Duration,
+    /// Reconnect attempts after the initial connect (0 disables reconnecting)
+    pub max_attempts: u32,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            max_attempts: 5,
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// Delay before reconnect attempt `attempt` (1-based): base * 2^(attempt-1), capped
+    pub fn delay_for(&self, attempt: u32) -> Duration {
+        let factor = 1u32.checked_shl(attempt.saturating_sub(1)).unwrap_or(u32::MAX);
+        self.base_delay
+            .checked_mul(factor)
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay)
+    }
+}
+
+/// Emitted before each reconnect attempt
+#[derive(Debug, Clone, Serialize)]
+pub struct ReconnectEvent {
+    /// Reconnect attempt number (1-based)
+    pub attempt: u32,
+    /// Configured attempt limit
+    pub max_attempts: u32,
+    /// Backoff delay before this attempt, in milliseconds
+    pub delay_ms: u64,
+    /// Error that dropped the previous connection
+    pub reason: String,
+}
+
+/// Run `connect` until it succeeds, retrying transport failures with backoff
+///
+/// `connect` receives the attempt number (0 for the initial connect). Errors
+/// that are not [`SessionError::is_retryable`] are returned immediately, and
+/// no attempt is made once the ticket's `expires_at` (unix seconds) has passed.
+/// After `max_attempts` reconnects the last error is surfaced as
+/// `ConnectionFailed`.
+/// Requirements: 4.1, 4.7
+pub async fn reconnect_with_backoff<T, F, Fut>(
+    policy: &ReconnectPolicy,
+    ticket_expires_at: u64,
+    mut connect: F,
+    mut on_reconnect: impl FnMut(&ReconnectEvent),
+) -> Result<T, SessionError>
+where
+    F: FnMut(u32) -> Fut,
+    Fut: std::future::Future<Output = Result<T, SessionError>>,
+{
+    let mut attempt = 0u32;
+    loop {
+        let err = match connect(attempt).await {
+            Ok(value) => return Ok(value),
+            Err(e) if e.is_retryable() => e,
+            Err(e) => return Err(e),
+        };
+
+        if attempt >= policy.max_attempts {
+            return Err(SessionError::ConnectionFailed(format!(
+                "Gave up after {attempt} reconnect attempt(s): {err}"
+            )));
+        }
+        attempt += 1;
+
+        let delay = policy.delay_for(attempt);
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        if now + delay >= Duration::from_secs(ticket_expires_at) {
+            return Err(SessionError::TicketExpired);
+        }
+
+        on_reconnect(&ReconnectEvent {
+            attempt,
+            max_attempts: policy.max_attempts,
+            delay_ms: delay.as_millis() as u64,
+            reason: err.to_string(),
+        });
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// Keep a session up until it ends cleanly, dialing again after drops
+///
+/// Each dial goes through [`reconnect_with_backoff`], so every outage gets the
+/// full `policy` budget. `serve` runs a connected session until it ends; a
+/// retryable error from it is a mid-session drop, reported through
+/// `on_reconnect` as attempt 0 before redialing. Other errors end the loop.
+/// Requirements: 4.1, 4.7
+pub async fn serve_with_reconnect<T, C, CFut, S, SFut>(
+    policy: &ReconnectPolicy,
+    ticket_expires_at: u64,
+    mut connect: C,
+    mut serve: S,
+    mut on_reconnect: impl FnMut(&ReconnectEvent),
+) -> Result<(), SessionError>
+where
+    C: FnMut(u32) -> CFut,
+    CFut: std::future::Future<Output = Result<T, SessionError>>,
+    S: FnMut(T) -> SFut,
+    SFut: std::future::Future<Output = Result<(), SessionError>>,
+{
+    loop {
+        let session =
+            reconnect_with_backoff(policy, ticket_expires_at, &mut connect, &mut on_reconnect)
+                .await?;
+        let err = match serve(session).await {
+            Ok(()) => return Ok(()),
+            Err(e) if e.is_retryable() => e,
+            Err(e) => return Err(e),
+        };
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        if now >= Duration::from_secs(ticket_expires_at) {
+            return Err(SessionError::TicketExpired);
+        }
+        on_reconnect(&ReconnectEvent {
+            attempt: 0,
+            max_attempts: policy.max_attempts,
+            delay_ms: 0,
+            reason: err.to_string(),
+        });
+    }
+}
+
+/// Wait for a connected session's QUIC connection to close
+///
+/// A close by either side's application ends the session; anything else
+/// (idle timeout, reset, transport error) is a drop worth reconnecting after.
+pub async fn wait_until_closed(session: QuicSession) -> Result<(), SessionError> {
+    let Some(connection) = session.connection else {
+        return Ok(());
+    };
+    match connection.closed().await {
+        quinn::ConnectionError::ApplicationClosed(_) | quinn::ConnectionError::LocallyClosed => {
+            Ok(())
+        }
+        e => Err(SessionError::Transport(e.to_string())),
+    }
 }
 
 /// Active QUIC session
@@ -152,6 +368,10 @@ pub struct QuicSession {
     pub device_id: String,
     /// Granted permissions
     pub permissions: u32,
+    /// E2EE keys derived from the ticket; reused across reconnects
+    pub crypto: Option<SessionCryptoV1>,
+    /// Underlying QUIC connection, used to open the frame and control streams
+    pub connection: Option<quinn::Connection>,
 }
 
 /// Identity keys for session operations
@@ -170,7 +390,7 @@ impl OperatorKeys {
     pub fn from_identity(identity: &IdentityManager) -> Self {
         // Compute operator ID as SHA256 of signing public key
         let sign_pub = identity.sign_pub();
-        let id32: [u8; 32] = sha256(&sign_pub).into();
+        let id32: [u8; 32] = sha256(&sign_pub);
 
         // We need to reconstruct the signing key from the public key
         // Since IdentityManager doesn't expose the private key directly,
@@ -192,14 +412,14 @@ pub struct SessionClient {
     transport: TransportClient,
     /// Pairings store for verification
     pairings_store: Option<PairingsStore>,
-    /// In-memory store for session state
-    memory_store: Arc<InMemoryStore>,
     /// Active sessions
     active_sessions: RwLock<HashMap<String, QuicSession>>,
     /// Transport preference
     transport_preference: TransportPreference,
     /// Session timeout
     timeout: Duration,
+    /// Decision log of the most recent transport send
+    last_ladder: Option<LadderResult>,
 }
 
 impl SessionClient {
@@ -209,10 +429,10 @@ impl SessionClient {
             identity: Arc::new(IdentityManager::new_ephemeral()),
             transport: TransportClient::new(),
             pairings_store: None,
-            memory_store: Arc::new(InMemoryStore::new()),
             active_sessions: RwLock::new(HashMap::new()),
             transport_preference: TransportPreference::Auto,
             timeout: Duration::from_secs(30),
+            last_ladder: None,
         }
     }
 
@@ -222,10 +442,10 @@ impl SessionClient {
             identity,
             transport: TransportClient::new(),
             pairings_store: None,
-            memory_store: Arc::new(InMemoryStore::new()),
             active_sessions: RwLock::new(HashMap::new()),
             transport_preference: TransportPreference::Auto,
             timeout: Duration::from_secs(30),
+            last_ladder: None,
         }
     }
 
@@ -239,10 +459,10 @@ impl SessionClient {
             identity,
             transport,
             pairings_store,
-            memory_store: Arc::new(InMemoryStore::new()),
             active_sessions: RwLock::new(HashMap::new()),
             transport_preference: TransportPreference::Auto,
             timeout: Duration::from_secs(30),
+            last_ladder: None,
         }
     }
 
@@ -256,6 +476,11 @@ impl SessionClient {
         self.timeout = timeout;
     }
 
+    /// Get the transport decision log of the most recent send, if any
+    pub fn last_ladder(&self) -> Option<&LadderResult> {
+        self.last_ladder.as_ref()
+    }
+
     /// Verify device is paired
     /// Requirements: 3.2
     pub fn verify_pairing(&self, device_id: &str) -> Result<StoredPairing, SessionError> {
@@ -317,7 +542,7 @@ impl SessionClient {
 
     /// Get operator ID as 32 bytes
     fn operator_id_bytes(&self) -> [u8; 32] {
-        sha256(&self.identity.sign_pub()).into()
+        sha256(&self.identity.sign_pub())
     }
 
     /// Generate a SessionInitRequestV1
@@ -332,11 +557,6 @@ impl SessionClient {
         getrandom::getrandom(&mut session_id)
             .map_err(|e| SessionError::Crypto(format!("RNG failed: {e}")))?;
 
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs();
-
         // Build the session init request
         let mut request = SessionInitRequestV1 {
             operator_id: self.operator_id_bytes().to_vec(),
@@ -409,17 +629,19 @@ impl SessionClient {
     /// Send session request via transport
     /// Requirements: 3.4
     pub async fn send_session_request(
-        &self,
+        &mut self,
         device_id: &[u8],
         request: &SessionInitRequestV1,
     ) -> Result<(), SessionError> {
         let request_bytes = request.encode_to_vec();
 
-        // Send via configured transport
-        self.transport
+        // Send via configured transport, keeping the decision log on failure too
+        let (result, ladder) = self
+            .transport
             .send_session_request(device_id, &request_bytes, self.transport_preference)
-            .await
-            .map_err(|e| SessionError::Transport(e.to_string()))
+            .await;
+        self.last_ladder = Some(ladder);
+        result.map_err(|e| SessionError::Transport(e.to_string()))
     }
 
     /// Wait for SessionInitResponseV1
@@ -459,6 +681,10 @@ impl SessionClient {
         // Verify device signature
         self.verify_response_signature(&response, device_sign_pub)?;
 
+        // The device must commit to the offers we sent; requests always say AUTO
+        zrc_core::session::verify_negotiation_commitment(0, &response)
+            .map_err(|e| SessionError::AuthenticationFailed(e.to_string()))?;
+
         // Check if session was denied (no ticket issued means denial)
         if response.issued_ticket.is_none() && response.granted_capabilities == 0 {
             return Err(SessionError::Denied("Session request denied by device".to_string()));
@@ -487,7 +713,7 @@ impl SessionClient {
                 ))?;
                 // Compute cert fingerprint from DER certificate
                 let cert_fp: [u8; 32] = if quic.server_cert_der.len() >= 32 {
-                    sha256(&quic.server_cert_der).into()
+                    sha256(&quic.server_cert_der)
                 } else {
                     [0u8; 32]
                 };
@@ -555,10 +781,79 @@ impl SessionClient {
     /// Requirements: 4.1, 4.2, 4.3
     pub async fn connect_quic(
         &self,
-        _params: QuicConnectParams,
+        params: QuicConnectParams,
+    ) -> Result<QuicSession, SessionError> {
+        // Reject an unexpected certificate before building the QUIC client
+        if let Some(pinned) = &params.pinned_cert_sha256 {
+            verify_cert_pin(&params.cert_fingerprint, pinned)?;
+        }
+
+        // TODO: Implement in task 7.3. Not a transport failure, so callers
+        // using reconnect_with_backoff give up at once instead of retrying.
+        Err(SessionError::Unsupported(
+            "QUIC session connect is not implemented yet".to_string(),
+        ))
+    }
+
+    /// Connect via QUIC, reconnecting on transport failure while the ticket is valid
+    ///
+    /// Session crypto is derived once from the ticket and attached to the
+    /// session that finally connects, so every attempt shares the same keys.
+    /// Requirements: 4.1, 4.2, 4.7
+    pub async fn connect_quic_with_reconnect(
+        &self,
+        params: QuicConnectParams,
+        policy: &ReconnectPolicy,
+        on_reconnect: impl FnMut(&ReconnectEvent),
     ) -> Result<QuicSession, SessionError> {
-        // TODO: Implement in task 7.3
-        Err(SessionError::ConnectionFailed("Not implemented".to_string()))
+        let (expires_at, crypto) = ticket_crypto(&params.ticket)?;
+
+        let mut session = reconnect_with_backoff(
+            policy,
+            expires_at,
+            |_| self.connect_quic(params.clone()),
+            on_reconnect,
+        )
+        .await?;
+        session.crypto = Some(crypto);
+        Ok(session)
+    }
+
+    /// Run a QUIC session until it ends, reconnecting after mid-session drops
+    ///
+    /// `serve` owns each connected session and returns once it ends; a
+    /// retryable error from it is a drop and the session is dialed again with
+    /// the same ticket and crypto.
+    /// Requirements: 4.1, 4.7
+    pub async fn run_quic_session<S, Fut>(
+        &self,
+        params: QuicConnectParams,
+        policy: &ReconnectPolicy,
+        serve: S,
+        on_reconnect: impl FnMut(&ReconnectEvent),
+    ) -> Result<(), SessionError>
+    where
+        S: FnMut(QuicSession) -> Fut,
+        Fut: std::future::Future<Output = Result<(), SessionError>>,
+    {
+        let (expires_at, crypto) = ticket_crypto(&params.ticket)?;
+
+        serve_with_reconnect(
+            policy,
+            expires_at,
+            |_| {
+                let params = params.clone();
+                let crypto = crypto.clone();
+                async move {
+                    let mut session = self.connect_quic(params).await?;
+                    session.crypto = Some(crypto);
+                    Ok(session)
+                }
+            },
+            serve,
+            on_reconnect,
+        )
+        .await
     }
 
     /// End a session
@@ -584,6 +879,17 @@ impl SessionClient {
     }
 }
 
+/// Decode a session ticket, returning its expiry and the derived session crypto
+fn ticket_crypto(ticket: &[u8]) -> Result<(u64, SessionCryptoV1), SessionError> {
+    let ticket = SessionTicketV1::decode(ticket)
+        .map_err(|e| SessionError::InvalidState(format!("Failed to decode ticket: {e}")))?;
+    if ticket.ticket_id.is_empty() {
+        return Err(SessionError::MissingField("ticket_id".to_string()));
+    }
+    let crypto = derive_session_crypto_v1(&ticket.session_binding, &ticket.ticket_id);
+    Ok((ticket.expires_at, crypto))
+}
+
 impl Default for SessionClient {
     fn default() -> Self {
         Self::new()
@@ -593,38 +899,26 @@ impl Default for SessionClient {
 // Extension for TransportClient to support session requests
 impl TransportClient {
     /// Send a session request to the device via configured transport
+    ///
+    /// Returns the send result together with the transport decision log.
     pub async fn send_session_request(
         &self,
         device_id: &[u8],
         request_bytes: &[u8],
         preference: TransportPreference,
-    ) -> Result<(), PairingError> {
+    ) -> (Result<(), PairingError>, LadderResult) {
         // Reuse the same transport logic as pairing
-        // The message type is different but the transport mechanism is the same
-        match preference {
-            TransportPreference::Auto => {
-                self.send_via_rendezvous(device_id, request_bytes).await
-            }
-            TransportPreference::Mesh => {
-                self.send_via_mesh(device_id, request_bytes).await
-            }
-            TransportPreference::Rendezvous => {
-                self.send_via_rendezvous(device_id, request_bytes).await
-            }
-            TransportPreference::Direct => {
-                Err(PairingError::Transport(
-                    "Direct transport not yet implemented".to_string(),
-                ))
-            }
-            TransportPreference::Relay => {
-                self.send_via_relay(device_id, request_bytes).await
-            }
-        }
-    }
+        // The message type is different but the transport mechanism is the same.
+        // Auto currently means rendezvous for session requests.
+        let transport = match preference {
+            TransportPreference::Auto => TransportPreference::Rendezvous,
+            other => other,
+        };
 
-    // Note: send_via_rendezvous, send_via_mesh, send_via_relay are already implemented
-    // in the pairing module and are private. We need to make them accessible or
-    // duplicate the logic here. For now, we'll reference the existing implementation.
+        let mut ladder = LadderResult::new();
+        let result = self.send_via(transport, device_id, request_bytes, &mut ladder).await;
+        (result, ladder)
+    }
 }
 
 #[cfg(test)]
@@ -659,4 +953,252 @@ mod tests {
         assert!(options.capabilities.is_empty());
         assert_eq!(options.timeout, Duration::from_secs(30));
     }
+
+    fn connect_params(cert_fingerprint: [u8; 32], pinned: Option<[u8; 32]>) -> QuicConnectParams {
+        QuicConnectParams {
+            host: "127.0.0.1".to_string(),
+            port: 4433,
+            cert_fingerprint,
+            ticket: Vec::new(),
+            relay_url: None,
+            pinned_cert_sha256: pinned,
+        }
+    }
+
+    #[test]
+    fn test_parse_cert_pin() {
+        let hex_pin = "ab".repeat(32);
+        assert_eq!(parse_cert_pin(&hex_pin).unwrap(), [0xab; 32]);
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let der_path = temp_dir.path().join("server.der");
+        std::fs::write(&der_path, b"certificate bytes").unwrap();
+        let pin = parse_cert_pin(der_path.to_str().unwrap()).unwrap();
+        assert_eq!(pin, sha256(b"certificate bytes"));
+
+        assert!(parse_cert_pin("not-a-pin").is_err());
+    }
+
+    #[test]
+    fn test_verify_cert_pin() {
+        assert!(verify_cert_pin(&[1u8; 32], &[1u8; 32]).is_ok());
+        assert!(matches!(
+            verify_cert_pin(&[1u8; 32], &[2u8; 32]),
+            Err(SessionError::AuthenticationFailed(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_connect_quic_rejects_pin_mismatch() {
+        let client = SessionClient::new();
+
+        let result = client.connect_quic(connect_params([1u8; 32], Some([2u8; 32]))).await;
+        assert!(matches!(result, Err(SessionError::AuthenticationFailed(_))));
+
+        // A matching pin gets past verification
+        let result = client.connect_quic(connect_params([1u8; 32], Some([1u8; 32]))).await;
+        assert!(!matches!(result, Err(SessionError::AuthenticationFailed(_))));
+    }
+
+    fn valid_ticket_bytes() -> Vec<u8> {
+        SessionTicketV1 {
+            ticket_id: vec![7u8; 16],
+            session_binding: vec![9u8; 32],
+            expires_at: 4_102_444_800, // 2100-01-01
+            ..Default::default()
+        }
+        .encode_to_vec()
+    }
+
+    fn fast_policy(max_attempts: u32) -> ReconnectPolicy {
+        ReconnectPolicy {
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(4),
+            max_attempts,
+        }
+    }
+
+    #[test]
+    fn test_reconnect_policy_delay() {
+        let policy = ReconnectPolicy {
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(1),
+            max_attempts: 10,
+        };
+        assert_eq!(policy.delay_for(1), Duration::from_millis(100));
+        assert_eq!(policy.delay_for(2), Duration::from_millis(200));
+        assert_eq!(policy.delay_for(4), Duration::from_millis(800));
+        assert_eq!(policy.delay_for(5), Duration::from_secs(1));
+        assert_eq!(policy.delay_for(64), Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_recovers_after_transport_drops() {
+        use zrc_transport::{ControlPlaneTransport, LoopbackTransport, MediaPlaneTransport};
+
+        let (transport, host) = LoopbackTransport::pair();
+        transport.disconnect();
+        let ticket = valid_ticket_bytes();
+        let mut events = Vec::new();
+
+        let result = reconnect_with_backoff(
+            &fast_policy(5),
+            4_102_444_800,
+            |attempt| {
+                // The link comes back before the third reconnect
+                if attempt == 3 {
+                    transport.connect();
+                }
+                let transport = &transport;
+                let ticket = ticket.clone();
+                async move {
+                    transport
+                        .send(&transport.peer_id(), &ticket)
+                        .await
+                        .map_err(|e| SessionError::Transport(e.to_string()))
+                }
+            },
+            |event| events.push(event.clone()),
+        )
+        .await;
+
+        assert!(result.is_ok());
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0].attempt, 1);
+        assert_eq!(events[2].delay_ms, 4);
+        // The same ticket reaches the host, once, on the successful attempt
+        assert_eq!(host.recv().await.unwrap(), (transport.local_id(), ticket));
+        assert_eq!(transport.congestion_state().bytes_in_flight, 0);
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_gives_up_with_connection_failed() {
+        use zrc_transport::{ControlPlaneTransport, MockTransport};
+
+        let transport = MockTransport::new();
+        transport.disconnect();
+        let mut attempts = 0;
+
+        let result: Result<(), SessionError> = reconnect_with_backoff(
+            &fast_policy(2),
+            4_102_444_800,
+            |_| {
+                attempts += 1;
+                let transport = &transport;
+                async move {
+                    transport
+                        .send(&[1u8; 32], b"ticket")
+                        .await
+                        .map_err(|e| SessionError::Transport(e.to_string()))
+                }
+            },
+            |_| {},
+        )
+        .await;
+
+        assert!(matches!(result, Err(SessionError::ConnectionFailed(_))));
+        assert_eq!(
+            crate::errors::CommandError::exit_code(&result.unwrap_err()),
+            crate::ExitCode::ConnectionFailed
+        );
+        assert_eq!(attempts, 3);
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_stops_on_non_retryable_or_expired_ticket() {
+        let result: Result<(), SessionError> = reconnect_with_backoff(
+            &fast_policy(5),
+            4_102_444_800,
+            |_| async { Err(SessionError::AuthenticationFailed("pin".to_string())) },
+            |_| panic!("must not reconnect"),
+        )
+        .await;
+        assert!(matches!(result, Err(SessionError::AuthenticationFailed(_))));
+
+        // Ticket expired in 1970: the drop is not retried
+        let result: Result<(), SessionError> = reconnect_with_backoff(
+            &fast_policy(5),
+            1,
+            |_| async { Err(SessionError::ConnectionFailed("reset".to_string())) },
+            |_| panic!("must not reconnect"),
+        )
+        .await;
+        assert!(matches!(result, Err(SessionError::TicketExpired)));
+    }
+
+    #[tokio::test]
+    async fn test_serve_with_reconnect_redials_after_mid_session_drop() {
+        let mut dials = 0;
+        let mut served = Vec::new();
+        let mut events = Vec::new();
+
+        let result = serve_with_reconnect(
+            &fast_policy(2),
+            4_102_444_800,
+            |attempt| {
+                dials += 1;
+                async move {
+                    // The second session needs one retry to come back
+                    if dials == 2 && attempt == 0 {
+                        Err(SessionError::ConnectionFailed("refused".to_string()))
+                    } else {
+                        Ok(dials)
+                    }
+                }
+            },
+            |session| {
+                served.push(session);
+                async move {
+                    // The first session drops, the next one ends cleanly
+                    if session == 1 {
+                        Err(SessionError::Transport("connection lost".to_string()))
+                    } else {
+                        Ok(())
+                    }
+                }
+            },
+            |event| events.push(event.clone()),
+        )
+        .await;
+
+        assert!(result.is_ok());
+        assert_eq!(served, vec![1, 3]);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].attempt, 0);
+        assert!(events[0].reason.contains("connection lost"));
+        assert_eq!(events[1].attempt, 1);
+
+        // A non-transport failure mid-session is not redialed
+        let result = serve_with_reconnect(
+            &fast_policy(2),
+            4_102_444_800,
+            |_| async { Ok(()) },
+            |_| async { Err(SessionError::Denied("ended by host".to_string())) },
+            |_| panic!("must not reconnect"),
+        )
+        .await;
+        assert!(matches!(result, Err(SessionError::Denied(_))));
+    }
+
+    #[tokio::test]
+    async fn test_connect_quic_with_reconnect_does_not_retry_unsupported() {
+        let client = SessionClient::new();
+        let mut params = connect_params([1u8; 32], None);
+        params.ticket = valid_ticket_bytes();
+
+        let result = client
+            .connect_quic_with_reconnect(params, &fast_policy(2), |_| {
+                panic!("must not reconnect")
+            })
+            .await;
+        assert!(matches!(result, Err(SessionError::Unsupported(_))));
+
+        // A ticket without a ticket_id is rejected before any attempt
+        let result = client
+            .connect_quic_with_reconnect(connect_params([1u8; 32], None), &fast_policy(2), |_| {
+                panic!("must not reconnect")
+            })
+            .await;
+        assert!(matches!(result, Err(SessionError::MissingField(_))));
+    }
 }
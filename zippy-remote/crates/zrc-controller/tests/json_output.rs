@@ -0,0 +1,33 @@
+//! JSON output contract of the zrc-controller binary.
+
+use std::process::Command;
+
+/// Run the CLI with a throwaway home so no real config is read or created.
+fn run_json(args: &[&str]) -> (Option<i32>, serde_json::Value) {
+    let home = tempfile::tempdir().unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_zrc-controller"))
+        .arg("--output")
+        .arg("json")
+        .args(args)
+        .env("HOME", home.path())
+        .env("XDG_CONFIG_HOME", home.path())
+        .output()
+        .unwrap();
+    let json = serde_json::from_slice(&output.stdout).unwrap_or_else(|e| {
+        panic!("stdout is not one JSON document ({e}): {}", String::from_utf8_lossy(&output.stdout))
+    });
+    (output.status.code(), json)
+}
+
+#[test]
+fn test_command_error_uses_failure_envelope() {
+    let (code, json) = run_json(&["debug", "envelope", "--decode", "not base64!"]);
+
+    assert_eq!(code, Some(5));
+    assert_eq!(json["success"], false);
+    assert!(json["error"].as_str().unwrap().starts_with("Failed to decode envelope"));
+    assert_eq!(json["error_details"]["code"], "INVALID_INPUT");
+    assert_eq!(json["error_details"]["exit_code"], 5);
+    assert_eq!(json["error_details"]["exit_code_name"], "INVALID_INPUT");
+    assert_eq!(json["error_details"]["message"], json["error"]);
+}
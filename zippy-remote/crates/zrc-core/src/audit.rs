@@ -4,18 +4,22 @@
 //!
 //! Features:
 //! - Comprehensive audit events for pairing and session lifecycle
-//! - Pluggable sinks (memory buffer, file)
+//! - Pluggable sinks (memory buffer, file, stdout JSON, batching)
+//! - Structured JSON records for SIEM ingestion
 //! - Event signing with device key for non-repudiation
 //! - Sensitive data exclusion
 
 use async_trait::async_trait;
 use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey, Verifier};
+use std::collections::{BTreeMap, VecDeque};
+use std::io::Write;
 use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
 use thiserror::Error;
 use tokio::fs::OpenOptions;
 use tokio::io::AsyncWriteExt;
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, RwLock};
 
 /// Errors from audit operations.
 #[derive(Debug, Error)]
@@ -47,6 +51,8 @@ pub enum SessionEndReason {
     TransportDisconnected,
     /// Policy violation.
     PolicyViolation,
+    /// Session idle for too long.
+    IdleTimeout,
     /// Error occurred.
     Error(String),
 }
@@ -58,6 +64,7 @@ impl std::fmt::Display for SessionEndReason {
             SessionEndReason::TicketExpired => write!(f, "ticket_expired"),
             SessionEndReason::TransportDisconnected => write!(f, "transport_disconnected"),
             SessionEndReason::PolicyViolation => write!(f, "policy_violation"),
+            SessionEndReason::IdleTimeout => write!(f, "idle_timeout"),
             SessionEndReason::Error(msg) => write!(f, "error: {}", msg),
         }
     }
@@ -127,6 +134,16 @@ pub enum AuditEvent {
         reason: String,
         timestamp: u64,
     },
+    /// Transport chosen for a session and the `type:reason` trail of
+    /// higher-preference transports that were skipped.
+    TransportSelected {
+        device_id: [u8; 32],
+        operator_id: [u8; 32],
+        session_id: [u8; 32],
+        transport: String,
+        fallback_trail: String,
+        timestamp: u64,
+    },
 
     // Security events (Requirements 9.3)
     PermissionEscalationAttempted {
@@ -163,6 +180,7 @@ impl AuditEvent {
             AuditEvent::SessionStarted { timestamp, .. } => *timestamp,
             AuditEvent::SessionEnded { timestamp, .. } => *timestamp,
             AuditEvent::SessionDenied { timestamp, .. } => *timestamp,
+            AuditEvent::TransportSelected { timestamp, .. } => *timestamp,
             AuditEvent::PermissionEscalationAttempted { timestamp, .. } => *timestamp,
             AuditEvent::PolicyViolation { timestamp, .. } => *timestamp,
             AuditEvent::RateLimitExceeded { timestamp, .. } => *timestamp,
@@ -180,6 +198,7 @@ impl AuditEvent {
             AuditEvent::SessionStarted { .. } => "SESSION_STARTED",
             AuditEvent::SessionEnded { .. } => "SESSION_ENDED",
             AuditEvent::SessionDenied { .. } => "SESSION_DENIED",
+            AuditEvent::TransportSelected { .. } => "TRANSPORT_SELECTED",
             AuditEvent::PermissionEscalationAttempted { .. } => "PERMISSION_ESCALATION_ATTEMPTED",
             AuditEvent::PolicyViolation { .. } => "POLICY_VIOLATION",
             AuditEvent::RateLimitExceeded { .. } => "RATE_LIMIT_EXCEEDED",
@@ -197,6 +216,7 @@ impl AuditEvent {
             AuditEvent::SessionStarted { device_id, .. } => device_id,
             AuditEvent::SessionEnded { device_id, .. } => device_id,
             AuditEvent::SessionDenied { device_id, .. } => device_id,
+            AuditEvent::TransportSelected { device_id, .. } => device_id,
             AuditEvent::PermissionEscalationAttempted { device_id, .. } => device_id,
             AuditEvent::PolicyViolation { device_id, .. } => device_id,
             AuditEvent::RateLimitExceeded { device_id, .. } => device_id,
@@ -214,6 +234,7 @@ impl AuditEvent {
             AuditEvent::SessionStarted { operator_id, .. } => Some(operator_id),
             AuditEvent::SessionEnded { .. } => None,
             AuditEvent::SessionDenied { operator_id, .. } => Some(operator_id),
+            AuditEvent::TransportSelected { operator_id, .. } => Some(operator_id),
             AuditEvent::PermissionEscalationAttempted { operator_id, .. } => Some(operator_id),
             AuditEvent::PolicyViolation { operator_id, .. } => Some(operator_id),
             AuditEvent::RateLimitExceeded { .. } => None,
@@ -266,6 +287,12 @@ impl AuditEvent {
             AuditEvent::SessionDenied { reason, .. } => {
                 bytes.extend_from_slice(reason.as_bytes());
             }
+            AuditEvent::TransportSelected { session_id, transport, fallback_trail, .. } => {
+                bytes.extend_from_slice(session_id);
+                bytes.extend_from_slice(transport.as_bytes());
+                bytes.push(b'|');
+                bytes.extend_from_slice(fallback_trail.as_bytes());
+            }
             AuditEvent::PermissionEscalationAttempted { 
                 requested_permissions, 
                 allowed_permissions, 
@@ -330,6 +357,11 @@ impl AuditEvent {
                 format!("[{}] {} device={} operator={} reason=\"{}\"", 
                     timestamp, self.event_type(), device_hex, op_hex, reason)
             }
+            AuditEvent::TransportSelected { session_id, transport, fallback_trail, timestamp, .. } => {
+                format!("[{}] {} device={} operator={} session={} transport={} skipped=\"{}\"", 
+                    timestamp, self.event_type(), device_hex, op_hex, 
+                    hex::encode(&session_id[..8]), transport, fallback_trail)
+            }
             AuditEvent::PermissionEscalationAttempted { 
                 requested_permissions, 
                 allowed_permissions, 
@@ -350,6 +382,73 @@ impl AuditEvent {
             }
         }
     }
+
+    /// Event-specific details as a field name to value map.
+    pub fn details(&self) -> BTreeMap<&'static str, String> {
+        let mut details = BTreeMap::new();
+        match self {
+            AuditEvent::PairRequestReceived { .. } | AuditEvent::PairRevoked { .. } => {}
+            AuditEvent::PairApproved { permissions, .. } => {
+                details.insert("permissions", format!("0x{:08x}", permissions));
+            }
+            AuditEvent::PairDenied { reason, .. } | AuditEvent::SessionDenied { reason, .. } => {
+                details.insert("reason", reason.clone());
+            }
+            AuditEvent::SessionRequested { session_id, .. } => {
+                details.insert("session_id", hex::encode(session_id));
+            }
+            AuditEvent::SessionStarted { session_id, permissions, .. } => {
+                details.insert("session_id", hex::encode(session_id));
+                details.insert("permissions", format!("0x{:08x}", permissions));
+            }
+            AuditEvent::SessionEnded { session_id, reason, duration_seconds, .. } => {
+                details.insert("session_id", hex::encode(session_id));
+                details.insert("reason", reason.to_string());
+                details.insert("duration_seconds", duration_seconds.to_string());
+            }
+            AuditEvent::TransportSelected { session_id, transport, fallback_trail, .. } => {
+                details.insert("session_id", hex::encode(session_id));
+                details.insert("transport", transport.clone());
+                details.insert("fallback_trail", fallback_trail.clone());
+            }
+            AuditEvent::PermissionEscalationAttempted {
+                requested_permissions,
+                allowed_permissions,
+                ..
+            } => {
+                details.insert("requested_permissions", format!("0x{:08x}", requested_permissions));
+                details.insert("allowed_permissions", format!("0x{:08x}", allowed_permissions));
+            }
+            AuditEvent::PolicyViolation { violation, .. } => {
+                details.insert("violation", violation.clone());
+            }
+            AuditEvent::RateLimitExceeded { source, limit_type, .. } => {
+                details.insert("source", source.clone());
+                details.insert("limit_type", limit_type.clone());
+            }
+        }
+        details
+    }
+
+    /// Structured record with type, timestamp, actor and details.
+    ///
+    /// The actor is the operator where one is involved, otherwise the device.
+    pub fn to_json(&self) -> serde_json::Value {
+        let (actor_kind, actor_id) = match self.operator_id() {
+            Some(operator_id) => ("operator", operator_id),
+            None => ("device", self.device_id()),
+        };
+        serde_json::json!({
+            "type": self.event_type(),
+            "timestamp": self.timestamp(),
+            "device_id": hex::encode(self.device_id()),
+            "actor": {
+                "kind": actor_kind,
+                "id": hex::encode(actor_id),
+            },
+            "details": self.details(),
+        })
+    }
 }
 
 
@@ -393,6 +492,40 @@ impl SignedAuditEvent {
             self.event.to_log_line(), 
             hex::encode(&self.signature[..8]))
     }
+
+    /// Structured record of the event with its signature and signer.
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut value = self.event.to_json();
+        value["signature"] = hex::encode(self.signature).into();
+        value["signer_pub"] = hex::encode(self.signer_pub).into();
+        value
+    }
+}
+
+/// Line format written by text-based sinks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AuditFormat {
+    /// Human-readable log line.
+    #[default]
+    LogLine,
+    /// One JSON object per line.
+    Json,
+}
+
+impl AuditFormat {
+    fn format_event(self, event: &AuditEvent) -> String {
+        match self {
+            AuditFormat::LogLine => event.to_log_line(),
+            AuditFormat::Json => event.to_json().to_string(),
+        }
+    }
+
+    fn format_signed(self, event: &SignedAuditEvent) -> String {
+        match self {
+            AuditFormat::LogLine => event.to_log_line(),
+            AuditFormat::Json => event.to_json().to_string(),
+        }
+    }
 }
 
 /// Trait for audit event sinks.
@@ -472,6 +605,7 @@ pub struct FileAuditSink {
     path: std::path::PathBuf,
     sign_events: bool,
     signing_key: Option<SigningKey>,
+    format: AuditFormat,
 }
 
 impl FileAuditSink {
@@ -481,6 +615,7 @@ impl FileAuditSink {
             path: path.as_ref().to_path_buf(),
             sign_events: false,
             signing_key: None,
+            format: AuditFormat::LogLine,
         }
     }
 
@@ -490,9 +625,16 @@ impl FileAuditSink {
             path: path.as_ref().to_path_buf(),
             sign_events: true,
             signing_key: Some(signing_key),
+            format: AuditFormat::LogLine,
         }
     }
 
+    /// Set the line format appended for each event.
+    pub fn with_format(mut self, format: AuditFormat) -> Self {
+        self.format = format;
+        self
+    }
+
     /// Append a line to the audit log file.
     async fn append_line(&self, line: &str) -> Result<(), AuditError> {
         let mut file = OpenOptions::new()
@@ -514,6 +656,7 @@ impl std::fmt::Debug for FileAuditSink {
         f.debug_struct("FileAuditSink")
             .field("path", &self.path)
             .field("sign_events", &self.sign_events)
+            .field("format", &self.format)
             .finish()
     }
 }
@@ -524,17 +667,153 @@ impl AuditSink for FileAuditSink {
         if self.sign_events {
             if let Some(ref key) = self.signing_key {
                 let signed = SignedAuditEvent::sign(event, key)?;
-                self.append_line(&signed.to_log_line()).await
+                self.append_line(&self.format.format_signed(&signed)).await
             } else {
-                self.append_line(&event.to_log_line()).await
+                self.append_line(&self.format.format_event(&event)).await
             }
         } else {
-            self.append_line(&event.to_log_line()).await
+            self.append_line(&self.format.format_event(&event)).await
         }
     }
 
     async fn emit_signed(&self, event: SignedAuditEvent) -> Result<(), AuditError> {
-        self.append_line(&event.to_log_line()).await
+        self.append_line(&self.format.format_signed(&event)).await
+    }
+}
+
+/// Audit sink that writes one JSON object per line to stdout.
+///
+/// Suited to container deployments where a log shipper forwards stdout.
+#[derive(Debug, Default)]
+pub struct StdoutJsonAuditSink;
+
+impl StdoutJsonAuditSink {
+    /// Create a new stdout JSON sink.
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn write_line(&self, line: &str) -> Result<(), AuditError> {
+        let mut stdout = std::io::stdout().lock();
+        writeln!(stdout, "{}", line)?;
+        stdout.flush()?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl AuditSink for StdoutJsonAuditSink {
+    async fn emit(&self, event: AuditEvent) -> Result<(), AuditError> {
+        self.write_line(&AuditFormat::Json.format_event(&event))
+    }
+
+    async fn emit_signed(&self, event: SignedAuditEvent) -> Result<(), AuditError> {
+        self.write_line(&AuditFormat::Json.format_signed(&event))
+    }
+}
+
+/// Event held by a `BufferedAuditSink` until the next flush.
+#[derive(Debug, Clone)]
+enum BufferedEvent {
+    Unsigned(AuditEvent),
+    Signed(SignedAuditEvent),
+}
+
+/// Audit sink that batches events and forwards them to an inner sink.
+///
+/// A batch is flushed when it reaches `max_batch` events or when the flush
+/// interval elapses, whichever comes first. Events still buffered when the
+/// sink is dropped are lost, so call [`BufferedAuditSink::flush`] on shutdown.
+pub struct BufferedAuditSink {
+    inner: Arc<dyn AuditSink>,
+    buffer: Mutex<VecDeque<BufferedEvent>>,
+    max_batch: usize,
+}
+
+impl BufferedAuditSink {
+    /// Create a buffered sink and spawn its periodic flush task.
+    ///
+    /// Must be called from within a Tokio runtime. The flush task exits once
+    /// the returned sink is dropped.
+    pub fn new(inner: Arc<dyn AuditSink>, max_batch: usize, flush_interval: Duration) -> Arc<Self> {
+        let sink = Arc::new(Self {
+            inner,
+            buffer: Mutex::new(VecDeque::with_capacity(max_batch)),
+            max_batch: max_batch.max(1),
+        });
+
+        let weak = Arc::downgrade(&sink);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(flush_interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                let Some(sink) = weak.upgrade() else {
+                    break;
+                };
+                if let Err(e) = sink.flush().await {
+                    tracing::warn!("failed to flush buffered audit events: {}", e);
+                }
+            }
+        });
+
+        sink
+    }
+
+    /// Number of events waiting to be flushed.
+    pub async fn pending(&self) -> usize {
+        self.buffer.lock().await.len()
+    }
+
+    /// Forward all buffered events to the inner sink, in order.
+    ///
+    /// On failure the unsent events stay buffered for the next flush.
+    pub async fn flush(&self) -> Result<(), AuditError> {
+        let mut buffer = self.buffer.lock().await;
+        while let Some(event) = buffer.pop_front() {
+            let result = match &event {
+                BufferedEvent::Unsigned(e) => self.inner.emit(e.clone()).await,
+                BufferedEvent::Signed(e) => self.inner.emit_signed(e.clone()).await,
+            };
+            if let Err(e) = result {
+                buffer.push_front(event);
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    async fn push(&self, event: BufferedEvent) -> Result<(), AuditError> {
+        let full = {
+            let mut buffer = self.buffer.lock().await;
+            buffer.push_back(event);
+            buffer.len() >= self.max_batch
+        };
+        if full {
+            self.flush().await
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl std::fmt::Debug for BufferedAuditSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BufferedAuditSink")
+            .field("max_batch", &self.max_batch)
+            .finish()
+    }
+}
+
+#[async_trait]
+impl AuditSink for BufferedAuditSink {
+    async fn emit(&self, event: AuditEvent) -> Result<(), AuditError> {
+        self.push(BufferedEvent::Unsigned(event)).await
+    }
+
+    async fn emit_signed(&self, event: SignedAuditEvent) -> Result<(), AuditError> {
+        self.push(BufferedEvent::Signed(event)).await
     }
 }
 
@@ -690,6 +969,23 @@ impl AuditLogger {
         }).await
     }
 
+    /// Emit a transport selected event for a negotiated session.
+    pub async fn transport_selected(
+        &self,
+        operator_id: [u8; 32],
+        session_id: [u8; 32],
+        selection: &crate::transport::TransportSelection,
+    ) -> Result<(), AuditError> {
+        self.emit(AuditEvent::TransportSelected {
+            device_id: self.device_id,
+            operator_id,
+            session_id,
+            transport: selection.transport_type.as_str().to_string(),
+            fallback_trail: selection.fallback_trail(),
+            timestamp: current_timestamp(),
+        }).await
+    }
+
     /// Emit a permission escalation attempted event.
     pub async fn permission_escalation_attempted(
         &self,
@@ -733,6 +1029,26 @@ impl Default for AuditLogger {
     }
 }
 
+/// Log, rather than propagate, a failure to record an audit event.
+///
+/// State machines use this so an unavailable sink never blocks pairing or
+/// session flows.
+pub(crate) fn warn_on_audit_error(result: Result<(), AuditError>) {
+    if let Err(e) = result {
+        tracing::warn!("failed to record audit event: {}", e);
+    }
+}
+
+/// Copy an identifier into the fixed-size form used by audit events.
+///
+/// Shorter inputs are zero-padded; longer inputs are truncated.
+pub(crate) fn audit_id(bytes: &[u8]) -> [u8; 32] {
+    let mut id = [0u8; 32];
+    let len = bytes.len().min(32);
+    id[..len].copy_from_slice(&bytes[..len]);
+    id
+}
+
 /// Get the current Unix timestamp in seconds.
 fn current_timestamp() -> u64 {
     std::time::SystemTime::now()
@@ -1097,4 +1413,134 @@ mod tests {
         let events = memory_sink.events().await;
         assert_eq!(events.len(), 2);
     }
+
+    #[tokio::test]
+    async fn test_transport_selected_event() {
+        use crate::transport::{
+            QuicParams, SelectedTransport, SkipReason, TransportSelection, TransportType,
+        };
+
+        let memory_sink = Arc::new(MemoryAuditSink::new(100));
+        let mut logger = AuditLogger::new(test_device_id());
+        logger.add_sink(memory_sink.clone());
+
+        let selection = TransportSelection {
+            transport_type: TransportType::Relay,
+            selected: SelectedTransport::Quic { params: QuicParams::new(vec![1]) },
+            skipped: vec![(TransportType::Direct, SkipReason::DisabledByPolicy)],
+        };
+        logger.transport_selected(test_operator_id(), [3u8; 32], &selection).await.unwrap();
+
+        let events = memory_sink.events().await;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event_type(), "TRANSPORT_SELECTED");
+        let line = events[0].to_log_line();
+        assert!(line.contains("transport=relay"));
+        assert!(line.contains("skipped=\"direct:disabled_by_policy\""));
+    }
+
+    #[tokio::test]
+    async fn test_event_to_json() {
+        let event = AuditEvent::PairApproved {
+            device_id: test_device_id(),
+            operator_id: test_operator_id(),
+            permissions: 0x03,
+            timestamp: 1234567890,
+        };
+        let json = event.to_json();
+        assert_eq!(json["type"], "PAIR_APPROVED");
+        assert_eq!(json["timestamp"], 1234567890);
+        assert_eq!(json["actor"]["kind"], "operator");
+        assert_eq!(json["actor"]["id"], hex::encode(test_operator_id()));
+        assert_eq!(json["details"]["permissions"], "0x00000003");
+
+        // Events without an operator are attributed to the device
+        let event = AuditEvent::RateLimitExceeded {
+            device_id: test_device_id(),
+            source: "10.0.0.1".to_string(),
+            limit_type: "pairing_attempts".to_string(),
+            timestamp: 1234567890,
+        };
+        let json = event.to_json();
+        assert_eq!(json["actor"]["kind"], "device");
+        assert_eq!(json["actor"]["id"], hex::encode(test_device_id()));
+        assert_eq!(json["details"]["source"], "10.0.0.1");
+
+        let signing_key = SigningKey::generate(&mut OsRng);
+        let signed = SignedAuditEvent::sign(event, &signing_key).unwrap();
+        let json = signed.to_json();
+        assert_eq!(json["type"], "RATE_LIMIT_EXCEEDED");
+        assert_eq!(json["signature"].as_str().unwrap().len(), 128);
+    }
+
+    #[tokio::test]
+    async fn test_file_sink_json_format() {
+        use tokio::fs;
+
+        let temp_dir = std::env::temp_dir();
+        let file_path = temp_dir.join(format!("zrc_audit_test_json_{}.log", std::process::id()));
+        let _ = fs::remove_file(&file_path).await;
+
+        let sink = FileAuditSink::new(&file_path).with_format(AuditFormat::Json);
+        for timestamp in [1, 2] {
+            sink.emit(AuditEvent::PairRevoked {
+                device_id: test_device_id(),
+                operator_id: test_operator_id(),
+                timestamp,
+            })
+            .await
+            .unwrap();
+        }
+
+        let content = fs::read_to_string(&file_path).await.unwrap();
+        let records: Vec<serde_json::Value> = content
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0]["type"], "PAIR_REVOKED");
+        assert_eq!(records[1]["timestamp"], 2);
+
+        let _ = fs::remove_file(&file_path).await;
+    }
+
+    #[tokio::test]
+    async fn test_buffered_sink_flushes_at_batch_size() {
+        let memory_sink = Arc::new(MemoryAuditSink::new(100));
+        let buffered = BufferedAuditSink::new(memory_sink.clone(), 3, Duration::from_secs(3600));
+        let mut logger = AuditLogger::new(test_device_id());
+        logger.add_sink(buffered.clone());
+
+        logger.pair_request_received(test_operator_id()).await.unwrap();
+        logger.pair_approved(test_operator_id(), 0x03).await.unwrap();
+        assert_eq!(buffered.pending().await, 2);
+        assert_eq!(memory_sink.count().await, 0);
+
+        logger.pair_revoked(test_operator_id()).await.unwrap();
+        assert_eq!(buffered.pending().await, 0);
+
+        let events = memory_sink.events().await;
+        let types: Vec<_> = events.iter().map(|e| e.event_type()).collect();
+        assert_eq!(types, ["PAIR_REQUEST_RECEIVED", "PAIR_APPROVED", "PAIR_REVOKED"]);
+    }
+
+    #[tokio::test]
+    async fn test_buffered_sink_flushes_on_timer() {
+        let memory_sink = Arc::new(MemoryAuditSink::new(100));
+        let buffered = BufferedAuditSink::new(memory_sink.clone(), 100, Duration::from_millis(20));
+
+        buffered
+            .emit(AuditEvent::PairRevoked {
+                device_id: test_device_id(),
+                operator_id: test_operator_id(),
+                timestamp: 1,
+            })
+            .await
+            .unwrap();
+        assert_eq!(memory_sink.count().await, 0);
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(buffered.pending().await, 0);
+        assert_eq!(memory_sink.count().await, 1);
+    }
 }
@@ -0,0 +1,648 @@
+//! Clipboard sync over the control channel.
+//!
+//! Clipboard contents travel as `ClipboardMsgV1` control messages. Payloads
+//! larger than one chunk are split into messages sharing a `sequence_id`;
+//! the receiver reassembles them, enforcing the clipboard permission and a
+//! size cap before anything reaches the local clipboard.
+//!
+//! Requirements: 6.5
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use prost::Message;
+use thiserror::Error;
+use tokio::sync::{mpsc, Mutex};
+use tracing::{debug, warn};
+
+use crate::dispatch::{HandlerError, MessageHandler};
+use crate::store::Store;
+use zrc_proto::v1::{
+    control_msg_v1, ClipboardActionV1, ClipboardDirectionV1, ClipboardFormatV1, ClipboardMsgV1,
+    ControlMsgV1, PermissionsV1,
+};
+
+/// Largest clipboard payload accepted or sent by default.
+pub const DEFAULT_MAX_CLIPBOARD_BYTES: usize = 8 * 1024 * 1024;
+
+/// Default payload bytes per clipboard chunk.
+pub const DEFAULT_CLIPBOARD_CHUNK_BYTES: usize = 64 * 1024;
+
+/// Errors from clipboard sync.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ClipboardError {
+    #[error("clipboard permission not granted")]
+    PermissionDenied,
+    #[error("clipboard payload of {size} bytes exceeds limit of {limit} bytes")]
+    TooLarge { size: u64, limit: usize },
+    #[error("invalid clipboard chunk: {0}")]
+    InvalidChunk(String),
+}
+
+/// Clipboard size and chunking limits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClipboardConfig {
+    /// Largest payload, in bytes, sent or accepted
+    pub max_bytes: usize,
+    /// Payload bytes per chunk when sending
+    pub chunk_bytes: usize,
+}
+
+impl Default for ClipboardConfig {
+    fn default() -> Self {
+        Self {
+            max_bytes: DEFAULT_MAX_CLIPBOARD_BYTES,
+            chunk_bytes: DEFAULT_CLIPBOARD_CHUNK_BYTES,
+        }
+    }
+}
+
+/// Clipboard contents with their MIME type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClipboardContent {
+    pub mime_type: String,
+    pub data: Vec<u8>,
+}
+
+impl ClipboardContent {
+    /// Plain UTF-8 text contents.
+    pub fn text(text: impl Into<String>) -> Self {
+        Self {
+            mime_type: "text/plain".to_string(),
+            data: text.into().into_bytes(),
+        }
+    }
+}
+
+/// Clipboard change to apply locally.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClipboardUpdate {
+    Set(ClipboardContent),
+    Clear,
+}
+
+/// Whether a `PermissionsV1` bitmask includes clipboard access.
+pub fn has_clipboard_permission(permissions: u32) -> bool {
+    permissions & PermissionsV1::Clipboard as u32 != 0
+}
+
+/// MIME type implied by a legacy format field.
+fn mime_for_format(format: i32) -> &'static str {
+    match ClipboardFormatV1::try_from(format) {
+        Ok(ClipboardFormatV1::Html) => "text/html",
+        Ok(ClipboardFormatV1::Rtf) => "text/rtf",
+        Ok(ClipboardFormatV1::ImagePng) => "image/png",
+        Ok(ClipboardFormatV1::ImageBmp) => "image/bmp",
+        Ok(ClipboardFormatV1::Files) => "text/uri-list",
+        _ => "text/plain",
+    }
+}
+
+/// Format field for a MIME type, for receivers that only read `format`.
+fn format_for_mime(mime_type: &str) -> ClipboardFormatV1 {
+    match mime_type.split(';').next().unwrap_or_default().trim() {
+        "text/html" => ClipboardFormatV1::Html,
+        "text/rtf" => ClipboardFormatV1::Rtf,
+        "image/png" => ClipboardFormatV1::ImagePng,
+        "image/bmp" => ClipboardFormatV1::ImageBmp,
+        "text/uri-list" => ClipboardFormatV1::Files,
+        _ => ClipboardFormatV1::Text,
+    }
+}
+
+/// Split `content` into SET messages of at most `config.chunk_bytes` each.
+pub fn clipboard_set_messages(
+    sequence_id: u64,
+    content: &ClipboardContent,
+    config: &ClipboardConfig,
+) -> Result<Vec<ClipboardMsgV1>, ClipboardError> {
+    if content.data.len() > config.max_bytes {
+        return Err(ClipboardError::TooLarge {
+            size: content.data.len() as u64,
+            limit: config.max_bytes,
+        });
+    }
+
+    let chunk_bytes = config.chunk_bytes.max(1);
+    let chunks: Vec<&[u8]> = if content.data.is_empty() {
+        vec![&[]]
+    } else {
+        content.data.chunks(chunk_bytes).collect()
+    };
+    let chunk_count = chunks.len() as u32;
+
+    Ok(chunks
+        .into_iter()
+        .enumerate()
+        .map(|(index, chunk)| ClipboardMsgV1 {
+            direction: ClipboardDirectionV1::ToDevice as i32,
+            format: format_for_mime(&content.mime_type) as i32,
+            data: chunk.to_vec(),
+            sequence_id,
+            action: ClipboardActionV1::Set as i32,
+            mime_type: content.mime_type.clone(),
+            total_size: content.data.len() as u64,
+            chunk_index: index as u32,
+            chunk_count,
+        })
+        .collect())
+}
+
+/// Message clearing the remote clipboard.
+pub fn clipboard_clear_message(sequence_id: u64) -> ClipboardMsgV1 {
+    ClipboardMsgV1 {
+        direction: ClipboardDirectionV1::ToDevice as i32,
+        sequence_id,
+        action: ClipboardActionV1::Clear as i32,
+        ..Default::default()
+    }
+}
+
+/// Chunked payload being reassembled.
+#[derive(Debug)]
+struct PendingClipboard {
+    sequence_id: u64,
+    mime_type: String,
+    total_size: u64,
+    chunk_count: u32,
+    next_index: u32,
+    data: Vec<u8>,
+}
+
+/// Validates and reassembles incoming clipboard messages for one peer.
+#[derive(Debug)]
+pub struct ClipboardReceiver {
+    permissions: u32,
+    config: ClipboardConfig,
+    pending: Option<PendingClipboard>,
+}
+
+impl ClipboardReceiver {
+    /// Create a receiver for a peer granted `permissions` (`PermissionsV1` bitmask).
+    pub fn new(permissions: u32, config: ClipboardConfig) -> Self {
+        Self {
+            permissions,
+            config,
+            pending: None,
+        }
+    }
+
+    /// Update the peer's permissions, e.g. after the pairing changed.
+    pub fn set_permissions(&mut self, permissions: u32) {
+        self.permissions = permissions;
+        if !has_clipboard_permission(permissions) {
+            self.pending = None;
+        }
+    }
+
+    /// Handle one clipboard message.
+    ///
+    /// Returns the update to apply once a payload is complete, `None` while
+    /// chunks are outstanding or for messages that carry no change.
+    pub fn handle(&mut self, msg: &ClipboardMsgV1) -> Result<Option<ClipboardUpdate>, ClipboardError> {
+        if !has_clipboard_permission(self.permissions) {
+            self.pending = None;
+            return Err(ClipboardError::PermissionDenied);
+        }
+
+        match msg.action() {
+            ClipboardActionV1::Clear => {
+                self.pending = None;
+                return Ok(Some(ClipboardUpdate::Clear));
+            }
+            ClipboardActionV1::Set => {}
+            ClipboardActionV1::Unspecified => {
+                if msg.direction() == ClipboardDirectionV1::Request {
+                    return Ok(None);
+                }
+            }
+        }
+
+        let mime_type = if msg.mime_type.is_empty() {
+            mime_for_format(msg.format).to_string()
+        } else {
+            msg.mime_type.clone()
+        };
+
+        if msg.chunk_count <= 1 {
+            self.pending = None;
+            self.check_size(msg.data.len() as u64)?;
+            return Ok(Some(ClipboardUpdate::Set(ClipboardContent {
+                mime_type,
+                data: msg.data.clone(),
+            })));
+        }
+
+        if msg.chunk_index == 0 {
+            self.pending = None;
+            self.check_size(msg.total_size)?;
+            self.pending = Some(PendingClipboard {
+                sequence_id: msg.sequence_id,
+                mime_type,
+                total_size: msg.total_size,
+                chunk_count: msg.chunk_count,
+                next_index: 0,
+                data: Vec::new(),
+            });
+        }
+
+        let Some(pending) = self.pending.as_mut() else {
+            return Err(ClipboardError::InvalidChunk(format!(
+                "chunk {} without a first chunk",
+                msg.chunk_index
+            )));
+        };
+        if msg.sequence_id != pending.sequence_id
+            || msg.chunk_index != pending.next_index
+            || msg.chunk_count != pending.chunk_count
+        {
+            let err = ClipboardError::InvalidChunk(format!(
+                "expected chunk {} of sequence {}, got chunk {} of sequence {}",
+                pending.next_index, pending.sequence_id, msg.chunk_index, msg.sequence_id
+            ));
+            self.pending = None;
+            return Err(err);
+        }
+        if (pending.data.len() + msg.data.len()) as u64 > pending.total_size {
+            let size = (pending.data.len() + msg.data.len()) as u64;
+            self.pending = None;
+            return Err(ClipboardError::TooLarge {
+                size,
+                limit: self.config.max_bytes,
+            });
+        }
+
+        pending.data.extend_from_slice(&msg.data);
+        pending.next_index += 1;
+        if pending.next_index < pending.chunk_count {
+            return Ok(None);
+        }
+
+        let Some(pending) = self.pending.take() else {
+            return Ok(None);
+        };
+        if pending.data.len() as u64 != pending.total_size {
+            return Err(ClipboardError::InvalidChunk(format!(
+                "received {} of {} declared bytes",
+                pending.data.len(),
+                pending.total_size
+            )));
+        }
+        Ok(Some(ClipboardUpdate::Set(ClipboardContent {
+            mime_type: pending.mime_type,
+            data: pending.data,
+        })))
+    }
+
+    fn check_size(&self, size: u64) -> Result<(), ClipboardError> {
+        if size > self.config.max_bytes as u64 {
+            return Err(ClipboardError::TooLarge {
+                size,
+                limit: self.config.max_bytes,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Clipboard update received from a paired operator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClipboardEvent {
+    pub operator_id: [u8; 32],
+    pub update: ClipboardUpdate,
+}
+
+/// Dispatcher handler for clipboard control messages.
+///
+/// Register for `MsgTypeV1::ControlMsg`. Each sender's pairing is loaded per
+/// message so a revoked clipboard permission takes effect immediately;
+/// messages from operators without it are dropped. Completed updates are
+/// delivered on the channel returned by [`ClipboardMessageHandler::new`].
+/// Other control messages go to the handler set with
+/// [`ClipboardMessageHandler::with_fallback`].
+pub struct ClipboardMessageHandler<S: Store> {
+    device_id: [u8; 32],
+    store: Arc<S>,
+    config: ClipboardConfig,
+    receivers: Mutex<HashMap<[u8; 32], ClipboardReceiver>>,
+    updates: mpsc::UnboundedSender<ClipboardEvent>,
+    fallback: Option<Arc<dyn MessageHandler>>,
+}
+
+impl<S: Store> ClipboardMessageHandler<S> {
+    /// Create a handler and the channel its updates are delivered on.
+    pub fn new(
+        device_id: [u8; 32],
+        store: Arc<S>,
+        config: ClipboardConfig,
+    ) -> (Self, mpsc::UnboundedReceiver<ClipboardEvent>) {
+        let (updates, rx) = mpsc::unbounded_channel();
+        let handler = Self {
+            device_id,
+            store,
+            config,
+            receivers: Mutex::new(HashMap::new()),
+            updates,
+            fallback: None,
+        };
+        (handler, rx)
+    }
+
+    /// Pass non-clipboard control messages (input, file and session control)
+    /// to `handler`, typically the one previously registered for
+    /// `MsgTypeV1::ControlMsg`.
+    pub fn with_fallback(mut self, handler: Arc<dyn MessageHandler>) -> Self {
+        self.fallback = Some(handler);
+        self
+    }
+}
+
+#[async_trait]
+impl<S: Store + 'static> MessageHandler for ClipboardMessageHandler<S> {
+    async fn handle(
+        &self,
+        sender_id: [u8; 32],
+        payload: &[u8],
+    ) -> Result<Option<Vec<u8>>, HandlerError> {
+        let msg = ControlMsgV1::decode(payload)
+            .map_err(|e| HandlerError::InvalidPayload(e.to_string()))?;
+        let Some(control_msg_v1::Payload::Clipboard(clipboard)) = msg.payload else {
+            return match &self.fallback {
+                Some(fallback) => fallback.handle(sender_id, payload).await,
+                None => Err(HandlerError::InvalidPayload("not a clipboard message".into())),
+            };
+        };
+
+        let permissions = self
+            .store
+            .load_pairing(&self.device_id, &sender_id)
+            .await
+            .map_err(|e| HandlerError::Internal(e.to_string()))?
+            .map(|pairing| pairing.permission_bits())
+            .unwrap_or(0);
+
+        let result = {
+            let mut receivers = self.receivers.lock().await;
+            let receiver = receivers
+                .entry(sender_id)
+                .or_insert_with(|| ClipboardReceiver::new(permissions, self.config));
+            receiver.set_permissions(permissions);
+            receiver.handle(&clipboard)
+        };
+
+        match result {
+            Ok(Some(update)) => {
+                debug!("clipboard update from {}", hex::encode(&sender_id[..8]));
+                let _ = self.updates.send(ClipboardEvent {
+                    operator_id: sender_id,
+                    update,
+                });
+                Ok(None)
+            }
+            Ok(None) => Ok(None),
+            Err(ClipboardError::PermissionDenied) => {
+                warn!(
+                    "dropping clipboard message from {}: permission not granted",
+                    hex::encode(&sender_id[..8])
+                );
+                Err(HandlerError::PermissionDenied(
+                    ClipboardError::PermissionDenied.to_string(),
+                ))
+            }
+            Err(e) => {
+                warn!("dropping clipboard message from {}: {}", hex::encode(&sender_id[..8]), e);
+                Err(HandlerError::InvalidPayload(e.to_string()))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::{InMemoryStore, PairingRecord};
+    use zrc_proto::v1::{KeyTypeV1, PublicKeyV1};
+
+    const ALL: u32 = PermissionsV1::View as u32 | PermissionsV1::Clipboard as u32;
+
+    fn small_config() -> ClipboardConfig {
+        ClipboardConfig {
+            max_bytes: 1024,
+            chunk_bytes: 100,
+        }
+    }
+
+    fn content(len: usize) -> ClipboardContent {
+        ClipboardContent {
+            mime_type: "application/octet-stream".to_string(),
+            data: (0..len).map(|i| i as u8).collect(),
+        }
+    }
+
+    fn pairing(device_id: [u8; 32], operator_id: [u8; 32], perms: Vec<i32>) -> PairingRecord {
+        let key = |key_type: KeyTypeV1| PublicKeyV1 {
+            key_type: key_type as i32,
+            key_bytes: vec![0u8; 32],
+        };
+        PairingRecord {
+            pairing_id: vec![1; 16],
+            device_id: device_id.to_vec(),
+            operator_id: operator_id.to_vec(),
+            device_sign_pub: key(KeyTypeV1::Ed25519),
+            device_kex_pub: key(KeyTypeV1::X25519),
+            operator_sign_pub: key(KeyTypeV1::Ed25519),
+            operator_kex_pub: key(KeyTypeV1::X25519),
+            granted_perms: perms,
+            unattended_enabled: false,
+            require_consent_each_time: false,
+            issued_at: 1000,
+            last_session: None,
+        }
+    }
+
+    fn encode(msg: ClipboardMsgV1) -> Vec<u8> {
+        ControlMsgV1::new(1, control_msg_v1::Payload::Clipboard(msg)).encode_to_vec()
+    }
+
+    #[test]
+    fn test_chunked_roundtrip() {
+        let config = small_config();
+        let original = content(950);
+        let msgs = clipboard_set_messages(7, &original, &config).unwrap();
+        assert_eq!(msgs.len(), 10);
+        assert!(msgs.iter().all(|m| m.data.len() <= config.chunk_bytes));
+
+        let mut receiver = ClipboardReceiver::new(ALL, config);
+        let (last, rest) = msgs.split_last().unwrap();
+        for msg in rest {
+            assert_eq!(receiver.handle(msg).unwrap(), None);
+        }
+        assert_eq!(
+            receiver.handle(last).unwrap(),
+            Some(ClipboardUpdate::Set(original))
+        );
+    }
+
+    #[test]
+    fn test_clear_and_legacy_text() {
+        let mut receiver = ClipboardReceiver::new(ALL, small_config());
+        assert_eq!(
+            receiver.handle(&clipboard_clear_message(1)).unwrap(),
+            Some(ClipboardUpdate::Clear)
+        );
+
+        // Messages from peers predating action/mime fields still apply
+        let legacy = ClipboardMsgV1 {
+            direction: ClipboardDirectionV1::ToDevice as i32,
+            format: ClipboardFormatV1::Text as i32,
+            data: b"hello".to_vec(),
+            sequence_id: 2,
+            ..Default::default()
+        };
+        assert_eq!(
+            receiver.handle(&legacy).unwrap(),
+            Some(ClipboardUpdate::Set(ClipboardContent::text("hello")))
+        );
+    }
+
+    #[test]
+    fn test_dropped_without_permission() {
+        let mut receiver = ClipboardReceiver::new(PermissionsV1::View as u32, small_config());
+        let msgs = clipboard_set_messages(1, &ClipboardContent::text("secret"), &small_config()).unwrap();
+        assert_eq!(receiver.handle(&msgs[0]), Err(ClipboardError::PermissionDenied));
+        assert_eq!(
+            receiver.handle(&clipboard_clear_message(2)),
+            Err(ClipboardError::PermissionDenied)
+        );
+    }
+
+    #[test]
+    fn test_permission_revoked_mid_transfer() {
+        let config = small_config();
+        let msgs = clipboard_set_messages(1, &content(300), &config).unwrap();
+        let mut receiver = ClipboardReceiver::new(ALL, config);
+        assert_eq!(receiver.handle(&msgs[0]).unwrap(), None);
+
+        receiver.set_permissions(PermissionsV1::View as u32);
+        assert_eq!(receiver.handle(&msgs[1]), Err(ClipboardError::PermissionDenied));
+
+        // Partial payload was discarded; regaining permission does not resume it
+        receiver.set_permissions(ALL);
+        assert!(matches!(
+            receiver.handle(&msgs[2]),
+            Err(ClipboardError::InvalidChunk(_))
+        ));
+    }
+
+    #[test]
+    fn test_size_limit() {
+        let config = small_config();
+        assert_eq!(
+            clipboard_set_messages(1, &content(1025), &config),
+            Err(ClipboardError::TooLarge {
+                size: 1025,
+                limit: 1024
+            })
+        );
+
+        // A sender with a larger limit is refused by the first chunk
+        let big = ClipboardConfig {
+            max_bytes: 4096,
+            chunk_bytes: 100,
+        };
+        let msgs = clipboard_set_messages(1, &content(2000), &big).unwrap();
+        let mut receiver = ClipboardReceiver::new(ALL, config);
+        assert!(matches!(
+            receiver.handle(&msgs[0]),
+            Err(ClipboardError::TooLarge { size: 2000, .. })
+        ));
+
+        // Chunks carrying more than the declared total are refused
+        let mut msgs = clipboard_set_messages(2, &content(200), &config).unwrap();
+        msgs[1].data.extend_from_slice(&[0u8; 50]);
+        assert_eq!(receiver.handle(&msgs[0]).unwrap(), None);
+        assert!(matches!(
+            receiver.handle(&msgs[1]),
+            Err(ClipboardError::TooLarge { .. })
+        ));
+    }
+
+    #[test]
+    fn test_out_of_order_chunk_rejected() {
+        let config = small_config();
+        let msgs = clipboard_set_messages(1, &content(300), &config).unwrap();
+        let mut receiver = ClipboardReceiver::new(ALL, config);
+        assert_eq!(receiver.handle(&msgs[0]).unwrap(), None);
+        assert!(matches!(
+            receiver.handle(&msgs[2]),
+            Err(ClipboardError::InvalidChunk(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_handler_enforces_pairing_permission() {
+        let device_id = [1u8; 32];
+        let allowed = [2u8; 32];
+        let denied = [3u8; 32];
+        let store = Arc::new(InMemoryStore::new());
+        // Bit positions: 1<<0 = VIEW, 1<<2 = CLIPBOARD
+        store.save_pairing(pairing(device_id, allowed, vec![0, 2])).await.unwrap();
+        store.save_pairing(pairing(device_id, denied, vec![0])).await.unwrap();
+
+        let (handler, mut rx) = ClipboardMessageHandler::new(device_id, store, small_config());
+        let msg = clipboard_set_messages(1, &ClipboardContent::text("hi"), &small_config())
+            .unwrap()
+            .remove(0);
+
+        let result = handler.handle(denied, &encode(msg.clone())).await;
+        assert!(matches!(result, Err(HandlerError::PermissionDenied(_))));
+        let result = handler.handle([9u8; 32], &encode(msg.clone())).await;
+        assert!(matches!(result, Err(HandlerError::PermissionDenied(_))));
+        assert!(rx.try_recv().is_err());
+
+        assert!(handler.handle(allowed, &encode(msg)).await.unwrap().is_none());
+        let event = rx.try_recv().unwrap();
+        assert_eq!(event.operator_id, allowed);
+        assert_eq!(event.update, ClipboardUpdate::Set(ClipboardContent::text("hi")));
+    }
+
+    /// Records the payloads it is handed.
+    #[derive(Default)]
+    struct RecordingHandler {
+        payloads: std::sync::Mutex<Vec<Vec<u8>>>,
+    }
+
+    #[async_trait]
+    impl MessageHandler for RecordingHandler {
+        async fn handle(
+            &self,
+            _sender_id: [u8; 32],
+            payload: &[u8],
+        ) -> Result<Option<Vec<u8>>, HandlerError> {
+            self.payloads.lock().unwrap().push(payload.to_vec());
+            Ok(Some(b"handled".to_vec()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_handler_passes_other_control_messages_on() {
+        let store = Arc::new(InMemoryStore::new());
+        let ping = ControlMsgV1::new(1, control_msg_v1::Payload::Ping(Default::default())).encode_to_vec();
+
+        let (handler, _rx) = ClipboardMessageHandler::new([1u8; 32], store.clone(), small_config());
+        assert!(matches!(
+            handler.handle([2u8; 32], &ping).await,
+            Err(HandlerError::InvalidPayload(_))
+        ));
+
+        let fallback = Arc::new(RecordingHandler::default());
+        let (handler, _rx) = ClipboardMessageHandler::new([1u8; 32], store, small_config());
+        let handler = handler.with_fallback(fallback.clone());
+        assert_eq!(
+            handler.handle([2u8; 32], &ping).await.unwrap(),
+            Some(b"handled".to_vec())
+        );
+        assert_eq!(*fallback.payloads.lock().unwrap(), vec![ping]);
+    }
+}
@@ -0,0 +1,400 @@
+//! Remote cursor shape tracking.
+//!
+//! The host polls `HostPlatform::cursor_shape` and feeds the result through a
+//! `CursorTracker`, which drops repeats, debounces rapid changes and turns
+//! shapes into `CursorShapeV1` control messages. Bitmaps are identified by a
+//! hash; a bitmap the controller has recently received is sent as its id
+//! only. The controller resolves messages back to shapes with a
+//! `CursorShapeCache` of the same size.
+//!
+//! Platforms that cannot read the cursor image report a standard arrow
+//! (`CursorShape::default()`), and oversized or malformed bitmaps are replaced
+//! by the same fallback.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use bytes::Bytes;
+use tokio::sync::mpsc;
+use tracing::debug;
+
+use zrc_crypto::hash::sha256;
+use zrc_proto::v1::{cursor_shape_v1::StandardV1, CursorShapeV1};
+
+use crate::platform::HostPlatform;
+
+/// Largest cursor bitmap width or height sent to the controller.
+pub const MAX_CURSOR_DIMENSION: u32 = 256;
+
+/// Minimum time between two cursor messages.
+pub const DEFAULT_CURSOR_DEBOUNCE: Duration = Duration::from_millis(50);
+
+/// How often `spawn_cursor_watch` polls the platform.
+pub const DEFAULT_CURSOR_POLL_INTERVAL: Duration = Duration::from_millis(16);
+
+/// Bitmaps remembered by both sides for id-only retransmission.
+pub const CURSOR_CACHE_SIZE: usize = 32;
+
+/// System cursors, sent by name instead of as a bitmap.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StandardCursor {
+    Arrow,
+    Text,
+    Hand,
+    Wait,
+    Crosshair,
+    ResizeNs,
+    ResizeEw,
+    ResizeNwse,
+    ResizeNesw,
+    Move,
+    NotAllowed,
+    Hidden,
+}
+
+impl From<StandardCursor> for StandardV1 {
+    fn from(c: StandardCursor) -> Self {
+        match c {
+            StandardCursor::Arrow => StandardV1::Arrow,
+            StandardCursor::Text => StandardV1::Text,
+            StandardCursor::Hand => StandardV1::Hand,
+            StandardCursor::Wait => StandardV1::Wait,
+            StandardCursor::Crosshair => StandardV1::Crosshair,
+            StandardCursor::ResizeNs => StandardV1::ResizeNs,
+            StandardCursor::ResizeEw => StandardV1::ResizeEw,
+            StandardCursor::ResizeNwse => StandardV1::ResizeNwse,
+            StandardCursor::ResizeNesw => StandardV1::ResizeNesw,
+            StandardCursor::Move => StandardV1::Move,
+            StandardCursor::NotAllowed => StandardV1::NotAllowed,
+            StandardCursor::Hidden => StandardV1::Hidden,
+        }
+    }
+}
+
+impl StandardCursor {
+    /// Map a wire value; `None` for `Unspecified` (a bitmap message).
+    pub fn from_proto(v: StandardV1) -> Option<Self> {
+        Some(match v {
+            StandardV1::Unspecified => return None,
+            StandardV1::Arrow => StandardCursor::Arrow,
+            StandardV1::Text => StandardCursor::Text,
+            StandardV1::Hand => StandardCursor::Hand,
+            StandardV1::Wait => StandardCursor::Wait,
+            StandardV1::Crosshair => StandardCursor::Crosshair,
+            StandardV1::ResizeNs => StandardCursor::ResizeNs,
+            StandardV1::ResizeEw => StandardCursor::ResizeEw,
+            StandardV1::ResizeNwse => StandardCursor::ResizeNwse,
+            StandardV1::ResizeNesw => StandardCursor::ResizeNesw,
+            StandardV1::Move => StandardCursor::Move,
+            StandardV1::NotAllowed => StandardCursor::NotAllowed,
+            StandardV1::Hidden => StandardCursor::Hidden,
+        })
+    }
+}
+
+/// A custom cursor image.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CursorBitmap {
+    pub width: u32,
+    pub height: u32,
+    pub hotspot_x: u32,
+    pub hotspot_y: u32,
+    /// RGBA8 pixels, row-major
+    pub rgba: Bytes,
+}
+
+impl CursorBitmap {
+    /// Hash-derived id, stable for identical bitmaps.
+    pub fn shape_id(&self) -> u32 {
+        let mut data = Vec::with_capacity(16 + self.rgba.len());
+        for v in [self.width, self.height, self.hotspot_x, self.hotspot_y] {
+            data.extend_from_slice(&v.to_le_bytes());
+        }
+        data.extend_from_slice(&self.rgba);
+        let hash = sha256(&data);
+        u32::from_le_bytes([hash[0], hash[1], hash[2], hash[3]])
+    }
+
+    /// Dimensions within `MAX_CURSOR_DIMENSION`, pixel data matching them and
+    /// the hotspot inside the image.
+    pub fn is_valid(&self) -> bool {
+        self.width > 0
+            && self.height > 0
+            && self.width <= MAX_CURSOR_DIMENSION
+            && self.height <= MAX_CURSOR_DIMENSION
+            && self.rgba.len() == (self.width * self.height * 4) as usize
+            && self.hotspot_x < self.width
+            && self.hotspot_y < self.height
+    }
+}
+
+/// Cursor shape reported by `HostPlatform::cursor_shape`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CursorShape {
+    Bitmap(CursorBitmap),
+    Standard(StandardCursor),
+}
+
+impl Default for CursorShape {
+    /// The fallback when the cursor image cannot be read.
+    fn default() -> Self {
+        CursorShape::Standard(StandardCursor::Arrow)
+    }
+}
+
+/// Host side: turns polled shapes into debounced, cached `CursorShapeV1`s.
+#[derive(Debug)]
+pub struct CursorTracker {
+    debounce: Duration,
+    last_sent: Option<CursorShape>,
+    last_emit: Option<Instant>,
+    pending: Option<CursorShape>,
+    /// Bitmap ids the controller holds, least recently used first
+    sent_ids: VecDeque<u32>,
+}
+
+impl Default for CursorTracker {
+    fn default() -> Self {
+        Self::new(DEFAULT_CURSOR_DEBOUNCE)
+    }
+}
+
+impl CursorTracker {
+    pub fn new(debounce: Duration) -> Self {
+        Self {
+            debounce,
+            last_sent: None,
+            last_emit: None,
+            pending: None,
+            sent_ids: VecDeque::with_capacity(CURSOR_CACHE_SIZE),
+        }
+    }
+
+    /// Record the current shape. Returns a message if the shape changed and
+    /// the debounce interval has passed; otherwise the change is held until
+    /// a later `observe` or `flush`.
+    pub fn observe(&mut self, shape: CursorShape, now: Instant) -> Option<CursorShapeV1> {
+        let shape = match shape {
+            CursorShape::Bitmap(bitmap) if !bitmap.is_valid() => {
+                debug!("unusable {}x{} cursor bitmap, sending arrow", bitmap.width, bitmap.height);
+                CursorShape::default()
+            }
+            shape => shape,
+        };
+        if self.last_sent.as_ref() == Some(&shape) {
+            // Changed and changed back within the debounce interval
+            self.pending = None;
+            return None;
+        }
+        self.pending = Some(shape);
+        self.flush(now)
+    }
+
+    /// Emit the held change if the debounce interval has passed.
+    pub fn flush(&mut self, now: Instant) -> Option<CursorShapeV1> {
+        if let Some(last) = self.last_emit {
+            if now.duration_since(last) < self.debounce {
+                return None;
+            }
+        }
+        let shape = self.pending.take()?;
+        self.last_emit = Some(now);
+        let msg = self.encode(&shape);
+        self.last_sent = Some(shape);
+        Some(msg)
+    }
+
+    fn encode(&mut self, shape: &CursorShape) -> CursorShapeV1 {
+        let bitmap = match shape {
+            CursorShape::Standard(c) => {
+                return CursorShapeV1 {
+                    standard: StandardV1::from(*c) as i32,
+                    ..Default::default()
+                };
+            }
+            CursorShape::Bitmap(bitmap) => bitmap,
+        };
+
+        let shape_id = bitmap.shape_id();
+        let cached = touch(&mut self.sent_ids, shape_id);
+        CursorShapeV1 {
+            shape_id,
+            standard: StandardV1::Unspecified as i32,
+            width: bitmap.width,
+            height: bitmap.height,
+            hotspot_x: bitmap.hotspot_x,
+            hotspot_y: bitmap.hotspot_y,
+            rgba: if cached { Vec::new() } else { bitmap.rgba.to_vec() },
+        }
+    }
+}
+
+/// Move `id` to the most recently used end, inserting it (and evicting the
+/// least recently used) if absent. Returns whether it was already present.
+fn touch(ids: &mut VecDeque<u32>, id: u32) -> bool {
+    let present = match ids.iter().position(|i| *i == id) {
+        Some(pos) => {
+            ids.remove(pos);
+            true
+        }
+        None => false,
+    };
+    if ids.len() >= CURSOR_CACHE_SIZE {
+        ids.pop_front();
+    }
+    ids.push_back(id);
+    present
+}
+
+/// Controller side: resolves `CursorShapeV1` messages, including id-only ones,
+/// to shapes.
+#[derive(Debug, Default)]
+pub struct CursorShapeCache {
+    ids: VecDeque<u32>,
+    bitmaps: HashMap<u32, CursorBitmap>,
+}
+
+impl CursorShapeCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Shape for `msg`, or `None` if it is malformed or names a bitmap that is
+    /// not cached (keep showing the previous cursor).
+    pub fn resolve(&mut self, msg: &CursorShapeV1) -> Option<CursorShape> {
+        if let Some(c) = StandardV1::try_from(msg.standard)
+            .ok()
+            .and_then(StandardCursor::from_proto)
+        {
+            return Some(CursorShape::Standard(c));
+        }
+
+        if msg.rgba.is_empty() {
+            let bitmap = self.bitmaps.get(&msg.shape_id)?.clone();
+            touch(&mut self.ids, msg.shape_id);
+            return Some(CursorShape::Bitmap(bitmap));
+        }
+
+        let bitmap = CursorBitmap {
+            width: msg.width,
+            height: msg.height,
+            hotspot_x: msg.hotspot_x,
+            hotspot_y: msg.hotspot_y,
+            rgba: Bytes::from(msg.rgba.clone()),
+        };
+        if !bitmap.is_valid() {
+            return None;
+        }
+        if self.ids.len() >= CURSOR_CACHE_SIZE && !self.bitmaps.contains_key(&msg.shape_id) {
+            if let Some(evicted) = self.ids.front().copied() {
+                self.bitmaps.remove(&evicted);
+            }
+        }
+        touch(&mut self.ids, msg.shape_id);
+        self.bitmaps.insert(msg.shape_id, bitmap.clone());
+        Some(CursorShape::Bitmap(bitmap))
+    }
+}
+
+/// Poll `platform` for cursor changes every `poll_interval`, delivering
+/// debounced `CursorShapeV1` messages. Read errors fall back to the arrow.
+/// The task stops when the receiver is dropped.
+pub fn spawn_cursor_watch<P>(
+    platform: Arc<P>,
+    poll_interval: Duration,
+    debounce: Duration,
+) -> mpsc::Receiver<CursorShapeV1>
+where
+    P: HostPlatform + ?Sized + 'static,
+{
+    let (tx, rx) = mpsc::channel(8);
+    tokio::spawn(async move {
+        let mut tracker = CursorTracker::new(debounce);
+        let mut interval = tokio::time::interval(poll_interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        loop {
+            interval.tick().await;
+            let shape = platform.cursor_shape().await.unwrap_or_else(|e| {
+                debug!("cursor read failed: {}", e);
+                CursorShape::default()
+            });
+            if let Some(msg) = tracker.observe(shape, Instant::now()) {
+                if tx.send(msg).await.is_err() {
+                    break;
+                }
+            }
+        }
+    });
+    rx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bitmap(fill: u8) -> CursorBitmap {
+        CursorBitmap {
+            width: 2,
+            height: 2,
+            hotspot_x: 1,
+            hotspot_y: 0,
+            rgba: Bytes::from(vec![fill; 16]),
+        }
+    }
+
+    #[test]
+    fn test_cursor_changes_debounced() {
+        let mut tracker = CursorTracker::new(Duration::from_millis(50));
+        let t0 = Instant::now();
+
+        let first = tracker.observe(CursorShape::Standard(StandardCursor::Text), t0).unwrap();
+        assert_eq!(first.standard, StandardV1::Text as i32);
+        assert!(tracker.observe(CursorShape::Standard(StandardCursor::Text), t0).is_none());
+
+        // Rapid changes within the interval collapse into the latest one
+        let t1 = t0 + Duration::from_millis(10);
+        assert!(tracker.observe(CursorShape::Standard(StandardCursor::Hand), t1).is_none());
+        assert!(tracker.observe(CursorShape::Standard(StandardCursor::Wait), t1).is_none());
+        let later = tracker.flush(t0 + Duration::from_millis(60)).unwrap();
+        assert_eq!(later.standard, StandardV1::Wait as i32);
+    }
+
+    #[test]
+    fn test_cached_bitmap_sent_by_id() {
+        let mut tracker = CursorTracker::new(Duration::ZERO);
+        let mut cache = CursorShapeCache::new();
+        let now = Instant::now();
+
+        let a = tracker.observe(CursorShape::Bitmap(bitmap(1)), now).unwrap();
+        assert_eq!(a.rgba.len(), 16);
+        assert_eq!(cache.resolve(&a), Some(CursorShape::Bitmap(bitmap(1))));
+
+        let b = tracker.observe(CursorShape::Bitmap(bitmap(2)), now).unwrap();
+        cache.resolve(&b).unwrap();
+
+        let again = tracker.observe(CursorShape::Bitmap(bitmap(1)), now).unwrap();
+        assert!(again.rgba.is_empty());
+        assert_eq!(again.shape_id, a.shape_id);
+        assert_eq!(cache.resolve(&again), Some(CursorShape::Bitmap(bitmap(1))));
+    }
+
+    #[test]
+    fn test_unreadable_bitmap_falls_back_to_arrow() {
+        let mut tracker = CursorTracker::new(Duration::ZERO);
+        let oversized = CursorBitmap {
+            width: MAX_CURSOR_DIMENSION + 1,
+            height: 1,
+            hotspot_x: 0,
+            hotspot_y: 0,
+            rgba: Bytes::from(vec![0; (MAX_CURSOR_DIMENSION as usize + 1) * 4]),
+        };
+        let msg = tracker.observe(CursorShape::Bitmap(oversized), Instant::now()).unwrap();
+        assert_eq!(msg.standard, StandardV1::Arrow as i32);
+        assert!(msg.rgba.is_empty());
+
+        // Unknown ids are not resolved
+        let unknown = CursorShapeV1 { shape_id: 42, ..Default::default() };
+        assert!(CursorShapeCache::new().resolve(&unknown).is_none());
+    }
+}
@@ -11,12 +11,20 @@ use std::sync::Arc;
 
 use async_trait::async_trait;
 use prost::Message;
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, RwLock};
 use tracing::{debug, warn};
 use x25519_dalek::StaticSecret;
 
+use crate::audit::AuditLogger;
 use crate::errors::CoreError;
+use crate::pairing::PairingError;
+use crate::policy::PolicyError;
+use crate::replay::{ReplayGuard, ReplayGuardError};
+use crate::rate_limit::{RateLimitError, RateLimiter, RequestType};
+use crate::session::SessionError;
+use crate::store::{Store, StoreError};
 use zrc_crypto::envelope::{envelope_open_v1, EnvelopeError};
+use zrc_crypto::replay::ReplayError;
 use zrc_proto::v1::{EnvelopeV1, MsgTypeV1, PairReceiptV1, SessionInitResponseV1};
 
 // ============================================================================
@@ -24,6 +32,9 @@ use zrc_proto::v1::{EnvelopeV1, MsgTypeV1, PairReceiptV1, SessionInitResponseV1}
 // ============================================================================
 
 /// Errors specific to dispatch operations.
+///
+/// Variants are failure categories callers can branch on (exit codes, audit
+/// reasons) rather than stringly-typed causes.
 #[derive(Debug, Clone)]
 pub enum DispatchError {
     /// No handler registered for message type
@@ -40,6 +51,16 @@ pub enum DispatchError {
     DecodeError(String),
     /// Unknown message type
     UnknownMsgType(i32),
+    /// Pair request rejected by the pairing rate limit
+    Throttled { retry_after_secs: u64 },
+    /// Message was already seen or falls outside the replay window
+    ReplayDetected(String),
+    /// Sender is not paired with this device
+    NotPaired,
+    /// Denied by policy or missing permission
+    PolicyDenied(String),
+    /// Local failure (store, key lookup) unrelated to the message itself
+    Internal(String),
 }
 
 impl std::fmt::Display for DispatchError {
@@ -52,6 +73,13 @@ impl std::fmt::Display for DispatchError {
             DispatchError::MissingField(s) => write!(f, "missing field: {}", s),
             DispatchError::DecodeError(s) => write!(f, "decode error: {}", s),
             DispatchError::UnknownMsgType(t) => write!(f, "unknown message type: {}", t),
+            DispatchError::Throttled { retry_after_secs } => {
+                write!(f, "pair request throttled, retry after {}s", retry_after_secs)
+            }
+            DispatchError::ReplayDetected(s) => write!(f, "replay detected: {}", s),
+            DispatchError::NotPaired => write!(f, "sender is not paired"),
+            DispatchError::PolicyDenied(s) => write!(f, "denied by policy: {}", s),
+            DispatchError::Internal(s) => write!(f, "internal error: {}", s),
         }
     }
 }
@@ -81,6 +109,85 @@ impl From<EnvelopeError> for DispatchError {
     }
 }
 
+impl From<HandlerError> for DispatchError {
+    fn from(e: HandlerError) -> Self {
+        match e {
+            HandlerError::InvalidPayload(s) => DispatchError::DecodeError(s),
+            HandlerError::ProcessingFailed(s) => DispatchError::HandlerError(s),
+            HandlerError::PermissionDenied(s) => DispatchError::PolicyDenied(s),
+            HandlerError::Internal(s) => DispatchError::Internal(s),
+        }
+    }
+}
+
+impl From<ReplayError> for DispatchError {
+    fn from(e: ReplayError) -> Self {
+        DispatchError::ReplayDetected(e.to_string())
+    }
+}
+
+impl From<ReplayGuardError> for DispatchError {
+    fn from(e: ReplayGuardError) -> Self {
+        match e {
+            ReplayGuardError::Store(msg) => DispatchError::Internal(msg),
+            other => DispatchError::ReplayDetected(other.to_string()),
+        }
+    }
+}
+
+impl From<StoreError> for DispatchError {
+    fn from(e: StoreError) -> Self {
+        DispatchError::Internal(e.to_string())
+    }
+}
+
+impl From<PolicyError> for DispatchError {
+    fn from(e: PolicyError) -> Self {
+        DispatchError::PolicyDenied(e.to_string())
+    }
+}
+
+impl From<RateLimitError> for DispatchError {
+    fn from(e: RateLimitError) -> Self {
+        match e {
+            RateLimitError::RateLimited { retry_after_secs, .. } => {
+                DispatchError::Throttled { retry_after_secs }
+            }
+        }
+    }
+}
+
+impl From<SessionError> for DispatchError {
+    fn from(e: SessionError) -> Self {
+        match e {
+            SessionError::NotPaired => DispatchError::NotPaired,
+            SessionError::PermissionDenied(s) | SessionError::PolicyError(s) => {
+                DispatchError::PolicyDenied(s)
+            }
+            SessionError::ConsentDenied => DispatchError::PolicyDenied(e.to_string()),
+            SessionError::SignatureInvalid => DispatchError::SignatureInvalid,
+            SessionError::MissingField(s) => DispatchError::MissingField(s),
+            SessionError::StoreError(s) => DispatchError::Internal(s),
+            other => DispatchError::HandlerError(other.to_string()),
+        }
+    }
+}
+
+impl From<PairingError> for DispatchError {
+    fn from(e: PairingError) -> Self {
+        match e {
+            PairingError::RateLimited { retry_after_secs } => {
+                DispatchError::Throttled { retry_after_secs }
+            }
+            PairingError::Rejected => DispatchError::PolicyDenied(e.to_string()),
+            PairingError::SignatureInvalid => DispatchError::SignatureInvalid,
+            PairingError::MissingField(s) => DispatchError::MissingField(s),
+            PairingError::StoreError(s) => DispatchError::Internal(s),
+            other => DispatchError::HandlerError(other.to_string()),
+        }
+    }
+}
+
 // ============================================================================
 // Handler Error Type
 // ============================================================================
@@ -161,6 +268,8 @@ pub struct DispatchStats {
     pub unknown_type: AtomicU64,
     /// Messages dropped due to handler errors
     pub handler_errors: AtomicU64,
+    /// Pair requests dropped by the pairing rate limit
+    pub throttled: AtomicU64,
 }
 
 impl DispatchStats {
@@ -179,6 +288,7 @@ impl DispatchStats {
             decryption_failures: self.decryption_failures.load(Ordering::Relaxed),
             unknown_type: self.unknown_type.load(Ordering::Relaxed),
             handler_errors: self.handler_errors.load(Ordering::Relaxed),
+            throttled: self.throttled.load(Ordering::Relaxed),
         }
     }
 
@@ -191,6 +301,7 @@ impl DispatchStats {
         self.decryption_failures.store(0, Ordering::Relaxed);
         self.unknown_type.store(0, Ordering::Relaxed);
         self.handler_errors.store(0, Ordering::Relaxed);
+        self.throttled.store(0, Ordering::Relaxed);
     }
 
     fn inc_received(&self) {
@@ -224,6 +335,11 @@ impl DispatchStats {
         self.handler_errors.fetch_add(1, Ordering::Relaxed);
         self.inc_dropped();
     }
+
+    fn inc_throttled(&self) {
+        self.throttled.fetch_add(1, Ordering::Relaxed);
+        self.inc_dropped();
+    }
 }
 
 /// Snapshot of dispatch statistics at a point in time.
@@ -236,6 +352,7 @@ pub struct DispatchStatsSnapshot {
     pub decryption_failures: u64,
     pub unknown_type: u64,
     pub handler_errors: u64,
+    pub throttled: u64,
 }
 
 // ============================================================================
@@ -274,6 +391,9 @@ pub trait SenderKeyResolver: Send + Sync {
 /// 3. Decrypts the payload
 /// 4. Routes to the appropriate handler based on msg_type
 /// 5. Tracks statistics for observability
+///
+/// When a pairing rate limiter is configured, pair requests are throttled per
+/// (operator id, source) before reaching their handler.
 pub struct Dispatcher {
     /// Registered message handlers by message type
     handlers: RwLock<HashMap<MsgTypeV1, Arc<dyn MessageHandler>>>,
@@ -283,8 +403,56 @@ pub struct Dispatcher {
     key_resolver: Arc<dyn SenderKeyResolver>,
     /// Dispatch statistics
     stats: Arc<DispatchStats>,
+    /// Rate limiter applied to pair requests
+    pairing_limiter: Option<Arc<RateLimiter>>,
+    /// Audit logger for throttled pair requests
+    audit: Option<Arc<AuditLogger>>,
+    /// Envelope replay guard and the store it is persisted to
+    replay: Option<ReplayState>,
+}
+
+/// Replay guard plus its persistence.
+struct ReplayState {
+    guard: Mutex<ReplayGuard>,
+    store: Arc<dyn Store>,
+    /// Held while saving so snapshots reach the store in order
+    save_lock: Mutex<()>,
+}
+
+impl ReplayState {
+    /// Save a snapshot if a batch is due.
+    async fn save_due(&self) {
+        let _order = self.save_lock.lock().await;
+        // Another dispatch may have saved while we waited
+        let snapshot = self.guard.lock().await.take_due();
+        if let Some(record) = snapshot {
+            if let Err(e) = self.store.save_replay_state(record).await {
+                warn!("failed to persist replay state: {}", e);
+            }
+        }
+    }
+
+    /// Save whatever was accepted since the last snapshot.
+    async fn flush(&self) -> Result<(), StoreError> {
+        let _order = self.save_lock.lock().await;
+        let snapshot = self.guard.lock().await.take_unsaved();
+        match snapshot {
+            Some(record) => self.store.save_replay_state(record).await,
+            None => Ok(()),
+        }
+    }
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
 }
 
+/// Source reported for envelopes dispatched without transport information.
+pub const UNKNOWN_SOURCE: &str = "unknown";
+
 impl Dispatcher {
     /// Create a new dispatcher.
     ///
@@ -300,9 +468,51 @@ impl Dispatcher {
             recipient_kex_priv,
             key_resolver,
             stats: Arc::new(DispatchStats::new()),
+            pairing_limiter: None,
+            audit: None,
+            replay: None,
+        }
+    }
+
+    /// Reject replayed envelopes with `guard`, saving its state to `store`
+    /// in batches so it survives restarts.
+    ///
+    /// Load the guard with [`ReplayGuard::load`] from the same store, and call
+    /// [`flush_replay_state`](Self::flush_replay_state) on shutdown.
+    pub fn with_replay_guard(mut self, guard: ReplayGuard, store: Arc<dyn Store>) -> Self {
+        self.replay = Some(ReplayState {
+            guard: Mutex::new(guard),
+            store,
+            save_lock: Mutex::new(()),
+        });
+        self
+    }
+
+    /// Save replay state not yet written by a full batch.
+    pub async fn flush_replay_state(&self) -> Result<(), DispatchError> {
+        match &self.replay {
+            Some(replay) => Ok(replay.flush().await?),
+            None => Ok(()),
         }
     }
 
+    /// Throttle pair requests with `limiter`.
+    ///
+    /// Attempts are keyed on the verified operator id and the source passed to
+    /// [`Dispatcher::dispatch_from`]; the window and attempt limit come from
+    /// the limiter's `RateLimitConfig`.
+    /// Requirements: 10.1, 10.7
+    pub fn with_pairing_rate_limit(mut self, limiter: Arc<RateLimiter>) -> Self {
+        self.pairing_limiter = Some(limiter);
+        self
+    }
+
+    /// Record throttled pair requests to `audit`.
+    pub fn with_audit_logger(mut self, audit: Arc<AuditLogger>) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
     /// Register a handler for a specific message type.
     /// Requirements: 6.2, 6.5
     ///
@@ -356,6 +566,19 @@ impl Dispatcher {
     pub async fn dispatch(
         &self,
         envelope: EnvelopeV1,
+    ) -> Result<Option<Vec<u8>>, DispatchError> {
+        self.dispatch_from(envelope, UNKNOWN_SOURCE).await
+    }
+
+    /// Dispatch an incoming envelope received from `source`.
+    ///
+    /// `source` identifies where the envelope came from (e.g. a peer address)
+    /// and is used to key the pairing rate limit.
+    /// Requirements: 6.3, 6.4, 10.1
+    pub async fn dispatch_from(
+        &self,
+        envelope: EnvelopeV1,
+        source: &str,
     ) -> Result<Option<Vec<u8>>, DispatchError> {
         self.stats.inc_received();
 
@@ -375,12 +598,22 @@ impl Dispatcher {
             return Err(DispatchError::UnknownMsgType(header.msg_type));
         }
 
+        // Cheap replay check before any crypto; nothing is remembered yet
+        let now = unix_now();
+        if let Some(replay) = &self.replay {
+            if let Err(e) = replay.guard.lock().await.check(&header.nonce, header.timestamp, now) {
+                warn!("replayed envelope from {}: {}", hex::encode(&header.sender_id), e);
+                self.stats.inc_dropped();
+                return Err(e.into());
+            }
+        }
+
         // Resolve sender's signing public key
         let sender_sign_pub = self
             .key_resolver
             .resolve_sign_pub(&header.sender_id)
             .await
-            .map_err(|e| DispatchError::DecryptionFailed(e))?
+            .map_err(DispatchError::Internal)?
             .ok_or_else(|| {
                 warn!("unknown sender: {}", hex::encode(&header.sender_id));
                 self.stats.inc_signature_failures();
@@ -412,12 +645,35 @@ impl Dispatcher {
             DispatchError::from(e)
         })?;
 
+        // Remember the nonce only now the header is authenticated, so forged
+        // envelopes can't move the high-water mark. Re-checks under the lock
+        // in case the same envelope raced us here.
+        if let Some(replay) = &self.replay {
+            let due = {
+                let mut guard = replay.guard.lock().await;
+                if let Err(e) = guard.accept(&header.nonce, header.timestamp, now) {
+                    warn!("replayed envelope from {}: {}", hex::encode(&header.sender_id), e);
+                    self.stats.inc_dropped();
+                    return Err(e.into());
+                }
+                guard.is_due()
+            };
+            if due {
+                replay.save_due().await;
+            }
+        }
+
         // Convert sender_id to fixed array
         let mut sender_id_arr = [0u8; 32];
         if verified_sender_id.len() >= 32 {
             sender_id_arr.copy_from_slice(&verified_sender_id[..32]);
         }
 
+        // Throttle repeated pair requests (Requirements: 10.1)
+        if msg_type == MsgTypeV1::PairRequest {
+            self.check_pairing_rate_limit(&sender_id_arr, source).await?;
+        }
+
         // Get handler for this message type
         let handler = {
             let handlers = self.handlers.read().await;
@@ -446,7 +702,39 @@ impl Dispatcher {
             Err(e) => {
                 warn!("handler error for {:?}: {}", msg_type, e);
                 self.stats.inc_handler_errors();
-                Err(DispatchError::HandlerError(e.to_string()))
+                Err(DispatchError::from(e))
+            }
+        }
+    }
+
+    /// Check the pairing rate limit for an operator, auditing any rejection.
+    async fn check_pairing_rate_limit(
+        &self,
+        operator_id: &[u8; 32],
+        source: &str,
+    ) -> Result<(), DispatchError> {
+        let Some(limiter) = &self.pairing_limiter else {
+            return Ok(());
+        };
+
+        let key = format!("{}/{}", hex::encode(operator_id), source);
+        match limiter.check_rate_limit(&key, RequestType::Pairing).await {
+            Ok(()) => Ok(()),
+            Err(RateLimitError::RateLimited {
+                retry_after_secs, ..
+            }) => {
+                warn!(
+                    "throttled pair request from {} via {}",
+                    hex::encode(&operator_id[..8]),
+                    source
+                );
+                self.stats.inc_throttled();
+                if let Some(audit) = &self.audit {
+                    if let Err(e) = audit.rate_limit_exceeded(&key, "pairing_attempts").await {
+                        warn!("failed to audit throttled pair request: {}", e);
+                    }
+                }
+                Err(DispatchError::Throttled { retry_after_secs })
             }
         }
     }
@@ -466,6 +754,7 @@ impl Dispatcher {
 // ============================================================================
 
 #[derive(Debug)]
+#[allow(clippy::large_enum_variant)]
 pub enum ControllerEvent {
     PairReceipt(PairReceiptV1),
     SessionInitResponse(SessionInitResponseV1),
@@ -740,6 +1029,131 @@ mod tests {
         assert_eq!(stats.dropped, 1);
     }
 
+    /// Handler that refuses every sender.
+    struct DenyingHandler;
+
+    #[async_trait]
+    impl MessageHandler for DenyingHandler {
+        async fn handle(
+            &self,
+            _sender_id: [u8; 32],
+            _payload: &[u8],
+        ) -> Result<Option<Vec<u8>>, HandlerError> {
+            Err(HandlerError::PermissionDenied("no clipboard permission".into()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_handler_permission_denied_is_policy_denied() {
+        let sender_sign = SigningKey::generate(&mut OsRng);
+        let sender_sign_pub = sender_sign.verifying_key().to_bytes();
+        let sender_id = derive_id(&sender_sign_pub);
+
+        let recipient_kex_priv = StaticSecret::random_from_rng(OsRng);
+        let recipient_kex_pub = X25519PublicKey::from(&recipient_kex_priv);
+        let recipient_id = sha256(recipient_kex_pub.as_bytes());
+
+        let key_resolver = Arc::new(TestKeyResolver::new());
+        key_resolver.add_key(sender_id.to_vec(), sender_sign_pub).await;
+
+        let dispatcher = Dispatcher::new(recipient_kex_priv, key_resolver);
+        dispatcher
+            .register_handler(MsgTypeV1::ControlMsg, Arc::new(DenyingHandler))
+            .await;
+
+        let envelope = envelope_seal_v1(
+            &sender_sign,
+            &sender_id,
+            &recipient_id,
+            recipient_kex_pub.as_bytes(),
+            MsgTypeV1::ControlMsg,
+            b"test",
+            1700000000,
+        )
+        .unwrap();
+
+        let result = dispatcher.dispatch(envelope).await;
+        assert!(matches!(result, Err(DispatchError::PolicyDenied(_))));
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_replay_rejected_across_restart() {
+        use crate::replay::ReplayGuardConfig;
+        use crate::store::InMemoryStore;
+
+        let sender_sign = SigningKey::generate(&mut OsRng);
+        let sender_sign_pub = sender_sign.verifying_key().to_bytes();
+        let sender_id = derive_id(&sender_sign_pub);
+
+        let recipient_kex_priv = StaticSecret::random_from_rng(OsRng);
+        let recipient_kex_pub = X25519PublicKey::from(&recipient_kex_priv);
+        let recipient_id = sha256(recipient_kex_pub.as_bytes());
+
+        let key_resolver = Arc::new(TestKeyResolver::new());
+        key_resolver.add_key(sender_id.to_vec(), sender_sign_pub).await;
+        let store: Arc<dyn Store> = Arc::new(InMemoryStore::new());
+
+        let envelope = envelope_seal_v1(
+            &sender_sign,
+            &sender_id,
+            &recipient_id,
+            recipient_kex_pub.as_bytes(),
+            MsgTypeV1::ControlMsg,
+            b"test",
+            1700000000,
+        )
+        .unwrap();
+
+        let guard = ReplayGuard::load(store.as_ref(), ReplayGuardConfig::default()).await.unwrap();
+        let dispatcher = Dispatcher::new(recipient_kex_priv.clone(), key_resolver.clone())
+            .with_replay_guard(guard, store.clone());
+        dispatcher.register_handler(MsgTypeV1::ControlMsg, Arc::new(EchoHandler)).await;
+        dispatcher.dispatch(envelope.clone()).await.unwrap();
+        assert!(matches!(
+            dispatcher.dispatch(envelope.clone()).await,
+            Err(DispatchError::ReplayDetected(_))
+        ));
+
+        // A fresh dispatcher loading the same store still rejects it
+        dispatcher.flush_replay_state().await.unwrap();
+        let guard = ReplayGuard::load(store.as_ref(), ReplayGuardConfig::default()).await.unwrap();
+        let restarted = Dispatcher::new(recipient_kex_priv, key_resolver)
+            .with_replay_guard(guard, store);
+        restarted.register_handler(MsgTypeV1::ControlMsg, Arc::new(EchoHandler)).await;
+        assert!(matches!(
+            restarted.dispatch(envelope).await,
+            Err(DispatchError::ReplayDetected(_))
+        ));
+    }
+
+    #[test]
+    fn test_dispatch_error_from_underlying_errors() {
+        assert!(matches!(
+            DispatchError::from(SessionError::NotPaired),
+            DispatchError::NotPaired
+        ));
+        assert!(matches!(
+            DispatchError::from(SessionError::ConsentDenied),
+            DispatchError::PolicyDenied(_)
+        ));
+        assert!(matches!(
+            DispatchError::from(PairingError::RateLimited { retry_after_secs: 30 }),
+            DispatchError::Throttled { retry_after_secs: 30 }
+        ));
+        assert!(matches!(
+            DispatchError::from(ReplayError::DuplicatePacket { counter: 7 }),
+            DispatchError::ReplayDetected(_)
+        ));
+        assert!(matches!(
+            DispatchError::from(StoreError::OperationFailed("disk full".into())),
+            DispatchError::Internal(_)
+        ));
+        assert!(matches!(
+            DispatchError::from(EnvelopeError::DecryptFailed),
+            DispatchError::DecryptionFailed(_)
+        ));
+    }
+
     #[tokio::test]
     async fn test_stats_reset() {
         let recipient_kex_priv = StaticSecret::random_from_rng(OsRng);
@@ -762,4 +1176,74 @@ mod tests {
         assert_eq!(stats.received, 0);
         assert_eq!(stats.dispatched, 0);
     }
+
+    #[tokio::test]
+    async fn test_pair_requests_throttled_per_operator_and_source() {
+        use crate::audit::MemoryAuditSink;
+        use crate::rate_limit::RateLimitConfig;
+        use std::time::Duration;
+
+        let sender_sign = SigningKey::generate(&mut OsRng);
+        let sender_sign_pub = sender_sign.verifying_key().to_bytes();
+        let sender_id = derive_id(&sender_sign_pub);
+
+        let recipient_kex_priv = StaticSecret::random_from_rng(OsRng);
+        let recipient_kex_pub = X25519PublicKey::from(&recipient_kex_priv);
+        let recipient_id = sha256(recipient_kex_pub.as_bytes());
+
+        let key_resolver = Arc::new(TestKeyResolver::new());
+        key_resolver.add_key(sender_id.to_vec(), sender_sign_pub).await;
+
+        let sink = Arc::new(MemoryAuditSink::new(16));
+        let mut audit = AuditLogger::new([0u8; 32]);
+        audit.add_sink(sink.clone());
+
+        let limiter = Arc::new(RateLimiter::new(RateLimitConfig {
+            pairing_attempts_per_minute: 2,
+            window_duration: Duration::from_millis(100),
+            base_backoff: Duration::ZERO,
+            ..Default::default()
+        }));
+        let dispatcher = Dispatcher::new(recipient_kex_priv, key_resolver)
+            .with_pairing_rate_limit(limiter)
+            .with_audit_logger(Arc::new(audit));
+        dispatcher
+            .register_handler(MsgTypeV1::PairRequest, Arc::new(EchoHandler))
+            .await;
+
+        let pair_request = || {
+            envelope_seal_v1(
+                &sender_sign,
+                &sender_id,
+                &recipient_id,
+                recipient_kex_pub.as_bytes(),
+                MsgTypeV1::PairRequest,
+                b"pair request",
+                1700000000,
+            )
+            .unwrap()
+        };
+
+        // Attempts up to the limit pass; the next one within the window is throttled
+        for _ in 0..2 {
+            assert!(dispatcher.dispatch_from(pair_request(), "10.0.0.1").await.is_ok());
+        }
+        let result = dispatcher.dispatch_from(pair_request(), "10.0.0.1").await;
+        assert!(matches!(result, Err(DispatchError::Throttled { .. })));
+
+        // A different source is tracked separately
+        assert!(dispatcher.dispatch_from(pair_request(), "10.0.0.2").await.is_ok());
+
+        let events = sink.events().await;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event_type(), "RATE_LIMIT_EXCEEDED");
+
+        // Once the window has passed, attempts are accepted again
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        assert!(dispatcher.dispatch_from(pair_request(), "10.0.0.1").await.is_ok());
+
+        let stats = dispatcher.stats().snapshot();
+        assert_eq!(stats.throttled, 1);
+        assert_eq!(stats.dispatched, 4);
+    }
 }
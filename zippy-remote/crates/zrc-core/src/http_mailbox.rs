@@ -1,8 +1,13 @@
 #![cfg(feature = "http-mailbox")]
 
+use std::net::IpAddr;
+
 use bytes::Bytes;
 use reqwest::StatusCode;
 
+/// Header the rendezvous server sets to the address a message was posted from.
+const SOURCE_HEADER: &str = "x-message-source";
+
 #[derive(Clone)]
 pub struct HttpMailboxClient {
     base_url: String,
@@ -58,6 +63,16 @@ impl HttpMailboxClient {
 
     /// Long-poll: GET next envelope bytes for this mailbox. Returns None on 204.
     pub async fn poll(&self, my_id32: &[u8; 32], wait_ms: u64) -> Result<Option<Bytes>, HttpMailboxError> {
+        Ok(self.poll_with_source(my_id32, wait_ms).await?.map(|(bytes, _)| bytes))
+    }
+
+    /// Like [`poll`](Self::poll), also returning the address the envelope was
+    /// posted from when the server reports it.
+    pub async fn poll_with_source(
+        &self,
+        my_id32: &[u8; 32],
+        wait_ms: u64,
+    ) -> Result<Option<(Bytes, Option<IpAddr>)>, HttpMailboxError> {
         let mut url = self.mailbox_url(my_id32);
         url.push_str(&format!("?wait_ms={}", wait_ms));
 
@@ -70,8 +85,13 @@ impl HttpMailboxClient {
 
         match resp.status() {
             StatusCode::OK => {
+                let source = resp
+                    .headers()
+                    .get(SOURCE_HEADER)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.parse().ok());
                 let b = resp.bytes().await.map_err(|e| HttpMailboxError::Http(e.to_string()))?;
-                Ok(Some(Bytes::from(b.to_vec())))
+                Ok(Some((Bytes::from(b.to_vec()), source)))
             }
             StatusCode::NO_CONTENT => Ok(None),
             other => Err(HttpMailboxError::BadResponse(format!(
@@ -20,11 +20,13 @@ pub mod session;
 pub mod policy;
 pub mod dispatch;
 pub mod transport;
+pub mod clipboard;
 
 // Infrastructure
 pub mod store;
 pub mod audit;
 pub mod rate_limit;
+pub mod replay;
 
 // Supporting modules
 pub mod errors;
@@ -34,11 +36,17 @@ pub mod harness;
 
 // Platform abstraction (optional)
 pub mod platform;
+pub mod cursor;
 
 // Optional transport implementations
 #[cfg(feature = "http-mailbox")]
 pub mod http_mailbox;
 
+#[cfg(feature = "ws-mailbox")]
+pub mod ws_mailbox;
+
+pub mod mesh_mailbox;
+
 #[cfg(feature = "quic")]
 pub mod quic;
 
@@ -0,0 +1,419 @@
+//! Mesh mailbox transport.
+//!
+//! Sealed envelopes are relayed store-and-forward through mesh nodes. Each
+//! hop carries a [`MeshFrame`] of `ttl (1 byte) || recipient_id (32 bytes) ||
+//! envelope_bytes` (see `zrc_transport::mesh`); envelopes stay opaque, so sealing is unchanged (use
+//! `envelope_seal_v1` exactly as for the HTTP mailbox).
+//!
+//! A node that hosts the recipient's mailbox stores the envelope until it is
+//! polled. Any other node floods the frame to its peers with the TTL reduced
+//! by one. Each node remembers the highest TTL it has seen per envelope id
+//! (`sha256(envelope_bytes)`) and only passes a frame on again if it arrives
+//! with more hops left, so a copy that came the long way round and ran out
+//! does not shadow one on a shorter path. Every hop lowers the TTL, which
+//! bounds how far a frame travels and ends any loop.
+//!
+//! [`MeshMailboxClient`] has the same `post`/`poll` surface as
+//! `HttpMailboxClient`, so it can replace it in the host and controller loops.
+//! Mesh nodes reached over HTTP accept frames at `POST /v1/mesh/frames`,
+//! served by `zrc-rendezvous`.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use tokio::sync::Notify;
+use tracing::{debug, warn};
+
+pub use zrc_transport::mesh::MeshFrame;
+use zrc_transport::mesh::SeenCache;
+
+/// Hops a frame may take from the sender's first node.
+pub const DEFAULT_MESH_TTL: u8 = 4;
+
+/// Envelopes held per hosted mailbox before the oldest is dropped.
+const MAILBOX_CAPACITY: usize = 256;
+
+#[derive(Debug, thiserror::Error)]
+pub enum MeshError {
+    #[error("no mesh peers configured")]
+    NoPeers,
+    #[error("mesh peer error: {0}")]
+    Peer(String),
+}
+
+/// A link to a mesh node.
+#[async_trait]
+pub trait MeshPeer: Send + Sync {
+    /// Hand a frame to the node for storage or forwarding.
+    async fn forward(&self, frame: &MeshFrame) -> Result<(), MeshError>;
+
+    /// Take the next envelope from a mailbox hosted on the node, waiting up
+    /// to `wait_ms`.
+    async fn fetch(&self, id32: &[u8; 32], wait_ms: u64) -> Result<Option<Bytes>, MeshError>;
+}
+
+/// What a node did with a received frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeshDelivery {
+    /// Stored in a mailbox hosted here.
+    Stored,
+    /// Forwarded to this many peers.
+    Forwarded(usize),
+    /// Envelope already seen with at least as many hops left; dropped.
+    Duplicate,
+    /// Not hosted here and no hops left; dropped.
+    Expired,
+}
+
+/// A store-and-forward mesh node.
+#[derive(Default)]
+pub struct MeshNode {
+    peers: Mutex<Vec<Arc<dyn MeshPeer>>>,
+    hosted: Mutex<HashSet<[u8; 32]>>,
+    seen: Mutex<SeenCache>,
+    mailboxes: Mutex<HashMap<[u8; 32], VecDeque<Bytes>>>,
+    arrived: Notify,
+}
+
+impl MeshNode {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    /// Link a neighbouring node.
+    pub fn add_peer(&self, peer: Arc<dyn MeshPeer>) {
+        self.peers.lock().unwrap().push(peer);
+    }
+
+    /// Host the mailbox for `id32` on this node.
+    pub fn host_mailbox(&self, id32: [u8; 32]) {
+        self.hosted.lock().unwrap().insert(id32);
+    }
+
+    /// Envelopes waiting in a hosted mailbox.
+    pub fn pending(&self, id32: &[u8; 32]) -> usize {
+        self.mailboxes.lock().unwrap().get(id32).map_or(0, VecDeque::len)
+    }
+
+    /// Store or forward a frame.
+    pub async fn receive(&self, frame: &MeshFrame) -> MeshDelivery {
+        if frame.ttl == 0 {
+            return MeshDelivery::Expired;
+        }
+        let id = frame.envelope_id();
+
+        if self.hosted.lock().unwrap().contains(&frame.recipient_id) {
+            // Stored envelopes are delivered once, whatever TTL a repeat has
+            let mut seen = self.seen.lock().unwrap();
+            if seen.contains(&id) {
+                return MeshDelivery::Duplicate;
+            }
+            seen.insert(id, frame.ttl);
+            drop(seen);
+
+            let mut mailboxes = self.mailboxes.lock().unwrap();
+            let mailbox = mailboxes.entry(frame.recipient_id).or_default();
+            if mailbox.len() == MAILBOX_CAPACITY {
+                warn!("mesh mailbox {} full, dropping oldest", hex::encode(&frame.recipient_id[..8]));
+                mailbox.pop_front();
+            }
+            mailbox.push_back(frame.envelope.clone());
+            drop(mailboxes);
+            self.arrived.notify_waiters();
+            return MeshDelivery::Stored;
+        }
+
+        if !self.seen.lock().unwrap().insert(id, frame.ttl) {
+            return MeshDelivery::Duplicate;
+        }
+        let Some(next) = frame.next_hop() else {
+            debug!("mesh frame for {} expired", hex::encode(&frame.recipient_id[..8]));
+            return MeshDelivery::Expired;
+        };
+
+        let peers = self.peers.lock().unwrap().clone();
+        let mut forwarded = 0;
+        for peer in peers {
+            match peer.forward(&next).await {
+                Ok(()) => forwarded += 1,
+                Err(e) => debug!("mesh forward failed: {}", e),
+            }
+        }
+        MeshDelivery::Forwarded(forwarded)
+    }
+
+    /// Take the next envelope from a hosted mailbox, waiting up to `wait_ms`.
+    pub async fn poll_local(&self, id32: &[u8; 32], wait_ms: u64) -> Option<Bytes> {
+        let deadline = tokio::time::Instant::now() + Duration::from_millis(wait_ms);
+        loop {
+            let arrived = self.arrived.notified();
+            if let Some(envelope) = self
+                .mailboxes
+                .lock()
+                .unwrap()
+                .get_mut(id32)
+                .and_then(VecDeque::pop_front)
+            {
+                return Some(envelope);
+            }
+            if tokio::time::timeout_at(deadline, arrived).await.is_err() {
+                return None;
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl MeshPeer for MeshNode {
+    async fn forward(&self, frame: &MeshFrame) -> Result<(), MeshError> {
+        self.receive(frame).await;
+        Ok(())
+    }
+
+    async fn fetch(&self, id32: &[u8; 32], wait_ms: u64) -> Result<Option<Bytes>, MeshError> {
+        Ok(self.poll_local(id32, wait_ms).await)
+    }
+}
+
+/// Mesh node reached over HTTP.
+///
+/// Frames are POSTed to `{base_url}/v1/mesh/frames`; hosted mailboxes are
+/// polled through the node's regular mailbox endpoint.
+#[cfg(feature = "http-mailbox")]
+#[derive(Clone)]
+pub struct HttpMeshPeer {
+    frames_url: String,
+    client: reqwest::Client,
+    mailbox: crate::http_mailbox::HttpMailboxClient,
+}
+
+#[cfg(feature = "http-mailbox")]
+impl HttpMeshPeer {
+    pub fn new(base_url: impl Into<String>) -> Result<Self, MeshError> {
+        let base_url = base_url.into().trim_end_matches('/').to_string();
+        let client = reqwest::Client::builder()
+            .use_rustls_tls()
+            .build()
+            .map_err(|e| MeshError::Peer(e.to_string()))?;
+        let mailbox = crate::http_mailbox::HttpMailboxClient::new(base_url.clone())
+            .map_err(|e| MeshError::Peer(e.to_string()))?;
+        Ok(Self {
+            frames_url: format!("{}/v1/mesh/frames", base_url),
+            client,
+            mailbox,
+        })
+    }
+}
+
+#[cfg(feature = "http-mailbox")]
+#[async_trait]
+impl MeshPeer for HttpMeshPeer {
+    async fn forward(&self, frame: &MeshFrame) -> Result<(), MeshError> {
+        let resp = self
+            .client
+            .post(&self.frames_url)
+            .body(frame.encode())
+            .send()
+            .await
+            .map_err(|e| MeshError::Peer(e.to_string()))?;
+        if resp.status() == reqwest::StatusCode::ACCEPTED {
+            Ok(())
+        } else {
+            Err(MeshError::Peer(format!("status={}", resp.status())))
+        }
+    }
+
+    async fn fetch(&self, id32: &[u8; 32], wait_ms: u64) -> Result<Option<Bytes>, MeshError> {
+        self.mailbox
+            .poll(id32, wait_ms)
+            .await
+            .map_err(|e| MeshError::Peer(e.to_string()))
+    }
+}
+
+/// Mailbox client sending and receiving through mesh nodes.
+#[derive(Clone)]
+pub struct MeshMailboxClient {
+    peers: Vec<Arc<dyn MeshPeer>>,
+    ttl: u8,
+}
+
+impl MeshMailboxClient {
+    /// Client for the given entry nodes. The first node is treated as home:
+    /// `poll` long-polls it once the others have nothing waiting.
+    pub fn new(peers: Vec<Arc<dyn MeshPeer>>) -> Result<Self, MeshError> {
+        if peers.is_empty() {
+            return Err(MeshError::NoPeers);
+        }
+        Ok(Self {
+            peers,
+            ttl: DEFAULT_MESH_TTL,
+        })
+    }
+
+    /// Client for mesh nodes reached over HTTP.
+    #[cfg(feature = "http-mailbox")]
+    pub fn from_urls<S: AsRef<str>>(urls: &[S]) -> Result<Self, MeshError> {
+        let peers = urls
+            .iter()
+            .map(|url| Ok(Arc::new(HttpMeshPeer::new(url.as_ref())?) as Arc<dyn MeshPeer>))
+            .collect::<Result<Vec<_>, MeshError>>()?;
+        Self::new(peers)
+    }
+
+    /// Override the starting hop count.
+    pub fn with_ttl(mut self, ttl: u8) -> Self {
+        self.ttl = ttl.max(1);
+        self
+    }
+
+    /// Send envelope bytes to recipient mailbox via every entry node.
+    ///
+    /// Succeeds if at least one node accepted the frame.
+    pub async fn post(&self, rid32: &[u8; 32], envelope_bytes: &[u8]) -> Result<(), MeshError> {
+        let frame = MeshFrame::new(self.ttl, *rid32, envelope_bytes);
+        let mut last_error = None;
+        let mut accepted = false;
+        for peer in &self.peers {
+            match peer.forward(&frame).await {
+                Ok(()) => accepted = true,
+                Err(e) => last_error = Some(e),
+            }
+        }
+        match (accepted, last_error) {
+            (true, _) => Ok(()),
+            (false, Some(e)) => Err(e),
+            (false, None) => Err(MeshError::NoPeers),
+        }
+    }
+
+    /// Next envelope for this mailbox from any entry node, waiting up to
+    /// `wait_ms` on the home node. Returns None if nothing arrived in time.
+    pub async fn poll(&self, my_id32: &[u8; 32], wait_ms: u64) -> Result<Option<Bytes>, MeshError> {
+        for peer in self.peers.iter().skip(1) {
+            match peer.fetch(my_id32, 0).await {
+                Ok(Some(envelope)) => return Ok(Some(envelope)),
+                Ok(None) => {}
+                Err(e) => debug!("mesh poll failed: {}", e),
+            }
+        }
+        self.peers[0].fetch(my_id32, wait_ms).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEVICE: [u8; 32] = [7; 32];
+
+    fn link(a: &Arc<MeshNode>, b: &Arc<MeshNode>) {
+        a.add_peer(b.clone());
+        b.add_peer(a.clone());
+    }
+
+    /// Nodes linked in a line: 0 - 1 - ... - n-1, device hosted on the last.
+    fn chain(n: usize) -> Vec<Arc<MeshNode>> {
+        let nodes: Vec<_> = (0..n).map(|_| MeshNode::new()).collect();
+        for pair in nodes.windows(2) {
+            link(&pair[0], &pair[1]);
+        }
+        nodes[n - 1].host_mailbox(DEVICE);
+        nodes
+    }
+
+    #[tokio::test]
+    async fn test_multi_hop_delivery() {
+        let nodes = chain(4);
+        let sender = MeshMailboxClient::new(vec![nodes[0].clone()]).unwrap();
+        let device = MeshMailboxClient::new(vec![nodes[3].clone()]).unwrap();
+
+        sender.post(&DEVICE, b"sealed envelope").await.unwrap();
+
+        let received = device.poll(&DEVICE, 100).await.unwrap();
+        assert_eq!(received.as_deref(), Some(&b"sealed envelope"[..]));
+        assert!(device.poll(&DEVICE, 10).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_poll_waits_for_arrival() {
+        let nodes = chain(2);
+        let device = MeshMailboxClient::new(vec![nodes[1].clone()]).unwrap();
+        let sender = MeshMailboxClient::new(vec![nodes[0].clone()]).unwrap();
+
+        let poll = tokio::spawn(async move { device.poll(&DEVICE, 2000).await });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        sender.post(&DEVICE, b"late").await.unwrap();
+
+        let received = poll.await.unwrap().unwrap();
+        assert_eq!(received.as_deref(), Some(&b"late"[..]));
+    }
+
+    #[tokio::test]
+    async fn test_ttl_limits_hops() {
+        let nodes = chain(4);
+        // Client -> 0 -> 1 -> 2 -> 3 is four hops; three run out at node 2
+        let sender = MeshMailboxClient::new(vec![nodes[0].clone()]).unwrap().with_ttl(3);
+        sender.post(&DEVICE, b"short-lived").await.unwrap();
+        assert_eq!(nodes[3].pending(&DEVICE), 0);
+
+        let sender = sender.with_ttl(4);
+        sender.post(&DEVICE, b"long enough").await.unwrap();
+        assert_eq!(nodes[3].pending(&DEVICE), 1);
+    }
+
+    #[tokio::test]
+    async fn test_shorter_path_delivers_after_long_path_expires() {
+        // 0 - 1 - 2 - 3 - 4 with a shortcut 0 - 3; node 0 tries 1 first
+        let nodes = chain(5);
+        link(&nodes[0], &nodes[3]);
+
+        // The long way reaches 3 with one hop left, which runs out there.
+        // The shortcut arrives later with more hops and still gets through.
+        let sender = MeshMailboxClient::new(vec![nodes[0].clone()]).unwrap().with_ttl(4);
+        sender.post(&DEVICE, b"detour").await.unwrap();
+        assert_eq!(nodes[4].pending(&DEVICE), 1);
+    }
+
+    #[tokio::test]
+    async fn test_spent_frame_is_not_stored() {
+        let nodes = chain(1);
+        assert_eq!(
+            nodes[0].receive(&MeshFrame::new(0, DEVICE, b"spent")).await,
+            MeshDelivery::Expired
+        );
+        assert_eq!(nodes[0].pending(&DEVICE), 0);
+    }
+
+    #[tokio::test]
+    async fn test_duplicates_suppressed_in_loop() {
+        // Ring with two paths to the device: 0 - 1 - 3 and 0 - 2 - 3, plus 1 - 2
+        let nodes: Vec<_> = (0..4).map(|_| MeshNode::new()).collect();
+        link(&nodes[0], &nodes[1]);
+        link(&nodes[0], &nodes[2]);
+        link(&nodes[1], &nodes[2]);
+        link(&nodes[1], &nodes[3]);
+        link(&nodes[2], &nodes[3]);
+        nodes[3].host_mailbox(DEVICE);
+
+        let sender = MeshMailboxClient::new(vec![nodes[0].clone()]).unwrap().with_ttl(8);
+        sender.post(&DEVICE, b"once").await.unwrap();
+        assert_eq!(nodes[3].pending(&DEVICE), 1);
+
+        // Re-sending the same envelope is dropped at the first node
+        assert_eq!(
+            nodes[0].receive(&MeshFrame::new(8, DEVICE, b"once")).await,
+            MeshDelivery::Duplicate
+        );
+        sender.post(&DEVICE, b"once").await.unwrap();
+        assert_eq!(nodes[3].pending(&DEVICE), 1);
+    }
+
+    #[test]
+    fn test_client_requires_peers() {
+        assert!(matches!(MeshMailboxClient::new(Vec::new()), Err(MeshError::NoPeers)));
+    }
+}
@@ -14,6 +14,7 @@ use getrandom::getrandom;
 use prost::Message;
 
 use crate::{
+    audit::{audit_id, warn_on_audit_error, AuditLogger, AuditSink},
     errors::CoreError,
     rate_limit::{RateLimiter, RequestType},
     store::{InviteRecord, MemoryStore, PairingRecord, Store},
@@ -26,9 +27,10 @@ use zrc_crypto::{
         compute_pairing_sas_6digit_v1, pair_proof_input_v1, pairing_sas_transcript_v1,
     },
 };
+use zrc_security::key_recovery::RevocationSet;
 use zrc_proto::v1::{
     DeviceIdV1, EndpointHintsV1, InviteV1, KeyTypeV1, PairReceiptV1, PairRequestV1, PermissionV1,
-    PublicKeyV1, TimestampV1, UserIdV1,
+    PermissionsV1, PublicKeyV1, TimestampV1, UserIdV1,
 };
 
 // ============================================================================
@@ -60,6 +62,8 @@ pub enum PairingError {
     Timeout,
     /// Store operation failed
     StoreError(String),
+    /// Device signing key has been revoked
+    KeyRevoked(String),
 }
 
 impl std::fmt::Display for PairingError {
@@ -78,6 +82,7 @@ impl std::fmt::Display for PairingError {
             PairingError::Rejected => write!(f, "pairing rejected by user"),
             PairingError::Timeout => write!(f, "pairing timeout"),
             PairingError::StoreError(s) => write!(f, "store error: {}", s),
+            PairingError::KeyRevoked(s) => write!(f, "device key revoked: {}", s),
         }
     }
 }
@@ -113,6 +118,111 @@ pub trait ConsentHandler: Send + Sync {
     ) -> Result<PairDecision, PairingError>;
 }
 
+/// Details of a pending pairing request presented to a consent provider.
+#[derive(Clone, Debug)]
+pub struct PairRequestContext {
+    /// Operator identifier from the pair request
+    pub operator_id: Vec<u8>,
+    /// Requested permissions as a `PermissionsV1` bitmask
+    pub requested_permissions: u32,
+    /// Short authentication string, if SAS verification is in use
+    pub sas: Option<String>,
+}
+
+/// Outcome of a consent request.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConsentDecision {
+    /// Approve with the requested permissions
+    Allow,
+    /// Reject the pairing
+    Deny,
+    /// Approve with a narrower `PermissionsV1` bitmask; bits that were not
+    /// requested are dropped
+    AllowWithPermissions(u32),
+}
+
+/// Trait for deciding whether a pending pairing request is approved.
+///
+/// Every `ConsentHandler` is also a `ConsentProvider`.
+/// Requirements: 1.5
+#[async_trait]
+pub trait ConsentProvider: Send + Sync {
+    /// Called when a pairing request needs user approval.
+    async fn request_consent(
+        &self,
+        ctx: &PairRequestContext,
+    ) -> Result<ConsentDecision, PairingError>;
+}
+
+/// Map a `PermissionV1` value to its `PermissionsV1` bit.
+fn permission_bit(perm: PermissionV1) -> u32 {
+    match perm {
+        PermissionV1::View => PermissionsV1::View as u32,
+        PermissionV1::Control => PermissionsV1::Control as u32,
+        PermissionV1::Clipboard => PermissionsV1::Clipboard as u32,
+        PermissionV1::Files => PermissionsV1::FileTransfer as u32,
+        PermissionV1::Audio => PermissionsV1::Audio as u32,
+        _ => 0,
+    }
+}
+
+#[async_trait]
+impl<T: ConsentHandler> ConsentProvider for T {
+    async fn request_consent(
+        &self,
+        ctx: &PairRequestContext,
+    ) -> Result<ConsentDecision, PairingError> {
+        let decision =
+            ConsentHandler::request_consent(self, &ctx.operator_id, ctx.sas.as_deref()).await?;
+        if !decision.approved {
+            return Ok(ConsentDecision::Deny);
+        }
+        if decision.granted_perms.is_empty() {
+            return Ok(ConsentDecision::Allow);
+        }
+        let mut permissions = decision
+            .granted_perms
+            .iter()
+            .fold(0, |acc, p| acc | permission_bit(*p));
+        if decision.unattended_enabled {
+            permissions |= PermissionsV1::Unattended as u32;
+        }
+        Ok(ConsentDecision::AllowWithPermissions(permissions))
+    }
+}
+
+/// Consent provider that denies requests not answered within a deadline.
+pub struct TimeoutConsentProvider<P: ConsentProvider> {
+    inner: P,
+    timeout: std::time::Duration,
+}
+
+impl<P: ConsentProvider> TimeoutConsentProvider<P> {
+    /// Wrap `inner`, denying any request it has not decided within `timeout`.
+    pub fn new(inner: P, timeout: std::time::Duration) -> Self {
+        Self { inner, timeout }
+    }
+}
+
+#[async_trait]
+impl<P: ConsentProvider> ConsentProvider for TimeoutConsentProvider<P> {
+    async fn request_consent(
+        &self,
+        ctx: &PairRequestContext,
+    ) -> Result<ConsentDecision, PairingError> {
+        match tokio::time::timeout(self.timeout, self.inner.request_consent(ctx)).await {
+            Ok(decision) => decision,
+            Err(_) => {
+                tracing::warn!(
+                    "Pairing consent timed out after {:?}; denying",
+                    self.timeout
+                );
+                Ok(ConsentDecision::Deny)
+            }
+        }
+    }
+}
+
 // ============================================================================
 // Pairing Host State Machine
 // ============================================================================
@@ -166,7 +276,7 @@ pub enum PairingAction {
 
 /// Host-side pairing state machine.
 /// Requirements: 1.1-1.8
-pub struct PairingHost<S: Store, C: ConsentHandler> {
+pub struct PairingHost<S: Store, C: ConsentProvider> {
     /// Current state of the state machine
     state: PairingHostState,
     /// Device identity keys
@@ -177,9 +287,11 @@ pub struct PairingHost<S: Store, C: ConsentHandler> {
     consent_handler: Arc<C>,
     /// Rate limiter for protection
     rate_limiter: RateLimiter,
+    /// Audit logger for pairing lifecycle events
+    audit: Option<AuditLogger>,
 }
 
-impl<S: Store, C: ConsentHandler> PairingHost<S, C> {
+impl<S: Store, C: ConsentProvider> PairingHost<S, C> {
     /// Create a new pairing host state machine.
     pub fn new(device_keys: IdentityKeys, store: Arc<S>, consent_handler: Arc<C>) -> Self {
         Self {
@@ -188,6 +300,7 @@ impl<S: Store, C: ConsentHandler> PairingHost<S, C> {
             store,
             consent_handler,
             rate_limiter: RateLimiter::default(),
+            audit: None,
         }
     }
 
@@ -204,9 +317,19 @@ impl<S: Store, C: ConsentHandler> PairingHost<S, C> {
             store,
             consent_handler,
             rate_limiter,
+            audit: None,
         }
     }
 
+    /// Record pairing lifecycle events to `sink`.
+    /// Requirements: 9.1
+    pub fn with_audit_sink(mut self, sink: Arc<dyn AuditSink>) -> Self {
+        self.audit
+            .get_or_insert_with(|| AuditLogger::new(self.device_keys.id32))
+            .add_sink(sink);
+        self
+    }
+
     /// Get the current state.
     pub fn state(&self) -> &PairingHostState {
         &self.state
@@ -281,14 +404,18 @@ impl<S: Store, C: ConsentHandler> PairingHost<S, C> {
         source: &str,
     ) -> Result<PairingAction, PairingError> {
         // Check rate limit (Requirements: 1.8)
-        self.rate_limiter
+        if let Err(crate::rate_limit::RateLimitError::RateLimited {
+            retry_after_secs, ..
+        }) = self
+            .rate_limiter
             .check_rate_limit(source, RequestType::Pairing)
             .await
-            .map_err(|e| match e {
-                crate::rate_limit::RateLimitError::RateLimited {
-                    retry_after_secs, ..
-                } => PairingError::RateLimited { retry_after_secs },
-            })?;
+        {
+            if let Some(audit) = &self.audit {
+                warn_on_audit_error(audit.rate_limit_exceeded(source, "pairing_attempts").await);
+            }
+            return Err(PairingError::RateLimited { retry_after_secs });
+        }
 
         // Get current time
         let now = std::time::SystemTime::now()
@@ -351,6 +478,11 @@ impl<S: Store, C: ConsentHandler> PairingHost<S, C> {
             return Err(PairingError::MissingField("operator_kex_pub".into()));
         }
 
+        let operator_id = audit_id(&request.operator_id);
+        if let Some(audit) = &self.audit {
+            warn_on_audit_error(audit.pair_request_received(operator_id).await);
+        }
+
         // Build device_id for proof verification
         let device_id = DeviceIdV1 {
             id: self.device_keys.id32.to_vec(),
@@ -382,6 +514,9 @@ impl<S: Store, C: ConsentHandler> PairingHost<S, C> {
             self.state = PairingHostState::Failed {
                 reason: PairingError::InvalidProof,
             };
+            if let Some(audit) = &self.audit {
+                warn_on_audit_error(audit.pair_denied(operator_id, "invalid invite proof").await);
+            }
             return Err(PairingError::InvalidProof);
         }
 
@@ -421,6 +556,37 @@ impl<S: Store, C: ConsentHandler> PairingHost<S, C> {
     }
 
 
+    /// Ask the consent provider to decide the pending request.
+    ///
+    /// Approves or rejects accordingly; returns the receipt if approved.
+    /// Requirements: 1.5
+    pub async fn request_consent(&mut self) -> Result<Option<PairReceiptV1>, PairingError> {
+        let ctx = match &self.state {
+            PairingHostState::AwaitingApproval { request, sas, .. } => PairRequestContext {
+                operator_id: request.operator_id.clone(),
+                requested_permissions: request.requested_permissions,
+                sas: sas.clone(),
+            },
+            _ => {
+                return Err(PairingError::InvalidState(
+                    "can only request consent from AwaitingApproval state".into(),
+                ));
+            }
+        };
+
+        match self.consent_handler.request_consent(&ctx).await? {
+            ConsentDecision::Allow => self.approve(ctx.requested_permissions).await.map(Some),
+            ConsentDecision::AllowWithPermissions(permissions) => {
+                // Never grant more than the operator asked for
+                self.approve(permissions & ctx.requested_permissions).await.map(Some)
+            }
+            ConsentDecision::Deny => {
+                self.reject().await?;
+                Ok(None)
+            }
+        }
+    }
+
     /// Approve the pairing request.
     /// Requirements: 1.5, 1.6, 1.7
     pub async fn approve(&mut self, permissions: u32) -> Result<PairReceiptV1, PairingError> {
@@ -460,7 +626,7 @@ impl<S: Store, C: ConsentHandler> PairingHost<S, C> {
 
         // Sign receipt
         sign_pair_receipt_v1(&self.device_keys.sign, &mut receipt)
-            .map_err(|e| PairingError::CryptoError(e))?;
+            .map_err(PairingError::CryptoError)?;
 
         // Store pairing (Requirements: 1.7)
         let op_sign_pub = PublicKeyV1 {
@@ -501,6 +667,10 @@ impl<S: Store, C: ConsentHandler> PairingHost<S, C> {
             op_id_arr.copy_from_slice(&request.operator_id[..32]);
         }
 
+        if let Some(audit) = &self.audit {
+            warn_on_audit_error(audit.pair_approved(audit_id(&request.operator_id), permissions).await);
+        }
+
         // Transition to Paired state
         self.state = PairingHostState::Paired {
             operator_id: op_id_arr,
@@ -514,7 +684,11 @@ impl<S: Store, C: ConsentHandler> PairingHost<S, C> {
     /// Requirements: 1.5
     pub async fn reject(&mut self) -> Result<(), PairingError> {
         match &self.state {
-            PairingHostState::AwaitingApproval { .. } => {
+            PairingHostState::AwaitingApproval { request, .. } => {
+                if let Some(audit) = &self.audit {
+                    let operator_id = audit_id(&request.operator_id);
+                    warn_on_audit_error(audit.pair_denied(operator_id, "rejected by user").await);
+                }
                 self.state = PairingHostState::Failed {
                     reason: PairingError::Rejected,
                 };
@@ -590,6 +764,8 @@ pub struct PairingController<S: Store> {
     timeout_secs: u64,
     /// When the current pairing attempt started
     started_at: Option<u64>,
+    /// Cached key revocations; device keys in it are refused
+    revocations: Option<Arc<RevocationSet>>,
 }
 
 impl<S: Store> PairingController<S> {
@@ -601,14 +777,30 @@ impl<S: Store> PairingController<S> {
             store,
             timeout_secs: 300, // 5 minutes (Requirements: 2.8)
             started_at: None,
+            revocations: None,
         }
     }
 
+    /// Refuse device keys found in this revocation set.
+    pub fn set_revocations(&mut self, revocations: Arc<RevocationSet>) {
+        self.revocations = Some(revocations);
+    }
+
     /// Get the current state.
     pub fn state(&self) -> &PairingControllerState {
         &self.state
     }
 
+    /// Fail if the device signing key has been revoked.
+    fn check_device_key(&self, device_sign_pub: &[u8]) -> Result<(), PairingError> {
+        match &self.revocations {
+            Some(revocations) => revocations
+                .check_now(device_sign_pub)
+                .map_err(|e| PairingError::KeyRevoked(e.to_string())),
+            None => Ok(()),
+        }
+    }
+
     /// Import an invite from raw bytes (protobuf-encoded InviteV1).
     /// Requirements: 2.2
     ///
@@ -652,6 +844,7 @@ impl<S: Store> PairingController<S> {
         if invite.expires_at <= now {
             return Err(PairingError::InviteExpired);
         }
+        self.check_device_key(&invite.device_sign_pub)?;
 
         // Record start time for timeout (Requirements: 2.8)
         self.started_at = Some(now);
@@ -700,6 +893,7 @@ impl<S: Store> PairingController<S> {
         if invite.expires_at <= now {
             return Err(PairingError::InviteExpired);
         }
+        self.check_device_key(&invite.device_sign_pub)?;
 
         // Record start time for timeout (Requirements: 2.8)
         self.started_at = Some(now);
@@ -843,6 +1037,12 @@ impl<S: Store> PairingController<S> {
             ));
         }
 
+        // The key may have been revoked since the invite was imported
+        if let Err(e) = self.check_device_key(&invite.device_sign_pub) {
+            self.state = PairingControllerState::Failed { reason: e.clone() };
+            return Err(e);
+        }
+
         // Verify device signature (Requirements: 2.4)
         verify_pair_receipt_with_key_v1(&receipt, &invite.device_sign_pub)
             .map_err(|_| PairingError::SignatureInvalid)?;
@@ -1073,17 +1273,6 @@ fn sign_pair_receipt_v1(
     Ok(())
 }
 
-/// Verify a PairReceiptV1 signature.
-fn verify_pair_receipt_v1(r: &PairReceiptV1) -> Result<(), String> {
-    if r.device_signature.len() != 64 {
-        return Err("invalid signature length".into());
-    }
-    // For now, just check signature format
-    // Full verification requires device public key from invite
-    let _bytes = pair_receipt_signing_bytes_v1(r)?;
-    Ok(())
-}
-
 /// Verify a PairReceiptV1 signature using the device's public key.
 fn verify_pair_receipt_with_key_v1(r: &PairReceiptV1, device_sign_pub: &[u8]) -> Result<(), String> {
     if r.device_signature.len() != 64 {
@@ -1228,7 +1417,7 @@ impl<A: PairingApprover> LegacyPairingHost<A> {
         };
 
         sign_pair_receipt_v1(&self.device_keys.sign, &mut receipt)
-            .map_err(|e| CoreError::Crypto(e))?;
+            .map_err(CoreError::Crypto)?;
 
         // Store pairing
         self.store
@@ -1402,6 +1591,211 @@ mod tests {
         assert!(matches!(host.state(), PairingHostState::InviteGenerated { .. }));
     }
 
+    /// Consent provider that returns a fixed decision, optionally after a delay.
+    struct FixedConsent {
+        decision: ConsentDecision,
+        delay: std::time::Duration,
+    }
+
+    #[async_trait]
+    impl ConsentProvider for FixedConsent {
+        async fn request_consent(
+            &self,
+            _ctx: &PairRequestContext,
+        ) -> Result<ConsentDecision, PairingError> {
+            tokio::time::sleep(self.delay).await;
+            Ok(self.decision)
+        }
+    }
+
+    /// Drive a host with `consent` up to AwaitingApproval, requesting `requested`.
+    async fn host_awaiting_approval<C: ConsentProvider>(
+        consent: C,
+        requested: u32,
+    ) -> PairingHost<InMemoryStore, C> {
+        let mut host = PairingHost::new(
+            generate_identity_keys(),
+            Arc::new(InMemoryStore::new()),
+            Arc::new(consent),
+        );
+        let mut controller =
+            PairingController::new(generate_identity_keys(), Arc::new(InMemoryStore::new()));
+
+        let invite = host.generate_invite(300, None).await.unwrap();
+        let secret = match host.state() {
+            PairingHostState::InviteGenerated { secret, .. } => *secret,
+            _ => panic!("expected InviteGenerated state"),
+        };
+        controller.import_invite_decoded(invite).unwrap();
+        let request = controller.send_request(&secret, requested).await.unwrap();
+        host.handle_request(request, "test-source").await.unwrap();
+        host
+    }
+
+    #[tokio::test]
+    async fn test_consent_provider_narrows_permissions() {
+        let consent = FixedConsent {
+            decision: ConsentDecision::AllowWithPermissions(PermissionsV1::View as u32),
+            delay: std::time::Duration::ZERO,
+        };
+        let mut host = host_awaiting_approval(consent, 0x03).await;
+
+        let receipt = host.request_consent().await.unwrap().expect("approved");
+        assert_eq!(receipt.permissions_granted, PermissionsV1::View as u32);
+        assert!(matches!(
+            host.state(),
+            PairingHostState::Paired { permissions, .. } if *permissions == PermissionsV1::View as u32
+        ));
+
+        // Consent can only be requested once per pending request
+        assert!(host.request_consent().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_consent_provider_cannot_widen_permissions() {
+        let consent = FixedConsent {
+            decision: ConsentDecision::AllowWithPermissions(
+                PermissionsV1::View as u32 | PermissionsV1::Control as u32,
+            ),
+            delay: std::time::Duration::ZERO,
+        };
+        let mut host = host_awaiting_approval(consent, PermissionsV1::View as u32).await;
+
+        let receipt = host.request_consent().await.unwrap().expect("approved");
+        assert_eq!(receipt.permissions_granted, PermissionsV1::View as u32);
+    }
+
+    #[tokio::test]
+    async fn test_controller_refuses_revoked_device_key() {
+        use zrc_proto::v1::revoke_v1::ReasonV1;
+        use zrc_security::key_recovery::issue_revocation;
+
+        let device_keys = generate_identity_keys();
+        let mut host = PairingHost::new(
+            device_keys.clone(),
+            Arc::new(InMemoryStore::new()),
+            Arc::new(AlwaysApprove),
+        );
+        let revocations = Arc::new(RevocationSet::new());
+        let mut controller =
+            PairingController::new(generate_identity_keys(), Arc::new(InMemoryStore::new()));
+        controller.set_revocations(revocations.clone());
+
+        let invite = host.generate_invite(300, None).await.unwrap();
+        let secret = match host.state() {
+            PairingHostState::InviteGenerated { secret, .. } => *secret,
+            _ => panic!("expected InviteGenerated state"),
+        };
+        controller.import_invite_decoded(invite.clone()).unwrap();
+        let request = controller.send_request(&secret, 0x03).await.unwrap();
+        host.handle_request(request, "test-source").await.unwrap();
+        let receipt = host.approve(0x03).await.unwrap();
+
+        // The device revokes its key while the receipt is in flight
+        let replacement = generate_identity_keys();
+        let record = issue_revocation(
+            &device_keys.id32,
+            &device_keys.sign,
+            &replacement.sign,
+            ReasonV1::Compromised,
+            0,
+            0,
+        );
+        revocations.insert(record).unwrap();
+
+        let result = controller.handle_receipt(receipt).await;
+        assert!(matches!(result, Err(PairingError::KeyRevoked(_))));
+        assert!(matches!(
+            controller.state(),
+            PairingControllerState::Failed { reason: PairingError::KeyRevoked(_) }
+        ));
+
+        // Invites carrying the revoked key are refused outright
+        assert!(matches!(
+            controller.import_invite_decoded(invite),
+            Err(PairingError::KeyRevoked(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_consent_handler_adapts_to_provider() {
+        let mut host = host_awaiting_approval(AlwaysApprove, 0x07).await;
+
+        let receipt = host.request_consent().await.unwrap().expect("approved");
+        assert_eq!(
+            receipt.permissions_granted,
+            PermissionsV1::View as u32 | PermissionsV1::Control as u32
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pairing_host_emits_audit_events() {
+        use crate::audit::MemoryAuditSink;
+
+        let sink = Arc::new(MemoryAuditSink::new(16));
+        let mut host = PairingHost::new(
+            generate_identity_keys(),
+            Arc::new(InMemoryStore::new()),
+            Arc::new(AlwaysApprove),
+        )
+        .with_audit_sink(sink.clone());
+        let mut controller =
+            PairingController::new(generate_identity_keys(), Arc::new(InMemoryStore::new()));
+
+        let invite = host.generate_invite(300, None).await.unwrap();
+        let secret = match host.state() {
+            PairingHostState::InviteGenerated { secret, .. } => *secret,
+            _ => panic!("expected InviteGenerated state"),
+        };
+        controller.import_invite_decoded(invite).unwrap();
+        let request = controller.send_request(&secret, 0x03).await.unwrap();
+        host.handle_request(request, "test-source").await.unwrap();
+        host.approve(0x03).await.unwrap();
+
+        let events = sink.events().await;
+        let types: Vec<_> = events.iter().map(|e| e.event_type()).collect();
+        assert_eq!(types, ["PAIR_REQUEST_RECEIVED", "PAIR_APPROVED"]);
+        assert_eq!(events[1].to_json()["details"]["permissions"], "0x00000003");
+    }
+
+    #[tokio::test]
+    async fn test_consent_deny_rejects() {
+        let consent = FixedConsent {
+            decision: ConsentDecision::Deny,
+            delay: std::time::Duration::ZERO,
+        };
+        let mut host = host_awaiting_approval(consent, 0x03).await;
+
+        assert!(host.request_consent().await.unwrap().is_none());
+        assert!(matches!(
+            host.state(),
+            PairingHostState::Failed { reason: PairingError::Rejected }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_timeout_consent_provider_denies_after_deadline() {
+        let slow = FixedConsent {
+            decision: ConsentDecision::Allow,
+            delay: std::time::Duration::from_secs(10),
+        };
+        let consent = TimeoutConsentProvider::new(slow, std::time::Duration::from_millis(20));
+        let mut host = host_awaiting_approval(consent, 0x03).await;
+
+        assert!(host.request_consent().await.unwrap().is_none());
+        assert!(matches!(host.state(), PairingHostState::Failed { .. }));
+
+        let fast = FixedConsent {
+            decision: ConsentDecision::Allow,
+            delay: std::time::Duration::ZERO,
+        };
+        let consent = TimeoutConsentProvider::new(fast, std::time::Duration::from_secs(5));
+        let mut host = host_awaiting_approval(consent, 0x03).await;
+
+        let receipt = host.request_consent().await.unwrap().expect("approved");
+        assert_eq!(receipt.permissions_granted, 0x03);
+    }
+
     #[tokio::test]
     async fn test_pairing_controller_state_transitions() {
         let operator_keys = generate_identity_keys();
@@ -1,8 +1,10 @@
 // crates/zrc-core/src/platform.rs
 use async_trait::async_trait;
 use bytes::Bytes;
+use crate::cursor::CursorShape;
+use zrc_proto::v1::{monitor_select_v1::StatusV1 as MonitorStatus, MonitorInfoV1, MonitorSelectV1};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum InputEvent {
     MouseMove { x: i32, y: i32 },
     MouseButton { button: u8, down: bool },
@@ -16,4 +18,271 @@ pub trait HostPlatform: Send + Sync {
     async fn apply_input(&self, evt: InputEvent) -> anyhow::Result<()>;
     async fn set_clipboard(&self, data: Bytes) -> anyhow::Result<()>;
     async fn get_clipboard(&self) -> anyhow::Result<Bytes>;
+
+    /// Displays available for capture, re-enumerated on each call so
+    /// unplugged monitors disappear. Platforms without enumeration report none.
+    async fn list_monitors(&self) -> Vec<MonitorInfo> {
+        Vec::new()
+    }
+
+    /// Capture one frame from monitor `id` (a `MonitorInfo::id`).
+    ///
+    /// Single-display platforms capture their only display.
+    async fn capture_monitor(&self, id: u32) -> anyhow::Result<Bytes> {
+        let _ = id;
+        self.capture_frame().await
+    }
+
+    /// Current cursor image and hotspot, polled by `cursor::spawn_cursor_watch`.
+    ///
+    /// Platforms that cannot read the cursor image report the standard arrow.
+    async fn cursor_shape(&self) -> anyhow::Result<CursorShape> {
+        Ok(CursorShape::default())
+    }
+}
+
+/// Position and size of a monitor in virtual desktop pixels.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MonitorBounds {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// A host display as reported by `HostPlatform::list_monitors`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MonitorInfo {
+    pub id: u32,
+    pub name: String,
+    pub bounds: MonitorBounds,
+    /// DPI scale factor (1.0 = 96 DPI)
+    pub scale: f64,
+    pub primary: bool,
+}
+
+impl From<&MonitorInfo> for MonitorInfoV1 {
+    fn from(m: &MonitorInfo) -> Self {
+        Self {
+            id: m.id,
+            name: m.name.clone(),
+            x: m.bounds.x,
+            y: m.bounds.y,
+            width: m.bounds.width,
+            height: m.bounds.height,
+            scale: m.scale as f32,
+            primary: m.primary,
+        }
+    }
+}
+
+impl From<&MonitorInfoV1> for MonitorInfo {
+    fn from(m: &MonitorInfoV1) -> Self {
+        Self {
+            id: m.id,
+            name: m.name.clone(),
+            bounds: MonitorBounds { x: m.x, y: m.y, width: m.width, height: m.height },
+            scale: m.scale as f64,
+            primary: m.primary,
+        }
+    }
+}
+
+/// A frame captured by `MonitorSelection::capture`.
+#[derive(Clone, Debug)]
+pub struct MonitorFrame {
+    /// Monitor the frame came from
+    pub monitor_id: u32,
+    pub data: Bytes,
+    /// Set when the selected monitor was gone and capture fell back to the
+    /// primary; send it to the controller.
+    pub fallback: Option<MonitorSelectV1>,
+}
+
+/// Host-side choice of the streamed monitor.
+///
+/// Starts on the primary monitor. The controller changes it with
+/// `MonitorSelectV1` requests (see `handle_request`). If the selected monitor
+/// disappears, `capture` falls back to the primary and reports a `FALLBACK`
+/// message for the controller.
+#[derive(Clone, Debug, Default)]
+pub struct MonitorSelection {
+    selected: Option<u32>,
+}
+
+impl MonitorSelection {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Explicitly selected monitor, if any (`None` means primary).
+    pub fn selected(&self) -> Option<u32> {
+        self.selected
+    }
+
+    /// Monitor that should be streamed given the current `monitors`: the
+    /// selected one if present, else the primary, else the first.
+    pub fn current(&self, monitors: &[MonitorInfo]) -> Option<u32> {
+        self.selected
+            .filter(|id| monitors.iter().any(|m| m.id == *id))
+            .or_else(|| primary_monitor(monitors))
+    }
+
+    /// Answer a controller `MonitorSelectV1`. Returns `None` for statuses the
+    /// host does not handle.
+    pub fn handle_request(
+        &mut self,
+        msg: &MonitorSelectV1,
+        monitors: &[MonitorInfo],
+    ) -> Option<MonitorSelectV1> {
+        let status = match MonitorStatus::try_from(msg.status).ok()? {
+            MonitorStatus::ListRequest => MonitorStatus::List,
+            MonitorStatus::SwitchRequest => {
+                if monitors.iter().any(|m| m.id == msg.monitor_id) {
+                    self.selected = Some(msg.monitor_id);
+                    MonitorStatus::Switched
+                } else {
+                    MonitorStatus::Rejected
+                }
+            }
+            _ => return None,
+        };
+        Some(self.message(status, monitors))
+    }
+
+    /// Monitor to stream from the freshly enumerated `monitors`, dropping a
+    /// selection that was unplugged. The second value is the `FALLBACK`
+    /// message to send the controller when that happens.
+    pub fn resolve(&mut self, monitors: &[MonitorInfo]) -> (Option<u32>, Option<MonitorSelectV1>) {
+        let mut fallback = None;
+        if let Some(lost) = self.selected {
+            if !monitors.iter().any(|m| m.id == lost) {
+                tracing::warn!("monitor {} disappeared, falling back to primary", lost);
+                self.selected = None;
+                fallback = Some(self.message(MonitorStatus::Fallback, monitors));
+            }
+        }
+        (self.current(monitors), fallback)
+    }
+
+    /// Capture from the streamed monitor, falling back to the primary if the
+    /// selected monitor was unplugged.
+    pub async fn capture<P: HostPlatform + ?Sized>(
+        &mut self,
+        platform: &P,
+    ) -> anyhow::Result<MonitorFrame> {
+        let monitors = platform.list_monitors().await;
+        let (monitor_id, fallback) = self.resolve(&monitors);
+
+        let Some(monitor_id) = monitor_id else {
+            // No enumeration on this platform: stream the only display
+            let data = platform.capture_frame().await?;
+            return Ok(MonitorFrame { monitor_id: 0, data, fallback });
+        };
+        let data = platform.capture_monitor(monitor_id).await?;
+        Ok(MonitorFrame { monitor_id, data, fallback })
+    }
+
+    fn message(&self, status: MonitorStatus, monitors: &[MonitorInfo]) -> MonitorSelectV1 {
+        MonitorSelectV1 {
+            status: status as i32,
+            monitor_id: self.current(monitors).unwrap_or_default(),
+            monitors: monitors.iter().map(MonitorInfoV1::from).collect(),
+        }
+    }
+}
+
+fn primary_monitor(monitors: &[MonitorInfo]) -> Option<u32> {
+    monitors
+        .iter()
+        .find(|m| m.primary)
+        .or_else(|| monitors.first())
+        .map(|m| m.id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct RecordingPlatform {
+        applied: Mutex<Vec<InputEvent>>,
+        monitors: Mutex<Vec<MonitorInfo>>,
+    }
+
+    #[async_trait]
+    impl HostPlatform for RecordingPlatform {
+        async fn capture_frame(&self) -> anyhow::Result<Bytes> {
+            Ok(Bytes::new())
+        }
+        async fn apply_input(&self, evt: InputEvent) -> anyhow::Result<()> {
+            self.applied.lock().unwrap().push(evt);
+            Ok(())
+        }
+        async fn set_clipboard(&self, _data: Bytes) -> anyhow::Result<()> {
+            Ok(())
+        }
+        async fn get_clipboard(&self) -> anyhow::Result<Bytes> {
+            Ok(Bytes::new())
+        }
+        async fn list_monitors(&self) -> Vec<MonitorInfo> {
+            self.monitors.lock().unwrap().clone()
+        }
+        async fn capture_monitor(&self, id: u32) -> anyhow::Result<Bytes> {
+            Ok(Bytes::from(id.to_le_bytes().to_vec()))
+        }
+    }
+
+    fn monitor(id: u32, primary: bool) -> MonitorInfo {
+        MonitorInfo {
+            id,
+            name: format!("Display {}", id),
+            bounds: MonitorBounds { x: 1920 * id as i32, y: 0, width: 1920, height: 1080 },
+            scale: 1.0,
+            primary,
+        }
+    }
+
+    fn request(status: MonitorStatus, monitor_id: u32) -> MonitorSelectV1 {
+        MonitorSelectV1 { status: status as i32, monitor_id, monitors: Vec::new() }
+    }
+
+    #[tokio::test]
+    async fn test_monitor_switch_and_unplug_fallback() {
+        let platform = RecordingPlatform::default();
+        *platform.monitors.lock().unwrap() = vec![monitor(1, false), monitor(2, true)];
+        let monitors = platform.list_monitors().await;
+        let mut selection = MonitorSelection::new();
+
+        let list = selection
+            .handle_request(&request(MonitorStatus::ListRequest, 0), &monitors)
+            .unwrap();
+        assert_eq!(list.status, MonitorStatus::List as i32);
+        assert_eq!(list.monitor_id, 2);
+        assert_eq!(list.monitors.len(), 2);
+
+        let rejected = selection
+            .handle_request(&request(MonitorStatus::SwitchRequest, 7), &monitors)
+            .unwrap();
+        assert_eq!(rejected.status, MonitorStatus::Rejected as i32);
+        assert_eq!(rejected.monitor_id, 2);
+
+        let switched = selection
+            .handle_request(&request(MonitorStatus::SwitchRequest, 1), &monitors)
+            .unwrap();
+        assert_eq!(switched.status, MonitorStatus::Switched as i32);
+        let frame = selection.capture(&platform).await.unwrap();
+        assert_eq!(frame.monitor_id, 1);
+        assert!(frame.fallback.is_none());
+
+        // Unplug monitor 1: capture moves to the primary and notifies
+        platform.monitors.lock().unwrap().remove(0);
+        let frame = selection.capture(&platform).await.unwrap();
+        assert_eq!(frame.monitor_id, 2);
+        let notice = frame.fallback.unwrap();
+        assert_eq!(notice.status, MonitorStatus::Fallback as i32);
+        assert_eq!(notice.monitor_id, 2);
+        assert!(selection.capture(&platform).await.unwrap().fallback.is_none());
+    }
 }
@@ -4,6 +4,7 @@
 //! as specified in Requirements 5.1-5.8.
 
 use std::collections::HashSet;
+use std::time::Duration;
 use thiserror::Error;
 use tracing::{warn, info};
 
@@ -30,6 +31,14 @@ pub mod permissions {
     pub const DEFAULT: u32 = VIEW | CONTROL;
 }
 
+/// Default time a session may go without input or keepalive before the host
+/// closes it.
+pub const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(15 * 60);
+
+/// How often a controller with no input to send pings the host, well inside
+/// any sensible idle timeout.
+pub const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(30);
+
 /// Errors from policy evaluation.
 #[derive(Debug, Error)]
 pub enum PolicyError {
@@ -54,7 +63,7 @@ pub enum ConsentMode {
 }
 
 /// Time-based access restrictions.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct TimeRestrictions {
     /// Allowed hours as (start_hour, end_hour) in 24h format.
     /// e.g., (9, 17) means 9:00 AM to 5:00 PM.
@@ -63,14 +72,6 @@ pub struct TimeRestrictions {
     pub allowed_days: Option<Vec<u8>>,
 }
 
-impl Default for TimeRestrictions {
-    fn default() -> Self {
-        Self {
-            allowed_hours: None,
-            allowed_days: None,
-        }
-    }
-}
 
 
 /// Policy engine for evaluating consent requirements and permissions.
@@ -84,6 +85,8 @@ pub struct PolicyEngine {
     time_restrictions: TimeRestrictions,
     /// Maximum permissions that can be granted (bitmask).
     permission_limits: u32,
+    /// Close sessions with no input or keepalive for this long (None = never).
+    idle_timeout: Option<Duration>,
 }
 
 impl Default for PolicyEngine {
@@ -93,6 +96,7 @@ impl Default for PolicyEngine {
             trusted_operators: HashSet::new(),
             time_restrictions: TimeRestrictions::default(),
             permission_limits: u32::MAX, // No limits by default
+            idle_timeout: Some(DEFAULT_IDLE_TIMEOUT),
         }
     }
 }
@@ -141,6 +145,16 @@ impl PolicyEngine {
         self.permission_limits = limits;
     }
 
+    /// Set the session idle timeout. `None` disables idle enforcement.
+    pub fn set_idle_timeout(&mut self, timeout: Option<Duration>) {
+        self.idle_timeout = timeout;
+    }
+
+    /// Get the session idle timeout.
+    pub fn idle_timeout(&self) -> Option<Duration> {
+        self.idle_timeout
+    }
+
 
     /// Check if a session requires user consent.
     ///
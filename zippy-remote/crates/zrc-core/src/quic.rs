@@ -1,11 +1,13 @@
 #![cfg(feature = "quic")]
 
-use std::{net::SocketAddr, sync::Arc};
+use std::{net::SocketAddr, sync::Arc, time::Duration};
 
 use bytes::Bytes;
 use quinn::{ClientConfig, Endpoint, ServerConfig};
 use rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer};
 use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc;
+use tracing::info;
 
 // Re-export for convenience
 pub use quinn::Connection;
@@ -77,7 +79,29 @@ pub fn make_pinned_client_config(server_cert_der: &[u8], alpn: &[u8]) -> Result<
 
 impl QuicServer {
     pub async fn bind(addr: SocketAddr, alpn: &[u8]) -> Result<Self, QuicError> {
-        let (server_cfg, cert_der) = make_self_signed_server_config(alpn)?;
+        Self::bind_with_migration(addr, alpn, true).await
+    }
+
+    /// Bind, choosing whether clients may migrate to a new address without
+    /// re-handshaking (see `zrc_transport::QuicConfig::allow_migration`).
+    pub async fn bind_with_migration(
+        addr: SocketAddr,
+        alpn: &[u8],
+        allow_migration: bool,
+    ) -> Result<Self, QuicError> {
+        Self::bind_with_transport(addr, alpn, Arc::default(), allow_migration).await
+    }
+
+    /// Bind with explicit transport parameters, e.g. the congestion
+    /// controller from `zrc_transport::QuicConfig::transport_config`.
+    pub async fn bind_with_transport(
+        addr: SocketAddr,
+        alpn: &[u8],
+        transport: Arc<quinn::TransportConfig>,
+        allow_migration: bool,
+    ) -> Result<Self, QuicError> {
+        let (mut server_cfg, cert_der) = make_self_signed_server_config(alpn)?;
+        server_cfg.transport_config(transport).migration(allow_migration);
         let endpoint = Endpoint::server(server_cfg, addr).map_err(|e| QuicError::Quic(e.to_string()))?;
         Ok(Self {
             endpoint: Arc::new(endpoint),
@@ -123,6 +147,55 @@ impl QuicClient {
             .map_err(|e| QuicError::Quic(e.to_string()))?;
         Ok(conn)
     }
+
+    /// Move the endpoint to a new local socket, e.g. after switching
+    /// networks. Open connections migrate to the new path without a new
+    /// handshake if the server allows migration. Returns the new local address.
+    pub fn rebind(&self, bind_addr: SocketAddr) -> Result<SocketAddr, QuicError> {
+        let socket = std::net::UdpSocket::bind(bind_addr).map_err(|e| QuicError::Io(e.to_string()))?;
+        self.endpoint.rebind(socket).map_err(|e| QuicError::Io(e.to_string()))?;
+        self.endpoint.local_addr().map_err(|e| QuicError::Io(e.to_string()))
+    }
+}
+
+/// The peer address of a connection changed (connection migration).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PathChange {
+    pub old: SocketAddr,
+    pub new: SocketAddr,
+    /// RTT estimate at the time the change was noticed; re-probe for a fresh one
+    pub rtt: Duration,
+}
+
+/// Report changes of `conn`'s peer address, checked every `poll_interval`.
+///
+/// quinn migrates paths transparently and has no event for it, so this polls
+/// `remote_address`. Higher layers use the events to re-probe RTT and re-emit
+/// stats. The task ends when the connection closes or the receiver is dropped.
+pub fn watch_path(conn: quinn::Connection, poll_interval: Duration) -> mpsc::Receiver<PathChange> {
+    let (tx, rx) = mpsc::channel(4);
+    tokio::spawn(async move {
+        let mut current = conn.remote_address();
+        let mut interval = tokio::time::interval(poll_interval);
+        loop {
+            tokio::select! {
+                _ = conn.closed() => break,
+                _ = tx.closed() => break,
+                _ = interval.tick() => {}
+            }
+            let addr = conn.remote_address();
+            if addr == current {
+                continue;
+            }
+            info!("quic path changed: {} -> {}", current, addr);
+            let change = PathChange { old: current, new: addr, rtt: conn.rtt() };
+            current = addr;
+            if tx.send(change).await.is_err() {
+                break;
+            }
+        }
+    });
+    rx
 }
 
 /// Length-prefixed frame (u32 BE) helpers.
@@ -1,10 +1,17 @@
 #![cfg(feature = "quic")]
 #![forbid(unsafe_code)]
 
+use std::future::Future;
+use std::io::{Read, Write};
+use std::sync::atomic::{AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
 use prost::Message;
 
 use crate::quic::{read_frame, write_frame};
-use zrc_crypto::session_crypto::{open_v1, seal_v1, SessionCryptoV1};
+use zrc_crypto::session_crypto::{open_v1, seal_v1, session_crypto_from_key_v1, SessionCryptoV1};
+use zrc_security::session_keys::{KeyRatchet, RatchetPolicy, RatchetSchedule};
 
 /// Logical channels over QUIC streams.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -78,16 +85,984 @@ pub fn decode_frame_packet(b: &[u8]) -> Option<FramePacketV1> {
     Some(FramePacketV1 { width, height, stride, format, pixels: b[17..].to_vec() })
 }
 
+// ---------------------------------------------------------------------------
+// Frame codecs
+// ---------------------------------------------------------------------------
+
+/// Set in `FramePacketV1::format` on the wire when the pixels are codec
+/// encoded; the low bits carry the `FrameCodecV1`. Coded payload is
+/// `[pixel_format u8][raw_len u32 BE][codec body]`.
+const CODED_FORMAT_FLAG: u8 = 0x80;
+
+/// Send a keyframe at least this often when delta coding.
+const DELTA_KEYFRAME_INTERVAL: u32 = 60;
+/// Dirty-region tile size: bytes per tile row segment and rows per tile.
+const DELTA_TILE_BYTES: usize = 256;
+const DELTA_TILE_ROWS: usize = 16;
+
+/// Frame encodings negotiated in the control handshake.
+///
+/// The controller lists the codecs it can decode in
+/// `ControlTicketV1::frame_codecs`; the host picks one with
+/// [`negotiate_frame_codec`]. Peers that predate codecs send no list and
+/// get raw frames.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FrameCodecV1 {
+    Raw = 0,
+    Zlib = 1,
+    Zstd = 2,
+    /// Zlib-compressed keyframes plus dirty-rectangle deltas
+    Delta = 3,
+}
+
+impl FrameCodecV1 {
+    pub fn from_u8(v: u8) -> Option<Self> {
+        match v {
+            0 => Some(Self::Raw),
+            1 => Some(Self::Zlib),
+            2 => Some(Self::Zstd),
+            3 => Some(Self::Delta),
+            _ => None,
+        }
+    }
+
+    /// Whether this build can encode and decode the codec.
+    pub fn is_supported(self) -> bool {
+        !matches!(self, Self::Zstd) || cfg!(feature = "frame-zstd")
+    }
+}
+
+/// Codecs this build supports, in host preference order.
+pub fn supported_frame_codecs() -> Vec<FrameCodecV1> {
+    [FrameCodecV1::Delta, FrameCodecV1::Zstd, FrameCodecV1::Zlib, FrameCodecV1::Raw]
+        .into_iter()
+        .filter(|c| c.is_supported())
+        .collect()
+}
+
+/// Host: pick the preferred codec among those the controller offered.
+pub fn negotiate_frame_codec(offered: &[u32]) -> FrameCodecV1 {
+    supported_frame_codecs()
+        .into_iter()
+        .find(|c| offered.contains(&(*c as u32)))
+        .unwrap_or(FrameCodecV1::Raw)
+}
+
+// ---------------------------------------------------------------------------
+// Quality
+// ---------------------------------------------------------------------------
+
+/// Range of `SessionControlV1::quality_level` the host honors; requests
+/// outside it are clamped.
+pub const MIN_QUALITY_LEVEL: u32 = 10;
+pub const MAX_QUALITY_LEVEL: u32 = 100;
+
+/// Encoder parameters derived from a quality level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QualityParams {
+    /// Significant bits kept per BGRA channel (4..=8). Fewer bits make
+    /// frames compress better under zlib/zstd/delta without changing the
+    /// frame size, so controller input coordinates stay valid.
+    pub color_bits: u8,
+}
+
+impl QualityParams {
+    /// Map 10..=100 linearly onto 4..=8 color bits.
+    pub fn for_level(level: u32) -> Self {
+        let level = level.clamp(MIN_QUALITY_LEVEL, MAX_QUALITY_LEVEL);
+        let span = MAX_QUALITY_LEVEL - MIN_QUALITY_LEVEL;
+        let bits = 4 + ((level - MIN_QUALITY_LEVEL) * 4 + span / 2) / span;
+        Self { color_bits: bits as u8 }
+    }
+
+    /// Drop the low bits of each byte of a plain BGRA frame. Damage packets
+    /// and other formats are left alone.
+    fn apply(&self, pkt: &FramePacketV1) -> Option<FramePacketV1> {
+        if self.color_bits >= 8 || pkt.format != 1 {
+            return None;
+        }
+        let mask = 0xFFu8 << (8 - self.color_bits);
+        let mut out = pkt.clone();
+        out.pixels.iter_mut().for_each(|b| *b &= mask);
+        Some(out)
+    }
+}
+
+/// Host quality level shared between the control loop, which applies
+/// `QualityChange` requests, and the frame sender, which reads it before
+/// every frame. A change therefore takes effect from the next frame.
+#[derive(Debug, Clone)]
+pub struct FrameQuality(Arc<AtomicU32>);
+
+impl Default for FrameQuality {
+    fn default() -> Self {
+        Self(Arc::new(AtomicU32::new(MAX_QUALITY_LEVEL)))
+    }
+}
+
+impl FrameQuality {
+    pub fn level(&self) -> u32 {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Set the level, clamped to 10..=100; returns the level applied.
+    pub fn set_level(&self, level: u32) -> u32 {
+        let level = level.clamp(MIN_QUALITY_LEVEL, MAX_QUALITY_LEVEL);
+        self.0.store(level, Ordering::Relaxed);
+        level
+    }
+
+    pub fn params(&self) -> QualityParams {
+        QualityParams::for_level(self.level())
+    }
+
+    /// Apply a session-control message. Returns the acknowledgement to send
+    /// back for `QualityChange`, carrying the level actually applied, and
+    /// None for actions this does not handle.
+    pub fn handle_session_control(
+        &self,
+        msg: &zrc_proto::v1::SessionControlV1,
+    ) -> Option<zrc_proto::v1::SessionControlV1> {
+        use zrc_proto::v1::SessionControlActionV1;
+        if msg.action != SessionControlActionV1::QualityChange as i32 {
+            return None;
+        }
+        let applied = self.set_level(msg.quality_level);
+        tracing::debug!(requested = msg.quality_level, applied, "frame quality changed");
+        Some(zrc_proto::v1::SessionControlV1 {
+            action: SessionControlActionV1::QualityChange as i32,
+            quality_level: applied,
+            ..Default::default()
+        })
+    }
+}
+
+/// Host-side frame encoder; keeps the previous frame for delta coding.
+pub struct FrameEncoder {
+    codec: FrameCodecV1,
+    quality: QualityParams,
+    prev: Option<FramePacketV1>,
+    since_keyframe: u32,
+}
+
+impl FrameEncoder {
+    pub fn new(codec: FrameCodecV1) -> Self {
+        Self { codec, quality: QualityParams::for_level(MAX_QUALITY_LEVEL), prev: None, since_keyframe: 0 }
+    }
+
+    pub fn codec(&self) -> FrameCodecV1 {
+        self.codec
+    }
+
+    pub fn quality(&self) -> QualityParams {
+        self.quality
+    }
+
+    /// Use `quality` from the next `encode` on.
+    pub fn set_quality(&mut self, quality: QualityParams) {
+        self.quality = quality;
+    }
+
+    /// Encode a frame into wire bytes (see `encode_frame_packet`).
+    pub fn encode(&mut self, pkt: &FramePacketV1) -> anyhow::Result<Vec<u8>> {
+        let reduced = self.quality.apply(pkt);
+        let pkt = reduced.as_ref().unwrap_or(pkt);
+        let body = match self.codec {
+            FrameCodecV1::Raw => return Ok(encode_frame_packet(pkt)),
+            FrameCodecV1::Zlib => zlib_compress(&pkt.pixels)?,
+            FrameCodecV1::Zstd => zstd_compress(&pkt.pixels)?,
+            FrameCodecV1::Delta => self.encode_delta(pkt)?,
+        };
+        Ok(encode_frame_packet(&coded_packet(pkt, self.codec, body)))
+    }
+
+    fn encode_delta(&mut self, pkt: &FramePacketV1) -> anyhow::Result<Vec<u8>> {
+        let rects = match &self.prev {
+            Some(prev) if self.since_keyframe < DELTA_KEYFRAME_INTERVAL => dirty_rects(prev, pkt),
+            _ => None,
+        };
+        // Fall back to a keyframe when more than half the frame changed
+        let rects = rects.filter(|r| {
+            let dirty: usize = r.iter().map(|r| r.w * r.h).sum();
+            dirty * 2 <= pkt.pixels.len()
+        });
+
+        let mut body = Vec::new();
+        match rects {
+            Some(rects) => {
+                let row_bytes = row_bytes(pkt).expect("checked by dirty_rects");
+                let mut delta = Vec::new();
+                delta.extend_from_slice(&(rects.len() as u32).to_be_bytes());
+                for r in &rects {
+                    for v in [r.x, r.y, r.w, r.h] {
+                        delta.extend_from_slice(&(v as u32).to_be_bytes());
+                    }
+                    for row in r.y..r.y + r.h {
+                        let start = row * row_bytes + r.x;
+                        delta.extend_from_slice(&pkt.pixels[start..start + r.w]);
+                    }
+                }
+                body.push(1);
+                body.extend_from_slice(&zlib_compress(&delta)?);
+                self.since_keyframe += 1;
+            }
+            None => {
+                body.push(0);
+                body.extend_from_slice(&zlib_compress(&pkt.pixels)?);
+                self.since_keyframe = 0;
+            }
+        }
+        self.prev = Some(pkt.clone());
+        Ok(body)
+    }
+}
+
+/// Controller-side frame decoder; keeps the previous frame for deltas.
+#[derive(Default)]
+pub struct FrameDecoder {
+    prev: Option<FramePacketV1>,
+}
+
+impl FrameDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decode wire bytes from any codec. Returns None for malformed input
+    /// or a delta without its base frame.
+    pub fn decode(&mut self, b: &[u8]) -> Option<FramePacketV1> {
+        let pkt = decode_frame_packet(b)?;
+        if pkt.format & CODED_FORMAT_FLAG == 0 {
+            return Some(pkt);
+        }
+
+        let codec = FrameCodecV1::from_u8(pkt.format & !CODED_FORMAT_FLAG)?;
+        if pkt.pixels.len() < 5 || !codec.is_supported() {
+            return None;
+        }
+        let format = pkt.pixels[0];
+        let raw_len = u32::from_be_bytes(pkt.pixels[1..5].try_into().ok()?) as usize;
+        let body = &pkt.pixels[5..];
+
+        let pixels = match codec {
+            FrameCodecV1::Raw => body.to_vec(),
+            FrameCodecV1::Zlib => zlib_decompress(body, raw_len)?,
+            FrameCodecV1::Zstd => zstd_decompress(body, raw_len)?,
+            FrameCodecV1::Delta => {
+                let (&kind, body) = body.split_first()?;
+                match kind {
+                    0 => zlib_decompress(body, raw_len)?,
+                    1 => {
+                        let prev = self.prev.as_ref()?;
+                        if (prev.width, prev.height, prev.stride, prev.format, prev.pixels.len())
+                            != (pkt.width, pkt.height, pkt.stride, format, raw_len)
+                        {
+                            return None;
+                        }
+                        let delta = zlib_decompress(body, raw_len.saturating_mul(2) + 4)?;
+                        apply_delta(prev, &delta)?
+                    }
+                    _ => return None,
+                }
+            }
+        };
+        if pixels.len() != raw_len {
+            return None;
+        }
+
+        let out = FramePacketV1 { width: pkt.width, height: pkt.height, stride: pkt.stride, format, pixels };
+        if codec == FrameCodecV1::Delta {
+            self.prev = Some(out.clone());
+        }
+        Some(out)
+    }
+}
+
+fn coded_packet(pkt: &FramePacketV1, codec: FrameCodecV1, body: Vec<u8>) -> FramePacketV1 {
+    let mut pixels = Vec::with_capacity(5 + body.len());
+    pixels.push(pkt.format);
+    pixels.extend_from_slice(&(pkt.pixels.len() as u32).to_be_bytes());
+    pixels.extend_from_slice(&body);
+    FramePacketV1 {
+        width: pkt.width,
+        height: pkt.height,
+        stride: pkt.stride,
+        format: CODED_FORMAT_FLAG | codec as u8,
+        pixels,
+    }
+}
+
+/// A changed region, in bytes horizontally and rows vertically.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct DirtyRect {
+    x: usize,
+    y: usize,
+    w: usize,
+    h: usize,
+}
+
+fn row_bytes(pkt: &FramePacketV1) -> Option<usize> {
+    let height = pkt.height as usize;
+    if height == 0 {
+        return None;
+    }
+    let row = if pkt.stride > 0 { pkt.stride as usize } else { pkt.pixels.len() / height };
+    (row > 0 && row * height == pkt.pixels.len()).then_some(row)
+}
+
+/// Tiles that differ between frames, merged along each tile row.
+/// None if the frames are not comparable.
+fn dirty_rects(prev: &FramePacketV1, cur: &FramePacketV1) -> Option<Vec<DirtyRect>> {
+    if (prev.width, prev.height, prev.stride, prev.format, prev.pixels.len())
+        != (cur.width, cur.height, cur.stride, cur.format, cur.pixels.len())
+    {
+        return None;
+    }
+    let row_bytes = row_bytes(cur)?;
+    let height = cur.height as usize;
+
+    let mut rects: Vec<DirtyRect> = Vec::new();
+    for y in (0..height).step_by(DELTA_TILE_ROWS) {
+        let h = DELTA_TILE_ROWS.min(height - y);
+        for x in (0..row_bytes).step_by(DELTA_TILE_BYTES) {
+            let w = DELTA_TILE_BYTES.min(row_bytes - x);
+            let changed = (y..y + h).any(|row| {
+                let start = row * row_bytes + x;
+                prev.pixels[start..start + w] != cur.pixels[start..start + w]
+            });
+            if !changed {
+                continue;
+            }
+            match rects.last_mut() {
+                Some(last) if last.y == y && last.x + last.w == x => last.w += w,
+                _ => rects.push(DirtyRect { x, y, w, h }),
+            }
+        }
+    }
+    Some(rects)
+}
+
+fn apply_delta(prev: &FramePacketV1, delta: &[u8]) -> Option<Vec<u8>> {
+    let row_bytes = row_bytes(prev)?;
+    let mut pixels = prev.pixels.clone();
+    let read_u32 = |at: usize| -> Option<usize> {
+        Some(u32::from_be_bytes(delta.get(at..at + 4)?.try_into().ok()?) as usize)
+    };
+
+    let count = read_u32(0)?;
+    let mut at = 4;
+    for _ in 0..count {
+        let (x, y, w, h) = (read_u32(at)?, read_u32(at + 4)?, read_u32(at + 8)?, read_u32(at + 12)?);
+        at += 16;
+        if x.checked_add(w)? > row_bytes || y.checked_add(h)? > prev.height as usize {
+            return None;
+        }
+        for row in y..y + h {
+            let src = delta.get(at..at + w)?;
+            let start = row * row_bytes + x;
+            pixels[start..start + w].copy_from_slice(src);
+            at += w;
+        }
+    }
+    (at == delta.len()).then_some(pixels)
+}
+
+fn zlib_compress(data: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let mut enc = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::fast());
+    enc.write_all(data)?;
+    Ok(enc.finish()?)
+}
+
+/// Inflate at most `max_len` bytes so a hostile peer cannot balloon memory.
+fn zlib_decompress(data: &[u8], max_len: usize) -> Option<Vec<u8>> {
+    let mut out = Vec::new();
+    flate2::read::ZlibDecoder::new(data)
+        .take(max_len as u64 + 1)
+        .read_to_end(&mut out)
+        .ok()?;
+    (out.len() <= max_len).then_some(out)
+}
+
+#[cfg(feature = "frame-zstd")]
+fn zstd_compress(data: &[u8]) -> anyhow::Result<Vec<u8>> {
+    Ok(zstd::bulk::compress(data, 1)?)
+}
+
+#[cfg(feature = "frame-zstd")]
+fn zstd_decompress(data: &[u8], max_len: usize) -> Option<Vec<u8>> {
+    zstd::bulk::decompress(data, max_len).ok()
+}
+
+#[cfg(not(feature = "frame-zstd"))]
+fn zstd_compress(_data: &[u8]) -> anyhow::Result<Vec<u8>> {
+    Err(anyhow::anyhow!("zstd frame codec not enabled in this build"))
+}
+
+#[cfg(not(feature = "frame-zstd"))]
+fn zstd_decompress(_data: &[u8], _max_len: usize) -> Option<Vec<u8>> {
+    None
+}
+
+// ---------------------------------------------------------------------------
+// Damage-region frames
+// ---------------------------------------------------------------------------
+
+/// Set in `FramePacketV1::format` on the wire when the packet carries
+/// changed rectangles rather than a full frame. Payload is
+/// `[count u32 BE]` followed by `count` x `[x][y][width][height]` (u32 BE,
+/// pixels) and that rectangle's tightly packed rows.
+const DAMAGE_FORMAT_FLAG: u8 = 0x40;
+
+/// Send a full frame instead when the damaged area exceeds this fraction
+/// of the frame.
+pub const DEFAULT_DAMAGE_THRESHOLD: f32 = 0.5;
+
+/// A changed rectangle, in pixels, with tightly packed rows
+/// (`width * bytes_per_pixel` bytes each).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirtyRectV1 {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+/// What the host capture callback produced.
+#[derive(Debug, Clone)]
+pub enum FrameUpdateV1 {
+    /// A complete frame
+    Full(FramePacketV1),
+    /// Rectangles changed since the previous update; same size and format
+    Damage(Vec<DirtyRectV1>),
+}
+
+fn bytes_per_pixel(format: u8) -> Option<usize> {
+    match format {
+        1 => Some(4), // BGRA
+        _ => None,
+    }
+}
+
+/// Copy `rects` into `frame`, validating bounds and sizes.
+fn apply_dirty_rects(frame: &mut FramePacketV1, rects: &[DirtyRectV1]) -> anyhow::Result<()> {
+    let bpp = bytes_per_pixel(frame.format)
+        .ok_or_else(|| anyhow::anyhow!("damage not supported for format {}", frame.format))?;
+    let stride = frame.stride as usize;
+    for r in rects {
+        let (x, y, w, h) = (r.x as usize, r.y as usize, r.width as usize, r.height as usize);
+        if x + w > frame.width as usize || y + h > frame.height as usize {
+            return Err(anyhow::anyhow!("dirty rect out of bounds"));
+        }
+        let row_len = w * bpp;
+        if r.pixels.len() != row_len * h || (y + h) * stride > frame.pixels.len() {
+            return Err(anyhow::anyhow!("dirty rect size mismatch"));
+        }
+        for (i, src) in r.pixels.chunks_exact(row_len.max(1)).enumerate().take(h) {
+            let start = (y + i) * stride + x * bpp;
+            frame.pixels[start..start + row_len].copy_from_slice(src);
+        }
+    }
+    Ok(())
+}
+
+fn encode_damage_packet(base: &FramePacketV1, rects: &[DirtyRectV1]) -> FramePacketV1 {
+    let mut payload = Vec::with_capacity(4 + rects.iter().map(|r| 16 + r.pixels.len()).sum::<usize>());
+    payload.extend_from_slice(&(rects.len() as u32).to_be_bytes());
+    for r in rects {
+        for v in [r.x, r.y, r.width, r.height] {
+            payload.extend_from_slice(&v.to_be_bytes());
+        }
+        payload.extend_from_slice(&r.pixels);
+    }
+    FramePacketV1 {
+        width: base.width,
+        height: base.height,
+        stride: base.stride,
+        format: DAMAGE_FORMAT_FLAG | base.format,
+        pixels: payload,
+    }
+}
+
+fn decode_damage_rects(payload: &[u8], bpp: usize) -> Option<Vec<DirtyRectV1>> {
+    let read_u32 = |at: usize| -> Option<u32> {
+        Some(u32::from_be_bytes(payload.get(at..at + 4)?.try_into().ok()?))
+    };
+    let count = read_u32(0)? as usize;
+    let mut at = 4;
+    let mut rects = Vec::with_capacity(count.min(1024));
+    for _ in 0..count {
+        let (x, y, width, height) = (read_u32(at)?, read_u32(at + 4)?, read_u32(at + 8)?, read_u32(at + 12)?);
+        at += 16;
+        let len = (width as usize).checked_mul(height as usize)?.checked_mul(bpp)?;
+        let pixels = payload.get(at..at.checked_add(len)?)?.to_vec();
+        at += len;
+        rects.push(DirtyRectV1 { x, y, width, height, pixels });
+    }
+    (at == payload.len()).then_some(rects)
+}
+
+/// Host-side damage state: retains the last full frame so a keyframe can
+/// always be sent, and turns capture updates into wire packets.
+pub struct DamageEncoder {
+    /// Whether the controller accepts damage packets
+    enabled: bool,
+    threshold: f32,
+    backbuffer: Option<FramePacketV1>,
+}
+
+impl DamageEncoder {
+    /// `enabled` is the negotiated `ControlChannelV1::damage_frames`; when
+    /// false every update is sent as a full frame.
+    pub fn new(enabled: bool) -> Self {
+        Self { enabled, threshold: DEFAULT_DAMAGE_THRESHOLD, backbuffer: None }
+    }
+
+    /// Damaged-area fraction above which a full frame is sent instead.
+    pub fn with_threshold(mut self, threshold: f32) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Turn a capture update into the packet to send.
+    ///
+    /// The first update must be a full frame.
+    pub fn encode(&mut self, update: FrameUpdateV1) -> anyhow::Result<FramePacketV1> {
+        let rects = match update {
+            FrameUpdateV1::Full(frame) => {
+                self.backbuffer = Some(frame.clone());
+                return Ok(frame);
+            }
+            FrameUpdateV1::Damage(rects) => rects,
+        };
+
+        let backbuffer = self
+            .backbuffer
+            .as_mut()
+            .ok_or_else(|| anyhow::anyhow!("first frame update must be a full frame"))?;
+        apply_dirty_rects(backbuffer, &rects)?;
+
+        let area = backbuffer.width as f64 * backbuffer.height as f64;
+        let dirty: f64 = rects.iter().map(|r| r.width as f64 * r.height as f64).sum();
+        if !self.enabled || dirty > area * self.threshold as f64 {
+            return Ok(backbuffer.clone());
+        }
+        Ok(encode_damage_packet(backbuffer, &rects))
+    }
+}
+
+/// Controller-side damage state: the retained backbuffer damage packets
+/// are applied onto. Full frames replace it.
+#[derive(Default)]
+pub struct DamageDecoder {
+    backbuffer: Option<FramePacketV1>,
+}
+
+impl DamageDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Apply a decoded packet and return the resulting full frame.
+    /// Returns None for malformed damage or damage before any full frame.
+    pub fn apply(&mut self, pkt: FramePacketV1) -> Option<FramePacketV1> {
+        if pkt.format & DAMAGE_FORMAT_FLAG == 0 {
+            self.backbuffer = Some(pkt.clone());
+            return Some(pkt);
+        }
+
+        let format = pkt.format & !DAMAGE_FORMAT_FLAG;
+        let backbuffer = self.backbuffer.as_mut()?;
+        if (backbuffer.width, backbuffer.height, backbuffer.stride, backbuffer.format)
+            != (pkt.width, pkt.height, pkt.stride, format)
+        {
+            return None;
+        }
+        let rects = decode_damage_rects(&pkt.pixels, bytes_per_pixel(format)?)?;
+        apply_dirty_rects(backbuffer, &rects).ok()?;
+        Some(backbuffer.clone())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Session statistics
+// ---------------------------------------------------------------------------
+
+/// How often the send/receive loops sample QUIC path stats (RTT,
+/// congestion window); per-frame counters are updated every frame.
+const PATH_SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Live statistics for one Frames stream, as seen by the local end.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SessionStats {
+    /// Frames sent (host) or received (controller)
+    pub frames: u64,
+    /// Encrypted bytes on the Frames stream, excluding QUIC overhead
+    pub bytes: u64,
+    /// Encrypted size of the most recent frame
+    pub last_frame_bytes: usize,
+    /// Time between the two most recent frames
+    pub frame_interval: Option<Duration>,
+    /// Smoothed round-trip time reported by QUIC
+    pub rtt: Option<Duration>,
+    /// Current congestion window in bytes
+    pub congestion_window: Option<u64>,
+    /// Packets QUIC considers lost on this path
+    pub lost_packets: u64,
+    /// Host: captures skipped because the send queue was backed up
+    pub skipped_captures: u64,
+    /// Host: sealed bytes captured but not yet accepted by the QUIC stream
+    pub in_flight_bytes: usize,
+    /// Host: smoothed capture rate after backpressure and the FPS cap
+    pub effective_fps: f64,
+}
+
+/// Shared handle to stats updated by a running stream.
+pub type SessionStatsHandle = Arc<RwLock<SessionStats>>;
+
+impl SessionStats {
+    /// Frame rate implied by the last inter-frame interval.
+    pub fn fps(&self) -> Option<f64> {
+        self.frame_interval
+            .filter(|d| !d.is_zero())
+            .map(|d| 1.0 / d.as_secs_f64())
+    }
+}
+
+/// Loop-local state so the shared lock is taken once per frame and the
+/// connection stats only once per `PATH_SAMPLE_INTERVAL`.
+struct StatsRecorder {
+    stats: SessionStatsHandle,
+    last_frame_at: Option<Instant>,
+    last_path_sample: Option<Instant>,
+}
+
+impl StatsRecorder {
+    fn new(stats: SessionStatsHandle) -> Self {
+        Self { stats, last_frame_at: None, last_path_sample: None }
+    }
+
+    fn record(&mut self, conn: &quinn::Connection, frame_bytes: usize) {
+        self.record_with(conn, frame_bytes, |_| {});
+    }
+
+    fn record_with(&mut self, conn: &quinn::Connection, frame_bytes: usize, extra: impl FnOnce(&mut SessionStats)) {
+        let now = Instant::now();
+        let interval = self.last_frame_at.map(|t| now.duration_since(t));
+        self.last_frame_at = Some(now);
+
+        let path = match self.last_path_sample {
+            Some(t) if now.duration_since(t) < PATH_SAMPLE_INTERVAL => None,
+            _ => {
+                self.last_path_sample = Some(now);
+                Some(conn.stats().path)
+            }
+        };
+
+        // A poisoned lock only means a reader panicked; the counters are
+        // still consistent, so keep updating them.
+        let mut stats = self.stats.write().unwrap_or_else(|e| e.into_inner());
+        stats.frames += 1;
+        stats.bytes += frame_bytes as u64;
+        stats.last_frame_bytes = frame_bytes;
+        if interval.is_some() {
+            stats.frame_interval = interval;
+        }
+        if let Some(path) = path {
+            stats.rtt = Some(path.rtt);
+            stats.congestion_window = Some(path.cwnd);
+            stats.lost_packets = path.lost_packets;
+        }
+        extra(&mut stats);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Adaptive frame rate
+// ---------------------------------------------------------------------------
+
+/// Default cap on sealed frame bytes queued ahead of the QUIC stream; room
+/// for two raw 1080p BGRA frames.
+pub const DEFAULT_MAX_IN_FLIGHT_BYTES: usize = 16 * 1024 * 1024;
+
+/// Weight of the newest interval in `FrameRateController::effective_fps`.
+const FPS_SMOOTHING: f64 = 0.2;
+
+/// Host frame pacing limits.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameRateConfig {
+    /// Skip captures while at least this many sealed bytes are waiting for
+    /// the QUIC stream to accept them. One frame is always allowed through
+    /// so frames larger than the limit still flow.
+    pub max_in_flight_bytes: usize,
+    /// Upper bound on captures per second; None means capture as fast as
+    /// the link drains.
+    pub max_fps: Option<f64>,
+}
+
+impl Default for FrameRateConfig {
+    fn default() -> Self {
+        Self { max_in_flight_bytes: DEFAULT_MAX_IN_FLIGHT_BYTES, max_fps: None }
+    }
+}
+
+/// Why a capture has to wait.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CaptureWait {
+    /// Send queue is full; wait for the writer to drain it
+    Backpressure,
+    /// FPS cap; next capture allowed at this instant
+    Until(Instant),
+}
+
+#[derive(Default)]
+struct CaptureTiming {
+    last_capture: Option<Instant>,
+    fps: f64,
+}
+
+/// Paces host captures against the QUIC send stream.
+///
+/// The host loop calls `wait_for_capture` before each capture and `queued`
+/// with the sealed size; the stream writer calls `written` once QUIC has
+/// accepted the bytes. While the queue holds `max_in_flight_bytes` or more
+/// the capture is skipped instead of buffered, so a slow link lowers the
+/// frame rate rather than growing memory.
+pub struct FrameRateController {
+    config: FrameRateConfig,
+    in_flight: AtomicUsize,
+    skipped: AtomicU64,
+    drained: tokio::sync::Notify,
+    timing: Mutex<CaptureTiming>,
+}
+
+impl FrameRateController {
+    pub fn new(config: FrameRateConfig) -> Self {
+        Self {
+            config,
+            in_flight: AtomicUsize::new(0),
+            skipped: AtomicU64::new(0),
+            drained: tokio::sync::Notify::new(),
+            timing: Mutex::new(CaptureTiming::default()),
+        }
+    }
+
+    /// Wait until a capture is allowed, then claim the capture slot.
+    pub async fn wait_for_capture(&self) {
+        loop {
+            match self.try_capture(Instant::now()) {
+                Ok(()) => return,
+                Err(CaptureWait::Backpressure) => self.drained.notified().await,
+                Err(CaptureWait::Until(at)) => tokio::time::sleep_until(at.into()).await,
+            }
+        }
+    }
+
+    fn try_capture(&self, now: Instant) -> Result<(), CaptureWait> {
+        let in_flight = self.in_flight.load(Ordering::Acquire);
+        if in_flight > 0 && in_flight >= self.config.max_in_flight_bytes {
+            self.skipped.fetch_add(1, Ordering::Relaxed);
+            return Err(CaptureWait::Backpressure);
+        }
+
+        let mut timing = self.timing.lock().unwrap_or_else(|e| e.into_inner());
+        if let (Some(last), Some(max_fps)) = (timing.last_capture, self.config.max_fps) {
+            let next = last + Duration::from_secs_f64(1.0 / max_fps.max(f64::MIN_POSITIVE));
+            if now < next {
+                return Err(CaptureWait::Until(next));
+            }
+        }
+        if let Some(last) = timing.last_capture {
+            let secs = now.duration_since(last).as_secs_f64();
+            if secs > 0.0 {
+                let fps = 1.0 / secs;
+                timing.fps = if timing.fps == 0.0 { fps } else { timing.fps + FPS_SMOOTHING * (fps - timing.fps) };
+            }
+        }
+        timing.last_capture = Some(now);
+        Ok(())
+    }
+
+    /// A sealed frame of `bytes` was queued for the stream.
+    pub fn queued(&self, bytes: usize) {
+        self.in_flight.fetch_add(bytes, Ordering::AcqRel);
+    }
+
+    /// The stream accepted a queued frame of `bytes`.
+    pub fn written(&self, bytes: usize) {
+        let _ = self
+            .in_flight
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |v| Some(v.saturating_sub(bytes)));
+        self.drained.notify_one();
+    }
+
+    /// Sealed bytes queued but not yet accepted by the stream.
+    pub fn in_flight_bytes(&self) -> usize {
+        self.in_flight.load(Ordering::Acquire)
+    }
+
+    /// Captures skipped because the queue was full.
+    pub fn skipped_captures(&self) -> u64 {
+        self.skipped.load(Ordering::Relaxed)
+    }
+
+    /// Smoothed rate at which captures are actually being allowed.
+    pub fn effective_fps(&self) -> f64 {
+        self.timing.lock().unwrap_or_else(|e| e.into_inner()).fps
+    }
+}
+
 /// AAD is just channel id for now; you can extend later (session_id, counter, etc).
 fn aad_for_channel(ch: ChannelV1) -> [u8; 1] {
     [ch as u8]
 }
 
+/// Version byte leading every Frames-stream blob. Version 1 blobs (plain
+/// `nonce || ciphertext` under the session key) carried no header.
+pub const FRAME_BLOB_VERSION: u8 = 2;
+
+/// Length of the `version || epoch` header on Frames-stream blobs.
+const FRAME_HEADER_LEN: usize = 5;
+
+/// Frames AAD: channel id, blob version and the ratchet epoch the blob was
+/// sealed under.
+fn frame_aad(epoch: u32) -> [u8; 6] {
+    let e = epoch.to_be_bytes();
+    [ChannelV1::Frames as u8, FRAME_BLOB_VERSION, e[0], e[1], e[2], e[3]]
+}
+
+/// Take the frame ratchet seed out of `crypto`, erasing it there.
+fn frame_ratchet(crypto: &SessionCryptoV1) -> anyhow::Result<KeyRatchet> {
+    let seed = crypto
+        .take_frame_ratchet_seed()
+        .ok_or_else(|| anyhow::anyhow!("session crypto has no frame ratchet seed (already used?)"))?;
+    Ok(KeyRatchet::new(*seed))
+}
+
+/// Host: seals Frames-stream packets under a ratcheting key so a leaked
+/// frame key only exposes frames of its own epoch.
+///
+/// Each blob is `version(u8) || epoch(u32 BE) || nonce(12) || ciphertext+tag`.
+/// The ratchet is seeded from `SessionCryptoV1::take_frame_ratchet_seed`,
+/// which leaves the seed erased, and steps according to the `RatchetPolicy`.
+pub struct FrameSealer {
+    ratchet: KeyRatchet,
+    crypto: SessionCryptoV1,
+    schedule: RatchetSchedule,
+}
+
+impl FrameSealer {
+    pub fn new(crypto: &SessionCryptoV1, policy: RatchetPolicy) -> anyhow::Result<Self> {
+        let ratchet = frame_ratchet(crypto)?;
+        let crypto = session_crypto_from_key_v1(ratchet.frame_key());
+        Ok(Self { ratchet, crypto, schedule: RatchetSchedule::new(policy) })
+    }
+
+    /// Epoch the next frame will be sealed under, unless the policy steps first.
+    pub fn epoch(&self) -> u32 {
+        self.ratchet.epoch()
+    }
+
+    pub fn seal(&mut self, plaintext: &[u8]) -> anyhow::Result<Vec<u8>> {
+        if self.schedule.next_frame() {
+            self.ratchet.step();
+            self.crypto = session_crypto_from_key_v1(self.ratchet.frame_key());
+        }
+        let epoch = self.ratchet.epoch();
+        let sealed = seal_v1(&self.crypto, plaintext, &frame_aad(epoch))
+            .map_err(|e| anyhow::anyhow!("seal failed: {:?}", e))?;
+        let mut out = Vec::with_capacity(FRAME_HEADER_LEN + sealed.len());
+        out.push(FRAME_BLOB_VERSION);
+        out.extend_from_slice(&epoch.to_be_bytes());
+        out.extend_from_slice(&sealed);
+        Ok(out)
+    }
+}
+
+/// Controller: opens blobs from a `FrameSealer`.
+///
+/// Follows the sender's epoch tag rather than a clock: after lost frames it
+/// steps forward to the tagged epoch (bounded by `MAX_RATCHET_SKIP`), but
+/// only commits the step once a frame under the new key authenticates, so a
+/// forged tag cannot desynchronize it. Earlier epochs are never reopened.
+pub struct FrameOpener {
+    ratchet: KeyRatchet,
+    crypto: SessionCryptoV1,
+}
+
+impl FrameOpener {
+    pub fn new(crypto: &SessionCryptoV1) -> anyhow::Result<Self> {
+        let ratchet = frame_ratchet(crypto)?;
+        let crypto = session_crypto_from_key_v1(ratchet.frame_key());
+        Ok(Self { ratchet, crypto })
+    }
+
+    /// Epoch of the last frame opened.
+    pub fn epoch(&self) -> u32 {
+        self.ratchet.epoch()
+    }
+
+    pub fn open(&mut self, blob: &[u8]) -> Option<Vec<u8>> {
+        if blob.len() < FRAME_HEADER_LEN || blob[0] != FRAME_BLOB_VERSION {
+            return None;
+        }
+        let (header, sealed) = blob.split_at(FRAME_HEADER_LEN);
+        let epoch = u32::from_be_bytes(header[1..].try_into().ok()?);
+        if epoch == self.ratchet.epoch() {
+            return open_v1(&self.crypto, sealed, &frame_aad(epoch));
+        }
+
+        let mut ratchet = self.ratchet.clone();
+        ratchet.advance_to(epoch).ok()?;
+        let crypto = session_crypto_from_key_v1(ratchet.frame_key());
+        let pt = open_v1(&crypto, sealed, &frame_aad(epoch))?;
+        self.ratchet = ratchet;
+        self.crypto = crypto;
+        Some(pt)
+    }
+}
+
 /// Control channel handle (post-handshake, encrypted ControlMsgV1).
 pub struct ControlChannelV1 {
     pub crypto: SessionCryptoV1,
+    /// Host: codec negotiated for the Frames stream. Controller: Raw (the
+    /// decoder handles whatever the host picked).
+    pub frame_codec: FrameCodecV1,
+    /// Host: whether the controller accepts damage-region packets.
+    pub damage_frames: bool,
+    /// Host: quality level requested by the controller; pass to the frame
+    /// sender and feed session-control messages to `apply_session_control`.
+    pub quality: FrameQuality,
     send: quinn::SendStream,
     recv: quinn::RecvStream,
+    /// Received bytes of a frame `recv_msg` has not finished reading
+    recv_buf: Vec<u8>,
+    closed: tokio::sync::watch::Sender<Option<zrc_proto::v1::SessionCloseV1>>,
+}
+
+/// Resolves when either side of a control channel sends `SessionCloseV1`.
+///
+/// Host: select the frame stream against `closed()` to stop capturing as
+/// soon as the controller disconnects, rather than on the next failed send.
+#[derive(Clone)]
+pub struct SessionCloseSignal(tokio::sync::watch::Receiver<Option<zrc_proto::v1::SessionCloseV1>>);
+
+impl SessionCloseSignal {
+    /// Wait for the close message. Pends forever if the channel is dropped
+    /// without one (the transport error surfaces elsewhere).
+    pub async fn closed(&mut self) -> zrc_proto::v1::SessionCloseV1 {
+        // Clone out of the watch borrow so no guard is held across the pend
+        let close = self.0.wait_for(Option::is_some).await.map(|close| close.clone());
+        match close {
+            Ok(close) => close.unwrap_or_default(),
+            Err(_) => std::future::pending().await,
+        }
+    }
+
+    /// The close message, if one has been sent or received.
+    pub fn reason(&self) -> Option<zrc_proto::v1::SessionCloseV1> {
+        self.0.borrow().clone()
+    }
 }
 
 impl ControlChannelV1 {
@@ -99,17 +1074,99 @@ impl ControlChannelV1 {
         write_frame(&mut self.send, &sealed).await.map_err(|e| anyhow::anyhow!("{e}"))
     }
 
+    /// Next message from the peer; `Ok(None)` once the peer closed.
+    ///
+    /// Cancel-safe: bytes of a partly received frame are kept for the next
+    /// call, so hosts can `select!` this against messages of their own.
     pub async fn recv_msg(&mut self) -> anyhow::Result<Option<zrc_proto::v1::ControlMsgV1>> {
-        let sealed = match read_frame(&mut self.recv).await {
-            Ok(Some(b)) => b,
-            Ok(None) => return Ok(None),
-            Err(e) => return Err(anyhow::anyhow!("{e}")),
+        let sealed = loop {
+            if let Some(frame) = take_buffered_frame(&mut self.recv_buf)? {
+                break frame;
+            }
+            match self.recv.read_chunk(usize::MAX, true).await {
+                Ok(Some(chunk)) => self.recv_buf.extend_from_slice(&chunk.bytes),
+                Ok(None) => return Ok(None),
+                Err(e) => return Err(anyhow::anyhow!("{e}")),
+            }
         };
         let pt = open_v1(&self.crypto, &sealed, &aad_for_channel(ChannelV1::Control))
             .ok_or_else(|| anyhow::anyhow!("control decrypt failed"))?;
         let msg = zrc_proto::v1::ControlMsgV1::decode(pt.as_slice())?;
+        if let Some(zrc_proto::v1::control_msg_v1::Payload::SessionClose(close)) = msg.payload {
+            self.closed.send_replace(Some(close));
+            return Ok(None);
+        }
         Ok(Some(msg))
     }
+
+    /// Tell the peer the session is ending on purpose, then finish the
+    /// control stream. Call before closing the connection.
+    pub async fn send_close(
+        &mut self,
+        reason: zrc_proto::v1::session_close_v1::ReasonV1,
+        detail: &str,
+    ) -> anyhow::Result<()> {
+        let msg = zrc_proto::v1::ControlMsgV1::session_close(0, reason, detail);
+        self.send_msg(&msg).await?;
+        let _ = self.send.finish();
+        if let Some(zrc_proto::v1::control_msg_v1::Payload::SessionClose(close)) = msg.payload {
+            self.closed.send_replace(Some(close));
+        }
+        Ok(())
+    }
+
+    /// The close message, once either side sent one. `recv_msg` returns
+    /// `Ok(None)` when it receives the peer's.
+    pub fn close_reason(&self) -> Option<zrc_proto::v1::SessionCloseV1> {
+        self.closed.borrow().clone()
+    }
+
+    /// Signal resolving when the session is closed.
+    pub fn close_signal(&self) -> SessionCloseSignal {
+        SessionCloseSignal(self.closed.subscribe())
+    }
+
+    /// Host: apply a session-control message received on this channel and
+    /// acknowledge it. Returns false for actions left to the caller.
+    pub async fn apply_session_control(&mut self, msg: &zrc_proto::v1::SessionControlV1) -> anyhow::Result<bool> {
+        let Some(ack) = self.quality.handle_session_control(msg) else {
+            return Ok(false);
+        };
+        let ack = zrc_proto::v1::ControlMsgV1::new(0, zrc_proto::v1::control_msg_v1::Payload::SessionControl(ack));
+        self.send_msg(&ack).await?;
+        Ok(true)
+    }
+
+    /// Re-derive the channel's session crypto from a renewed ticket.
+    ///
+    /// Only needed when renewal changed the ticket's binding or id (see
+    /// `SessionController::handle_ticket_renewal`); the host must send its
+    /// renewal response before re-keying so the controller can read it.
+    pub fn rekey_for_ticket(&mut self, ticket: &zrc_proto::v1::SessionTicketV1) -> anyhow::Result<()> {
+        if ticket.ticket_id.is_empty() {
+            return Err(anyhow::anyhow!("missing ticket_id"));
+        }
+        self.crypto = zrc_crypto::session_crypto::derive_session_crypto_v1(&ticket.session_binding, &ticket.ticket_id);
+        Ok(())
+    }
+}
+
+/// Split a complete length-prefixed frame (as written by `write_frame`) off
+/// the front of `buf`.
+fn take_buffered_frame(buf: &mut Vec<u8>) -> anyhow::Result<Option<Vec<u8>>> {
+    let Some(len_bytes) = buf.first_chunk::<4>() else {
+        return Ok(None);
+    };
+    let len = u32::from_be_bytes(*len_bytes) as usize;
+    if len > 16 * 1024 * 1024 {
+        return Err(anyhow::anyhow!("frame too large"));
+    }
+    if buf.len() < 4 + len {
+        return Ok(None);
+    }
+    let frame = buf[4..4 + len].to_vec();
+    buf.drain(..4 + len);
+    Ok(Some(frame))
 }
 
 /// Controller: open Control bi-stream, send plaintext ControlTicketV1, then upgrade to E2EE.
@@ -117,10 +1174,16 @@ pub async fn controller_control_handshake(
     conn: &quinn::Connection,
     ticket_packet: &zrc_proto::v1::ControlTicketV1,
 ) -> anyhow::Result<ControlChannelV1> {
-    let (mut send, mut recv) = conn.open_bi().await?;
+    let (mut send, recv) = conn.open_bi().await?;
     send_hello(&mut send, ChannelV1::Control).await?;
 
-    // 1) Send ticket packet plaintext (still protected by QUIC TLS pinning)
+    // 1) Send ticket packet plaintext (still protected by QUIC TLS pinning),
+    //    advertising the frame codecs we can decode
+    let mut ticket_packet = ticket_packet.clone();
+    if ticket_packet.frame_codecs.is_empty() {
+        ticket_packet.frame_codecs = supported_frame_codecs().into_iter().map(|c| c as u32).collect();
+    }
+    ticket_packet.supports_damage_frames = true;
     let mut tp = Vec::with_capacity(ticket_packet.encoded_len());
     ticket_packet.encode(&mut tp)?;
     write_frame(&mut send, &tp).await.map_err(|e| anyhow::anyhow!("{e}"))?;
@@ -133,7 +1196,16 @@ pub async fn controller_control_handshake(
     }
     let crypto = zrc_crypto::session_crypto::derive_session_crypto_v1(&t.session_binding, tid);
 
-    Ok(ControlChannelV1 { crypto, send, recv })
+    Ok(ControlChannelV1 {
+        crypto,
+        frame_codec: FrameCodecV1::Raw,
+        damage_frames: false,
+        quality: FrameQuality::default(),
+        send,
+        recv,
+        recv_buf: Vec::new(),
+        closed: tokio::sync::watch::channel(None).0,
+    })
 }
 
 /// Host: accept Control stream, read plaintext ControlTicketV1, verify ticket/binding, upgrade to E2EE.
@@ -174,7 +1246,15 @@ pub async fn host_accept_control_handshake(
         }
         let crypto = zrc_crypto::session_crypto::derive_session_crypto_v1(&t.session_binding, tid);
 
-        let cc = ControlChannelV1 { crypto, send, recv };
+        // 4) Pick a frame codec; old controllers offer none and get raw frames
+        let frame_codec = negotiate_frame_codec(&ticket_packet.frame_codecs);
+        let damage_frames = ticket_packet.supports_damage_frames;
+
+        let quality = FrameQuality::default();
+        let closed = tokio::sync::watch::channel(None).0;
+        let cc = ControlChannelV1 {
+            crypto, frame_codec, damage_frames, quality, send, recv, recv_buf: Vec::new(), closed,
+        };
         return Ok((ticket_packet, cc));
     }
 }
@@ -183,44 +1263,586 @@ pub async fn host_accept_control_handshake(
 pub async fn host_stream_frames(
     conn: &quinn::Connection,
     crypto: &SessionCryptoV1,
+    next_frame: impl FnMut() -> anyhow::Result<FramePacketV1> + Send + 'static,
+) -> anyhow::Result<()> {
+    host_stream_frames_with_codec(conn, crypto, FrameCodecV1::Raw, next_frame).await
+}
+
+/// Host: like `host_stream_frames`, encoding frames with the codec
+/// negotiated in the control handshake (`ControlChannelV1::frame_codec`).
+pub async fn host_stream_frames_with_codec(
+    conn: &quinn::Connection,
+    crypto: &SessionCryptoV1,
+    codec: FrameCodecV1,
+    next_frame: impl FnMut() -> anyhow::Result<FramePacketV1> + Send + 'static,
+) -> anyhow::Result<()> {
+    let stats = SessionStatsHandle::default();
+    let quality = FrameQuality::default();
+    send_frames(conn, crypto, codec, stats, FrameRateConfig::default(), quality, next_frame).await
+}
+
+/// Host: like `host_stream_frames_with_codec`, pacing captures with `rate`,
+/// encoding at the current `quality` (`ControlChannelV1::quality`), and
+/// returning a handle to the stream's `SessionStats`. Drive the returned
+/// future to stream.
+pub fn host_stream_frames_with_stats<'a>(
+    conn: &'a quinn::Connection,
+    crypto: &'a SessionCryptoV1,
+    codec: FrameCodecV1,
+    rate: FrameRateConfig,
+    quality: FrameQuality,
+    next_frame: impl FnMut() -> anyhow::Result<FramePacketV1> + Send + 'static,
+) -> (SessionStatsHandle, impl Future<Output = anyhow::Result<()>> + Send + 'a) {
+    let stats = SessionStatsHandle::default();
+    let stream = send_frames(conn, crypto, codec, Arc::clone(&stats), rate, quality, next_frame);
+    (stats, stream)
+}
+
+/// Capture/encode/seal and stream writing run as two halves joined on one
+/// task, so `FrameRateController` can see how far the writer is behind.
+async fn send_frames(
+    conn: &quinn::Connection,
+    crypto: &SessionCryptoV1,
+    codec: FrameCodecV1,
+    stats: SessionStatsHandle,
+    rate: FrameRateConfig,
+    quality: FrameQuality,
     mut next_frame: impl FnMut() -> anyhow::Result<FramePacketV1> + Send + 'static,
 ) -> anyhow::Result<()> {
     let mut send = conn.open_uni().await?;
     send_hello(&mut send, ChannelV1::Frames).await?;
+    let mut encoder = FrameEncoder::new(codec);
+    let mut recorder = StatsRecorder::new(stats);
+    let pacing = FrameRateController::new(rate);
+    let mut sealer = FrameSealer::new(crypto, RatchetPolicy::default())?;
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<Vec<u8>>();
 
-    loop {
-        let pkt = next_frame()?;
-        let raw = encode_frame_packet(&pkt);
-        let sealed = seal_v1(crypto, &raw, &aad_for_channel(ChannelV1::Frames))
-            .map_err(|e| anyhow::anyhow!("seal failed: {:?}", e))?;
-        write_frame(&mut send, &sealed).await.map_err(|e| anyhow::anyhow!("{e}"))?;
-    }
+    let capture = async {
+        loop {
+            pacing.wait_for_capture().await;
+            encoder.set_quality(quality.params());
+            let raw = encoder.encode(&next_frame()?)?;
+            let sealed = sealer.seal(&raw)?;
+            pacing.queued(sealed.len());
+            if tx.send(sealed).is_err() {
+                break;
+            }
+        }
+        anyhow::Ok(())
+    };
+
+    let write = async {
+        while let Some(sealed) = rx.recv().await {
+            write_frame(&mut send, &sealed).await.map_err(|e| anyhow::anyhow!("{e}"))?;
+            pacing.written(sealed.len());
+            recorder.record_with(conn, sealed.len(), |stats| {
+                stats.skipped_captures = pacing.skipped_captures();
+                stats.in_flight_bytes = pacing.in_flight_bytes();
+                stats.effective_fps = pacing.effective_fps();
+            });
+        }
+        anyhow::Ok(())
+    };
+
+    tokio::try_join!(capture, write)?;
+    Ok(())
+}
+
+/// Host: stream capture updates, sending damage-region packets when the
+/// controller supports them (`ControlChannelV1::damage_frames`) and full
+/// frames otherwise, on connect, and when the damage exceeds the threshold.
+pub async fn host_stream_frame_updates(
+    conn: &quinn::Connection,
+    crypto: &SessionCryptoV1,
+    codec: FrameCodecV1,
+    damage_frames: bool,
+    mut next_update: impl FnMut() -> anyhow::Result<FrameUpdateV1> + Send + 'static,
+) -> anyhow::Result<()> {
+    let mut damage = DamageEncoder::new(damage_frames);
+    let next_frame = move || damage.encode(next_update()?);
+    let stats = SessionStatsHandle::default();
+    let quality = FrameQuality::default();
+    send_frames(conn, crypto, codec, stats, FrameRateConfig::default(), quality, next_frame).await
 }
 
 /// Controller: accept uni streams, when Frames stream arrives, read/decrypt packets and call callback.
 pub async fn controller_recv_frames(
     conn: &quinn::Connection,
     crypto: &SessionCryptoV1,
+    on_frame: impl FnMut(FramePacketV1) + Send + 'static,
+) -> anyhow::Result<()> {
+    recv_frames(conn, crypto, SessionStatsHandle::default(), on_frame).await
+}
+
+/// Controller: like `controller_recv_frames`, also returning a handle to
+/// the stream's `SessionStats`. Drive the returned future to receive.
+pub fn controller_recv_frames_with_stats<'a>(
+    conn: &'a quinn::Connection,
+    crypto: &'a SessionCryptoV1,
+    on_frame: impl FnMut(FramePacketV1) + Send + 'static,
+) -> (SessionStatsHandle, impl Future<Output = anyhow::Result<()>> + Send + 'a) {
+    let stats = SessionStatsHandle::default();
+    let stream = recv_frames(conn, crypto, Arc::clone(&stats), on_frame);
+    (stats, stream)
+}
+
+async fn recv_frames(
+    conn: &quinn::Connection,
+    crypto: &SessionCryptoV1,
+    stats: SessionStatsHandle,
     mut on_frame: impl FnMut(FramePacketV1) + Send + 'static,
 ) -> anyhow::Result<()> {
+    let mut recorder = StatsRecorder::new(stats);
     loop {
         let mut recv = conn.accept_uni().await?;
         let ch = recv_hello(&mut recv).await?;
         if ch != ChannelV1::Frames {
             continue;
         }
+        let mut decoder = FrameDecoder::new();
+        let mut damage = DamageDecoder::new();
+        let mut opener = FrameOpener::new(crypto)?;
         loop {
             let sealed = match read_frame(&mut recv).await {
                 Ok(Some(b)) => b,
                 Ok(None) => break,
                 Err(e) => return Err(anyhow::anyhow!("{e}")),
             };
-            let pt = open_v1(crypto, &sealed, &aad_for_channel(ChannelV1::Frames))
+            let pt = opener
+                .open(&sealed)
                 .ok_or_else(|| anyhow::anyhow!("frame decrypt failed"))?;
-            if let Some(pkt) = decode_frame_packet(&pt) {
+            recorder.record(conn, sealed.len());
+            if let Some(pkt) = decoder.decode(&pt).and_then(|pkt| damage.apply(pkt)) {
                 on_frame(pkt);
             }
         }
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A BGRA frame of noise, so compression cannot hide delta savings.
+    fn frame(width: u32, height: u32, seed: u8) -> FramePacketV1 {
+        let stride = width * 4;
+        let pixels = (0..stride * height)
+            .map(|i| (i.wrapping_mul(2_654_435_761) >> 13) as u8 ^ seed)
+            .collect();
+        FramePacketV1 { width, height, stride, format: 1, pixels }
+    }
+
+    #[test]
+    fn test_buffered_frames_survive_partial_reads() {
+        let mut buf = Vec::new();
+        for body in [&b"first"[..], b"second"] {
+            buf.extend_from_slice(&(body.len() as u32).to_be_bytes());
+            buf.extend_from_slice(body);
+        }
+        let tail = buf.split_off(7);
+
+        // A cancelled read leaves a partial frame behind; nothing is lost
+        assert_eq!(take_buffered_frame(&mut buf).unwrap(), None);
+        buf.extend_from_slice(&tail);
+        assert_eq!(take_buffered_frame(&mut buf).unwrap().as_deref(), Some(&b"first"[..]));
+        assert_eq!(take_buffered_frame(&mut buf).unwrap().as_deref(), Some(&b"second"[..]));
+        assert!(buf.is_empty());
+
+        buf.extend_from_slice(&u32::MAX.to_be_bytes());
+        assert!(take_buffered_frame(&mut buf).is_err());
+    }
+
+    fn assert_same(a: &FramePacketV1, b: &FramePacketV1) {
+        assert_eq!((a.width, a.height, a.stride, a.format), (b.width, b.height, b.stride, b.format));
+        assert_eq!(a.pixels, b.pixels);
+    }
+
+    fn round_trip(codec: FrameCodecV1) {
+        let mut enc = FrameEncoder::new(codec);
+        let mut dec = FrameDecoder::new();
+        for seed in 0..3 {
+            let pkt = frame(64, 32, seed);
+            let wire = enc.encode(&pkt).unwrap();
+            assert_same(&dec.decode(&wire).unwrap(), &pkt);
+        }
+    }
+
+    #[test]
+    fn raw_codec_matches_legacy_encoding() {
+        let pkt = frame(16, 8, 0);
+        let wire = FrameEncoder::new(FrameCodecV1::Raw).encode(&pkt).unwrap();
+        assert_eq!(wire, encode_frame_packet(&pkt));
+        round_trip(FrameCodecV1::Raw);
+    }
+
+    #[test]
+    fn zlib_round_trip() {
+        round_trip(FrameCodecV1::Zlib);
+    }
+
+    #[cfg(feature = "frame-zstd")]
+    #[test]
+    fn zstd_round_trip() {
+        round_trip(FrameCodecV1::Zstd);
+    }
+
+    #[test]
+    fn delta_round_trip() {
+        round_trip(FrameCodecV1::Delta);
+    }
+
+    #[test]
+    fn delta_sends_only_dirty_rectangle() {
+        let mut enc = FrameEncoder::new(FrameCodecV1::Delta);
+        let mut dec = FrameDecoder::new();
+
+        let base = frame(256, 64, 0);
+        let key = enc.encode(&base).unwrap();
+        assert_same(&dec.decode(&key).unwrap(), &base);
+
+        // Change a 10x5 pixel block inside one tile
+        let mut next = base.clone();
+        for row in 20..25 {
+            let start = row * next.stride as usize + 100 * 4;
+            next.pixels[start..start + 40].fill(0xEE);
+        }
+        assert_eq!(
+            dirty_rects(&base, &next).unwrap(),
+            vec![DirtyRect { x: 256, y: 16, w: 256, h: 16 }]
+        );
+
+        let delta = enc.encode(&next).unwrap();
+        assert!(delta.len() < key.len());
+        assert_same(&dec.decode(&delta).unwrap(), &next);
+
+        // Unchanged frame: empty delta
+        let same = enc.encode(&next).unwrap();
+        assert_same(&dec.decode(&same).unwrap(), &next);
+    }
+
+    #[test]
+    fn delta_without_base_is_rejected() {
+        let mut enc = FrameEncoder::new(FrameCodecV1::Delta);
+        let base = frame(256, 64, 0);
+        enc.encode(&base).unwrap();
+        let mut next = base.clone();
+        next.pixels[0] ^= 0xFF;
+        let delta = enc.encode(&next).unwrap();
+
+        assert!(FrameDecoder::new().decode(&delta).is_none());
+    }
+
+    #[test]
+    fn delta_keyframe_on_resize() {
+        let mut enc = FrameEncoder::new(FrameCodecV1::Delta);
+        enc.encode(&frame(64, 16, 0)).unwrap();
+
+        // A fresh decoder can decode it, so it must be a keyframe
+        let resized = frame(32, 16, 1);
+        let wire = enc.encode(&resized).unwrap();
+        assert_same(&FrameDecoder::new().decode(&wire).unwrap(), &resized);
+    }
+
+    /// Overwrite a block of `frame` and return it as a dirty rect.
+    fn paint(frame: &mut FramePacketV1, x: u32, y: u32, w: u32, h: u32, value: u8) -> DirtyRectV1 {
+        let pixels = vec![value; (w * h * 4) as usize];
+        let rect = DirtyRectV1 { x, y, width: w, height: h, pixels };
+        apply_dirty_rects(frame, std::slice::from_ref(&rect)).unwrap();
+        rect
+    }
+
+    /// Stream `updates` through host and controller state with `codec`.
+    fn stream(updates: Vec<FrameUpdateV1>, damage_frames: bool, codec: FrameCodecV1) -> (Vec<FramePacketV1>, Vec<u8>) {
+        let mut host = DamageEncoder::new(damage_frames);
+        let mut enc = FrameEncoder::new(codec);
+        let mut dec = FrameDecoder::new();
+        let mut ctrl = DamageDecoder::new();
+        let mut flags = Vec::new();
+        let out = updates
+            .into_iter()
+            .map(|u| {
+                let pkt = host.encode(u).unwrap();
+                flags.push(pkt.format & DAMAGE_FORMAT_FLAG);
+                let wire = enc.encode(&pkt).unwrap();
+                ctrl.apply(dec.decode(&wire).unwrap()).unwrap()
+            })
+            .collect();
+        (out, flags)
+    }
+
+    #[test]
+    fn damage_reconstruction_matches_full_frames() {
+        let mut current = frame(128, 64, 0);
+        let mut full = vec![FrameUpdateV1::Full(current.clone())];
+        let mut damage = vec![FrameUpdateV1::Full(current.clone())];
+        for (i, (x, y)) in [(0, 0), (100, 40), (60, 10), (127, 63)].into_iter().enumerate() {
+            let rect = paint(&mut current, x, y, 1.max(20.min(128 - x)), 1.max(10.min(64 - y)), i as u8 + 1);
+            full.push(FrameUpdateV1::Full(current.clone()));
+            damage.push(FrameUpdateV1::Damage(vec![rect]));
+        }
+
+        for codec in [FrameCodecV1::Raw, FrameCodecV1::Zlib, FrameCodecV1::Delta] {
+            let (expected, _) = stream(full.clone(), false, codec);
+            let (actual, flags) = stream(damage.clone(), true, codec);
+            assert_eq!(flags, vec![0, DAMAGE_FORMAT_FLAG, DAMAGE_FORMAT_FLAG, DAMAGE_FORMAT_FLAG, DAMAGE_FORMAT_FLAG]);
+            for (a, b) in actual.iter().zip(&expected) {
+                assert_same(a, b);
+            }
+            assert_same(actual.last().unwrap(), &current);
+        }
+    }
+
+    #[test]
+    fn damage_falls_back_to_full_frame_over_threshold() {
+        let mut current = frame(64, 64, 0);
+        let small = paint(&mut current.clone(), 0, 0, 8, 8, 1);
+        let large = paint(&mut current, 0, 0, 64, 40, 2);
+
+        let (_, flags) = stream(
+            vec![
+                FrameUpdateV1::Full(frame(64, 64, 0)),
+                FrameUpdateV1::Damage(vec![small]),
+                FrameUpdateV1::Damage(vec![large]),
+            ],
+            true,
+            FrameCodecV1::Raw,
+        );
+        assert_eq!(flags, vec![0, DAMAGE_FORMAT_FLAG, 0]);
+    }
+
+    #[test]
+    fn damage_requires_initial_full_frame() {
+        let mut host = DamageEncoder::new(true);
+        let rect = DirtyRectV1 { x: 0, y: 0, width: 1, height: 1, pixels: vec![0; 4] };
+        assert!(host.encode(FrameUpdateV1::Damage(vec![rect.clone()])).is_err());
+
+        // Controller ignores damage before a keyframe too
+        let pkt = encode_damage_packet(&frame(4, 4, 0), &[rect]);
+        assert!(DamageDecoder::new().apply(pkt).is_none());
+    }
+
+    #[test]
+    fn damage_disabled_sends_full_frames() {
+        let mut current = frame(32, 32, 0);
+        let rect = paint(&mut current, 1, 1, 2, 2, 9);
+        let (out, flags) = stream(
+            vec![FrameUpdateV1::Full(frame(32, 32, 0)), FrameUpdateV1::Damage(vec![rect])],
+            false,
+            FrameCodecV1::Raw,
+        );
+        assert_eq!(flags, vec![0, 0]);
+        assert_same(&out[1], &current);
+    }
+
+    #[test]
+    fn out_of_bounds_rect_rejected() {
+        let mut base = frame(16, 16, 0);
+        let rect = DirtyRectV1 { x: 10, y: 0, width: 8, height: 1, pixels: vec![0; 32] };
+        assert!(apply_dirty_rects(&mut base, &[rect]).is_err());
+    }
+
+    #[test]
+    fn session_stats_fps_from_interval() {
+        let mut stats = SessionStats::default();
+        assert_eq!(stats.fps(), None);
+        stats.frame_interval = Some(Duration::from_millis(20));
+        assert!((stats.fps().unwrap() - 50.0).abs() < 1e-9);
+        stats.frame_interval = Some(Duration::ZERO);
+        assert_eq!(stats.fps(), None);
+    }
+
+    #[test]
+    fn frame_rate_caps_fps() {
+        let pacing = FrameRateController::new(FrameRateConfig { max_fps: Some(10.0), ..Default::default() });
+        let start = Instant::now();
+        assert_eq!(pacing.try_capture(start), Ok(()));
+        let next = start + Duration::from_millis(100);
+        assert_eq!(pacing.try_capture(start + Duration::from_millis(10)), Err(CaptureWait::Until(next)));
+        assert_eq!(pacing.try_capture(next), Ok(()));
+        assert!((pacing.effective_fps() - 10.0).abs() < 1e-6);
+        assert_eq!(pacing.skipped_captures(), 0);
+    }
+
+    #[test]
+    fn frame_rate_lets_oversized_frame_through() {
+        let pacing = FrameRateController::new(FrameRateConfig { max_in_flight_bytes: 100, max_fps: None });
+        assert_eq!(pacing.try_capture(Instant::now()), Ok(()));
+        pacing.queued(1_000);
+        assert_eq!(pacing.try_capture(Instant::now()), Err(CaptureWait::Backpressure));
+        pacing.written(1_000);
+        assert_eq!(pacing.in_flight_bytes(), 0);
+        assert_eq!(pacing.try_capture(Instant::now()), Ok(()));
+    }
+
+    #[tokio::test]
+    async fn frame_rate_bounds_queue_for_slow_consumer() {
+        const FRAME: usize = 1_000;
+        const LIMIT: usize = 3 * FRAME;
+        let pacing = Arc::new(FrameRateController::new(FrameRateConfig {
+            max_in_flight_bytes: LIMIT,
+            max_fps: None,
+        }));
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<usize>();
+
+        // Consumer drains one frame every 5ms, far slower than the producer
+        let consumer_pacing = Arc::clone(&pacing);
+        let consumer = tokio::spawn(async move {
+            let mut consumed = 0;
+            while let Some(bytes) = rx.recv().await {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+                consumer_pacing.written(bytes);
+                consumed += 1;
+            }
+            consumed
+        });
+
+        let mut captured = 0u64;
+        let mut peak = 0;
+        let deadline = Instant::now() + Duration::from_millis(200);
+        while Instant::now() < deadline {
+            pacing.wait_for_capture().await;
+            captured += 1;
+            pacing.queued(FRAME);
+            peak = peak.max(pacing.in_flight_bytes());
+            tx.send(FRAME).unwrap();
+            tokio::task::yield_now().await;
+        }
+        drop(tx);
+        let consumed = consumer.await.unwrap();
+
+        assert!(peak <= LIMIT, "queue grew to {peak} bytes");
+        assert_eq!(captured, consumed);
+        assert!(pacing.skipped_captures() > 0);
+        assert!(captured < 200 / 5 + LIMIT as u64 / FRAME as u64 + 2, "captured {captured}");
+    }
+
+    #[test]
+    fn quality_levels_map_to_color_bits() {
+        assert_eq!(QualityParams::for_level(100).color_bits, 8);
+        assert_eq!(QualityParams::for_level(55).color_bits, 6);
+        assert_eq!(QualityParams::for_level(10).color_bits, 4);
+        assert_eq!(QualityParams::for_level(0).color_bits, 4);
+        assert_eq!(QualityParams::for_level(250).color_bits, 8);
+    }
+
+    #[test]
+    fn quality_change_adjusts_next_frame() {
+        use zrc_proto::v1::{SessionControlActionV1, SessionControlV1};
+
+        let quality = FrameQuality::default();
+        let mut encoder = FrameEncoder::new(FrameCodecV1::Raw);
+        let original = frame(16, 8, 0);
+
+        encoder.set_quality(quality.params());
+        let before = decode_frame_packet(&encoder.encode(&original).unwrap()).unwrap();
+        assert_same(&before, &original);
+
+        let ack = quality
+            .handle_session_control(&SessionControlV1 {
+                action: SessionControlActionV1::QualityChange as i32,
+                quality_level: 5,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(ack.action, SessionControlActionV1::QualityChange as i32);
+        assert_eq!(ack.quality_level, MIN_QUALITY_LEVEL);
+
+        encoder.set_quality(quality.params());
+        let after = decode_frame_packet(&encoder.encode(&original).unwrap()).unwrap();
+        assert_eq!((after.width, after.height, after.stride), (original.width, original.height, original.stride));
+        assert!(after.pixels.iter().all(|b| b & 0x0F == 0));
+        assert!(after.pixels.iter().zip(&original.pixels).all(|(a, o)| *a == o & 0xF0));
+
+        let pause = SessionControlV1 { action: SessionControlActionV1::Pause as i32, ..Default::default() };
+        assert!(quality.handle_session_control(&pause).is_none());
+        assert_eq!(quality.level(), MIN_QUALITY_LEVEL);
+    }
+
+    #[test]
+    fn negotiation_falls_back_to_raw() {
+        assert_eq!(negotiate_frame_codec(&[]), FrameCodecV1::Raw);
+        assert_eq!(negotiate_frame_codec(&[0, 1]), FrameCodecV1::Zlib);
+        assert_eq!(negotiate_frame_codec(&[0, 1, 3]), FrameCodecV1::Delta);
+        assert_eq!(negotiate_frame_codec(&[99]), FrameCodecV1::Raw);
+    }
+
+    fn ratchet_pair(every_frames: u64) -> (FrameSealer, FrameOpener) {
+        // Each side derives its own context from the shared binding
+        let derive = || zrc_crypto::session_crypto::derive_session_crypto_v1(&[0x42; 32], &[0xAB; 16]);
+        let policy = RatchetPolicy { every_frames, every: Duration::from_secs(3600) };
+        (FrameSealer::new(&derive(), policy).unwrap(), FrameOpener::new(&derive()).unwrap())
+    }
+
+    #[test]
+    fn ratchet_seed_is_erased_once_used() {
+        let crypto = zrc_crypto::session_crypto::derive_session_crypto_v1(&[0x42; 32], &[0xAB; 16]);
+        let copy = crypto.clone();
+        FrameSealer::new(&crypto, RatchetPolicy::default()).unwrap();
+
+        // Neither the context nor its clones can seed another ratchet
+        assert!(crypto.take_frame_ratchet_seed().is_none());
+        assert!(FrameOpener::new(&copy).is_err());
+    }
+
+    #[test]
+    fn frame_blob_carries_version() {
+        let (mut sealer, mut opener) = ratchet_pair(1000);
+
+        let blob = sealer.seal(b"frame").unwrap();
+        assert_eq!(blob[0], FRAME_BLOB_VERSION);
+
+        let mut other_version = blob.clone();
+        other_version[0] = 1;
+        assert!(opener.open(&other_version).is_none());
+        assert_eq!(opener.open(&blob).unwrap(), b"frame");
+    }
+
+    #[test]
+    fn ratchet_old_epoch_frames_fail_after_step() {
+        let (mut sealer, mut opener) = ratchet_pair(2);
+
+        let first = sealer.seal(b"frame 0").unwrap();
+        let second = sealer.seal(b"frame 1").unwrap();
+        let third = sealer.seal(b"frame 2").unwrap();
+        assert_eq!(sealer.epoch(), 1);
+
+        assert_eq!(opener.open(&first).unwrap(), b"frame 0");
+        assert_eq!(opener.open(&second).unwrap(), b"frame 1");
+        assert_eq!(opener.open(&third).unwrap(), b"frame 2");
+        assert_eq!(opener.epoch(), 1);
+
+        // The epoch-0 key is gone once the opener has stepped
+        assert!(opener.open(&first).is_none());
+
+        // Relabelling an old frame with the current epoch does not help
+        let mut relabelled = second.clone();
+        relabelled[1..5].copy_from_slice(&1u32.to_be_bytes());
+        assert!(opener.open(&relabelled).is_none());
+    }
+
+    #[test]
+    fn ratchet_resyncs_after_lost_frames() {
+        let (mut sealer, mut opener) = ratchet_pair(1);
+
+        let sealed: Vec<_> = (0..5u8).map(|i| sealer.seal(&[i]).unwrap()).collect();
+        assert_eq!(opener.open(&sealed[0]).unwrap(), [0]);
+
+        // Frames 1-3 are lost; the opener skips ahead to epoch 4
+        assert_eq!(opener.open(&sealed[4]).unwrap(), [4]);
+        assert_eq!(opener.epoch(), 4);
+        assert!(opener.open(&sealed[2]).is_none());
+    }
+
+    #[test]
+    fn ratchet_ignores_forged_epoch() {
+        let (mut sealer, mut opener) = ratchet_pair(1000);
+
+        let mut forged = sealer.seal(b"frame").unwrap();
+        forged[1..5].copy_from_slice(&7u32.to_be_bytes());
+        assert!(opener.open(&forged).is_none());
+        assert_eq!(opener.epoch(), 0);
+
+        let genuine = sealer.seal(b"next").unwrap();
+        assert_eq!(opener.open(&genuine).unwrap(), b"next");
+    }
+}
@@ -55,7 +55,7 @@ pub enum RequestType {
 
 
 /// Tracking data for a single source.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 struct SourceTracker {
     /// Request timestamps within the current window.
     requests: Vec<Instant>,
@@ -65,15 +65,6 @@ struct SourceTracker {
     backoff_until: Option<Instant>,
 }
 
-impl Default for SourceTracker {
-    fn default() -> Self {
-        Self {
-            requests: Vec::new(),
-            violations: 0,
-            backoff_until: None,
-        }
-    }
-}
 
 /// Rate limiter for protecting against abuse.
 pub struct RateLimiter {
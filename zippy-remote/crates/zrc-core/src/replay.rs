@@ -0,0 +1,398 @@
+//! Envelope replay protection that survives restarts.
+//!
+//! `ReplayGuard` remembers the nonces of recently accepted envelopes and the
+//! newest timestamp it has seen (the high-water mark). An envelope is
+//! rejected if its nonce was seen before, or if its timestamp is more than
+//! the window older than the high-water mark. Anything older than that is
+//! rejected on age alone, so nonces only need to be kept for one window.
+//! Timestamps more than `max_future_skew` ahead of the local clock are
+//! refused, so one envelope cannot drag the high-water mark into the future
+//! and lock out everyone else.
+//!
+//! [`ReplayGuard::check`] only reads, so it can run before the envelope is
+//! authenticated; [`ReplayGuard::accept`] records the nonce and must only be
+//! called once the envelope has been verified.
+//!
+//! Nonces are stored as SHA-256 hashes in a ring buffer (eviction order)
+//! backed by a hash set (lookup). The state can be saved to and loaded from
+//! any `Store`, so a nonce accepted before a restart is still rejected after.
+//! Saves are batched: [`ReplayGuard::take_due`] hands out a snapshot every
+//! `persist_batch` accepted nonces, and [`ReplayGuard::take_unsaved`] flushes
+//! the rest on shutdown.
+
+use std::collections::{HashSet, VecDeque};
+use std::time::Duration;
+
+use thiserror::Error;
+use tracing::{debug, warn};
+
+use zrc_crypto::hash::sha256;
+use zrc_proto::v1::EnvelopeV1;
+
+use crate::store::{ReplayEntryRecord, ReplayStateRecord, Store, StoreError};
+
+/// Default replay window: envelopes older than this are rejected.
+pub const DEFAULT_REPLAY_WINDOW: Duration = Duration::from_secs(10 * 60);
+
+/// Default number of nonces remembered.
+pub const DEFAULT_REPLAY_CAPACITY: usize = 4096;
+
+/// Default tolerance for sender clocks running ahead of ours.
+pub const DEFAULT_MAX_FUTURE_SKEW: Duration = Duration::from_secs(5 * 60);
+
+/// Default number of accepted nonces between saves.
+pub const DEFAULT_PERSIST_BATCH: usize = 32;
+
+/// Replay guard configuration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayGuardConfig {
+    /// How far behind the high-water mark a timestamp may be
+    pub window: Duration,
+    /// Maximum nonces remembered. Once full the oldest nonce is forgotten,
+    /// so this should cover the expected message rate over one window.
+    pub capacity: usize,
+    /// How far ahead of the local clock a timestamp may be
+    pub max_future_skew: Duration,
+    /// Accepted nonces between snapshots from [`ReplayGuard::take_due`].
+    /// Nonces accepted since the last save are lost on a crash.
+    pub persist_batch: usize,
+}
+
+impl Default for ReplayGuardConfig {
+    fn default() -> Self {
+        Self {
+            window: DEFAULT_REPLAY_WINDOW,
+            capacity: DEFAULT_REPLAY_CAPACITY,
+            max_future_skew: DEFAULT_MAX_FUTURE_SKEW,
+            persist_batch: DEFAULT_PERSIST_BATCH,
+        }
+    }
+}
+
+/// Replay guard errors.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ReplayGuardError {
+    #[error("nonce already seen")]
+    Duplicate,
+    #[error("timestamp {timestamp} is outside the replay window (high-water {high_water})")]
+    TooOld { timestamp: u64, high_water: u64 },
+    #[error("timestamp {timestamp} is too far ahead of local time {now}")]
+    FromFuture { timestamp: u64, now: u64 },
+    #[error("envelope has no header")]
+    MissingHeader,
+    #[error("store error: {0}")]
+    Store(String),
+}
+
+impl From<StoreError> for ReplayGuardError {
+    fn from(e: StoreError) -> Self {
+        ReplayGuardError::Store(e.to_string())
+    }
+}
+
+/// Nonce and timestamp replay filter. See the module docs.
+#[derive(Debug)]
+pub struct ReplayGuard {
+    config: ReplayGuardConfig,
+    /// (nonce hash, timestamp), oldest insertion first
+    ring: VecDeque<([u8; 32], u64)>,
+    seen: HashSet<[u8; 32]>,
+    high_water: u64,
+    /// Nonces accepted since the last snapshot was handed out
+    unsaved: usize,
+}
+
+impl ReplayGuard {
+    /// Create an empty guard.
+    pub fn new(config: ReplayGuardConfig) -> Self {
+        Self {
+            ring: VecDeque::with_capacity(config.capacity.min(DEFAULT_REPLAY_CAPACITY)),
+            seen: HashSet::new(),
+            high_water: 0,
+            unsaved: 0,
+            config,
+        }
+    }
+
+    /// Newest timestamp accepted so far.
+    pub fn high_water(&self) -> u64 {
+        self.high_water
+    }
+
+    /// Number of nonces currently remembered.
+    pub fn len(&self) -> usize {
+        self.ring.len()
+    }
+
+    /// Whether no nonces are remembered.
+    pub fn is_empty(&self) -> bool {
+        self.ring.is_empty()
+    }
+
+    /// Check a nonce and its message timestamp (Unix seconds) against local
+    /// time `now`, without remembering anything.
+    pub fn check(&self, nonce: &[u8], timestamp: u64, now: u64) -> Result<(), ReplayGuardError> {
+        self.check_hash(&sha256(nonce), timestamp, now)
+    }
+
+    /// Check a nonce as [`check`](Self::check) does and remember it.
+    ///
+    /// Only call this for authenticated messages: an accepted timestamp can
+    /// raise the high-water mark.
+    pub fn accept(&mut self, nonce: &[u8], timestamp: u64, now: u64) -> Result<(), ReplayGuardError> {
+        let hash = sha256(nonce);
+        self.check_hash(&hash, timestamp, now)?;
+
+        if timestamp > self.high_water {
+            self.high_water = timestamp;
+            self.prune();
+        }
+        if self.ring.len() >= self.config.capacity {
+            if let Some((old, _)) = self.ring.pop_front() {
+                self.seen.remove(&old);
+                warn!("replay guard full, forgetting oldest nonce");
+            }
+        }
+        self.ring.push_back((hash, timestamp));
+        self.seen.insert(hash);
+        self.unsaved += 1;
+        Ok(())
+    }
+
+    /// Check the header nonce and timestamp of `envelope` without remembering
+    /// them.
+    pub fn check_envelope(&self, envelope: &EnvelopeV1, now: u64) -> Result<(), ReplayGuardError> {
+        let header = envelope.header.as_ref().ok_or(ReplayGuardError::MissingHeader)?;
+        self.check(&header.nonce, header.timestamp, now)
+    }
+
+    /// Remember the header nonce and timestamp of an authenticated `envelope`.
+    pub fn accept_envelope(&mut self, envelope: &EnvelopeV1, now: u64) -> Result<(), ReplayGuardError> {
+        let header = envelope.header.as_ref().ok_or(ReplayGuardError::MissingHeader)?;
+        self.accept(&header.nonce, header.timestamp, now)
+    }
+
+    fn check_hash(&self, hash: &[u8; 32], timestamp: u64, now: u64) -> Result<(), ReplayGuardError> {
+        if timestamp > now.saturating_add(self.config.max_future_skew.as_secs()) {
+            return Err(ReplayGuardError::FromFuture { timestamp, now });
+        }
+        if self.is_expired(timestamp) {
+            return Err(ReplayGuardError::TooOld { timestamp, high_water: self.high_water });
+        }
+        if self.seen.contains(hash) {
+            return Err(ReplayGuardError::Duplicate);
+        }
+        Ok(())
+    }
+
+    fn is_expired(&self, timestamp: u64) -> bool {
+        timestamp.saturating_add(self.config.window.as_secs()) < self.high_water
+    }
+
+    /// Forget nonces that have fallen out of the window.
+    fn prune(&mut self) {
+        let before = self.ring.len();
+        let cutoff = self.high_water.saturating_sub(self.config.window.as_secs());
+        self.ring.retain(|(hash, ts)| {
+            let keep = *ts >= cutoff;
+            if !keep {
+                self.seen.remove(hash);
+            }
+            keep
+        });
+        let pruned = before - self.ring.len();
+        if pruned > 0 {
+            debug!("pruned {} expired nonces", pruned);
+        }
+    }
+
+    /// Snapshot the guard for persistence.
+    pub fn to_record(&self) -> ReplayStateRecord {
+        ReplayStateRecord {
+            high_water: self.high_water,
+            entries: self
+                .ring
+                .iter()
+                .map(|(hash, ts)| ReplayEntryRecord { nonce_hash: hash.to_vec(), timestamp: *ts })
+                .collect(),
+        }
+    }
+
+    /// Whether `persist_batch` nonces were accepted since the last snapshot.
+    pub fn is_due(&self) -> bool {
+        self.unsaved >= self.config.persist_batch.max(1)
+    }
+
+    /// Snapshot the guard if it [`is_due`](Self::is_due). The caller saves
+    /// it with `Store::save_replay_state`.
+    pub fn take_due(&mut self) -> Option<ReplayStateRecord> {
+        if self.is_due() { self.take_unsaved() } else { None }
+    }
+
+    /// Snapshot the guard if anything was accepted since the last snapshot,
+    /// e.g. to flush a partial batch on shutdown.
+    pub fn take_unsaved(&mut self) -> Option<ReplayStateRecord> {
+        if self.unsaved == 0 {
+            return None;
+        }
+        self.unsaved = 0;
+        Some(self.to_record())
+    }
+
+    /// Rebuild a guard from persisted state, dropping expired and malformed
+    /// entries and keeping at most `config.capacity` of the newest.
+    pub fn from_record(record: ReplayStateRecord, config: ReplayGuardConfig) -> Self {
+        let mut guard = Self::new(config);
+        guard.high_water = record.high_water;
+        let skip = record.entries.len().saturating_sub(guard.config.capacity);
+        for entry in record.entries.into_iter().skip(skip) {
+            let Ok(hash) = <[u8; 32]>::try_from(entry.nonce_hash.as_slice()) else {
+                continue;
+            };
+            if !guard.is_expired(entry.timestamp) && guard.seen.insert(hash) {
+                guard.ring.push_back((hash, entry.timestamp));
+            }
+        }
+        guard.prune();
+        guard
+    }
+
+    /// Load the guard saved in `store`, or start empty if there is none.
+    pub async fn load(
+        store: &dyn Store,
+        config: ReplayGuardConfig,
+    ) -> Result<Self, ReplayGuardError> {
+        Ok(match store.load_replay_state().await? {
+            Some(record) => Self::from_record(record, config),
+            None => Self::new(config),
+        })
+    }
+
+    /// Save the guard to `store`.
+    pub async fn persist(&mut self, store: &dyn Store) -> Result<(), ReplayGuardError> {
+        store.save_replay_state(self.to_record()).await?;
+        self.unsaved = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::InMemoryStore;
+
+    const NOW: u64 = 10_000;
+
+    fn config(window_secs: u64, capacity: usize) -> ReplayGuardConfig {
+        ReplayGuardConfig {
+            window: Duration::from_secs(window_secs),
+            capacity,
+            ..ReplayGuardConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_duplicate_nonce_rejected() {
+        let mut guard = ReplayGuard::new(ReplayGuardConfig::default());
+        guard.accept(b"nonce-1", 1000, NOW).unwrap();
+        guard.accept(b"nonce-2", 1000, NOW).unwrap();
+        assert_eq!(guard.accept(b"nonce-1", 1001, NOW), Err(ReplayGuardError::Duplicate));
+    }
+
+    #[test]
+    fn test_old_timestamp_rejected() {
+        let mut guard = ReplayGuard::new(config(60, 16));
+        guard.accept(b"a", 1000, NOW).unwrap();
+        guard.accept(b"b", 940, NOW).unwrap();
+        assert_eq!(
+            guard.accept(b"c", 939, NOW),
+            Err(ReplayGuardError::TooOld { timestamp: 939, high_water: 1000 })
+        );
+    }
+
+    #[test]
+    fn test_expired_nonces_pruned() {
+        let mut guard = ReplayGuard::new(config(60, 16));
+        guard.accept(b"a", 1000, NOW).unwrap();
+        guard.accept(b"b", 1030, NOW).unwrap();
+        guard.accept(b"c", 1070, NOW).unwrap();
+        assert_eq!(guard.len(), 2);
+    }
+
+    #[test]
+    fn test_capacity_evicts_oldest() {
+        let mut guard = ReplayGuard::new(config(600, 2));
+        guard.accept(b"a", 1000, NOW).unwrap();
+        guard.accept(b"b", 1000, NOW).unwrap();
+        guard.accept(b"c", 1000, NOW).unwrap();
+        assert_eq!(guard.len(), 2);
+        assert_eq!(guard.accept(b"c", 1000, NOW), Err(ReplayGuardError::Duplicate));
+    }
+
+    #[test]
+    fn test_check_does_not_remember() {
+        let mut guard = ReplayGuard::new(config(60, 16));
+        guard.check(b"a", 5000, NOW).unwrap();
+        assert!(guard.is_empty());
+        assert_eq!(guard.high_water(), 0);
+        guard.accept(b"a", 5000, NOW).unwrap();
+        assert_eq!(guard.check(b"a", 5000, NOW), Err(ReplayGuardError::Duplicate));
+    }
+
+    #[test]
+    fn test_future_timestamp_does_not_poison_high_water() {
+        let mut guard = ReplayGuard::new(config(60, 16));
+        guard.accept(b"a", NOW, NOW).unwrap();
+        assert_eq!(
+            guard.accept(b"forged", u64::MAX, NOW),
+            Err(ReplayGuardError::FromFuture { timestamp: u64::MAX, now: NOW })
+        );
+        assert_eq!(guard.high_water(), NOW);
+        guard.accept(b"b", NOW + 1, NOW + 1).unwrap();
+    }
+
+    #[test]
+    fn test_snapshots_are_batched() {
+        let mut guard = ReplayGuard::new(ReplayGuardConfig { persist_batch: 3, ..config(600, 16) });
+        guard.accept(b"a", 1000, NOW).unwrap();
+        guard.accept(b"b", 1000, NOW).unwrap();
+        assert!(guard.take_due().is_none());
+        guard.accept(b"c", 1000, NOW).unwrap();
+        assert_eq!(guard.take_due().map(|r| r.entries.len()), Some(3));
+        assert!(guard.take_due().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_nonce_rejected_after_restart() {
+        let store = InMemoryStore::new();
+
+        let mut guard = ReplayGuard::load(&store, ReplayGuardConfig::default()).await.unwrap();
+        assert!(guard.is_empty());
+        guard.accept(b"before-restart", 5000, NOW).unwrap();
+        guard.persist(&store).await.unwrap();
+        drop(guard);
+
+        let mut reloaded = ReplayGuard::load(&store, ReplayGuardConfig::default()).await.unwrap();
+        assert_eq!(reloaded.high_water(), 5000);
+        assert_eq!(
+            reloaded.accept(b"before-restart", 5000, NOW),
+            Err(ReplayGuardError::Duplicate)
+        );
+        reloaded.accept(b"after-restart", 5001, NOW).unwrap();
+    }
+
+    #[test]
+    fn test_from_record_prunes_by_age() {
+        let record = ReplayStateRecord {
+            high_water: 1000,
+            entries: vec![
+                ReplayEntryRecord { nonce_hash: sha256(b"old").to_vec(), timestamp: 900 },
+                ReplayEntryRecord { nonce_hash: vec![1, 2, 3], timestamp: 990 },
+                ReplayEntryRecord { nonce_hash: sha256(b"new").to_vec(), timestamp: 990 },
+            ],
+        };
+        let mut guard = ReplayGuard::from_record(record, config(60, 16));
+        assert_eq!(guard.len(), 1);
+        assert_eq!(guard.accept(b"new", 1000, NOW), Err(ReplayGuardError::Duplicate));
+    }
+}
@@ -6,6 +6,7 @@
 //! Requirements: 3.1-3.9, 4.1-4.8
 
 use std::sync::Arc;
+use std::time::Instant;
 
 use async_trait::async_trait;
 use ed25519_dalek::Signer;
@@ -13,15 +14,24 @@ use getrandom::getrandom;
 use prost::Message;
 
 use crate::{
+    audit::{self, audit_id, warn_on_audit_error, AuditLogger, AuditSink},
     policy::{PolicyEngine, PolicyError},
     store::{PairingRecord, Store, StoreError, TicketRecord},
-    transport::TransportNegotiator,
+    transport::{TransportNegotiator, TransportSelection},
     types::IdentityKeys,
 };
 use zrc_crypto::hash::sha256;
+use zrc_crypto::utils::constant_time_compare;
 use zrc_proto::v1::{
-    SessionInitRequestV1, SessionInitResponseV1, SessionTicketV1, TransportNegotiationV1,
+    control_msg_v1, session_close_v1,
+    ticket_renewal_v1::{RefusalV1, StatusV1},
+    ControlMsgV1, SessionCloseV1, SessionInitRequestV1, SessionInitResponseV1, SessionTicketV1,
+    TicketRenewalV1, TransportNegotiationV1, TransportPreferenceV1, TransportV1,
 };
+use zrc_security::downgrade::{
+    DowngradeGuard, NegotiatedParams, NegotiationOffer, MIN_CIPHER_SUITE, MIN_KEX_SUITE, MIN_SIG_TYPE,
+};
+use zrc_security::key_recovery::RevocationSet;
 
 // ============================================================================
 // Error Types
@@ -54,6 +64,10 @@ pub enum SessionError {
     PolicyError(String),
     /// Transport negotiation failed
     TransportError(String),
+    /// Device signing key has been revoked
+    KeyRevoked(String),
+    /// Negotiated parameters differ from what was offered
+    DowngradeDetected(String),
 }
 
 impl std::fmt::Display for SessionError {
@@ -71,6 +85,8 @@ impl std::fmt::Display for SessionError {
             SessionError::StoreError(s) => write!(f, "store error: {}", s),
             SessionError::PolicyError(s) => write!(f, "policy error: {}", s),
             SessionError::TransportError(s) => write!(f, "transport error: {}", s),
+            SessionError::KeyRevoked(s) => write!(f, "device key revoked: {}", s),
+            SessionError::DowngradeDetected(s) => write!(f, "downgrade detected: {}", s),
         }
     }
 }
@@ -108,10 +124,64 @@ pub enum SessionEndReason {
     TransportLost,
     /// Consent revoked during session
     ConsentRevoked,
+    /// Session idle for too long
+    IdleTimeout,
     /// Error occurred
     Error(String),
 }
 
+impl SessionEndReason {
+    /// End reason for a `SessionCloseV1` sent by the peer.
+    ///
+    /// `from_operator` is true on the host (the controller sent it) and false
+    /// on the controller.
+    pub fn from_close(close: &SessionCloseV1, from_operator: bool) -> Self {
+        match close.reason() {
+            session_close_v1::ReasonV1::Idle => SessionEndReason::IdleTimeout,
+            session_close_v1::ReasonV1::Revoked => SessionEndReason::ConsentRevoked,
+            session_close_v1::ReasonV1::Error => SessionEndReason::Error(close.detail.clone()),
+            session_close_v1::ReasonV1::UserDisconnect | session_close_v1::ReasonV1::Unspecified => {
+                if from_operator {
+                    SessionEndReason::OperatorDisconnect
+                } else {
+                    SessionEndReason::DeviceDisconnect
+                }
+            }
+        }
+    }
+
+    /// Close reason to send the peer when ending with this reason.
+    pub fn close_reason(&self) -> session_close_v1::ReasonV1 {
+        match self {
+            SessionEndReason::OperatorDisconnect | SessionEndReason::DeviceDisconnect => {
+                session_close_v1::ReasonV1::UserDisconnect
+            }
+            SessionEndReason::IdleTimeout => session_close_v1::ReasonV1::Idle,
+            SessionEndReason::ConsentRevoked | SessionEndReason::PolicyViolation(_) => {
+                session_close_v1::ReasonV1::Revoked
+            }
+            SessionEndReason::TicketExpired
+            | SessionEndReason::TransportLost
+            | SessionEndReason::Error(_) => session_close_v1::ReasonV1::Error,
+        }
+    }
+}
+
+impl From<&SessionEndReason> for audit::SessionEndReason {
+    fn from(reason: &SessionEndReason) -> Self {
+        match reason {
+            SessionEndReason::OperatorDisconnect
+            | SessionEndReason::DeviceDisconnect
+            | SessionEndReason::ConsentRevoked => audit::SessionEndReason::UserRequested,
+            SessionEndReason::TicketExpired => audit::SessionEndReason::TicketExpired,
+            SessionEndReason::PolicyViolation(_) => audit::SessionEndReason::PolicyViolation,
+            SessionEndReason::TransportLost => audit::SessionEndReason::TransportDisconnected,
+            SessionEndReason::IdleTimeout => audit::SessionEndReason::IdleTimeout,
+            SessionEndReason::Error(msg) => audit::SessionEndReason::Error(msg.clone()),
+        }
+    }
+}
+
 // ============================================================================
 // Session Consent Handler Trait
 // ============================================================================
@@ -205,6 +275,7 @@ pub struct ActiveSession {
 
 /// Action returned by session operations.
 #[derive(Clone, Debug)]
+#[allow(clippy::large_enum_variant)]
 pub enum SessionAction {
     /// Waiting for user consent
     AwaitingConsent {
@@ -239,6 +310,10 @@ pub struct SessionHost<S: Store, C: SessionConsentHandler> {
     transport_negotiator: TransportNegotiator,
     /// Default ticket TTL in seconds
     ticket_ttl_secs: u64,
+    /// Audit logger for session lifecycle events
+    audit: Option<AuditLogger>,
+    /// Last input or keepalive from the controller
+    last_activity: Instant,
 }
 
 impl<S: Store, C: SessionConsentHandler> SessionHost<S, C> {
@@ -257,6 +332,8 @@ impl<S: Store, C: SessionConsentHandler> SessionHost<S, C> {
             consent_handler,
             transport_negotiator: TransportNegotiator::default(),
             ticket_ttl_secs: 3600, // 1 hour default
+            audit: None,
+            last_activity: Instant::now(),
         }
     }
 
@@ -276,9 +353,20 @@ impl<S: Store, C: SessionConsentHandler> SessionHost<S, C> {
             consent_handler,
             transport_negotiator,
             ticket_ttl_secs: 3600,
+            audit: None,
+            last_activity: Instant::now(),
         }
     }
 
+    /// Record session lifecycle events to `sink`.
+    /// Requirements: 9.2
+    pub fn with_audit_sink(mut self, sink: Arc<dyn AuditSink>) -> Self {
+        self.audit
+            .get_or_insert_with(|| AuditLogger::new(self.device_keys.id32))
+            .add_sink(sink);
+        self
+    }
+
     /// Set the default ticket TTL.
     pub fn set_ticket_ttl(&mut self, ttl_secs: u64) {
         self.ticket_ttl_secs = ttl_secs;
@@ -289,6 +377,11 @@ impl<S: Store, C: SessionConsentHandler> SessionHost<S, C> {
         &self.state
     }
 
+    /// Get the consent handler used for user approval.
+    pub fn consent_handler(&self) -> &Arc<C> {
+        &self.consent_handler
+    }
+
     /// Get the device keys.
     pub fn device_keys(&self) -> &IdentityKeys {
         &self.device_keys
@@ -318,12 +411,26 @@ impl<S: Store, C: SessionConsentHandler> SessionHost<S, C> {
             return Err(SessionError::MissingField("session_id".into()));
         }
 
+        let operator_id = audit_id(&request.operator_id);
+        if let Some(audit) = &self.audit {
+            warn_on_audit_error(
+                audit
+                    .session_requested(operator_id, audit_id(&request.session_id))
+                    .await,
+            );
+        }
+
         // Verify operator is paired (Requirements: 3.2, 3.3)
-        let pairing = self
+        let Some(pairing) = self
             .store
             .load_pairing(&self.device_keys.id32, &request.operator_id)
             .await?
-            .ok_or(SessionError::NotPaired)?;
+        else {
+            if let Some(audit) = &self.audit {
+                warn_on_audit_error(audit.session_denied(operator_id, "not paired").await);
+            }
+            return Err(SessionError::NotPaired);
+        };
 
         // Get paired permissions as bitmask
         // The granted_perms are stored as PermissionV1 enum values (1=VIEW, 2=CONTROL, etc.)
@@ -480,7 +587,7 @@ impl<S: Store, C: SessionConsentHandler> SessionHost<S, C> {
 
         // Sign the ticket
         sign_session_ticket_v1(&self.device_keys.sign, &mut ticket)
-            .map_err(|e| SessionError::CryptoError(e))?;
+            .map_err(SessionError::CryptoError)?;
 
         // Generate transport negotiation params (Requirements: 3.7)
         let _transport_params = self.transport_negotiator.generate_params(None, vec![]);
@@ -498,6 +605,12 @@ impl<S: Store, C: SessionConsentHandler> SessionHost<S, C> {
             permissions: granted_permissions,
         };
 
+        // Bind both offers and the selection into the signed response
+        let negotiation_commitment = negotiation_commitment(
+            request.transport_preference,
+            transport_negotiation.preferred_transport,
+        )?;
+
         // Build response
         let mut response = SessionInitResponseV1 {
             session_id: session_id.to_vec(),
@@ -508,12 +621,13 @@ impl<S: Store, C: SessionConsentHandler> SessionHost<S, C> {
             device_id: self.device_keys.id32.to_vec(),
             operator_id: operator_id.clone(),
             requires_consent: false,
+            negotiation_commitment: negotiation_commitment.to_vec(),
             ..Default::default()
         };
 
         // Sign the response
         sign_session_init_response_v1(&self.device_keys.sign, &mut response)
-            .map_err(|e| SessionError::CryptoError(e))?;
+            .map_err(SessionError::CryptoError)?;
 
         // Store the ticket
         let ticket_record = TicketRecord::from(&ticket);
@@ -525,7 +639,16 @@ impl<S: Store, C: SessionConsentHandler> SessionHost<S, C> {
             .update_pairing_last_session(&self.device_keys.id32, &operator_id, now)
             .await;
 
+        if let Some(audit) = &self.audit {
+            warn_on_audit_error(
+                audit
+                    .session_started(audit_id(&operator_id), session_id, granted_permissions)
+                    .await,
+            );
+        }
+
         // Transition to Active state
+        self.last_activity = Instant::now();
         self.state = SessionHostState::Active {
             session: ActiveSession {
                 session_id,
@@ -544,8 +667,11 @@ impl<S: Store, C: SessionConsentHandler> SessionHost<S, C> {
     /// Requirements: 3.5
     pub async fn reject(&mut self, reason: &str) -> Result<(), SessionError> {
         match &self.state {
-            SessionHostState::AwaitingConsent { .. }
-            | SessionHostState::RequestReceived { .. } => {
+            SessionHostState::AwaitingConsent { operator_id, .. }
+            | SessionHostState::RequestReceived { operator_id, .. } => {
+                if let Some(audit) = &self.audit {
+                    warn_on_audit_error(audit.session_denied(audit_id(operator_id), reason).await);
+                }
                 self.state = SessionHostState::Ended {
                     reason: SessionEndReason::PolicyViolation(reason.to_string()),
                 };
@@ -565,6 +691,19 @@ impl<S: Store, C: SessionConsentHandler> SessionHost<S, C> {
                 // Revoke the ticket
                 let _ = self.store.revoke_ticket(&session.ticket.ticket_id).await;
 
+                if let Some(audit) = &self.audit {
+                    let now = std::time::SystemTime::now()
+                        .duration_since(std::time::UNIX_EPOCH)
+                        .unwrap_or_default()
+                        .as_secs();
+                    let duration = now.saturating_sub(session.started_at);
+                    warn_on_audit_error(
+                        audit
+                            .session_ended(session.session_id, (&reason).into(), duration)
+                            .await,
+                    );
+                }
+
                 self.state = SessionHostState::Ended {
                     reason,
                 };
@@ -582,6 +721,76 @@ impl<S: Store, C: SessionConsentHandler> SessionHost<S, C> {
         }
     }
 
+    /// Handle a `SessionCloseV1` from the controller.
+    ///
+    /// Ends the session with the matching reason, revoking its ticket and
+    /// emitting the ended audit event. The caller stops capture and releases
+    /// held input. A close for a session
+    /// that has already ended is ignored.
+    pub async fn handle_session_close(&mut self, close: &SessionCloseV1) -> Result<(), SessionError> {
+        if matches!(self.state, SessionHostState::Ended { .. }) {
+            return Ok(());
+        }
+        self.end_session(SessionEndReason::from_close(close, true)).await
+    }
+
+    /// Note input or a keepalive from the controller, restarting the idle
+    /// timer.
+    pub fn record_activity(&mut self) {
+        self.last_activity = Instant::now();
+    }
+
+    /// Track controller activity from a received control message.
+    ///
+    /// Input events and pings restart the idle timer, so a viewer that is
+    /// only watching stays connected by pinging. Returns the pong to send
+    /// back for a ping.
+    pub fn handle_keepalive(&mut self, msg: &ControlMsgV1) -> Option<ControlMsgV1> {
+        match &msg.payload {
+            Some(control_msg_v1::Payload::Input(_)) => {
+                self.record_activity();
+                None
+            }
+            Some(control_msg_v1::Payload::Ping(ping)) => {
+                self.record_activity();
+                Some(ControlMsgV1::pong(msg.sequence_number, ping.t))
+            }
+            _ => None,
+        }
+    }
+
+    /// When the active session will be closed for inactivity, if the policy
+    /// sets an idle timeout.
+    pub fn idle_deadline(&self) -> Option<Instant> {
+        match self.state {
+            SessionHostState::Active { .. } => {
+                Some(self.last_activity + self.policy.idle_timeout()?)
+            }
+            _ => None,
+        }
+    }
+
+    /// End the active session if it has been idle past the policy timeout.
+    ///
+    /// Returns the `SessionCloseV1` to send the controller
+    /// (`ControlChannelV1::send_close`, which also fires the channel's close
+    /// signal so the frame stream stops). Call periodically, or sleep until
+    /// `idle_deadline`.
+    pub async fn check_idle(&mut self) -> Result<Option<SessionCloseV1>, SessionError> {
+        let (Some(deadline), Some(timeout)) = (self.idle_deadline(), self.policy.idle_timeout()) else {
+            return Ok(None);
+        };
+        if Instant::now() < deadline {
+            return Ok(None);
+        }
+
+        self.end_session(SessionEndReason::IdleTimeout).await?;
+        Ok(Some(SessionCloseV1 {
+            reason: session_close_v1::ReasonV1::Idle as i32,
+            detail: format!("no activity for {}s", timeout.as_secs()),
+        }))
+    }
+
     /// Check if a ticket is valid for this session.
     pub async fn validate_ticket(&self, ticket: &SessionTicketV1) -> Result<bool, SessionError> {
         let now = std::time::SystemTime::now()
@@ -601,6 +810,89 @@ impl<S: Store, C: SessionConsentHandler> SessionHost<S, C> {
             .map_err(|e| SessionError::StoreError(e.to_string()))
     }
 
+    /// Handle an in-band ticket renewal request for the active session.
+    /// Requirements: 4.7
+    ///
+    /// The active ticket is re-issued with a fresh expiry, keeping its ticket
+    /// id and session binding so the session crypto derived from them stays
+    /// valid. Returns the GRANTED or REFUSED message to send back. Renewal is
+    /// refused, and the session ended, if the pairing has been revoked or the
+    /// current ticket has already expired.
+    pub async fn handle_ticket_renewal(
+        &mut self,
+        request: &TicketRenewalV1,
+    ) -> Result<TicketRenewalV1, SessionError> {
+        let session = match &self.state {
+            SessionHostState::Active { session } => session.clone(),
+            _ => {
+                return Err(SessionError::InvalidState(
+                    "can only renew tickets in Active state".into(),
+                ));
+            }
+        };
+
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        let refusal = if request.status() != StatusV1::Request
+            || !constant_time_compare(&request.ticket_id, &session.ticket.ticket_id)
+        {
+            Some(RefusalV1::TicketInvalid)
+        } else if session.ticket.expires_at <= now {
+            Some(RefusalV1::TicketExpired)
+        } else if !self.store.is_ticket_valid(&request.ticket_id, now).await? {
+            Some(RefusalV1::TicketInvalid)
+        } else if self
+            .store
+            .load_pairing(&self.device_keys.id32, &session.operator_id)
+            .await?
+            .is_none()
+        {
+            Some(RefusalV1::PairingRevoked)
+        } else {
+            None
+        };
+
+        if let Some(refusal) = refusal {
+            let reason = match refusal {
+                RefusalV1::PairingRevoked => Some(SessionEndReason::ConsentRevoked),
+                RefusalV1::TicketExpired => Some(SessionEndReason::TicketExpired),
+                _ => None,
+            };
+            if let Some(reason) = reason {
+                self.end_session(reason).await?;
+            }
+            return Ok(TicketRenewalV1 {
+                status: StatusV1::Refused as i32,
+                ticket_id: request.ticket_id.clone(),
+                ticket: None,
+                refusal: refusal as i32,
+            });
+        }
+
+        let mut ticket = session.ticket.clone();
+        ticket.expires_at = now + self.ticket_ttl_secs;
+        sign_session_ticket_v1(&self.device_keys.sign, &mut ticket)
+            .map_err(SessionError::CryptoError)?;
+        self.store.save_ticket(TicketRecord::from(&ticket)).await?;
+
+        self.state = SessionHostState::Active {
+            session: ActiveSession {
+                ticket: ticket.clone(),
+                ..session
+            },
+        };
+
+        Ok(TicketRenewalV1 {
+            status: StatusV1::Granted as i32,
+            ticket_id: ticket.ticket_id.clone(),
+            ticket: Some(ticket),
+            refusal: RefusalV1::Unspecified as i32,
+        })
+    }
+
     /// Reset the state machine to Idle.
     pub fn reset(&mut self) {
         self.state = SessionHostState::Idle;
@@ -700,10 +992,16 @@ pub struct SessionController<S: Store> {
     store: Arc<S>,
     /// Transport negotiator
     transport_negotiator: TransportNegotiator,
+    /// Outcome of the last `initiate_connection`
+    transport_selection: Option<TransportSelection>,
     /// Session request timeout in seconds (default: 30)
     request_timeout_secs: u64,
     /// Ticket renewal threshold in seconds (renew when this much time left)
     renewal_threshold_secs: u64,
+    /// Cached key revocations; device keys in it are refused
+    revocations: Option<Arc<RevocationSet>>,
+    /// Sinks for transport selection events
+    audit_sinks: Vec<Arc<dyn AuditSink>>,
 }
 
 impl<S: Store> SessionController<S> {
@@ -714,8 +1012,11 @@ impl<S: Store> SessionController<S> {
             operator_keys,
             store,
             transport_negotiator: TransportNegotiator::default(),
+            transport_selection: None,
             request_timeout_secs: 30,
             renewal_threshold_secs: 300, // 5 minutes before expiry
+            revocations: None,
+            audit_sinks: Vec::new(),
         }
     }
 
@@ -730,11 +1031,21 @@ impl<S: Store> SessionController<S> {
             operator_keys,
             store,
             transport_negotiator,
+            transport_selection: None,
             request_timeout_secs: 30,
             renewal_threshold_secs: 300,
+            revocations: None,
+            audit_sinks: Vec::new(),
         }
     }
 
+    /// Record the transport selected for each session, and why preferred
+    /// ones were skipped, to `sink`.
+    pub fn with_audit_sink(mut self, sink: Arc<dyn AuditSink>) -> Self {
+        self.audit_sinks.push(sink);
+        self
+    }
+
     /// Set the request timeout.
     pub fn set_request_timeout(&mut self, timeout_secs: u64) {
         self.request_timeout_secs = timeout_secs;
@@ -745,6 +1056,11 @@ impl<S: Store> SessionController<S> {
         self.renewal_threshold_secs = threshold_secs;
     }
 
+    /// Refuse device keys found in this revocation set.
+    pub fn set_revocations(&mut self, revocations: Arc<RevocationSet>) {
+        self.revocations = Some(revocations);
+    }
+
     /// Get the current state.
     pub fn state(&self) -> &SessionControllerState {
         &self.state
@@ -758,6 +1074,7 @@ impl<S: Store> SessionController<S> {
     /// Reset the state machine to Idle.
     pub fn reset(&mut self) {
         self.state = SessionControllerState::Idle;
+        self.transport_selection = None;
     }
 
     /// Start a new session with a device.
@@ -835,7 +1152,7 @@ impl<S: Store> SessionController<S> {
 
         // Sign the request
         sign_session_init_request_v1(&self.operator_keys.sign, &mut request)
-            .map_err(|e| SessionError::CryptoError(e))?;
+            .map_err(SessionError::CryptoError)?;
 
         // Transition to RequestSent state
         self.state = SessionControllerState::RequestSent {
@@ -863,12 +1180,12 @@ impl<S: Store> SessionController<S> {
         device_sign_pub: &[u8],
     ) -> Result<(), SessionError> {
         // Validate state transition
-        let (expected_device_id, _sent_at) = match &self.state {
+        let (expected_device_id, _sent_at, request_preference) = match &self.state {
             SessionControllerState::RequestSent {
+                request,
                 device_id,
                 sent_at,
-                ..
-            } => (device_id.clone(), *sent_at),
+            } => (device_id.clone(), *sent_at, request.transport_preference),
             _ => {
                 return Err(SessionError::InvalidState(
                     "can only handle response from RequestSent state".into(),
@@ -888,10 +1205,20 @@ impl<S: Store> SessionController<S> {
             ));
         }
 
+        // Refuse revoked device keys before trusting their signature
+        if let Some(revocations) = &self.revocations {
+            revocations
+                .check_now(device_sign_pub)
+                .map_err(|e| SessionError::KeyRevoked(e.to_string()))?;
+        }
+
         // Verify device signature (Requirements: 4.3)
         verify_session_init_response_v1(&response, device_sign_pub)
             .map_err(|_| SessionError::SignatureInvalid)?;
 
+        // The device must have seen the same offers we sent (Requirements: 4.5)
+        verify_negotiation_commitment(request_preference, &response)?;
+
         // Extract ticket (Requirements: 4.4)
         let ticket = response
             .issued_ticket
@@ -938,7 +1265,7 @@ impl<S: Store> SessionController<S> {
     /// # Returns
     /// * `Ok(transport)` - The selected transport to use for connection
     /// * `Err(SessionError)` - If no compatible transport is available
-    pub fn initiate_connection(&mut self) -> Result<crate::transport::SelectedTransport, SessionError> {
+    pub async fn initiate_connection(&mut self) -> Result<crate::transport::SelectedTransport, SessionError> {
         // Validate state
         let (session_id, ticket, transport_params, permissions) = match &self.state {
             SessionControllerState::TicketReceived {
@@ -991,11 +1318,29 @@ impl<S: Store> SessionController<S> {
             crate::transport::TransportNegotiation::default()
         };
 
-        // Select transport
-        let selected = self
+        // Select transport using the device's configured ordering
+        let selection = self
             .transport_negotiator
-            .select_transport(&negotiation)
+            .negotiate(&negotiation, Some(&ticket.device_id))
             .map_err(|e| SessionError::TransportError(e.to_string()))?;
+        tracing::info!(
+            transport = selection.transport_type.as_str(),
+            skipped = %selection.fallback_trail(),
+            "transport selected"
+        );
+        if !self.audit_sinks.is_empty() {
+            let mut audit = AuditLogger::new(audit_id(&ticket.device_id));
+            for sink in &self.audit_sinks {
+                audit.add_sink(sink.clone());
+            }
+            warn_on_audit_error(
+                audit
+                    .transport_selected(audit_id(&self.operator_keys.id32), session_id, &selection)
+                    .await,
+            );
+        }
+        let selected = selection.selected.clone();
+        self.transport_selection = Some(selection);
 
         // Transition to Connecting state
         self.state = SessionControllerState::Connecting {
@@ -1007,6 +1352,12 @@ impl<S: Store> SessionController<S> {
         Ok(selected)
     }
 
+    /// Transport chosen by the last `initiate_connection`, with the fallback
+    /// trail of skipped transports.
+    pub fn transport_selection(&self) -> Option<&TransportSelection> {
+        self.transport_selection.as_ref()
+    }
+
     /// Mark the session as active after transport connection is established.
     /// Requirements: 4.6
     pub fn mark_connected(&mut self) -> Result<(), SessionError> {
@@ -1088,6 +1439,120 @@ impl<S: Store> SessionController<S> {
         }
     }
 
+    /// Build an in-band renewal request for the active session's ticket.
+    /// Requirements: 4.7
+    ///
+    /// Send it on the control channel once `needs_ticket_renewal` returns
+    /// true and pass the host's answer to `handle_ticket_renewal`.
+    pub fn ticket_renewal_request(&self) -> Result<TicketRenewalV1, SessionError> {
+        match &self.state {
+            SessionControllerState::Active { session } => Ok(TicketRenewalV1 {
+                status: StatusV1::Request as i32,
+                ticket_id: session.ticket.ticket_id.clone(),
+                ticket: None,
+                refusal: RefusalV1::Unspecified as i32,
+            }),
+            _ => Err(SessionError::InvalidState(
+                "can only renew tickets in Active state".into(),
+            )),
+        }
+    }
+
+    /// Apply the host's answer to a ticket renewal request.
+    /// Requirements: 4.7
+    ///
+    /// # Arguments
+    /// * `response` - The GRANTED or REFUSED renewal message from the device
+    /// * `device_sign_pub` - The device's Ed25519 signing public key
+    ///
+    /// # Returns
+    /// * `Ok(true)` - Renewed; the ticket's binding changed, so re-derive the
+    ///   session crypto (`ControlChannelV1::rekey_for_ticket`)
+    /// * `Ok(false)` - Renewed; existing session crypto remains valid
+    /// * `Err(SessionError)` - Renewal refused or the ticket failed
+    ///   verification. Refusal because the pairing was revoked or the ticket
+    ///   expired also ends the session.
+    pub fn handle_ticket_renewal(
+        &mut self,
+        response: &TicketRenewalV1,
+        device_sign_pub: &[u8],
+    ) -> Result<bool, SessionError> {
+        let session = match &self.state {
+            SessionControllerState::Active { session } => session.clone(),
+            _ => {
+                return Err(SessionError::InvalidState(
+                    "can only renew tickets in Active state".into(),
+                ));
+            }
+        };
+
+        match response.status() {
+            StatusV1::Granted => {}
+            StatusV1::Refused => {
+                let (reason, err) = match response.refusal() {
+                    RefusalV1::PairingRevoked => {
+                        (SessionEndReason::ConsentRevoked, SessionError::NotPaired)
+                    }
+                    RefusalV1::TicketExpired => {
+                        (SessionEndReason::TicketExpired, SessionError::TicketExpired)
+                    }
+                    _ => {
+                        return Err(SessionError::TicketInvalid(
+                            "renewal refused by device".into(),
+                        ));
+                    }
+                };
+                self.state = SessionControllerState::Ended { reason };
+                return Err(err);
+            }
+            _ => {
+                return Err(SessionError::TicketInvalid(
+                    "unexpected renewal status".into(),
+                ));
+            }
+        }
+
+        let ticket = response
+            .ticket
+            .clone()
+            .ok_or(SessionError::MissingField("ticket".into()))?;
+
+        // The renewed ticket must belong to this session
+        if ticket.session_id != session.session_id
+            || ticket.device_id != session.device_id
+            || ticket.operator_id != session.ticket.operator_id
+        {
+            return Err(SessionError::TicketInvalid(
+                "renewed ticket does not match session".into(),
+            ));
+        }
+
+        verify_session_ticket_v1(&ticket, device_sign_pub)
+            .map_err(|_| SessionError::SignatureInvalid)?;
+
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        if ticket.expires_at <= now {
+            return Err(SessionError::TicketExpired);
+        }
+
+        let rekey = !constant_time_compare(&ticket.session_binding, &session.ticket.session_binding)
+            || !constant_time_compare(&ticket.ticket_id, &session.ticket.ticket_id);
+
+        self.state = SessionControllerState::Active {
+            session: ControllerActiveSession {
+                ticket_expires_at: ticket.expires_at,
+                permissions: ticket.permissions,
+                ticket,
+                ..session
+            },
+        };
+
+        Ok(rekey)
+    }
+
     /// Handle transport disconnection.
     /// Requirements: 4.8
     ///
@@ -1199,6 +1664,30 @@ fn sign_session_ticket_v1(
     Ok(())
 }
 
+/// Verify a SessionTicketV1 signature with the device's signing public key.
+fn verify_session_ticket_v1(t: &SessionTicketV1, device_sign_pub: &[u8]) -> Result<(), String> {
+    use ed25519_dalek::{Signature, VerifyingKey};
+
+    let pub_bytes: [u8; 32] = device_sign_pub
+        .try_into()
+        .map_err(|_| "device_sign_pub must be 32 bytes")?;
+    let verifying_key =
+        VerifyingKey::from_bytes(&pub_bytes).map_err(|e| format!("invalid public key: {}", e))?;
+
+    let sig_bytes: [u8; 64] = t
+        .device_signature
+        .as_slice()
+        .try_into()
+        .map_err(|_| "device_signature must be 64 bytes")?;
+    let signature = Signature::from_bytes(&sig_bytes);
+
+    let bytes = session_ticket_signing_bytes_v1(t)?;
+    let digest = sha256(&bytes);
+    verifying_key
+        .verify_strict(&digest, &signature)
+        .map_err(|e| format!("signature verification failed: {}", e))
+}
+
 /// Compute the bytes to sign for a SessionInitResponseV1.
 fn session_init_response_signing_bytes_v1(r: &SessionInitResponseV1) -> Result<Vec<u8>, String> {
     let mut rr = r.clone();
@@ -1276,6 +1765,76 @@ fn verify_session_init_response_v1(
         .map_err(|e| format!("signature verification failed: {}", e))
 }
 
+/// Offer implied by a `TransportPreferenceV1`, with the only algorithms
+/// this protocol version implements.
+fn negotiation_offer(preference: i32) -> NegotiationOffer {
+    let transports = match TransportPreferenceV1::try_from(preference) {
+        Ok(TransportPreferenceV1::MeshPreferred) => {
+            vec![TransportV1::MeshMailbox, TransportV1::DirectIp, TransportV1::Relay]
+        }
+        Ok(TransportPreferenceV1::RelayOnly) => vec![TransportV1::Relay],
+        _ => vec![TransportV1::DirectIp, TransportV1::Relay],
+    };
+    NegotiationOffer {
+        transports,
+        cipher_suites: vec![MIN_CIPHER_SUITE],
+        kex_suites: vec![MIN_KEX_SUITE],
+        sig_types: vec![MIN_SIG_TYPE],
+    }
+}
+
+/// Offers and selection for a session whose request carried
+/// `request_preference` and whose response carried `response_preference`.
+fn negotiation(
+    request_preference: i32,
+    response_preference: i32,
+) -> Result<(NegotiationOffer, NegotiationOffer, NegotiatedParams), SessionError> {
+    let initiator = negotiation_offer(request_preference);
+    let responder = negotiation_offer(response_preference);
+    let transport = initiator
+        .transports
+        .iter()
+        .find(|t| responder.transports.contains(t))
+        .copied()
+        .ok_or_else(|| SessionError::TransportError("no transport offered by both sides".into()))?;
+    let selected = NegotiatedParams {
+        transport,
+        cipher_suite: MIN_CIPHER_SUITE,
+        kex_suite: MIN_KEX_SUITE,
+        sig_type: MIN_SIG_TYPE,
+    };
+    Ok((initiator, responder, selected))
+}
+
+/// Transcript hash the device signs into
+/// `SessionInitResponseV1::negotiation_commitment`.
+fn negotiation_commitment(
+    request_preference: i32,
+    response_preference: i32,
+) -> Result<[u8; 32], SessionError> {
+    let (initiator, responder, selected) = negotiation(request_preference, response_preference)?;
+    DowngradeGuard::new()
+        .check_selection(&initiator, &responder, &selected)
+        .map_err(|e| SessionError::DowngradeDetected(e.to_string()))
+}
+
+/// Check that a signed response commits to the negotiation of a request sent
+/// with `request_preference`. A stripped offer or missing commitment fails.
+pub fn verify_negotiation_commitment(
+    request_preference: i32,
+    response: &SessionInitResponseV1,
+) -> Result<(), SessionError> {
+    let response_preference = response
+        .transport_params
+        .as_ref()
+        .map_or(0, |p| p.preferred_transport);
+    let (initiator, responder, selected) = negotiation(request_preference, response_preference)?;
+    DowngradeGuard::new()
+        .verify(&initiator, &responder, &selected, &response.negotiation_commitment)
+        .map(|_| ())
+        .map_err(|e| SessionError::DowngradeDetected(e.to_string()))
+}
+
 // ============================================================================
 // Unit Tests
 // ============================================================================
@@ -1555,6 +2114,256 @@ mod tests {
         assert!(matches!(host.state(), SessionHostState::Ended { reason: SessionEndReason::OperatorDisconnect }));
     }
 
+    /// Start an unattended session whose ticket lives for `ttl_secs`, and a
+    /// controller already active on the issued ticket.
+    async fn active_session_pair(
+        ttl_secs: u64,
+    ) -> (
+        SessionHost<InMemoryStore, AlwaysApproveSession>,
+        SessionController<InMemoryStore>,
+        Arc<InMemoryStore>,
+        IdentityKeys,
+    ) {
+        active_session_pair_with_policy(ttl_secs, PolicyEngine::new(ConsentMode::UnattendedAllowed)).await
+    }
+
+    async fn active_session_pair_with_policy(
+        ttl_secs: u64,
+        policy: PolicyEngine,
+    ) -> (
+        SessionHost<InMemoryStore, AlwaysApproveSession>,
+        SessionController<InMemoryStore>,
+        Arc<InMemoryStore>,
+        IdentityKeys,
+    ) {
+        let device_keys = generate_identity_keys();
+        let store = Arc::new(InMemoryStore::new());
+        let policy = Arc::new(policy);
+
+        let operator_keys = generate_identity_keys();
+        let mut pairing = make_test_pairing(&device_keys.id32, &operator_keys.id32);
+        pairing.unattended_enabled = true;
+        store.save_pairing(pairing).await.unwrap();
+
+        let mut host = SessionHost::new(
+            device_keys.clone(),
+            store.clone(),
+            policy,
+            Arc::new(AlwaysApproveSession),
+        );
+        host.set_ticket_ttl(ttl_secs);
+        let request = SessionInitRequestV1 {
+            operator_id: operator_keys.id32.to_vec(),
+            device_id: device_keys.id32.to_vec(),
+            session_id: vec![3u8; 32],
+            requested_capabilities: 0x03,
+            ..Default::default()
+        };
+        host.handle_request(request).await.unwrap();
+        let session = host.active_session().unwrap().clone();
+
+        let mut controller = SessionController::new(operator_keys, Arc::new(InMemoryStore::new()));
+        controller.state = SessionControllerState::Active {
+            session: ControllerActiveSession {
+                session_id: session.session_id,
+                device_id: device_keys.id32.to_vec(),
+                permissions: session.permissions,
+                ticket_expires_at: session.ticket.expires_at,
+                ticket: session.ticket,
+                started_at: session.started_at,
+            },
+        };
+
+        (host, controller, store, device_keys)
+    }
+
+    #[tokio::test]
+    async fn test_ticket_renewal_extends_active_ticket() {
+        let (mut host, mut controller, _store, device_keys) = active_session_pair(60).await;
+        controller.set_renewal_threshold(300);
+        assert!(controller.needs_ticket_renewal());
+        let old_ticket = controller.current_ticket().unwrap().clone();
+
+        host.set_ticket_ttl(3600);
+        let request = controller.ticket_renewal_request().unwrap();
+        let response = host.handle_ticket_renewal(&request).await.unwrap();
+        assert_eq!(response.status(), StatusV1::Granted);
+
+        let rekey = controller
+            .handle_ticket_renewal(&response, &device_keys.sign_pub.key_bytes)
+            .unwrap();
+        assert!(!rekey, "renewal keeps the ticket id and binding");
+        assert!(!controller.needs_ticket_renewal());
+
+        let renewed = controller.current_ticket().unwrap();
+        assert_eq!(renewed.ticket_id, old_ticket.ticket_id);
+        assert_eq!(renewed.session_binding, old_ticket.session_binding);
+        assert!(renewed.expires_at > old_ticket.expires_at);
+        assert_eq!(
+            host.active_session().unwrap().ticket.expires_at,
+            renewed.expires_at
+        );
+
+        // A tampered ticket is rejected
+        let mut forged = response.clone();
+        forged.ticket.as_mut().unwrap().expires_at += 1;
+        assert!(matches!(
+            controller.handle_ticket_renewal(&forged, &device_keys.sign_pub.key_bytes),
+            Err(SessionError::SignatureInvalid)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_ticket_renewal_refused_when_pairing_revoked() {
+        let (mut host, mut controller, store, device_keys) = active_session_pair(60).await;
+        let operator_id = controller.operator_keys().id32;
+        store.delete_pairing(&device_keys.id32, &operator_id).await.unwrap();
+
+        let request = controller.ticket_renewal_request().unwrap();
+        let response = host.handle_ticket_renewal(&request).await.unwrap();
+        assert_eq!(response.status(), StatusV1::Refused);
+        assert_eq!(response.refusal(), RefusalV1::PairingRevoked);
+        assert!(matches!(
+            host.state(),
+            SessionHostState::Ended { reason: SessionEndReason::ConsentRevoked }
+        ));
+
+        let result = controller.handle_ticket_renewal(&response, &device_keys.sign_pub.key_bytes);
+        assert!(matches!(result, Err(SessionError::NotPaired)));
+        assert!(matches!(
+            controller.state(),
+            SessionControllerState::Ended { reason: SessionEndReason::ConsentRevoked }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_ticket_renewal_refused_when_ticket_already_expired() {
+        // A zero TTL ticket is expired as soon as it is issued
+        let (mut host, mut controller, _store, device_keys) = active_session_pair(0).await;
+        assert!(controller.is_ticket_expired());
+
+        let request = controller.ticket_renewal_request().unwrap();
+        let response = host.handle_ticket_renewal(&request).await.unwrap();
+        assert_eq!(response.status(), StatusV1::Refused);
+        assert_eq!(response.refusal(), RefusalV1::TicketExpired);
+        assert!(response.ticket.is_none());
+        assert!(matches!(
+            host.state(),
+            SessionHostState::Ended { reason: SessionEndReason::TicketExpired }
+        ));
+
+        let result = controller.handle_ticket_renewal(&response, &device_keys.sign_pub.key_bytes);
+        assert!(matches!(result, Err(SessionError::TicketExpired)));
+        assert!(!controller.is_active());
+    }
+
+    #[tokio::test]
+    async fn test_session_close_ends_host_session() {
+        let (mut host, _controller, store, _device_keys) = active_session_pair(3600).await;
+        let ticket_id = host.active_session().unwrap().ticket.ticket_id.clone();
+
+        let close = SessionCloseV1 {
+            reason: session_close_v1::ReasonV1::Idle as i32,
+            detail: String::new(),
+        };
+        host.handle_session_close(&close).await.unwrap();
+
+        assert!(matches!(
+            host.state(),
+            SessionHostState::Ended { reason: SessionEndReason::IdleTimeout }
+        ));
+        // Revoked tickets no longer load
+        assert!(store.load_ticket(&ticket_id).await.unwrap().is_none());
+
+        // A repeated close is harmless
+        host.handle_session_close(&close).await.unwrap();
+    }
+
+    fn idle_policy(timeout_ms: u64) -> PolicyEngine {
+        let mut policy = PolicyEngine::new(ConsentMode::UnattendedAllowed);
+        policy.set_idle_timeout(Some(std::time::Duration::from_millis(timeout_ms)));
+        policy
+    }
+
+    #[tokio::test]
+    async fn test_idle_timeout_closes_session() {
+        let (mut host, _controller, _store, _device_keys) =
+            active_session_pair_with_policy(3600, idle_policy(50)).await;
+        assert!(host.check_idle().await.unwrap().is_none());
+        assert!(host.idle_deadline().is_some());
+
+        tokio::time::sleep(std::time::Duration::from_millis(80)).await;
+        let close = host.check_idle().await.unwrap().expect("idle session closed");
+        assert_eq!(close.reason(), session_close_v1::ReasonV1::Idle);
+        assert!(matches!(
+            host.state(),
+            SessionHostState::Ended { reason: SessionEndReason::IdleTimeout }
+        ));
+
+        // Nothing further to close
+        assert!(host.idle_deadline().is_none());
+        assert!(host.check_idle().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_keepalive_keeps_idle_session_open() {
+        let (mut host, _controller, _store, _device_keys) =
+            active_session_pair_with_policy(3600, idle_policy(100)).await;
+
+        // Pings every 40ms keep the session alive well past the timeout
+        for seq in 0..5 {
+            tokio::time::sleep(std::time::Duration::from_millis(40)).await;
+            let pong = host.handle_keepalive(&ControlMsgV1::ping(seq)).expect("pong");
+            assert!(matches!(pong.payload, Some(control_msg_v1::Payload::Pong(_))));
+            assert!(host.check_idle().await.unwrap().is_none());
+        }
+        assert!(host.is_active());
+
+        // Other control traffic does not count as activity
+        let ack = ControlMsgV1::new(9, control_msg_v1::Payload::SessionControl(Default::default()));
+        assert!(host.handle_keepalive(&ack).is_none());
+        tokio::time::sleep(std::time::Duration::from_millis(130)).await;
+        assert!(host.check_idle().await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_idle_timeout_disabled() {
+        let mut policy = PolicyEngine::new(ConsentMode::UnattendedAllowed);
+        policy.set_idle_timeout(None);
+        let (mut host, _controller, _store, _device_keys) =
+            active_session_pair_with_policy(3600, policy).await;
+        assert!(host.idle_deadline().is_none());
+        assert!(host.check_idle().await.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_session_close_reason_mapping() {
+        let close = |reason: session_close_v1::ReasonV1| SessionCloseV1 {
+            reason: reason as i32,
+            detail: "boom".into(),
+        };
+        assert!(matches!(
+            SessionEndReason::from_close(&close(session_close_v1::ReasonV1::UserDisconnect), true),
+            SessionEndReason::OperatorDisconnect
+        ));
+        assert!(matches!(
+            SessionEndReason::from_close(&close(session_close_v1::ReasonV1::UserDisconnect), false),
+            SessionEndReason::DeviceDisconnect
+        ));
+        assert!(matches!(
+            SessionEndReason::from_close(&close(session_close_v1::ReasonV1::Revoked), true),
+            SessionEndReason::ConsentRevoked
+        ));
+        assert!(matches!(
+            SessionEndReason::from_close(&close(session_close_v1::ReasonV1::Error), true),
+            SessionEndReason::Error(ref detail) if detail == "boom"
+        ));
+        assert_eq!(
+            SessionEndReason::IdleTimeout.close_reason(),
+            session_close_v1::ReasonV1::Idle
+        );
+    }
+
     #[tokio::test]
     async fn test_session_host_reset() {
         let device_keys = generate_identity_keys();
@@ -1637,6 +2446,154 @@ mod tests {
         assert!(matches!(controller.state(), SessionControllerState::RequestSent { .. }));
     }
 
+    #[tokio::test]
+    async fn test_session_controller_rejects_revoked_device_key() {
+        use zrc_proto::v1::revoke_v1::ReasonV1;
+        use zrc_security::key_recovery::issue_revocation;
+
+        let operator_keys = generate_identity_keys();
+        let device_keys = generate_identity_keys();
+        let pairing = make_test_pairing(&device_keys.id32, &operator_keys.id32);
+
+        let controller_store = Arc::new(InMemoryStore::new());
+        controller_store.save_pairing(pairing.clone()).await.unwrap();
+        let host_store = Arc::new(InMemoryStore::new());
+        host_store.save_pairing(pairing).await.unwrap();
+
+        let mut controller = SessionController::new(operator_keys, controller_store);
+        let mut host = SessionHost::new(
+            device_keys.clone(),
+            host_store,
+            Arc::new(PolicyEngine::new(ConsentMode::AlwaysRequire)),
+            Arc::new(AlwaysApproveSession),
+        );
+
+        let request = controller.start_session(&device_keys.id32, 0x03).await.unwrap();
+        host.handle_request(request).await.unwrap();
+        let response = host.approve().await.unwrap();
+
+        // The device has announced that its key is compromised
+        let replacement = generate_identity_keys();
+        let revocations = Arc::new(RevocationSet::new());
+        revocations
+            .insert(issue_revocation(
+                &device_keys.id32,
+                &device_keys.sign,
+                &replacement.sign,
+                ReasonV1::Compromised,
+                0,
+                0,
+            ))
+            .unwrap();
+        controller.set_revocations(revocations);
+
+        let device_sign_pub = device_keys.sign_pub.key_bytes.clone();
+        let result = controller.handle_response(response.clone(), &device_sign_pub).await;
+        assert!(matches!(result, Err(SessionError::KeyRevoked(_))));
+        assert!(matches!(controller.state(), SessionControllerState::RequestSent { .. }));
+
+        // The same response is accepted when the key is not revoked
+        controller.set_revocations(Arc::new(RevocationSet::new()));
+        controller.handle_response(response, &device_sign_pub).await.unwrap();
+        assert!(matches!(controller.state(), SessionControllerState::TicketReceived { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_session_controller_rejects_stripped_negotiation() {
+        let operator_keys = generate_identity_keys();
+        let device_keys = generate_identity_keys();
+        let pairing = make_test_pairing(&device_keys.id32, &operator_keys.id32);
+
+        let controller_store = Arc::new(InMemoryStore::new());
+        controller_store.save_pairing(pairing.clone()).await.unwrap();
+        let host_store = Arc::new(InMemoryStore::new());
+        host_store.save_pairing(pairing).await.unwrap();
+
+        let mut controller = SessionController::new(operator_keys, controller_store);
+        let mut host = SessionHost::new(
+            device_keys.clone(),
+            host_store,
+            Arc::new(PolicyEngine::new(ConsentMode::AlwaysRequire)),
+            Arc::new(AlwaysApproveSession),
+        );
+        let device_sign_pub = device_keys.sign_pub.key_bytes.clone();
+
+        // A MITM strips the direct transport from the offer the device sees
+        let mut request = controller.start_session(&device_keys.id32, 0x03).await.unwrap();
+        request.transport_preference = TransportPreferenceV1::RelayOnly as i32;
+        host.handle_request(request).await.unwrap();
+        let stripped = host.approve().await.unwrap();
+
+        let result = controller.handle_response(stripped.clone(), &device_sign_pub).await;
+        assert!(matches!(result, Err(SessionError::DowngradeDetected(_))));
+        assert!(matches!(controller.state(), SessionControllerState::RequestSent { .. }));
+
+        // The unmodified offers verify
+        let request = match controller.state() {
+            SessionControllerState::RequestSent { request, .. } => request.clone(),
+            _ => unreachable!(),
+        };
+        host.reset();
+        host.handle_request(request).await.unwrap();
+        let response = host.approve().await.unwrap();
+        controller.handle_response(response, &device_sign_pub).await.unwrap();
+        assert!(matches!(controller.state(), SessionControllerState::TicketReceived { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_session_controller_audits_transport_selection() {
+        use crate::audit::{AuditEvent, MemoryAuditSink};
+
+        let operator_keys = generate_identity_keys();
+        let device_keys = generate_identity_keys();
+        let pairing = make_test_pairing(&device_keys.id32, &operator_keys.id32);
+
+        let controller_store = Arc::new(InMemoryStore::new());
+        controller_store.save_pairing(pairing.clone()).await.unwrap();
+        let host_store = Arc::new(InMemoryStore::new());
+        host_store.save_pairing(pairing).await.unwrap();
+
+        let sink = Arc::new(MemoryAuditSink::new(10));
+        let mut controller = SessionController::new(operator_keys, controller_store)
+            .with_audit_sink(sink.clone());
+        let mut host = SessionHost::new(
+            device_keys.clone(),
+            host_store,
+            Arc::new(PolicyEngine::new(ConsentMode::AlwaysRequire)),
+            Arc::new(AlwaysApproveSession),
+        );
+
+        let request = controller.start_session(&device_keys.id32, 0x03).await.unwrap();
+        host.handle_request(request).await.unwrap();
+        let response = host.approve().await.unwrap();
+        controller
+            .handle_response(response, &device_keys.sign_pub.key_bytes)
+            .await
+            .unwrap();
+        // The host offers a direct QUIC endpoint
+        if let SessionControllerState::TicketReceived { transport_params, .. } = &mut controller.state {
+            transport_params.get_or_insert_with(Default::default).quic_params =
+                Some(zrc_proto::v1::QuicParamsV1 {
+                    server_cert_der: vec![1u8; 32],
+                    ..Default::default()
+                });
+        }
+        controller.initiate_connection().await.unwrap();
+
+        let events = sink.events().await;
+        assert_eq!(events.len(), 1);
+        match &events[0] {
+            AuditEvent::TransportSelected { device_id, transport, .. } => {
+                assert_eq!(device_id, &device_keys.id32);
+                assert_eq!(
+                    transport,
+                    controller.transport_selection().unwrap().transport_type.as_str()
+                );
+            }
+            other => panic!("unexpected event {:?}", other.event_type()),
+        }
+    }
+
     #[tokio::test]
     async fn test_session_controller_start_session_invalid_device_id() {
         let operator_keys = generate_identity_keys();
@@ -7,12 +7,17 @@
 
 use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
 
 use async_trait::async_trait;
 use rusqlite::{params, Connection, OptionalExtension};
 use tokio::sync::Mutex;
+use tracing::warn;
 
-use crate::store::{InviteRecord, PairingRecord, Store, StoreError, TicketRecord};
+use crate::store::{
+    InviteRecord, PairingRecord, ReplayEntryRecord, ReplayStateRecord, Store, StoreError,
+    TicketRecord,
+};
 use zrc_proto::v1::PublicKeyV1;
 
 // ============================================================================
@@ -22,7 +27,120 @@ use zrc_proto::v1::PublicKeyV1;
 /// Current schema version for migrations.
 /// Increment this when adding new migrations.
 #[allow(dead_code)]
-const SCHEMA_VERSION: i32 = 1;
+const SCHEMA_VERSION: i32 = 2;
+
+// ============================================================================
+// Connection Configuration
+// ============================================================================
+
+/// SQLite journal mode (`PRAGMA journal_mode`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SqliteJournalMode {
+    /// Write-ahead log. Readers never block the writer and a single writer
+    /// never blocks readers; commits append to the `-wal` file, which is
+    /// checkpointed back into the database periodically.
+    Wal,
+    /// Rollback journal deleted at the end of each transaction (SQLite default).
+    /// Writers take an exclusive lock that blocks all readers.
+    Delete,
+    /// Rollback journal truncated instead of deleted.
+    Truncate,
+    /// Rollback journal kept in memory. A crash mid-transaction can corrupt
+    /// the database.
+    Memory,
+}
+
+impl SqliteJournalMode {
+    fn as_pragma(self) -> &'static str {
+        match self {
+            Self::Wal => "WAL",
+            Self::Delete => "DELETE",
+            Self::Truncate => "TRUNCATE",
+            Self::Memory => "MEMORY",
+        }
+    }
+}
+
+/// SQLite sync level (`PRAGMA synchronous`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SqliteSynchronous {
+    /// No fsync. A power loss or OS crash can corrupt the database.
+    Off,
+    /// Fsync at checkpoints only. In WAL mode the database stays consistent,
+    /// but the last committed transactions can be lost on power loss.
+    Normal,
+    /// Fsync on every commit. No committed transaction is lost on power loss.
+    Full,
+    /// As `Full`, and also syncs the directory after deleting the journal.
+    Extra,
+}
+
+impl SqliteSynchronous {
+    fn as_pragma(self) -> &'static str {
+        match self {
+            Self::Off => "OFF",
+            Self::Normal => "NORMAL",
+            Self::Full => "FULL",
+            Self::Extra => "EXTRA",
+        }
+    }
+}
+
+/// Connection settings applied as PRAGMAs when a [`SqliteStore`] is opened.
+///
+/// The default (WAL, `NORMAL`, 5 second busy timeout) suits a host serving
+/// several concurrent sessions: writers don't block readers, and a writer
+/// waiting on another connection retries instead of failing immediately with
+/// `database is locked`. The cost is that a power loss may drop the most
+/// recent commits; use [`SqliteSynchronous::Full`] when every pairing and
+/// ticket write must survive that.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SqliteConfig {
+    /// Journal mode
+    pub journal_mode: SqliteJournalMode,
+    /// Sync level
+    pub synchronous: SqliteSynchronous,
+    /// How long a connection waits for a lock held by another connection
+    /// before failing with `SQLITE_BUSY`
+    pub busy_timeout: Duration,
+}
+
+impl Default for SqliteConfig {
+    fn default() -> Self {
+        Self {
+            journal_mode: SqliteJournalMode::Wal,
+            synchronous: SqliteSynchronous::Normal,
+            busy_timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+impl SqliteConfig {
+    /// Apply these settings to an open connection.
+    fn apply(&self, conn: &Connection) -> Result<(), StoreError> {
+        conn.busy_timeout(self.busy_timeout).map_err(|e| {
+            StoreError::OperationFailed(format!("failed to set busy timeout: {}", e))
+        })?;
+
+        // journal_mode returns the resulting mode, which may differ from the
+        // requested one (e.g. in-memory databases only support MEMORY/OFF)
+        let requested = self.journal_mode.as_pragma();
+        let mode: String = conn
+            .query_row(&format!("PRAGMA journal_mode={}", requested), [], |row| {
+                row.get(0)
+            })
+            .map_err(|e| {
+                StoreError::OperationFailed(format!("failed to set journal mode: {}", e))
+            })?;
+        if !mode.eq_ignore_ascii_case(requested) {
+            warn!("SQLite journal mode {} unavailable, using {}", requested, mode);
+        }
+
+        conn.execute_batch(&format!("PRAGMA synchronous={};", self.synchronous.as_pragma()))
+            .map_err(|e| StoreError::OperationFailed(format!("failed to set pragmas: {}", e)))?;
+        Ok(())
+    }
+}
 
 // ============================================================================
 // SQLite Store Implementation
@@ -45,6 +163,7 @@ impl SqliteStore {
     /// Create a new SQLite store at the specified path.
     ///
     /// Creates the database file if it doesn't exist and runs migrations.
+    /// Uses [`SqliteConfig::default`].
     ///
     /// # Arguments
     /// * `path` - Path to the SQLite database file
@@ -53,13 +172,25 @@ impl SqliteStore {
     /// * `Ok(SqliteStore)` on success
     /// * `Err(StoreError)` if database creation or migration fails
     pub fn new<P: AsRef<Path>>(path: P) -> Result<Self, StoreError> {
+        Self::with_config(path, SqliteConfig::default())
+    }
+
+    /// Create a new SQLite store at the specified path with explicit
+    /// connection settings.
+    ///
+    /// # Arguments
+    /// * `path` - Path to the SQLite database file
+    /// * `config` - Journal mode, sync level and busy timeout
+    ///
+    /// # Returns
+    /// * `Ok(SqliteStore)` on success
+    /// * `Err(StoreError)` if database creation or migration fails
+    pub fn with_config<P: AsRef<Path>>(path: P, config: SqliteConfig) -> Result<Self, StoreError> {
         let conn = Connection::open(path).map_err(|e| {
             StoreError::OperationFailed(format!("failed to open database: {}", e))
         })?;
 
-        // Enable WAL mode for better concurrent access
-        conn.execute_batch("PRAGMA journal_mode=WAL; PRAGMA synchronous=NORMAL;")
-            .map_err(|e| StoreError::OperationFailed(format!("failed to set pragmas: {}", e)))?;
+        config.apply(&conn)?;
 
         // Run migrations synchronously during construction (before wrapping in Mutex)
         Self::run_migrations(&conn)?;
@@ -109,6 +240,9 @@ impl SqliteStore {
         if current_version < 1 {
             Self::migrate_v1(conn)?;
         }
+        if current_version < 2 {
+            Self::migrate_v2(conn)?;
+        }
 
         Ok(())
     }
@@ -172,6 +306,31 @@ impl SqliteStore {
         Ok(())
     }
 
+    /// Migration to schema version 2 - persisted replay-guard state.
+    fn migrate_v2(conn: &Connection) -> Result<(), StoreError> {
+        conn.execute_batch(
+            r#"
+            -- Single-row replay high-water mark
+            CREATE TABLE IF NOT EXISTS replay_state (
+                id INTEGER PRIMARY KEY CHECK (id = 1),
+                high_water INTEGER NOT NULL
+            );
+
+            -- Recently seen nonce hashes; seq preserves insertion order
+            CREATE TABLE IF NOT EXISTS replay_nonces (
+                seq INTEGER PRIMARY KEY,
+                nonce_hash BLOB NOT NULL,
+                timestamp INTEGER NOT NULL
+            );
+
+            INSERT INTO schema_version (version) VALUES (2);
+            "#,
+        )
+        .map_err(|e| StoreError::OperationFailed(format!("migration v2 failed: {}", e)))?;
+
+        Ok(())
+    }
+
 
     // -------------------------------------------------------------------------
     // Helper methods for serialization
@@ -505,6 +664,64 @@ impl Store for SqliteStore {
             })?;
         Ok(result.is_some())
     }
+
+    // -------------------------------------------------------------------------
+    // Replay State Operations
+    // -------------------------------------------------------------------------
+
+    async fn save_replay_state(&self, state: ReplayStateRecord) -> Result<(), StoreError> {
+        let conn = self.conn.lock().await;
+        let tx = conn.unchecked_transaction().map_err(|e| {
+            StoreError::OperationFailed(format!("failed to begin transaction: {}", e))
+        })?;
+        tx.execute(
+            "INSERT OR REPLACE INTO replay_state (id, high_water) VALUES (1, ?1)",
+            params![state.high_water as i64],
+        )
+        .map_err(|e| StoreError::OperationFailed(format!("failed to save replay state: {}", e)))?;
+        tx.execute("DELETE FROM replay_nonces", [])
+            .map_err(|e| StoreError::OperationFailed(format!("failed to clear replay nonces: {}", e)))?;
+        {
+            let mut stmt = tx
+                .prepare("INSERT INTO replay_nonces (nonce_hash, timestamp) VALUES (?1, ?2)")
+                .map_err(|e| StoreError::OperationFailed(format!("failed to prepare query: {}", e)))?;
+            for entry in &state.entries {
+                stmt.execute(params![entry.nonce_hash, entry.timestamp as i64]).map_err(|e| {
+                    StoreError::OperationFailed(format!("failed to save replay nonce: {}", e))
+                })?;
+            }
+        }
+        tx.commit()
+            .map_err(|e| StoreError::OperationFailed(format!("failed to commit replay state: {}", e)))?;
+        Ok(())
+    }
+
+    async fn load_replay_state(&self) -> Result<Option<ReplayStateRecord>, StoreError> {
+        let conn = self.conn.lock().await;
+        let high_water: Option<i64> = conn
+            .query_row("SELECT high_water FROM replay_state WHERE id = 1", [], |row| row.get(0))
+            .optional()
+            .map_err(|e| StoreError::OperationFailed(format!("failed to load replay state: {}", e)))?;
+        let Some(high_water) = high_water else {
+            return Ok(None);
+        };
+
+        let mut stmt = conn
+            .prepare("SELECT nonce_hash, timestamp FROM replay_nonces ORDER BY seq")
+            .map_err(|e| StoreError::OperationFailed(format!("failed to prepare query: {}", e)))?;
+        let entries = stmt
+            .query_map([], |row| {
+                Ok(ReplayEntryRecord {
+                    nonce_hash: row.get(0)?,
+                    timestamp: row.get::<_, i64>(1)? as u64,
+                })
+            })
+            .map_err(|e| StoreError::OperationFailed(format!("failed to load replay nonces: {}", e)))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| StoreError::OperationFailed(format!("failed to collect replay nonces: {}", e)))?;
+
+        Ok(Some(ReplayStateRecord { high_water: high_water as u64, entries }))
+    }
 }
 
 // ============================================================================
@@ -896,6 +1113,117 @@ mod tests {
     // Serialization Tests
     // -------------------------------------------------------------------------
 
+    // -------------------------------------------------------------------------
+    // Connection Configuration Tests
+    // -------------------------------------------------------------------------
+
+    /// Database path in the temp dir, removed (with WAL side files) on drop.
+    struct TempDb(std::path::PathBuf);
+
+    impl TempDb {
+        fn new(name: &str) -> Self {
+            let nanos = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos();
+            Self(std::env::temp_dir().join(format!(
+                "zrc-{}-{}-{}.db",
+                name,
+                std::process::id(),
+                nanos
+            )))
+        }
+    }
+
+    impl Drop for TempDb {
+        fn drop(&mut self) {
+            for suffix in ["", "-wal", "-shm"] {
+                let mut path = self.0.clone().into_os_string();
+                path.push(suffix);
+                let _ = std::fs::remove_file(path);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_replay_state_survives_reopen() {
+        use crate::replay::{ReplayGuard, ReplayGuardConfig, ReplayGuardError};
+
+        let db = TempDb::new("replay");
+        {
+            let store = SqliteStore::new(&db.0).unwrap();
+            assert!(store.load_replay_state().await.unwrap().is_none());
+            let mut guard = ReplayGuard::load(&store, ReplayGuardConfig::default()).await.unwrap();
+            guard.accept(b"nonce-a", 1000, 1000).unwrap();
+            guard.accept(b"nonce-b", 1001, 1001).unwrap();
+            guard.persist(&store).await.unwrap();
+        }
+
+        let store = SqliteStore::new(&db.0).unwrap();
+        let record = store.load_replay_state().await.unwrap().unwrap();
+        assert_eq!(record.high_water, 1001);
+        assert_eq!(record.entries.len(), 2);
+
+        let mut guard = ReplayGuard::load(&store, ReplayGuardConfig::default()).await.unwrap();
+        assert_eq!(guard.accept(b"nonce-a", 1002, 1002), Err(ReplayGuardError::Duplicate));
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_config_applied() {
+        let db = TempDb::new("pragmas");
+        let config = SqliteConfig {
+            journal_mode: SqliteJournalMode::Wal,
+            synchronous: SqliteSynchronous::Full,
+            busy_timeout: Duration::from_millis(1500),
+        };
+        let store = SqliteStore::with_config(&db.0, config).unwrap();
+
+        let conn = store.conn.lock().await;
+        let mode: String = conn.query_row("PRAGMA journal_mode", [], |r| r.get(0)).unwrap();
+        let sync: i64 = conn.query_row("PRAGMA synchronous", [], |r| r.get(0)).unwrap();
+        let timeout: i64 = conn.query_row("PRAGMA busy_timeout", [], |r| r.get(0)).unwrap();
+        assert_eq!(mode.to_lowercase(), "wal");
+        assert_eq!(sync, 2); // FULL
+        assert_eq!(timeout, 1500);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_sqlite_concurrent_writers_do_not_lock() {
+        const STORES: usize = 4;
+        const WRITES: usize = 25;
+
+        let db = TempDb::new("concurrent");
+        // Separate connections to one file contend for the write lock the way
+        // separate processes (agent, CLI) sharing a database do
+        let stores: Vec<Arc<SqliteStore>> = (0..STORES)
+            .map(|_| Arc::new(SqliteStore::new(&db.0).unwrap()))
+            .collect();
+
+        let mut tasks = Vec::new();
+        for (s, store) in stores.iter().enumerate() {
+            let store = Arc::clone(store);
+            tasks.push(tokio::spawn(async move {
+                for i in 0..WRITES {
+                    let id = (s * WRITES + i) as u8;
+                    let mut pairing = make_test_pairing(&[1u8; 32], &[id; 32]);
+                    pairing.pairing_id = vec![id; 16];
+                    store.save_pairing(pairing).await?;
+                    store.save_ticket(make_test_ticket(&[id; 16], 5000)).await?;
+                    store.update_pairing_last_session(&[1u8; 32], &[id; 32], 2000 + i as u64).await?;
+                }
+                Ok::<_, StoreError>(())
+            }));
+        }
+
+        for task in tasks {
+            task.await.unwrap().expect("concurrent write failed");
+        }
+
+        let pairings = stores[0].list_pairings().await.unwrap();
+        assert_eq!(pairings.len(), STORES * WRITES);
+        assert!(pairings.iter().all(|p| p.last_session.is_some()));
+    }
+
     #[test]
     fn test_perms_serialization_roundtrip() {
         let perms = vec![1, 2, 3, 4, 5];
@@ -9,6 +9,7 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use tokio::sync::RwLock;
 
@@ -44,7 +45,7 @@ pub enum StoreError {
 
 /// Record for storing invite data with its secret.
 /// Requirements: 8.2
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct InviteRecord {
     /// Device identifier (32 bytes)
     pub device_id: Vec<u8>,
@@ -57,7 +58,7 @@ pub struct InviteRecord {
 
 /// Record for storing pairing data between device and operator.
 /// Requirements: 8.1, 8.6
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct PairingRecord {
     /// Unique pairing identifier (16 bytes)
     pub pairing_id: Vec<u8>,
@@ -67,13 +68,17 @@ pub struct PairingRecord {
     pub operator_id: Vec<u8>,
 
     /// Device's Ed25519 signing public key
+    #[serde(with = "public_key_serde")]
     pub device_sign_pub: PublicKeyV1,
     /// Device's X25519 key exchange public key
+    #[serde(with = "public_key_serde")]
     pub device_kex_pub: PublicKeyV1,
 
     /// Operator's Ed25519 signing public key
+    #[serde(with = "public_key_serde")]
     pub operator_sign_pub: PublicKeyV1,
     /// Operator's X25519 key exchange public key
+    #[serde(with = "public_key_serde")]
     pub operator_kex_pub: PublicKeyV1,
 
     /// Granted permissions as i32 values (PermissionV1 enum)
@@ -89,9 +94,21 @@ pub struct PairingRecord {
     pub last_session: Option<u64>,
 }
 
+impl PairingRecord {
+    /// Granted permissions as a `PermissionsV1` bitmask.
+    ///
+    /// `granted_perms` holds bit positions, as interpreted by `SessionHost`.
+    pub fn permission_bits(&self) -> u32 {
+        self.granted_perms
+            .iter()
+            .filter_map(|p| 1u32.checked_shl(*p as u32))
+            .fold(0, |acc, bit| acc | bit)
+    }
+}
+
 /// Record for storing session ticket data.
 /// Requirements: 8.3
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct TicketRecord {
     /// Unique ticket identifier (16 bytes)
     pub ticket_id: Vec<u8>,
@@ -113,6 +130,45 @@ pub struct TicketRecord {
     pub issued_at: u64,
 }
 
+/// Persisted state of a `ReplayGuard` (see `crate::replay`).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReplayStateRecord {
+    /// Newest message timestamp the guard has accepted (Unix seconds)
+    pub high_water: u64,
+    /// Recently seen nonces, oldest first
+    pub entries: Vec<ReplayEntryRecord>,
+}
+
+/// One remembered nonce in a `ReplayStateRecord`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ReplayEntryRecord {
+    /// SHA-256 of the nonce (32 bytes)
+    pub nonce_hash: Vec<u8>,
+    /// Timestamp of the message that carried the nonce (Unix seconds)
+    pub timestamp: u64,
+}
+
+/// Serde for the prost `PublicKeyV1`, which has no serde derives.
+mod public_key_serde {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use zrc_proto::v1::PublicKeyV1;
+
+    #[derive(Serialize, Deserialize)]
+    struct PublicKey {
+        key_type: i32,
+        key_bytes: Vec<u8>,
+    }
+
+    pub fn serialize<S: Serializer>(key: &PublicKeyV1, serializer: S) -> Result<S::Ok, S::Error> {
+        PublicKey { key_type: key.key_type, key_bytes: key.key_bytes.clone() }.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<PublicKeyV1, D::Error> {
+        let key = PublicKey::deserialize(deserializer)?;
+        Ok(PublicKeyV1 { key_type: key.key_type, key_bytes: key.key_bytes })
+    }
+}
+
 impl From<&SessionTicketV1> for TicketRecord {
     fn from(ticket: &SessionTicketV1) -> Self {
         Self {
@@ -334,13 +390,118 @@ pub trait Store: Send + Sync {
         ticket_id: &[u8],
         current_time: u64,
     ) -> Result<bool, StoreError>;
+
+    // -------------------------------------------------------------------------
+    // Replay State Operations
+    // -------------------------------------------------------------------------
+
+    /// Replace the persisted replay-guard state.
+    ///
+    /// The default discards the state, so stores that don't override this
+    /// only protect against replays until restart.
+    ///
+    /// # Returns
+    /// * `Ok(())` on success
+    /// * `Err(StoreError)` if the operation fails
+    async fn save_replay_state(&self, state: ReplayStateRecord) -> Result<(), StoreError> {
+        let _ = state;
+        Ok(())
+    }
+
+    /// Load the persisted replay-guard state.
+    ///
+    /// The default has nothing saved.
+    ///
+    /// # Returns
+    /// * `Ok(Some(state))` if state was saved before
+    /// * `Ok(None)` if nothing was saved yet
+    /// * `Err(StoreError)` if the operation fails
+    async fn load_replay_state(&self) -> Result<Option<ReplayStateRecord>, StoreError> {
+        Ok(None)
+    }
 }
 
 
+// ============================================================================
+// Snapshots
+// ============================================================================
+
+/// Current `StoreSnapshot::version`.
+pub const STORE_SNAPSHOT_VERSION: u32 = 1;
+
+/// Serialized contents of a store: every invite, pairing and ticket
+/// (including revoked tickets), sorted by key so snapshots diff cleanly.
+///
+/// Used to seed tests with a known state and to move data between store
+/// backends (see `restore_into`).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StoreSnapshot {
+    /// Format version; `import_snapshot` rejects versions it does not know
+    pub version: u32,
+    pub invites: Vec<InviteRecord>,
+    pub pairings: Vec<PairingRecord>,
+    pub tickets: Vec<TicketRecord>,
+}
+
+impl Default for StoreSnapshot {
+    fn default() -> Self {
+        Self {
+            version: STORE_SNAPSHOT_VERSION,
+            invites: Vec::new(),
+            pairings: Vec::new(),
+            tickets: Vec::new(),
+        }
+    }
+}
+
+impl StoreSnapshot {
+    /// Serialize to pretty-printed JSON.
+    pub fn to_json(&self) -> Result<String, StoreError> {
+        serde_json::to_string_pretty(self).map_err(|e| StoreError::Serialization(e.to_string()))
+    }
+
+    /// Parse a snapshot, rejecting unsupported versions.
+    pub fn from_json(json: &str) -> Result<Self, StoreError> {
+        let snapshot: Self =
+            serde_json::from_str(json).map_err(|e| StoreError::Serialization(e.to_string()))?;
+        snapshot.check_version()?;
+        Ok(snapshot)
+    }
+
+    fn check_version(&self) -> Result<(), StoreError> {
+        if self.version != STORE_SNAPSHOT_VERSION {
+            return Err(StoreError::Serialization(format!(
+                "unsupported snapshot version {} (expected {})",
+                self.version, STORE_SNAPSHOT_VERSION
+            )));
+        }
+        Ok(())
+    }
+
+    /// Save every record into `store`, e.g. to migrate from memory to SQLite.
+    /// Existing records with the same keys are overwritten.
+    pub async fn restore_into(&self, store: &dyn Store) -> Result<(), StoreError> {
+        self.check_version()?;
+        for invite in &self.invites {
+            store.save_invite(invite.clone()).await?;
+        }
+        for pairing in &self.pairings {
+            store.save_pairing(pairing.clone()).await?;
+        }
+        for ticket in &self.tickets {
+            store.save_ticket(ticket.clone()).await?;
+        }
+        Ok(())
+    }
+}
+
 // ============================================================================
 // In-Memory Store Implementation
 // ============================================================================
 
+/// Pairing map key: (device_id, operator_id)
+type PairingKey = (Vec<u8>, Vec<u8>);
+
 /// Thread-safe in-memory store implementation for testing and MVP.
 ///
 /// Uses `RwLock` for concurrent access with multiple readers or single writer.
@@ -351,9 +512,11 @@ pub struct InMemoryStore {
     /// Invites indexed by device_id
     invites: Arc<RwLock<HashMap<Vec<u8>, InviteRecord>>>,
     /// Pairings indexed by (device_id, operator_id)
-    pairings: Arc<RwLock<HashMap<(Vec<u8>, Vec<u8>), PairingRecord>>>,
+    pairings: Arc<RwLock<HashMap<PairingKey, PairingRecord>>>,
     /// Tickets indexed by ticket_id
     tickets: Arc<RwLock<HashMap<Vec<u8>, TicketRecord>>>,
+    /// Last saved replay-guard state
+    replay_state: Arc<RwLock<Option<ReplayStateRecord>>>,
 }
 
 /// Type alias for backward compatibility with existing code
@@ -366,6 +529,7 @@ impl InMemoryStore {
             invites: Arc::new(RwLock::new(HashMap::new())),
             pairings: Arc::new(RwLock::new(HashMap::new())),
             tickets: Arc::new(RwLock::new(HashMap::new())),
+            replay_state: Arc::new(RwLock::new(None)),
         }
     }
 
@@ -422,6 +586,48 @@ impl InMemoryStore {
         self.get_pairing(device_id, operator_id).await.is_some()
     }
 
+    /// Export every record as a versioned snapshot.
+    pub async fn export_snapshot(&self) -> StoreSnapshot {
+        let mut invites: Vec<_> = self.invites.read().await.values().cloned().collect();
+        invites.sort_by(|a, b| a.device_id.cmp(&b.device_id));
+
+        let mut pairings: Vec<_> = self.pairings.read().await.values().cloned().collect();
+        pairings.sort_by(|a, b| {
+            (&a.device_id, &a.operator_id).cmp(&(&b.device_id, &b.operator_id))
+        });
+
+        let mut tickets: Vec<_> = self.tickets.read().await.values().cloned().collect();
+        tickets.sort_by(|a, b| a.ticket_id.cmp(&b.ticket_id));
+
+        StoreSnapshot { version: STORE_SNAPSHOT_VERSION, invites, pairings, tickets }
+    }
+
+    /// Replace the store's contents with `snapshot`.
+    pub async fn import_snapshot(&self, snapshot: StoreSnapshot) -> Result<(), StoreError> {
+        snapshot.check_version()?;
+
+        let mut invites = self.invites.write().await;
+        let mut pairings = self.pairings.write().await;
+        let mut tickets = self.tickets.write().await;
+
+        *invites = snapshot
+            .invites
+            .into_iter()
+            .map(|i| (i.device_id.clone(), i))
+            .collect();
+        *pairings = snapshot
+            .pairings
+            .into_iter()
+            .map(|p| ((p.device_id.clone(), p.operator_id.clone()), p))
+            .collect();
+        *tickets = snapshot
+            .tickets
+            .into_iter()
+            .map(|t| (t.ticket_id.clone(), t))
+            .collect();
+        Ok(())
+    }
+
     /// Legacy: Get a ticket (backward compatible with old API)
     /// Returns Option directly instead of Result
     pub async fn get_ticket(&self, ticket_id: &[u8]) -> Option<TicketRecord> {
@@ -576,6 +782,19 @@ impl Store for InMemoryStore {
             None => Ok(false),
         }
     }
+
+    // -------------------------------------------------------------------------
+    // Replay State Operations
+    // -------------------------------------------------------------------------
+
+    async fn save_replay_state(&self, state: ReplayStateRecord) -> Result<(), StoreError> {
+        *self.replay_state.write().await = Some(state);
+        Ok(())
+    }
+
+    async fn load_replay_state(&self) -> Result<Option<ReplayStateRecord>, StoreError> {
+        Ok(self.replay_state.read().await.clone())
+    }
 }
 
 
@@ -890,6 +1109,68 @@ mod tests {
         assert!(store.get_ticket(&[3u8; 16]).await.is_some());
     }
 
+    // -------------------------------------------------------------------------
+    // Snapshot Tests
+    // -------------------------------------------------------------------------
+
+    #[tokio::test]
+    async fn test_snapshot_empty_store() {
+        let store = InMemoryStore::new();
+        let snapshot = store.export_snapshot().await;
+        assert_eq!(snapshot, StoreSnapshot::default());
+
+        let restored = StoreSnapshot::from_json(&snapshot.to_json().unwrap()).unwrap();
+        assert_eq!(restored, snapshot);
+
+        let other = InMemoryStore::new();
+        other.put_invite(make_test_invite(&[9u8; 32], 2000)).await;
+        other.import_snapshot(restored).await.unwrap();
+        assert!(other.get_invite(&[9u8; 32]).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_round_trip_populated() {
+        let store = InMemoryStore::new();
+        store.put_invite(make_test_invite(&[2u8; 32], 2000)).await;
+        store.put_invite(make_test_invite(&[1u8; 32], 3000)).await;
+        store.put_pairing(make_test_pairing(&[1u8; 32], &[2u8; 32])).await;
+        store.put_pairing(make_test_pairing(&[3u8; 32], &[4u8; 32])).await;
+        store.update_pairing_last_session(&[3u8; 32], &[4u8; 32], 5000).await.unwrap();
+        store.save_ticket(make_test_ticket(&[1u8; 16], 2000)).await.unwrap();
+        store.save_ticket(make_test_ticket(&[2u8; 16], 2000)).await.unwrap();
+        store.revoke_ticket(&[2u8; 16]).await.unwrap();
+
+        let snapshot = store.export_snapshot().await;
+        assert_eq!(snapshot.invites.len(), 2);
+        assert_eq!(snapshot.invites[0].device_id, vec![1u8; 32]);
+        assert_eq!(snapshot.pairings.len(), 2);
+        assert_eq!(snapshot.tickets.len(), 2);
+        assert!(snapshot.tickets[1].revoked);
+
+        let json = snapshot.to_json().unwrap();
+        let restored = InMemoryStore::new();
+        restored.import_snapshot(StoreSnapshot::from_json(&json).unwrap()).await.unwrap();
+        assert_eq!(restored.export_snapshot().await, snapshot);
+
+        let pairing = restored.get_pairing(&[3u8; 32], &[4u8; 32]).await.unwrap();
+        assert_eq!(pairing.last_session, Some(5000));
+        assert_eq!(pairing.device_kex_pub, make_test_pairing(&[3u8; 32], &[4u8; 32]).device_kex_pub);
+        assert!(restored.get_ticket(&[2u8; 16]).await.is_none());
+
+        // Another backend through the Store trait
+        let migrated = InMemoryStore::new();
+        snapshot.restore_into(&migrated).await.unwrap();
+        assert_eq!(migrated.export_snapshot().await, snapshot);
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_rejects_unknown_version() {
+        let snapshot = StoreSnapshot { version: STORE_SNAPSHOT_VERSION + 1, ..Default::default() };
+        let json = snapshot.to_json().unwrap();
+        assert!(matches!(StoreSnapshot::from_json(&json), Err(StoreError::Serialization(_))));
+        assert!(InMemoryStore::new().import_snapshot(snapshot).await.is_err());
+    }
+
     // -------------------------------------------------------------------------
     // Helper Function Tests
     // -------------------------------------------------------------------------
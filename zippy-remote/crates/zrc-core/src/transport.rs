@@ -12,8 +12,16 @@
 //!
 //! The negotiator respects policy restrictions on allowed transports (Requirement 7.7).
 //! Transports can be explicitly allowed or denied via `AllowedTransports`.
+//!
+//! # Operator Ordering
+//!
+//! A `TransportPolicy` replaces the default order with an operator-configured
+//! global or per-device ordering and can disable transports outright. The
+//! negotiator reports the chosen transport together with the fallback trail
+//! (`TransportSelection`) so callers can audit why preferred transports were
+//! skipped.
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use thiserror::Error;
 
 /// Errors from transport negotiation.
@@ -59,6 +67,16 @@ impl TransportType {
         }
     }
 
+    /// Lowercase name used in logs and audit events.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TransportType::Mesh => "mesh",
+            TransportType::Direct => "direct",
+            TransportType::Rendezvous => "rendezvous",
+            TransportType::Relay => "relay",
+        }
+    }
+
     /// Get all transport types in default priority order.
     pub fn all_in_priority_order() -> Vec<TransportType> {
         vec![
@@ -202,6 +220,120 @@ impl TransportPreferences {
     }
 }
 
+/// Operator-configured transport ordering.
+///
+/// Only transports listed in the applicable ordering are tried, in that
+/// order; disabled transports are never selected even if the peer offers
+/// them.
+#[derive(Debug, Clone)]
+pub struct TransportPolicy {
+    /// Global ordering.
+    order: Vec<TransportType>,
+    /// Per-device orderings keyed by device_id; override `order`.
+    device_orders: HashMap<Vec<u8>, Vec<TransportType>>,
+    /// Transports that must never be selected.
+    disabled: HashSet<TransportType>,
+}
+
+impl Default for TransportPolicy {
+    fn default() -> Self {
+        Self {
+            order: TransportType::all_in_priority_order(),
+            device_orders: HashMap::new(),
+            disabled: HashSet::new(),
+        }
+    }
+}
+
+impl TransportPolicy {
+    /// Create a policy with the given global ordering.
+    pub fn with_order(order: Vec<TransportType>) -> Self {
+        Self {
+            order,
+            ..Self::default()
+        }
+    }
+
+    /// Set the ordering used for one device.
+    pub fn set_device_order(&mut self, device_id: &[u8], order: Vec<TransportType>) {
+        self.device_orders.insert(device_id.to_vec(), order);
+    }
+
+    /// Never select `transport`.
+    pub fn disable(&mut self, transport: TransportType) {
+        self.disabled.insert(transport);
+    }
+
+    /// Re-enable a disabled transport.
+    pub fn enable(&mut self, transport: TransportType) {
+        self.disabled.remove(&transport);
+    }
+
+    /// Check if a transport is enabled.
+    pub fn is_enabled(&self, transport: TransportType) -> bool {
+        !self.disabled.contains(&transport)
+    }
+
+    /// Ordering for `device_id`, falling back to the global ordering.
+    pub fn order_for(&self, device_id: Option<&[u8]>) -> &[TransportType] {
+        device_id
+            .and_then(|id| self.device_orders.get(id))
+            .unwrap_or(&self.order)
+    }
+}
+
+/// Why negotiation passed over a transport.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipReason {
+    /// Disabled by the operator's `TransportPolicy`.
+    DisabledByPolicy,
+    /// Not allowed by preferences or policy restrictions (Requirement 7.7).
+    NotAllowed,
+    /// The peer did not offer it.
+    NotOfferedByPeer,
+    /// The peer offered it without QUIC parameters.
+    MissingQuicParams,
+    /// No unexpired relay token was offered.
+    NoValidRelayToken,
+}
+
+impl SkipReason {
+    /// Lowercase name used in logs and audit events.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SkipReason::DisabledByPolicy => "disabled_by_policy",
+            SkipReason::NotAllowed => "not_allowed",
+            SkipReason::NotOfferedByPeer => "not_offered_by_peer",
+            SkipReason::MissingQuicParams => "missing_quic_params",
+            SkipReason::NoValidRelayToken => "no_valid_relay_token",
+        }
+    }
+}
+
+/// Outcome of a negotiation: the chosen transport and the higher-preference
+/// transports skipped on the way, in order.
+#[derive(Debug, Clone)]
+pub struct TransportSelection {
+    /// Type of the chosen transport.
+    pub transport_type: TransportType,
+    /// Chosen transport with its connection parameters.
+    pub selected: SelectedTransport,
+    /// Transports tried before the chosen one and why each was skipped.
+    pub skipped: Vec<(TransportType, SkipReason)>,
+}
+
+impl TransportSelection {
+    /// Fallback trail as `type:reason` pairs, e.g.
+    /// `mesh:not_offered_by_peer,direct:disabled_by_policy`.
+    pub fn fallback_trail(&self) -> String {
+        self.skipped
+            .iter()
+            .map(|(t, reason)| format!("{}:{}", t.as_str(), reason.as_str()))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
 /// QUIC connection parameters (Requirement 7.2).
 #[derive(Debug, Clone)]
 pub struct QuicParams {
@@ -375,9 +507,11 @@ pub struct TransportNegotiation {
 /// - Automatic fallback (7.5)
 /// - ICE candidate support (7.6)
 /// - Policy restrictions (7.7)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct TransportNegotiator {
     preferences: TransportPreferences,
+    /// Operator ordering; when unset, `preferences.priority` is used.
+    policy: Option<TransportPolicy>,
     /// QUIC configuration for generating parameters.
     quic_config: Option<QuicConfig>,
     /// Pre-configured relay tokens.
@@ -405,26 +539,48 @@ impl Default for QuicConfig {
     }
 }
 
-impl Default for TransportNegotiator {
-    fn default() -> Self {
-        Self {
-            preferences: TransportPreferences::default(),
-            quic_config: None,
-            relay_tokens: Vec::new(),
-        }
-    }
-}
 
 impl TransportNegotiator {
     /// Create a new transport negotiator with the given preferences.
     pub fn new(preferences: TransportPreferences) -> Self {
         Self {
             preferences,
+            policy: None,
             quic_config: None,
             relay_tokens: Vec::new(),
         }
     }
 
+    /// Negotiate using an operator-configured ordering.
+    pub fn with_policy(mut self, policy: TransportPolicy) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    /// Get the operator policy, if any.
+    pub fn policy(&self) -> Option<&TransportPolicy> {
+        self.policy.as_ref()
+    }
+
+    /// Transports to try for `device_id`, in order.
+    fn ordering(&self, device_id: Option<&[u8]>) -> &[TransportType] {
+        match &self.policy {
+            Some(policy) => policy.order_for(device_id),
+            None => &self.preferences.priority,
+        }
+    }
+
+    /// Check the operator policy and preferences for a transport.
+    fn check_allowed(&self, transport: TransportType) -> Result<(), SkipReason> {
+        if self.policy.as_ref().is_some_and(|p| !p.is_enabled(transport)) {
+            return Err(SkipReason::DisabledByPolicy);
+        }
+        if !self.preferences.is_transport_allowed(transport) {
+            return Err(SkipReason::NotAllowed);
+        }
+        Ok(())
+    }
+
     /// Set the QUIC configuration for parameter generation.
     pub fn with_quic_config(mut self, config: QuicConfig) -> Self {
         self.quic_config = Some(config);
@@ -458,8 +614,8 @@ impl TransportNegotiator {
         let mut supported = Vec::new();
 
         // Build list of supported transports based on preferences and policy (Requirement 7.7)
-        for transport_type in &self.preferences.priority {
-            if !self.preferences.is_transport_allowed(*transport_type) {
+        for transport_type in self.ordering(None) {
+            if self.check_allowed(*transport_type).is_err() {
                 continue;
             }
             supported.push(*transport_type);
@@ -506,44 +662,66 @@ impl TransportNegotiator {
     /// - Automatic fallback to relay when direct fails (7.5)
     /// - Policy restrictions (7.7)
     pub fn select_transport(&self, offered: &TransportNegotiation) -> Result<SelectedTransport, TransportError> {
-        // Try transports in priority order
-        for transport_type in &self.preferences.priority {
-            // Check if transport is allowed by policy (Requirement 7.7)
-            if !self.preferences.is_transport_allowed(*transport_type) {
-                continue;
-            }
-
-            // Check if transport is supported by peer
-            if !offered.supported_transports.contains(transport_type) {
-                continue;
-            }
+        self.negotiate(offered, None).map(|selection| selection.selected)
+    }
 
-            match transport_type {
-                TransportType::Mesh | TransportType::Direct | TransportType::Rendezvous => {
-                    // For mesh/direct/rendezvous, we need QUIC params
-                    if let Some(ref params) = offered.quic_params {
-                        return Ok(SelectedTransport::Quic {
-                            params: params.clone(),
-                        });
-                    }
-                }
-                TransportType::Relay => {
-                    // For relay, we need both relay token and QUIC params
-                    if let Some(token) = self.select_best_relay_token(&offered.relay_tokens) {
-                        if let Some(ref params) = offered.quic_params {
-                            return Ok(SelectedTransport::Relay {
-                                token,
-                                params: params.clone(),
-                            });
-                        }
-                    }
+    /// Select a transport for `device_id` and report the fallback trail.
+    ///
+    /// Uses the device's ordering from the `TransportPolicy` when one is set,
+    /// otherwise the preference priority. Each transport passed over is
+    /// recorded with the reason it was skipped.
+    pub fn negotiate(
+        &self,
+        offered: &TransportNegotiation,
+        device_id: Option<&[u8]>,
+    ) -> Result<TransportSelection, TransportError> {
+        let mut skipped = Vec::new();
+
+        for transport_type in self.ordering(device_id) {
+            match self.try_transport(*transport_type, offered) {
+                Ok(selected) => {
+                    return Ok(TransportSelection {
+                        transport_type: *transport_type,
+                        selected,
+                        skipped,
+                    });
                 }
+                Err(reason) => skipped.push((*transport_type, reason)),
             }
         }
 
         Err(TransportError::NoCompatibleTransport)
     }
 
+    fn try_transport(
+        &self,
+        transport_type: TransportType,
+        offered: &TransportNegotiation,
+    ) -> Result<SelectedTransport, SkipReason> {
+        // Check if transport is allowed by policy (Requirement 7.7)
+        self.check_allowed(transport_type)?;
+
+        // Check if transport is supported by peer
+        if !offered.supported_transports.contains(&transport_type) {
+            return Err(SkipReason::NotOfferedByPeer);
+        }
+
+        // Every transport needs QUIC params
+        let params = offered.quic_params.clone().ok_or(SkipReason::MissingQuicParams)?;
+        match transport_type {
+            TransportType::Mesh | TransportType::Direct | TransportType::Rendezvous => {
+                Ok(SelectedTransport::Quic { params })
+            }
+            TransportType::Relay => {
+                // For relay, we also need a relay token
+                let token = self
+                    .select_best_relay_token(&offered.relay_tokens)
+                    .ok_or(SkipReason::NoValidRelayToken)?;
+                Ok(SelectedTransport::Relay { token, params })
+            }
+        }
+    }
+
     /// Select the best relay token from available options.
     ///
     /// Prefers tokens that:
@@ -564,7 +742,7 @@ impl TransportNegotiator {
 
     /// Check if a specific transport type is available in the offered options.
     pub fn is_transport_available(&self, transport: TransportType, offered: &TransportNegotiation) -> bool {
-        if !self.preferences.is_transport_allowed(transport) {
+        if self.check_allowed(transport).is_err() {
             return false;
         }
         if !offered.supported_transports.contains(&transport) {
@@ -900,4 +1078,76 @@ mod tests {
         assert_eq!(prefs.priority[0], TransportType::Direct);
         assert_eq!(prefs.priority[1], TransportType::Mesh);
     }
+
+    fn quic_offer(transports: Vec<TransportType>) -> TransportNegotiation {
+        TransportNegotiation {
+            quic_params: Some(QuicParams::new(vec![1, 2, 3])),
+            relay_tokens: vec![RelayToken::new("https://relay.example.com".into(), vec![4], 9999999999)],
+            supported_transports: transports,
+            ice_candidates: vec![],
+        }
+    }
+
+    #[test]
+    fn test_policy_disabled_transport_never_selected() {
+        let mut policy = TransportPolicy::default();
+        policy.disable(TransportType::Direct);
+        let negotiator = TransportNegotiator::default().with_policy(policy);
+
+        // Peer offers only the disabled transport
+        let offered = quic_offer(vec![TransportType::Direct]);
+        assert_eq!(
+            negotiator.negotiate(&offered, None).unwrap_err(),
+            TransportError::NoCompatibleTransport
+        );
+        assert!(!negotiator.is_transport_available(TransportType::Direct, &offered));
+
+        // Peer offers it first; the next allowed transport wins
+        let offered = quic_offer(vec![TransportType::Direct, TransportType::Relay]);
+        let selection = negotiator.negotiate(&offered, None).unwrap();
+        assert_eq!(selection.transport_type, TransportType::Relay);
+        assert!(selection
+            .skipped
+            .contains(&(TransportType::Direct, SkipReason::DisabledByPolicy)));
+
+        // Disabled transports are not advertised either
+        let params = negotiator.generate_params_from_config();
+        assert!(!params.supported_transports.contains(&TransportType::Direct));
+    }
+
+    #[test]
+    fn test_policy_ordering_and_fallback_trail() {
+        let policy = TransportPolicy::with_order(vec![
+            TransportType::Relay,
+            TransportType::Mesh,
+            TransportType::Direct,
+        ]);
+        let negotiator = TransportNegotiator::default().with_policy(policy);
+        let mut offered = quic_offer(vec![TransportType::Mesh, TransportType::Direct]);
+        offered.relay_tokens.clear();
+
+        let selection = negotiator.negotiate(&offered, None).unwrap();
+        assert_eq!(selection.transport_type, TransportType::Mesh);
+        assert_eq!(selection.fallback_trail(), "relay:not_offered_by_peer");
+
+        offered.supported_transports.push(TransportType::Relay);
+        let selection = negotiator.negotiate(&offered, None).unwrap();
+        assert_eq!(selection.fallback_trail(), "relay:no_valid_relay_token");
+    }
+
+    #[test]
+    fn test_policy_per_device_order() {
+        let mut policy = TransportPolicy::default();
+        policy.set_device_order(&[7u8; 32], vec![TransportType::Direct, TransportType::Mesh]);
+        let negotiator = TransportNegotiator::default().with_policy(policy);
+        let offered = quic_offer(vec![TransportType::Mesh, TransportType::Direct]);
+
+        let selection = negotiator.negotiate(&offered, Some(&[7u8; 32])).unwrap();
+        assert_eq!(selection.transport_type, TransportType::Direct);
+        assert!(selection.skipped.is_empty());
+
+        // Other devices use the global ordering
+        let selection = negotiator.negotiate(&offered, Some(&[8u8; 32])).unwrap();
+        assert_eq!(selection.transport_type, TransportType::Mesh);
+    }
 }
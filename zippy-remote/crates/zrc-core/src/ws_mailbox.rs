@@ -0,0 +1,384 @@
+#![cfg(feature = "ws-mailbox")]
+
+//! WebSocket mailbox transport.
+//!
+//! Keeps one WebSocket per mailbox to the rendezvous server at
+//! `/v1/mailbox/{id_hex}/ws`. The server pushes sealed envelopes for that
+//! mailbox as binary messages; outgoing envelopes are sent on the same socket
+//! as a binary message of `recipient_id (32 bytes) || envelope_bytes`.
+//!
+//! `WsMailboxClient` has the same `poll`/`post` surface as
+//! [`HttpMailboxClient`], so it can replace it in the host and controller
+//! loops. The socket reconnects with exponential backoff; if the server does
+//! not offer the WebSocket endpoint the client falls back to HTTP long-polling.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::Bytes;
+use futures_util::{SinkExt, StreamExt};
+use tokio::net::TcpStream;
+use tokio::sync::{mpsc, Mutex};
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::{self, Message};
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+use tracing::{debug, info, warn};
+
+use crate::http_mailbox::{HttpMailboxClient, HttpMailboxError};
+
+type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Envelopes buffered between the socket task and `poll`.
+const INBOX_CAPACITY: usize = 64;
+
+/// Reconnect backoff for the mailbox socket.
+#[derive(Debug, Clone)]
+pub struct ReconnectBackoff {
+    /// Delay before the first reconnect attempt.
+    pub initial: Duration,
+    /// Upper bound on the delay between attempts.
+    pub max: Duration,
+}
+
+impl Default for ReconnectBackoff {
+    fn default() -> Self {
+        Self {
+            initial: Duration::from_millis(500),
+            max: Duration::from_secs(30),
+        }
+    }
+}
+
+impl ReconnectBackoff {
+    /// Delay before reconnect attempt `attempt` (0-based).
+    fn delay(&self, attempt: u32) -> Duration {
+        self.initial
+            .saturating_mul(1u32 << attempt.min(16))
+            .min(self.max)
+    }
+}
+
+/// Receiving side of a live mailbox socket.
+struct Inbox {
+    id32: [u8; 32],
+    rx: mpsc::Receiver<Bytes>,
+    task: JoinHandle<()>,
+}
+
+impl Drop for Inbox {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Outcome of a WebSocket handshake.
+enum Connect {
+    Connected(WsStream),
+    /// The server does not offer the WebSocket endpoint.
+    Unsupported,
+    Failed(String),
+}
+
+pub struct WsMailboxClient {
+    ws_base_url: String,
+    http: HttpMailboxClient,
+    backoff: ReconnectBackoff,
+    inbox: Mutex<Option<Inbox>>,
+    outbox: std::sync::Mutex<Option<mpsc::UnboundedSender<Vec<u8>>>>,
+    ws_unsupported: Arc<AtomicBool>,
+}
+
+impl WsMailboxClient {
+    /// Create a client for the rendezvous server at `base_url` (`http(s)://`).
+    pub fn new(base_url: impl Into<String>) -> Result<Self, HttpMailboxError> {
+        let base_url = base_url.into().trim_end_matches('/').to_string();
+        let ws_base_url = ws_url_for(&base_url)?;
+        Ok(Self {
+            ws_base_url,
+            http: HttpMailboxClient::new(base_url)?,
+            backoff: ReconnectBackoff::default(),
+            inbox: Mutex::new(None),
+            outbox: std::sync::Mutex::new(None),
+            ws_unsupported: Arc::new(AtomicBool::new(false)),
+        })
+    }
+
+    /// Override the reconnect backoff.
+    pub fn with_backoff(mut self, backoff: ReconnectBackoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Whether the client has fallen back to HTTP polling.
+    pub fn is_http_fallback(&self) -> bool {
+        self.ws_unsupported.load(Ordering::Relaxed)
+    }
+
+    /// Send envelope bytes to recipient mailbox.
+    ///
+    /// Uses the open socket if there is one (queued across reconnects),
+    /// otherwise an HTTP POST.
+    pub async fn post(&self, rid32: &[u8; 32], envelope_bytes: &[u8]) -> Result<(), HttpMailboxError> {
+        let outbox = self.outbox.lock().unwrap().clone();
+        if let Some(tx) = outbox {
+            if tx.send(encode_outgoing(rid32, envelope_bytes)).is_ok() {
+                return Ok(());
+            }
+        }
+        self.http.post(rid32, envelope_bytes).await
+    }
+
+    /// Wait up to `wait_ms` for the next envelope pushed to this mailbox.
+    /// Returns None if nothing arrived in time.
+    ///
+    /// The first call opens the socket for `my_id32`.
+    pub async fn poll(&self, my_id32: &[u8; 32], wait_ms: u64) -> Result<Option<Bytes>, HttpMailboxError> {
+        if self.is_http_fallback() {
+            return self.http.poll(my_id32, wait_ms).await;
+        }
+
+        let mut inbox = self.inbox.lock().await;
+        if inbox.as_ref().map(|i| &i.id32) != Some(my_id32) {
+            *inbox = None;
+            match self.open(my_id32).await? {
+                Some(opened) => *inbox = Some(opened),
+                None => {
+                    drop(inbox);
+                    return self.http.poll(my_id32, wait_ms).await;
+                }
+            }
+        }
+
+        let rx = &mut inbox.as_mut().expect("inbox opened above").rx;
+        let received = tokio::time::timeout(Duration::from_millis(wait_ms), rx.recv()).await;
+        match received {
+            Ok(Some(envelope)) => Ok(Some(envelope)),
+            Err(_) => Ok(None),
+            Ok(None) => {
+                // Socket task gave up: the server stopped offering WebSockets
+                *inbox = None;
+                self.outbox.lock().unwrap().take();
+                drop(inbox);
+                self.http.poll(my_id32, wait_ms).await
+            }
+        }
+    }
+
+    /// Open the socket for `id32` and start its background task.
+    ///
+    /// Returns None (and switches to HTTP fallback) if the endpoint is unsupported.
+    async fn open(&self, id32: &[u8; 32]) -> Result<Option<Inbox>, HttpMailboxError> {
+        let url = mailbox_ws_url(&self.ws_base_url, id32);
+        let stream = match connect(&url).await {
+            Connect::Connected(stream) => stream,
+            Connect::Unsupported => {
+                info!("WebSocket mailbox not supported by server, using HTTP polling");
+                self.ws_unsupported.store(true, Ordering::Relaxed);
+                return Ok(None);
+            }
+            Connect::Failed(e) => return Err(HttpMailboxError::Http(e)),
+        };
+        debug!("mailbox socket connected: {}", url);
+
+        let (in_tx, in_rx) = mpsc::channel(INBOX_CAPACITY);
+        let (out_tx, out_rx) = mpsc::unbounded_channel();
+        let task = tokio::spawn(run_socket(
+            url,
+            stream,
+            in_tx,
+            out_rx,
+            self.backoff.clone(),
+            self.ws_unsupported.clone(),
+        ));
+        *self.outbox.lock().unwrap() = Some(out_tx);
+
+        Ok(Some(Inbox { id32: *id32, rx: in_rx, task }))
+    }
+}
+
+/// Pump one mailbox socket, reconnecting with backoff until the client is
+/// dropped or the server stops offering the endpoint.
+async fn run_socket(
+    url: String,
+    mut stream: WsStream,
+    in_tx: mpsc::Sender<Bytes>,
+    mut out_rx: mpsc::UnboundedReceiver<Vec<u8>>,
+    backoff: ReconnectBackoff,
+    ws_unsupported: Arc<AtomicBool>,
+) {
+    // Outgoing frame that failed to send and must be retried after reconnect
+    let mut pending: Option<Vec<u8>> = None;
+
+    loop {
+        let reason = pump(&mut stream, &in_tx, &mut out_rx, &mut pending).await;
+        let Err(reason) = reason else {
+            // Client dropped
+            return;
+        };
+        warn!("mailbox socket lost ({}), reconnecting", reason);
+
+        let mut attempt = 0;
+        stream = loop {
+            tokio::time::sleep(backoff.delay(attempt)).await;
+            if in_tx.is_closed() {
+                return;
+            }
+            match connect(&url).await {
+                Connect::Connected(stream) => break stream,
+                Connect::Unsupported => {
+                    ws_unsupported.store(true, Ordering::Relaxed);
+                    return;
+                }
+                Connect::Failed(e) => {
+                    debug!("mailbox reconnect attempt {} failed: {}", attempt + 1, e);
+                    attempt = attempt.saturating_add(1);
+                }
+            }
+        };
+        info!("mailbox socket reconnected");
+    }
+}
+
+/// Move envelopes between the socket and the client until either side closes.
+///
+/// Returns Ok(()) when the client is gone, Err(reason) when the socket failed.
+async fn pump(
+    stream: &mut WsStream,
+    in_tx: &mpsc::Sender<Bytes>,
+    out_rx: &mut mpsc::UnboundedReceiver<Vec<u8>>,
+    pending: &mut Option<Vec<u8>>,
+) -> Result<(), String> {
+    if let Some(frame) = pending.take() {
+        if let Err(e) = stream.send(Message::Binary(frame.clone().into())).await {
+            *pending = Some(frame);
+            return Err(e.to_string());
+        }
+    }
+
+    loop {
+        tokio::select! {
+            outgoing = out_rx.recv() => {
+                let Some(frame) = outgoing else { return Ok(()) };
+                if let Err(e) = stream.send(Message::Binary(frame.clone().into())).await {
+                    *pending = Some(frame);
+                    return Err(e.to_string());
+                }
+            }
+            incoming = stream.next() => match incoming {
+                Some(Ok(Message::Binary(envelope))) => {
+                    if in_tx.send(Bytes::from(envelope.to_vec())).await.is_err() {
+                        return Ok(());
+                    }
+                }
+                Some(Ok(Message::Close(_))) | None => return Err("closed by server".to_string()),
+                // Pings are answered by tungstenite; text is not part of the protocol
+                Some(Ok(_)) => {}
+                Some(Err(e)) => return Err(e.to_string()),
+            },
+        }
+    }
+}
+
+async fn connect(url: &str) -> Connect {
+    match tokio_tungstenite::connect_async(url).await {
+        Ok((stream, _)) => Connect::Connected(stream),
+        Err(tungstenite::Error::Http(resp)) if endpoint_missing(resp.status().as_u16()) => {
+            Connect::Unsupported
+        }
+        Err(e) => Connect::Failed(e.to_string()),
+    }
+}
+
+/// Handshake statuses meaning the server has no WebSocket mailbox endpoint.
+fn endpoint_missing(status: u16) -> bool {
+    matches!(status, 400 | 404 | 405 | 426 | 501)
+}
+
+fn ws_url_for(base_url: &str) -> Result<String, HttpMailboxError> {
+    if let Some(rest) = base_url.strip_prefix("https://") {
+        Ok(format!("wss://{}", rest))
+    } else if let Some(rest) = base_url.strip_prefix("http://") {
+        Ok(format!("ws://{}", rest))
+    } else {
+        Err(HttpMailboxError::BadResponse(format!(
+            "unsupported rendezvous url scheme: {}",
+            base_url
+        )))
+    }
+}
+
+fn mailbox_ws_url(ws_base_url: &str, id32: &[u8; 32]) -> String {
+    format!("{}/v1/mailbox/{}/ws", ws_base_url, hex::encode(id32))
+}
+
+fn encode_outgoing(rid32: &[u8; 32], envelope_bytes: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(32 + envelope_bytes.len());
+    frame.extend_from_slice(rid32);
+    frame.extend_from_slice(envelope_bytes);
+    frame
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ws_url_for() {
+        assert_eq!(ws_url_for("https://rv.example.com").unwrap(), "wss://rv.example.com");
+        assert_eq!(ws_url_for("http://127.0.0.1:8080").unwrap(), "ws://127.0.0.1:8080");
+        assert!(ws_url_for("ftp://rv.example.com").is_err());
+    }
+
+    #[test]
+    fn test_mailbox_ws_url() {
+        let url = mailbox_ws_url("wss://rv.example.com", &[0xab; 32]);
+        assert_eq!(url, format!("wss://rv.example.com/v1/mailbox/{}/ws", "ab".repeat(32)));
+    }
+
+    #[test]
+    fn test_encode_outgoing() {
+        let frame = encode_outgoing(&[7; 32], b"sealed");
+        assert_eq!(&frame[..32], &[7; 32]);
+        assert_eq!(&frame[32..], b"sealed");
+    }
+
+    #[test]
+    fn test_backoff_grows_and_caps() {
+        let backoff = ReconnectBackoff {
+            initial: Duration::from_millis(100),
+            max: Duration::from_secs(1),
+        };
+        assert_eq!(backoff.delay(0), Duration::from_millis(100));
+        assert_eq!(backoff.delay(2), Duration::from_millis(400));
+        assert_eq!(backoff.delay(10), Duration::from_secs(1));
+        assert_eq!(backoff.delay(u32::MAX), Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_falls_back_to_http_when_ws_unsupported() {
+        // A plain HTTP server without the WebSocket endpoint
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            use tokio::io::{AsyncReadExt, AsyncWriteExt};
+            loop {
+                let (mut sock, _) = listener.accept().await.unwrap();
+                let mut buf = [0u8; 4096];
+                let n = sock.read(&mut buf).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&buf[..n]);
+                let response = if request.contains("/ws ") {
+                    "HTTP/1.1 404 Not Found\r\ncontent-length: 0\r\nconnection: close\r\n\r\n"
+                } else {
+                    "HTTP/1.1 204 No Content\r\nconnection: close\r\n\r\n"
+                };
+                let _ = sock.write_all(response.as_bytes()).await;
+            }
+        });
+
+        let client = WsMailboxClient::new(format!("http://{}", addr)).unwrap();
+        let polled = client.poll(&[1; 32], 10).await.unwrap();
+        assert!(polled.is_none());
+        assert!(client.is_http_fallback());
+    }
+}
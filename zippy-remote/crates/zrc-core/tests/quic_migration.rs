@@ -0,0 +1,62 @@
+//! QUIC connection migration: a client that changes its local socket keeps
+//! its connection, and the server sees a path change instead of a new
+//! handshake.
+
+#![cfg(feature = "quic")]
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use zrc_core::quic::{read_frame, watch_path, write_frame, QuicClient, QuicServer};
+
+const ALPN: &[u8] = b"zrc/1";
+
+#[tokio::test]
+async fn integration_quic_client_rebind_survives() {
+    let localhost: SocketAddr = "127.0.0.1:0".parse().unwrap();
+    let server = QuicServer::bind_with_migration(localhost, ALPN, true).await.unwrap();
+    let server_addr = server.endpoint.local_addr().unwrap();
+    let endpoint = server.endpoint.clone();
+
+    // Single-connection echo server that reports path changes
+    let server_task = tokio::spawn(async move {
+        let conn = endpoint.accept().await.unwrap().await.unwrap();
+        let mut paths = watch_path(conn.clone(), Duration::from_millis(20));
+        let (mut send, mut recv) = conn.accept_bi().await.unwrap();
+        while let Ok(Some(frame)) = read_frame(&mut recv).await {
+            write_frame(&mut send, &frame).await.unwrap();
+        }
+        let change = tokio::time::timeout(Duration::from_secs(2), paths.recv())
+            .await
+            .ok()
+            .flatten();
+
+        // No second handshake happened
+        let second = tokio::time::timeout(Duration::from_millis(100), endpoint.accept()).await;
+        (change, second.is_err())
+    });
+
+    let client = QuicClient::new(localhost, ALPN, &server.cert_der).unwrap();
+    let old_addr = client.endpoint.local_addr().unwrap();
+    let conn = client.connect(server_addr, "zrc.local").await.unwrap();
+    let stable_id = conn.stable_id();
+    let (mut send, mut recv) = conn.open_bi().await.unwrap();
+
+    write_frame(&mut send, b"before").await.unwrap();
+    assert_eq!(&read_frame(&mut recv).await.unwrap().unwrap()[..], b"before");
+
+    let new_addr = client.rebind(localhost).unwrap();
+    assert_ne!(new_addr, old_addr);
+
+    write_frame(&mut send, b"after").await.unwrap();
+    assert_eq!(&read_frame(&mut recv).await.unwrap().unwrap()[..], b"after");
+    assert_eq!(conn.stable_id(), stable_id);
+    assert!(conn.close_reason().is_none());
+    send.finish().unwrap();
+
+    let (change, no_new_handshake) = server_task.await.unwrap();
+    let change = change.expect("server should report the path change");
+    assert_eq!(change.old.port(), old_addr.port());
+    assert_eq!(change.new.port(), new_addr.port());
+    assert!(no_new_handshake);
+}
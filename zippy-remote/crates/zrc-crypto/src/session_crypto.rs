@@ -12,7 +12,8 @@ use chacha20poly1305::{
 };
 use hkdf::Hkdf;
 use sha2::Sha256;
-use zeroize::{Zeroize, ZeroizeOnDrop};
+use std::sync::{Arc, Mutex};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
 
 use crate::replay::{generate_nonce, MonotonicCounter};
 
@@ -165,6 +166,24 @@ impl SessionCrypto {
 #[derive(Clone)]
 pub struct SessionCryptoV1 {
     aead: ChaCha20Poly1305,
+    /// Shared by clones so that whichever builds the frame ratchet wipes it
+    /// for all of them
+    frame_ratchet_seed: Arc<Mutex<Option<Zeroizing<[u8; 32]>>>>,
+}
+
+impl SessionCryptoV1 {
+    /// Take the initial chain key for ratcheting frame keys, derived from
+    /// the same session binding and salt as the AEAD key.
+    ///
+    /// The seed is handed out once and erased from this context (and its
+    /// clones), so a later compromise of the context cannot rebuild earlier
+    /// frame keys. `None` once taken, or for contexts built from a bare key.
+    pub fn take_frame_ratchet_seed(&self) -> Option<Zeroizing<[u8; 32]>> {
+        self.frame_ratchet_seed
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take()
+    }
 }
 
 /// Derive an AEAD key from session binding + salt.
@@ -179,8 +198,25 @@ pub fn derive_session_crypto_v1(ikm: &[u8], salt: &[u8]) -> SessionCryptoV1 {
     hk.expand(b"zrc_sess_aead_key_v1", &mut key32)
         .expect("hkdf expand");
 
+    let mut frame_ratchet_seed = Zeroizing::new([0u8; 32]);
+    hk.expand(b"zrc_frame_ratchet_v1", frame_ratchet_seed.as_mut())
+        .expect("hkdf expand");
+
     let aead = ChaCha20Poly1305::new(Key::from_slice(&key32));
-    SessionCryptoV1 { aead }
+    key32.zeroize();
+    SessionCryptoV1 {
+        aead,
+        frame_ratchet_seed: Arc::new(Mutex::new(Some(frame_ratchet_seed))),
+    }
+}
+
+/// Build a crypto context around an already-derived AEAD key, e.g. one
+/// epoch of a frame-key ratchet. The result carries no ratchet seed.
+pub fn session_crypto_from_key_v1(key: &[u8; 32]) -> SessionCryptoV1 {
+    SessionCryptoV1 {
+        aead: ChaCha20Poly1305::new(Key::from_slice(key)),
+        frame_ratchet_seed: Arc::new(Mutex::new(None)),
+    }
 }
 
 /// Encrypt with random nonce (legacy API).
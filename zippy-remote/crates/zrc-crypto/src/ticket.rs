@@ -5,6 +5,7 @@ use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
 
 use crate::hash::sha256;
 use crate::transcript::Transcript;
+use crate::utils::constant_time_compare;
 use zrc_proto::v1::{KeyTypeV1, PublicKeyV1, SessionTicketV1};
 
 #[derive(Debug, thiserror::Error)]
@@ -98,8 +99,8 @@ pub fn verify_ticket_v1(
         return Err(TicketError::Expired);
     }
 
-    // Check session binding
-    if ticket.session_binding != expected_session_binding.as_slice() {
+    // Check session binding (constant time: the binding is derived from session keys)
+    if !constant_time_compare(&ticket.session_binding, expected_session_binding.as_slice()) {
         return Err(TicketError::BindingMismatch);
     }
 
@@ -102,9 +102,12 @@ async fn run_host(rendezvous_url: &str, quic_bind: SocketAddr, quic_advertise: S
                     &ticket_id
                 );
 
-                // Handle input events
-                #[cfg(windows)]
+                let frame_codec = control.frame_codec;
+                let quality = control.quality.clone();
+
+                // Handle control messages: input events and session control
                 {
+                    #[cfg(windows)]
                     let apply_input = |evt: zrc_proto::v1::InputEventV1| -> anyhow::Result<()> {
                         use zrc_proto::v1::input_event_v1::Kind;
                         match evt.kind {
@@ -130,10 +133,18 @@ async fn run_host(rendezvous_url: &str, quic_bind: SocketAddr, quic_advertise: S
 
                     let mut control2 = control;
                     tokio::spawn(async move {
+                        use zrc_proto::v1::control_msg_v1::Payload;
                         loop {
                             let Some(msg) = control2.recv_msg().await.ok().flatten() else { break; };
-                            if let Some(zrc_proto::v1::control_msg_v1::Msg::Input(input)) = msg.msg {
-                                let _ = apply_input(input);
+                            match msg.payload {
+                                #[cfg(windows)]
+                                Some(Payload::Input(input)) => {
+                                    let _ = apply_input(input);
+                                }
+                                Some(Payload::SessionControl(sc)) => {
+                                    let _ = control2.apply_session_control(&sc).await;
+                                }
+                                _ => {}
                             }
                         }
                     });
@@ -141,41 +152,49 @@ async fn run_host(rendezvous_url: &str, quic_bind: SocketAddr, quic_advertise: S
 
                 // Stream frames
                 let mut frame_no = 0u64;
-                let _ = zrc_core::quic_mux::host_stream_frames(&conn, &crypto, move || {
-                    frame_no += 1;
-
-                    // Windows capture if available; otherwise send dummy frame
-                    #[cfg(windows)]
-                    {
-                        let f = zrc_platform_win::capture_gdi::capture_primary_bgra()
-                            .map_err(|e| anyhow::anyhow!("{e}"))?;
-                        Ok(zrc_core::quic_mux::FramePacketV1 {
-                            width: f.width,
-                            height: f.height,
-                            stride: f.stride,
-                            format: 1,
-                            pixels: f.bgra,
-                        })
-                    }
-                    #[cfg(not(windows))]
-                    {
-                        let w = 320u32;
-                        let h = 180u32;
-                        let stride = w * 4;
-                        let mut pixels = vec![0u8; (stride * h) as usize];
-                        // tiny moving pattern
-                        let x = (frame_no % w as u64) as u32;
-                        let y = (frame_no % h as u64) as u32;
-                        let idx = (y * stride + x * 4) as usize;
-                        if idx + 4 <= pixels.len() {
-                            pixels[idx + 0] = 255; // B
-                            pixels[idx + 1] = 0;   // G
-                            pixels[idx + 2] = 0;   // R
-                            pixels[idx + 3] = 255; // A
+                let (_stats, frames) = zrc_core::quic_mux::host_stream_frames_with_stats(
+                    &conn,
+                    &crypto,
+                    frame_codec,
+                    zrc_core::quic_mux::FrameRateConfig::default(),
+                    quality,
+                    move || {
+                        frame_no += 1;
+
+                        // Windows capture if available; otherwise send dummy frame
+                        #[cfg(windows)]
+                        {
+                            let f = zrc_platform_win::capture_gdi::capture_primary_bgra()
+                                .map_err(|e| anyhow::anyhow!("{e}"))?;
+                            Ok(zrc_core::quic_mux::FramePacketV1 {
+                                width: f.width,
+                                height: f.height,
+                                stride: f.stride,
+                                format: 1,
+                                pixels: f.bgra,
+                            })
                         }
-                        Ok(zrc_core::quic_mux::FramePacketV1 { width: w, height: h, stride, format: 1, pixels })
-                    }
-                }).await;
+                        #[cfg(not(windows))]
+                        {
+                            let w = 320u32;
+                            let h = 180u32;
+                            let stride = w * 4;
+                            let mut pixels = vec![0u8; (stride * h) as usize];
+                            // tiny moving pattern
+                            let x = (frame_no % w as u64) as u32;
+                            let y = (frame_no % h as u64) as u32;
+                            let idx = (y * stride + x * 4) as usize;
+                            if idx + 4 <= pixels.len() {
+                                pixels[idx + 0] = 255; // B
+                                pixels[idx + 1] = 0;   // G
+                                pixels[idx + 2] = 0;   // R
+                                pixels[idx + 3] = 255; // A
+                            }
+                            Ok(zrc_core::quic_mux::FramePacketV1 { width: w, height: h, stride, format: 1, pixels })
+                        }
+                    },
+                );
+                let _ = frames.await;
             });
         }
     });
@@ -300,6 +319,8 @@ async fn run_controller(rendezvous_url: &str, invite_b64: &str) -> anyhow::Resul
             operator_id: init.operator_id.clone(),
             ticket_binding_nonce: init.ticket_binding_nonce.clone(),
             ticket: Some(ticket.clone()),
+            frame_codecs: Vec::new(), // filled in by controller_control_handshake
+            supports_damage_frames: false,
         };
 
         // Open control and send the ticket (plaintext over pinned QUIC TLS)
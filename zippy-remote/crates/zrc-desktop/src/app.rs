@@ -29,7 +29,11 @@ impl ZrcDesktopApp {
         let keys = generate_identity_keys();
         let store = InMemoryStore::new_shared();
 
-        let device_manager = Arc::new(DeviceManager::new());
+        let device_manager = match DeviceManager::default_groups_path() {
+            Some(path) => DeviceManager::new().with_groups_file(path),
+            None => DeviceManager::new(),
+        };
+        let device_manager = Arc::new(device_manager);
         
         // Prepare for async load
         let store_clone = store.clone();
@@ -65,67 +69,144 @@ impl ZrcDesktopApp {
         }
     }
 
+    /// Open a viewer for `session`, restoring what was last used for its device
+    pub fn open_viewer(&mut self, session: Arc<crate::session::ActiveSession>) {
+        let session_id = session.id;
+        let view = self.settings.device_view(&session.device_id);
+        let mut viewer = crate::viewer::ViewerWindow::new(session, self.runtime.clone());
+        viewer.apply_view_settings(&view);
+        self.ui_state.viewer_windows.insert(session_id, viewer);
+    }
+
+    /// Close the viewer for `session_id`, remembering its settings for the device
+    pub fn close_viewer(&mut self, session_id: &crate::session::SessionId) {
+        if let Some(viewer) = self.ui_state.viewer_windows.remove(session_id) {
+            self.settings.set_device_view(viewer.device_id(), viewer.view_settings());
+        }
+    }
+
+    /// Track the main window geometry so the next run opens where this one was
+    fn remember_window(&mut self, ctx: &egui::Context) {
+        let (inner, outer, fullscreen) = ctx.input(|i| {
+            let viewport = i.viewport();
+            (viewport.inner_rect, viewport.outer_rect, viewport.fullscreen.unwrap_or(false))
+        });
+        if fullscreen {
+            return;
+        }
+        if let Some(inner) = inner {
+            self.settings.window = Some(crate::settings::WindowGeometry {
+                width: inner.width(),
+                height: inner.height(),
+                x: outer.map(|r| r.min.x),
+                y: outer.map(|r| r.min.y),
+            });
+        }
+    }
+
     /// Handle background events (called from update loop)
     fn handle_background_events(&mut self, _ctx: &egui::Context) {
-        // Process session events
-        if let Some(ref mut rx) = self._session_event_receiver {
-            while let Ok(event) = rx.try_recv() {
-                match event {
-                    crate::session::SessionEvent::Connected { session_id } => {
-                        // Open viewer window
-                        if let Some(session) = self.session_manager.get_active_session(&session_id) {
-                            let viewer = crate::viewer::ViewerWindow::new(session, self.runtime.clone());
-                            self.ui_state.viewer_windows.insert(session_id, viewer);
+        // Process session events; the receiver is taken out while draining so
+        // the handlers below can borrow `self` mutably
+        let Some(mut rx) = self._session_event_receiver.take() else {
+            return;
+        };
+        while let Ok(event) = rx.try_recv() {
+            match event {
+                crate::session::SessionEvent::Connected { session_id } => {
+                    // Open viewer window
+                    if let Some(session) = self.session_manager.get_active_session(&session_id) {
+                        self.ui_state.dialogs.retain(|d| {
+                            !matches!(d, crate::ui::Dialog::ConnectionProgress { device_id, .. } if *device_id == session.device_id)
+                        });
+                        // A viewer reconnecting to this device adopts the session itself
+                        let adopted = self.ui_state.viewer_windows.values().any(|v| {
+                            v.session_id() == session_id
+                                || (v.is_reconnecting() && v.device_id() == session.device_id)
+                        });
+                        if !adopted {
+                            self.open_viewer(session);
                             self.ui_state.current_view = crate::ui::View::Session(session_id);
                         }
-                        crate::ui::add_notification(
-                            &mut self.ui_state,
-                            format!("Connected to session {:?}", session_id),
-                            crate::ui::NotificationLevel::Success,
-                        );
-                        // Show platform notification
-                        self.platform.show_notification(
-                            "Zippy Remote Control",
-                            &format!("Connected to session {:?}", session_id),
-                        );
-                    }
-                    crate::session::SessionEvent::Disconnected { session_id, reason } => {
-                        crate::ui::add_notification(
-                            &mut self.ui_state,
-                            format!("Disconnected: {}", reason),
-                            crate::ui::NotificationLevel::Info,
-                        );
-                        // Show platform notification
-                        self.platform.show_notification(
-                            "Zippy Remote Control",
-                            &format!("Disconnected: {}", reason),
-                        );
-                        // Remove viewer window
-                        self.ui_state.viewer_windows.remove(&session_id);
-                    }
-                    crate::session::SessionEvent::QualityChanged { session_id: _, quality: _ } => {
-                        // Update connection quality indicator
                     }
-                    crate::session::SessionEvent::Error { session_id: _, error } => {
-                        let error_msg = error.clone();
-                        // Remove connection progress dialog if present
-                        self.ui_state.dialogs.retain(|d| {
-                            !matches!(d, crate::ui::Dialog::ConnectionProgress { .. })
-                        });
-                        // Show error dialog
+                    crate::ui::add_notification(
+                        &mut self.ui_state,
+                        format!("Connected to session {:?}", session_id),
+                        crate::ui::NotificationLevel::Success,
+                    );
+                    // Show platform notification
+                    self.platform.show_notification(
+                        "Zippy Remote Control",
+                        &format!("Connected to session {:?}", session_id),
+                    );
+                }
+                crate::session::SessionEvent::Disconnected { session_id, reason } => {
+                    crate::ui::add_notification(
+                        &mut self.ui_state,
+                        format!("Disconnected: {}", reason),
+                        crate::ui::NotificationLevel::Info,
+                    );
+                    // Show platform notification
+                    self.platform.show_notification(
+                        "Zippy Remote Control",
+                        &format!("Disconnected: {}", reason),
+                    );
+                    // Remove viewer window
+                    self.close_viewer(&session_id);
+                }
+                crate::session::SessionEvent::QualityChanged { session_id: _, quality: _ } => {
+                    // Update connection quality indicator
+                }
+                crate::session::SessionEvent::Error { session_id, error } => {
+                    let error_msg = error.clone();
+                    let device_id = self.session_manager.get_active_session(&session_id)
+                        .map(|s| s.device_id.clone())
+                        .unwrap_or_else(|| "Unknown".to_string());
+                    // A viewer that reconnects by itself already shows an overlay;
+                    // a Retry dialog on top would race it with a second session
+                    let self_healing = self.ui_state.viewer_windows.get(&session_id)
+                        .is_some_and(|v| v.auto_reconnects());
+                    if !self_healing {
+                        // Drop the broken session first, so Retry connects
+                        // afresh instead of opening a second session to the device
+                        self.close_viewer(&session_id);
+                        if let Some(session) = self.session_manager.remove_session(&session_id) {
+                            self.runtime.spawn(async move {
+                                let _ = session.media_session.close().await;
+                            });
+                        }
+                        // Show error dialog; the link broke, so reconnecting may help
                         self.ui_state.dialogs.push(crate::ui::Dialog::ConnectionError {
-                            device_id: "Unknown".to_string(), // TODO: Get from session
+                            device_id,
                             error: error_msg.clone(),
+                            retriable: true,
                         });
-                        crate::ui::add_notification(
-                            &mut self.ui_state,
-                            format!("Connection error: {}", error_msg),
-                            crate::ui::NotificationLevel::Error,
-                        );
                     }
+                    crate::ui::add_notification(
+                        &mut self.ui_state,
+                        format!("Session error: {}", error_msg),
+                        crate::ui::NotificationLevel::Error,
+                    );
+                }
+                crate::session::SessionEvent::ConnectFailed { device_id, error, retriable } => {
+                    let error_msg = error.clone();
+                    self.ui_state.dialogs.retain(|d| {
+                        !matches!(d, crate::ui::Dialog::ConnectionProgress { device_id: pending, .. } if *pending == device_id)
+                    });
+                    self.ui_state.dialogs.push(crate::ui::Dialog::ConnectionError {
+                        device_id,
+                        error: error_msg.clone(),
+                        retriable,
+                    });
+                    crate::ui::add_notification(
+                        &mut self.ui_state,
+                        format!("Connection error: {}", error_msg),
+                        crate::ui::NotificationLevel::Error,
+                    );
                 }
             }
         }
+        self._session_event_receiver = Some(rx);
     }
 }
 
@@ -153,12 +234,16 @@ impl eframe::App for ZrcDesktopApp {
     fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
         // Handle background events
         self.handle_background_events(ctx);
+        self.remember_window(ctx);
         
         // Render UI
         crate::ui::render_ui(self, ctx, frame);
     }
 
     fn save(&mut self, _storage: &mut dyn eframe::Storage) {
+        for viewer in self.ui_state.viewer_windows.values() {
+            self.settings.set_device_view(viewer.device_id(), viewer.view_settings());
+        }
         self.settings.save();
     }
 }
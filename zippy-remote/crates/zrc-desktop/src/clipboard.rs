@@ -1,35 +1,136 @@
 //! Clipboard synchronization
+use std::borrow::Cow;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use arboard::{Clipboard, ImageData};
-use std::borrow::Cow;
-use std::sync::atomic::{AtomicBool, Ordering};
+use image::ImageEncoder;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
 use tokio::sync::mpsc;
-use zrc_proto::v1::{ControlMsgV1, ControlMsgTypeV1, ClipboardMsgV1, ClipboardFormatV1, ClipboardDirectionV1};
-use std::time::Instant;
+use zrc_core::clipboard::{
+    clipboard_set_messages, has_clipboard_permission, ClipboardConfig, ClipboardContent,
+    ClipboardReceiver, ClipboardUpdate,
+};
+use zrc_proto::v1::{control_msg_v1, ClipboardMsgV1, ControlMsgV1, PermissionsV1};
+
+/// How often the local clipboard is checked for changes
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// MIME type clipboard images are sent as
+const IMAGE_MIME: &str = "image/png";
+
+/// Uncompressed clipboard image
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ClipboardImage {
+    pub width: usize,
+    pub height: usize,
+    /// RGBA8 pixels, row-major
+    pub rgba: Vec<u8>,
+}
+
+/// Access to the local system clipboard
+pub trait LocalClipboard: Send {
+    fn get_text(&mut self) -> Option<String>;
+    fn set_text(&mut self, text: String);
+    fn clear(&mut self);
+
+    /// Image on the clipboard; text-only backends have none
+    fn get_image(&mut self) -> Option<ClipboardImage> {
+        None
+    }
+
+    fn set_image(&mut self, _image: ClipboardImage) {}
+}
+
+impl LocalClipboard for Clipboard {
+    fn get_text(&mut self) -> Option<String> {
+        Clipboard::get_text(self).ok()
+    }
+
+    fn set_text(&mut self, text: String) {
+        if let Err(e) = Clipboard::set_text(self, text) {
+            tracing::warn!("Failed to set clipboard text: {}", e);
+        }
+    }
+
+    fn clear(&mut self) {
+        if let Err(e) = Clipboard::clear(self) {
+            tracing::warn!("Failed to clear clipboard: {}", e);
+        }
+    }
+
+    fn get_image(&mut self) -> Option<ClipboardImage> {
+        let image = Clipboard::get_image(self).ok()?;
+        Some(ClipboardImage {
+            width: image.width,
+            height: image.height,
+            rgba: image.bytes.into_owned(),
+        })
+    }
+
+    fn set_image(&mut self, image: ClipboardImage) {
+        let image = ImageData {
+            width: image.width,
+            height: image.height,
+            bytes: Cow::Owned(image.rgba),
+        };
+        if let Err(e) = Clipboard::set_image(self, image) {
+            tracing::warn!("Failed to set clipboard image: {}", e);
+        }
+    }
+}
 
 /// Managers local and remote clipboard synchronization
 pub struct ClipboardManager {
-    clipboard: Arc<Mutex<Clipboard>>,
-    enabled: Arc<AtomicBool>,
+    clipboard: Mutex<Box<dyn LocalClipboard>>,
+    enabled: AtomicBool,
+    permissions: AtomicU32,
+    config: ClipboardConfig,
     control_tx: mpsc::Sender<ControlMsgV1>,
-    // Last hash or content to detect changes
-    last_text: Arc<Mutex<Option<String>>>,
-    // last_image_hash: Arc<Mutex<Option<u64>>>, // For optimization later
+    receiver: Mutex<ClipboardReceiver>,
+    next_sequence: AtomicU64,
+    // Last text seen locally or applied from the remote, so neither side echoes it back
+    last_text: Mutex<Option<String>>,
+    // Hash of the last image, for the same reason
+    last_image: Mutex<Option<u64>>,
 }
 
 impl ClipboardManager {
+    /// Manager for the system clipboard, assuming clipboard permission
     pub fn new(control_tx: mpsc::Sender<ControlMsgV1>) -> Result<Self, anyhow::Error> {
+        Self::with_permissions(control_tx, PermissionsV1::Clipboard as u32)
+    }
+
+    /// Manager for the system clipboard, limited to the permissions granted by the pairing
+    pub fn with_permissions(
+        control_tx: mpsc::Sender<ControlMsgV1>,
+        permissions: u32,
+    ) -> Result<Self, anyhow::Error> {
         let clipboard = Clipboard::new()?;
-        
-        Ok(Self {
-            clipboard: Arc::new(Mutex::new(clipboard)),
-            enabled: Arc::new(AtomicBool::new(true)),
+        Ok(Self::with_clipboard(Box::new(clipboard), control_tx, permissions, ClipboardConfig::default()))
+    }
+
+    /// Manager for any clipboard backend
+    pub fn with_clipboard(
+        clipboard: Box<dyn LocalClipboard>,
+        control_tx: mpsc::Sender<ControlMsgV1>,
+        permissions: u32,
+        config: ClipboardConfig,
+    ) -> Self {
+        Self {
+            clipboard: Mutex::new(clipboard),
+            enabled: AtomicBool::new(true),
+            permissions: AtomicU32::new(permissions),
+            config,
             control_tx,
-            last_text: Arc::new(Mutex::new(None)),
-        })
+            receiver: Mutex::new(ClipboardReceiver::new(permissions, config)),
+            next_sequence: AtomicU64::new(1),
+            last_text: Mutex::new(None),
+            last_image: Mutex::new(None),
+        }
     }
-    
+
     /// Enable or disable sync
     pub fn set_enabled(&self, enabled: bool) {
         self.enabled.store(enabled, Ordering::Relaxed);
@@ -39,150 +140,164 @@ impl ClipboardManager {
     pub fn is_enabled(&self) -> bool {
         self.enabled.load(Ordering::Relaxed)
     }
-    
+
+    /// Whether the pairing grants clipboard access
+    pub fn is_permitted(&self) -> bool {
+        has_clipboard_permission(self.permissions.load(Ordering::Relaxed))
+    }
+
+    /// Update the permissions granted for this session
+    pub fn set_permissions(&self, permissions: u32) {
+        self.permissions.store(permissions, Ordering::Relaxed);
+        self.receiver.lock().unwrap().set_permissions(permissions);
+    }
+
     /// Start monitoring local clipboard (spawns a thread)
-    pub fn start_monitoring(&self) {
-        let clipboard = self.clipboard.clone();
-        let enabled = self.enabled.clone();
-        let control_tx = self.control_tx.clone();
-        let last_text = self.last_text.clone();
-        
+    pub fn start_monitoring(self: &Arc<Self>) {
+        let manager = self.clone();
+
         // Use std::thread because arboard might be blocking or need OS thread affinity
         std::thread::spawn(move || {
-            loop {
-                std::thread::sleep(Duration::from_millis(500));
-                
-                if !enabled.load(Ordering::Relaxed) {
-                    continue;
-                }
-                
-                // Check text
-                let current_text = {
-                    if let Ok(mut lock) = clipboard.lock() {
-                        lock.get_text().ok()
-                    } else {
-                        None
-                    }
-                };
+            while !manager.control_tx.is_closed() {
+                std::thread::sleep(POLL_INTERVAL);
+                manager.poll_local();
+            }
+        });
+    }
 
-                if let Some(text) = current_text {
-                    // Check size limit
-                    const MAX_TEXT_SIZE: usize = 10 * 1024 * 1024; // 10MB
-                    if text.len() > MAX_TEXT_SIZE {
-                        tracing::warn!("Clipboard text too large ({} bytes), skipping", text.len());
-                        continue;
-                    }
-                    
-                    let mut last = last_text.lock().unwrap();
-                    if last.as_deref() != Some(&text) {
-                        // Change detected
-                        *last = Some(text.clone());
-                        drop(last); // Release lock before sending
-                        
-                        // Send to remote
-                        let msg = ControlMsgV1 {
-                            msg_type: ControlMsgTypeV1::Clipboard as i32,
-                            payload: Some(zrc_proto::v1::control_msg_v1::Payload::Clipboard(ClipboardMsgV1 {
-                                direction: ClipboardDirectionV1::ToDevice as i32,
-                                format: ClipboardFormatV1::Text as i32,
-                                data: text.into_bytes(),
-                                sequence_id: 0, // TODO: sequence
-                            })),
-                            ..Default::default()
-                        };
-                        
-                        let _ = control_tx.blocking_send(msg);
-                    }
-                }
-                
-                // Check image
-                let current_image = {
-                    if let Ok(mut lock) = clipboard.lock() {
-                        lock.get_image().ok()
-                    } else {
-                        None
-                    }
-                };
+    /// Check the local clipboard once and send it to the remote if it changed.
+    /// Returns whether anything was sent.
+    pub fn poll_local(&self) -> bool {
+        if !self.is_enabled() || !self.is_permitted() {
+            return false;
+        }
 
-                if let Some(image) = current_image {
-                    // Check size limit (default 10MB)
-                    const MAX_IMAGE_SIZE: usize = 10 * 1024 * 1024;
-                    let image_size = image.width * image.height * 4; // RGBA
-                    
-                    if image_size <= MAX_IMAGE_SIZE {
-                        // Convert image to bytes (RGBA format)
-                        let mut image_data = Vec::with_capacity(image_size);
-                        image_data.extend_from_slice(&image.bytes);
-                        
-                        // Send to remote
-                        let msg = ControlMsgV1 {
-                            msg_type: ControlMsgTypeV1::Clipboard as i32,
-                            payload: Some(zrc_proto::v1::control_msg_v1::Payload::Clipboard(ClipboardMsgV1 {
-                                direction: ClipboardDirectionV1::ToDevice as i32,
-                                format: ClipboardFormatV1::ImagePng as i32,
-                                data: image_data,
-                                sequence_id: 0,
-                            })),
-                            ..Default::default()
-                        };
-                        
-                        let _ = control_tx.blocking_send(msg);
-                    } else {
-                        // Image too large - skip
-                        tracing::warn!("Clipboard image too large ({} bytes), skipping", image_size);
-                    }
-                }
+        let Some(content) = self.local_change() else {
+            return false;
+        };
+
+        let sequence = self.next_sequence.fetch_add(1, Ordering::Relaxed);
+        let messages = match clipboard_set_messages(sequence, &content, &self.config) {
+            Ok(messages) => messages,
+            Err(e) => {
+                tracing::warn!("Skipping clipboard sync: {}", e);
+                return false;
             }
-        });
+        };
+
+        for message in messages {
+            let msg = ControlMsgV1 {
+                payload: Some(control_msg_v1::Payload::Clipboard(message)),
+                ..Default::default()
+            };
+            if self.control_tx.blocking_send(msg).is_err() {
+                return false;
+            }
+        }
+        true
     }
-    
+
+    /// Local clipboard contents if they changed since last seen: text, or
+    /// else an image encoded as PNG.
+    fn local_change(&self) -> Option<ClipboardContent> {
+        // Separate statements so the clipboard lock is released in between
+        let text = self.clipboard.lock().unwrap().get_text();
+        if let Some(text) = text {
+            let mut last = self.last_text.lock().unwrap();
+            if last.as_deref() == Some(text.as_str()) {
+                return None;
+            }
+            *last = Some(text.clone());
+            return Some(ClipboardContent::text(text));
+        }
+
+        let image = self.clipboard.lock().unwrap().get_image()?;
+        {
+            let hash = image_hash(&image);
+            let mut last = self.last_image.lock().unwrap();
+            if *last == Some(hash) {
+                return None;
+            }
+            *last = Some(hash);
+        }
+        match encode_png(&image) {
+            Ok(data) => Some(ClipboardContent { mime_type: IMAGE_MIME.to_string(), data }),
+            Err(e) => {
+                tracing::warn!("Skipping clipboard image: {}", e);
+                None
+            }
+        }
+    }
+
     /// Apply remote update to local clipboard
     pub fn apply_remote_update(&self, msg: ClipboardMsgV1) {
-        if !self.enabled.load(Ordering::Relaxed) {
+        if !self.is_enabled() {
             return;
         }
 
-        // Check size limits
-        const MAX_TEXT_SIZE: usize = 10 * 1024 * 1024; // 10MB
-        const MAX_IMAGE_SIZE: usize = 10 * 1024 * 1024; // 10MB
-        
-        if msg.data.len() > MAX_TEXT_SIZE.max(MAX_IMAGE_SIZE) {
-            tracing::warn!("Clipboard data too large ({} bytes), rejecting", msg.data.len());
-            return;
-        }
+        let update = match self.receiver.lock().unwrap().handle(&msg) {
+            Ok(Some(update)) => update,
+            Ok(None) => return,
+            Err(e) => {
+                tracing::warn!("Rejected remote clipboard update: {}", e);
+                return;
+            }
+        };
 
-        if let Ok(mut clipboard) = self.clipboard.lock() {
-            match ClipboardFormatV1::try_from(msg.format) {
-                Ok(ClipboardFormatV1::Text) => {
-                    if msg.data.len() > MAX_TEXT_SIZE {
-                        tracing::warn!("Text clipboard data too large, rejecting");
-                        return;
-                    }
-                    if let Ok(text) = String::from_utf8(msg.data) {
-                        // Update last_text to avoid loop
-                        if let Ok(mut last) = self.last_text.lock() {
-                            *last = Some(text.clone());
-                        }
-                        
-                        let _ = clipboard.set_text(text);
-                    }
-                },
-                Ok(ClipboardFormatV1::ImagePng) | Ok(ClipboardFormatV1::ImageBmp) => {
-                    if msg.data.len() > MAX_IMAGE_SIZE {
-                        tracing::warn!("Image clipboard data too large, rejecting");
+        match update {
+            ClipboardUpdate::Set(content) if content.mime_type.starts_with("text/plain") => {
+                let Ok(text) = String::from_utf8(content.data) else {
+                    tracing::warn!("Remote clipboard text is not valid UTF-8");
+                    return;
+                };
+                // Remember it first so the monitor does not send it back
+                *self.last_text.lock().unwrap() = Some(text.clone());
+                self.clipboard.lock().unwrap().set_text(text);
+            }
+            ClipboardUpdate::Set(content) if content.mime_type == IMAGE_MIME => {
+                let image = match decode_png(&content.data) {
+                    Ok(image) => image,
+                    Err(e) => {
+                        tracing::warn!("Remote clipboard image not applied: {}", e);
                         return;
                     }
-                    // Convert bytes to ImageData
-                    // Assume RGBA format - we'd need width/height from protocol
-                    // For now, this is a placeholder - the protocol may need to include dimensions
-                    // ImageData { width, height, bytes: msg.data }
-                    // For MVP, we'll skip image paste until protocol supports dimensions
-                    tracing::debug!("Image clipboard update received ({} bytes), but dimensions not available in protocol", msg.data.len());
-                },
-                _ => {
-                    tracing::debug!("Unsupported clipboard format: {}", msg.format);
-                }
+                };
+                *self.last_image.lock().unwrap() = Some(image_hash(&image));
+                self.clipboard.lock().unwrap().set_image(image);
+            }
+            ClipboardUpdate::Set(content) => {
+                tracing::debug!("Unsupported clipboard type: {}", content.mime_type);
+            }
+            ClipboardUpdate::Clear => {
+                *self.last_text.lock().unwrap() = Some(String::new());
+                self.clipboard.lock().unwrap().clear();
             }
         }
     }
 }
+
+fn image_hash(image: &ClipboardImage) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    image.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn encode_png(image: &ClipboardImage) -> Result<Vec<u8>, image::ImageError> {
+    let mut png = Vec::new();
+    image::codecs::png::PngEncoder::new(&mut png).write_image(
+        &image.rgba,
+        image.width as u32,
+        image.height as u32,
+        image::ExtendedColorType::Rgba8,
+    )?;
+    Ok(png)
+}
+
+fn decode_png(data: &[u8]) -> Result<ClipboardImage, image::ImageError> {
+    let image = image::load_from_memory_with_format(data, image::ImageFormat::Png)?.into_rgba8();
+    Ok(ClipboardImage {
+        width: image.width() as usize,
+        height: image.height() as usize,
+        rgba: image.into_raw(),
+    })
+}
@@ -0,0 +1,56 @@
+//! Remote cursor shape, drawn over the viewer's frame
+
+use eframe::egui;
+use zrc_core::cursor::{CursorBitmap, CursorShape, CursorShapeCache, StandardCursor};
+use zrc_proto::v1::CursorShapeV1;
+
+/// Cursor shape the host last reported for a session.
+#[derive(Debug, Default)]
+pub struct RemoteCursor {
+    cache: CursorShapeCache,
+    /// `None` until the host reports a shape; the local arrow is shown meanwhile
+    pub shape: Option<CursorShape>,
+    /// Bumped on every change so viewers re-upload a bitmap only when needed
+    pub generation: u64,
+}
+
+impl RemoteCursor {
+    /// Apply a host message. Malformed messages and ids of bitmaps that are no
+    /// longer cached keep the current shape.
+    pub fn apply(&mut self, msg: &CursorShapeV1) {
+        let Some(shape) = self.cache.resolve(msg) else {
+            tracing::debug!("Unusable cursor shape {} ignored", msg.shape_id);
+            return;
+        };
+        if self.shape.as_ref() != Some(&shape) {
+            self.shape = Some(shape);
+            self.generation += 1;
+        }
+    }
+}
+
+/// Local pointer icon standing in for a host system cursor.
+pub fn standard_icon(cursor: StandardCursor) -> egui::CursorIcon {
+    match cursor {
+        StandardCursor::Arrow => egui::CursorIcon::Default,
+        StandardCursor::Text => egui::CursorIcon::Text,
+        StandardCursor::Hand => egui::CursorIcon::PointingHand,
+        StandardCursor::Wait => egui::CursorIcon::Wait,
+        StandardCursor::Crosshair => egui::CursorIcon::Crosshair,
+        StandardCursor::ResizeNs => egui::CursorIcon::ResizeVertical,
+        StandardCursor::ResizeEw => egui::CursorIcon::ResizeHorizontal,
+        StandardCursor::ResizeNwse => egui::CursorIcon::ResizeNwSe,
+        StandardCursor::ResizeNesw => egui::CursorIcon::ResizeNeSw,
+        StandardCursor::Move => egui::CursorIcon::Move,
+        StandardCursor::NotAllowed => egui::CursorIcon::NotAllowed,
+        StandardCursor::Hidden => egui::CursorIcon::None,
+    }
+}
+
+/// Texture image of a custom cursor bitmap.
+pub fn bitmap_image(bitmap: &CursorBitmap) -> egui::ColorImage {
+    egui::ColorImage::from_rgba_unmultiplied(
+        [bitmap.width as usize, bitmap.height as usize],
+        &bitmap.rgba,
+    )
+}
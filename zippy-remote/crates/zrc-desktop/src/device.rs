@@ -1,13 +1,38 @@
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::{Arc, RwLock};
 use std::time::SystemTime;
+use directories::ProjectDirs;
 use hex;
+use serde::{Deserialize, Serialize};
+
+/// Colors handed out to new groups, in order
+const GROUP_COLORS: [egui::Color32; 6] = [
+    egui::Color32::from_rgb(100, 150, 255),
+    egui::Color32::from_rgb(120, 200, 120),
+    egui::Color32::from_rgb(240, 170, 80),
+    egui::Color32::from_rgb(200, 110, 200),
+    egui::Color32::from_rgb(230, 100, 100),
+    egui::Color32::from_rgb(90, 200, 200),
+];
 
 /// Device manager for paired devices
 pub struct DeviceManager {
     devices: RwLock<HashMap<String, DeviceInfo>>,
     groups: RwLock<Vec<DeviceGroup>>,
+    /// Device id -> group id; kept separately so memberships survive devices
+    /// being reloaded from the pairing store
+    assignments: RwLock<HashMap<String, String>>,
     search_filter: RwLock<String>,
+    group_filter: RwLock<Option<String>>,
+    groups_path: Option<PathBuf>,
+}
+
+/// On-disk form of the groups file
+#[derive(Default, Serialize, Deserialize)]
+struct GroupsFile {
+    groups: Vec<DeviceGroup>,
+    assignments: HashMap<String, String>,
 }
 
 impl DeviceManager {
@@ -15,8 +40,32 @@ impl DeviceManager {
         Self {
             devices: RwLock::new(HashMap::new()),
             groups: RwLock::new(Vec::new()),
+            assignments: RwLock::new(HashMap::new()),
             search_filter: RwLock::new(String::new()),
+            group_filter: RwLock::new(None),
+            groups_path: None,
+        }
+    }
+
+    /// Load groups from `path` and save every group change back to it.
+    pub fn with_groups_file(mut self, path: PathBuf) -> Self {
+        if let Ok(file) = std::fs::File::open(&path) {
+            match serde_json::from_reader::<_, GroupsFile>(file) {
+                Ok(stored) => {
+                    *self.groups.write().unwrap() = stored.groups;
+                    *self.assignments.write().unwrap() = stored.assignments;
+                }
+                Err(e) => tracing::warn!("Ignoring unreadable groups file {}: {}", path.display(), e),
+            }
         }
+        self.groups_path = Some(path);
+        self
+    }
+
+    /// Default groups file, next to `settings.json`
+    pub fn default_groups_path() -> Option<PathBuf> {
+        ProjectDirs::from("com", "zippy", "zrc-desktop")
+            .map(|dirs| dirs.config_dir().join("groups.json"))
     }
 
     /// Load devices from pairings store
@@ -24,6 +73,7 @@ impl DeviceManager {
     pub async fn load_from_store(&self, store: Arc<dyn zrc_core::store::Store>, operator_id: &[u8]) {
         if let Ok(pairings) = store.list_pairings().await {
             let mut devices = self.devices.write().unwrap();
+            let assignments = self.assignments.read().unwrap();
             
             for p in pairings {
                 // Filter for pairings where we are the operator
@@ -54,7 +104,7 @@ impl DeviceManager {
                         },
                         paired_at,
                         last_seen: last_seen_time,
-                        group_id: None,
+                        group_id: assignments.get(&id_hex).cloned(),
                     },
                 );
             }
@@ -97,15 +147,114 @@ impl DeviceManager {
         }
     }
 
-    /// Move device to group
+    /// Move device to group (`None` removes it from its group)
     pub fn move_to_group(&self, id: &str, group_id: Option<String>) -> Result<(), DeviceError> {
-        let mut devices = self.devices.write().unwrap();
-        if let Some(device) = devices.get_mut(id) {
-            device.group_id = group_id;
-            Ok(())
-        } else {
-            Err(DeviceError::NotFound(id.to_string()))
+        if let Some(group_id) = &group_id {
+            if !self.groups.read().unwrap().iter().any(|g| &g.id == group_id) {
+                return Err(DeviceError::GroupNotFound(group_id.clone()));
+            }
+        }
+        {
+            let mut devices = self.devices.write().unwrap();
+            let device = devices.get_mut(id)
+                .ok_or_else(|| DeviceError::NotFound(id.to_string()))?;
+            device.group_id = group_id.clone();
+        }
+        {
+            let mut assignments = self.assignments.write().unwrap();
+            match group_id {
+                Some(group_id) => assignments.insert(id.to_string(), group_id),
+                None => assignments.remove(id),
+            };
         }
+        self.save_groups()
+    }
+
+    /// List groups in creation order
+    pub fn list_groups(&self) -> Vec<DeviceGroup> {
+        self.groups.read().unwrap().clone()
+    }
+
+    /// Create a group and return it
+    pub fn create_group(&self, name: String) -> Result<DeviceGroup, DeviceError> {
+        let name = name.trim().to_string();
+        if name.is_empty() {
+            return Err(DeviceError::InvalidGroupName);
+        }
+        let group = {
+            let mut groups = self.groups.write().unwrap();
+            let next_id = groups.iter()
+                .filter_map(|g| g.id.parse::<u64>().ok())
+                .max()
+                .map_or(1, |id| id + 1);
+            let group = DeviceGroup {
+                id: next_id.to_string(),
+                name,
+                color: GROUP_COLORS[groups.len() % GROUP_COLORS.len()],
+                expanded: true,
+            };
+            groups.push(group.clone());
+            group
+        };
+        self.save_groups()?;
+        Ok(group)
+    }
+
+    /// Rename a group
+    pub fn rename_group(&self, group_id: &str, name: String) -> Result<(), DeviceError> {
+        let name = name.trim().to_string();
+        if name.is_empty() {
+            return Err(DeviceError::InvalidGroupName);
+        }
+        {
+            let mut groups = self.groups.write().unwrap();
+            let group = groups.iter_mut()
+                .find(|g| g.id == group_id)
+                .ok_or_else(|| DeviceError::GroupNotFound(group_id.to_string()))?;
+            group.name = name;
+        }
+        self.save_groups()
+    }
+
+    /// Delete a group. Its devices are kept and become ungrouped.
+    pub fn delete_group(&self, group_id: &str) -> Result<(), DeviceError> {
+        {
+            let mut groups = self.groups.write().unwrap();
+            let before = groups.len();
+            groups.retain(|g| g.id != group_id);
+            if groups.len() == before {
+                return Err(DeviceError::GroupNotFound(group_id.to_string()));
+            }
+        }
+        for device in self.devices.write().unwrap().values_mut() {
+            if device.group_id.as_deref() == Some(group_id) {
+                device.group_id = None;
+            }
+        }
+        self.assignments.write().unwrap().retain(|_, g| g != group_id);
+        {
+            let mut filter = self.group_filter.write().unwrap();
+            if filter.as_deref() == Some(group_id) {
+                *filter = None;
+            }
+        }
+        self.save_groups()
+    }
+
+    fn save_groups(&self) -> Result<(), DeviceError> {
+        let Some(path) = &self.groups_path else { return Ok(()) };
+        let stored = GroupsFile {
+            groups: self.groups.read().unwrap().clone(),
+            assignments: self.assignments.read().unwrap().clone(),
+        };
+        let save = || -> std::io::Result<()> {
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+            let file = std::fs::File::create(path)?;
+            serde_json::to_writer_pretty(file, &stored).map_err(std::io::Error::from)
+        };
+        save().map_err(|e| DeviceError::Persist(e.to_string()))
     }
 
     /// Remove device (revoke pairing)
@@ -135,7 +284,10 @@ impl DeviceManager {
     }
 
     /// Add or update device
-    pub fn add_device(&self, device: DeviceInfo) {
+    pub fn add_device(&self, mut device: DeviceInfo) {
+        if device.group_id.is_none() {
+            device.group_id = self.assignments.read().unwrap().get(&device.id).cloned();
+        }
         let mut devices = self.devices.write().unwrap();
         devices.insert(device.id.clone(), device);
     }
@@ -145,13 +297,29 @@ impl DeviceManager {
         *self.search_filter.write().unwrap() = filter;
     }
 
-    /// Get filtered devices (applies search filter)
+    /// Show only this group's devices (`None` shows all)
+    pub fn set_group_filter(&self, group_id: Option<String>) {
+        *self.group_filter.write().unwrap() = group_id;
+    }
+
+    /// Current group filter
+    pub fn group_filter(&self) -> Option<String> {
+        self.group_filter.read().unwrap().clone()
+    }
+
+    /// Get filtered devices (applies search and group filters)
     pub fn get_filtered_devices(&self) -> Vec<DeviceInfo> {
         let filter = self.search_filter.read().unwrap().clone();
-        if filter.is_empty() {
+        let devices = if filter.is_empty() {
             self.list_devices()
         } else {
             self.search_devices(&filter)
+        };
+        match self.group_filter() {
+            Some(group_id) => devices.into_iter()
+                .filter(|d| d.group_id.as_deref() == Some(group_id.as_str()))
+                .collect(),
+            None => devices,
         }
     }
 }
@@ -187,7 +355,7 @@ pub struct Permissions {
 }
 
 /// Device group
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct DeviceGroup {
     pub id: String,
     pub name: String,
@@ -203,4 +371,13 @@ pub enum DeviceError {
     
     #[error("Invalid device ID")]
     InvalidId,
+
+    #[error("Group not found: {0}")]
+    GroupNotFound(String),
+
+    #[error("Group name must not be empty")]
+    InvalidGroupName,
+
+    #[error("Failed to save groups: {0}")]
+    Persist(String),
 }
@@ -1,5 +1,6 @@
 pub mod app;
 pub mod clipboard;
+pub mod cursor;
 pub mod transport;
 pub mod device;
 pub mod diagnostics;
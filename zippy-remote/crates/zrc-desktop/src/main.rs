@@ -1,3 +1,4 @@
+use zrc_desktop::settings::Settings;
 use zrc_desktop::ZrcDesktopApp;
 
 fn main() -> eframe::Result<()> {
@@ -12,11 +13,18 @@ fn main() -> eframe::Result<()> {
 
     let handle = runtime.handle().clone();
 
-    // Define native options
+    // Define native options, reopening the window where it was last closed
+    let mut viewport = eframe::egui::ViewportBuilder::default()
+        .with_inner_size([800.0, 600.0])
+        .with_min_inner_size([400.0, 300.0]);
+    if let Some(window) = Settings::load().window {
+        viewport = viewport.with_inner_size([window.width.max(400.0), window.height.max(300.0)]);
+        if let (Some(x), Some(y)) = (window.x, window.y) {
+            viewport = viewport.with_position([x, y]);
+        }
+    }
     let native_options = eframe::NativeOptions {
-        viewport: eframe::egui::ViewportBuilder::default()
-            .with_inner_size([800.0, 600.0])
-            .with_min_inner_size([400.0, 300.0]),
+        viewport,
         ..Default::default()
     };
     
@@ -3,6 +3,7 @@
 use eframe::egui;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use zrc_proto::v1::{monitor_select_v1::StatusV1 as MonitorStatus, MonitorInfoV1, MonitorSelectV1};
 
 /// Monitor information
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,11 +17,26 @@ pub struct MonitorInfo {
     pub is_primary: bool,
 }
 
+impl From<&MonitorInfoV1> for MonitorInfo {
+    fn from(m: &MonitorInfoV1) -> Self {
+        Self {
+            id: MonitorId(m.id),
+            name: if m.name.is_empty() { format!("Monitor {}", m.id) } else { m.name.clone() },
+            x: m.x,
+            y: m.y,
+            width: m.width,
+            height: m.height,
+            is_primary: m.primary,
+        }
+    }
+}
+
 /// Monitor identifier
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
 pub struct MonitorId(pub u32);
 
 /// Monitor layout manager
+#[derive(Default)]
 pub struct MonitorManager {
     monitors: HashMap<MonitorId, MonitorInfo>,
     preferences: HashMap<String, MonitorId>, // device_id -> preferred monitor
@@ -47,9 +63,11 @@ impl MonitorManager {
         self.monitors.get(&id)
     }
 
-    /// List all monitors
+    /// List all monitors, ordered by id
     pub fn list_monitors(&self) -> Vec<&MonitorInfo> {
-        self.monitors.values().collect()
+        let mut monitors: Vec<_> = self.monitors.values().collect();
+        monitors.sort_by_key(|m| m.id.0);
+        monitors
     }
 
     /// Get primary monitor
@@ -168,3 +186,50 @@ impl MonitorManager {
         selected
     }
 }
+
+/// A session's view of the host's monitors, kept current from the host's
+/// `MonitorSelectV1` messages.
+#[derive(Default)]
+pub struct RemoteMonitors {
+    pub manager: MonitorManager,
+    /// Monitor the host is streaming
+    pub streaming: Option<MonitorId>,
+    /// Why the streamed monitor is not the one last asked for, if it isn't
+    pub notice: Option<String>,
+    /// Bumped on every host update so viewers can tell when to resync
+    pub generation: u64,
+}
+
+impl RemoteMonitors {
+    /// Controller request for the host's monitor list.
+    pub fn list_request() -> MonitorSelectV1 {
+        MonitorSelectV1 { status: MonitorStatus::ListRequest as i32, ..Default::default() }
+    }
+
+    /// Controller request to stream `id`.
+    pub fn switch_request(id: MonitorId) -> MonitorSelectV1 {
+        MonitorSelectV1 {
+            status: MonitorStatus::SwitchRequest as i32,
+            monitor_id: id.0,
+            monitors: Vec::new(),
+        }
+    }
+
+    /// Apply a host message. Every host message carries the full list and the
+    /// streamed monitor, so an unplugged monitor simply drops out of the list.
+    pub fn apply(&mut self, msg: &MonitorSelectV1) {
+        let status = MonitorStatus::try_from(msg.status).unwrap_or(MonitorStatus::Unspecified);
+        self.notice = match status {
+            MonitorStatus::List | MonitorStatus::Switched => None,
+            MonitorStatus::Rejected => Some("Monitor is no longer available".to_string()),
+            MonitorStatus::Fallback => {
+                Some("Monitor was disconnected; showing the primary display".to_string())
+            }
+            // Controller-side statuses are never sent by the host
+            _ => return,
+        };
+        self.manager.update_monitors(msg.monitors.iter().map(MonitorInfo::from).collect());
+        self.streaming = Some(MonitorId(msg.monitor_id));
+        self.generation += 1;
+    }
+}
@@ -121,27 +121,41 @@ proptest! {
     /// For any clipboard sync operation, content exceeding the size limit SHALL be rejected without partial transfer.
     #[test]
     fn test_clipboard_size_enforcement(
-        _max_size in 1024usize..10485760usize, // 1KB to 10MB
-        _content_size in 512usize..20971520usize, // 512B to 20MB
+        max_size in 1024usize..65536usize,
+        content_size in 512usize..131072usize,
     ) {
-        use crate::clipboard::ClipboardManager;
+        use crate::clipboard::{ClipboardManager, LocalClipboard};
         use tokio::sync::mpsc;
-        
-        // We need runtime for channel
-         let runtime = tokio::runtime::Runtime::new().unwrap();
-         let _guard = runtime.enter();
+        use zrc_core::clipboard::ClipboardConfig;
+        use zrc_proto::v1::PermissionsV1;
 
-        let (tx, _rx) = mpsc::channel(10);
-        let manager = ClipboardManager::new(tx).unwrap();
-        manager.set_enabled(true);
-        // manager.set_max_size(max_size); // Not implemented yet?
-        
-        // If set_max_size is not implemented, we can't test it.
-        // Checking clipboard.rs content (Step 1514 showed 8KB).
-        // I should check if set_max_size exists.
-        // If not, I skip this test logic for now or implement it.
-        // Assuming it's NOT implemented based on previous steps.
-        // I will assume it passes for now to fix compilation.
+        struct FixedClipboard(String);
+        impl LocalClipboard for FixedClipboard {
+            fn get_text(&mut self) -> Option<String> {
+                Some(self.0.clone())
+            }
+            fn set_text(&mut self, _text: String) {}
+            fn clear(&mut self) {}
+        }
+
+        let (tx, mut rx) = mpsc::channel(1);
+        let manager = ClipboardManager::with_clipboard(
+            Box::new(FixedClipboard("x".repeat(content_size))),
+            tx,
+            PermissionsV1::Clipboard as u32,
+            ClipboardConfig { max_bytes: max_size, chunk_bytes: max_size },
+        );
+
+        let sent = manager.poll_local();
+        prop_assert_eq!(sent, content_size <= max_size);
+        if sent {
+            let msg = rx.try_recv().unwrap();
+            prop_assert!(matches!(
+                msg.payload,
+                Some(zrc_proto::v1::control_msg_v1::Payload::Clipboard(ref c)) if c.data.len() == content_size
+            ));
+        }
+        prop_assert!(rx.try_recv().is_err());
     }
 }
 
@@ -318,4 +332,481 @@ mod unit_tests {
         // (Full UI testing would require egui context)
         assert!(true, "Accessibility helpers are available");
     }
+
+    /// Successive input sends on one session carry increasing sequence
+    /// numbers, and a failed send is surfaced to the UI once.
+    #[tokio::test]
+    async fn test_control_sequence_increments() {
+        use crate::session::{ControlSender, SessionEvent, SessionId};
+        use prost::Message;
+        use std::sync::Arc;
+        use zrc_proto::v1::{control_msg_v1::Payload, ControlMsgV1, InputEventV1};
+        use zrc_transport::{LoopbackTransport, MediaSession};
+
+        let (local, remote) = LoopbackTransport::pair();
+        let local = Arc::new(local);
+        let (events_tx, mut events_rx) = tokio::sync::mpsc::channel(4);
+        let sender = ControlSender::new(SessionId(7), local.clone(), Some(events_tx));
+
+        for expected in 0..3u64 {
+            let sent = sender.send(Payload::Input(InputEventV1::default())).await.unwrap();
+            assert_eq!(sent, expected);
+            let msg = ControlMsgV1::decode(remote.recv_control().await.unwrap()).unwrap();
+            assert_eq!(msg.sequence_number, expected);
+        }
+
+        local.disconnect();
+        assert!(sender.send(Payload::Input(InputEventV1::default())).await.is_err());
+        assert!(sender.send(Payload::Input(InputEventV1::default())).await.is_err());
+        match events_rx.try_recv() {
+            Ok(SessionEvent::Error { session_id, .. }) => assert_eq!(session_id, SessionId(7)),
+            other => panic!("expected an error event, got {:?}", other),
+        }
+        assert!(events_rx.try_recv().is_err());
+    }
+
+    /// Host monitor messages refresh the dropdown list and streamed monitor,
+    /// including when the streamed monitor is unplugged.
+    #[test]
+    fn test_remote_monitors_follow_host() {
+        use crate::monitor::{MonitorId, RemoteMonitors};
+        use zrc_proto::v1::{monitor_select_v1::StatusV1, MonitorInfoV1, MonitorSelectV1};
+
+        let info = |id: u32, primary: bool| MonitorInfoV1 {
+            id,
+            width: 1920,
+            height: 1080,
+            primary,
+            ..Default::default()
+        };
+        let mut monitors = RemoteMonitors::default();
+
+        monitors.apply(&MonitorSelectV1 {
+            status: StatusV1::Switched as i32,
+            monitor_id: 2,
+            monitors: vec![info(1, true), info(2, false)],
+        });
+        assert_eq!(monitors.streaming, Some(MonitorId(2)));
+        assert_eq!(monitors.manager.list_monitors().len(), 2);
+        assert_eq!(monitors.manager.list_monitors()[1].name, "Monitor 2");
+        assert!(monitors.notice.is_none());
+
+        monitors.apply(&MonitorSelectV1 {
+            status: StatusV1::Fallback as i32,
+            monitor_id: 1,
+            monitors: vec![info(1, true)],
+        });
+        assert_eq!(monitors.streaming, Some(MonitorId(1)));
+        assert_eq!(monitors.manager.list_monitors().len(), 1);
+        assert!(monitors.notice.is_some());
+        assert_eq!(monitors.generation, 2);
+
+        // Controller requests echoed back are ignored
+        monitors.apply(&RemoteMonitors::switch_request(MonitorId(3)));
+        assert_eq!(monitors.generation, 2);
+    }
+
+    /// Host cursor messages update the shape; id-only repeats resolve from
+    /// the cache and unknown ids keep the current shape.
+    #[test]
+    fn test_remote_cursor_follows_host() {
+        use crate::cursor::RemoteCursor;
+        use std::time::{Duration, Instant};
+        use zrc_core::cursor::{CursorShape, CursorTracker, StandardCursor};
+        use zrc_proto::v1::CursorShapeV1;
+
+        let mut tracker = CursorTracker::new(Duration::ZERO);
+        let mut cursor = RemoteCursor::default();
+        let bitmap = zrc_core::cursor::CursorBitmap {
+            width: 1,
+            height: 1,
+            hotspot_x: 0,
+            hotspot_y: 0,
+            rgba: bytes::Bytes::from_static(&[1, 2, 3, 4]),
+        };
+        let now = Instant::now();
+
+        cursor.apply(&tracker.observe(CursorShape::Bitmap(bitmap.clone()), now).unwrap());
+        assert_eq!(cursor.shape, Some(CursorShape::Bitmap(bitmap.clone())));
+        cursor.apply(&tracker.observe(CursorShape::Standard(StandardCursor::Text), now).unwrap());
+        assert_eq!(cursor.shape, Some(CursorShape::Standard(StandardCursor::Text)));
+
+        let cached = tracker.observe(CursorShape::Bitmap(bitmap.clone()), now).unwrap();
+        assert!(cached.rgba.is_empty());
+        cursor.apply(&cached);
+        assert_eq!(cursor.shape, Some(CursorShape::Bitmap(bitmap)));
+        assert_eq!(cursor.generation, 3);
+
+        cursor.apply(&CursorShapeV1 { shape_id: 42, ..Default::default() });
+        assert_eq!(cursor.generation, 3);
+    }
+
+    /// Group filtering returns only members and composes with search;
+    /// deleting a group ungroups its devices instead of removing them.
+    #[test]
+    fn test_device_groups_filter_and_delete() {
+        use crate::device::{DeviceInfo, DeviceManager, DeviceStatus};
+        use std::time::SystemTime;
+
+        let device = |id: &str, name: &str| DeviceInfo {
+            id: id.to_string(),
+            display_name: name.to_string(),
+            status: DeviceStatus::Unknown,
+            permissions: Default::default(),
+            paired_at: SystemTime::now(),
+            last_seen: None,
+            group_id: None,
+        };
+        let path = std::env::temp_dir().join(format!("zrc-groups-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let manager = DeviceManager::new().with_groups_file(path.clone());
+        manager.add_device(device("aa01", "Office PC"));
+        manager.add_device(device("bb02", "Office Laptop"));
+        manager.add_device(device("cc03", "Home PC"));
+
+        let office = manager.create_group("Office".to_string()).unwrap();
+        manager.move_to_group("aa01", Some(office.id.clone())).unwrap();
+        manager.move_to_group("bb02", Some(office.id.clone())).unwrap();
+        assert!(manager.move_to_group("cc03", Some("missing".to_string())).is_err());
+
+        manager.set_group_filter(Some(office.id.clone()));
+        let mut ids: Vec<_> = manager.get_filtered_devices().into_iter().map(|d| d.id).collect();
+        ids.sort();
+        assert_eq!(ids, vec!["aa01", "bb02"]);
+
+        manager.set_search_filter("laptop".to_string());
+        let ids: Vec<_> = manager.get_filtered_devices().into_iter().map(|d| d.id).collect();
+        assert_eq!(ids, vec!["bb02"]);
+        manager.set_search_filter(String::new());
+
+        // Groups and memberships survive a restart
+        let reloaded = DeviceManager::new().with_groups_file(path.clone());
+        reloaded.add_device(device("aa01", "Office PC"));
+        assert_eq!(reloaded.list_groups().len(), 1);
+        assert_eq!(reloaded.get_device("aa01").unwrap().group_id, Some(office.id.clone()));
+
+        manager.delete_group(&office.id).unwrap();
+        assert!(manager.list_groups().is_empty());
+        assert_eq!(manager.group_filter(), None);
+        assert_eq!(manager.list_devices().len(), 3);
+        assert!(manager.list_devices().iter().all(|d| d.group_id.is_none()));
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    /// Retry on a connection error dialog starts a new attempt for that
+    /// dialog's device; fatal errors cannot be retried.
+    #[tokio::test]
+    async fn test_retry_reconnects_dialog_device() {
+        use crate::session::{SessionEvent, SessionManager};
+        use crate::ui::{retry_connection, Dialog, UiState};
+        use std::sync::Arc;
+        use std::time::Duration;
+        use zrc_core::keys::generate_identity_keys;
+        use zrc_core::store::InMemoryStore;
+
+        let mut manager = SessionManager::new(generate_identity_keys(), Arc::new(InMemoryStore::new()));
+        let (events_tx, mut events_rx) = tokio::sync::mpsc::channel(4);
+        manager.set_event_sender(events_tx);
+        let manager = Arc::new(manager);
+        let runtime = tokio::runtime::Handle::current();
+
+        let device_id = hex::encode([0x42u8; 32]);
+        let mut ui_state = UiState::default();
+        ui_state.dialogs.push(Dialog::ConnectionError {
+            device_id: hex::encode([0x17u8; 32]),
+            error: "not paired".to_string(),
+            retriable: false,
+        });
+        ui_state.dialogs.push(Dialog::ConnectionError {
+            device_id: device_id.clone(),
+            error: "timed out".to_string(),
+            retriable: true,
+        });
+
+        retry_connection(&mut ui_state, &runtime, &manager, Duration::from_secs(5), 0);
+        assert_eq!(ui_state.dialogs.len(), 2);
+        assert!(matches!(ui_state.dialogs[0], Dialog::ConnectionError { .. }));
+
+        retry_connection(&mut ui_state, &runtime, &manager, Duration::from_secs(5), 1);
+        assert_eq!(ui_state.dialogs.len(), 2);
+        assert!(matches!(&ui_state.dialogs[1], Dialog::ConnectionProgress { device_id: pending, .. } if *pending == device_id));
+
+        // The attempt reaches the session manager for that device; it has no
+        // pairing here, so it fails at once and is not retried automatically
+        match tokio::time::timeout(Duration::from_secs(5), events_rx.recv()).await.unwrap() {
+            Some(SessionEvent::ConnectFailed { device_id: failed, retriable, .. }) => {
+                assert_eq!(failed, device_id);
+                assert!(!retriable);
+            }
+            other => panic!("expected a failed connection attempt, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_clipboard_toggle_gates_sync() {
+        use std::sync::{Arc, Mutex};
+        use crate::clipboard::{ClipboardManager, LocalClipboard};
+        use zrc_core::clipboard::{clipboard_set_messages, ClipboardConfig, ClipboardContent};
+        use zrc_proto::v1::{control_msg_v1, PermissionsV1};
+
+        #[derive(Clone, Default)]
+        struct SharedClipboard(Arc<Mutex<Option<String>>>);
+        impl LocalClipboard for SharedClipboard {
+            fn get_text(&mut self) -> Option<String> {
+                self.0.lock().unwrap().clone()
+            }
+            fn set_text(&mut self, text: String) {
+                *self.0.lock().unwrap() = Some(text);
+            }
+            fn clear(&mut self) {
+                *self.0.lock().unwrap() = None;
+            }
+        }
+
+        let local = SharedClipboard::default();
+        let (tx, mut rx) = tokio::sync::mpsc::channel(16);
+        let manager = ClipboardManager::with_clipboard(
+            Box::new(local.clone()),
+            tx,
+            PermissionsV1::Clipboard as u32,
+            ClipboardConfig::default(),
+        );
+
+        *local.0.lock().unwrap() = Some("secret".to_string());
+        manager.set_enabled(false);
+        assert!(!manager.poll_local());
+        assert!(rx.try_recv().is_err());
+
+        manager.set_enabled(true);
+        assert!(manager.poll_local());
+        match rx.try_recv().unwrap().payload {
+            Some(control_msg_v1::Payload::Clipboard(msg)) => assert_eq!(msg.data, b"secret"),
+            other => panic!("expected a clipboard message, got {:?}", other),
+        }
+        assert!(!manager.poll_local());
+
+        // Text received from the remote is applied but not sent back
+        let remote = clipboard_set_messages(7, &ClipboardContent::text("from host"), &ClipboardConfig::default()).unwrap();
+        manager.apply_remote_update(remote[0].clone());
+        assert_eq!(local.0.lock().unwrap().as_deref(), Some("from host"));
+        assert!(!manager.poll_local());
+
+        // Disabled sync ignores remote updates too
+        manager.set_enabled(false);
+        let remote = clipboard_set_messages(8, &ClipboardContent::text("ignored"), &ClipboardConfig::default()).unwrap();
+        manager.apply_remote_update(remote[0].clone());
+        assert_eq!(local.0.lock().unwrap().as_deref(), Some("from host"));
+
+        // Without the pairing's clipboard permission nothing is sent
+        manager.set_enabled(true);
+        manager.set_permissions(PermissionsV1::View as u32);
+        *local.0.lock().unwrap() = Some("changed".to_string());
+        assert!(!manager.poll_local());
+        assert!(rx.try_recv().is_err());
+    }
+
+    /// Local images are sent as PNG once per change; remote images are
+    /// applied and not sent back.
+    #[test]
+    fn test_clipboard_images_sync_as_png() {
+        use std::sync::{Arc, Mutex};
+        use crate::clipboard::{ClipboardImage, ClipboardManager, LocalClipboard};
+        use zrc_core::clipboard::{clipboard_set_messages, ClipboardConfig, ClipboardContent};
+        use zrc_proto::v1::{control_msg_v1, PermissionsV1};
+
+        #[derive(Clone, Default)]
+        struct ImageClipboard(Arc<Mutex<Option<ClipboardImage>>>);
+        impl LocalClipboard for ImageClipboard {
+            fn get_text(&mut self) -> Option<String> {
+                None
+            }
+            fn set_text(&mut self, _text: String) {}
+            fn clear(&mut self) {
+                *self.0.lock().unwrap() = None;
+            }
+            fn get_image(&mut self) -> Option<ClipboardImage> {
+                self.0.lock().unwrap().clone()
+            }
+            fn set_image(&mut self, image: ClipboardImage) {
+                *self.0.lock().unwrap() = Some(image);
+            }
+        }
+
+        let image = |fill: u8| ClipboardImage { width: 2, height: 1, rgba: vec![fill; 8] };
+        let local = ImageClipboard::default();
+        let (tx, mut rx) = tokio::sync::mpsc::channel(16);
+        let manager = ClipboardManager::with_clipboard(
+            Box::new(local.clone()),
+            tx,
+            PermissionsV1::Clipboard as u32,
+            ClipboardConfig::default(),
+        );
+
+        *local.0.lock().unwrap() = Some(image(1));
+        assert!(manager.poll_local());
+        let png = match rx.try_recv().unwrap().payload {
+            Some(control_msg_v1::Payload::Clipboard(msg)) => {
+                assert_eq!(msg.mime_type, "image/png");
+                msg.data
+            }
+            other => panic!("expected a clipboard message, got {:?}", other),
+        };
+        assert!(!manager.poll_local());
+
+        *local.0.lock().unwrap() = Some(image(2));
+        assert!(manager.poll_local());
+        assert!(rx.try_recv().is_ok());
+
+        // The first image coming back from the host decodes to the same pixels
+        let remote = clipboard_set_messages(
+            9,
+            &ClipboardContent { mime_type: "image/png".to_string(), data: png },
+            &ClipboardConfig::default(),
+        )
+        .unwrap();
+        manager.apply_remote_update(remote[0].clone());
+        assert_eq!(local.0.lock().unwrap().clone(), Some(image(1)));
+        assert!(!manager.poll_local());
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_device_view_settings_are_per_device() {
+        use crate::settings::{DeviceViewSettings, Settings, WindowGeometry};
+        use crate::viewer::ZoomLevel;
+
+        let device_a = hex::encode([0xAAu8; 32]);
+        let device_b = hex::encode([0xBBu8; 32]);
+
+        let mut settings = Settings {
+            default_input_mode: "control".to_string(),
+            window: Some(WindowGeometry { width: 1024.0, height: 768.0, x: Some(40.0), y: Some(60.0) }),
+            ..Default::default()
+        };
+        let view_a = DeviceViewSettings {
+            zoom: ZoomLevel::Custom(1.5),
+            input_mode: "view_only".to_string(),
+            quality: 35,
+            show_toolbar: false,
+            show_stats: false,
+            auto_reconnect: true,
+        };
+        settings.set_device_view(&device_a, view_a.clone());
+
+        // Device B has nothing saved, so it gets the global defaults
+        let view_b = settings.device_view(&device_b);
+        assert_eq!(view_b.input_mode, "control");
+        assert_eq!(view_b.zoom, ZoomLevel::Fit);
+        assert_eq!(view_b.quality, 80);
+        assert!(view_b.show_toolbar && view_b.show_stats);
+
+        let path = std::env::temp_dir().join(format!("zrc-settings-{}.json", std::process::id()));
+        settings.save_to(&path).unwrap();
+        let loaded = Settings::load_from(&path);
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(loaded.device_view(&device_a), view_a);
+        assert_eq!(loaded.device_view(&device_b), view_b);
+        assert_eq!(loaded.devices.len(), 1);
+        assert_eq!(loaded.window, settings.window);
+    }
+
+    /// A viewer with auto-reconnect on shows the reconnect overlay when its
+    /// media session drops and clears it once it is handed a new session.
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_reconnect_overlay_on_media_drop() {
+        use crate::clipboard::{ClipboardManager, LocalClipboard};
+        use crate::session::{ActiveSession, Capabilities, SessionId, SessionStats};
+        use crate::settings::DeviceViewSettings;
+        use crate::viewer::{ViewerAction, ViewerWindow};
+        use std::sync::atomic::AtomicBool;
+        use std::sync::{Arc, RwLock};
+        use std::time::{Duration, Instant};
+        use zrc_core::clipboard::ClipboardConfig;
+        use zrc_core::keys::generate_identity_keys;
+        use zrc_core::session::SessionController;
+        use zrc_core::store::InMemoryStore;
+        use zrc_transport::LoopbackTransport;
+
+        struct NoClipboard;
+        impl LocalClipboard for NoClipboard {
+            fn get_text(&mut self) -> Option<String> {
+                None
+            }
+            fn set_text(&mut self, _text: String) {}
+            fn clear(&mut self) {}
+        }
+
+        let device_id = hex::encode([0x5Au8; 32]);
+        let loopback_session = |id: u64| {
+            let (local, remote) = LoopbackTransport::pair();
+            let (control_tx, _control_rx) = tokio::sync::mpsc::channel(8);
+            let session = Arc::new(ActiveSession {
+                id: SessionId(id),
+                core_id: [0u8; 32],
+                device_id: device_id.clone(),
+                capabilities: Capabilities::default(),
+                started_at: Instant::now(),
+                controller: Arc::new(tokio::sync::Mutex::new(SessionController::new(
+                    generate_identity_keys(),
+                    Arc::new(InMemoryStore::new()),
+                ))),
+                media_session: Arc::new(local),
+                file_transfer: Arc::new(crate::transfer::FileTransferManager::new()),
+                clipboard_manager: Arc::new(ClipboardManager::with_clipboard(
+                    Box::new(NoClipboard),
+                    control_tx.clone(),
+                    0,
+                    ClipboardConfig::default(),
+                )),
+                control_tx,
+                recording: Arc::new(AtomicBool::new(false)),
+                monitors: Arc::default(),
+                cursor: Arc::default(),
+                stats: RwLock::new(SessionStats::default()),
+                diagnostics: crate::diagnostics::ConnectionDiagnostics::new(),
+            });
+            (session, remote)
+        };
+
+        let (first, first_remote) = loopback_session(1);
+        let mut viewer = ViewerWindow::new(first, tokio::runtime::Handle::current());
+        viewer.apply_view_settings(&DeviceViewSettings { auto_reconnect: true, ..Default::default() });
+        assert!(viewer.auto_reconnects());
+        assert!(viewer.connection_overlay().is_none());
+
+        // Simulate the link dropping under the viewer
+        first_remote.disconnect();
+        let ctx = eframe::egui::Context::default();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while viewer.connection_overlay().is_none() {
+            assert!(Instant::now() < deadline, "media drop not noticed");
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            viewer.poll_frames(&ctx);
+        }
+        assert!(viewer.is_reconnecting());
+        assert!(viewer.connection_overlay().unwrap().starts_with("Reconnecting"));
+
+        let result_tx = match viewer.poll_reconnect() {
+            Some(ViewerAction::Reconnect { device_id: target, result_tx }) => {
+                assert_eq!(target, device_id);
+                result_tx
+            }
+            _ => panic!("expected a reconnect request"),
+        };
+        // Only one attempt is in flight at a time
+        assert!(viewer.poll_reconnect().is_none());
+
+        let (second, _second_remote) = loopback_session(2);
+        assert!(result_tx.send(Ok(second)).is_ok());
+        match viewer.poll_reconnect() {
+            Some(ViewerAction::Reconnected { previous }) => assert_eq!(previous, SessionId(1)),
+            _ => panic!("expected the viewer to adopt the new session"),
+        }
+        assert_eq!(viewer.session_id(), SessionId(2));
+        assert!(viewer.connection_overlay().is_none());
+        assert!(!viewer.is_reconnecting());
+    }
 }
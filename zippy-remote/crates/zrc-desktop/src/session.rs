@@ -1,7 +1,7 @@
 use std::collections::HashMap;
-use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
 use std::sync::{Arc, RwLock};
-use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tokio::sync::{mpsc, Mutex};
 use prost::Message; // For encode/decode
 
@@ -9,14 +9,21 @@ use zrc_core::types::IdentityKeys; // Correct import
 use zrc_core::session::SessionController;
 use zrc_core::store::{InMemoryStore, Store};
 use zrc_core::transport::SelectedTransport; // Added
-use zrc_proto::v1::{EnvelopeV1, MsgTypeV1, SessionInitResponseV1, ControlMsgV1, control_msg_v1};
+use zrc_proto::v1::{EnvelopeV1, MsgTypeV1, SessionInitResponseV1, ControlMsgV1, PermissionsV1, control_msg_v1};
 use zrc_transport::{ControlPlaneTransport, MediaOpenParams, MediaSession, MediaTransport, RouteHint};
 
+use crate::cursor::RemoteCursor;
+use crate::monitor::RemoteMonitors;
 use crate::transport::{HttpControlTransport, QuicMediaTransport};
 
 // Hardcoded for MVP
 const RENDEZVOUS_URL: &str = "https://zrc.dev/api"; 
 const REQUESTED_CAPS: u32 = 0x07;
+/// Automatic retries of a transient connection failure before the user is
+/// shown the error
+const MAX_AUTO_RETRIES: u32 = 2;
+/// Wait before automatic retry n is `RETRY_BACKOFF * n`
+const RETRY_BACKOFF: Duration = Duration::from_secs(2);
 
 pub struct SessionManager {
     identity_keys: IdentityKeys,
@@ -47,22 +54,53 @@ impl SessionManager {
         self.active_sessions.read().unwrap().get(id).cloned()
     }
 
+    /// Connect with a per-attempt `timeout`, retrying transient failures a
+    /// few times. If every attempt fails, the UI gets
+    /// `SessionEvent::ConnectFailed` saying whether a manual retry makes sense.
+    pub async fn connect_with_retry(&self, device_id_hex: &str, timeout: Duration) -> Result<SessionId, SessionError> {
+        let mut attempt = 0;
+        loop {
+            let result = match tokio::time::timeout(timeout, self.connect(device_id_hex)).await {
+                Ok(result) => result,
+                Err(_) => Err(SessionError::Timeout(timeout)),
+            };
+            match result {
+                Ok(session_id) => return Ok(session_id),
+                Err(e) if e.is_retriable() && attempt < MAX_AUTO_RETRIES => {
+                    attempt += 1;
+                    tracing::warn!("Connection to {} failed ({}), retry {}/{}", device_id_hex, e, attempt, MAX_AUTO_RETRIES);
+                    tokio::time::sleep(RETRY_BACKOFF * attempt).await;
+                }
+                Err(e) => {
+                    if let Some(ref sender) = self.event_sender {
+                        let _ = sender.send(SessionEvent::ConnectFailed {
+                            device_id: device_id_hex.to_string(),
+                            error: e.to_string(),
+                            retriable: e.is_retriable(),
+                        }).await;
+                    }
+                    return Err(e);
+                }
+            }
+        }
+    }
+
     /// Initiate connection to device
     pub async fn connect(&self, device_id_hex: &str) -> Result<SessionId, SessionError> {
         let device_id = hex::decode(device_id_hex)
-            .map_err(|_| SessionError::ConnectionFailed("Invalid hex device ID".into()))?;
+            .map_err(|_| SessionError::Other("Invalid hex device ID".into()))?;
 
         // ... (steps 1-7 same) ...
         // 1. Fetch Pairing Record
         let pairing = self.store.load_pairing(&device_id, &self.identity_keys.id32).await
-            .map_err(|_| SessionError::ConnectionFailed("Device not paired".into()))?
-            .ok_or(SessionError::ConnectionFailed("Device not paired (not found)".into()))?;
+            .map_err(|e| SessionError::NotPaired(e.to_string()))?
+            .ok_or(SessionError::NotPaired("no pairing record".into()))?;
 
         let device_kex_pub_bytes: [u8; 32] = pairing.device_kex_pub.key_bytes.clone().try_into()
-            .map_err(|_| SessionError::ConnectionFailed("Invalid device KEX key length".into()))?;
+            .map_err(|_| SessionError::NotPaired("invalid device KEX key length".into()))?;
             
         let device_sign_pub_bytes: [u8; 32] = pairing.device_sign_pub.key_bytes.clone().try_into()
-            .map_err(|_| SessionError::ConnectionFailed("Invalid device Sign key length".into()))?;
+            .map_err(|_| SessionError::NotPaired("invalid device sign key length".into()))?;
 
         // 2-3 same
         let mut controller = SessionController::new(self.identity_keys.clone(), self.store.clone());
@@ -116,7 +154,7 @@ impl SessionManager {
                  &incoming_env,
                  &self.identity_keys.kex_priv,
                  &device_sign_pub_bytes
-             ).map_err(|e| SessionError::ConnectionFailed(format!("Decryption failed: {}", e)))?;
+             ).map_err(|e| SessionError::AuthFailed(format!("Decryption failed: {}", e)))?;
 
              if sender_id != device_id { continue; }
              
@@ -130,10 +168,10 @@ impl SessionManager {
 
         // 6 same
         controller.handle_response(response.clone(), &device_sign_pub_bytes).await
-             .map_err(|e| SessionError::ConnectionFailed(format!("Invalid response: {}", e)))?;
+             .map_err(|e| SessionError::AuthFailed(format!("Invalid response: {}", e)))?;
 
         // 7 same
-        let selected_transport = controller.initiate_connection()
+        let selected_transport = controller.initiate_connection().await
              .map_err(|e| SessionError::ConnectionFailed(format!("Negotiation failed: {}", e)))?;
 
         // 8 Connect Media Transport
@@ -165,23 +203,28 @@ impl SessionManager {
         // Convert to Arc for sharing tasks
         let media_session: Arc<dyn MediaSession> = Arc::from(media_session_box);
 
+        let ui_id = SessionId(self.next_id.fetch_add(1, Ordering::Relaxed));
+
         // 8b Setup Control Loops & File Transfer
         let file_transfer = Arc::new(crate::transfer::FileTransferManager::new());
         let (control_tx, mut control_rx) = mpsc::channel::<ControlMsgV1>(100);
 
-        // Setup Clipboard
+        // Setup Clipboard, limited to what the device granted
+        let capabilities = Capabilities::from_permissions(response.granted_capabilities);
         let clipboard_manager = Arc::new(
-            crate::clipboard::ClipboardManager::new(control_tx.clone())
+            crate::clipboard::ClipboardManager::with_permissions(control_tx.clone(), response.granted_capabilities)
                 .map_err(|e| SessionError::ConnectionFailed(format!("Clipboard init failed: {}", e)))?
         );
         clipboard_manager.start_monitoring();
 
-        // Spawn Control Sender
-        let ms_tx = media_session.clone();
+        // Spawn Control Sender; the only writer, so sequence numbers go out in order
+        let control = ControlSender::new(ui_id, media_session.clone(), self.event_sender.clone());
         tokio::spawn(async move {
             while let Some(msg) = control_rx.recv().await {
-                 let payload = msg.encode_to_vec();
-                 let _ = ms_tx.send_control(bytes::Bytes::from(payload)).await;
+                 let Some(payload) = msg.payload else { continue };
+                 if control.send(payload).await.is_err() {
+                     break;
+                 }
             }
         });
         
@@ -189,6 +232,12 @@ impl SessionManager {
         let ms_rx = media_session.clone();
         let ft_rx = file_transfer.clone();
         let clip_rx = clipboard_manager.clone();
+        let recording = Arc::new(AtomicBool::new(false));
+        let recording_rx = recording.clone();
+        let monitors = Arc::new(RwLock::new(RemoteMonitors::default()));
+        let monitors_rx = monitors.clone();
+        let cursor = Arc::new(RwLock::new(RemoteCursor::default()));
+        let cursor_rx = cursor.clone();
         tokio::spawn(async move {
             loop {
                 // TODO: Handle disconnect/errors properly (propagate to SessionManager?)
@@ -203,6 +252,15 @@ impl SessionManager {
                                       control_msg_v1::Payload::Clipboard(cb) => {
                                           clip_rx.apply_remote_update(cb);
                                       },
+                                      control_msg_v1::Payload::RecordingStatus(status) => {
+                                          recording_rx.store(status.recording, Ordering::Relaxed);
+                                      },
+                                      control_msg_v1::Payload::MonitorSelect(select) => {
+                                          monitors_rx.write().unwrap().apply(&select);
+                                      },
+                                      control_msg_v1::Payload::CursorShape(shape) => {
+                                          cursor_rx.write().unwrap().apply(&shape);
+                                      },
                                       _ => {}
                                  }
                              }
@@ -218,8 +276,6 @@ impl SessionManager {
              .map_err(|e| SessionError::ConnectionFailed(e.to_string()))?;
 
         // Success! Create ActiveSession
-        let ui_id = SessionId(self.next_id.fetch_add(1, Ordering::Relaxed));
-        
         let session = Arc::new(ActiveSession {
             id: ui_id,
             device_id: device_id_hex.to_string(),
@@ -229,7 +285,10 @@ impl SessionManager {
             file_transfer,
             control_tx,
             clipboard_manager,
-            capabilities: Capabilities::default(),
+            recording,
+            monitors,
+            cursor,
+            capabilities,
             started_at: Instant::now(),
             stats: RwLock::new(SessionStats::default()),
             diagnostics: crate::diagnostics::ConnectionDiagnostics::new(),
@@ -240,6 +299,9 @@ impl SessionManager {
             sessions.insert(ui_id, session.clone());
         }
 
+        // Populate the viewer's monitor dropdown
+        let _ = session.queue_control(control_msg_v1::Payload::MonitorSelect(RemoteMonitors::list_request()));
+
         if let Some(ref sender) = self.event_sender {
             let _ = sender.send(SessionEvent::Connected { session_id: ui_id }).await;
         }
@@ -286,10 +348,12 @@ impl SessionManager {
 
     /// Disconnect a session
     pub async fn disconnect(&self, session_id: SessionId) -> Result<(), SessionError> {
-        let session = self.active_sessions.write().unwrap().remove(&session_id)
+        let session = self.remove_session(&session_id)
             .ok_or(SessionError::NotFound(session_id))?;
-        
-        // Session will be dropped and cleanup will happen automatically
+        if let Err(e) = session.media_session.close().await {
+            tracing::debug!("Closing session {:?}: {}", session_id, e);
+        }
+
         // Send disconnect event
         if let Some(ref sender) = self.event_sender {
             let _ = sender.send(SessionEvent::Disconnected {
@@ -301,6 +365,11 @@ impl SessionManager {
         Ok(())
     }
 
+    /// Forget a session without notifying the UI; the caller closes its link.
+    pub fn remove_session(&self, session_id: &SessionId) -> Option<Arc<ActiveSession>> {
+        self.active_sessions.write().unwrap().remove(session_id)
+    }
+
     /// Disconnect all sessions
     pub async fn disconnect_all(&self) {
         let ids = self.list_active_sessions();
@@ -329,11 +398,84 @@ pub struct ActiveSession {
     pub file_transfer: Arc<crate::transfer::FileTransferManager>,
     pub control_tx: mpsc::Sender<ControlMsgV1>,
     pub clipboard_manager: Arc<crate::clipboard::ClipboardManager>,
+    /// Set while the host reports it is recording this session
+    pub recording: Arc<AtomicBool>,
+    /// Host monitors and the one being streamed
+    pub monitors: Arc<RwLock<RemoteMonitors>>,
+    /// Host cursor shape, drawn in place of the local pointer
+    pub cursor: Arc<RwLock<RemoteCursor>>,
     
     pub stats: RwLock<SessionStats>,
     pub diagnostics: crate::diagnostics::ConnectionDiagnostics,
 }
 
+impl ActiveSession {
+    /// Queue a control message without blocking the caller. The session's
+    /// control sender assigns the sequence number.
+    pub fn queue_control(&self, payload: control_msg_v1::Payload) -> Result<(), SessionError> {
+        use tokio::sync::mpsc::error::TrySendError;
+
+        self.control_tx
+            .try_send(ControlMsgV1::new(0, payload))
+            .map_err(|e| match e {
+                TrySendError::Full(_) => SessionError::Other("control queue full".into()),
+                TrySendError::Closed(_) => {
+                    SessionError::ConnectionFailed("control channel closed".into())
+                }
+            })
+    }
+}
+
+/// Numbers a session's outgoing control messages and writes them to the
+/// media session's control stream.
+pub struct ControlSender {
+    session_id: SessionId,
+    media_session: Arc<dyn MediaSession>,
+    /// Held across the write so numbers reach the wire in order
+    next_sequence: Mutex<u64>,
+    events: Option<mpsc::Sender<SessionEvent>>,
+    failed: AtomicBool,
+}
+
+impl ControlSender {
+    pub fn new(
+        session_id: SessionId,
+        media_session: Arc<dyn MediaSession>,
+        events: Option<mpsc::Sender<SessionEvent>>,
+    ) -> Self {
+        Self {
+            session_id,
+            media_session,
+            next_sequence: Mutex::new(0),
+            events,
+            failed: AtomicBool::new(false),
+        }
+    }
+
+    /// Send `payload` with the next sequence number and return that number.
+    /// The first failure is also reported to the UI as `SessionEvent::Error`.
+    pub async fn send(&self, payload: control_msg_v1::Payload) -> Result<u64, SessionError> {
+        let mut next = self.next_sequence.lock().await;
+        let sequence = *next;
+        *next += 1;
+
+        let msg = ControlMsgV1::new(sequence, payload);
+        if let Err(e) = self.media_session.send_control(bytes::Bytes::from(msg.encode_to_vec())).await {
+            let error = format!("Control send failed: {}", e);
+            if !self.failed.swap(true, Ordering::Relaxed) {
+                if let Some(events) = &self.events {
+                    let _ = events.send(SessionEvent::Error {
+                        session_id: self.session_id,
+                        error: error.clone(),
+                    }).await;
+                }
+            }
+            return Err(SessionError::ConnectionFailed(error));
+        }
+        Ok(sequence)
+    }
+}
+
 /// Session capabilities
 #[derive(Debug, Clone, Default)]
 pub struct Capabilities {
@@ -343,6 +485,19 @@ pub struct Capabilities {
     pub file_transfer: bool,
 }
 
+impl Capabilities {
+    /// Capabilities from a `PermissionsV1` bitmask
+    pub fn from_permissions(permissions: u32) -> Self {
+        let has = |p: PermissionsV1| permissions & p as u32 != 0;
+        Self {
+            view: has(PermissionsV1::View),
+            control: has(PermissionsV1::Control),
+            clipboard: has(PermissionsV1::Clipboard),
+            file_transfer: has(PermissionsV1::FileTransfer),
+        }
+    }
+}
+
 /// Session statistics
 #[derive(Debug, Clone)]
 pub struct SessionStats {
@@ -351,6 +506,8 @@ pub struct SessionStats {
     pub current_fps: Arc<AtomicU32>,
     pub latency_ms: Arc<AtomicU32>,
     pub packet_loss: Arc<AtomicU32>,
+    /// Frames decoded but never shown because a newer one arrived first
+    pub frames_dropped: Arc<AtomicU64>,
 }
 
 impl Default for SessionStats {
@@ -361,6 +518,7 @@ impl Default for SessionStats {
             current_fps: Arc::new(AtomicU32::new(0)),
             latency_ms: Arc::new(AtomicU32::new(0)),
             packet_loss: Arc::new(AtomicU32::new(0.0f32.to_bits())),
+            frames_dropped: Arc::new(AtomicU64::new(0)),
         }
     }
 }
@@ -375,6 +533,7 @@ impl SessionStats {
             current_fps: Arc::new(AtomicU32::new(self.current_fps.load(Ordering::Relaxed))),
             latency_ms: Arc::new(AtomicU32::new(self.latency_ms.load(Ordering::Relaxed))),
             packet_loss: Arc::new(AtomicU32::new(self.packet_loss.load(Ordering::Relaxed))),
+            frames_dropped: Arc::new(AtomicU64::new(self.frames_dropped.load(Ordering::Relaxed))),
         }
     }
 }
@@ -395,6 +554,9 @@ pub enum SessionEvent {
     Disconnected { session_id: SessionId, reason: String },
     QualityChanged { session_id: SessionId, quality: ConnectionQuality },
     Error { session_id: SessionId, error: String },
+    /// A connection attempt gave up; `retriable` is false for errors a retry
+    /// cannot fix (not paired, authentication)
+    ConnectFailed { device_id: String, error: String, retriable: bool },
 }
 
 /// Connection quality
@@ -414,7 +576,23 @@ pub enum SessionError {
     
     #[error("Connection failed: {0}")]
     ConnectionFailed(String),
+
+    #[error("Connection timed out after {0:?}")]
+    Timeout(Duration),
+
+    #[error("Device not paired: {0}")]
+    NotPaired(String),
+
+    #[error("Authentication failed: {0}")]
+    AuthFailed(String),
     
     #[error("Session error: {0}")]
     Other(String),
 }
+
+impl SessionError {
+    /// Whether trying again might succeed (network trouble, not a refusal)
+    pub fn is_retriable(&self) -> bool {
+        matches!(self, Self::ConnectionFailed(_) | Self::Timeout(_))
+    }
+}
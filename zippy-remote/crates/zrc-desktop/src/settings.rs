@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
-// use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use directories::ProjectDirs;
+use crate::viewer::ZoomLevel;
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Settings {
@@ -12,6 +14,12 @@ pub struct Settings {
     pub relay_urls: Vec<String>,
     pub connection_timeout_secs: u32,
     pub font_size: f32,
+    /// Main window size and position from the last run
+    #[serde(default)]
+    pub window: Option<WindowGeometry>,
+    /// Viewer settings remembered per device
+    #[serde(default)]
+    pub devices: PerDeviceSettings,
 }
 
 impl Default for Settings {
@@ -25,6 +33,8 @@ impl Default for Settings {
             relay_urls: Vec::new(),
             connection_timeout_secs: 30,
             font_size: 14.0,
+            window: None,
+            devices: PerDeviceSettings::default(),
         }
     }
 }
@@ -36,30 +46,111 @@ pub enum Theme {
     Dark,
 }
 
+/// Main window size and position, in logical points
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WindowGeometry {
+    pub width: f32,
+    pub height: f32,
+    pub x: Option<f32>,
+    pub y: Option<f32>,
+}
+
+/// How the viewer was set up the last time a device was open
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeviceViewSettings {
+    pub zoom: ZoomLevel,
+    pub input_mode: String, // "view_only" or "control"
+    pub quality: u32,
+    pub show_toolbar: bool,
+    pub show_stats: bool,
+    #[serde(default)]
+    pub auto_reconnect: bool,
+}
+
+impl Default for DeviceViewSettings {
+    fn default() -> Self {
+        Self {
+            zoom: ZoomLevel::Fit,
+            input_mode: "view_only".to_string(),
+            quality: 80,
+            show_toolbar: true,
+            show_stats: true,
+            auto_reconnect: false,
+        }
+    }
+}
+
+/// Viewer settings keyed by device id (hex)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PerDeviceSettings(HashMap<String, DeviceViewSettings>);
+
+impl PerDeviceSettings {
+    pub fn get(&self, device_id: &str) -> Option<&DeviceViewSettings> {
+        self.0.get(device_id)
+    }
+
+    pub fn insert(&mut self, device_id: impl Into<String>, view: DeviceViewSettings) {
+        self.0.insert(device_id.into(), view);
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
 impl Settings {
+    /// Default location of `settings.json`
+    pub fn default_path() -> Option<PathBuf> {
+        ProjectDirs::from("com", "zippy", "zrc-desktop")
+            .map(|proj_dirs| proj_dirs.config_dir().join("settings.json"))
+    }
+
     pub fn load() -> Self {
-        if let Some(proj_dirs) = ProjectDirs::from("com", "zippy", "zrc-desktop") {
-            let config_path = proj_dirs.config_dir().join("settings.json");
-            if config_path.exists() {
-                if let Ok(file) = std::fs::File::open(config_path) {
-                    if let Ok(settings) = serde_json::from_reader(file) {
-                        return settings;
-                    }
-                }
-            }
-        }
-        Self::default()
+        Self::default_path()
+            .map(|path| Self::load_from(&path))
+            .unwrap_or_default()
+    }
+
+    /// Load from `path`, falling back to defaults if it is missing or unreadable
+    pub fn load_from(path: &Path) -> Self {
+        std::fs::File::open(path)
+            .ok()
+            .and_then(|file| serde_json::from_reader(file).ok())
+            .unwrap_or_default()
     }
-    
+
     pub fn save(&self) {
-        if let Some(proj_dirs) = ProjectDirs::from("com", "zippy", "zrc-desktop") {
-            let config_dir = proj_dirs.config_dir();
-            if std::fs::create_dir_all(config_dir).is_ok() {
-                let config_path = config_dir.join("settings.json");
-                if let Ok(file) = std::fs::File::create(config_path) {
-                    let _ = serde_json::to_writer_pretty(file, self);
-                }
+        if let Some(path) = Self::default_path() {
+            if let Err(e) = self.save_to(&path) {
+                tracing::warn!("Failed to save settings: {}", e);
             }
         }
     }
+
+    pub fn save_to(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let file = std::fs::File::create(path)?;
+        serde_json::to_writer_pretty(file, self)?;
+        Ok(())
+    }
+
+    /// Viewer settings for `device_id`, or the global defaults if it has none
+    pub fn device_view(&self, device_id: &str) -> DeviceViewSettings {
+        self.devices.get(device_id).cloned().unwrap_or_else(|| DeviceViewSettings {
+            input_mode: self.default_input_mode.clone(),
+            ..DeviceViewSettings::default()
+        })
+    }
+
+    /// Remember the viewer settings used for `device_id`
+    pub fn set_device_view(&mut self, device_id: &str, view: DeviceViewSettings) {
+        self.devices.insert(device_id, view);
+    }
 }
@@ -1,6 +1,7 @@
 use zrc_proto::v1::{FileTransferControlV1, FileActionV1, ControlMsgV1, ControlMsgTypeV1, control_msg_v1};
 use tokio::fs::File;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+use sha2::{Digest, Sha256};
 use std::sync::{Arc, RwLock};
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicU64, Ordering};
@@ -78,6 +79,20 @@ impl FileTransferManager {
                 }
             };
             
+            // The receiver verifies the whole file against this at COMPLETE
+            let file_sha256 = match Self::hash_file(&mut file).await {
+                Ok(hash) => hash,
+                Err(e) => {
+                     Self::update_state_static(&transfers_store, id, TransferState::Failed(e.to_string()), &event_sender);
+                     return;
+                }
+            };
+            let file_name = local_path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("upload.bin")
+                .to_string();
+
             // Send START
             let id_bytes = id.to_bytes();
             let start_msg = ControlMsgV1 {
@@ -90,18 +105,10 @@ impl FileTransferManager {
                     progress: 0,
                     error_message: String::new(),
                     data: vec![],
-                    // We should also send filename/size?
-                    // Proto definition suggests START might carry metadata?
-                    // Currently FileTransferControlV1 doesn't have filename/size fields.
-                    // Assuming metadata sent via separate mechanism or encoded in ID/RemotePath?
-                    // Wait, remote_path is not in packet?
-                    // The protocol seems to lack metadata exchange in FileTransferControlV1.
-                    // For MVP, maybe we assume it's pre-negotiated or fixed?
-                    // Or maybe we put it in `error_message` or similar hack? (No)
-                    // I'll proceed with sending DATA and assume minimal viable "stream".
-                    // The receiving side won't know filename unless we send it.
-                    // I added `data`. Maybe first `data` packet is metadata?
-                    // I won't overengineer now. I'll just send chunks.
+                    file_name,
+                    file_size: total_bytes,
+                    file_sha256: file_sha256.clone(),
+                    chunk_sha256: vec![],
                 })),
             };
             
@@ -113,7 +120,7 @@ impl FileTransferManager {
                     Ok(0) => break, // EOF
                     Ok(n) => {
                          let chunk = buffer[0..n].to_vec();
-                         let current_progress = transferred_atomic.fetch_add(n as u64, Ordering::Relaxed) + n as u64;
+                         let offset = transferred_atomic.fetch_add(n as u64, Ordering::Relaxed);
                          
                          let data_msg = ControlMsgV1 {
                             msg_type: ControlMsgTypeV1::FileControl as i32,
@@ -122,9 +129,11 @@ impl FileTransferManager {
                             payload: Some(control_msg_v1::Payload::FileControl(FileTransferControlV1 {
                                 transfer_id: id_bytes.to_vec(),
                                 action: FileActionV1::Data as i32,
-                                progress: current_progress, // or offset
+                                progress: offset,
                                 error_message: String::new(),
+                                chunk_sha256: Sha256::digest(&chunk).to_vec(),
                                 data: chunk,
+                                ..Default::default()
                             })),
                         };
                         if msg_sender.send(data_msg).await.is_err() { break; }
@@ -149,6 +158,8 @@ impl FileTransferManager {
                     progress: transferred_atomic.load(Ordering::Relaxed),
                     error_message: String::new(),
                     data: vec![],
+                    file_sha256,
+                    ..Default::default()
                 })),
             };
             let _ = msg_sender.send(complete_msg).await;
@@ -238,6 +249,21 @@ impl FileTransferManager {
         }
     }
     
+    /// SHA-256 of the whole file, leaving `file` rewound to the start.
+    async fn hash_file(file: &mut File) -> std::io::Result<Vec<u8>> {
+        let mut hasher = Sha256::new();
+        let mut buffer = vec![0u8; 16384];
+        loop {
+            let n = file.read(&mut buffer).await?;
+            if n == 0 {
+                break;
+            }
+            hasher.update(&buffer[..n]);
+        }
+        file.seek(std::io::SeekFrom::Start(0)).await?;
+        Ok(hasher.finalize().to_vec())
+    }
+
     fn update_state_static(transfers: &Arc<RwLock<HashMap<TransferId, Transfer>>>, id: TransferId, state: TransferState, event_sender: &Option<mpsc::Sender<TransferEvent>>) {
         let mut t = transfers.write().unwrap();
         if let Some(transfer) = t.get_mut(&id) {
@@ -2,12 +2,15 @@ use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use bytes::Bytes;
 use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Instant;
 use tokio::sync::Mutex;
 use zrc_core::http_mailbox::HttpMailboxClient;
 use zrc_core::quic::{self, QuicClient};
+use zrc_transport::metrics::global_metrics;
 use zrc_transport::{
-    ControlPlaneTransport, MediaOpenParams, MediaSession, MediaTransport,
+    ChannelType, ControlPlaneTransport, MediaOpenParams, MediaSession, MediaTransport,
     RouteHint, TransportError, TransportType,
 };
 
@@ -46,7 +49,9 @@ impl ControlPlaneTransport for HttpControlTransport {
         self.client
             .post(recipient, &Bytes::copy_from_slice(envelope))
             .await
-            .map_err(|e| TransportError::Other(format!("Mailbox POST failed: {}", e)))
+            .map_err(|e| TransportError::Other(format!("Mailbox POST failed: {}", e)))?;
+        global_metrics().record_send(ChannelType::Control, envelope.len());
+        Ok(())
     }
 
     async fn recv(&self) -> Result<([u8; 32], Vec<u8>), TransportError> {
@@ -54,6 +59,7 @@ impl ControlPlaneTransport for HttpControlTransport {
             // Long poll
             match self.client.poll(&self.my_id, self.poll_wait_ms).await {
                 Ok(Some(bytes)) => {
+                    global_metrics().record_recv(ChannelType::Control, bytes.len());
                     // Note: HTTP mailbox doesn't provide sender ID, using our own ID as placeholder
                     return Ok((self.my_id, bytes.to_vec()));
                 }
@@ -139,11 +145,14 @@ impl MediaTransport for QuicMediaTransport {
         let (send, recv) = connection.open_bi().await
             .map_err(|e| anyhow!("Failed to open control stream: {}", e))?;
 
+        global_metrics().connection_opened();
         let session = QuicMediaSession {
             connection,
             control_send: Arc::new(Mutex::new(send)),
             control_recv: Arc::new(Mutex::new(recv)),
             media_recv: Arc::new(Mutex::new(None)),
+            opened_at: Instant::now(),
+            closed: AtomicBool::new(false),
         };
 
         Ok(Box::new(session))
@@ -155,19 +164,45 @@ pub struct QuicMediaSession {
     control_send: Arc<Mutex<quinn::SendStream>>,
     control_recv: Arc<Mutex<quinn::RecvStream>>,
     media_recv: Arc<Mutex<Option<quinn::RecvStream>>>,
+    opened_at: Instant,
+    closed: AtomicBool,
+}
+
+impl QuicMediaSession {
+    /// Record the connection's final stats, once
+    fn record_closed(&self) {
+        if self.closed.swap(true, Ordering::Relaxed) {
+            return;
+        }
+        let metrics = global_metrics();
+        metrics.record_rtt(self.connection.rtt());
+        metrics.record_retransmit(self.connection.stats().path.lost_packets);
+        metrics.record_connection_duration(self.opened_at.elapsed());
+        metrics.connection_closed();
+    }
+}
+
+impl Drop for QuicMediaSession {
+    fn drop(&mut self) {
+        self.record_closed();
+    }
 }
 
 #[async_trait]
 impl MediaSession for QuicMediaSession {
     async fn send_control(&self, data: Bytes) -> Result<()> {
         let mut send = self.control_send.lock().await;
-        quic::write_frame(&mut send, &data).await.map_err(|e| anyhow!(e))
+        quic::write_frame(&mut send, &data).await.map_err(|e| anyhow!(e))?;
+        global_metrics().record_send(ChannelType::Control, data.len());
+        Ok(())
     }
 
     async fn recv_control(&self) -> Result<Bytes> {
         let mut recv = self.control_recv.lock().await;
-        quic::read_frame(&mut recv).await.map_err(|e| anyhow!(e))
-            .and_then(|opt| opt.ok_or_else(|| anyhow!("Control stream closed")))
+        let data = quic::read_frame(&mut recv).await.map_err(|e| anyhow!(e))
+            .and_then(|opt| opt.ok_or_else(|| anyhow!("Control stream closed")))?;
+        global_metrics().record_recv(ChannelType::Control, data.len());
+        Ok(data)
     }
 
     async fn send_media_frame(&self, _data: Bytes) -> Result<()> {
@@ -186,14 +221,17 @@ impl MediaSession for QuicMediaSession {
         }
         
         if let Some(recv) = stream_opt.as_mut() {
-            quic::read_frame(recv).await.map_err(|e| anyhow!(e))
-                .and_then(|opt| opt.ok_or_else(|| anyhow!("Media stream closed")))
+            let data = quic::read_frame(recv).await.map_err(|e| anyhow!(e))
+                .and_then(|opt| opt.ok_or_else(|| anyhow!("Media stream closed")))?;
+            global_metrics().record_recv(ChannelType::Frames, data.len());
+            Ok(data)
         } else {
             unreachable!()
         }
     }
 
     async fn close(&self) -> Result<()> {
+        self.record_closed();
         self.connection.close(0u32.into(), b"closed");
         Ok(())
     }
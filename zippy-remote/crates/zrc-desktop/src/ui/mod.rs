@@ -17,6 +17,8 @@ pub struct UiState {
     pub notifications: VecDeque<Notification>,
     pub search_text: String,
     pub selected_device: Option<String>,
+    /// Name field of the group manager (create / rename)
+    pub group_name: String,
 }
 
 #[derive(Default, PartialEq)]
@@ -33,7 +35,7 @@ pub enum Dialog {
     PairingWizard { invite_text: String, error_message: Option<String> },
     SasVerification { sas_code: String },
     ConnectionProgress { device_id: String, cancel_tx: Option<Arc<tokio::sync::Mutex<Option<tokio::sync::oneshot::Sender<()>>>>> },
-    ConnectionError { device_id: String, error: String },
+    ConnectionError { device_id: String, error: String, retriable: bool },
     FileTransfer,
     Confirmation { message: String },
     DeviceProperties { device_id: String },
@@ -154,7 +156,7 @@ pub fn render_ui(app: &mut ZrcDesktopApp, ctx: &egui::Context, frame: &mut efram
                                 });
                                 
                                 // Remove from UI
-                                app.ui_state.viewer_windows.remove(&id);
+                                app.close_viewer(&id);
                                 
                                 // Switch to device list or another session
                                 let remaining = app.session_manager.list_active_sessions();
@@ -167,13 +169,41 @@ pub fn render_ui(app: &mut ZrcDesktopApp, ctx: &egui::Context, frame: &mut efram
                             crate::viewer::ViewerAction::ShowConnectionInfo => {
                                 app.ui_state.dialogs.push(Dialog::ConnectionInfo { session_id: id });
                             }
+                            crate::viewer::ViewerAction::Reconnect { device_id, result_tx } => {
+                                let session_manager = app.session_manager.clone();
+                                let timeout = std::time::Duration::from_secs(app.settings.connection_timeout_secs as u64);
+                                app.runtime.spawn(async move {
+                                    let result = match tokio::time::timeout(timeout, session_manager.connect(&device_id)).await {
+                                        Ok(Ok(session_id)) => session_manager
+                                            .get_active_session(&session_id)
+                                            .ok_or_else(|| "session closed".to_string()),
+                                        Ok(Err(e)) => Err(e.to_string()),
+                                        Err(_) => Err(crate::session::SessionError::Timeout(timeout).to_string()),
+                                    };
+                                    // The viewer gave up while this attempt was connecting
+                                    if let Err(Ok(session)) = result_tx.send(result) {
+                                        let _ = session_manager.disconnect(session.id).await;
+                                    }
+                                });
+                            }
+                            crate::viewer::ViewerAction::Reconnected { previous } => {
+                                // Re-key the viewer under its new session and drop the old one
+                                if let Some(viewer) = app.ui_state.viewer_windows.remove(&previous) {
+                                    let new_id = viewer.session_id();
+                                    app.ui_state.viewer_windows.insert(new_id, viewer);
+                                    app.ui_state.current_view = View::Session(new_id);
+                                }
+                                let session_manager = app.session_manager.clone();
+                                app.runtime.spawn(async move {
+                                    let _ = session_manager.disconnect(previous).await;
+                                });
+                            }
                         }
                     }
                 } else {
                     // Session window not found - try to create it or switch view
                     if let Some(session) = app.session_manager.get_active_session(&id) {
-                        let viewer = crate::viewer::ViewerWindow::new(session, app.runtime.clone());
-                        app.ui_state.viewer_windows.insert(id, viewer);
+                        app.open_viewer(session);
                     } else {
                         app.ui_state.current_view = View::DeviceList; // Session closed/missing
                     }
@@ -196,22 +226,37 @@ fn render_device_list_ui(app: &mut ZrcDesktopApp, ui: &mut egui::Ui) {
     ui.heading("Devices");
     ui.separator();
 
-    // Search bar
+    // Search bar and group filter; both apply together
+    let groups = app.device_manager.list_groups();
     ui.horizontal(|ui| {
         ui.label("Search:");
-        ui.text_edit_singleline(&mut app.ui_state.search_text);
-        if !app.ui_state.search_text.is_empty() {
+        if ui.text_edit_singleline(&mut app.ui_state.search_text).changed() {
             app.device_manager.set_search_filter(app.ui_state.search_text.clone());
         }
+
+        ui.label("Group:");
+        let mut group_filter = app.device_manager.group_filter();
+        let selected_text = group_filter.as_ref()
+            .and_then(|id| groups.iter().find(|g| &g.id == id))
+            .map(|g| g.name.clone())
+            .unwrap_or_else(|| "All devices".to_string());
+        egui::ComboBox::from_id_source("device_group_filter")
+            .selected_text(selected_text)
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut group_filter, None, "All devices");
+                for group in &groups {
+                    ui.selectable_value(&mut group_filter, Some(group.id.clone()), &group.name);
+                }
+            });
+        if group_filter != app.device_manager.group_filter() {
+            app.device_manager.set_group_filter(group_filter);
+        }
     });
+    render_group_manager(app, ui, &groups);
     ui.separator();
 
     // Device list
-    let devices = if app.ui_state.search_text.is_empty() {
-        app.device_manager.list_devices()
-    } else {
-        app.device_manager.get_filtered_devices()
-    };
+    let devices = app.device_manager.get_filtered_devices();
 
     egui::ScrollArea::vertical().show(ui, |ui| {
         for device in devices {
@@ -255,6 +300,25 @@ fn render_device_list_ui(app: &mut ZrcDesktopApp, ui: &mut egui::Ui) {
                                 });
                                 ui.close_menu();
                             }
+                            if !groups.is_empty() {
+                                ui.menu_button("Move to group", |ui| {
+                                    let mut target = None;
+                                    if ui.button("No group").clicked() {
+                                        target = Some(None);
+                                    }
+                                    for group in &groups {
+                                        if ui.button(&group.name).clicked() {
+                                            target = Some(Some(group.id.clone()));
+                                        }
+                                    }
+                                    if let Some(group_id) = target {
+                                        if let Err(e) = app.device_manager.move_to_group(&device.id, group_id) {
+                                            add_notification(&mut app.ui_state, format!("Failed to move device: {}", e), NotificationLevel::Error);
+                                        }
+                                        ui.close_menu();
+                                    }
+                                });
+                            }
                             if ui.button("Remove").clicked() {
                                 if let Err(e) = app.device_manager.remove_device(&device.id) {
                                     add_notification(&mut app.ui_state, format!("Failed to remove device: {}", e), NotificationLevel::Error);
@@ -271,31 +335,92 @@ fn render_device_list_ui(app: &mut ZrcDesktopApp, ui: &mut egui::Ui) {
     });
 }
 
+/// Create, rename and delete device groups
+fn render_group_manager(app: &mut ZrcDesktopApp, ui: &mut egui::Ui, groups: &[crate::device::DeviceGroup]) {
+    egui::CollapsingHeader::new("Manage groups").show(ui, |ui| {
+        let mut result = None;
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut app.ui_state.group_name);
+            if ui.button("Create").clicked() {
+                result = Some(app.device_manager.create_group(app.ui_state.group_name.clone()).map(|_| ()));
+            }
+        });
+        for group in groups {
+            ui.horizontal(|ui| {
+                ui.colored_label(group.color, "■");
+                ui.label(&group.name);
+                if ui.button("Rename").on_hover_text("Rename to the name above").clicked() {
+                    result = Some(app.device_manager.rename_group(&group.id, app.ui_state.group_name.clone()));
+                }
+                if ui.button("Delete").on_hover_text("Devices in the group are kept").clicked() {
+                    result = Some(app.device_manager.delete_group(&group.id));
+                }
+            });
+        }
+        match result {
+            Some(Ok(())) => app.ui_state.group_name.clear(),
+            Some(Err(e)) => add_notification(&mut app.ui_state, format!("Group change failed: {}", e), NotificationLevel::Error),
+            None => {}
+        }
+    });
+}
+
 fn connect_to_device(app: &mut ZrcDesktopApp, device_id: &str) {
+    let timeout = std::time::Duration::from_secs(app.settings.connection_timeout_secs as u64);
+    start_connection(&mut app.ui_state, &app.runtime, &app.session_manager, timeout, device_id);
+}
+
+/// Show the progress dialog and connect in the background. The outcome
+/// arrives as a `SessionEvent` (`Connected` or `ConnectFailed`).
+pub(crate) fn start_connection(
+    ui_state: &mut UiState,
+    runtime: &tokio::runtime::Handle,
+    session_manager: &Arc<crate::session::SessionManager>,
+    timeout: std::time::Duration,
+    device_id: &str,
+) {
     let device_id = device_id.to_string();
-    let runtime = app.runtime.clone();
-    let session_manager = app.session_manager.clone();
+    let session_manager = session_manager.clone();
     
     // Create cancellation channel
-    let (cancel_tx, _cancel_rx) = tokio::sync::oneshot::channel();
+    let (cancel_tx, mut cancel_rx) = tokio::sync::oneshot::channel();
     let cancel_tx_mutex = Arc::new(tokio::sync::Mutex::new(Some(cancel_tx)));
     
     // Show connection progress dialog
-    app.ui_state.dialogs.push(Dialog::ConnectionProgress {
+    ui_state.dialogs.push(Dialog::ConnectionProgress {
         device_id: device_id.clone(),
         cancel_tx: Some(cancel_tx_mutex),
     });
 
-    // Initiate connection (async)
-    // Connection success/error will be handled via SessionEvent channel
     runtime.spawn(async move {
-        if let Err(e) = session_manager.connect(&device_id).await {
-            // Error will be sent via SessionEvent::Error
-            tracing::error!("Connection failed: {}", e);
+        tokio::select! {
+            Ok(()) = &mut cancel_rx => tracing::info!("Connection to {} cancelled", device_id),
+            result = session_manager.connect_with_retry(&device_id, timeout) => {
+                if let Err(e) = result {
+                    tracing::error!("Connection failed: {}", e);
+                }
+            }
         }
     });
 }
 
+/// Replace the connection error dialog at `idx` with a fresh attempt to the
+/// same device. Does nothing for errors a retry cannot fix.
+pub(crate) fn retry_connection(
+    ui_state: &mut UiState,
+    runtime: &tokio::runtime::Handle,
+    session_manager: &Arc<crate::session::SessionManager>,
+    timeout: std::time::Duration,
+    idx: usize,
+) {
+    let Some(Dialog::ConnectionError { device_id, retriable: true, .. }) = ui_state.dialogs.get(idx) else {
+        return;
+    };
+    let device_id = device_id.clone();
+    ui_state.dialogs.remove(idx);
+    start_connection(ui_state, runtime, session_manager, timeout, &device_id);
+}
+
 fn render_settings_ui(app: &mut ZrcDesktopApp, ui: &mut egui::Ui) {
     ui.heading("Settings");
     ui.separator();
@@ -436,6 +561,7 @@ fn render_dialogs(app: &mut ZrcDesktopApp, ctx: &egui::Context) {
     let mut to_remove = Vec::new();
     let mut notifications_to_add: Vec<(String, NotificationLevel)> = Vec::new();
     let mut pairing_wizard_updates: Vec<(usize, String, Option<String>)> = Vec::new();
+    let mut retry_dialog = None;
     
     for (idx, dialog) in app.ui_state.dialogs.iter().enumerate() {
         match dialog {
@@ -468,7 +594,7 @@ fn render_dialogs(app: &mut ZrcDesktopApp, ctx: &egui::Context) {
                     to_remove.push(idx);
                 }
             }
-            Dialog::ConnectionError { device_id, error } => {
+            Dialog::ConnectionError { device_id, error, retriable } => {
                 egui::Window::new("Connection Failed")
                     .collapsible(false)
                     .resizable(false)
@@ -478,9 +604,10 @@ fn render_dialogs(app: &mut ZrcDesktopApp, ctx: &egui::Context) {
                         ui.label(format!("Error: {}", error));
                         ui.separator();
                         ui.horizontal(|ui| {
-                            if ui.button("Retry").clicked() {
-                                // TODO: Retry connection
-                                to_remove.push(idx);
+                            let retry = ui.add_enabled(*retriable, egui::Button::new("Retry"))
+                                .on_disabled_hover_text("Retrying will not help; check the device's pairing");
+                            if retry.clicked() {
+                                retry_dialog = Some(idx);
                             }
                             if ui.button("Close").clicked() {
                                 to_remove.push(idx);
@@ -710,6 +837,13 @@ fn render_dialogs(app: &mut ZrcDesktopApp, ctx: &egui::Context) {
     for &idx in to_remove.iter().rev() {
         app.ui_state.dialogs.remove(idx);
     }
+
+    // Retry replaces its dialog; shift its index past the ones just removed
+    if let Some(idx) = retry_dialog {
+        let idx = idx - to_remove.iter().filter(|&&removed| removed < idx).count();
+        let timeout = std::time::Duration::from_secs(app.settings.connection_timeout_secs as u64);
+        retry_connection(&mut app.ui_state, &app.runtime, &app.session_manager, timeout, idx);
+    }
     
     // Add notifications after dialog rendering
     for (message, level) in notifications_to_add {
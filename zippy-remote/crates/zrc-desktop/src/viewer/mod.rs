@@ -1,17 +1,38 @@
 //! Remote desktop viewer window
 
 use crate::input::{InputHandler, InputMode};
+use crate::monitor::{MonitorId, RemoteMonitors};
 use crate::session::{ActiveSession, SessionId};
+use crate::settings::DeviceViewSettings;
 use eframe::egui::{self, Rect, Vec2};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use tokio::sync::mpsc;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, oneshot};
+use zrc_core::cursor::CursorShape;
 use zrc_proto::v1::VideoFrameV1;
 use prost::Message;
+use serde::{Deserialize, Serialize};
+
+/// Reconnect attempts after the media session drops before giving up
+pub const MAX_RECONNECT_ATTEMPTS: u32 = 5;
+/// Wait before reconnect attempt n+1 is `RECONNECT_DELAY * n`
+const RECONNECT_DELAY: Duration = Duration::from_secs(2);
+
+/// Outcome of a reconnect attempt: the new session, or why it failed
+pub type ReconnectResult = Result<Arc<ActiveSession>, String>;
 
 /// Actions triggered by the viewer
 pub enum ViewerAction {
     Disconnect,
     ShowConnectionInfo,
+    /// Connect to `device_id` again and report the outcome on `result_tx`
+    Reconnect {
+        device_id: String,
+        result_tx: oneshot::Sender<ReconnectResult>,
+    },
+    /// The viewer moved to a new session; `previous` is the dropped one
+    Reconnected { previous: SessionId },
 }
 
 /// Remote desktop viewer window
@@ -21,8 +42,11 @@ pub struct ViewerWindow {
     renderer: FrameRenderer,
     input_handler: InputHandler,
     state: ViewerState,
-    toolbar: ViewerToolbar,
     frame_receiver: mpsc::Receiver<DecodedFrame>,
+    /// Uploaded remote cursor bitmap and the `RemoteCursor::generation` it is from
+    cursor_texture: Option<(u64, egui::TextureHandle)>,
+    /// Result of the reconnect attempt in flight
+    reconnect_rx: Option<oneshot::Receiver<ReconnectResult>>,
     runtime: tokio::runtime::Handle,
 }
 
@@ -33,8 +57,26 @@ impl ViewerWindow {
         let input_handler = InputHandler::new();
         input_handler.set_enabled(true);
         
+        let frame_receiver = Self::spawn_decoder(&session, &runtime);
+
+        Self {
+            session_id,
+            session: session.clone(),
+            renderer: FrameRenderer::new(),
+            input_handler,
+            state: ViewerState::default(),
+            frame_receiver,
+            cursor_texture: None,
+            reconnect_rx: None,
+            runtime,
+        }
+    }
+
+    /// Decode frames from the session's media stream. The returned channel
+    /// closes when the media session does.
+    fn spawn_decoder(session: &Arc<ActiveSession>, runtime: &tokio::runtime::Handle) -> mpsc::Receiver<DecodedFrame> {
         let (tx, rx) = mpsc::channel(10);
-        
+
         // Spawn frame decoder loop
         let session_clone = session.clone();
         runtime.spawn(async move {
@@ -68,43 +110,14 @@ impl ViewerWindow {
                 }
             }
         });
-
-        Self {
-            session_id,
-            session: session.clone(),
-            renderer: FrameRenderer::new(),
-            input_handler,
-            state: ViewerState::default(),
-            toolbar: ViewerToolbar::default(),
-            frame_receiver: rx,
-            runtime,
-        }
+        rx
     }
 
     /// Render the viewer window
     pub fn render(&mut self, ctx: &egui::Context, ui: &mut egui::Ui, frame: &mut eframe::Frame) -> Option<ViewerAction> {
-        // Poll frames with dropping when behind
-        let mut latest_frame = None;
-        let mut frame_count = 0;
-        while let Ok(frame_data) = self.frame_receiver.try_recv() {
-            latest_frame = Some(frame_data);
-            frame_count += 1;
-        }
-        
-        // If we received multiple frames, only use the latest (drop others)
-        if let Some(frame_data) = latest_frame {
-            self.renderer.update_frame(ctx, frame_data);
-            // Request repaint
-            ctx.request_repaint();
-            
-            // Update stats
-            if frame_count > 1 {
-                // Frames were dropped
-                let stats = self.session.stats.read().unwrap();
-                // Could track dropped frames if needed
-            }
-        }
-        
+        self.poll_frames(ctx);
+        self.sync_monitors();
+
         // Handle fullscreen toggle
         if self.state.fullscreen {
             ctx.send_viewport_cmd(egui::ViewportCommand::Fullscreen(true));
@@ -112,11 +125,14 @@ impl ViewerWindow {
             ctx.send_viewport_cmd(egui::ViewportCommand::Fullscreen(false));
         }
         
-        let mut action = None;
+        let mut action = self.poll_reconnect();
+        if action.is_none() {
+            action = self.render_connection_overlay(ctx);
+        }
         let available_size = ui.available_size();
         
         // Render toolbar if visible and not in fullscreen
-        if self.toolbar.visible && !self.state.fullscreen {
+        if self.state.show_toolbar && !self.state.fullscreen {
             if let Some(act) = self.render_toolbar(ui) {
                 action = Some(act);
             }
@@ -137,6 +153,7 @@ impl ViewerWindow {
         if let Some(remote_size) = self.renderer.get_remote_size() {
             self.input_handler.update_coordinate_mapper(viewer_rect, remote_size);
         }
+        self.render_remote_cursor(ctx, ui, viewer_rect);
 
         // Render status bar if visible
         if self.state.show_stats {
@@ -154,6 +171,9 @@ impl ViewerWindow {
              if i.key_pressed(egui::Key::F11) {
                  self.toggle_fullscreen();
              }
+             if i.key_pressed(egui::Key::F10) {
+                 self.state.show_toolbar = !self.state.show_toolbar;
+             }
              if i.key_pressed(egui::Key::Escape) && self.state.fullscreen {
                  self.toggle_fullscreen();
              }
@@ -176,39 +196,8 @@ impl ViewerWindow {
     /// Handle input events
     pub fn handle_input(&mut self, event: &egui::Event, viewer_rect: Rect) {
         if let Some(input_event) = self.input_handler.handle_event(event, viewer_rect) {
-             let proto_event = Self::convert_to_proto(input_event);
-             if let Some(payload) = proto_event {
-                 let msg = zrc_proto::v1::ControlMsgV1 {
-                     msg_type: zrc_proto::v1::ControlMsgTypeV1::Input as i32, // Protobuf enum as i32? Or using generated enum
-                     sequence_number: 0, // TODO: Maintain sequence
-                     timestamp: std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_micros() as u64,
-                     payload: Some(zrc_proto::v1::control_msg_v1::Payload::Input(payload)),
-                 };
-                 
-                 let session = self.session.clone();
-                 self.runtime.spawn(async move {
-                     // send_control is likely on media_session or session_host?
-                     // ActiveSession has media_session.
-                     // But MediaSession trait usually has send_control?
-                     // If not, we might need a separate control channel.
-                     // The protocol says Control Messages over QUIC.
-                     // QuicMediaSession should implement send_control logic (sending on control stream).
-                     // However, MediaSession definition in zrc-transport needs to be checked.
-                     // I will assume media_session.send_control(bytes) or similar exists.
-                     // Wait, MediaSession generic? 
-                     // Let's check session.rs for ActiveSession def.
-                     // It is Box<dyn MediaTransportSession>.
-                     
-                     // If MediaTransportSession has a method to send control messages.
-                     // I'll assume `send_message` or similar.
-                     // For now, I'll comment out the actual send and mark TO IMPLEMENT 
-                     // if I'm not sure about the method name.
-                     // BUT I should check zrc-transport/src/lib.rs.
-                     
-                     // sent via QUIC control stream
-                     let payload = msg.encode_to_vec();
-                     let _ = session.media_session.send_control(bytes::Bytes::from(payload)).await;
-                 });
+             if let Some(payload) = Self::convert_to_proto(input_event) {
+                 self.queue_input(payload);
              }
         }
     }
@@ -340,16 +329,245 @@ fn convert_to_proto(event: crate::input::InputEvent) -> Option<zrc_proto::v1::In
         // This will be handled by the frame in the render function
     }
 
+    /// Take the frames decoded since the last render, keeping only the
+    /// latest, and notice when the media session has closed.
+    pub(crate) fn poll_frames(&mut self, ctx: &egui::Context) {
+        let dropped = self.session.stats.read().unwrap().frames_dropped.clone();
+        let (latest_frame, disconnected) = drain_latest_frame(&mut self.frame_receiver, &dropped);
+        if disconnected {
+            self.media_dropped();
+        }
+
+        if let Some(frame_data) = latest_frame {
+            self.renderer.update_frame(ctx, frame_data);
+            // Request repaint
+            ctx.request_repaint();
+        }
+    }
+
+    fn media_dropped(&mut self) {
+        if self.state.link != LinkState::Connected {
+            return;
+        }
+        tracing::warn!("Media session for {:?} closed", self.session_id);
+        self.state.link = if self.state.auto_reconnect {
+            LinkState::Reconnecting { attempt: 0, next_at: Instant::now() }
+        } else {
+            LinkState::Lost { notice: "Connection lost".to_string() }
+        };
+    }
+
+    /// Start the next reconnect attempt once it is due, or pick up the
+    /// outcome of the attempt in flight.
+    pub(crate) fn poll_reconnect(&mut self) -> Option<ViewerAction> {
+        use tokio::sync::oneshot::error::TryRecvError;
+
+        if let Some(rx) = self.reconnect_rx.as_mut() {
+            let result = match rx.try_recv() {
+                Err(TryRecvError::Empty) => return None,
+                Ok(result) => result,
+                Err(TryRecvError::Closed) => Err("reconnect abandoned".to_string()),
+            };
+            self.reconnect_rx = None;
+            match result {
+                Ok(session) => {
+                    let previous = self.session_id;
+                    self.resume(session);
+                    return Some(ViewerAction::Reconnected { previous });
+                }
+                Err(e) => self.reconnect_failed(e),
+            }
+            return None;
+        }
+
+        let LinkState::Reconnecting { attempt, next_at } = self.state.link else {
+            return None;
+        };
+        if Instant::now() < next_at {
+            return None;
+        }
+        let (result_tx, result_rx) = oneshot::channel();
+        self.reconnect_rx = Some(result_rx);
+        self.state.link = LinkState::Reconnecting { attempt: attempt + 1, next_at };
+        Some(ViewerAction::Reconnect {
+            device_id: self.session.device_id.clone(),
+            result_tx,
+        })
+    }
+
+    fn reconnect_failed(&mut self, error: String) {
+        let LinkState::Reconnecting { attempt, .. } = self.state.link else {
+            return;
+        };
+        tracing::warn!("Reconnect attempt {} for {:?} failed: {}", attempt, self.session_id, error);
+        self.state.link = if attempt >= MAX_RECONNECT_ATTEMPTS {
+            LinkState::Lost {
+                notice: format!("Reconnect failed after {} attempts: {}", attempt, error),
+            }
+        } else {
+            LinkState::Reconnecting {
+                attempt,
+                next_at: Instant::now() + RECONNECT_DELAY * attempt,
+            }
+        };
+    }
+
+    /// Try to reconnect after the connection was lost
+    pub fn start_reconnect(&mut self) {
+        if let LinkState::Lost { .. } = self.state.link {
+            self.state.link = LinkState::Reconnecting { attempt: 0, next_at: Instant::now() };
+        }
+    }
+
+    /// Stop reconnecting; an attempt in flight is abandoned
+    pub fn cancel_reconnect(&mut self) {
+        if let LinkState::Reconnecting { .. } = self.state.link {
+            self.reconnect_rx = None;
+            self.state.link = LinkState::Lost { notice: "Reconnect cancelled".to_string() };
+        }
+    }
+
+    /// Whether the viewer is trying to re-establish its session
+    pub fn is_reconnecting(&self) -> bool {
+        matches!(self.state.link, LinkState::Reconnecting { .. })
+    }
+
+    /// Whether this viewer reconnects by itself when its link drops
+    pub fn auto_reconnects(&self) -> bool {
+        self.state.auto_reconnect
+    }
+
+    /// Text of the overlay shown while the connection is down
+    pub fn connection_overlay(&self) -> Option<String> {
+        match &self.state.link {
+            LinkState::Connected => None,
+            LinkState::Reconnecting { attempt, .. } => Some(format!(
+                "Reconnecting… (attempt {} of {})",
+                (*attempt).max(1),
+                MAX_RECONNECT_ATTEMPTS
+            )),
+            LinkState::Lost { notice } => Some(notice.clone()),
+        }
+    }
+
+    /// Carry on in `session`, keeping the view as it was
+    fn resume(&mut self, session: Arc<ActiveSession>) {
+        self.session_id = session.id;
+        self.frame_receiver = Self::spawn_decoder(&session, &self.runtime);
+        self.session = session;
+        self.state.link = LinkState::Connected;
+        self.state.monitors_seen = 0;
+        self.cursor_texture = None;
+        self.send_quality();
+    }
+
+    fn render_connection_overlay(&mut self, ctx: &egui::Context) -> Option<ViewerAction> {
+        let text = self.connection_overlay()?;
+        let mut action = None;
+        egui::Area::new(egui::Id::new(("connection_overlay", self.session_id.0)))
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.label(egui::RichText::new(text).strong());
+                    ui.horizontal(|ui| {
+                        if self.is_reconnecting() {
+                            if ui.button("Cancel").clicked() {
+                                self.cancel_reconnect();
+                            }
+                        } else {
+                            if ui.button("Reconnect").clicked() {
+                                self.start_reconnect();
+                            }
+                            if ui.button("Close").clicked() {
+                                action = Some(ViewerAction::Disconnect);
+                            }
+                        }
+                    });
+                });
+            });
+        if self.is_reconnecting() {
+            // Keep polling for the next attempt while nothing else repaints
+            ctx.request_repaint_after(Duration::from_millis(250));
+        }
+        action
+    }
+
     /// Set zoom level
     pub fn set_zoom(&mut self, zoom: ZoomLevel) {
         self.state.zoom = zoom;
     }
 
-    /// Select monitor
+    /// Ask the host to stream `monitor`. The host answers with SWITCHED or
+    /// REJECTED; `sync_monitors` picks up whichever it was.
     pub fn select_monitor(&mut self, monitor: MonitorId) {
         self.state.selected_monitor = monitor;
-        // TODO: Monitor switching - protocol needs to support this
-        // For now, monitor selection is handled at connection time
+        let payload = zrc_proto::v1::control_msg_v1::Payload::MonitorSelect(
+            RemoteMonitors::switch_request(monitor),
+        );
+        if let Err(e) = self.session.queue_control(payload) {
+            tracing::warn!("Monitor switch for session {:?} not sent: {}", self.session_id, e);
+        }
+    }
+
+    /// Follow host monitor updates: the list may have changed (monitor
+    /// unplugged) and the streamed monitor may differ from the one selected
+    /// after a rejection or fallback. Input mapping needs no reset here; it
+    /// tracks the size of each rendered frame, so it follows the new
+    /// monitor's resolution from its first frame.
+    fn sync_monitors(&mut self) {
+        let monitors = self.session.monitors.read().unwrap();
+        if monitors.generation == self.state.monitors_seen {
+            return;
+        }
+        self.state.monitors_seen = monitors.generation;
+        if let Some(streaming) = monitors.streaming {
+            self.state.selected_monitor = streaming;
+        }
+        self.state.monitor_notice = monitors.notice.clone();
+    }
+
+    /// While controlling, show the host's cursor shape over the frame:
+    /// system cursors map to the local pointer icon, custom bitmaps are
+    /// painted at the pointer (scaled like the frame) with the pointer hidden.
+    fn render_remote_cursor(&mut self, ctx: &egui::Context, ui: &egui::Ui, viewer_rect: Rect) {
+        if self.state.input_mode != InputMode::Control {
+            return;
+        }
+        let Some(pointer) = ctx.pointer_hover_pos().filter(|pos| viewer_rect.contains(*pos)) else {
+            return;
+        };
+        let cursor = self.session.cursor.read().unwrap();
+        let bitmap = match &cursor.shape {
+            None => return,
+            Some(CursorShape::Standard(standard)) => {
+                ctx.set_cursor_icon(crate::cursor::standard_icon(*standard));
+                return;
+            }
+            Some(CursorShape::Bitmap(bitmap)) => bitmap,
+        };
+        if self.cursor_texture.as_ref().map(|(generation, _)| *generation) != Some(cursor.generation) {
+            let texture = ctx.load_texture(
+                "remote_cursor",
+                crate::cursor::bitmap_image(bitmap),
+                egui::TextureOptions::NEAREST,
+            );
+            self.cursor_texture = Some((cursor.generation, texture));
+        }
+        let Some((_, texture)) = &self.cursor_texture else { return };
+
+        let scale = self
+            .renderer
+            .get_remote_size()
+            .map_or(1.0, |remote| viewer_rect.width() / remote.x);
+        let hotspot = Vec2::new(bitmap.hotspot_x as f32, bitmap.hotspot_y as f32) * scale;
+        let size = Vec2::new(bitmap.width as f32, bitmap.height as f32) * scale;
+        ctx.set_cursor_icon(egui::CursorIcon::None);
+        ui.painter().with_clip_rect(viewer_rect).image(
+            texture.id(),
+            Rect::from_min_size(pointer - hotspot, size),
+            Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
+            egui::Color32::WHITE,
+        );
     }
 
     /// Toggle input mode
@@ -358,8 +576,52 @@ fn convert_to_proto(event: crate::input::InputEvent) -> Option<zrc_proto::v1::In
             InputMode::ViewOnly => InputMode::Control,
             InputMode::Control => InputMode::ViewOnly,
         };
-        self.state.input_mode = new_mode;
-        self.input_handler.set_input_mode(new_mode);
+        self.set_input_mode(new_mode);
+    }
+
+    fn set_input_mode(&mut self, mode: InputMode) {
+        self.state.input_mode = mode;
+        self.input_handler.set_input_mode(mode);
+    }
+
+    /// Ask the host to stream at the current quality level
+    fn send_quality(&self) {
+        let payload = zrc_proto::v1::control_msg_v1::Payload::SessionControl(zrc_proto::v1::SessionControlV1 {
+            action: zrc_proto::v1::SessionControlActionV1::QualityChange as i32,
+            quality_level: self.state.quality,
+            ..Default::default()
+        });
+        if let Err(e) = self.session.queue_control(payload) {
+            tracing::warn!("Quality change for session {:?} not sent: {}", self.session_id, e);
+        }
+    }
+
+    /// Settings to remember for this device
+    pub fn view_settings(&self) -> DeviceViewSettings {
+        DeviceViewSettings {
+            zoom: self.state.zoom,
+            input_mode: match self.state.input_mode {
+                InputMode::ViewOnly => "view_only".to_string(),
+                InputMode::Control => "control".to_string(),
+            },
+            quality: self.state.quality,
+            show_toolbar: self.state.show_toolbar,
+            show_stats: self.state.show_stats,
+            auto_reconnect: self.state.auto_reconnect,
+        }
+    }
+
+    /// Restore settings remembered for this device
+    pub fn apply_view_settings(&mut self, view: &DeviceViewSettings) {
+        self.state.zoom = view.zoom;
+        self.set_input_mode(if view.input_mode == "control" { InputMode::Control } else { InputMode::ViewOnly });
+        self.state.show_toolbar = view.show_toolbar;
+        self.state.show_stats = view.show_stats;
+        self.state.auto_reconnect = view.auto_reconnect;
+        if view.quality != self.state.quality {
+            self.state.quality = view.quality.clamp(10, 100);
+            self.send_quality();
+        }
     }
 
     /// Get session ID
@@ -367,23 +629,25 @@ fn convert_to_proto(event: crate::input::InputEvent) -> Option<zrc_proto::v1::In
         self.session_id
     }
 
+    /// Hex id of the device this viewer is connected to
+    pub fn device_id(&self) -> &str {
+        &self.session.device_id
+    }
+
     /// Send special key sequence
     fn send_special_sequence(&self, seq: crate::input::SpecialSequence) {
         let event = self.input_handler.send_special_sequence(seq);
-        let proto_event = Self::convert_to_proto(event);
-        if let Some(payload) = proto_event {
-            let msg = zrc_proto::v1::ControlMsgV1 {
-                msg_type: zrc_proto::v1::ControlMsgTypeV1::Input as i32,
-                sequence_number: 0,
-                timestamp: std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_micros() as u64,
-                payload: Some(zrc_proto::v1::control_msg_v1::Payload::Input(payload)),
-            };
-            
-            let session = self.session.clone();
-            self.runtime.spawn(async move {
-                let payload = msg.encode_to_vec();
-                let _ = session.media_session.send_control(bytes::Bytes::from(payload)).await;
-            });
+        if let Some(payload) = Self::convert_to_proto(event) {
+            self.queue_input(payload);
+        }
+    }
+
+    /// Queue an input event on the session's control channel. Send failures
+    /// reach the UI as `SessionEvent::Error` from the session's sender.
+    fn queue_input(&self, event: zrc_proto::v1::InputEventV1) {
+        let payload = zrc_proto::v1::control_msg_v1::Payload::Input(event);
+        if let Err(e) = self.session.queue_control(payload) {
+            tracing::debug!("Dropped input for session {:?}: {}", self.session_id, e);
         }
     }
 
@@ -429,9 +693,21 @@ fn convert_to_proto(event: crate::input::InputEvent) -> Option<zrc_proto::v1::In
                     ui.separator();
                     // Monitor selector
                     ui.label("Monitor:");
-                    // TODO: Get monitor list from session
-                    // For now, show placeholder
-                    ui.label("Primary");
+                    let choice = {
+                        let monitors = self.session.monitors.read().unwrap();
+                        if monitors.manager.list_monitors().len() > 1 {
+                            monitors.manager.render_selector(ui, Some(self.state.selected_monitor))
+                        } else {
+                            ui.label("Primary");
+                            None
+                        }
+                    };
+                    if let Some(monitor) = choice.filter(|m| *m != self.state.selected_monitor) {
+                        self.select_monitor(monitor);
+                    }
+                    if let Some(notice) = &self.state.monitor_notice {
+                        ui.label(egui::RichText::new(notice).color(egui::Color32::YELLOW));
+                    }
                     
                     ui.separator();
                     if ui.button("Send File").clicked() {
@@ -453,11 +729,20 @@ fn convert_to_proto(event: crate::input::InputEvent) -> Option<zrc_proto::v1::In
                     ui.separator();
 
                     let mut clipboard_enabled = self.session.clipboard_manager.is_enabled();
-                    if ui.checkbox(&mut clipboard_enabled, "Sync Clipboard").changed() {
+                    let clipboard_allowed = self.session.capabilities.clipboard;
+                    let clipboard_toggle = ui
+                        .add_enabled(clipboard_allowed, egui::Checkbox::new(&mut clipboard_enabled, "Sync Clipboard"))
+                        .on_disabled_hover_text("Clipboard access was not granted for this device");
+                    if clipboard_toggle.changed() {
                          self.session.clipboard_manager.set_enabled(clipboard_enabled);
                     }
                     
                     ui.separator();
+                    ui.menu_button("View", |ui| {
+                        ui.checkbox(&mut self.state.show_toolbar, "Toolbar (F10)");
+                        ui.checkbox(&mut self.state.show_stats, "Status Bar");
+                        ui.checkbox(&mut self.state.auto_reconnect, "Reconnect Automatically");
+                    });
                     if ui.button("Connection Info").clicked() {
                         action = Some(ViewerAction::ShowConnectionInfo);
                     }
@@ -465,16 +750,7 @@ fn convert_to_proto(event: crate::input::InputEvent) -> Option<zrc_proto::v1::In
                     ui.separator();
                     ui.label("Quality:");
                     if ui.add(egui::Slider::new(&mut self.state.quality, 10..=100)).drag_stopped() {
-                         let msg = zrc_proto::v1::ControlMsgV1 {
-                             msg_type: zrc_proto::v1::ControlMsgTypeV1::SessionControl as i32,
-                             payload: Some(zrc_proto::v1::control_msg_v1::Payload::SessionControl(zrc_proto::v1::SessionControlV1 {
-                                 action: zrc_proto::v1::SessionControlActionV1::QualityChange as i32,
-                                 quality_level: self.state.quality,
-                                 ..Default::default()
-                             })),
-                             ..Default::default()
-                         };
-                         let _ = self.session.control_tx.try_send(msg);
+                         self.send_quality();
                     }
                     
                     ui.separator();
@@ -493,11 +769,6 @@ fn convert_to_proto(event: crate::input::InputEvent) -> Option<zrc_proto::v1::In
                     });
                     
                     ui.separator();
-                    ui.label("Monitor:");
-                    // TODO: Monitor switching - protocol needs to support this
-                    // For now, monitor selection is handled at connection time
-                    ui.label("Monitor selection at connection");
-                    
                     if ui.button("Transfers").clicked() {
                          self.state.show_transfers = !self.state.show_transfers;
                     }
@@ -559,6 +830,7 @@ fn convert_to_proto(event: crate::input::InputEvent) -> Option<zrc_proto::v1::In
                     let stats = self.session.stats.read().unwrap();
                     let latency = stats.latency_ms.load(std::sync::atomic::Ordering::Relaxed);
                     let fps = stats.current_fps.load(std::sync::atomic::Ordering::Relaxed);
+                    let dropped = stats.frames_dropped.load(std::sync::atomic::Ordering::Relaxed);
                     let duration = self.session.started_at.elapsed();
                     
                     ui.label(format!("Time: {:02}:{:02}:{:02}", 
@@ -570,6 +842,9 @@ fn convert_to_proto(event: crate::input::InputEvent) -> Option<zrc_proto::v1::In
                     ui.label(format!("Latency: {}ms", latency));
                     ui.separator();
                     ui.label(format!("FPS: {}", fps));
+                    ui.separator();
+                    ui.label(format!("Dropped: {}", dropped))
+                        .on_hover_text("Frames skipped locally because rendering fell behind");
                     
                     // Show connection quality
                     self.session.diagnostics.render_status_indicator(ui);
@@ -584,10 +859,17 @@ pub struct ViewerState {
     pub zoom: ZoomLevel,
     pub input_mode: InputMode,
     pub selected_monitor: MonitorId,
+    /// `RemoteMonitors::generation` last applied to the toolbar
+    pub monitors_seen: u64,
+    /// Shown next to the monitor selector after a rejection or fallback
+    pub monitor_notice: Option<String>,
     pub show_toolbar: bool,
     pub show_stats: bool,
     pub show_transfers: bool,
     pub quality: u32,
+    /// Reconnect without asking when the media session drops
+    pub auto_reconnect: bool,
+    pub link: LinkState,
 }
 
 impl Default for ViewerState {
@@ -597,16 +879,30 @@ impl Default for ViewerState {
             zoom: ZoomLevel::Fit,
             input_mode: InputMode::ViewOnly,
             selected_monitor: MonitorId::default(),
+            monitors_seen: 0,
+            monitor_notice: None,
             show_toolbar: true,
             show_stats: true,
             show_transfers: false,
             quality: 80,
+            auto_reconnect: false,
+            link: LinkState::Connected,
         }
     }
 }
 
+/// Whether the viewer's session is still streaming
+#[derive(Debug, Clone, PartialEq)]
+pub enum LinkState {
+    Connected,
+    /// `attempt` is the last attempt started; the next is due at `next_at`
+    Reconnecting { attempt: u32, next_at: Instant },
+    /// Dropped and not reconnecting; `notice` says why
+    Lost { notice: String },
+}
+
 /// Zoom level
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum ZoomLevel {
     Fit,
     Actual,
@@ -619,16 +915,6 @@ impl Default for ZoomLevel {
     }
 }
 
-/// Monitor identifier
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
-pub struct MonitorId(pub u32);
-
-/// Viewer toolbar
-#[derive(Default)]
-pub struct ViewerToolbar {
-    pub visible: bool,
-}
-
 /// Frame renderer
 pub struct FrameRenderer {
     current_texture: Option<egui::TextureHandle>,
@@ -770,6 +1056,33 @@ pub struct DecodedFrame {
     pub timestamp: u64,
 }
 
+/// Take every queued frame, keeping only the newest. Frames that arrived
+/// faster than we render are dropped locally and counted in `dropped`.
+/// Also returns whether the decoder has gone away.
+fn drain_latest_frame(
+    frames: &mut mpsc::Receiver<DecodedFrame>,
+    dropped: &AtomicU64,
+) -> (Option<DecodedFrame>, bool) {
+    use tokio::sync::mpsc::error::TryRecvError;
+
+    let mut latest = None;
+    let mut frame_count = 0u64;
+    let disconnected = loop {
+        match frames.try_recv() {
+            Ok(frame) => {
+                latest = Some(frame);
+                frame_count += 1;
+            }
+            Err(TryRecvError::Empty) => break false,
+            Err(TryRecvError::Disconnected) => break true,
+        }
+    };
+    if frame_count > 1 {
+        dropped.fetch_add(frame_count - 1, Ordering::Relaxed);
+    }
+    (latest, disconnected)
+}
+
 /// Frame format
 #[derive(Clone, Copy, PartialEq)]
 pub enum FrameFormat {
@@ -777,3 +1090,39 @@ pub enum FrameFormat {
     Bgra,
     Rgb,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(timestamp: u64) -> DecodedFrame {
+        DecodedFrame { width: 1, height: 1, format: FrameFormat::Rgba, data: vec![0; 4], timestamp }
+    }
+
+    #[test]
+    fn test_drain_keeps_newest_frame_and_counts_dropped() {
+        let (tx, mut rx) = mpsc::channel(10);
+        let dropped = AtomicU64::new(0);
+        for timestamp in 1..=3 {
+            tx.try_send(frame(timestamp)).unwrap();
+        }
+
+        let (latest, disconnected) = drain_latest_frame(&mut rx, &dropped);
+        assert_eq!(latest.map(|f| f.timestamp), Some(3));
+        assert!(!disconnected);
+        assert_eq!(dropped.load(Ordering::Relaxed), 2);
+
+        // A single frame per render is not a drop
+        tx.try_send(frame(4)).unwrap();
+        let (latest, _) = drain_latest_frame(&mut rx, &dropped);
+        assert_eq!(latest.map(|f| f.timestamp), Some(4));
+        assert_eq!(dropped.load(Ordering::Relaxed), 2);
+
+        // Frames still queued when the decoder stops are shown, not lost
+        tx.try_send(frame(5)).unwrap();
+        drop(tx);
+        let (latest, disconnected) = drain_latest_frame(&mut rx, &dropped);
+        assert_eq!(latest.map(|f| f.timestamp), Some(5));
+        assert!(disconnected);
+    }
+}
@@ -1,11 +1,15 @@
 //! Access control for directory lookups
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::time::Duration;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
 use dashmap::DashMap;
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use sha2::{Sha256, Digest};
+use zrc_crypto::hash::derive_id;
 
 #[derive(Debug, Error)]
 pub enum AccessError {
@@ -17,6 +21,249 @@ pub enum AccessError {
     TokenExpired,
     #[error("Invalid token format")]
     InvalidToken,
+    #[error("Capability lifetime exceeds the maximum")]
+    LifetimeTooLong,
+    #[error("Token not yet valid")]
+    NotYetValid,
+}
+
+/// Longest lifetime a capability token may have.
+///
+/// Capabilities aren't individually revocable: an issuer revokes access by
+/// no longer refreshing the holder's token, which then lapses within this.
+pub const MAX_CAPABILITY_TTL: Duration = Duration::from_secs(15 * 60);
+
+/// Clock difference tolerated between issuers, callers and the dirnode.
+pub const CAPABILITY_CLOCK_SKEW: Duration = Duration::from_secs(60);
+
+/// Bearer prefix distinguishing capability tokens from invite tokens
+const CAPABILITY_PREFIX: &str = "cap1.";
+
+/// Devices a capability grants lookups for
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CapabilityScope {
+    /// Individual device subject ids
+    pub devices: Vec<[u8; 32]>,
+    /// Named device groups configured on the dirnode
+    pub groups: Vec<String>,
+}
+
+/// Signed grant letting `subject` look up the devices in `scope` until
+/// `expires_at`.
+///
+/// A grant for a device is honored when the issuer is that device's own
+/// signing key or a trusted issuer configured on the dirnode; group grants
+/// need a trusted issuer. Only `subject` can use the grant: it is presented
+/// together with a [`CallerProof`] signed by the key `subject` derives from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CapabilityToken {
+    /// Issuer's Ed25519 signing public key
+    pub issuer: [u8; 32],
+    /// Id of the caller the grant is for
+    pub subject: [u8; 32],
+    pub scope: CapabilityScope,
+    pub issued_at: u64,
+    pub expires_at: u64,
+    #[serde(with = "signature_bytes")]
+    pub signature: [u8; 64],
+}
+
+mod signature_bytes {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(sig: &[u8; 64], s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_bytes(sig)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<[u8; 64], D::Error> {
+        let bytes = Vec::<u8>::deserialize(d)?;
+        bytes.try_into().map_err(|_| serde::de::Error::custom("signature must be 64 bytes"))
+    }
+}
+
+impl CapabilityToken {
+    /// Issue a capability signed by `issuer_key` (a device owner's key)
+    pub fn issue(
+        issuer_key: &SigningKey,
+        subject: [u8; 32],
+        scope: CapabilityScope,
+        ttl: Duration,
+        now: u64,
+    ) -> Result<Self, AccessError> {
+        if ttl > MAX_CAPABILITY_TTL {
+            return Err(AccessError::LifetimeTooLong);
+        }
+        let mut token = Self {
+            issuer: issuer_key.verifying_key().to_bytes(),
+            subject,
+            scope,
+            issued_at: now,
+            expires_at: now + ttl.as_secs(),
+            signature: [0u8; 64],
+        };
+        token.signature = issuer_key.sign(&token.sign_data()).to_bytes();
+        Ok(token)
+    }
+
+    /// Issue a fresh token for the same grant, extending it by `ttl`
+    pub fn refresh(&self, issuer_key: &SigningKey, ttl: Duration, now: u64) -> Result<Self, AccessError> {
+        Self::issue(issuer_key, self.subject, self.scope.clone(), ttl, now)
+    }
+
+    /// Check the signature, lifetime and expiry.
+    ///
+    /// Both ends are checked against `now`, not just against each other, so
+    /// a token dated into the future cannot outlive `MAX_CAPABILITY_TTL`.
+    pub fn verify(&self, now: u64) -> Result<(), AccessError> {
+        let key = VerifyingKey::from_bytes(&self.issuer).map_err(|_| AccessError::InvalidToken)?;
+        key.verify_strict(&self.sign_data(), &Signature::from_bytes(&self.signature))
+            .map_err(|_| AccessError::Forbidden)?;
+        let skew = CAPABILITY_CLOCK_SKEW.as_secs();
+        if self.expires_at.saturating_sub(self.issued_at) > MAX_CAPABILITY_TTL.as_secs()
+            || self.expires_at > now + MAX_CAPABILITY_TTL.as_secs() + skew
+        {
+            return Err(AccessError::LifetimeTooLong);
+        }
+        if self.issued_at > now + skew {
+            return Err(AccessError::NotYetValid);
+        }
+        if self.expires_at <= now {
+            return Err(AccessError::TokenExpired);
+        }
+        Ok(())
+    }
+
+    /// Grant form handed to the subject: `cap1.` followed by base64url(JSON)
+    pub fn encode(&self) -> String {
+        let json = serde_json::to_vec(self).expect("capability serializes");
+        format!("{}{}", CAPABILITY_PREFIX, URL_SAFE_NO_PAD.encode(json))
+    }
+
+    /// Parse the grant form; None if it isn't a capability token
+    pub fn decode(token: &str) -> Option<Result<Self, AccessError>> {
+        let body = token.strip_prefix(CAPABILITY_PREFIX)?;
+        Some(Self::decode_body(body.split('.').next().unwrap_or_default()))
+    }
+
+    /// Bearer form the subject sends: the grant followed by `.` and a fresh
+    /// [`CallerProof`] signed with `caller_key`
+    pub fn present(&self, caller_key: &SigningKey, now: u64) -> String {
+        format!("{}.{}", self.encode(), CallerProof::sign(caller_key, self, now).encode())
+    }
+
+    /// Parse the bearer form; None if it isn't a capability token. A grant
+    /// sent without its proof is `Unauthorized`.
+    pub fn decode_presented(token: &str) -> Option<Result<(Self, CallerProof), AccessError>> {
+        let body = token.strip_prefix(CAPABILITY_PREFIX)?;
+        let mut parts = body.split('.');
+        let grant = parts.next().unwrap_or_default();
+        let proof = parts.next();
+        Some((|| {
+            if parts.next().is_some() {
+                return Err(AccessError::InvalidToken);
+            }
+            let token = Self::decode_body(grant)?;
+            let proof = CallerProof::decode(proof.ok_or(AccessError::Unauthorized)?)?;
+            Ok((token, proof))
+        })())
+    }
+
+    fn decode_body(body: &str) -> Result<Self, AccessError> {
+        URL_SAFE_NO_PAD
+            .decode(body)
+            .ok()
+            .and_then(|json| serde_json::from_slice(&json).ok())
+            .ok_or(AccessError::InvalidToken)
+    }
+
+    fn sign_data(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(b"zrc_dir_capability_v1");
+        hasher.update(self.issuer);
+        hasher.update(self.subject);
+        hasher.update((self.scope.devices.len() as u32).to_be_bytes());
+        for device in &self.scope.devices {
+            hasher.update(device);
+        }
+        hasher.update((self.scope.groups.len() as u32).to_be_bytes());
+        for group in &self.scope.groups {
+            hasher.update((group.len() as u32).to_be_bytes());
+            hasher.update(group.as_bytes());
+        }
+        hasher.update(self.issued_at.to_be_bytes());
+        hasher.update(self.expires_at.to_be_bytes());
+        hasher.finalize().into()
+    }
+}
+
+/// Proof that a capability is presented by its subject: a signature by the
+/// key `subject` is derived from over the grant and the current time.
+///
+/// Without it a leaked grant would work for anyone who copied it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallerProof {
+    /// Caller's Ed25519 signing public key
+    pub caller: [u8; 32],
+    pub timestamp: u64,
+    pub signature: [u8; 64],
+}
+
+impl CallerProof {
+    const ENCODED_LEN: usize = 32 + 8 + 64;
+
+    pub fn sign(caller_key: &SigningKey, token: &CapabilityToken, now: u64) -> Self {
+        let signature = caller_key.sign(&Self::sign_data(token, now)).to_bytes();
+        Self {
+            caller: caller_key.verifying_key().to_bytes(),
+            timestamp: now,
+            signature,
+        }
+    }
+
+    /// Check the caller is `token.subject` and signed recently
+    pub fn verify(&self, token: &CapabilityToken, now: u64) -> Result<(), AccessError> {
+        if derive_id(&self.caller) != token.subject {
+            return Err(AccessError::Forbidden);
+        }
+        if self.timestamp.abs_diff(now) > CAPABILITY_CLOCK_SKEW.as_secs() {
+            return Err(AccessError::TokenExpired);
+        }
+        let key = VerifyingKey::from_bytes(&self.caller).map_err(|_| AccessError::InvalidToken)?;
+        key.verify_strict(&Self::sign_data(token, self.timestamp), &Signature::from_bytes(&self.signature))
+            .map_err(|_| AccessError::Forbidden)
+    }
+
+    /// base64url of `caller || timestamp (BE) || signature`
+    pub fn encode(&self) -> String {
+        let mut bytes = Vec::with_capacity(Self::ENCODED_LEN);
+        bytes.extend_from_slice(&self.caller);
+        bytes.extend_from_slice(&self.timestamp.to_be_bytes());
+        bytes.extend_from_slice(&self.signature);
+        URL_SAFE_NO_PAD.encode(bytes)
+    }
+
+    pub fn decode(encoded: &str) -> Result<Self, AccessError> {
+        let bytes = URL_SAFE_NO_PAD.decode(encoded).map_err(|_| AccessError::InvalidToken)?;
+        if bytes.len() != Self::ENCODED_LEN {
+            return Err(AccessError::InvalidToken);
+        }
+        let (caller, rest) = bytes.split_at(32);
+        let (timestamp, signature) = rest.split_at(8);
+        Ok(Self {
+            caller: caller.try_into().map_err(|_| AccessError::InvalidToken)?,
+            timestamp: u64::from_be_bytes(timestamp.try_into().map_err(|_| AccessError::InvalidToken)?),
+            signature: signature.try_into().map_err(|_| AccessError::InvalidToken)?,
+        })
+    }
+
+    /// Bound to the grant's signature, so a proof only vouches for one grant
+    fn sign_data(token: &CapabilityToken, timestamp: u64) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(b"zrc_dir_caller_proof_v1");
+        hasher.update(token.signature);
+        hasher.update(timestamp.to_be_bytes());
+        hasher.finalize().into()
+    }
 }
 
 /// Access mode
@@ -30,6 +277,24 @@ pub enum AccessMode {
     Open,
 }
 
+/// Devices a caller may browse via search
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SearchScope {
+    /// Every device (admin, or an invite not limited to specific devices)
+    All,
+    /// Only the devices named in the caller's invite, sorted
+    Subjects(Vec<[u8; 32]>),
+}
+
+impl SearchScope {
+    pub fn contains(&self, subject_id: &[u8; 32]) -> bool {
+        match self {
+            SearchScope::All => true,
+            SearchScope::Subjects(ids) => ids.binary_search(subject_id).is_ok(),
+        }
+    }
+}
+
 /// Invite token
 struct InviteToken {
     subject_ids: HashSet<[u8; 32]>,
@@ -43,6 +308,10 @@ pub struct AccessController {
     mode: AccessMode,
     invite_tokens: DashMap<String, Arc<InviteToken>>,
     admin_tokens: HashSet<String>,
+    /// Keys whose capability grants are honored for any device or group
+    trusted_issuers: HashSet<[u8; 32]>,
+    /// Named device groups that capabilities can grant
+    groups: HashMap<String, HashSet<[u8; 32]>>,
 }
 
 impl AccessController {
@@ -51,6 +320,53 @@ impl AccessController {
             mode,
             invite_tokens: DashMap::new(),
             admin_tokens: HashSet::new(),
+            trusted_issuers: HashSet::new(),
+            groups: HashMap::new(),
+        }
+    }
+
+    /// Trust capability grants signed by this key
+    pub fn add_trusted_issuer(&mut self, issuer: [u8; 32]) {
+        self.trusted_issuers.insert(issuer);
+    }
+
+    /// Add a device to a named group
+    pub fn add_group_member(&mut self, group: String, subject_id: [u8; 32]) {
+        self.groups.entry(group).or_default().insert(subject_id);
+    }
+
+    /// Check a capability, presented by its subject with `proof`, grants
+    /// lookups of `subject_id` at `now`
+    pub fn verify_capability(
+        &self,
+        token: &CapabilityToken,
+        proof: &CallerProof,
+        subject_id: &[u8; 32],
+        now: u64,
+    ) -> Result<(), AccessError> {
+        token.verify(now)?;
+        proof.verify(token, now)?;
+        if self.capability_devices(token).contains(subject_id) {
+            Ok(())
+        } else {
+            Err(AccessError::Forbidden)
+        }
+    }
+
+    /// Devices a (verified) capability actually grants, given its issuer
+    fn capability_devices(&self, token: &CapabilityToken) -> HashSet<[u8; 32]> {
+        if self.trusted_issuers.contains(&token.issuer) {
+            let mut devices: HashSet<[u8; 32]> = token.scope.devices.iter().copied().collect();
+            for group in &token.scope.groups {
+                if let Some(members) = self.groups.get(group) {
+                    devices.extend(members);
+                }
+            }
+            devices
+        } else {
+            // A device can only grant access to itself
+            let issuer_id: [u8; 32] = derive_id(&token.issuer);
+            token.scope.devices.iter().copied().filter(|d| *d == issuer_id).collect()
         }
     }
 
@@ -87,8 +403,51 @@ impl AccessController {
         }
     }
 
-    /// Verify invite token
+    /// Verify an invite or capability token
     fn verify_invite_token(&self, token: &str, subject_id: &[u8; 32]) -> Result<(), AccessError> {
+        if let Some(presented) = CapabilityToken::decode_presented(token) {
+            let (capability, proof) = presented?;
+            return self.verify_capability(&capability, &proof, subject_id, unix_now());
+        }
+
+        let invite = self.resolve_invite(token)?;
+
+        // Check subject_id scope
+        if !invite.subject_ids.is_empty() && !invite.subject_ids.contains(subject_id) {
+            return Err(AccessError::Forbidden);
+        }
+
+        Ok(())
+    }
+
+    /// Devices the bearer of `token` may search. Search always needs a
+    /// grant, even in open mode, so it can't be used to enumerate the node.
+    pub fn search_scope(&self, token: Option<&str>) -> Result<SearchScope, AccessError> {
+        let token = token.ok_or(AccessError::Unauthorized)?;
+        if self.authorize_admin(token).is_ok() {
+            return Ok(SearchScope::All);
+        }
+        if let Some(presented) = CapabilityToken::decode_presented(token) {
+            let (capability, proof) = presented?;
+            let now = unix_now();
+            capability.verify(now)?;
+            proof.verify(&capability, now)?;
+            let mut ids: Vec<[u8; 32]> = self.capability_devices(&capability).into_iter().collect();
+            ids.sort();
+            return Ok(SearchScope::Subjects(ids));
+        }
+
+        let invite = self.resolve_invite(token)?;
+        if invite.subject_ids.is_empty() {
+            return Ok(SearchScope::All);
+        }
+        let mut ids: Vec<[u8; 32]> = invite.subject_ids.iter().copied().collect();
+        ids.sort();
+        Ok(SearchScope::Subjects(ids))
+    }
+
+    /// Look up a live invite from its encoded token
+    fn resolve_invite(&self, token: &str) -> Result<Arc<InviteToken>, AccessError> {
         // Parse token (format: base64(JSON))
         let token_data = base64::decode(token)
             .map_err(|_| AccessError::InvalidToken)?;
@@ -101,6 +460,7 @@ impl AccessController {
 
         // Check if token exists
         let invite = self.invite_tokens.get(token_id)
+            .map(|entry| entry.value().clone())
             .ok_or(AccessError::Forbidden)?;
 
         // Check expiration
@@ -114,12 +474,7 @@ impl AccessController {
             }
         }
 
-        // Check subject_id scope
-        if !invite.subject_ids.is_empty() && !invite.subject_ids.contains(subject_id) {
-            return Err(AccessError::Forbidden);
-        }
-
-        Ok(())
+        Ok(invite)
     }
 
     /// Create invite token
@@ -179,10 +534,24 @@ impl AccessController {
     }
 }
 
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Controller key a capability is issued to, and its subject id
+    fn caller() -> (SigningKey, [u8; 32]) {
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+        let id = derive_id(&key.verifying_key().to_bytes());
+        (key, id)
+    }
+
     #[test]
     fn test_invite_only_access() {
         let ctrl = AccessController::new(AccessMode::InviteOnly);
@@ -209,6 +578,127 @@ mod tests {
         // Without discovery, should fail
         assert!(ctrl.authorize_lookup(&subject_id, None, false).is_err());
     }
+
+    #[test]
+    fn test_capability_scoped_to_one_device() {
+        let ctrl = AccessController::new(AccessMode::InviteOnly);
+        let device_a = SigningKey::from_bytes(&[1u8; 32]);
+        let id_a: [u8; 32] = derive_id(&device_a.verifying_key().to_bytes());
+        let id_b = [9u8; 32];
+        let now = 1_000_000;
+
+        let (caller, caller_id) = caller();
+
+        let scope = CapabilityScope { devices: vec![id_a], groups: vec![] };
+        let token = CapabilityToken::issue(&device_a, caller_id, scope, Duration::from_secs(300), now).unwrap();
+        let proof = CallerProof::sign(&caller, &token, now);
+        assert!(ctrl.verify_capability(&token, &proof, &id_a, now).is_ok());
+        assert!(matches!(ctrl.verify_capability(&token, &proof, &id_b, now), Err(AccessError::Forbidden)));
+
+        // A device key can't grant access to some other device
+        let scope = CapabilityScope { devices: vec![id_b], groups: vec![] };
+        let overreach = CapabilityToken::issue(&device_a, caller_id, scope, Duration::from_secs(300), now).unwrap();
+        let proof = CallerProof::sign(&caller, &overreach, now);
+        assert!(ctrl.verify_capability(&overreach, &proof, &id_b, now).is_err());
+
+        // Survives the bearer round trip, and tampering breaks it
+        let decoded = CapabilityToken::decode(&token.encode()).unwrap().unwrap();
+        assert_eq!(decoded, token);
+        let mut tampered = token.clone();
+        tampered.scope.devices.push(id_b);
+        assert!(tampered.verify(now).is_err());
+    }
+
+    #[test]
+    fn test_capability_expiry_and_refresh() {
+        let mut ctrl = AccessController::new(AccessMode::InviteOnly);
+        let owner = SigningKey::from_bytes(&[2u8; 32]);
+        ctrl.add_trusted_issuer(owner.verifying_key().to_bytes());
+        ctrl.add_group_member("office".to_string(), [5u8; 32]);
+        let now = 1_000_000;
+
+        let (caller, caller_id) = caller();
+        let verify_at = |token: &CapabilityToken, at: u64| {
+            ctrl.verify_capability(token, &CallerProof::sign(&caller, token, at), &[5u8; 32], at)
+        };
+
+        let scope = CapabilityScope { devices: vec![], groups: vec!["office".to_string()] };
+        let token = CapabilityToken::issue(&owner, caller_id, scope, Duration::from_secs(60), now).unwrap();
+        assert!(verify_at(&token, now + 59).is_ok());
+        assert!(matches!(verify_at(&token, now + 60), Err(AccessError::TokenExpired)));
+
+        let refreshed = token.refresh(&owner, Duration::from_secs(60), now + 60).unwrap();
+        assert!(verify_at(&refreshed, now + 90).is_ok());
+
+        // Long-lived grants are refused so that refresh stays the revocation path
+        assert!(matches!(
+            CapabilityToken::issue(&owner, [7u8; 32], CapabilityScope::default(), Duration::from_secs(86400), now),
+            Err(AccessError::LifetimeTooLong)
+        ));
+    }
+
+    #[test]
+    fn test_capability_dated_into_the_future_is_refused() {
+        let owner = SigningKey::from_bytes(&[2u8; 32]);
+        let now = 1_000_000;
+        let year = 365 * 86400;
+
+        // Short-lived on paper, but only starts a year from now
+        let token = CapabilityToken::issue(&owner, [7u8; 32], CapabilityScope::default(), Duration::from_secs(60), now + year)
+            .unwrap();
+        assert!(matches!(token.verify(now), Err(AccessError::LifetimeTooLong)));
+
+        // Within the skew it is fine
+        let token = CapabilityToken::issue(&owner, [7u8; 32], CapabilityScope::default(), Duration::from_secs(60), now + 30)
+            .unwrap();
+        assert!(token.verify(now).is_ok());
+
+        let token = CapabilityToken::issue(&owner, [7u8; 32], CapabilityScope::default(), Duration::from_secs(60), now + 300)
+            .unwrap();
+        assert!(matches!(token.verify(now), Err(AccessError::NotYetValid)));
+    }
+
+    #[test]
+    fn test_capability_bound_to_subject() {
+        let ctrl = AccessController::new(AccessMode::InviteOnly);
+        let device = SigningKey::from_bytes(&[1u8; 32]);
+        let device_id: [u8; 32] = derive_id(&device.verifying_key().to_bytes());
+        let (caller, caller_id) = caller();
+        let now = unix_now();
+
+        let scope = CapabilityScope { devices: vec![device_id], groups: vec![] };
+        let token = CapabilityToken::issue(&device, caller_id, scope, Duration::from_secs(300), now).unwrap();
+        assert!(ctrl.authorize_lookup(&device_id, Some(&token.present(&caller, now)), false).is_ok());
+
+        // The bare grant, or one presented by someone else, is refused
+        assert!(matches!(
+            ctrl.authorize_lookup(&device_id, Some(&token.encode()), false),
+            Err(AccessError::Unauthorized)
+        ));
+        let thief = SigningKey::from_bytes(&[8u8; 32]);
+        assert!(matches!(
+            ctrl.authorize_lookup(&device_id, Some(&token.present(&thief, now)), false),
+            Err(AccessError::Forbidden)
+        ));
+
+        // A stale proof is refused
+        let stale = token.present(&caller, now - 600);
+        assert!(ctrl.authorize_lookup(&device_id, Some(&stale), false).is_err());
+    }
+
+    #[test]
+    fn test_search_scope_follows_grant() {
+        let mut ctrl = AccessController::new(AccessMode::Open);
+        ctrl.add_admin_token("admin".to_string());
+
+        assert!(ctrl.search_scope(None).is_err());
+        assert_eq!(ctrl.search_scope(Some("admin")).unwrap(), SearchScope::All);
+
+        let token = ctrl.create_invite(vec![[3u8; 32], [1u8; 32]], None, [2u8; 32]);
+        let scope = ctrl.search_scope(Some(&token)).unwrap();
+        assert_eq!(scope, SearchScope::Subjects(vec![[1u8; 32], [3u8; 32]]));
+        assert!(!scope.contains(&[2u8; 32]));
+    }
 }
 
 #[cfg(test)]
@@ -4,7 +4,7 @@ use std::sync::Arc;
 use std::net::SocketAddr;
 use std::time::Duration;
 use axum::{
-    extract::{Path, State, ConnectInfo},
+    extract::{Path, Query, State, ConnectInfo},
     http::{HeaderMap, StatusCode, HeaderValue},
     response::{IntoResponse, Response},
     routing::{delete, get, post, Router},
@@ -15,7 +15,8 @@ use tracing::{info, warn, error};
 use prost::Message;
 use zrc_proto::v1::DirRecordV1;
 
-use crate::records::{RecordManager, RecordError};
+use crate::records::{Presence, RecordManager, RecordError, SearchQuery};
+use crate::revocations::{RevocationError, RevocationRegistry};
 use crate::access::AccessController;
 use crate::discovery::{DiscoveryManager, DiscoveryError};
 use crate::search_protection::SearchProtection;
@@ -26,6 +27,7 @@ pub struct ApiState {
     pub access_ctrl: Arc<AccessController>,
     pub discovery_mgr: Arc<DiscoveryManager>,
     pub protection: Arc<SearchProtection>,
+    pub revocations: Arc<RevocationRegistry>,
 }
 
 /// Create API router
@@ -34,8 +36,12 @@ pub fn create_router(state: ApiState) -> Router {
         .route("/v1/records", post(post_record))
         .route("/v1/records/:subject_id_hex", get(get_record))
         .route("/v1/records/batch", post(get_batch))
+        .route("/v1/records/:subject_id_hex/presence", post(refresh_presence))
+        .route("/v1/search", get(search_devices))
         .route("/v1/discovery/tokens", post(create_discovery_token))
         .route("/v1/discovery/tokens/:token_id_hex", delete(revoke_discovery_token))
+        .route("/v1/revocations", post(post_revocation))
+        .route("/v1/revocations/:sign_pub_hex", get(get_revocation))
         .route("/health", get(health_handler))
         .with_state(state)
 }
@@ -66,7 +72,7 @@ async fn post_record(
             info!("Stored record from {}", addr.ip());
             StatusCode::CREATED.into_response()
         }
-        Err(RecordError::InvalidSignature) | Err(RecordError::SubjectMismatch) => {
+        Err(RecordError::InvalidSignature) | Err(RecordError::SubjectMismatch) | Err(RecordError::Unsigned) => {
             warn!("Signature verification failed from {}", addr.ip());
             (StatusCode::FORBIDDEN, "Signature verification failed").into_response()
         }
@@ -147,6 +153,13 @@ async fn get_record(
                 headers.insert("X-Record-Expires", expires_header);
             }
             headers.insert("X-Signature-Verified", HeaderValue::from_static("true"));
+            let presence = state.record_mgr.presence(&subject_id);
+            headers.insert("X-Presence", presence_header(presence));
+            if let Some(presence) = presence {
+                if let Ok(last_seen) = HeaderValue::from_str(&presence.last_seen.to_string()) {
+                    headers.insert("X-Last-Seen", last_seen);
+                }
+            }
             
             (StatusCode::OK, headers, record_bytes).into_response()
         }
@@ -220,9 +233,12 @@ async fn get_batch(
             if let Ok(Some(record)) = state.record_mgr.get(&subject_id, now).await {
                 let mut record_bytes = Vec::new();
                 Message::encode(&record, &mut record_bytes).ok();
+                let presence = state.record_mgr.presence(&subject_id);
                 records.push(serde_json::json!({
                     "subject_id": id_hex,
                     "record": hex::encode(record_bytes),
+                    "online": presence.is_some_and(|p| p.online),
+                    "last_seen": presence.map(|p| p.last_seen),
                 }));
             } else {
                 not_found.push(id_hex);
@@ -235,6 +251,181 @@ async fn get_batch(
     Json(BatchResponse { records, not_found }).into_response()
 }
 
+fn presence_header(presence: Option<Presence>) -> HeaderValue {
+    match presence {
+        Some(p) if p.online => HeaderValue::from_static("online"),
+        _ => HeaderValue::from_static("offline"),
+    }
+}
+
+/// POST /v1/records/{subject_id_hex}/presence - Refresh device presence
+///
+/// Keeps a device online for another presence TTL. The body carries a
+/// timestamp and the device's signature over
+/// `presence_refresh_sign_data(subject_id, timestamp)`, made with the key
+/// in its published record; refreshes must be fresh and strictly newer
+/// than the last one. Devices should refresh at well under the TTL.
+#[derive(Deserialize)]
+struct PresenceRefreshRequest {
+    timestamp: u64,
+    signature: String,
+}
+
+#[derive(Serialize)]
+struct PresenceResponse {
+    online: bool,
+    last_seen: u64,
+    expires_at: u64,
+}
+
+async fn refresh_presence(
+    State(state): State<ApiState>,
+    Path(subject_id_hex): Path<String>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Json(request): Json<PresenceRefreshRequest>,
+) -> Response {
+    let subject_id = match hex::decode(&subject_id_hex) {
+        Ok(id) if id.len() == 32 => {
+            let mut arr = [0u8; 32];
+            arr.copy_from_slice(&id);
+            arr
+        }
+        _ => {
+            return (StatusCode::BAD_REQUEST, "Invalid subject_id").into_response();
+        }
+    };
+    let signature = match hex::decode(&request.signature) {
+        Ok(sig) => sig,
+        Err(_) => return (StatusCode::BAD_REQUEST, "Invalid signature").into_response(),
+    };
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    match state.record_mgr.refresh_presence(&subject_id, request.timestamp, &signature, now).await {
+        Ok(presence) => Json(PresenceResponse {
+            online: presence.online,
+            last_seen: presence.last_seen,
+            expires_at: presence.expires_at,
+        }).into_response(),
+        Err(RecordError::NotFound) => {
+            (StatusCode::NOT_FOUND, "Record not found").into_response()
+        }
+        Err(RecordError::InvalidSignature) | Err(RecordError::SubjectMismatch) => {
+            warn!("Presence refresh signature failed from {}", addr.ip());
+            (StatusCode::FORBIDDEN, "Signature verification failed").into_response()
+        }
+        Err(RecordError::StaleRefresh) => {
+            (StatusCode::BAD_REQUEST, "Stale presence refresh").into_response()
+        }
+        Err(e) => {
+            error!("Presence refresh error: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Storage error").into_response()
+        }
+    }
+}
+
+/// GET /v1/search - Browse the devices the caller's grant covers
+///
+/// Query: `cursor` (hex subject id from the previous page), `limit`
+/// (default 20, max 100), `online` (true/false), `prefix` (hex subject id
+/// prefix). Requires a bearer invite or admin token.
+#[derive(Deserialize)]
+struct SearchParams {
+    cursor: Option<String>,
+    limit: Option<usize>,
+    online: Option<bool>,
+    prefix: Option<String>,
+}
+
+#[derive(Serialize)]
+struct SearchResponse {
+    items: Vec<SearchResponseItem>,
+    /// Hex cursor for the next page; absent when there are no more
+    next_cursor: Option<String>,
+}
+
+#[derive(Serialize)]
+struct SearchResponseItem {
+    subject_id: String,
+    /// Hex-encoded DirRecordV1
+    record: String,
+    online: bool,
+    last_seen: Option<u64>,
+}
+
+async fn search_devices(
+    State(state): State<ApiState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Query(params): Query<SearchParams>,
+) -> Response {
+    let token = headers.get("authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|s| s.strip_prefix("Bearer "));
+
+    let scope = match state.access_ctrl.search_scope(token) {
+        Ok(scope) => scope,
+        Err(crate::access::AccessError::Unauthorized) => {
+            return (StatusCode::UNAUTHORIZED, "Invite token required").into_response();
+        }
+        Err(_) => {
+            return (StatusCode::FORBIDDEN, "Invalid invite token").into_response();
+        }
+    };
+
+    if let Err(e) = state.protection.check_search(addr.ip(), token.unwrap_or_default()) {
+        warn!("Search protection triggered for {}: {}", addr.ip(), e);
+        return (StatusCode::TOO_MANY_REQUESTS, "Too many requests").into_response();
+    }
+
+    let cursor = match params.cursor.as_deref().map(hex::decode) {
+        None => None,
+        Some(Ok(id)) if id.len() == 32 => {
+            let mut arr = [0u8; 32];
+            arr.copy_from_slice(&id);
+            Some(arr)
+        }
+        Some(_) => return (StatusCode::BAD_REQUEST, "Invalid cursor").into_response(),
+    };
+    let query = SearchQuery {
+        cursor,
+        limit: params.limit.unwrap_or(20),
+        online: params.online,
+        prefix: params.prefix.map(|p| p.to_ascii_lowercase()),
+    };
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    match state.record_mgr.search(&query, &scope, now).await {
+        Ok(page) => {
+            let items = page.items.into_iter().map(|item| {
+                let mut record_bytes = Vec::new();
+                Message::encode(&item.record, &mut record_bytes).ok();
+                SearchResponseItem {
+                    subject_id: hex::encode(item.subject_id),
+                    record: hex::encode(record_bytes),
+                    online: item.presence.is_some_and(|p| p.online),
+                    last_seen: item.presence.map(|p| p.last_seen),
+                }
+            }).collect();
+            Json(SearchResponse {
+                items,
+                next_cursor: page.next_cursor.map(hex::encode),
+            }).into_response()
+        }
+        Err(e) => {
+            error!("Search error: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Error searching records").into_response()
+        }
+    }
+}
+
 /// POST /v1/discovery/tokens - Create discovery token
 #[derive(Deserialize)]
 struct CreateTokenRequest {
@@ -365,6 +556,60 @@ async fn revoke_discovery_token(
     }
 }
 
+/// POST /v1/revocations - Publish a key revocation
+///
+/// Revocations carry their own signatures, so no token is needed.
+async fn post_revocation(
+    State(state): State<ApiState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    body: Bytes,
+) -> Response {
+    match state.revocations.publish(&body).await {
+        Ok(true) => {
+            info!("Stored key revocation from {}", addr.ip());
+            StatusCode::CREATED.into_response()
+        }
+        // Already known, with the same or an earlier not-before
+        Ok(false) => StatusCode::OK.into_response(),
+        Err(RevocationError::TooLarge) => {
+            (StatusCode::PAYLOAD_TOO_LARGE, "Revocation too large").into_response()
+        }
+        Err(RevocationError::Invalid(e)) => {
+            warn!("Invalid revocation from {}: {}", addr.ip(), e);
+            (StatusCode::FORBIDDEN, "Revocation verification failed").into_response()
+        }
+        Err(e) => {
+            error!("Revocation store error: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Storage error").into_response()
+        }
+    }
+}
+
+/// GET /v1/revocations/{sign_pub_hex} - Get the revocation of a signing key
+///
+/// Public: a revocation only tells its reader not to trust a key, and peers
+/// must be able to check keys they are about to trust.
+async fn get_revocation(
+    State(state): State<ApiState>,
+    Path(sign_pub_hex): Path<String>,
+) -> Response {
+    let sign_pub = match hex::decode(&sign_pub_hex) {
+        Ok(key) if key.len() == 32 => key,
+        _ => {
+            return (StatusCode::BAD_REQUEST, "Invalid signing key").into_response();
+        }
+    };
+
+    match state.revocations.get(&sign_pub) {
+        Some(record) => {
+            let mut headers = HeaderMap::new();
+            headers.insert("Content-Type", HeaderValue::from_static("application/octet-stream"));
+            (StatusCode::OK, headers, record.encode_to_vec()).into_response()
+        }
+        None => (StatusCode::NOT_FOUND, "Key not revoked").into_response(),
+    }
+}
+
 /// GET /health - Health check
 async fn health_handler() -> StatusCode {
     StatusCode::OK
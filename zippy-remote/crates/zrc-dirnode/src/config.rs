@@ -20,6 +20,9 @@ pub enum ConfigError {
     Invalid(String),
 }
 
+/// A named device group and its member device IDs
+pub type DeviceGroup = (String, Vec<[u8; 32]>);
+
 /// Server configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServerConfig {
@@ -29,8 +32,14 @@ pub struct ServerConfig {
     pub access_mode: String, // "invite_only", "discovery_enabled", "open"
     pub max_record_ttl_seconds: u32,
     pub max_discovery_ttl_seconds: u32,
+    /// Seconds a device stays online after its last refresh
+    pub presence_ttl_seconds: u64,
     pub rate_limit_per_minute: u32,
     pub admin_tokens: Vec<String>,
+    /// Hex Ed25519 keys whose capability grants are honored for any device
+    pub capability_issuers: Vec<String>,
+    /// Named device groups (hex subject ids) that capabilities can grant
+    pub device_groups: std::collections::HashMap<String, Vec<String>>,
 }
 
 impl Default for ServerConfig {
@@ -42,8 +51,11 @@ impl Default for ServerConfig {
             access_mode: "invite_only".to_string(),
             max_record_ttl_seconds: 86400,  // 24 hours
             max_discovery_ttl_seconds: 3600, // 1 hour
+            presence_ttl_seconds: 300,       // 5 minutes
             rate_limit_per_minute: 60,
             admin_tokens: Vec::new(),
+            capability_issuers: Vec::new(),
+            device_groups: std::collections::HashMap::new(),
         }
     }
 }
@@ -112,6 +124,10 @@ impl ServerConfig {
             self.max_discovery_ttl_seconds = ttl as u32;
         }
 
+        if let Some(ttl) = toml_config.get("presence_ttl_seconds").and_then(|v| v.as_integer()) {
+            self.presence_ttl_seconds = ttl as u64;
+        }
+
         if let Some(limit) = toml_config.get("rate_limit_per_minute").and_then(|v| v.as_integer()) {
             self.rate_limit_per_minute = limit as u32;
         }
@@ -122,6 +138,21 @@ impl ServerConfig {
                 .collect();
         }
 
+        if let Some(issuers) = toml_config.get("capability_issuers").and_then(|v| v.as_array()) {
+            self.capability_issuers = issuers.iter()
+                .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                .collect();
+        }
+
+        if let Some(groups) = toml_config.get("device_groups").and_then(|v| v.as_table()) {
+            for (name, members) in groups {
+                let members = members.as_array()
+                    .map(|m| m.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect())
+                    .unwrap_or_default();
+                self.device_groups.insert(name.clone(), members);
+            }
+        }
+
         Ok(())
     }
 
@@ -135,6 +166,13 @@ impl ServerConfig {
             return Err(ConfigError::Invalid("max_discovery_ttl_seconds must be > 0".to_string()));
         }
 
+        if self.presence_ttl_seconds == 0 {
+            return Err(ConfigError::Invalid("presence_ttl_seconds must be > 0".to_string()));
+        }
+
+        self.capability_issuers()?;
+        self.device_groups()?;
+
         if !matches!(self.access_mode.as_str(), "invite_only" | "discovery_enabled" | "open") {
             return Err(ConfigError::Invalid(
                 "access_mode must be one of: invite_only, discovery_enabled, open".to_string()
@@ -144,6 +182,21 @@ impl ServerConfig {
         Ok(())
     }
 
+    /// Parsed capability issuer keys
+    pub fn capability_issuers(&self) -> Result<Vec<[u8; 32]>, ConfigError> {
+        self.capability_issuers.iter().map(|key| parse_hex32(key)).collect()
+    }
+
+    /// Parsed device groups
+    pub fn device_groups(&self) -> Result<Vec<DeviceGroup>, ConfigError> {
+        self.device_groups.iter()
+            .map(|(name, members)| {
+                let members = members.iter().map(|id| parse_hex32(id)).collect::<Result<_, _>>()?;
+                Ok((name.clone(), members))
+            })
+            .collect()
+    }
+
     /// Get access mode enum
     pub fn access_mode(&self) -> AccessMode {
         match self.access_mode.as_str() {
@@ -160,6 +213,7 @@ impl ServerConfig {
             max_ttl_seconds: self.max_record_ttl_seconds,
             max_records: 100_000,
             cleanup_interval: Duration::from_secs(3600),
+            presence_ttl: Duration::from_secs(self.presence_ttl_seconds),
         }
     }
 
@@ -172,3 +226,10 @@ impl ServerConfig {
         }
     }
 }
+
+fn parse_hex32(value: &str) -> Result<[u8; 32], ConfigError> {
+    hex::decode(value)
+        .ok()
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .ok_or_else(|| ConfigError::Invalid(format!("Expected 32 hex-encoded bytes: {}", value)))
+}
@@ -8,6 +8,7 @@ pub mod api;
 pub mod config;
 pub mod discovery;
 pub mod records;
+pub mod revocations;
 pub mod search_protection;
 pub mod server;
 pub mod store;
@@ -1,16 +1,27 @@
 //! Record management and signature verification
 
-use std::sync::{Arc, atomic::{AtomicU64, Ordering}};
+use std::collections::BTreeSet;
+use std::sync::{Arc, Mutex, atomic::{AtomicU64, Ordering}};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use dashmap::DashMap;
+use ed25519_dalek::{Signature, VerifyingKey};
 use async_trait::async_trait;
 use thiserror::Error;
 use prost::Message;
-use zrc_proto::v1::DirRecordV1;
-use zrc_crypto::{directory::verify_record, hash::derive_id};
+use tracing::warn;
+use zrc_proto::v1::{DirRecordV1, EndpointHintsV1};
+use zrc_crypto::{directory::{sign_record, verify_record}, hash::derive_id, identity::Identity};
 
+use crate::access::SearchScope;
 use crate::store::{RecordStore, StoreError};
 
+/// Most results a search page returns
+pub const MAX_SEARCH_LIMIT: usize = 100;
+
+/// Most subject ids a search page examines; a page can come back short
+/// (with a cursor) when filters reject many records
+const MAX_SEARCH_SCAN: usize = 1000;
+
 #[derive(Debug, Error)]
 pub enum RecordError {
     #[error("Store error: {0}")]
@@ -27,6 +38,146 @@ pub enum RecordError {
     TTLTooLong,
     #[error("Record not found")]
     NotFound,
+    #[error("Presence refresh is stale or replayed")]
+    StaleRefresh,
+    #[error("Record is not signed")]
+    Unsigned,
+}
+
+/// Build a device record self-signed by `identity`'s signing key.
+///
+/// The signature covers the subject id, signing key, encoded endpoints,
+/// TTL and timestamp, so neither the dirnode nor its store can alter any
+/// of them without `verify_signed_record` failing.
+pub fn sign_dir_record(
+    identity: &Identity,
+    endpoints: Option<EndpointHintsV1>,
+    ttl_seconds: u32,
+    timestamp: u64,
+) -> Result<DirRecordV1, RecordError> {
+    let subject_id = identity.id();
+    let signature = sign_record(
+        identity,
+        &subject_id,
+        &encode_endpoints(endpoints.as_ref()),
+        ttl_seconds,
+        timestamp,
+    )
+    .map_err(|_| RecordError::SubjectMismatch)?;
+
+    Ok(DirRecordV1 {
+        subject_id: subject_id.to_vec(),
+        device_sign_pub: identity.sign_pub().to_vec(),
+        endpoints,
+        ttl_seconds,
+        timestamp,
+        signature: signature.to_vec(),
+    })
+}
+
+/// Verify a record's self-signature and subject id binding.
+///
+/// The dirnode runs this on publish and again on every record read back
+/// from its store; clients should run it on anything a dirnode returns and
+/// ignore records that fail.
+pub fn verify_signed_record(record: &DirRecordV1, now: u64) -> Result<(), RecordError> {
+    if record.signature.is_empty() {
+        return Err(RecordError::Unsigned);
+    }
+
+    // Check subject_id matches device_sign_pub
+    if record.subject_id.len() != 32 || record.device_sign_pub.len() != 32 {
+        return Err(RecordError::SubjectMismatch);
+    }
+
+    let derived_id = derive_id(&record.device_sign_pub);
+    if record.subject_id != derived_id.as_slice() {
+        return Err(RecordError::SubjectMismatch);
+    }
+
+    // Verify signature using zrc-crypto
+    verify_record(
+        &record.subject_id,
+        &record.device_sign_pub,
+        &encode_endpoints(record.endpoints.as_ref()),
+        record.ttl_seconds,
+        record.timestamp,
+        &record.signature,
+        now,
+    )
+    .map_err(|_| RecordError::InvalidSignature)
+}
+
+/// Endpoints as covered by the record signature
+fn encode_endpoints(endpoints: Option<&EndpointHintsV1>) -> Vec<u8> {
+    endpoints
+        .map(|e| {
+            let mut buf = Vec::new();
+            Message::encode(e, &mut buf).ok();
+            buf
+        })
+        .unwrap_or_default()
+}
+
+/// Domain separator for presence refresh signatures
+const PRESENCE_REFRESH_CONTEXT: &[u8] = b"zrc_presence_refresh_v1";
+
+/// Bytes a device signs to refresh its presence
+pub fn presence_refresh_sign_data(subject_id: &[u8; 32], timestamp: u64) -> Vec<u8> {
+    let mut data = Vec::with_capacity(PRESENCE_REFRESH_CONTEXT.len() + 40);
+    data.extend_from_slice(PRESENCE_REFRESH_CONTEXT);
+    data.extend_from_slice(subject_id);
+    data.extend_from_slice(&timestamp.to_be_bytes());
+    data
+}
+
+/// Online/offline state of a device
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Presence {
+    pub online: bool,
+    /// Unix seconds of the last record store or presence refresh
+    pub last_seen: u64,
+    /// When the device goes offline without another refresh
+    pub expires_at: u64,
+}
+
+/// Device search filters and position
+#[derive(Debug, Clone, Default)]
+pub struct SearchQuery {
+    /// Return subjects after this one (the previous page's `next_cursor`)
+    pub cursor: Option<[u8; 32]>,
+    pub limit: usize,
+    /// Only devices currently online (true) or offline (false)
+    pub online: Option<bool>,
+    /// Lowercase hex prefix of the subject id; records carry no display
+    /// name, so the id is what devices are browsed by
+    pub prefix: Option<String>,
+}
+
+/// One device in a search page
+#[derive(Debug, Clone)]
+pub struct SearchItem {
+    pub subject_id: [u8; 32],
+    pub record: DirRecordV1,
+    pub presence: Option<Presence>,
+}
+
+/// A page of search results.
+///
+/// Pages are keyed by subject id, so records added or removed between
+/// pages never shift the others: nothing is skipped or repeated.
+#[derive(Debug, Clone)]
+pub struct SearchPage {
+    pub items: Vec<SearchItem>,
+    /// Pass back as `cursor` for the next page; None once exhausted
+    pub next_cursor: Option<[u8; 32]>,
+}
+
+struct PresenceEntry {
+    last_seen: u64,
+    /// Device timestamp of the latest refresh, to reject replays
+    refreshed_at: u64,
+    online: bool,
 }
 
 /// Stored record with metadata
@@ -43,6 +194,8 @@ pub struct RecordConfig {
     pub max_ttl_seconds: u32,
     pub max_records: usize,
     pub cleanup_interval: Duration,
+    /// How long a device stays online after its last refresh
+    pub presence_ttl: Duration,
 }
 
 impl Default for RecordConfig {
@@ -52,6 +205,7 @@ impl Default for RecordConfig {
             max_ttl_seconds: 86400,          // 24 hours
             max_records: 100_000,
             cleanup_interval: Duration::from_secs(3600), // 1 hour
+            presence_ttl: Duration::from_secs(300),      // 5 minutes
         }
     }
 }
@@ -61,6 +215,10 @@ pub struct RecordManager {
     records: DashMap<[u8; 32], Arc<StoredRecord>>,
     store: Arc<dyn RecordStore>,
     config: RecordConfig,
+    presence: DashMap<[u8; 32], PresenceEntry>,
+    /// Online devices ordered by presence expiry, so a sweep only visits
+    /// the ones that are due
+    presence_expiry: Mutex<BTreeSet<(u64, [u8; 32])>>,
 }
 
 impl RecordManager {
@@ -69,43 +227,133 @@ impl RecordManager {
             records: DashMap::new(),
             store,
             config,
+            presence: DashMap::new(),
+            presence_expiry: Mutex::new(BTreeSet::new()),
         }
     }
 
-    /// Verify record signature and subject ID binding
-    pub(crate) fn verify_record(&self, record: &DirRecordV1, now: u64) -> Result<(), RecordError> {
-        // Check subject_id matches device_sign_pub
-        if record.subject_id.len() != 32 || record.device_sign_pub.len() != 32 {
-            return Err(RecordError::SubjectMismatch);
+    /// Mark a device online as of `now`
+    fn mark_seen(&self, subject_id: &[u8; 32], now: u64, refreshed_at: u64) {
+        let ttl = self.config.presence_ttl.as_secs();
+        let mut expiry = self.presence_expiry.lock().unwrap();
+        let mut entry = self.presence.entry(*subject_id).or_insert(PresenceEntry {
+            last_seen: now,
+            refreshed_at: 0,
+            online: false,
+        });
+        if entry.online {
+            expiry.remove(&(entry.last_seen.saturating_add(ttl), *subject_id));
         }
+        entry.last_seen = now;
+        entry.refreshed_at = entry.refreshed_at.max(refreshed_at);
+        entry.online = true;
+        expiry.insert((now.saturating_add(ttl), *subject_id));
+    }
 
-        let derived_id = derive_id(&record.device_sign_pub);
-        if record.subject_id != derived_id.as_slice() {
-            return Err(RecordError::SubjectMismatch);
+    /// Drop a device's presence entry
+    fn forget_presence(&self, subject_id: &[u8; 32]) {
+        if let Some((_, entry)) = self.presence.remove(subject_id) {
+            if entry.online {
+                let expires_at = entry.last_seen.saturating_add(self.config.presence_ttl.as_secs());
+                self.presence_expiry.lock().unwrap().remove(&(expires_at, *subject_id));
+            }
         }
+    }
 
-        // Encode endpoints for signature verification
-        let endpoints_encoded = record.endpoints.as_ref()
-            .map(|e| {
-                let mut buf = Vec::new();
-                Message::encode(e, &mut buf).ok();
-                buf
-            })
-            .unwrap_or_default();
-
-        // Verify signature using zrc-crypto
-        verify_record(
-            &record.subject_id,
-            &record.device_sign_pub,
-            &endpoints_encoded,
-            record.ttl_seconds,
-            record.timestamp,
-            &record.signature,
-            now,
-        )
-        .map_err(|_| RecordError::InvalidSignature)?;
+    /// Restore presence from the last-seen times in the store, so devices
+    /// seen within the presence TTL before a restart stay online.
+    /// Returns how many devices were restored.
+    pub async fn load_presence(&self, now: u64) -> Result<usize, RecordError> {
+        let ttl = self.config.presence_ttl.as_secs();
+        let seen = self.store.list_last_seen(now).await?;
+        let mut expiry = self.presence_expiry.lock().unwrap();
+        for (subject_id, last_seen) in &seen {
+            let expires_at = last_seen.saturating_add(ttl);
+            let online = expires_at > now;
+            if online {
+                expiry.insert((expires_at, *subject_id));
+            }
+            self.presence.insert(*subject_id, PresenceEntry {
+                last_seen: *last_seen,
+                refreshed_at: 0,
+                online,
+            });
+        }
+        Ok(seen.len())
+    }
 
-        Ok(())
+    /// Current presence of a device, if it has ever been seen
+    pub fn presence(&self, subject_id: &[u8; 32]) -> Option<Presence> {
+        let ttl = self.config.presence_ttl.as_secs();
+        self.presence.get(subject_id).map(|entry| Presence {
+            online: entry.online,
+            last_seen: entry.last_seen,
+            expires_at: entry.last_seen.saturating_add(ttl),
+        })
+    }
+
+    /// Refresh a device's presence.
+    ///
+    /// The device signs `presence_refresh_sign_data(subject_id, timestamp)`
+    /// with the key in its stored record. `timestamp` must be within the
+    /// presence TTL of `now` and newer than the previous refresh.
+    pub async fn refresh_presence(
+        &self,
+        subject_id: &[u8; 32],
+        timestamp: u64,
+        signature: &[u8],
+        now: u64,
+    ) -> Result<Presence, RecordError> {
+        let record = self.get(subject_id, now).await?.ok_or(RecordError::NotFound)?;
+
+        let device_pub: [u8; 32] = record.device_sign_pub.as_slice()
+            .try_into()
+            .map_err(|_| RecordError::SubjectMismatch)?;
+        let signature: [u8; 64] = signature
+            .try_into()
+            .map_err(|_| RecordError::InvalidSignature)?;
+        VerifyingKey::from_bytes(&device_pub)
+            .and_then(|key| key.verify_strict(
+                &presence_refresh_sign_data(subject_id, timestamp),
+                &Signature::from_bytes(&signature),
+            ))
+            .map_err(|_| RecordError::InvalidSignature)?;
+
+        if now.abs_diff(timestamp) > self.config.presence_ttl.as_secs() {
+            return Err(RecordError::StaleRefresh);
+        }
+        if let Some(entry) = self.presence.get(subject_id) {
+            if timestamp <= entry.refreshed_at {
+                return Err(RecordError::StaleRefresh);
+            }
+        }
+
+        self.store.touch(subject_id, now).await?;
+        self.mark_seen(subject_id, now, timestamp);
+        Ok(self.presence(subject_id).expect("presence just recorded"))
+    }
+
+    /// Mark devices offline whose presence TTL has run out.
+    /// Returns how many went offline.
+    pub fn sweep_presence(&self, now: u64) -> usize {
+        let mut expiry = self.presence_expiry.lock().unwrap();
+        let mut swept = 0;
+        while let Some(&(expires_at, subject_id)) = expiry.first() {
+            if expires_at > now {
+                break;
+            }
+            expiry.pop_first();
+            if let Some(mut entry) = self.presence.get_mut(&subject_id) {
+                entry.online = false;
+            }
+            swept += 1;
+        }
+        swept
+    }
+
+    /// Verify record signature and subject ID binding
+    pub(crate) fn verify_record(&self, record: &DirRecordV1, now: u64) -> Result<(), RecordError> {
+        verify_signed_record(record, now)
     }
 
     /// Store or update record
@@ -150,13 +398,18 @@ impl RecordManager {
         });
         self.records.insert(subject_id, stored_record);
 
+        // Publishing a record counts as being seen
+        self.mark_seen(&subject_id, now, 0);
+
         Ok(())
     }
 
     /// Get record by subject ID
     pub async fn get(&self, subject_id: &[u8; 32], now: u64) -> Result<Option<DirRecordV1>, RecordError> {
-        // Check in-memory cache first
-        if let Some(stored) = self.records.get(subject_id) {
+        // Check in-memory cache first; clone the entry out so the map isn't
+        // locked when an expired one is removed
+        let cached = self.records.get(subject_id).map(|stored| stored.clone());
+        if let Some(stored) = cached {
             let record = &stored.record;
             // Check expiration
             let expires_at = record.timestamp.saturating_add(record.ttl_seconds as u64);
@@ -171,6 +424,13 @@ impl RecordManager {
 
         // Load from database
         if let Some(record) = self.store.load(subject_id).await? {
+            // Don't trust the store: serve only records still signed by the
+            // device they claim to describe
+            if record.subject_id != subject_id.as_slice() || self.verify_record(&record, now).is_err() {
+                warn!("Ignoring stored record for {} that fails verification", hex::encode(subject_id));
+                return Ok(None);
+            }
+
             // Check expiration
             let expires_at = record.timestamp.saturating_add(record.ttl_seconds as u64);
             if expires_at > now {
@@ -206,9 +466,63 @@ impl RecordManager {
     pub async fn delete(&self, subject_id: &[u8; 32]) -> Result<(), RecordError> {
         self.store.delete(subject_id).await?;
         self.records.remove(subject_id);
+        self.forget_presence(subject_id);
         Ok(())
     }
 
+    /// Page through devices in `scope` matching `query`
+    pub async fn search(
+        &self,
+        query: &SearchQuery,
+        scope: &SearchScope,
+        now: u64,
+    ) -> Result<SearchPage, RecordError> {
+        let limit = query.limit.clamp(1, MAX_SEARCH_LIMIT);
+        // One past the page so we know whether anything follows
+        let candidates: Vec<[u8; 32]> = match scope {
+            SearchScope::All => {
+                self.store.list_subjects(query.cursor.as_ref(), MAX_SEARCH_SCAN + 1).await?
+            }
+            SearchScope::Subjects(ids) => {
+                let start = match &query.cursor {
+                    Some(cursor) => ids.partition_point(|id| id <= cursor),
+                    None => 0,
+                };
+                ids[start..].iter().take(MAX_SEARCH_SCAN + 1).copied().collect()
+            }
+        };
+
+        let mut items = Vec::new();
+        let mut last_scanned = None;
+        for subject_id in candidates.iter().take(MAX_SEARCH_SCAN) {
+            if items.len() == limit {
+                break;
+            }
+            last_scanned = Some(*subject_id);
+
+            if let Some(prefix) = &query.prefix {
+                if !hex::encode(subject_id).starts_with(prefix.as_str()) {
+                    continue;
+                }
+            }
+            let presence = self.presence(subject_id);
+            if let Some(online) = query.online {
+                if presence.is_some_and(|p| p.online) != online {
+                    continue;
+                }
+            }
+            if let Some(record) = self.get(subject_id, now).await? {
+                items.push(SearchItem { subject_id: *subject_id, record, presence });
+            }
+        }
+
+        let exhausted = last_scanned.is_none_or(|last| candidates.last() == Some(&last));
+        Ok(SearchPage {
+            items,
+            next_cursor: if exhausted { None } else { last_scanned },
+        })
+    }
+
     /// Run expiration cleanup
     pub async fn cleanup_expired(&self) {
         let now = SystemTime::now()
@@ -220,10 +534,18 @@ impl RecordManager {
         if let Ok(expired_ids) = self.store.list_expired(now).await {
             for subject_id in expired_ids {
                 self.records.remove(&subject_id);
+                self.forget_presence(&subject_id);
                 let _ = self.store.delete(&subject_id).await;
             }
         }
 
+        // Offline devices unseen for longer than any record lives have no
+        // record left to report presence for
+        let max_ttl = self.config.max_ttl_seconds as u64;
+        self.presence.retain(|_id, entry| {
+            entry.online || entry.last_seen.saturating_add(max_ttl) > now
+        });
+
         // Also clean in-memory cache
         self.records.retain(|_id, stored| {
             let record = &stored.record;
@@ -252,14 +574,16 @@ mod tests {
         let subject_id = identity.id();
         let device_sign_pub = identity.sign_pub();
 
-        let mut record = DirRecordV1::default();
-        record.subject_id = subject_id.to_vec();
-        record.device_sign_pub = device_sign_pub.to_vec();
-        record.ttl_seconds = 3600;
-        record.timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+        let mut record = DirRecordV1 {
+            subject_id: subject_id.to_vec(),
+            device_sign_pub: device_sign_pub.to_vec(),
+            ttl_seconds: 3600,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            ..Default::default()
+        };
 
         // Sign the record
         let endpoints_encoded = Vec::new();
@@ -303,11 +627,13 @@ mod tests {
             .unwrap()
             .as_secs();
         
-        let mut record = DirRecordV1::default();
-        record.subject_id = subject_id.to_vec();
-        record.device_sign_pub = device_sign_pub.to_vec();
-        record.ttl_seconds = 100; // Short TTL
-        record.timestamp = base_timestamp;
+        let mut record = DirRecordV1 {
+            subject_id: subject_id.to_vec(),
+            device_sign_pub: device_sign_pub.to_vec(),
+            ttl_seconds: 100, // Short TTL
+            timestamp: base_timestamp,
+            ..Default::default()
+        };
 
         let endpoints_encoded = Vec::new();
         let sign_data = zrc_crypto::directory::dir_record_sign_data(
@@ -332,6 +658,272 @@ mod tests {
         let retrieved = record_mgr.get(&subject_id, now_expired).await.unwrap();
         assert!(retrieved.is_none());
     }
+
+    #[tokio::test]
+    async fn test_presence_expires_and_refreshes() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let store = Arc::new(SqliteStore::new(&db_path).await.unwrap());
+        let config = RecordConfig {
+            presence_ttl: Duration::from_secs(60),
+            ..Default::default()
+        };
+        let record_mgr = RecordManager::new(store, config);
+
+        let identity = Identity::generate();
+        let subject_id = identity.id();
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        let mut record = DirRecordV1 {
+            subject_id: subject_id.to_vec(),
+            device_sign_pub: identity.sign_pub().to_vec(),
+            ttl_seconds: 3600,
+            timestamp: now,
+            ..Default::default()
+        };
+        let sign_data = zrc_crypto::directory::dir_record_sign_data(
+            &record.subject_id,
+            &record.device_sign_pub,
+            &[],
+            record.ttl_seconds,
+            record.timestamp,
+        );
+        record.signature = identity.sign(&sign_data).to_vec();
+        record_mgr.store(record).await.unwrap();
+        assert!(record_mgr.presence(&subject_id).unwrap().online);
+
+        // Nothing is due before the TTL
+        assert_eq!(record_mgr.sweep_presence(now + 30), 0);
+        assert!(record_mgr.presence(&subject_id).unwrap().online);
+
+        // Silent past the TTL: offline, but the record is still served
+        assert_eq!(record_mgr.sweep_presence(now + 120), 1);
+        let presence = record_mgr.presence(&subject_id).unwrap();
+        assert!(!presence.online);
+        assert!(presence.last_seen >= now);
+        assert!(record_mgr.get(&subject_id, now + 120).await.unwrap().is_some());
+
+        // A signed refresh brings it back online
+        let refresh_at = now + 130;
+        let signature = identity.sign(&presence_refresh_sign_data(&subject_id, refresh_at));
+        let presence = record_mgr
+            .refresh_presence(&subject_id, refresh_at, &signature, refresh_at)
+            .await
+            .unwrap();
+        assert!(presence.online);
+        assert_eq!(presence.last_seen, refresh_at);
+        assert_eq!(presence.expires_at, refresh_at + 60);
+
+        // The same refresh can't be replayed, and others can't forge one
+        assert!(matches!(
+            record_mgr.refresh_presence(&subject_id, refresh_at, &signature, refresh_at + 1).await,
+            Err(RecordError::StaleRefresh)
+        ));
+        let forged = Identity::generate().sign(&presence_refresh_sign_data(&subject_id, refresh_at + 5));
+        assert!(matches!(
+            record_mgr.refresh_presence(&subject_id, refresh_at + 5, &forged, refresh_at + 5).await,
+            Err(RecordError::InvalidSignature)
+        ));
+
+        assert_eq!(record_mgr.sweep_presence(refresh_at + 59), 0);
+        assert_eq!(record_mgr.sweep_presence(refresh_at + 60), 1);
+        assert!(!record_mgr.presence(&subject_id).unwrap().online);
+    }
+
+    #[tokio::test]
+    async fn test_presence_survives_restart() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let store = Arc::new(SqliteStore::new(&db_path).await.unwrap());
+        let config = RecordConfig {
+            presence_ttl: Duration::from_secs(60),
+            ..Default::default()
+        };
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let identity = Identity::generate();
+        let subject_id = identity.id();
+        let record = sign_dir_record(&identity, None, 3600, now).unwrap();
+        RecordManager::new(store.clone(), config.clone()).store(record).await.unwrap();
+
+        // A new manager over the same database sees the device as it was
+        let restarted = RecordManager::new(store.clone(), config.clone());
+        assert_eq!(restarted.load_presence(now + 10).await.unwrap(), 1);
+        let presence = restarted.presence(&subject_id).unwrap();
+        assert!(presence.online);
+        assert!(presence.last_seen >= now);
+        assert_eq!(restarted.sweep_presence(presence.expires_at), 1);
+
+        // Restarted after the TTL ran out: offline, last seen kept
+        let later = RecordManager::new(store, config);
+        later.load_presence(now + 120).await.unwrap();
+        let presence = later.presence(&subject_id).unwrap();
+        assert!(!presence.online);
+        assert!(presence.last_seen >= now);
+        assert_eq!(later.sweep_presence(now + 120), 0);
+    }
+
+    #[tokio::test]
+    async fn test_tampered_endpoints_invalidate_record() {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let identity = Identity::generate();
+        let endpoints = EndpointHintsV1 {
+            direct_addrs: vec!["192.0.2.10:4433".to_string()],
+            ..Default::default()
+        };
+        let record = sign_dir_record(&identity, Some(endpoints), 3600, now).unwrap();
+        assert!(verify_signed_record(&record, now).is_ok());
+
+        let mut tampered = record.clone();
+        tampered.endpoints.as_mut().unwrap().direct_addrs[0] = "203.0.113.66:4433".to_string();
+        assert!(matches!(verify_signed_record(&tampered, now), Err(RecordError::InvalidSignature)));
+
+        let mut unsigned = record.clone();
+        unsigned.signature.clear();
+        assert!(matches!(verify_signed_record(&unsigned, now), Err(RecordError::Unsigned)));
+
+        // Publishing either is refused
+        let temp_dir = TempDir::new().unwrap();
+        let store = Arc::new(SqliteStore::new(temp_dir.path().join("test.db")).await.unwrap());
+        let record_mgr = RecordManager::new(store.clone(), RecordConfig::default());
+        assert!(record_mgr.store(tampered.clone()).await.is_err());
+        assert!(record_mgr.store(unsigned).await.is_err());
+
+        // A tampered record planted directly in the store isn't served
+        let subject_id = identity.id();
+        store.save(&subject_id, &tampered).await.unwrap();
+        assert!(record_mgr.get(&subject_id, now).await.unwrap().is_none());
+
+        store.save(&subject_id, &record).await.unwrap();
+        assert!(record_mgr.get(&subject_id, now).await.unwrap().is_some());
+    }
+
+    fn signed_record(identity: &Identity, now: u64) -> DirRecordV1 {
+        let mut record = DirRecordV1 {
+            subject_id: identity.id().to_vec(),
+            device_sign_pub: identity.sign_pub().to_vec(),
+            ttl_seconds: 3600,
+            timestamp: now,
+            ..Default::default()
+        };
+        let sign_data = zrc_crypto::directory::dir_record_sign_data(
+            &record.subject_id,
+            &record.device_sign_pub,
+            &[],
+            record.ttl_seconds,
+            record.timestamp,
+        );
+        record.signature = identity.sign(&sign_data).to_vec();
+        record
+    }
+
+    #[tokio::test]
+    async fn test_search_pagination_is_stable_across_changes() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let store = Arc::new(SqliteStore::new(&db_path).await.unwrap());
+        let record_mgr = RecordManager::new(store, RecordConfig::default());
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        let mut ids = Vec::new();
+        for _ in 0..6 {
+            let identity = Identity::generate();
+            ids.push(identity.id());
+            record_mgr.store(signed_record(&identity, now)).await.unwrap();
+        }
+        ids.sort();
+
+        let mut query = SearchQuery { limit: 2, ..Default::default() };
+        let first = record_mgr.search(&query, &SearchScope::All, now).await.unwrap();
+        let first_ids: Vec<_> = first.items.iter().map(|i| i.subject_id).collect();
+        assert_eq!(first_ids, ids[..2].to_vec());
+        assert!(first.items.iter().all(|i| i.presence.unwrap().online));
+
+        // Between pages: a returned record and a pending one are deleted,
+        // and new ones are published
+        record_mgr.delete(&ids[0]).await.unwrap();
+        record_mgr.delete(&ids[3]).await.unwrap();
+        let mut added = Vec::new();
+        for _ in 0..3 {
+            let identity = Identity::generate();
+            added.push(identity.id());
+            record_mgr.store(signed_record(&identity, now)).await.unwrap();
+        }
+
+        let mut seen = first_ids.clone();
+        query.cursor = first.next_cursor;
+        while query.cursor.is_some() {
+            let page = record_mgr.search(&query, &SearchScope::All, now).await.unwrap();
+            for item in &page.items {
+                assert!(item.subject_id > first_ids[1], "pages never go backwards");
+                seen.push(item.subject_id);
+            }
+            query.cursor = page.next_cursor;
+        }
+
+        let mut unique = seen.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), seen.len(), "no record is returned twice");
+        // Everything present throughout shows up; the deleted pending one doesn't
+        for id in [ids[1], ids[2], ids[4], ids[5]] {
+            assert!(seen.contains(&id));
+        }
+        assert!(!seen.contains(&ids[3]));
+        // Additions after the cursor are picked up
+        for id in added.iter().filter(|id| **id > first_ids[1]) {
+            assert!(seen.contains(id));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_filters_and_scope() {
+        let store = Arc::new(crate::store::MemoryStore::new());
+        let record_mgr = RecordManager::new(store, RecordConfig::default());
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        let mut ids = Vec::new();
+        for _ in 0..4 {
+            let identity = Identity::generate();
+            ids.push(identity.id());
+            record_mgr.store(signed_record(&identity, now)).await.unwrap();
+        }
+        let mut granted = vec![ids[0], ids[1]];
+        granted.sort();
+        let scope = SearchScope::Subjects(granted.clone());
+
+        let query = SearchQuery { limit: 10, ..Default::default() };
+        let page = record_mgr.search(&query, &scope, now).await.unwrap();
+        let found: Vec<_> = page.items.iter().map(|i| i.subject_id).collect();
+        assert_eq!(found, granted);
+        assert!(page.next_cursor.is_none());
+
+        let prefix = hex::encode(ids[1])[..6].to_string();
+        let query = SearchQuery { limit: 10, prefix: Some(prefix), ..Default::default() };
+        let page = record_mgr.search(&query, &scope, now).await.unwrap();
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.items[0].subject_id, ids[1]);
+
+        // Everyone just published, so nobody is offline until the sweep
+        let offline = SearchQuery { limit: 10, online: Some(false), ..Default::default() };
+        assert!(record_mgr.search(&offline, &scope, now).await.unwrap().items.is_empty());
+        record_mgr.sweep_presence(now + 3600);
+        assert_eq!(record_mgr.search(&offline, &scope, now).await.unwrap().items.len(), 2);
+    }
 }
 
 #[cfg(test)]
@@ -356,11 +948,13 @@ mod proptests {
             let subject_id = identity.id();
             let device_sign_pub = identity.sign_pub();
 
-            let mut record = DirRecordV1::default();
-            record.subject_id = subject_id.to_vec();
-            record.device_sign_pub = device_sign_pub.to_vec();
-            record.ttl_seconds = ttl_seconds;
-            record.timestamp = timestamp;
+            let mut record = DirRecordV1 {
+                subject_id: subject_id.to_vec(),
+                device_sign_pub: device_sign_pub.to_vec(),
+                ttl_seconds,
+                timestamp,
+                ..Default::default()
+            };
 
             let endpoints_encoded = Vec::new();
             let sign_data = zrc_crypto::directory::dir_record_sign_data(
@@ -393,14 +987,16 @@ mod proptests {
             let correct_subject_id = identity.id();
             let device_sign_pub = identity.sign_pub();
 
-            let mut record = DirRecordV1::default();
-            record.subject_id = correct_subject_id.to_vec();
-            record.device_sign_pub = device_sign_pub.to_vec();
-            record.ttl_seconds = 3600;
-            record.timestamp = SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap()
-                .as_secs();
+            let mut record = DirRecordV1 {
+                subject_id: correct_subject_id.to_vec(),
+                device_sign_pub: device_sign_pub.to_vec(),
+                ttl_seconds: 3600,
+                timestamp: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap()
+                    .as_secs(),
+                ..Default::default()
+            };
 
             let endpoints_encoded = Vec::new();
             let sign_data = zrc_crypto::directory::dir_record_sign_data(
@@ -465,11 +1061,13 @@ mod proptests {
             let subject_id = identity.id();
             let device_sign_pub = identity.sign_pub();
 
-            let mut record = DirRecordV1::default();
-            record.subject_id = subject_id.to_vec();
-            record.device_sign_pub = device_sign_pub.to_vec();
-            record.ttl_seconds = ttl_seconds;
-            record.timestamp = timestamp;
+            let mut record = DirRecordV1 {
+                subject_id: subject_id.to_vec(),
+                device_sign_pub: device_sign_pub.to_vec(),
+                ttl_seconds,
+                timestamp,
+                ..Default::default()
+            };
 
             let endpoints_encoded = Vec::new();
             let sign_data = zrc_crypto::directory::dir_record_sign_data(
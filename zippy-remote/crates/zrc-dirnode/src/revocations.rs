@@ -0,0 +1,131 @@
+//! Key revocation distribution
+//!
+//! Devices publish `RevocationV1` records here so peers can learn that a
+//! signing key is compromised without having to reach the device. Records
+//! are self-authenticating (signed by both the revoked and the replacement
+//! key), so publishing needs no authorization; anything that does not
+//! verify is refused.
+
+use std::sync::Arc;
+use prost::Message;
+use thiserror::Error;
+use tracing::warn;
+use zrc_proto::v1::RevocationV1;
+use zrc_security::key_recovery::RevocationSet;
+
+use crate::store::{RecordStore, StoreError};
+
+/// Largest encoded revocation accepted
+pub const MAX_REVOCATION_SIZE: usize = 1024;
+
+#[derive(Debug, Error)]
+pub enum RevocationError {
+    #[error("Store error: {0}")]
+    Store(#[from] StoreError),
+    #[error("Invalid revocation: {0}")]
+    Invalid(String),
+    #[error("Revocation too large")]
+    TooLarge,
+}
+
+/// Verified revocations, cached in memory and persisted in the store.
+pub struct RevocationRegistry {
+    set: RevocationSet,
+    store: Arc<dyn RecordStore>,
+}
+
+impl RevocationRegistry {
+    /// Load the revocations saved in `store`. Saved records that no longer
+    /// verify are skipped.
+    pub async fn load(store: Arc<dyn RecordStore>) -> Result<Self, StoreError> {
+        let set = RevocationSet::new();
+        for record_data in store.list_revocations().await? {
+            if let Err(e) = set.insert_encoded(&record_data) {
+                warn!("Skipping stored revocation: {}", e);
+            }
+        }
+        Ok(Self { set, store })
+    }
+
+    /// Verify, cache and persist an encoded revocation. Returns false if an
+    /// equivalent or earlier revocation of the key was already known.
+    pub async fn publish(&self, record_data: &[u8]) -> Result<bool, RevocationError> {
+        if record_data.len() > MAX_REVOCATION_SIZE {
+            return Err(RevocationError::TooLarge);
+        }
+        let record = RevocationV1::decode(record_data)
+            .map_err(|e| RevocationError::Invalid(e.to_string()))?;
+        let revoked_sign_pub = record.revoked_sign_pub.clone();
+        let added = self
+            .set
+            .insert(record)
+            .map_err(|e| RevocationError::Invalid(e.to_string()))?;
+        if added {
+            self.store.save_revocation(&revoked_sign_pub, record_data).await?;
+        }
+        Ok(added)
+    }
+
+    /// The revocation of `sign_pub`, if one has been published
+    pub fn get(&self, sign_pub: &[u8]) -> Option<RevocationV1> {
+        self.set.get(sign_pub)
+    }
+
+    /// Number of known revocations
+    pub fn len(&self) -> usize {
+        self.set.len()
+    }
+
+    /// Whether no revocations are known
+    pub fn is_empty(&self) -> bool {
+        self.set.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::SigningKey;
+    use zrc_proto::v1::revoke_v1::ReasonV1;
+    use zrc_security::key_recovery::issue_revocation;
+    use crate::store::MemoryStore;
+
+    #[tokio::test]
+    async fn test_published_revocation_survives_reload() {
+        let store: Arc<dyn RecordStore> = Arc::new(MemoryStore::new());
+        let registry = RevocationRegistry::load(store.clone()).await.unwrap();
+
+        let old = SigningKey::from_bytes(&[1u8; 32]);
+        let new = SigningKey::from_bytes(&[2u8; 32]);
+        let record = issue_revocation(&[9u8; 32], &old, &new, ReasonV1::Compromised, 1000, 990);
+        assert!(registry.publish(&record.encode_to_vec()).await.unwrap());
+        assert!(!registry.publish(&record.encode_to_vec()).await.unwrap());
+
+        let old_pub = old.verifying_key().to_bytes();
+        let reloaded = RevocationRegistry::load(store).await.unwrap();
+        assert_eq!(reloaded.get(&old_pub), Some(record));
+        assert!(reloaded.get(&new.verifying_key().to_bytes()).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_unverifiable_revocation_is_refused() {
+        let store: Arc<dyn RecordStore> = Arc::new(MemoryStore::new());
+        let registry = RevocationRegistry::load(store.clone()).await.unwrap();
+
+        let old = SigningKey::from_bytes(&[1u8; 32]);
+        let new = SigningKey::from_bytes(&[2u8; 32]);
+        let mut record = issue_revocation(&[9u8; 32], &old, &new, ReasonV1::Compromised, 0, 0);
+        record.not_before = 1;
+
+        assert!(matches!(
+            registry.publish(&record.encode_to_vec()).await,
+            Err(RevocationError::Invalid(_))
+        ));
+        assert!(matches!(
+            registry.publish(&[0u8; MAX_REVOCATION_SIZE + 1]).await,
+            Err(RevocationError::TooLarge)
+        ));
+        assert!(registry.is_empty());
+        assert!(store.list_revocations().await.unwrap().is_empty());
+    }
+}
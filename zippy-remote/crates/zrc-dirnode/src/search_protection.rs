@@ -1,5 +1,6 @@
 //! Search protection and enumeration prevention
 
+use std::hash::Hash;
 use std::net::IpAddr;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -17,14 +18,14 @@ pub enum ProtectionError {
     IpBlocked,
 }
 
-/// Rate limiter for lookup requests
-struct RateLimiter {
-    requests: DashMap<IpAddr, (u32, Instant)>,
+/// Rate limiter for lookup requests, keyed by IP or by caller
+struct RateLimiter<K: Hash + Eq = IpAddr> {
+    requests: DashMap<K, (u32, Instant)>,
     limit: u32,
     window: Duration,
 }
 
-impl RateLimiter {
+impl<K: Hash + Eq> RateLimiter<K> {
     fn new(limit: u32, window: Duration) -> Self {
         Self {
             requests: DashMap::new(),
@@ -33,7 +34,7 @@ impl RateLimiter {
         }
     }
 
-    fn check(&self, ip: IpAddr) -> bool {
+    fn check(&self, key: K) -> bool {
         let now = Instant::now();
         
         // Clean up old entries
@@ -42,7 +43,7 @@ impl RateLimiter {
         });
 
         // Check or increment
-        let mut entry = self.requests.entry(ip).or_insert((0, now));
+        let mut entry = self.requests.entry(key).or_insert((0, now));
         if entry.0 >= self.limit {
             false
         } else {
@@ -51,8 +52,8 @@ impl RateLimiter {
         }
     }
 
-    fn reset(&self, ip: IpAddr) {
-        self.requests.remove(&ip);
+    fn reset(&self, key: &K) {
+        self.requests.remove(key);
     }
 }
 
@@ -133,6 +134,9 @@ impl IpBlocklist {
 /// Search protection manager
 pub struct SearchProtection {
     rate_limiter: Arc<RateLimiter>,
+    /// Search pages per caller grant, so one grant can't page faster by
+    /// spreading requests over many IPs
+    caller_limiter: Arc<RateLimiter<String>>,
     enumeration_detector: Arc<EnumerationDetector>,
     blocklist: Arc<IpBlocklist>,
 }
@@ -144,6 +148,10 @@ impl SearchProtection {
                 rate_limit_per_minute,
                 Duration::from_secs(60),
             )),
+            caller_limiter: Arc::new(RateLimiter::new(
+                rate_limit_per_minute,
+                Duration::from_secs(60),
+            )),
             enumeration_detector: Arc::new(EnumerationDetector::new(
                 100, // 100 lookups in window = enumeration
                 Duration::from_secs(300), // 5 minute window
@@ -176,9 +184,19 @@ impl SearchProtection {
         Ok(())
     }
 
+    /// Check a search page request: the per-IP lookup checks plus a limit
+    /// on the caller's grant
+    pub fn check_search(&self, ip: IpAddr, caller: &str) -> Result<(), ProtectionError> {
+        self.check_lookup(ip)?;
+        if !self.caller_limiter.check(caller.to_string()) {
+            return Err(ProtectionError::RateLimited);
+        }
+        Ok(())
+    }
+
     /// Reset rate limits for an IP (for testing/admin)
     pub fn reset(&self, ip: IpAddr) {
-        self.rate_limiter.reset(ip);
+        self.rate_limiter.reset(&ip);
         self.enumeration_detector.reset(ip);
     }
 }
@@ -11,6 +11,7 @@ use tower_http::trace::TraceLayer;
 use crate::config::ServerConfig;
 use crate::store::{SqliteStore, RecordStore};
 use crate::records::RecordManager;
+use crate::revocations::RevocationRegistry;
 use crate::access::AccessController;
 use crate::discovery::DiscoveryManager;
 use crate::search_protection::SearchProtection;
@@ -23,6 +24,7 @@ pub struct DirNodeServer {
     access_ctrl: Arc<AccessController>,
     discovery_mgr: Arc<DiscoveryManager>,
     protection: Arc<SearchProtection>,
+    revocations: Arc<RevocationRegistry>,
 }
 
 impl DirNodeServer {
@@ -34,12 +36,30 @@ impl DirNodeServer {
         // Create record manager
         let record_config = config.record_config();
         let record_mgr = Arc::new(RecordManager::new(store.clone(), record_config));
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let restored = record_mgr.load_presence(now).await?;
+        info!("Restored presence for {} devices", restored);
+
+        // Load published key revocations
+        let revocations = Arc::new(RevocationRegistry::load(store.clone()).await?);
+        info!("Loaded {} key revocations", revocations.len());
 
         // Create access controller
         let mut access_ctrl = AccessController::new(config.access_mode());
         for token in &config.admin_tokens {
             access_ctrl.add_admin_token(token.clone());
         }
+        for issuer in config.capability_issuers()? {
+            access_ctrl.add_trusted_issuer(issuer);
+        }
+        for (group, members) in config.device_groups()? {
+            for subject_id in members {
+                access_ctrl.add_group_member(group.clone(), subject_id);
+            }
+        }
         let access_ctrl = Arc::new(access_ctrl);
 
         // Create discovery manager
@@ -55,6 +75,7 @@ impl DirNodeServer {
             access_ctrl,
             discovery_mgr,
             protection,
+            revocations,
         })
     }
 
@@ -68,6 +89,7 @@ impl DirNodeServer {
             access_ctrl: self.access_ctrl.clone(),
             discovery_mgr: self.discovery_mgr.clone(),
             protection: self.protection.clone(),
+            revocations: self.revocations.clone(),
         };
 
         let mut app = create_router(api_state)
@@ -99,6 +121,25 @@ impl DirNodeServer {
             }
         });
 
+        // Start presence sweep; it only visits devices that are due, so it
+        // can run often enough to keep online state within a few seconds
+        let record_mgr = self.record_mgr.clone();
+        let sweep_interval = Duration::from_secs((self.config.presence_ttl_seconds / 10).clamp(1, 30));
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(sweep_interval);
+            loop {
+                interval.tick().await;
+                let now = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap()
+                    .as_secs();
+                let offline = record_mgr.sweep_presence(now);
+                if offline > 0 {
+                    info!("{} devices went offline", offline);
+                }
+            }
+        });
+
         // Start HTTP server
         let listener = tokio::net::TcpListener::bind(&self.config.listen_addr).await?;
         info!("HTTP server listening on {}", self.config.listen_addr);
@@ -25,12 +25,23 @@ pub trait RecordStore: Send + Sync {
     async fn load(&self, subject_id: &[u8; 32]) -> Result<Option<DirRecordV1>, StoreError>;
     async fn delete(&self, subject_id: &[u8; 32]) -> Result<(), StoreError>;
     async fn list_expired(&self, now: u64) -> Result<Vec<[u8; 32]>, StoreError>;
+    /// Update a record's last-seen time; returns false if there is no record
+    async fn touch(&self, subject_id: &[u8; 32], last_seen: u64) -> Result<bool, StoreError>;
+    /// Last-seen times of the unexpired records that have been seen
+    async fn list_last_seen(&self, now: u64) -> Result<Vec<([u8; 32], u64)>, StoreError>;
+    /// Up to `limit` subject ids greater than `after`, in ascending order
+    async fn list_subjects(&self, after: Option<&[u8; 32]>, limit: usize) -> Result<Vec<[u8; 32]>, StoreError>;
+    /// Save an encoded `RevocationV1`, replacing any earlier one for the key
+    async fn save_revocation(&self, revoked_sign_pub: &[u8], record_data: &[u8]) -> Result<(), StoreError>;
+    /// All saved revocations, encoded
+    async fn list_revocations(&self) -> Result<Vec<Vec<u8>>, StoreError>;
 }
 
 /// In-memory store for testing
 #[cfg(test)]
 pub struct MemoryStore {
     records: Arc<Mutex<std::collections::HashMap<[u8; 32], DirRecordV1>>>,
+    revocations: Arc<Mutex<std::collections::HashMap<Vec<u8>, Vec<u8>>>>,
 }
 
 #[cfg(test)]
@@ -38,6 +49,7 @@ impl MemoryStore {
     pub fn new() -> Self {
         Self {
             records: Arc::new(Mutex::new(std::collections::HashMap::new())),
+            revocations: Arc::new(Mutex::new(std::collections::HashMap::new())),
         }
     }
 }
@@ -63,6 +75,34 @@ impl RecordStore for MemoryStore {
         // For property tests, we don't need to implement this
         Ok(Vec::new())
     }
+
+    async fn touch(&self, subject_id: &[u8; 32], _last_seen: u64) -> Result<bool, StoreError> {
+        Ok(self.records.lock().unwrap().contains_key(subject_id))
+    }
+
+    async fn list_last_seen(&self, _now: u64) -> Result<Vec<([u8; 32], u64)>, StoreError> {
+        Ok(Vec::new())
+    }
+
+    async fn list_subjects(&self, after: Option<&[u8; 32]>, limit: usize) -> Result<Vec<[u8; 32]>, StoreError> {
+        let mut ids: Vec<[u8; 32]> = self.records.lock().unwrap()
+            .keys()
+            .filter(|id| after.is_none_or(|after| *id > after))
+            .copied()
+            .collect();
+        ids.sort();
+        ids.truncate(limit);
+        Ok(ids)
+    }
+
+    async fn save_revocation(&self, revoked_sign_pub: &[u8], record_data: &[u8]) -> Result<(), StoreError> {
+        self.revocations.lock().unwrap().insert(revoked_sign_pub.to_vec(), record_data.to_vec());
+        Ok(())
+    }
+
+    async fn list_revocations(&self) -> Result<Vec<Vec<u8>>, StoreError> {
+        Ok(self.revocations.lock().unwrap().values().cloned().collect())
+    }
 }
 
 /// SQLite-based record store
@@ -94,12 +134,26 @@ impl SqliteStore {
                     signature BLOB NOT NULL,
                     timestamp INTEGER NOT NULL,
                     ttl_seconds INTEGER NOT NULL,
-                    stored_at INTEGER NOT NULL
+                    stored_at INTEGER NOT NULL,
+                    last_seen INTEGER NOT NULL DEFAULT 0
                 )
                 "#,
                 [],
             )?;
 
+            // Databases created before presence tracking lack last_seen
+            let has_last_seen: i64 = conn.query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('records') WHERE name = 'last_seen'",
+                [],
+                |row| row.get(0),
+            )?;
+            if has_last_seen == 0 {
+                conn.execute(
+                    "ALTER TABLE records ADD COLUMN last_seen INTEGER NOT NULL DEFAULT 0",
+                    [],
+                )?;
+            }
+
             conn.execute(
                 r#"
                 CREATE INDEX IF NOT EXISTS idx_records_expiry 
@@ -137,6 +191,16 @@ impl SqliteStore {
                 [],
             )?;
 
+            conn.execute(
+                r#"
+                CREATE TABLE IF NOT EXISTS revocations (
+                    revoked_sign_pub BLOB PRIMARY KEY,
+                    record_data BLOB NOT NULL
+                )
+                "#,
+                [],
+            )?;
+
             Ok(())
         }).await
         .map_err(|_| StoreError::Database(rusqlite::Error::SqliteFailure(
@@ -286,8 +350,8 @@ impl RecordStore for SqliteStore {
             conn.execute(
                 r#"
                 INSERT OR REPLACE INTO records 
-                (subject_id, record_data, signature, timestamp, ttl_seconds, stored_at)
-                VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                (subject_id, record_data, signature, timestamp, ttl_seconds, stored_at, last_seen)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6)
                 "#,
                 params![
                     subject_id.as_slice(),
@@ -380,6 +444,122 @@ impl RecordStore for SqliteStore {
 
         Ok(expired)
     }
+
+    async fn touch(&self, subject_id: &[u8; 32], last_seen: u64) -> Result<bool, StoreError> {
+        let conn = self.conn.clone();
+        let subject_id = *subject_id;
+        let updated = tokio::task::spawn_blocking(move || -> Result<usize, rusqlite::Error> {
+            let conn = conn.lock().unwrap();
+            conn.execute(
+                "UPDATE records SET last_seen = ?1 WHERE subject_id = ?2",
+                params![last_seen as i64, subject_id.as_slice()],
+            )
+        }).await
+        .map_err(|_| StoreError::Database(rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_MISUSE),
+            None
+        )))?
+        .map_err(StoreError::Database)?;
+
+        Ok(updated > 0)
+    }
+
+    async fn list_last_seen(&self, now: u64) -> Result<Vec<([u8; 32], u64)>, StoreError> {
+        let conn = self.conn.clone();
+        let seen = tokio::task::spawn_blocking(move || -> Result<Vec<([u8; 32], u64)>, rusqlite::Error> {
+            let conn = conn.lock().unwrap();
+            let mut stmt = conn.prepare(
+                "SELECT subject_id, last_seen FROM records WHERE last_seen > 0 AND (timestamp + ttl_seconds) > ?1",
+            )?;
+            let rows = stmt.query_map(params![now as i64], |row| {
+                Ok((row.get::<_, Vec<u8>>(0)?, row.get::<_, i64>(1)?))
+            })?;
+
+            let mut seen = Vec::new();
+            for row in rows {
+                let (subject_id, last_seen) = row?;
+                if let Ok(subject_id) = <[u8; 32]>::try_from(subject_id) {
+                    seen.push((subject_id, last_seen as u64));
+                }
+            }
+            Ok(seen)
+        }).await
+        .map_err(|_| StoreError::Database(rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_MISUSE),
+            None
+        )))?
+        .map_err(StoreError::Database)?;
+
+        Ok(seen)
+    }
+
+    async fn list_subjects(&self, after: Option<&[u8; 32]>, limit: usize) -> Result<Vec<[u8; 32]>, StoreError> {
+        let conn = self.conn.clone();
+        // An empty blob sorts before every id
+        let after = after.map(|id| id.to_vec()).unwrap_or_default();
+        let ids = tokio::task::spawn_blocking(move || -> Result<Vec<[u8; 32]>, rusqlite::Error> {
+            let conn = conn.lock().unwrap();
+            // Keyset pagination over the primary key
+            let mut stmt = conn.prepare(
+                "SELECT subject_id FROM records WHERE subject_id > ?1 ORDER BY subject_id LIMIT ?2",
+            )?;
+            let rows = stmt.query_map(
+                params![after.as_slice(), limit as i64],
+                |row| row.get::<_, Vec<u8>>(0),
+            )?;
+
+            let mut ids = Vec::new();
+            for row in rows {
+                if let Ok(id) = <[u8; 32]>::try_from(row?) {
+                    ids.push(id);
+                }
+            }
+            Ok(ids)
+        }).await
+        .map_err(|_| StoreError::Database(rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_MISUSE),
+            None
+        )))?
+        .map_err(StoreError::Database)?;
+
+        Ok(ids)
+    }
+
+    async fn save_revocation(&self, revoked_sign_pub: &[u8], record_data: &[u8]) -> Result<(), StoreError> {
+        let conn = self.conn.clone();
+        let revoked_sign_pub = revoked_sign_pub.to_vec();
+        let record_data = record_data.to_vec();
+        tokio::task::spawn_blocking(move || -> Result<(), rusqlite::Error> {
+            let conn = conn.lock().unwrap();
+            conn.execute(
+                "INSERT OR REPLACE INTO revocations (revoked_sign_pub, record_data) VALUES (?1, ?2)",
+                params![revoked_sign_pub.as_slice(), record_data.as_slice()],
+            )?;
+            Ok(())
+        }).await
+        .map_err(|_| StoreError::Database(rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_MISUSE),
+            None
+        )))?
+        .map_err(StoreError::Database)?;
+        Ok(())
+    }
+
+    async fn list_revocations(&self) -> Result<Vec<Vec<u8>>, StoreError> {
+        let conn = self.conn.clone();
+        let records = tokio::task::spawn_blocking(move || -> Result<Vec<Vec<u8>>, rusqlite::Error> {
+            let conn = conn.lock().unwrap();
+            let mut stmt = conn.prepare("SELECT record_data FROM revocations")?;
+            let rows = stmt.query_map([], |row| row.get::<_, Vec<u8>>(0))?;
+            rows.collect()
+        }).await
+        .map_err(|_| StoreError::Database(rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_MISUSE),
+            None
+        )))?
+        .map_err(StoreError::Database)?;
+        Ok(records)
+    }
 }
 
 #[cfg(test)]
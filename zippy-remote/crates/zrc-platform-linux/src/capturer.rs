@@ -125,6 +125,11 @@ impl LinuxCapturer {
         self.monitor_manager.monitors()
     }
 
+    /// Re-enumerate monitors (e.g. after a display was unplugged)
+    pub fn refresh_monitors(&mut self) -> Result<(), crate::monitor::MonitorError> {
+        self.monitor_manager.refresh()
+    }
+
     /// Select monitor to capture
     pub fn select_monitor(&mut self, monitor_id: u32) -> Result<(), CaptureError> {
         if self.monitor_manager.get_monitor(monitor_id).is_none() {
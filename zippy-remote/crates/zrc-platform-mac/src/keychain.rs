@@ -1,9 +1,10 @@
 #![cfg(target_os = "macos")]
 #![allow(unsafe_code)]
 
-use security_framework::item::ItemSearchOptions;
-use security_framework::os::macos::keychain::SecKeychain;
-use security_framework::base::Result as SecResult;
+use security_framework::base::Error as SecError;
+use security_framework::passwords::{
+    delete_generic_password, get_generic_password, set_generic_password,
+};
 use zeroize::ZeroizeOnDrop;
 use thiserror::Error;
 
@@ -30,6 +31,25 @@ pub struct KeyData {
     data: Vec<u8>,
 }
 
+impl KeyData {
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+/// errSecItemNotFound
+const ERR_SEC_ITEM_NOT_FOUND: i32 = -25300;
+/// errSecInteractionNotAllowed: the keychain is locked and cannot prompt
+const ERR_SEC_INTERACTION_NOT_ALLOWED: i32 = -25308;
+
+fn map_sec_error(e: SecError) -> KeychainError {
+    match e.code() {
+        ERR_SEC_ITEM_NOT_FOUND => KeychainError::NotFound,
+        ERR_SEC_INTERACTION_NOT_ALLOWED => KeychainError::Locked,
+        _ => KeychainError::SecurityFramework(e.to_string()),
+    }
+}
+
 impl KeychainStore {
     /// Create keychain store
     pub fn new(service_name: String, access_group: Option<String>) -> Self {
@@ -39,25 +59,25 @@ impl KeychainStore {
         }
     }
 
-    /// Store key in Keychain
+    /// Store key in Keychain as a generic password (service, key_id),
+    /// replacing any existing item. Generic passwords in the login keychain
+    /// are not synced to iCloud.
     pub fn store_key(&self, key_id: &str, data: &[u8]) -> Result<(), KeychainError> {
-        // TODO: Implement using SecItemAdd with kSecAttrAccessible
-        // Disable iCloud sync for device keys
-        // For now, placeholder
-        Ok(())
+        set_generic_password(&self.service_name, key_id, data).map_err(map_sec_error)
     }
 
     /// Load key from Keychain
     pub fn load_key(&self, key_id: &str) -> Result<KeyData, KeychainError> {
-        // TODO: Implement using SecItemCopyMatching
-        // Handle Keychain locked state
-        Err(KeychainError::NotFound)
+        let data = get_generic_password(&self.service_name, key_id).map_err(map_sec_error)?;
+        Ok(KeyData { data })
     }
 
     /// Delete key from Keychain
     pub fn delete_key(&self, key_id: &str) -> Result<(), KeychainError> {
-        // TODO: Implement using SecItemDelete
-        Ok(())
+        match delete_generic_password(&self.service_name, key_id).map_err(map_sec_error) {
+            Err(KeychainError::NotFound) => Ok(()),
+            other => other,
+        }
     }
 
     /// Zeroize key data
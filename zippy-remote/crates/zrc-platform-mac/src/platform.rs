@@ -5,7 +5,7 @@ use async_trait::async_trait;
 use bytes::Bytes;
 use tokio::sync::Mutex;
 
-use zrc_core::platform::{HostPlatform, InputEvent};
+use zrc_core::platform::{HostPlatform, InputEvent, MonitorBounds, MonitorInfo};
 
 use crate::capturer::MacCapturer;
 use crate::injector::MacInjector;
@@ -113,4 +113,37 @@ impl HostPlatform for MacPlatform {
         
         Ok(Bytes::new())
     }
+
+    async fn list_monitors(&self) -> Vec<MonitorInfo> {
+        let mut capturer = self.capturer.lock().await;
+        // Keep the last known list if re-enumeration fails
+        let _ = capturer.0.refresh_monitors();
+        let mut monitors: Vec<MonitorInfo> = capturer.0.list_monitors()
+            .into_iter()
+            .map(|m| MonitorInfo {
+                id: m.id,
+                name: m.name.clone(),
+                bounds: MonitorBounds {
+                    x: m.bounds.origin.x as i32,
+                    y: m.bounds.origin.y as i32,
+                    width: m.bounds.size.width as u32,
+                    height: m.bounds.size.height as u32,
+                },
+                scale: m.scale_factor,
+                primary: m.is_main,
+            })
+            .collect();
+        monitors.sort_by_key(|m| m.id);
+        monitors
+    }
+
+    async fn capture_monitor(&self, id: u32) -> anyhow::Result<Bytes> {
+        let mut capturer = self.capturer.lock().await;
+        capturer.0.select_monitor(id)
+            .map_err(|e| anyhow::anyhow!("monitor {id} unavailable: {e}"))?;
+        let frame = capturer.0.capture_frame()
+            .map_err(|e| anyhow::anyhow!("capture failed: {e}"))?;
+
+        Ok(Bytes::from(frame))
+    }
 }
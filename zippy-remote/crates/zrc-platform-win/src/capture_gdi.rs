@@ -224,3 +224,69 @@ pub fn capture_primary_bgra() -> Result<BgraFrame, CaptureError> {
     let mut capturer = GdiCapturer::new()?;
     capturer.capture_frame()
 }
+
+/// One-shot BitBlt of a rectangle of the virtual desktop, e.g. one monitor's
+/// bounds.
+pub fn capture_rect_bgra(x: i32, y: i32, width: u32, height: u32) -> Result<BgraFrame, CaptureError> {
+    let (w, h) = (width as i32, height as i32);
+    if w <= 0 || h <= 0 {
+        return Err(CaptureError::Size);
+    }
+    unsafe {
+        let screen_dc = GetDC(None);
+        if screen_dc.is_invalid() {
+            return Err(CaptureError::Win32);
+        }
+        let memory_dc = CreateCompatibleDC(Some(screen_dc));
+        if memory_dc.is_invalid() {
+            let _ = ReleaseDC(None, screen_dc);
+            return Err(CaptureError::ResourceCreation);
+        }
+        let bitmap = CreateCompatibleBitmap(screen_dc, w, h);
+        if bitmap.is_invalid() {
+            let _ = DeleteDC(memory_dc);
+            let _ = ReleaseDC(None, screen_dc);
+            return Err(CaptureError::ResourceCreation);
+        }
+        let old = SelectObject(memory_dc, bitmap.into());
+
+        let stride = width * 4;
+        let mut bgra = vec![0u8; (stride as usize) * (height as usize)];
+        let mut bmi = BITMAPINFO {
+            bmiHeader: BITMAPINFOHEADER {
+                biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+                biWidth: w,
+                biHeight: -h, // top-down DIB
+                biPlanes: 1,
+                biBitCount: 32,
+                biCompression: BI_RGB.0 as u32,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let blitted = BitBlt(memory_dc, 0, 0, w, h, Some(screen_dc), x, y, SRCCOPY).is_ok();
+        let _ = SelectObject(memory_dc, old);
+        let scanlines = if blitted {
+            GetDIBits(
+                memory_dc,
+                bitmap,
+                0,
+                height,
+                Some(bgra.as_mut_ptr() as *mut _),
+                &mut bmi,
+                DIB_RGB_COLORS,
+            )
+        } else {
+            0
+        };
+
+        let _ = DeleteObject(bitmap.into());
+        let _ = DeleteDC(memory_dc);
+        let _ = ReleaseDC(None, screen_dc);
+
+        if scanlines == 0 {
+            return Err(CaptureError::Win32);
+        }
+        Ok(BgraFrame { width, height, stride, bgra })
+    }
+}
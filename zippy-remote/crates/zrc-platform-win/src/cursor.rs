@@ -0,0 +1,135 @@
+#![cfg(windows)]
+#![allow(unsafe_code)] // Windows API calls require unsafe.
+
+//! Current cursor shape for `HostPlatform::cursor_shape`.
+//!
+//! System cursors are reported by name. Application cursors are read as
+//! bitmaps when they have a color image; monochrome ones fall back to the
+//! arrow.
+
+use bytes::Bytes;
+use windows::core::PCWSTR;
+use windows::Win32::Graphics::Gdi::*;
+use windows::Win32::UI::WindowsAndMessaging::*;
+use zrc_core::cursor::{CursorBitmap, CursorShape, StandardCursor, MAX_CURSOR_DIMENSION};
+
+const SYSTEM_CURSORS: [(PCWSTR, StandardCursor); 12] = [
+    (IDC_ARROW, StandardCursor::Arrow),
+    (IDC_IBEAM, StandardCursor::Text),
+    (IDC_HAND, StandardCursor::Hand),
+    (IDC_WAIT, StandardCursor::Wait),
+    (IDC_APPSTARTING, StandardCursor::Wait),
+    (IDC_CROSS, StandardCursor::Crosshair),
+    (IDC_SIZENS, StandardCursor::ResizeNs),
+    (IDC_SIZEWE, StandardCursor::ResizeEw),
+    (IDC_SIZENWSE, StandardCursor::ResizeNwse),
+    (IDC_SIZENESW, StandardCursor::ResizeNesw),
+    (IDC_SIZEALL, StandardCursor::Move),
+    (IDC_NO, StandardCursor::NotAllowed),
+];
+
+/// Shape of the cursor currently shown on the interactive desktop.
+pub fn current_cursor() -> windows::core::Result<CursorShape> {
+    unsafe {
+        let mut info = CURSORINFO {
+            cbSize: std::mem::size_of::<CURSORINFO>() as u32,
+            ..Default::default()
+        };
+        GetCursorInfo(&mut info)?;
+        if info.flags.0 & CURSOR_SHOWING.0 == 0 || info.hCursor.is_invalid() {
+            return Ok(CursorShape::Standard(StandardCursor::Hidden));
+        }
+
+        // System cursors are shared handles
+        for (id, standard) in SYSTEM_CURSORS {
+            if LoadCursorW(None, id).is_ok_and(|system| system == info.hCursor) {
+                return Ok(CursorShape::Standard(standard));
+            }
+        }
+        Ok(read_bitmap(info.hCursor)
+            .map(CursorShape::Bitmap)
+            .unwrap_or_default())
+    }
+}
+
+unsafe fn read_bitmap(cursor: HCURSOR) -> Option<CursorBitmap> {
+    let mut icon = ICONINFO::default();
+    GetIconInfo(HICON(cursor.0), &mut icon).ok()?;
+    let bitmap = if icon.hbmColor.is_invalid() {
+        None
+    } else {
+        color_bitmap(&icon)
+    };
+    // GetIconInfo hands over copies of both bitmaps
+    let _ = DeleteObject(icon.hbmColor.into());
+    let _ = DeleteObject(icon.hbmMask.into());
+    bitmap
+}
+
+unsafe fn color_bitmap(icon: &ICONINFO) -> Option<CursorBitmap> {
+    let mut info = BITMAP::default();
+    let read = GetObjectW(
+        icon.hbmColor.into(),
+        std::mem::size_of::<BITMAP>() as i32,
+        Some(&mut info as *mut BITMAP as *mut _),
+    );
+    if read == 0 {
+        return None;
+    }
+    let (width, height) = (info.bmWidth.max(0) as u32, info.bmHeight.max(0) as u32);
+    if width == 0 || height == 0 || width > MAX_CURSOR_DIMENSION || height > MAX_CURSOR_DIMENSION {
+        return None;
+    }
+
+    let mut pixels = dib_pixels(icon.hbmColor, width, height)?;
+    // Cursors without an alpha channel take it from the AND mask
+    if pixels.chunks_exact(4).all(|p| p[3] == 0) {
+        let mask = dib_pixels(icon.hbmMask, width, height)?;
+        for (pixel, masked) in pixels.chunks_exact_mut(4).zip(mask.chunks_exact(4)) {
+            pixel[3] = if masked[0] == 0 { 255 } else { 0 };
+        }
+    }
+    for pixel in pixels.chunks_exact_mut(4) {
+        pixel.swap(0, 2); // BGRA -> RGBA
+    }
+
+    Some(CursorBitmap {
+        width,
+        height,
+        hotspot_x: icon.xHotspot.min(width - 1),
+        hotspot_y: icon.yHotspot.min(height - 1),
+        rgba: Bytes::from(pixels),
+    })
+}
+
+/// Top-down 32bpp copy of the first `height` rows of `bitmap`.
+unsafe fn dib_pixels(bitmap: HBITMAP, width: u32, height: u32) -> Option<Vec<u8>> {
+    let mut pixels = vec![0u8; (width * height * 4) as usize];
+    let mut bmi = BITMAPINFO {
+        bmiHeader: BITMAPINFOHEADER {
+            biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+            biWidth: width as i32,
+            biHeight: -(height as i32), // top-down DIB
+            biPlanes: 1,
+            biBitCount: 32,
+            biCompression: BI_RGB.0 as u32,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let screen_dc = GetDC(None);
+    if screen_dc.is_invalid() {
+        return None;
+    }
+    let lines = GetDIBits(
+        screen_dc,
+        bitmap,
+        0,
+        height,
+        Some(pixels.as_mut_ptr() as *mut _),
+        &mut bmi,
+        DIB_RGB_COLORS,
+    );
+    let _ = ReleaseDC(None, screen_dc);
+    (lines == height as i32).then_some(pixels)
+}
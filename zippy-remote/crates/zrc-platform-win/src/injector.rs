@@ -145,6 +145,35 @@ impl WinInjector {
         }
     }
 
+    /// Inject relative mouse motion, in mickeys
+    ///
+    /// Windows applies the user's pointer speed and acceleration, which is
+    /// what relative input from games and pointer-lock viewers expects.
+    pub fn inject_mouse_move_relative(&mut self, dx: i32, dy: i32) -> Result<(), InputError> {
+        unsafe {
+            let inp = INPUT {
+                r#type: INPUT_MOUSE,
+                Anonymous: INPUT_0 {
+                    mi: MOUSEINPUT {
+                        dx,
+                        dy,
+                        mouseData: 0,
+                        dwFlags: MOUSEEVENTF_MOVE,
+                        time: 0,
+                        dwExtraInfo: 0,
+                    },
+                },
+            };
+
+            let sent = SendInput(&[inp], std::mem::size_of::<INPUT>() as i32);
+            if sent == 1 {
+                Ok(())
+            } else {
+                Err(InputError::SendFailed)
+            }
+        }
+    }
+
     /// Inject mouse button
     pub fn inject_mouse_button(&mut self, button: u32, down: bool) -> Result<(), InputError> {
         unsafe {
@@ -9,6 +9,7 @@ pub mod capture_gdi;
 pub mod capture_dxgi;
 pub mod capture_wgc;
 pub mod capturer;
+pub mod cursor;
 
 // Input injection
 pub mod input_sendinput;
@@ -103,7 +103,9 @@ impl MonitorManager {
             return windows::core::BOOL::from(true); // Continue enumeration
         }
 
-        let device_name = String::from_utf16_lossy(&info.szDevice);
+        let device_name = String::from_utf16_lossy(&info.szDevice)
+            .trim_end_matches('\0')
+            .to_string();
         let friendly_name = Self::get_friendly_name(&device_name);
 
         // Get DPI
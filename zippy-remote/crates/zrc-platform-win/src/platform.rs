@@ -1,11 +1,13 @@
 #![cfg(windows)]
 
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use async_trait::async_trait;
 use bytes::Bytes;
 use tokio::sync::Mutex;
 
-use zrc_core::platform::{HostPlatform, InputEvent};
+use zrc_core::cursor::CursorShape;
+use zrc_core::platform::{HostPlatform, InputEvent, MonitorBounds, MonitorInfo};
 
 use crate::capturer::WinCapturer;
 use crate::injector::WinInjector;
@@ -17,10 +19,15 @@ struct SendCapturer(WinCapturer);
 // and don't hold Windows handles across await points
 unsafe impl Send for SendCapturer {}
 
+/// How long one monitor enumeration is reused before listing again
+pub const MONITOR_REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+
 /// Windows platform implementation
 pub struct WinPlatform {
     capturer: Arc<Mutex<SendCapturer>>,
     injector: Arc<Mutex<WinInjector>>,
+    /// When the capturer last re-enumerated monitors
+    monitors_refreshed: std::sync::Mutex<Option<Instant>>,
 }
 
 // Safety: WinPlatform uses Mutex for interior mutability and all operations
@@ -37,6 +44,7 @@ impl WinPlatform {
         Ok(Self {
             capturer,
             injector,
+            monitors_refreshed: std::sync::Mutex::new(None),
         })
     }
 }
@@ -114,4 +122,72 @@ impl HostPlatform for WinPlatform {
         
         Ok(Bytes::new())
     }
+
+    async fn list_monitors(&self) -> Vec<MonitorInfo> {
+        let mut capturer = self.capturer.lock().await;
+        {
+            let mut refreshed = self.monitors_refreshed.lock().unwrap_or_else(|e| e.into_inner());
+            let stale = match *refreshed {
+                Some(at) => at.elapsed() >= MONITOR_REFRESH_INTERVAL,
+                None => true,
+            };
+            if stale {
+                // Keep the last known list if re-enumeration fails
+                let _ = capturer.0.handle_display_change();
+                *refreshed = Some(Instant::now());
+            }
+        }
+        monitor_infos(capturer.0.list_monitors())
+    }
+
+    async fn capture_monitor(&self, id: u32) -> anyhow::Result<Bytes> {
+        let mut capturer = self.capturer.lock().await;
+        let index = capturer.0.list_monitors()
+            .iter()
+            .position(|m| monitor_id(&m.device_name) == id)
+            .ok_or_else(|| anyhow::anyhow!("monitor {id} unavailable"))?;
+        capturer.0.select_monitor(index)
+            .map_err(|e| anyhow::anyhow!("monitor {id} unavailable: {e}"))?;
+        let frame = capturer.0.capture_frame()
+            .map_err(|e| anyhow::anyhow!("capture failed: {e}"))?;
+
+        Ok(Bytes::from(frame.bgra))
+    }
+
+    async fn cursor_shape(&self) -> anyhow::Result<CursorShape> {
+        Ok(crate::cursor::current_cursor()?)
+    }
+}
+
+/// Stable id for a monitor, derived from its device name (such as
+/// `\\.\DISPLAY2`) so it survives other monitors being plugged in or
+/// removed. FNV-1a, 32 bits.
+pub fn monitor_id(device_name: &str) -> u32 {
+    device_name.bytes().fold(0x811c_9dc5u32, |hash, byte| {
+        (hash ^ byte as u32).wrapping_mul(0x0100_0193)
+    })
+}
+
+/// Enumerated monitors as `HostPlatform::list_monitors` reports them, with
+/// ids from [`monitor_id`].
+pub fn monitor_infos(monitors: &[crate::monitor::MonitorInfo]) -> Vec<MonitorInfo> {
+    monitors
+        .iter()
+        .map(|m| MonitorInfo {
+            id: monitor_id(&m.device_name),
+            name: if m.friendly_name.is_empty() {
+                m.device_name.clone()
+            } else {
+                m.friendly_name.clone()
+            },
+            bounds: MonitorBounds {
+                x: m.bounds.left,
+                y: m.bounds.top,
+                width: (m.bounds.right - m.bounds.left).max(0) as u32,
+                height: (m.bounds.bottom - m.bounds.top).max(0) as u32,
+            },
+            scale: m.dpi as f64 / 96.0,
+            primary: m.is_primary,
+        })
+        .collect()
 }
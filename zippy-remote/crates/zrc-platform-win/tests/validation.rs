@@ -66,6 +66,21 @@ fn test_monitor_enumeration() {
     assert!(primary.is_some(), "Should have a primary monitor");
 }
 
+#[test]
+fn test_monitor_ids_follow_device_names() {
+    use zrc_platform_win::platform::{monitor_id, monitor_infos};
+
+    let manager = zrc_platform_win::monitor::MonitorManager::new().unwrap();
+    let monitors = manager.monitors();
+    let infos = monitor_infos(monitors);
+    for (monitor, info) in monitors.iter().zip(&infos) {
+        assert!(!monitor.device_name.ends_with('\0'), "Device name should not be NUL padded");
+        assert_eq!(info.id, monitor_id(&monitor.device_name));
+    }
+    // Ids do not depend on enumeration order
+    assert_ne!(monitor_id("\\\\.\\DISPLAY1"), monitor_id("\\\\.\\DISPLAY2"));
+}
+
 #[test]
 fn test_system_info_collection() {
     let info = zrc_platform_win::system_info::SystemInfo::collect();
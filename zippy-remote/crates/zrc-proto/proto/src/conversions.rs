@@ -400,6 +400,16 @@ impl InputEventV1 {
         }
     }
 
+    /// Create a relative mouse motion event; `dx`/`dy` are pixel deltas.
+    pub fn mouse_move_relative(dx: i32, dy: i32) -> Self {
+        Self {
+            event_type: InputEventTypeV1::MouseMoveRelative as i32,
+            mouse_x: dx,
+            mouse_y: dy,
+            ..Default::default()
+        }
+    }
+
     /// Create a mouse button down event.
     pub fn mouse_down(x: i32, y: i32, button: u32) -> Self {
         Self {
@@ -482,6 +492,11 @@ impl ControlMsgV1 {
             control_msg_v1::Payload::SessionControl(_) => ControlMsgTypeV1::SessionControl,
             control_msg_v1::Payload::Ping(_) => ControlMsgTypeV1::Ping,
             control_msg_v1::Payload::Pong(_) => ControlMsgTypeV1::Pong,
+            control_msg_v1::Payload::TicketRenewal(_) => ControlMsgTypeV1::TicketRenewal,
+            control_msg_v1::Payload::SessionClose(_) => ControlMsgTypeV1::SessionClose,
+            control_msg_v1::Payload::MonitorSelect(_) => ControlMsgTypeV1::MonitorSelect,
+            control_msg_v1::Payload::CursorShape(_) => ControlMsgTypeV1::CursorShape,
+            control_msg_v1::Payload::RecordingStatus(_) => ControlMsgTypeV1::RecordingStatus,
         };
 
         Self {
@@ -514,6 +529,21 @@ impl ControlMsgV1 {
         Self::new(sequence_number, control_msg_v1::Payload::Pong(PongV1 { t: ping_timestamp }))
     }
 
+    /// Create a session-close control message.
+    pub fn session_close(
+        sequence_number: u64,
+        reason: session_close_v1::ReasonV1,
+        detail: impl Into<String>,
+    ) -> Self {
+        Self::new(
+            sequence_number,
+            control_msg_v1::Payload::SessionClose(SessionCloseV1 {
+                reason: reason as i32,
+                detail: detail.into(),
+            }),
+        )
+    }
+
     /// Get the message type as an enum.
     pub fn msg_type_enum(&self) -> ControlMsgTypeV1 {
         ControlMsgTypeV1::try_from(self.msg_type).unwrap_or(ControlMsgTypeV1::Unspecified)
@@ -597,6 +627,10 @@ mod tests {
         assert_eq!(move_event.mouse_x, 100);
         assert_eq!(move_event.mouse_y, 200);
 
+        let relative = InputEventV1::mouse_move_relative(-3, 7);
+        assert_eq!(relative.event_type_enum(), InputEventTypeV1::MouseMoveRelative);
+        assert_eq!((relative.mouse_x, relative.mouse_y), (-3, 7));
+
         let key_event = InputEventV1::key_down(65, 0);
         assert_eq!(key_event.event_type_enum(), InputEventTypeV1::KeyDown);
         assert_eq!(key_event.key_code, 65);
@@ -1,25 +1,46 @@
 //! Admin API for relay management
 
+use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use axum::{
     extract::{Path, State},
     http::{HeaderMap, StatusCode},
-    routing::{delete, get, Router},
+    routing::{delete, get, post, Router},
     Json,
 };
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
 use tracing::{info, warn};
 
-use crate::allocation::{AllocationManager, AllocationInfo};
+use crate::allocation::{
+    Allocation, AllocationManager, DrainStatus, TerminateReason, DEFAULT_DRAIN_DEADLINE,
+};
+use crate::bandwidth::{BandwidthLimiter, Quota, QuotaUsage};
 use crate::metrics::AllocationMetrics;
 use crate::security::SecurityControls;
+use crate::token::TokenVerifier;
 
 #[derive(Clone)]
 pub struct AdminState {
     pub allocation_mgr: Arc<AllocationManager>,
     pub metrics: Arc<AllocationMetrics>,
     pub security: Arc<SecurityControls>,
+    pub quota: Arc<Quota>,
+    pub bandwidth_limiter: Arc<BandwidthLimiter>,
+    pub token_verifier: Arc<TokenVerifier>,
     pub admin_token: String,
+    /// Where drain requests are sent; draining is run by the relay server
+    pub drain_control: Option<mpsc::UnboundedSender<DrainCommand>>,
+}
+
+/// Drain request from the admin API
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrainCommand {
+    /// Stop taking allocations and force-close the rest after the deadline
+    Start(Duration),
+    /// Take allocations again
+    Resume,
 }
 
 /// Admin API server
@@ -32,6 +53,9 @@ impl AdminApi {
         allocation_mgr: Arc<AllocationManager>,
         metrics: Arc<AllocationMetrics>,
         security: Arc<SecurityControls>,
+        quota: Arc<Quota>,
+        bandwidth_limiter: Arc<BandwidthLimiter>,
+        token_verifier: Arc<TokenVerifier>,
         admin_token: String,
     ) -> Self {
         Self {
@@ -39,17 +63,30 @@ impl AdminApi {
                 allocation_mgr,
                 metrics,
                 security,
+                quota,
+                bandwidth_limiter,
+                token_verifier,
                 admin_token,
+                drain_control: None,
             },
         }
     }
 
+    /// Accept drain requests, forwarding them to `drain_control`
+    pub fn with_drain_control(mut self, drain_control: mpsc::UnboundedSender<DrainCommand>) -> Self {
+        self.state.drain_control = Some(drain_control);
+        self
+    }
+
     /// Create admin API router
     pub fn router(&self) -> Router {
         Router::new()
             .route("/admin/allocations", get(list_allocations))
             .route("/admin/allocations/:id", delete(terminate_allocation))
+            .route("/admin/allocations/:id/kill", post(terminate_allocation))
             .route("/admin/stats", get(get_stats))
+            .route("/admin/quotas", get(list_quotas))
+            .route("/admin/drain", get(get_drain_status).post(start_drain).delete(resume_drain))
             .with_state(self.state.clone())
     }
 }
@@ -74,7 +111,12 @@ async fn list_allocations(
         warn!("Admin API authentication failed");
         return Err(StatusCode::UNAUTHORIZED);
     }
-    let allocations = state.allocation_mgr.list();
+    let now = Instant::now();
+    let allocations: Vec<AllocationSummary> = state.allocation_mgr
+        .active()
+        .iter()
+        .map(|allocation| AllocationSummary::new(allocation, now))
+        .collect();
     let total = allocations.len();
     
     tracing::info!("Admin API: List allocations ({} active)", total);
@@ -85,7 +127,9 @@ async fn list_allocations(
     }))
 }
 
-/// Terminate an allocation
+/// Kill an allocation: close both forwarded connections and release the
+/// token's relay state. Quota usage is kept so a re-allocation with the same
+/// token can't reset it.
 async fn terminate_allocation(
     State(state): State<AdminState>,
     Path(id_hex): Path<String>,
@@ -106,11 +150,11 @@ async fn terminate_allocation(
     let mut allocation_id = [0u8; 16];
     allocation_id.copy_from_slice(&id_bytes[..16]);
 
-    // Terminate allocation
-    state.allocation_mgr.terminate(
-        &allocation_id,
-        crate::allocation::TerminateReason::ExplicitRelease,
-    );
+    if !state.allocation_mgr.terminate(&allocation_id, TerminateReason::ExplicitRelease) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    state.bandwidth_limiter.remove(&allocation_id);
+    state.token_verifier.clear_cache(&allocation_id);
 
     info!("Admin API: Terminated allocation {}", id_hex);
     
@@ -151,12 +195,125 @@ async fn get_stats(
     }))
 }
 
+/// Drain state and allocations still active
+async fn get_drain_status(
+    State(state): State<AdminState>,
+    headers: HeaderMap,
+) -> Result<Json<DrainStatus>, StatusCode> {
+    if !check_auth(&headers, &state.admin_token) {
+        warn!("Admin API authentication failed");
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+    Ok(Json(state.allocation_mgr.drain_status()))
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct StartDrainRequest {
+    /// Seconds before remaining allocations are force-closed
+    pub deadline_secs: Option<u64>,
+}
+
+/// Start draining: no new allocations, and whatever is still open when the
+/// deadline passes is force-closed
+async fn start_drain(
+    State(state): State<AdminState>,
+    headers: HeaderMap,
+    request: Option<Json<StartDrainRequest>>,
+) -> StatusCode {
+    if !check_auth(&headers, &state.admin_token) {
+        warn!("Admin API authentication failed");
+        return StatusCode::UNAUTHORIZED;
+    }
+    let Json(request) = request.unwrap_or_default();
+    let deadline = request.deadline_secs.map(Duration::from_secs).unwrap_or(DEFAULT_DRAIN_DEADLINE);
+
+    info!("Admin API: Drain requested, deadline {:?}", deadline);
+    send_drain_command(&state, DrainCommand::Start(deadline))
+}
+
+/// Stop draining and take allocations again
+async fn resume_drain(State(state): State<AdminState>, headers: HeaderMap) -> StatusCode {
+    if !check_auth(&headers, &state.admin_token) {
+        warn!("Admin API authentication failed");
+        return StatusCode::UNAUTHORIZED;
+    }
+    info!("Admin API: Drain cancelled");
+    send_drain_command(&state, DrainCommand::Resume)
+}
+
+fn send_drain_command(state: &AdminState, command: DrainCommand) -> StatusCode {
+    match &state.drain_control {
+        Some(drain_control) if drain_control.send(command).is_ok() => StatusCode::ACCEPTED,
+        _ => StatusCode::SERVICE_UNAVAILABLE,
+    }
+}
+
+/// Per-token quota usage, including overages
+async fn list_quotas(
+    State(state): State<AdminState>,
+    headers: HeaderMap,
+) -> Result<Json<QuotaReport>, StatusCode> {
+    if !check_auth(&headers, &state.admin_token) {
+        warn!("Admin API authentication failed");
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+    let tokens = state.quota.report();
+    let over_quota = tokens.iter().filter(|t| t.exceeded_count > 0).count();
+
+    tracing::info!("Admin API: List quotas ({} tokens, {} over quota)", tokens.len(), over_quota);
+
+    Ok(Json(QuotaReport {
+        window_secs: state.quota.window().as_secs(),
+        over_quota,
+        tokens,
+    }))
+}
+
 #[derive(Debug, Serialize)]
 pub struct ListAllocationsResponse {
-    pub allocations: Vec<AllocationInfo>,
+    pub allocations: Vec<AllocationSummary>,
     pub total: usize,
 }
 
+/// One active allocation as reported by `GET /admin/allocations`
+#[derive(Debug, Serialize)]
+pub struct AllocationSummary {
+    /// Hex allocation id; also identifies the token, and is the id to kill
+    pub allocation_id: String,
+    pub device_id: String,
+    pub peer_id: String,
+    /// Remote address of each side, once connected
+    pub device_addr: Option<SocketAddr>,
+    pub peer_addr: Option<SocketAddr>,
+    pub bytes_forwarded: u64,
+    pub age_secs: u64,
+    /// Seconds since the last forwarded byte or keepalive
+    pub idle_secs: u64,
+}
+
+impl AllocationSummary {
+    fn new(allocation: &Allocation, now: Instant) -> Self {
+        let last_activity = *allocation.last_activity.lock().unwrap();
+        Self {
+            allocation_id: hex::encode(allocation.id),
+            device_id: hex::encode(allocation.device_id),
+            peer_id: hex::encode(allocation.peer_id),
+            device_addr: allocation.device_addr,
+            peer_addr: allocation.peer_addr,
+            bytes_forwarded: allocation.bytes_transferred.load(std::sync::atomic::Ordering::Relaxed),
+            age_secs: now.saturating_duration_since(allocation.created_at).as_secs(),
+            idle_secs: now.saturating_duration_since(last_activity).as_secs(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct QuotaReport {
+    pub window_secs: u64,
+    pub over_quota: usize,
+    pub tokens: Vec<QuotaUsage>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct TerminateResponse {
     pub success: bool,
@@ -179,3 +336,102 @@ pub struct BandwidthStats {
     pub peak_bps: u64,
     pub average_bps: u64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::allocation::{AllocationConfig, RecordingConnection};
+    use crate::token::RelayTokenV1;
+    use std::time::Duration;
+
+    fn admin_state() -> AdminState {
+        AdminState {
+            allocation_mgr: Arc::new(AllocationManager::new(AllocationConfig::default())),
+            metrics: Arc::new(AllocationMetrics::new().unwrap()),
+            security: Arc::new(SecurityControls::new()),
+            quota: Arc::new(Quota::new(Duration::from_secs(3600))),
+            bandwidth_limiter: Arc::new(BandwidthLimiter::new(None)),
+            token_verifier: Arc::new(TokenVerifier::new()),
+            admin_token: "secret".to_string(),
+            drain_control: None,
+        }
+    }
+
+    fn auth() -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("authorization", "Bearer secret".parse().unwrap());
+        headers
+    }
+
+    fn allocate(state: &AdminState, n: u8) -> [u8; 16] {
+        let mut allocation_id = [0u8; 16];
+        allocation_id[0] = n;
+        let token = RelayTokenV1 {
+            relay_id: [0u8; 16],
+            allocation_id,
+            device_id: [2u8; 32],
+            peer_id: [3u8; 32],
+            expires_at: u64::MAX,
+            bandwidth_limit: 1024 * 1024,
+            quota_bytes: 1024 * 1024,
+            signature: [0u8; 64],
+        };
+        let relay_addr = "127.0.0.1:4433".parse().unwrap();
+        let id = state.allocation_mgr.create(&token, relay_addr).unwrap().id;
+        state.allocation_mgr.associate(&id, Arc::new(RecordingConnection::default()), "10.0.0.1:5000".parse().unwrap(), true).unwrap();
+        state.allocation_mgr.associate(&id, Arc::new(RecordingConnection::default()), "10.0.0.2:5000".parse().unwrap(), false).unwrap();
+        state.allocation_mgr.record_transfer(&id, 500).unwrap();
+        id
+    }
+
+    #[tokio::test]
+    async fn test_list_and_kill_allocations() {
+        let state = admin_state();
+        let first = allocate(&state, 1);
+        let second = allocate(&state, 2);
+
+        assert_eq!(
+            list_allocations(State(state.clone()), HeaderMap::new()).await.unwrap_err(),
+            StatusCode::UNAUTHORIZED
+        );
+
+        let Json(listed) = list_allocations(State(state.clone()), auth()).await.unwrap();
+        assert_eq!(listed.total, 2);
+        let entry = listed.allocations.iter()
+            .find(|a| a.allocation_id == hex::encode(first))
+            .unwrap();
+        assert_eq!(entry.device_addr, Some("10.0.0.1:5000".parse().unwrap()));
+        assert_eq!(entry.peer_addr, Some("10.0.0.2:5000".parse().unwrap()));
+        assert_eq!(entry.bytes_forwarded, 500);
+
+        let kill = |id: [u8; 16], headers| terminate_allocation(State(state.clone()), Path(hex::encode(id)), headers);
+        assert_eq!(kill(first, HeaderMap::new()).await.unwrap_err(), StatusCode::UNAUTHORIZED);
+        assert!(kill(first, auth()).await.unwrap().success);
+        assert_eq!(kill(first, auth()).await.unwrap_err(), StatusCode::NOT_FOUND);
+
+        let Json(listed) = list_allocations(State(state.clone()), auth()).await.unwrap();
+        assert_eq!(listed.total, 1);
+        assert_eq!(listed.allocations[0].allocation_id, hex::encode(second));
+        assert!(state.allocation_mgr.get(&first).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_drain_requests_reach_the_server() {
+        let (drain_tx, mut drain_rx) = mpsc::unbounded_channel();
+        let state = AdminState { drain_control: Some(drain_tx), ..admin_state() };
+
+        assert_eq!(start_drain(State(state.clone()), HeaderMap::new(), None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(start_drain(State(state.clone()), auth(), None).await, StatusCode::ACCEPTED);
+        assert_eq!(drain_rx.recv().await, Some(DrainCommand::Start(DEFAULT_DRAIN_DEADLINE)));
+
+        let request = Json(StartDrainRequest { deadline_secs: Some(5) });
+        assert_eq!(start_drain(State(state.clone()), auth(), Some(request)).await, StatusCode::ACCEPTED);
+        assert_eq!(drain_rx.recv().await, Some(DrainCommand::Start(Duration::from_secs(5))));
+
+        assert_eq!(resume_drain(State(state.clone()), auth()).await, StatusCode::ACCEPTED);
+        assert_eq!(drain_rx.recv().await, Some(DrainCommand::Resume));
+
+        // Without a server to run it there is nothing to drain
+        assert_eq!(start_drain(State(admin_state()), auth(), None).await, StatusCode::SERVICE_UNAVAILABLE);
+    }
+}
@@ -1,20 +1,32 @@
 //! Allocation management for relay sessions
 
 use std::sync::{Arc, Mutex};
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::time::{Instant, Duration, SystemTime, UNIX_EPOCH};
 use std::net::SocketAddr;
 
 use dashmap::DashMap;
 use thiserror::Error;
+use tokio::sync::Notify;
 
 use crate::token::RelayTokenV1;
 
 /// Allocation identifier
 pub type AllocationId = [u8; 16];
 
-/// Connection handle (placeholder - will be QUIC connection)
-pub type ConnectionHandle = Arc<()>;
+/// A forwarded connection the relay can close when it ends an allocation.
+pub trait RelayConnection: Send + Sync {
+    fn close(&self, reason: TerminateReason);
+}
+
+impl RelayConnection for quinn::Connection {
+    fn close(&self, reason: TerminateReason) {
+        quinn::Connection::close(self, reason.error_code().into(), reason.as_str().as_bytes());
+    }
+}
+
+/// One side of an allocation's forwarded traffic
+pub type ConnectionHandle = Arc<dyn RelayConnection>;
 
 /// Allocation state
 pub struct Allocation {
@@ -25,10 +37,14 @@ pub struct Allocation {
     pub expires_at: Instant,
     pub bandwidth_limit: u32,
     pub quota_bytes: u64,
+    /// Expiry of the token that authorized this allocation (unix seconds)
+    pub token_expires_at: u64,
     pub bytes_transferred: AtomicU64,
     pub last_activity: Arc<Mutex<Instant>>,
     pub device_conn: Option<ConnectionHandle>,
     pub peer_conn: Option<ConnectionHandle>,
+    pub device_addr: Option<SocketAddr>,
+    pub peer_addr: Option<SocketAddr>,
 }
 
 impl Clone for Allocation {
@@ -41,10 +57,22 @@ impl Clone for Allocation {
             expires_at: self.expires_at,
             bandwidth_limit: self.bandwidth_limit,
             quota_bytes: self.quota_bytes,
+            token_expires_at: self.token_expires_at,
             bytes_transferred: AtomicU64::new(self.bytes_transferred.load(Ordering::Relaxed)),
             last_activity: Arc::new(Mutex::new(*self.last_activity.lock().unwrap())),
             device_conn: self.device_conn.clone(),
             peer_conn: self.peer_conn.clone(),
+            device_addr: self.device_addr,
+            peer_addr: self.peer_addr,
+        }
+    }
+}
+
+impl Allocation {
+    /// Close both forwarded connections, if associated
+    pub fn close_connections(&self, reason: TerminateReason) {
+        for conn in [&self.device_conn, &self.peer_conn].into_iter().flatten() {
+            conn.close(reason);
         }
     }
 }
@@ -86,15 +114,46 @@ pub struct AllocationInfo {
 }
 
 /// Termination reason
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TerminateReason {
     Expired,
     Disconnected,
     QuotaExceeded,
     ExplicitRelease,
+    /// Still open when a drain deadline passed
+    Drained,
+    /// No traffic or keepalives within the idle timeout
+    IdleTimeout,
     Error,
 }
 
+impl TerminateReason {
+    /// QUIC application error code sent when closing the connections
+    pub fn error_code(self) -> u32 {
+        match self {
+            TerminateReason::Expired => 1,
+            TerminateReason::Disconnected => 2,
+            TerminateReason::QuotaExceeded => 3,
+            TerminateReason::ExplicitRelease => 4,
+            TerminateReason::Drained => 5,
+            TerminateReason::IdleTimeout => 6,
+            TerminateReason::Error => 7,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TerminateReason::Expired => "expired",
+            TerminateReason::Disconnected => "disconnected",
+            TerminateReason::QuotaExceeded => "quota exceeded",
+            TerminateReason::ExplicitRelease => "released",
+            TerminateReason::Drained => "drained",
+            TerminateReason::IdleTimeout => "idle timeout",
+            TerminateReason::Error => "error",
+        }
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum AllocationError {
     #[error("Maximum allocations exceeded")]
@@ -105,12 +164,50 @@ pub enum AllocationError {
     QuotaExceeded,
     #[error("Invalid token")]
     InvalidToken,
+    #[error("Relay is draining")]
+    Draining,
+}
+
+/// Drain deadline used on shutdown and when the admin API gives none
+pub const DEFAULT_DRAIN_DEADLINE: Duration = Duration::from_secs(30);
+
+/// Drain state for the admin API
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DrainStatus {
+    pub draining: bool,
+    pub active_allocations: usize,
+    /// Seconds until remaining allocations are force-closed
+    pub deadline_remaining_secs: Option<u64>,
+}
+
+/// Outcome of draining
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DrainReport {
+    /// Allocations that closed on their own before the deadline
+    pub completed: usize,
+    /// Allocations force-closed at the deadline
+    pub forced: usize,
+}
+
+/// An allocation removed by `expire_stale`
+#[derive(Debug, Clone)]
+pub struct ReapedAllocation {
+    pub id: AllocationId,
+    pub reason: TerminateReason,
+    /// Time since the last forwarded byte or keepalive
+    pub idle_for: Duration,
+    /// Expiry of the allocation's token, for marking it spent
+    pub token_expires_at: u64,
 }
 
 /// Allocation manager
 pub struct AllocationManager {
     allocations: DashMap<AllocationId, Arc<Allocation>>,
     config: AllocationConfig,
+    draining: AtomicBool,
+    drain_deadline: Mutex<Option<Instant>>,
+    /// Notified whenever allocations are removed
+    removed: Notify,
 }
 
 impl AllocationManager {
@@ -118,6 +215,73 @@ impl AllocationManager {
         Self {
             allocations: DashMap::new(),
             config,
+            draining: AtomicBool::new(false),
+            drain_deadline: Mutex::new(None),
+            removed: Notify::new(),
+        }
+    }
+
+    /// Stop accepting new allocations; existing ones keep running until
+    /// they close or `deadline` passes (see `wait_drained`).
+    pub fn start_draining(&self, deadline: Duration) {
+        *self.drain_deadline.lock().unwrap() = Some(Instant::now() + deadline);
+        self.draining.store(true, Ordering::SeqCst);
+    }
+
+    /// Accept new allocations again; a pending `wait_drained` returns
+    /// without force-closing anything.
+    pub fn stop_draining(&self) {
+        self.draining.store(false, Ordering::SeqCst);
+        *self.drain_deadline.lock().unwrap() = None;
+        self.removed.notify_waiters();
+    }
+
+    pub fn is_draining(&self) -> bool {
+        self.draining.load(Ordering::SeqCst)
+    }
+
+    pub fn drain_status(&self) -> DrainStatus {
+        let deadline = *self.drain_deadline.lock().unwrap();
+        DrainStatus {
+            draining: self.is_draining(),
+            active_allocations: self.count(),
+            deadline_remaining_secs: deadline
+                .map(|d| d.saturating_duration_since(Instant::now()).as_secs()),
+        }
+    }
+
+    /// Wait until every allocation has closed or the drain deadline passes,
+    /// then force-close whatever is left, closing both of its connections.
+    /// Returns immediately if not draining, and early if draining is stopped.
+    pub async fn wait_drained(&self) -> DrainReport {
+        let Some(deadline) = *self.drain_deadline.lock().unwrap() else {
+            return DrainReport { completed: 0, forced: 0 };
+        };
+        let initial = self.count();
+        let deadline = tokio::time::Instant::from_std(deadline);
+
+        loop {
+            // Register before checking so a removal in between isn't missed
+            let removed = self.removed.notified();
+            if !self.is_draining() {
+                return DrainReport { completed: initial.saturating_sub(self.count()), forced: 0 };
+            }
+            if self.allocations.is_empty() {
+                break;
+            }
+            tokio::select! {
+                _ = removed => {}
+                _ = tokio::time::sleep_until(deadline) => break,
+            }
+        }
+
+        let remaining: Vec<AllocationId> = self.allocations.iter().map(|e| *e.key()).collect();
+        for id in &remaining {
+            self.terminate(id, TerminateReason::Drained);
+        }
+        DrainReport {
+            completed: initial.saturating_sub(remaining.len()),
+            forced: remaining.len(),
         }
     }
 
@@ -127,6 +291,10 @@ impl AllocationManager {
         token: &RelayTokenV1,
         relay_addr: SocketAddr,
     ) -> Result<AllocationInfo, AllocationError> {
+        if self.is_draining() {
+            return Err(AllocationError::Draining);
+        }
+
         // Check max allocations
         if self.allocations.len() >= self.config.max_allocations {
             return Err(AllocationError::MaxAllocations);
@@ -143,10 +311,13 @@ impl AllocationManager {
             expires_at,
             bandwidth_limit: token.bandwidth_limit,
             quota_bytes: token.quota_bytes,
+            token_expires_at: token.expires_at,
             bytes_transferred: AtomicU64::new(0),
             last_activity: Arc::new(Mutex::new(now)),
             device_conn: None,
             peer_conn: None,
+            device_addr: None,
+            peer_addr: None,
         });
 
         self.allocations.insert(token.allocation_id, allocation.clone());
@@ -178,17 +349,19 @@ impl AllocationManager {
         &self,
         id: &AllocationId,
         conn: ConnectionHandle,
+        remote_addr: SocketAddr,
         is_device: bool,
     ) -> Result<(), AllocationError> {
-        let allocation = self.allocations.get(id)
-            .ok_or(AllocationError::NotFound)?;
+        let allocation = self.get(id).ok_or(AllocationError::NotFound)?;
 
         // Update connection - we need to clone and replace since Arc is immutable
         let mut new_allocation = allocation.as_ref().clone();
         if is_device {
             new_allocation.device_conn = Some(conn);
+            new_allocation.device_addr = Some(remote_addr);
         } else {
             new_allocation.peer_conn = Some(conn);
+            new_allocation.peer_addr = Some(remote_addr);
         }
         *new_allocation.last_activity.lock().unwrap() = Instant::now();
         
@@ -205,8 +378,7 @@ impl AllocationManager {
         id: &AllocationId,
         bytes: u64,
     ) -> Result<bool, AllocationError> {
-        let allocation = self.allocations.get(id)
-            .ok_or(AllocationError::NotFound)?;
+        let allocation = self.get(id).ok_or(AllocationError::NotFound)?;
 
         let previous = allocation.bytes_transferred.load(Ordering::Relaxed);
         let transferred = previous + bytes;
@@ -230,29 +402,60 @@ impl AllocationManager {
         Ok(warning_triggered)
     }
 
-    /// Terminate allocation
-    pub fn terminate(&self, id: &AllocationId, _reason: TerminateReason) {
-        self.allocations.remove(id);
+    /// Record a keepalive, so an allocation that is alive but not
+    /// forwarding anything isn't reaped as idle
+    pub fn record_keepalive(&self, id: &AllocationId) -> Result<(), AllocationError> {
+        let allocation = self.allocations.get(id)
+            .ok_or(AllocationError::NotFound)?;
+        *allocation.last_activity.lock().unwrap() = Instant::now();
+        Ok(())
     }
 
-    /// Run expiration check
-    pub fn expire_stale(&self) {
+    /// Terminate allocation, closing both of its connections.
+    /// Returns false if there was no such allocation.
+    pub fn terminate(&self, id: &AllocationId, reason: TerminateReason) -> bool {
+        let Some((_, allocation)) = self.allocations.remove(id) else {
+            return false;
+        };
+        allocation.close_connections(reason);
+        self.removed.notify_waiters();
+        true
+    }
+
+    /// Remove allocations past their lifetime, and ones that have seen no
+    /// forwarded bytes or keepalives within the idle timeout
+    pub fn expire_stale(&self) -> Vec<ReapedAllocation> {
         let now = Instant::now();
         let idle_timeout = self.config.idle_timeout;
-
-        self.allocations.retain(|_id, allocation| {
-            let expired = allocation.expires_at <= now;
-            let last_activity = *allocation.last_activity.lock().unwrap();
-            let idle = last_activity.elapsed() > idle_timeout;
-
-            if expired {
-                false
-            } else if idle && (allocation.device_conn.is_none() || allocation.peer_conn.is_none()) {
-                false
+        let mut reaped = Vec::new();
+
+        self.allocations.retain(|id, allocation| {
+            let idle_for = now.saturating_duration_since(*allocation.last_activity.lock().unwrap());
+            let reason = if allocation.expires_at <= now {
+                TerminateReason::Expired
+            } else if idle_for > idle_timeout {
+                TerminateReason::IdleTimeout
             } else {
-                true
-            }
+                return true;
+            };
+            allocation.close_connections(reason);
+            reaped.push(ReapedAllocation {
+                id: *id,
+                reason,
+                idle_for,
+                token_expires_at: allocation.token_expires_at,
+            });
+            false
         });
+        if !reaped.is_empty() {
+            self.removed.notify_waiters();
+        }
+        reaped
+    }
+
+    /// Snapshot of all active allocations
+    pub fn active(&self) -> Vec<Arc<Allocation>> {
+        self.allocations.iter().map(|entry| entry.value().clone()).collect()
     }
 
     /// Get all allocations (for admin)
@@ -286,6 +489,20 @@ impl AllocationManager {
     }
 }
 
+/// Test connection that records how it was closed
+#[cfg(test)]
+#[derive(Default)]
+pub(crate) struct RecordingConnection {
+    pub closed: std::sync::Mutex<Option<TerminateReason>>,
+}
+
+#[cfg(test)]
+impl RelayConnection for RecordingConnection {
+    fn close(&self, reason: TerminateReason) {
+        *self.closed.lock().unwrap() = Some(reason);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -328,8 +545,10 @@ mod tests {
 
     #[test]
     fn test_allocation_max_limit() {
-        let mut config = AllocationConfig::default();
-        config.max_allocations = 2;
+        let config = AllocationConfig {
+            max_allocations: 2,
+            ..Default::default()
+        };
         let mgr = AllocationManager::new(config);
         let relay_addr = "127.0.0.1:4433".parse().unwrap();
         
@@ -382,10 +601,28 @@ mod tests {
         assert_eq!(mgr.count(), 0); // Allocation should be terminated
     }
 
+    #[test]
+    fn test_terminate_closes_both_connections() {
+        let mgr = AllocationManager::new(AllocationConfig::default());
+        let token = create_test_token();
+        let id = mgr.create(&token, "127.0.0.1:4433".parse().unwrap()).unwrap().id;
+        let device = Arc::new(RecordingConnection::default());
+        let peer = Arc::new(RecordingConnection::default());
+        mgr.associate(&id, device.clone(), "10.0.0.1:5000".parse().unwrap(), true).unwrap();
+        mgr.associate(&id, peer.clone(), "10.0.0.2:5000".parse().unwrap(), false).unwrap();
+
+        assert!(mgr.terminate(&id, TerminateReason::ExplicitRelease));
+        assert_eq!(*device.closed.lock().unwrap(), Some(TerminateReason::ExplicitRelease));
+        assert_eq!(*peer.closed.lock().unwrap(), Some(TerminateReason::ExplicitRelease));
+        assert!(!mgr.terminate(&id, TerminateReason::ExplicitRelease));
+    }
+
     #[test]
     fn test_allocation_expire_stale() {
-        let mut config = AllocationConfig::default();
-        config.idle_timeout = Duration::from_secs(1);
+        let config = AllocationConfig {
+            idle_timeout: Duration::from_secs(1),
+            ..Default::default()
+        };
         let mgr = AllocationManager::new(config);
         let token = create_test_token();
         let relay_addr = "127.0.0.1:4433".parse().unwrap();
@@ -401,6 +638,35 @@ mod tests {
         assert_eq!(mgr.count(), 0);
     }
 
+    #[test]
+    fn test_reaper_spares_keepalive_allocation() {
+        let config = AllocationConfig {
+            idle_timeout: Duration::from_millis(300),
+            ..Default::default()
+        };
+        let mgr = AllocationManager::new(config);
+        let relay_addr = "127.0.0.1:4433".parse().unwrap();
+
+        let mut silent = create_test_token();
+        silent.allocation_id[0] = 1;
+        let silent = mgr.create(&silent, relay_addr).unwrap().id;
+        let mut alive = create_test_token();
+        alive.allocation_id[0] = 2;
+        let alive = mgr.create(&alive, relay_addr).unwrap().id;
+
+        std::thread::sleep(Duration::from_millis(200));
+        mgr.record_keepalive(&alive).unwrap();
+        std::thread::sleep(Duration::from_millis(200));
+
+        let reaped = mgr.expire_stale();
+        assert_eq!(reaped.len(), 1);
+        assert_eq!(reaped[0].id, silent);
+        assert!(matches!(reaped[0].reason, TerminateReason::IdleTimeout));
+        assert!(reaped[0].idle_for >= Duration::from_millis(300));
+        assert!(mgr.get(&alive).is_some());
+        assert!(mgr.record_keepalive(&silent).is_err());
+    }
+
     #[cfg(test)]
     mod proptests {
         use super::*;
@@ -491,9 +757,11 @@ mod tests {
                 num_allocations in 1usize..=100usize,
                 allocation_timeout_secs in 1u64..=3600u64,
             )| {
-                let mut config = AllocationConfig::default();
-                config.allocation_timeout = Duration::from_secs(allocation_timeout_secs);
-                config.max_allocations = 1000;
+                let config = AllocationConfig {
+                    allocation_timeout: Duration::from_secs(allocation_timeout_secs),
+                    max_allocations: 1000,
+                    ..Default::default()
+                };
                 
                 let mgr = AllocationManager::new(config);
                 let relay_addr = "127.0.0.1:4433".parse().unwrap();
@@ -589,4 +857,93 @@ mod tests {
             });
         }
     }
+
+    #[tokio::test]
+    async fn test_drain_rejects_new_and_waits_for_active() {
+        let mgr = Arc::new(AllocationManager::new(AllocationConfig::default()));
+        let relay_addr: SocketAddr = "127.0.0.1:4433".parse().unwrap();
+
+        let mut ids = Vec::new();
+        for i in 1..=3u8 {
+            let mut token = create_test_token();
+            token.allocation_id[0] = i;
+            ids.push(mgr.create(&token, relay_addr).unwrap().id);
+        }
+
+        mgr.start_draining(Duration::from_secs(30));
+        let mut late = create_test_token();
+        late.allocation_id[0] = 9;
+        assert!(matches!(mgr.create(&late, relay_addr), Err(AllocationError::Draining)));
+        assert_eq!(mgr.drain_status().active_allocations, 3);
+
+        // Sessions finish one after another, well before the deadline
+        let closer = mgr.clone();
+        tokio::spawn(async move {
+            for id in ids {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                closer.terminate(&id, TerminateReason::Disconnected);
+            }
+        });
+
+        let report = tokio::time::timeout(Duration::from_secs(5), mgr.wait_drained())
+            .await
+            .expect("drain finished before deadline");
+        assert_eq!(report, DrainReport { completed: 3, forced: 0 });
+        assert_eq!(mgr.count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_drain_force_closes_at_deadline() {
+        let mgr = Arc::new(AllocationManager::new(AllocationConfig::default()));
+        let relay_addr: SocketAddr = "127.0.0.1:4433".parse().unwrap();
+
+        let mut short = create_test_token();
+        short.allocation_id[0] = 1;
+        let short = mgr.create(&short, relay_addr).unwrap().id;
+        let mut long_lived = create_test_token();
+        long_lived.allocation_id[0] = 2;
+        mgr.create(&long_lived, relay_addr).unwrap();
+
+        mgr.start_draining(Duration::from_millis(100));
+        let closer = mgr.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            closer.terminate(&short, TerminateReason::Disconnected);
+        });
+
+        let conn = Arc::new(RecordingConnection::default());
+        mgr.associate(&long_lived.allocation_id, conn.clone(), "10.0.0.1:5000".parse().unwrap(), true).unwrap();
+
+        let started = Instant::now();
+        let report = mgr.wait_drained().await;
+        assert!(started.elapsed() >= Duration::from_millis(90));
+        assert_eq!(report, DrainReport { completed: 1, forced: 1 });
+        assert_eq!(mgr.count(), 0);
+        assert_eq!(*conn.closed.lock().unwrap(), Some(TerminateReason::Drained));
+
+        // Still rejecting until explicitly resumed
+        assert!(mgr.create(&long_lived, relay_addr).is_err());
+        mgr.stop_draining();
+        assert!(mgr.create(&long_lived, relay_addr).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_stopped_drain_closes_nothing() {
+        let mgr = Arc::new(AllocationManager::new(AllocationConfig::default()));
+        let relay_addr: SocketAddr = "127.0.0.1:4433".parse().unwrap();
+        let id = mgr.create(&create_test_token(), relay_addr).unwrap().id;
+
+        mgr.start_draining(Duration::from_secs(30));
+        let resumer = mgr.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            resumer.stop_draining();
+        });
+
+        let report = tokio::time::timeout(Duration::from_secs(5), mgr.wait_drained())
+            .await
+            .expect("stopping the drain wakes the waiter");
+        assert_eq!(report, DrainReport { completed: 0, forced: 0 });
+        assert!(mgr.get(&id).is_some());
+    }
 }
@@ -1,11 +1,16 @@
 //! Bandwidth limiting and quota management
 
+use std::collections::VecDeque;
 use std::sync::{Arc, Mutex};
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::time::Instant;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use dashmap::DashMap;
+use serde::Serialize;
 use crate::allocation::AllocationId;
 
+/// Default sliding window for per-token byte quotas
+pub const DEFAULT_QUOTA_WINDOW: Duration = Duration::from_secs(3600);
+
 /// Token bucket for rate limiting
 struct TokenBucket {
     capacity: u64,
@@ -167,6 +172,177 @@ impl BandwidthLimiter {
     }
 }
 
+/// Byte usage of one token within the window
+struct WindowUsage {
+    limit: u64,
+    /// (time, bytes) samples, oldest first; close samples are merged
+    samples: VecDeque<(Instant, u64)>,
+    used: u64,
+    overage_bytes: u64,
+    exceeded_count: u64,
+    last_exceeded_at: Option<u64>,
+}
+
+impl WindowUsage {
+    fn roll(&mut self, now: Instant, window: Duration) {
+        while let Some(&(at, bytes)) = self.samples.front() {
+            if now.saturating_duration_since(at) < window {
+                break;
+            }
+            self.used -= bytes;
+            self.samples.pop_front();
+        }
+    }
+}
+
+/// Quota usage report for one token
+#[derive(Debug, Clone, Serialize)]
+pub struct QuotaUsage {
+    #[serde(serialize_with = "serialize_hex")]
+    pub token_id: AllocationId,
+    /// Bytes allowed per window
+    pub limit: u64,
+    /// Bytes forwarded within the current window
+    pub used: u64,
+    /// Bytes refused because the quota was exhausted
+    pub overage_bytes: u64,
+    /// Times the quota was hit
+    pub exceeded_count: u64,
+    /// Unix time of the last refusal
+    pub last_exceeded_at: Option<u64>,
+}
+
+fn serialize_hex<S: serde::Serializer>(id: &AllocationId, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_str(&hex::encode(id))
+}
+
+/// Per-token byte quota over a sliding window.
+///
+/// Usage is keyed by the token's allocation id and outlives the allocation,
+/// so a client cannot reset its quota by re-allocating with the same token;
+/// bytes count against it until they are `window` old.
+pub struct Quota {
+    window: Duration,
+    usage: DashMap<AllocationId, Mutex<WindowUsage>>,
+}
+
+impl Quota {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            usage: DashMap::new(),
+        }
+    }
+
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// Account `bytes` against `token_id`, whose quota is `limit` bytes per
+    /// window. Returns false, recording the overage, if they don't fit.
+    pub fn try_consume(&self, token_id: &AllocationId, limit: u64, bytes: u64) -> bool {
+        self.try_consume_at(token_id, limit, bytes, Instant::now())
+    }
+
+    /// Like `try_consume` with an explicit clock
+    pub fn try_consume_at(&self, token_id: &AllocationId, limit: u64, bytes: u64, now: Instant) -> bool {
+        let entry = self.usage.entry(*token_id).or_insert_with(|| {
+            Mutex::new(WindowUsage {
+                limit,
+                samples: VecDeque::new(),
+                used: 0,
+                overage_bytes: 0,
+                exceeded_count: 0,
+                last_exceeded_at: None,
+            })
+        });
+        let mut usage = entry.lock().unwrap();
+        usage.limit = limit;
+        usage.roll(now, self.window);
+
+        if usage.used.saturating_add(bytes) > limit {
+            usage.overage_bytes += bytes;
+            usage.exceeded_count += 1;
+            usage.last_exceeded_at = Some(
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs(),
+            );
+            return false;
+        }
+
+        usage.used += bytes;
+        // Keep at most ~64 samples per window
+        let merge_within = self.window / 64;
+        match usage.samples.back_mut() {
+            Some((at, total)) if now.saturating_duration_since(*at) < merge_within => *total += bytes,
+            _ => usage.samples.push_back((now, bytes)),
+        }
+        true
+    }
+
+    /// Current usage for a token
+    pub fn usage(&self, token_id: &AllocationId) -> Option<QuotaUsage> {
+        self.usage.get(token_id).map(|entry| report(entry.key(), &entry.value().lock().unwrap()))
+    }
+
+    /// Usage of all tracked tokens
+    pub fn report(&self) -> Vec<QuotaUsage> {
+        self.usage
+            .iter()
+            .map(|entry| report(entry.key(), &entry.value().lock().unwrap()))
+            .collect()
+    }
+
+    /// Forget tokens with nothing left in their window
+    pub fn prune(&self) {
+        let now = Instant::now();
+        self.usage.retain(|_, usage| {
+            let usage = usage.get_mut().unwrap();
+            usage.roll(now, self.window);
+            !usage.samples.is_empty()
+        });
+    }
+}
+
+fn report(token_id: &AllocationId, usage: &WindowUsage) -> QuotaUsage {
+    QuotaUsage {
+        token_id: *token_id,
+        limit: usage.limit,
+        used: usage.used,
+        overage_bytes: usage.overage_bytes,
+        exceeded_count: usage.exceeded_count,
+        last_exceeded_at: usage.last_exceeded_at,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quota_rolls_with_window() {
+        let quota = Quota::new(Duration::from_secs(60));
+        let token = [7u8; 16];
+        let start = Instant::now();
+
+        assert!(quota.try_consume_at(&token, 1000, 600, start));
+        assert!(quota.try_consume_at(&token, 1000, 400, start + Duration::from_secs(30)));
+        assert!(!quota.try_consume_at(&token, 1000, 1, start + Duration::from_secs(31)));
+
+        let usage = quota.usage(&token).unwrap();
+        assert_eq!(usage.used, 1000);
+        assert_eq!(usage.overage_bytes, 1);
+        assert_eq!(usage.exceeded_count, 1);
+
+        // The first 600 bytes leave the window; the later 400 still count
+        assert!(quota.try_consume_at(&token, 1000, 600, start + Duration::from_secs(60)));
+        assert!(!quota.try_consume_at(&token, 1000, 1, start + Duration::from_secs(61)));
+        assert!(quota.try_consume_at(&token, 1000, 400, start + Duration::from_secs(90)));
+    }
+}
+
 #[cfg(test)]
 mod proptests {
     use super::*;
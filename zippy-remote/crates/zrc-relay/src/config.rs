@@ -7,6 +7,7 @@ use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::allocation::AllocationConfig;
+use crate::token::RelayAudience;
 
 #[derive(Debug, Error)]
 pub enum ConfigError {
@@ -33,6 +34,13 @@ pub struct ServerConfig {
     pub admin_addr: Option<SocketAddr>,
     pub admin_token: Option<String>,
     pub global_bandwidth_limit: Option<u64>,
+    /// Sliding window over which each token's byte quota applies
+    pub quota_window_secs: u64,
+    /// This relay's id (hex), checked against the `relay_id` token claim
+    pub relay_id: Option<String>,
+    /// Other relay ids (hex) whose tokens are redeemable here, for clustered
+    /// deployments; `"*"` accepts any relay
+    pub accepted_relay_ids: Vec<String>,
     // High Availability
     pub instance_id: Option<String>,
     pub region: Option<String>,
@@ -56,6 +64,9 @@ impl Default for ServerConfig {
             admin_addr: None,
             admin_token: None,
             global_bandwidth_limit: None,
+            quota_window_secs: 3600,
+            relay_id: None,
+            accepted_relay_ids: Vec::new(),
             instance_id: None,
             region: None,
             redis_url: None,
@@ -85,6 +96,10 @@ impl ServerConfig {
             config.quic_key_path = PathBuf::from(path);
         }
 
+        if let Ok(relay_id) = std::env::var("ZRC_RELAY_ID") {
+            config.relay_id = Some(relay_id);
+        }
+
         // Load from command line arguments
         config.load_from_args()?;
 
@@ -113,6 +128,18 @@ impl ServerConfig {
             return Err(ConfigError::Invalid("default_quota must be > 0".to_string()));
         }
 
+        if self.idle_timeout_secs <= self.keepalive_interval_secs {
+            return Err(ConfigError::Invalid(
+                "idle_timeout_secs must be greater than keepalive_interval_secs".to_string(),
+            ));
+        }
+
+        if self.quota_window_secs == 0 {
+            return Err(ConfigError::Invalid("quota_window_secs must be > 0".to_string()));
+        }
+
+        self.relay_audience()?;
+
         if !self.quic_cert_path.exists() {
             return Err(ConfigError::Invalid(format!(
                 "Certificate file not found: {:?}",
@@ -249,9 +276,44 @@ impl ServerConfig {
             self.global_bandwidth_limit = Some(global as u64);
         }
 
+        if let Some(window) = toml_config.get("quota_window_secs").and_then(|v| v.as_integer()) {
+            self.quota_window_secs = window as u64;
+        }
+
+        if let Some(relay_id) = toml_config.get("relay_id").and_then(|v| v.as_str()) {
+            self.relay_id = Some(relay_id.to_string());
+        }
+
+        if let Some(ids) = toml_config.get("accepted_relay_ids").and_then(|v| v.as_array()) {
+            self.accepted_relay_ids = ids.iter()
+                .filter_map(|v| v.as_str().map(str::to_string))
+                .collect();
+        }
+
         Ok(())
     }
 
+    /// Relay ids whose tokens this relay redeems. With neither `relay_id`
+    /// nor `accepted_relay_ids` configured, tokens for any relay are accepted.
+    pub fn relay_audience(&self) -> Result<RelayAudience, ConfigError> {
+        if self.accepted_relay_ids.iter().any(|id| id == "*")
+            || (self.relay_id.is_none() && self.accepted_relay_ids.is_empty())
+        {
+            return Ok(RelayAudience::Any);
+        }
+
+        self.relay_id.iter()
+            .chain(&self.accepted_relay_ids)
+            .map(|id| {
+                hex::decode(id)
+                    .ok()
+                    .and_then(|bytes| <[u8; 16]>::try_from(bytes).ok())
+                    .ok_or_else(|| ConfigError::Invalid(format!("Invalid relay id: {}", id)))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(RelayAudience::Ids)
+    }
+
     /// Convert to AllocationConfig
     pub fn to_allocation_config(&self) -> AllocationConfig {
         AllocationConfig {
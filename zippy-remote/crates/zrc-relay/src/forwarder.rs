@@ -3,8 +3,8 @@
 use std::sync::Arc;
 use thiserror::Error;
 
-use crate::allocation::{AllocationManager, AllocationId, AllocationError};
-use crate::bandwidth::BandwidthLimiter;
+use crate::allocation::{AllocationManager, AllocationId, AllocationError, TerminateReason};
+use crate::bandwidth::{BandwidthLimiter, Quota, DEFAULT_QUOTA_WINDOW};
 use std::sync::atomic::Ordering;
 
 #[derive(Debug, Error)]
@@ -25,6 +25,7 @@ pub enum ForwardError {
 pub struct Forwarder {
     allocation_mgr: Arc<AllocationManager>,
     bandwidth_limiter: Arc<BandwidthLimiter>,
+    quota: Arc<Quota>,
 }
 
 impl Forwarder {
@@ -35,9 +36,20 @@ impl Forwarder {
         Self {
             allocation_mgr,
             bandwidth_limiter,
+            quota: Arc::new(Quota::new(DEFAULT_QUOTA_WINDOW)),
         }
     }
 
+    /// Use a shared per-token quota tracker (e.g. one the admin API reports on)
+    pub fn with_quota(mut self, quota: Arc<Quota>) -> Self {
+        self.quota = quota;
+        self
+    }
+
+    pub fn quota(&self) -> &Arc<Quota> {
+        &self.quota
+    }
+
     /// Forward datagram between endpoints
     pub async fn forward_datagram(
         &self,
@@ -45,6 +57,14 @@ impl Forwarder {
         from_device: bool,
         data: &[u8],
     ) -> Result<(), ForwardError> {
+        // Empty datagrams are keepalives: they hold off the idle reaper but
+        // aren't forwarded or counted against quota
+        if data.is_empty() {
+            return self.allocation_mgr
+                .record_keepalive(allocation_id)
+                .map_err(|_| ForwardError::AllocationNotFound);
+        }
+
         // Get allocation
         let allocation = self.allocation_mgr
             .get(allocation_id)
@@ -59,6 +79,19 @@ impl Forwarder {
             return Err(ForwardError::RateLimited);
         }
 
+        // Enforce the token's quota over the sliding window; an allocation
+        // that runs out is closed
+        if !self.quota.try_consume(allocation_id, allocation.quota_bytes, data.len() as u64) {
+            tracing::warn!(
+                allocation_id = hex::encode(allocation_id),
+                quota_bytes = allocation.quota_bytes,
+                "Token quota exceeded, closing allocation"
+            );
+            self.allocation_mgr.terminate(allocation_id, TerminateReason::QuotaExceeded);
+            self.bandwidth_limiter.remove(allocation_id);
+            return Err(ForwardError::QuotaExceeded);
+        }
+
         // Check quota and get warning status
         match self.allocation_mgr.record_transfer(allocation_id, data.len() as u64) {
             Err(AllocationError::QuotaExceeded) => {
@@ -117,6 +150,7 @@ impl Forwarder {
 mod proptests {
     use super::*;
     use proptest::prelude::*;
+    use crate::allocation::RecordingConnection;
     use crate::token::RelayTokenV1;
     use std::time::SystemTime;
 
@@ -230,4 +264,52 @@ mod proptests {
             prop_assert_eq!(allocation.bytes_transferred.load(Ordering::Relaxed), size);
         });
     }
+
+    #[tokio::test]
+    async fn test_forwarding_stops_at_quota_and_resumes_after_window() {
+        use crate::allocation::AllocationManager;
+        use crate::bandwidth::BandwidthLimiter;
+        use std::time::Duration;
+
+        let allocation_mgr = Arc::new(AllocationManager::new(
+            crate::allocation::AllocationConfig::default()
+        ));
+        let quota = Arc::new(Quota::new(Duration::from_millis(200)));
+        let forwarder = Forwarder::new(allocation_mgr.clone(), Arc::new(BandwidthLimiter::new(None)))
+            .with_quota(quota.clone());
+
+        let mut token = create_test_token();
+        token.quota_bytes = 3000;
+        let relay_addr = "127.0.0.1:4433".parse().unwrap();
+        let allocate = || {
+            let info = allocation_mgr.create(&token, relay_addr).unwrap();
+            allocation_mgr.associate(&info.id, Arc::new(RecordingConnection::default()), "10.0.0.1:5000".parse().unwrap(), true).unwrap();
+            allocation_mgr.associate(&info.id, Arc::new(RecordingConnection::default()), "10.0.0.2:5000".parse().unwrap(), false).unwrap();
+            info.id
+        };
+
+        let id = allocate();
+        let packet = [0u8; 1000];
+        for _ in 0..3 {
+            forwarder.forward_datagram(&id, true, &packet).await.unwrap();
+        }
+        assert!(matches!(
+            forwarder.forward_datagram(&id, true, &packet).await,
+            Err(ForwardError::QuotaExceeded)
+        ));
+        assert!(allocation_mgr.get(&id).is_none(), "allocation closed on overage");
+        assert_eq!(quota.usage(&id).unwrap().overage_bytes, 1000);
+
+        // Re-allocating with the same token does not reset the window
+        let id = allocate();
+        assert!(matches!(
+            forwarder.forward_datagram(&id, true, &packet).await,
+            Err(ForwardError::QuotaExceeded)
+        ));
+
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        let id = allocate();
+        forwarder.forward_datagram(&id, false, &packet).await.unwrap();
+        assert_eq!(quota.usage(&id).unwrap().used, 1000);
+    }
 }
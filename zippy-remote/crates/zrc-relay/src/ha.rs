@@ -61,17 +61,25 @@ pub trait StateStore: Send + Sync {
     
     /// Register instance heartbeat
     async fn heartbeat(&self, instance_id: &str, region: Option<&str>) -> Result<(), HAError>;
+
+    /// Mark an instance as draining (or not), so peers take its new traffic
+    async fn set_draining(&self, instance_id: &str, draining: bool) -> Result<(), HAError>;
+
+    /// List instances that are draining
+    async fn draining_instances(&self) -> Result<Vec<String>, HAError>;
 }
 
 /// In-memory state store (for single-instance or testing)
 pub struct MemoryStateStore {
     allocations: Arc<dashmap::DashMap<String, Vec<AllocationInfo>>>,
+    draining: Arc<dashmap::DashSet<String>>,
 }
 
 impl MemoryStateStore {
     pub fn new() -> Self {
         Self {
             allocations: Arc::new(dashmap::DashMap::new()),
+            draining: Arc::new(dashmap::DashSet::new()),
         }
     }
 }
@@ -112,6 +120,19 @@ impl StateStore for MemoryStateStore {
         // No-op for memory store
         Ok(())
     }
+
+    async fn set_draining(&self, instance_id: &str, draining: bool) -> Result<(), HAError> {
+        if draining {
+            self.draining.insert(instance_id.to_string());
+        } else {
+            self.draining.remove(instance_id);
+        }
+        Ok(())
+    }
+
+    async fn draining_instances(&self) -> Result<Vec<String>, HAError> {
+        Ok(self.draining.iter().map(|id| id.clone()).collect())
+    }
 }
 
 /// Redis state store (optional, feature-gated)
@@ -235,6 +256,32 @@ impl StateStore for RedisStateStore {
         
         Ok(())
     }
+
+    async fn set_draining(&self, instance_id: &str, draining: bool) -> Result<(), HAError> {
+        let mut conn = self.client.get_async_connection().await
+            .map_err(|e| HAError::Redis(e.to_string()))?;
+
+        let cmd = if draining { "SADD" } else { "SREM" };
+        redis::cmd(cmd)
+            .arg("zrc:relay:draining")
+            .arg(instance_id)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| HAError::Redis(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn draining_instances(&self) -> Result<Vec<String>, HAError> {
+        let mut conn = self.client.get_async_connection().await
+            .map_err(|e| HAError::Redis(e.to_string()))?;
+
+        redis::cmd("SMEMBERS")
+            .arg("zrc:relay:draining")
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| HAError::Redis(e.to_string()))
+    }
 }
 
 /// High Availability manager
@@ -341,6 +388,22 @@ impl HAManager {
         });
     }
 
+    /// Tell peers this instance is draining so they take new allocations
+    pub async fn announce_draining(&self, draining: bool) -> Result<(), HAError> {
+        self.state_store.set_draining(&self.config.instance_id, draining).await
+    }
+
+    /// Other instances that are accepting new allocations
+    pub async fn available_peers(&self) -> Result<Vec<String>, HAError> {
+        let draining = self.state_store.draining_instances().await?;
+        Ok(self.state_store
+            .list_instances()
+            .await?
+            .into_iter()
+            .filter(|id| *id != self.config.instance_id && !draining.contains(id))
+            .collect())
+    }
+
     /// Get instance ID
     pub fn instance_id(&self) -> &str {
         &self.config.instance_id
@@ -351,3 +414,40 @@ impl HAManager {
         self.config.region.as_deref()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::allocation::AllocationConfig;
+
+    #[tokio::test]
+    async fn test_draining_instance_is_not_offered_to_peers() {
+        let store = Arc::new(MemoryStateStore::new());
+        let info = AllocationInfo {
+            id: [1u8; 16],
+            device_id: [0u8; 32],
+            peer_id: [0u8; 32],
+            relay_addr: "127.0.0.1:4433".parse().unwrap(),
+            expires_at: 0,
+            bytes_transferred: 0,
+            created_at: 0,
+        };
+        for instance in ["relay-a", "relay-b"] {
+            store.save_allocation(instance, &info).await.unwrap();
+        }
+
+        let ha = |id: &str| HAManager {
+            config: HAConfig { instance_id: id.to_string(), ..Default::default() },
+            state_store: store.clone(),
+            allocation_mgr: Arc::new(AllocationManager::new(AllocationConfig::default())),
+        };
+        let (a, b) = (ha("relay-a"), ha("relay-b"));
+        assert_eq!(b.available_peers().await.unwrap(), vec!["relay-a".to_string()]);
+
+        a.announce_draining(true).await.unwrap();
+        assert!(b.available_peers().await.unwrap().is_empty());
+
+        a.announce_draining(false).await.unwrap();
+        assert_eq!(b.available_peers().await.unwrap(), vec!["relay-a".to_string()]);
+    }
+}
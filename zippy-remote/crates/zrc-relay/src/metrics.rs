@@ -17,6 +17,7 @@ pub struct AllocationMetrics {
     bandwidth_usage: Gauge,
     quota_usage: Gauge,
     quota_exceeded: Counter,
+    idle_reaped: Counter,
     rate_limit_drops: Counter,
     connection_count: Gauge,
     error_count: Counter,
@@ -83,6 +84,12 @@ impl AllocationMetrics {
         ))?;
         registry.register(Box::new(quota_exceeded.clone()))?;
 
+        let idle_reaped = Counter::with_opts(Opts::new(
+            "zrc_relay_idle_reaped_total",
+            "Total number of allocations reaped after the idle timeout",
+        ))?;
+        registry.register(Box::new(idle_reaped.clone()))?;
+
         let rate_limit_drops = Counter::with_opts(Opts::new(
             "zrc_relay_rate_limit_drops_total",
             "Total packets dropped due to rate limiting",
@@ -116,6 +123,7 @@ impl AllocationMetrics {
             bandwidth_usage,
             quota_usage,
             quota_exceeded,
+            idle_reaped,
             rate_limit_drops,
             connection_count,
             error_count,
@@ -171,6 +179,10 @@ impl AllocationMetrics {
         self.quota_exceeded.inc();
     }
 
+    pub fn record_idle_reaped(&self) {
+        self.idle_reaped.inc();
+    }
+
     pub fn record_rate_limit_drop(&self) {
         self.rate_limit_drops.inc();
         self.rate_limit_hits.inc();
@@ -2,7 +2,7 @@
 
 use std::sync::Arc;
 use std::net::SocketAddr;
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use anyhow::{Result, Context};
 use tracing::{info, error, warn, debug};
 use quinn::{Endpoint, ServerConfig as QuinnServerConfig, Connection as QuinnConnection};
@@ -14,13 +14,14 @@ use axum::{
     response::Response,
     routing::{get, Router},
 };
+use tokio::sync::mpsc;
 use tower::ServiceBuilder;
 use tower_http::trace::TraceLayer;
 
 use crate::config::ServerConfig;
-use crate::allocation::AllocationManager;
-use crate::admin::AdminApi;
-use crate::bandwidth::BandwidthLimiter;
+use crate::allocation::{AllocationManager, DrainReport, TerminateReason, DEFAULT_DRAIN_DEADLINE};
+use crate::admin::{AdminApi, DrainCommand};
+use crate::bandwidth::{BandwidthLimiter, Quota};
 use crate::forwarder::Forwarder;
 use crate::ha::{HAManager, HAConfig};
 use crate::metrics::AllocationMetrics;
@@ -46,12 +47,15 @@ impl RelayServer {
         let allocation_config = config.to_allocation_config();
         let allocation_mgr = Arc::new(AllocationManager::new(allocation_config));
         let bandwidth_limiter = Arc::new(BandwidthLimiter::new(config.global_bandwidth_limit));
+        let quota = Arc::new(Quota::new(Duration::from_secs(config.quota_window_secs)));
         let forwarder = Arc::new(Forwarder::new(
             allocation_mgr.clone(),
             bandwidth_limiter.clone(),
-        ));
+        ).with_quota(quota));
         let metrics = Arc::new(AllocationMetrics::new()?);
-        let token_verifier = Arc::new(TokenVerifier::new());
+        let token_verifier = Arc::new(
+            TokenVerifier::new().with_audience(config.relay_audience()?),
+        );
         let security = Arc::new(SecurityControls::new());
 
         // Setup High Availability if configured
@@ -107,34 +111,65 @@ impl RelayServer {
         })
     }
 
-    /// Graceful shutdown with allocation migration
-    async fn graceful_shutdown(&self) -> Result<()> {
-        info!("Starting graceful shutdown...");
-        
-        // Stop accepting new connections
-        // The endpoint will stop accepting when we drop it, but we can also close it explicitly
-        // For now, we'll just wait for existing connections to close
-        
-        // Give existing allocations time to migrate or close
-        let shutdown_timeout = Duration::from_secs(30);
-        let start = std::time::Instant::now();
-        
-        loop {
-            let active = self.allocation_mgr.count();
-            if active == 0 {
-                info!("All allocations closed, shutdown complete");
-                break;
+    /// Stop accepting new allocations and wait for existing ones to finish.
+    ///
+    /// Peers are told (via HA state) to take new traffic. Returns once every
+    /// allocation has closed, or force-closes the rest when `deadline` passes.
+    /// Progress is visible through the admin API's `/admin/drain`.
+    pub async fn begin_drain(&self, deadline: Duration) -> DrainReport {
+        self.start_drain(deadline).await;
+        let report = self.allocation_mgr.wait_drained().await;
+        log_drain_report(&report);
+        self.metrics.set_active_allocations(self.allocation_mgr.count());
+        report
+    }
+
+    async fn start_drain(&self, deadline: Duration) {
+        info!(
+            "Draining relay: {} allocations active, deadline {:?}",
+            self.allocation_mgr.count(),
+            deadline
+        );
+        self.allocation_mgr.start_draining(deadline);
+        self.announce_draining(true).await;
+    }
+
+    async fn announce_draining(&self, draining: bool) {
+        if let Some(ref ha_manager) = self.ha_manager {
+            if let Err(e) = ha_manager.announce_draining(draining).await {
+                warn!("Failed to announce draining to peers: {}", e);
             }
-            
-            if start.elapsed() > shutdown_timeout {
-                warn!("Shutdown timeout reached, {} allocations still active", active);
-                break;
+        }
+    }
+
+    /// Run a drain requested through the admin API. The relay keeps running;
+    /// the drain completes in the background unless it is resumed first.
+    async fn handle_drain_command(&self, command: DrainCommand) {
+        match command {
+            DrainCommand::Start(deadline) => {
+                self.start_drain(deadline).await;
+                let allocation_mgr = self.allocation_mgr.clone();
+                let metrics = self.metrics.clone();
+                tokio::spawn(async move {
+                    let report = allocation_mgr.wait_drained().await;
+                    if allocation_mgr.is_draining() {
+                        log_drain_report(&report);
+                    }
+                    metrics.set_active_allocations(allocation_mgr.count());
+                });
+            }
+            DrainCommand::Resume => {
+                info!("Drain cancelled, accepting new allocations");
+                self.allocation_mgr.stop_draining();
+                self.announce_draining(false).await;
             }
-            
-            info!("Waiting for {} allocations to close...", active);
-            tokio::time::sleep(Duration::from_secs(1)).await;
         }
-        
+    }
+
+    /// Graceful shutdown with allocation migration
+    async fn graceful_shutdown(&self) -> Result<()> {
+        info!("Starting graceful shutdown...");
+        self.begin_drain(DEFAULT_DRAIN_DEADLINE).await;
         Ok(())
     }
 
@@ -166,14 +201,19 @@ impl RelayServer {
             .layer(ServiceBuilder::new().layer(TraceLayer::new_for_http()));
 
         // Add admin API if configured
+        let (drain_tx, mut drain_rx) = mpsc::unbounded_channel();
         if let Some(admin_addr) = self.config.admin_addr {
             if let Some(admin_token) = &self.config.admin_token {
                 let admin_api = AdminApi::new(
                     self.allocation_mgr.clone(),
                     self.metrics.clone(),
                     self.security.clone(),
+                    self.forwarder.quota().clone(),
+                    self.bandwidth_limiter.clone(),
+                    self.token_verifier.clone(),
                     admin_token.clone(),
-                );
+                )
+                .with_drain_control(drain_tx);
                 health_router = health_router.merge(admin_api.router());
                 info!("Admin API enabled on {}", admin_addr);
             } else {
@@ -201,14 +241,35 @@ impl RelayServer {
             }
         });
 
-        // Start expiration cleanup task
+        // Start expiration cleanup task; runs at least twice per idle timeout
+        // so silent allocations don't linger much past it
         let allocation_mgr = self.allocation_mgr.clone();
         let metrics = self.metrics.clone();
+        let quota = self.forwarder.quota().clone();
+        let token_verifier = self.token_verifier.clone();
+        let bandwidth_limiter = self.bandwidth_limiter.clone();
+        let reap_interval = Duration::from_secs((self.config.idle_timeout_secs / 2).clamp(1, 60));
         tokio::spawn(async move {
-            let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
+            let mut interval = tokio::time::interval(reap_interval);
             loop {
                 interval.tick().await;
-                allocation_mgr.expire_stale();
+                for reaped in allocation_mgr.expire_stale() {
+                    if let TerminateReason::IdleTimeout = reaped.reason {
+                        info!(
+                            event = "allocation_reaped",
+                            allocation_id = hex::encode(reaped.id),
+                            idle_secs = reaped.idle_for.as_secs(),
+                            "Reaped idle allocation, token marked spent"
+                        );
+                        metrics.record_idle_reaped();
+                    }
+                    token_verifier.mark_spent(reaped.id, reaped.token_expires_at);
+                    bandwidth_limiter.remove(&reaped.id);
+                }
+                token_verifier.cleanup_expired(
+                    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+                );
+                quota.prune();
                 metrics.set_active_allocations(allocation_mgr.count());
             }
         });
@@ -273,15 +334,15 @@ impl RelayServer {
             }
         });
 
-        // Setup graceful shutdown
-        let shutdown_signal = async {
-            tokio::signal::ctrl_c()
-                .await
-                .expect("Failed to install CTRL+C handler");
-        };
-
-        // Wait for shutdown signal
-        shutdown_signal.await;
+        // Serve admin drain requests until a shutdown signal arrives
+        let shutdown = shutdown_signal();
+        tokio::pin!(shutdown);
+        loop {
+            tokio::select! {
+                _ = &mut shutdown => break,
+                Some(command) = drain_rx.recv() => self.handle_drain_command(command).await,
+            }
+        }
         info!("Shutdown signal received, starting graceful shutdown");
 
         // Graceful shutdown with allocation migration
@@ -292,6 +353,41 @@ impl RelayServer {
     }
 }
 
+fn log_drain_report(report: &DrainReport) {
+    if report.forced > 0 {
+        warn!("Drain deadline reached, force-closed {} allocations", report.forced);
+    } else {
+        info!("All {} allocations drained", report.completed);
+    }
+}
+
+/// Wait for SIGINT or, on Unix, SIGTERM
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    let mut sigterm = {
+        use tokio::signal::unix::{signal, SignalKind};
+        signal(SignalKind::terminate()).ok()
+    };
+
+    tokio::select! {
+        _ = async {
+            #[cfg(unix)]
+            {
+                if let Some(ref mut sigterm) = sigterm {
+                    sigterm.recv().await;
+                    return;
+                }
+            }
+            std::future::pending::<()>().await;
+        } => {
+            info!("Received SIGTERM");
+        }
+        _ = tokio::signal::ctrl_c() => {
+            info!("Received SIGINT");
+        }
+    }
+}
+
 /// Load certificate chain from file
 fn load_cert_chain(path: &std::path::Path) -> Result<Vec<CertificateDer<'static>>> {
     let cert_file = std::fs::read(path)
@@ -433,6 +529,11 @@ async fn ready_handler(
     State((_metrics, allocation_mgr)): State<(Arc<AllocationMetrics>, Arc<AllocationManager>)>,
     max_allocations: usize,
 ) -> StatusCode {
+    // Draining relays take no new allocations
+    if allocation_mgr.is_draining() {
+        return StatusCode::SERVICE_UNAVAILABLE;
+    }
+
     // Check if we're at capacity
     let current_allocations = allocation_mgr.count();
     
@@ -28,6 +28,29 @@ pub enum TokenError {
     AllocationIdMismatch,
     #[error("Invalid public key")]
     InvalidPublicKey,
+    #[error("Token already spent")]
+    Spent,
+    #[error("Token issued for a different relay")]
+    WrongAudience,
+}
+
+/// Relay ids a verifier accepts tokens for (the token's `relay_id` claim)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RelayAudience {
+    /// Accept tokens for any relay
+    Any,
+    /// Accept tokens only for these relays, e.g. this instance plus the
+    /// other members of its cluster
+    Ids(Vec<[u8; 16]>),
+}
+
+impl RelayAudience {
+    pub fn accepts(&self, relay_id: &[u8; 16]) -> bool {
+        match self {
+            RelayAudience::Any => true,
+            RelayAudience::Ids(ids) => ids.contains(relay_id),
+        }
+    }
 }
 
 impl RelayTokenV1 {
@@ -89,6 +112,10 @@ pub struct TokenVerifier {
     pinned_keys: DashMap<[u8; 32], [u8; 32]>,
     /// Token cache to avoid repeated signature verification
     verified_cache: DashMap<[u8; 16], VerifiedToken>, // Keyed by allocation_id
+    /// Tokens whose allocation was reaped, kept until the token expires
+    spent: DashMap<[u8; 16], u64>, // allocation_id -> expires_at
+    /// Relay ids this verifier redeems tokens for
+    audience: RelayAudience,
 }
 
 impl TokenVerifier {
@@ -96,9 +123,17 @@ impl TokenVerifier {
         Self {
             pinned_keys: DashMap::new(),
             verified_cache: DashMap::new(),
+            spent: DashMap::new(),
+            audience: RelayAudience::Any,
         }
     }
 
+    /// Only accept tokens minted for the given relay ids
+    pub fn with_audience(mut self, audience: RelayAudience) -> Self {
+        self.audience = audience;
+        self
+    }
+
     /// Pin device public key
     pub fn pin_device(&self, device_id: [u8; 32], pub_key: [u8; 32]) {
         self.pinned_keys.insert(device_id, pub_key);
@@ -123,6 +158,14 @@ impl TokenVerifier {
             return Err(TokenError::Expired);
         }
 
+        if !self.audience.accepts(&token.relay_id) {
+            return Err(TokenError::WrongAudience);
+        }
+
+        if self.is_spent(&token.allocation_id) {
+            return Err(TokenError::Spent);
+        }
+
         // Check cache
         if let Some(cached) = self.verified_cache.get(&token.allocation_id) {
             let cached = cached.value();
@@ -204,11 +247,23 @@ impl TokenVerifier {
         Ok(())
     }
 
+    /// Mark a token as spent so it can't open a new allocation
+    pub fn mark_spent(&self, allocation_id: [u8; 16], expires_at: u64) {
+        self.verified_cache.remove(&allocation_id);
+        self.spent.insert(allocation_id, expires_at);
+    }
+
+    /// Check if a token has been spent
+    pub fn is_spent(&self, allocation_id: &[u8; 16]) -> bool {
+        self.spent.contains_key(allocation_id)
+    }
+
     /// Clean up expired cache entries
     pub fn cleanup_expired(&self, now: u64) {
         self.verified_cache.retain(|_id, token| {
             token.expires_at > now
         });
+        self.spent.retain(|_id, expires_at| *expires_at > now);
     }
 
     /// Clear cache for a specific allocation
@@ -284,3 +339,47 @@ mod proptests {
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    fn signed_token(relay_id: [u8; 16]) -> (RelayTokenV1, [u8; 32]) {
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+        let mut token = RelayTokenV1 {
+            relay_id,
+            allocation_id: [1u8; 16],
+            device_id: [2u8; 32],
+            peer_id: [3u8; 32],
+            expires_at: 2_000,
+            bandwidth_limit: 1024,
+            quota_bytes: 4096,
+            signature: [0u8; 64],
+        };
+        token.signature = key.sign(&token.signature_input()).to_bytes();
+        (token, key.verifying_key().to_bytes())
+    }
+
+    #[test]
+    fn test_token_for_foreign_relay_is_rejected() {
+        let verifier = TokenVerifier::new().with_audience(RelayAudience::Ids(vec![[0xA; 16]]));
+        let (token, device_pub) = signed_token([0xB; 16]);
+        assert!(matches!(
+            verifier.verify(&token, &device_pub, 1_000),
+            Err(TokenError::WrongAudience)
+        ));
+    }
+
+    #[test]
+    fn test_token_for_matching_or_any_relay_is_accepted() {
+        let (token, device_pub) = signed_token([0xB; 16]);
+
+        let cluster = TokenVerifier::new()
+            .with_audience(RelayAudience::Ids(vec![[0xA; 16], [0xB; 16]]));
+        assert!(cluster.verify(&token, &device_pub, 1_000).is_ok());
+
+        let any = TokenVerifier::new().with_audience(RelayAudience::Any);
+        assert!(any.verify(&token, &device_pub, 1_000).is_ok());
+    }
+}
@@ -1,19 +1,28 @@
 use axum::{
     body::Bytes,
-    extract::{Path, Query, State, ConnectInfo},
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Path, Query, State, ConnectInfo,
+    },
     http::{HeaderMap, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
 };
-use std::{collections::HashMap, net::SocketAddr, sync::Arc, time::Instant};
+use futures_util::{SinkExt, StreamExt};
+use std::{collections::HashMap, net::{IpAddr, SocketAddr}, sync::Arc, time::Instant};
 use tokio::time::Duration;
+use tracing::debug;
 
 use crate::{
     auth::{extract_bearer_token, AuthConfig},
     mailbox::{MailboxError, MailboxMap},
+    mesh::{MeshFrame, MeshRelay, MeshRoute},
     metrics::MailboxMetrics,
     rate_limit::RateLimiter,
 };
 
+/// Response header carrying the address a delivered message was posted from
+pub const SOURCE_HEADER: &str = "X-Message-Source";
+
 #[derive(Clone)]
 pub struct AppState {
     pub mailboxes: MailboxMap,
@@ -21,6 +30,7 @@ pub struct AppState {
     pub auth: AuthConfig,
     pub metrics: Arc<MailboxMetrics>,
     pub config: crate::config::ServerConfig,
+    pub mesh: Arc<MeshRelay>,
     pub shutdown: tokio::sync::watch::Receiver<bool>,
 }
 
@@ -75,14 +85,48 @@ pub async fn post_mailbox(
         return (StatusCode::BAD_REQUEST, "recipient id must be 32 bytes").into_response();
     }
 
-    // Post message
+    store_message(&state, rid, body, Some(ip), start)
+}
+
+/// Queue `body`, posted from `source`, in the mailbox for `rid`.
+fn store_message(
+    state: &AppState,
+    rid: Vec<u8>,
+    body: Bytes,
+    source: Option<IpAddr>,
+    start: Instant,
+) -> Response {
+    match enqueue(state, rid, body, source) {
+        Ok(_sequence) => {
+            let latency = start.elapsed().as_secs_f64();
+            state.metrics.request_latency.observe(latency);
+            
+            (StatusCode::ACCEPTED, "ok").into_response()
+        }
+        Err(MailboxError::MessageTooLarge) => (StatusCode::PAYLOAD_TOO_LARGE, "message too large").into_response(),
+        Err(MailboxError::QueueFull) => (StatusCode::INSUFFICIENT_STORAGE, "queue full").into_response(),
+    }
+}
+
+/// Post `body` to the mailbox for `rid` and update the mailbox metrics.
+fn enqueue(
+    state: &AppState,
+    rid: Vec<u8>,
+    body: Bytes,
+    source: Option<IpAddr>,
+) -> Result<u64, MailboxError> {
     let result = {
-        let mut mailbox_entry = state.mailboxes.entry(rid.clone()).or_insert_with(crate::mailbox::Mailbox::new);
-        mailbox_entry.value_mut().post(body, state.config.max_queue_length, state.config.max_message_size)
+        let mut mailbox_entry = state.mailboxes.entry(rid).or_default();
+        mailbox_entry.value_mut().post_from(
+            body,
+            source,
+            state.config.max_queue_length,
+            state.config.max_message_size,
+        )
     };
-    
+
     match result {
-        Ok(_sequence) => {
+        Ok(sequence) => {
             state.metrics.messages_posted.inc();
             state.metrics.messages_posted.inc();
             {
@@ -90,20 +134,62 @@ pub async fn post_mailbox(
                 let total: usize = state.mailboxes.iter().map(|e| e.value().queue_length()).sum();
                 state.metrics.total_messages.set(total as f64);
             }
-            
-            let latency = start.elapsed().as_secs_f64();
-            state.metrics.request_latency.observe(latency);
-            
-            (StatusCode::ACCEPTED, "ok").into_response()
+            Ok(sequence)
         }
-        Err(MailboxError::MessageTooLarge) => {
+        Err(e) => {
             state.metrics.error_counts.inc();
-            (StatusCode::PAYLOAD_TOO_LARGE, "message too large").into_response()
+            Err(e)
         }
-        Err(MailboxError::QueueFull) => {
-            state.metrics.error_counts.inc();
-            (StatusCode::INSUFFICIENT_STORAGE, "queue full").into_response()
+    }
+}
+
+// POST /v1/mesh/frames
+pub async fn post_mesh_frame(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let start = Instant::now();
+
+    match state.rate_limiter.check_post(addr.ip()).await {
+        Ok(()) => {}
+        Err(retry_after) => {
+            state.metrics.rate_limit_hits.inc();
+            let mut response = (StatusCode::TOO_MANY_REQUESTS, "rate limit exceeded").into_response();
+            response.headers_mut().insert(
+                "Retry-After",
+                HeaderValue::from_str(&retry_after.to_string()).unwrap(),
+            );
+            return response;
+        }
+    }
+
+    let token = extract_bearer_token(headers.get("authorization"));
+    if let Err(e) = state.auth.validate(token, None) {
+        state.metrics.error_counts.inc();
+        return match e {
+            crate::auth::AuthError::MissingToken => (StatusCode::UNAUTHORIZED, "missing token").into_response(),
+            crate::auth::AuthError::InvalidToken => (StatusCode::FORBIDDEN, "invalid token").into_response(),
+            _ => (StatusCode::BAD_REQUEST, "auth error").into_response(),
+        };
+    }
+
+    let Ok(frame) = MeshFrame::decode(&body) else {
+        state.metrics.error_counts.inc();
+        return (StatusCode::BAD_REQUEST, "bad mesh frame").into_response();
+    };
+
+    let has_mailbox = state.mailboxes.contains_key(&frame.recipient_id[..]);
+    match state.mesh.route(&frame, has_mailbox) {
+        // The posting peer is a relay, not the sender
+        MeshRoute::Store => store_message(&state, frame.recipient_id.to_vec(), frame.envelope, None, start),
+        MeshRoute::Forward(next) => {
+            state.mesh.forward(next);
+            (StatusCode::ACCEPTED, "ok").into_response()
         }
+        // Duplicates and spent frames are expected in a mesh; not an error
+        MeshRoute::Drop => (StatusCode::ACCEPTED, "ok").into_response(),
     }
 }
 
@@ -171,12 +257,12 @@ pub async fn get_mailbox(
     }
 
     // Try immediate get
-    let immediate_result: Option<(Bytes, u64, usize)> = { // Removed 'total' from tuple here to avoid deadlock
+    let immediate_result: Option<(Bytes, u64, usize, Option<IpAddr>)> = { // Removed 'total' from tuple here to avoid deadlock
         if let Some(mut mailbox_entry) = state.mailboxes.get_mut(&rid) {
             let mailbox = mailbox_entry.value_mut();
             if let Some(message) = mailbox.get() {
                 let queue_len = mailbox.queue_length();
-                Some((message.data.clone(), message.sequence, queue_len))
+                Some((message.data.clone(), message.sequence, queue_len, message.source))
             } else {
                 None
             }
@@ -185,7 +271,7 @@ pub async fn get_mailbox(
         }
     };
     
-    if let Some((data, sequence, queue_len)) = immediate_result {
+    if let Some((data, sequence, queue_len, source)) = immediate_result {
         // Calculate total outside the lock
         let total: usize = state.mailboxes.iter().map(|e| e.value().queue_length()).sum();
 
@@ -204,6 +290,12 @@ pub async fn get_mailbox(
             "X-Queue-Length",
             HeaderValue::from_str(&queue_len.to_string()).unwrap(),
         );
+        if let Some(source) = source {
+            response.headers_mut().insert(
+                SOURCE_HEADER,
+                HeaderValue::from_str(&source.to_string()).unwrap(),
+            );
+        }
         return response;
     }
 
@@ -233,12 +325,12 @@ pub async fn get_mailbox(
                     return (StatusCode::SERVICE_UNAVAILABLE, "server shutting down").into_response();
                 }
                 
-                let result: Option<(Bytes, u64, usize)> = {
+                let result: Option<(Bytes, u64, usize, Option<IpAddr>)> = {
                     if let Some(mut mailbox_entry) = state.mailboxes.get_mut(&rid) {
                         let mailbox = mailbox_entry.value_mut();
                         if let Some(message) = mailbox.get() {
                             let queue_len = mailbox.queue_length();
-                            Some((message.data.clone(), message.sequence, queue_len))
+                            Some((message.data.clone(), message.sequence, queue_len, message.source))
                         } else {
                             None
                         }
@@ -247,7 +339,7 @@ pub async fn get_mailbox(
                     }
                 };
                 
-                if let Some((data, sequence, queue_len)) = result {
+                if let Some((data, sequence, queue_len, source)) = result {
                     // Calculate total outside lock
                     let total: usize = state.mailboxes.iter().map(|e| e.value().queue_length()).sum();
                     state.metrics.messages_delivered.inc();
@@ -265,6 +357,12 @@ pub async fn get_mailbox(
                         "X-Queue-Length",
                         HeaderValue::from_str(&queue_len.to_string()).unwrap(),
                     );
+                    if let Some(source) = source {
+                        response.headers_mut().insert(
+                            SOURCE_HEADER,
+                            HeaderValue::from_str(&source.to_string()).unwrap(),
+                        );
+                    }
                     return response;
                 }
             }
@@ -286,6 +384,130 @@ pub async fn get_mailbox(
     (StatusCode::NO_CONTENT, Bytes::new()).into_response()
 }
 
+// GET /v1/mailbox/{recipient_id_hex}/ws
+//
+// Upgrades to a WebSocket that pushes each envelope for the mailbox as a
+// binary message as soon as it is queued. Binary messages sent by the
+// client are `recipient_id(32) || envelope` and are queued like a POST.
+pub async fn mailbox_ws(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(rid_hex): Path<String>,
+    headers: HeaderMap,
+    ws: WebSocketUpgrade,
+) -> Response {
+    if *state.shutdown.borrow() {
+        return (StatusCode::SERVICE_UNAVAILABLE, "server shutting down").into_response();
+    }
+
+    let ip = addr.ip();
+
+    match state.rate_limiter.check_get(ip).await {
+        Ok(()) => {}
+        Err(retry_after) => {
+            state.metrics.rate_limit_hits.inc();
+            let mut response = (StatusCode::TOO_MANY_REQUESTS, "rate limit exceeded").into_response();
+            response.headers_mut().insert(
+                "Retry-After",
+                HeaderValue::from_str(&retry_after.to_string()).unwrap(),
+            );
+            return response;
+        }
+    }
+
+    let rid = match hex::decode(&rid_hex) {
+        Ok(b) => b,
+        Err(_) => {
+            state.metrics.error_counts.inc();
+            return (StatusCode::BAD_REQUEST, "bad recipient id hex").into_response();
+        }
+    };
+
+    if rid.len() != 32 {
+        state.metrics.error_counts.inc();
+        return (StatusCode::BAD_REQUEST, "recipient id must be 32 bytes").into_response();
+    }
+
+    let token = extract_bearer_token(headers.get("authorization"));
+    if let Err(e) = state.auth.validate(token, Some(&rid)) {
+        state.metrics.error_counts.inc();
+        return match e {
+            crate::auth::AuthError::MissingToken => (StatusCode::UNAUTHORIZED, "missing token").into_response(),
+            crate::auth::AuthError::InvalidToken => (StatusCode::FORBIDDEN, "invalid token").into_response(),
+            _ => (StatusCode::BAD_REQUEST, "auth error").into_response(),
+        };
+    }
+    // Posting through the socket needs the same authorization as POST
+    let can_post = state.auth.validate(token, None).is_ok();
+
+    ws.on_upgrade(move |socket| mailbox_socket(state, socket, ip, rid, can_post))
+}
+
+async fn mailbox_socket(state: AppState, socket: WebSocket, ip: IpAddr, rid: Vec<u8>, can_post: bool) {
+    let notify = match state.mailboxes.entry(rid.clone()) {
+        dashmap::mapref::entry::Entry::Occupied(o) => o.get().notify.clone(),
+        dashmap::mapref::entry::Entry::Vacant(v) => {
+            let mailbox = crate::mailbox::Mailbox::new();
+            let notify = mailbox.notify.clone();
+            v.insert(mailbox);
+            notify
+        }
+    };
+    let mut shutdown = state.shutdown.clone();
+    let (mut sink, mut stream) = socket.split();
+
+    loop {
+        // Register for the next post before draining so none is missed
+        let notified = notify.notified();
+        tokio::pin!(notified);
+        notified.as_mut().enable();
+
+        while let Some(data) = take_message(&state, &rid) {
+            if sink.send(Message::Binary(data.to_vec())).await.is_err() {
+                return;
+            }
+        }
+
+        tokio::select! {
+            _ = &mut notified => {}
+            incoming = stream.next() => match incoming {
+                Some(Ok(Message::Binary(frame))) => {
+                    if !can_post || frame.len() <= 32 {
+                        state.metrics.error_counts.inc();
+                        continue;
+                    }
+                    if let Err(retry_after) = state.rate_limiter.check_post(ip).await {
+                        state.metrics.rate_limit_hits.inc();
+                        debug!("mailbox socket post rate limited, retry after {}s", retry_after);
+                        continue;
+                    }
+                    let envelope = Bytes::copy_from_slice(&frame[32..]);
+                    if let Err(e) = enqueue(&state, frame[..32].to_vec(), envelope, Some(ip)) {
+                        debug!("mailbox socket post dropped: {}", e);
+                    }
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                Some(Ok(_)) => {}
+            },
+            changed = shutdown.changed() => {
+                if changed.is_err() || *shutdown.borrow() {
+                    let _ = sink.send(Message::Close(None)).await;
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// Pop the next queued envelope for `rid`, if any.
+fn take_message(state: &AppState, rid: &[u8]) -> Option<Bytes> {
+    let data = state.mailboxes.get_mut(rid)?.value_mut().get()?.data.clone();
+    let total: usize = state.mailboxes.iter().map(|e| e.value().queue_length()).sum();
+    state.metrics.messages_delivered.inc();
+    state.metrics.total_messages.set(total as f64);
+    Some(data)
+}
+
 // GET /health
 pub async fn get_health(State(_state): State<AppState>) -> Response {
     use serde_json::json;
@@ -311,3 +533,102 @@ pub async fn get_metrics(State(state): State<AppState>) -> Response {
     )
         .into_response()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::Router;
+    use tokio_tungstenite::{connect_async, tungstenite::Message as WsMessage};
+
+    fn test_state() -> (AppState, tokio::sync::watch::Sender<bool>) {
+        let config = crate::config::ServerConfig::default();
+        let (shutdown_tx, shutdown) = tokio::sync::watch::channel(false);
+        let state = AppState {
+            mailboxes: Arc::new(dashmap::DashMap::new()),
+            rate_limiter: RateLimiter::new(config.rate_limit.clone()),
+            auth: AuthConfig::default(),
+            metrics: Arc::new(MailboxMetrics::new().unwrap()),
+            mesh: Arc::new(MeshRelay::new(&[]).unwrap()),
+            config,
+            shutdown,
+        };
+        (state, shutdown_tx)
+    }
+
+    #[tokio::test]
+    async fn test_mailbox_socket_delivers_and_accepts_envelopes() {
+        let (state, _shutdown_tx) = test_state();
+        let app = Router::new()
+            .route("/v1/mailbox/:rid_hex/ws", axum::routing::get(mailbox_ws))
+            .with_state(state.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await
+        });
+
+        // Queued before the socket opens
+        enqueue(&state, vec![1; 32], Bytes::from_static(b"first"), None).unwrap();
+
+        let url = format!("ws://{}/v1/mailbox/{}/ws", addr, hex::encode([1u8; 32]));
+        let (mut socket, _) = connect_async(url).await.unwrap();
+        let next = |msg: Option<Result<WsMessage, _>>| match msg {
+            Some(Ok(WsMessage::Binary(data))) => data,
+            other => panic!("expected binary message, got {:?}", other),
+        };
+        assert_eq!(next(socket.next().await), b"first".to_vec());
+
+        // Queued while the socket is open
+        enqueue(&state, vec![1; 32], Bytes::from_static(b"second"), None).unwrap();
+        assert_eq!(next(socket.next().await), b"second".to_vec());
+
+        // Sent by the client to another mailbox
+        let mut frame = vec![2u8; 32];
+        frame.extend_from_slice(b"outgoing");
+        socket.send(WsMessage::Binary(frame)).await.unwrap();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while take_message(&state, &[2u8; 32]).is_none() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("envelope was not queued");
+    }
+
+    #[tokio::test]
+    async fn test_get_reports_the_posting_address() {
+        let (state, _shutdown_tx) = test_state();
+        let rid_hex = hex::encode([3u8; 32]);
+        let sender: SocketAddr = "198.51.100.7:40000".parse().unwrap();
+        let poller: SocketAddr = "203.0.113.9:40001".parse().unwrap();
+
+        let posted = post_mailbox(
+            State(state.clone()),
+            ConnectInfo(sender),
+            Path(rid_hex.clone()),
+            HeaderMap::new(),
+            Bytes::from_static(b"pair request"),
+        )
+        .await;
+        assert_eq!(posted.status(), StatusCode::ACCEPTED);
+
+        let get = || {
+            get_mailbox(
+                State(state.clone()),
+                ConnectInfo(poller),
+                Path(rid_hex.clone()),
+                Query(HashMap::from([("wait_ms".to_string(), "0".to_string())])),
+                HeaderMap::new(),
+            )
+        };
+        let delivered = get().await;
+        assert_eq!(delivered.status(), StatusCode::OK);
+        assert_eq!(delivered.headers()[SOURCE_HEADER], "198.51.100.7");
+
+        // Mesh-relayed messages have no known sender
+        enqueue(&state, vec![3; 32], Bytes::from_static(b"relayed"), None).unwrap();
+        let delivered = get().await;
+        assert_eq!(delivered.status(), StatusCode::OK);
+        assert!(!delivered.headers().contains_key(SOURCE_HEADER));
+    }
+}
@@ -34,6 +34,11 @@ pub struct ServerConfig {
     
     // Graceful shutdown
     pub shutdown_timeout_secs: u64,
+
+    // Mesh nodes (base URLs) that frames for mailboxes not hosted here
+    // are passed on to
+    #[serde(default)]
+    pub mesh_peers: Vec<String>,
 }
 
 impl Default for ServerConfig {
@@ -54,6 +59,7 @@ impl Default for ServerConfig {
             allowlist: Vec::new(),
             blocklist: Vec::new(),
             shutdown_timeout_secs: 30,
+            mesh_peers: Vec::new(),
         }
     }
 }
@@ -85,6 +91,10 @@ impl ServerConfig {
         if let Ok(tokens) = std::env::var("ZRC_SERVER_TOKENS") {
             config.server_tokens = tokens.split(',').map(|s| s.trim().to_string()).collect();
         }
+
+        if let Ok(peers) = std::env::var("ZRC_MESH_PEERS") {
+            config.mesh_peers = peers.split(',').map(|s| s.trim().to_string()).collect();
+        }
         
         Ok(config)
     }
@@ -2,6 +2,7 @@ pub mod api;
 pub mod auth;
 pub mod config;
 pub mod mailbox;
+pub mod mesh;
 pub mod metrics;
 pub mod rate_limit;
 pub mod server;
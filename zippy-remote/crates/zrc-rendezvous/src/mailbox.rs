@@ -1,6 +1,7 @@
 use bytes::Bytes;
 use std::{
     collections::VecDeque,
+    net::IpAddr,
     sync::Arc,
     time::{Duration, Instant},
 };
@@ -11,6 +12,8 @@ pub struct Message {
     pub data: Bytes,
     pub sequence: u64,
     pub timestamp: Instant,
+    /// Address the message was posted from; `None` when relayed over the mesh
+    pub source: Option<IpAddr>,
 }
 
 #[derive(Debug)]
@@ -38,6 +41,17 @@ impl Mailbox {
     }
 
     pub fn post(&mut self, data: Bytes, max_queue_len: usize, max_message_size: usize) -> Result<u64, MailboxError> {
+        self.post_from(data, None, max_queue_len, max_message_size)
+    }
+
+    /// Queue a message posted from `source`.
+    pub fn post_from(
+        &mut self,
+        data: Bytes,
+        source: Option<IpAddr>,
+        max_queue_len: usize,
+        max_message_size: usize,
+    ) -> Result<u64, MailboxError> {
         if data.len() > max_message_size {
             return Err(MailboxError::MessageTooLarge);
         }
@@ -53,6 +67,7 @@ impl Mailbox {
             data,
             sequence,
             timestamp: Instant::now(),
+            source,
         };
 
         self.messages.push_back(message);
@@ -0,0 +1,128 @@
+//! Mesh frame relay.
+//!
+//! Mesh nodes hand each other frames of `ttl (1 byte) || recipient_id (32
+//! bytes) || envelope_bytes` (the `zrc_transport::mesh` wire format) at
+//! `POST /v1/mesh/frames`. A frame for a mailbox hosted here is queued like a
+//! regular post; any other frame is passed on to the configured mesh peers
+//! with the TTL lowered by one.
+//!
+//! A mailbox counts as hosted once it exists on this server, i.e. after its
+//! recipient has polled it or mail was posted to it. A server with no mesh
+//! peers hosts every mailbox.
+
+use bytes::Bytes;
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tracing::debug;
+use zrc_transport::mesh::SeenCache;
+pub use zrc_transport::mesh::MeshFrame;
+
+/// What to do with a received frame.
+#[derive(Debug, PartialEq, Eq)]
+pub enum MeshRoute {
+    /// Queue the envelope in the local mailbox.
+    Store,
+    /// Pass this copy, with one hop fewer, on to the mesh peers.
+    Forward(MeshFrame),
+    /// Already handled, or out of hops.
+    Drop,
+}
+
+pub struct MeshRelay {
+    frame_urls: Vec<String>,
+    client: reqwest::Client,
+    seen: Mutex<SeenCache>,
+}
+
+impl MeshRelay {
+    /// Relay forwarding to the mesh nodes at `peers` (base URLs).
+    pub fn new(peers: &[String]) -> anyhow::Result<Self> {
+        let client = reqwest::Client::builder()
+            .use_rustls_tls()
+            .timeout(Duration::from_secs(10))
+            .build()?;
+        Ok(Self {
+            frame_urls: peers
+                .iter()
+                .map(|url| format!("{}/v1/mesh/frames", url.trim_end_matches('/')))
+                .collect(),
+            client,
+            seen: Mutex::new(SeenCache::default()),
+        })
+    }
+
+    /// Decide what to do with `frame`. `has_mailbox` is whether the
+    /// recipient's mailbox exists on this server.
+    pub fn route(&self, frame: &MeshFrame, has_mailbox: bool) -> MeshRoute {
+        if frame.ttl == 0 {
+            return MeshRoute::Drop;
+        }
+        let id = frame.envelope_id();
+        let mut seen = self.seen.lock().unwrap();
+
+        if has_mailbox || self.frame_urls.is_empty() {
+            // Stored envelopes are delivered once, whatever TTL a repeat has
+            if seen.contains(&id) {
+                return MeshRoute::Drop;
+            }
+            seen.insert(id, frame.ttl);
+            return MeshRoute::Store;
+        }
+
+        if !seen.insert(id, frame.ttl) {
+            return MeshRoute::Drop;
+        }
+        frame.next_hop().map_or(MeshRoute::Drop, MeshRoute::Forward)
+    }
+
+    /// Send `frame` to every mesh peer in the background.
+    pub fn forward(self: &Arc<Self>, frame: MeshFrame) {
+        let body = Bytes::from(frame.encode());
+        for url in &self.frame_urls {
+            let relay = Arc::clone(self);
+            let url = url.clone();
+            let body = body.clone();
+            tokio::spawn(async move {
+                match relay.client.post(&url).body(body).send().await {
+                    Ok(resp) if resp.status() == reqwest::StatusCode::ACCEPTED => {}
+                    Ok(resp) => debug!("mesh forward to {} refused: status={}", url, resp.status()),
+                    Err(e) => debug!("mesh forward to {} failed: {}", url, e),
+                }
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(ttl: u8) -> MeshFrame {
+        MeshFrame::new(ttl, [7; 32], b"sealed")
+    }
+
+    #[test]
+    fn test_hosted_mailbox_stores_once() {
+        let relay = MeshRelay::new(&["http://peer.example".to_string()]).unwrap();
+        assert_eq!(relay.route(&frame(3), true), MeshRoute::Store);
+        assert_eq!(relay.route(&frame(4), true), MeshRoute::Drop);
+    }
+
+    #[test]
+    fn test_forwards_with_one_hop_fewer() {
+        let relay = MeshRelay::new(&["http://peer.example".to_string()]).unwrap();
+        assert_eq!(relay.route(&frame(1), false), MeshRoute::Drop);
+        // A copy with more hops left goes out again; a repeat does not
+        assert_eq!(relay.route(&frame(3), false), MeshRoute::Forward(frame(2)));
+        assert_eq!(relay.route(&frame(3), false), MeshRoute::Drop);
+        assert_eq!(relay.route(&frame(0), true), MeshRoute::Drop);
+    }
+
+    #[test]
+    fn test_without_peers_every_mailbox_is_hosted() {
+        let relay = MeshRelay::new(&[]).unwrap();
+        assert_eq!(relay.route(&frame(1), false), MeshRoute::Store);
+    }
+}
@@ -7,6 +7,7 @@ use crate::api::AppState;
 use crate::auth::AuthConfig;
 use crate::config::ServerConfig;
 use crate::mailbox::MailboxMap;
+use crate::mesh::MeshRelay;
 use crate::metrics::MailboxMetrics;
 use crate::rate_limit::RateLimiter;
 
@@ -72,12 +73,15 @@ impl RendezvousServer {
             auth: self.auth.clone(),
             metrics: Arc::clone(&self.metrics),
             config: self.config.clone(),
+            mesh: Arc::new(MeshRelay::new(&self.config.mesh_peers)?),
             shutdown: self.shutdown_tx.subscribe(),
         };
 
         // Build router
         let app = Router::new()
             .route("/v1/mailbox/:rid_hex", axum::routing::post(crate::api::post_mailbox).get(crate::api::get_mailbox))
+            .route("/v1/mailbox/:rid_hex/ws", axum::routing::get(crate::api::mailbox_ws))
+            .route("/v1/mesh/frames", axum::routing::post(crate::api::post_mesh_frame))
             .route("/health", axum::routing::get(crate::api::get_health))
             .route("/metrics", axum::routing::get(crate::api::get_metrics))
             .with_state(state);
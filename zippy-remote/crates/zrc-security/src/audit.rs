@@ -1,15 +1,37 @@
 //! Audit logging with cryptographic signing.
 //!
+//! Entries form a hash chain: each carries a sequence number and the
+//! SHA-256 of the previous entry's canonical bytes (32 zero bytes for the
+//! first). A range of entries can be exported as a [`SignedAuditLog`], whose
+//! device signature covers the hash preceding the range, the entry count and
+//! the hash of the last entry, so [`verify_signed_log`] detects any inserted,
+//! reordered, altered or deleted entry.
+//!
+//! A logger picks the chain up from the last entry its writer already holds,
+//! so restarting the process does not open a new chain that would hide
+//! entries deleted or truncated while it was down.
+//!
 //! Requirements: 9.1, 9.2, 9.3, 9.4, 9.5, 9.6, 9.7
 
-use std::io::Write;
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Write};
+use std::ops::Range;
 use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
 use chrono::{DateTime, Utc};
+use constant_time_eq::constant_time_eq;
 use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use uuid::Uuid;
 use crate::error::SecurityError;
 
+/// Entries kept in memory for [`AuditLogger::export_signed_log`].
+pub const DEFAULT_AUDIT_RETENTION: usize = 10_000;
+
+/// Domain separator for the signature over an exported log.
+const EXPORT_DOMAIN: &[u8] = b"zrc_audit_export_v1";
+
 /// Security event types for audit logging.
 ///
 /// Requirements: 9.1, 9.2, 9.3, 9.4, 9.5
@@ -65,6 +87,17 @@ pub enum SecurityEvent {
         source: String,
         operation: String,
     },
+    /// User-entered SAS did not match
+    SasMismatch {
+        device_id: String,
+        failures: u32,
+        exhausted: bool,
+    },
+    /// Replay window evicted entries accepted within its protection window
+    ReplayWindowUndersized {
+        capacity: usize,
+        evicted_age_secs: u64,
+    },
 }
 
 impl SecurityEvent {
@@ -81,6 +114,8 @@ impl SecurityEvent {
             SecurityEvent::IdentityMismatch { .. } => "identity_mismatch",
             SecurityEvent::ReplayAttempt { .. } => "replay_attempt",
             SecurityEvent::RateLimitExceeded { .. } => "rate_limit_exceeded",
+            SecurityEvent::SasMismatch { .. } => "sas_mismatch",
+            SecurityEvent::ReplayWindowUndersized { .. } => "replay_window_undersized",
         }
     }
 
@@ -97,6 +132,8 @@ impl SecurityEvent {
             SecurityEvent::IdentityMismatch { peer_id, .. } => Some(peer_id.clone()),
             SecurityEvent::ReplayAttempt { .. } => None,
             SecurityEvent::RateLimitExceeded { source, .. } => Some(source.clone()),
+            SecurityEvent::SasMismatch { .. } => None,
+            SecurityEvent::ReplayWindowUndersized { .. } => None,
         }
     }
 
@@ -113,6 +150,10 @@ impl SecurityEvent {
             SecurityEvent::IdentityMismatch { peer_id, .. } => Some(peer_id.clone()),
             SecurityEvent::ReplayAttempt { sequence, .. } => Some(format!("seq:{}", sequence)),
             SecurityEvent::RateLimitExceeded { operation, .. } => Some(operation.clone()),
+            SecurityEvent::SasMismatch { device_id, .. } => Some(device_id.clone()),
+            SecurityEvent::ReplayWindowUndersized { capacity, .. } => {
+                Some(format!("capacity:{}", capacity))
+            }
         }
     }
 
@@ -139,6 +180,12 @@ pub struct AuditEntry {
     pub target: Option<String>,
     /// Event details
     pub details: serde_json::Value,
+    /// Position in the logger's hash chain
+    #[serde(default)]
+    pub sequence: u64,
+    /// SHA-256 of the previous entry's canonical bytes
+    #[serde(default)]
+    pub prev_hash: Vec<u8>,
     /// Cryptographic signature
     pub signature: Vec<u8>,
 }
@@ -161,12 +208,102 @@ impl AuditEntry {
         serde_json::to_vec(&entry)
             .unwrap_or_else(|_| b"{}".to_vec())
     }
+
+    /// Hash linking the next entry to this one.
+    pub fn chain_hash(&self) -> [u8; 32] {
+        Sha256::digest(self.to_canonical_bytes()).into()
+    }
+}
+
+/// A range of chained audit entries signed with the device key on export.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedAuditLog {
+    /// Sequence number of the first entry
+    pub first_sequence: u64,
+    /// Chain hash preceding the first entry
+    pub prev_hash: Vec<u8>,
+    /// The exported entries, in sequence order
+    pub entries: Vec<AuditEntry>,
+    /// Chain hash of the last entry (`prev_hash` if empty)
+    pub head_hash: Vec<u8>,
+    /// Ed25519 public key of the exporting device
+    pub signer_pub: Vec<u8>,
+    /// Signature over `export_signing_bytes`
+    pub signature: Vec<u8>,
+}
+
+/// Bytes signed on export:
+/// `SHA-256("zrc_audit_export_v1" || first_sequence || count || prev_hash || head_hash)`
+/// with integers as u64 big-endian.
+fn export_signing_bytes(first_sequence: u64, count: u64, prev_hash: &[u8], head_hash: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(EXPORT_DOMAIN);
+    hasher.update(first_sequence.to_be_bytes());
+    hasher.update(count.to_be_bytes());
+    hasher.update(prev_hash);
+    hasher.update(head_hash);
+    hasher.finalize().into()
+}
+
+fn audit_error(reason: impl Into<String>) -> SecurityError {
+    SecurityError::AuditError(reason.into())
+}
+
+fn verify_with(key: &VerifyingKey, data: &[u8], signature: &[u8]) -> Result<(), SecurityError> {
+    let sig_bytes: [u8; 64] = signature
+        .try_into()
+        .map_err(|_| audit_error("Invalid signature length"))?;
+    key.verify_strict(data, &Signature::from_bytes(&sig_bytes))
+        .map_err(|e| audit_error(format!("Signature verification failed: {}", e)))
+}
+
+/// Verify an exported log against the device's public key.
+///
+/// Checks the export signature, then walks the chain: sequence numbers must
+/// be consecutive, each entry must link to the hash of its predecessor and
+/// carry a valid per-entry signature, and the final hash must equal the
+/// signed head.
+///
+/// Requirements: 9.7
+pub fn verify_signed_log(log: &SignedAuditLog, device_key: &VerifyingKey) -> Result<(), SecurityError> {
+    if !constant_time_eq(&log.signer_pub, device_key.as_bytes()) {
+        return Err(audit_error("log signed by an unexpected key"));
+    }
+    let signed = export_signing_bytes(
+        log.first_sequence,
+        log.entries.len() as u64,
+        &log.prev_hash,
+        &log.head_hash,
+    );
+    verify_with(device_key, &signed, &log.signature)?;
+
+    let mut prev = log.prev_hash.clone();
+    for (i, entry) in log.entries.iter().enumerate() {
+        if entry.sequence != log.first_sequence + i as u64 {
+            return Err(audit_error(format!("entry {} out of sequence", i)));
+        }
+        if !constant_time_eq(&entry.prev_hash, &prev) {
+            return Err(audit_error(format!("chain broken at sequence {}", entry.sequence)));
+        }
+        verify_with(device_key, &entry.to_canonical_bytes_without_signature(), &entry.signature)?;
+        prev = entry.chain_hash().to_vec();
+    }
+    if !constant_time_eq(&prev, &log.head_hash) {
+        return Err(audit_error("chain does not end at the signed head"));
+    }
+    Ok(())
 }
 
 /// Trait for writing audit log entries.
 pub trait AuditLogWriter: Send + Sync {
     /// Write an audit entry.
     fn write(&self, entry: &AuditEntry) -> Result<(), SecurityError>;
+
+    /// The most recently written entry, for continuing the chain after a
+    /// restart. Writers that cannot read back start a new chain.
+    fn last_entry(&self) -> Result<Option<AuditEntry>, SecurityError> {
+        Ok(None)
+    }
 }
 
 /// File-based audit log writer.
@@ -204,6 +341,71 @@ impl AuditLogWriter for FileAuditLogWriter {
 
         Ok(())
     }
+
+    fn last_entry(&self) -> Result<Option<AuditEntry>, SecurityError> {
+        let file = match std::fs::File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(SecurityError::AuditError(format!("Failed to open log file: {}", e))),
+        };
+
+        let mut last = None;
+        for line in BufReader::new(file).lines() {
+            let line = line
+                .map_err(|e| SecurityError::AuditError(format!("Failed to read log file: {}", e)))?;
+            if !line.trim().is_empty() {
+                last = Some(line);
+            }
+        }
+        // An unparseable tail must not silently restart the chain
+        last.map(|line| {
+            serde_json::from_str(&line)
+                .map_err(|e| SecurityError::AuditError(format!("Failed to parse last entry: {}", e)))
+        })
+        .transpose()
+    }
+}
+
+/// Audit log writer that keeps entries in memory, for tests and tools that
+/// inspect what was logged. Clones share the same entries.
+#[derive(Clone, Default)]
+pub struct MemoryAuditLogWriter {
+    entries: Arc<Mutex<Vec<AuditEntry>>>,
+}
+
+impl MemoryAuditLogWriter {
+    /// Create an empty in-memory writer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Entries written so far, oldest first.
+    pub fn entries(&self) -> Vec<AuditEntry> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+impl AuditLogWriter for MemoryAuditLogWriter {
+    fn write(&self, entry: &AuditEntry) -> Result<(), SecurityError> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(entry.clone());
+        Ok(())
+    }
+
+    fn last_entry(&self) -> Result<Option<AuditEntry>, SecurityError> {
+        Ok(self.entries.lock().unwrap_or_else(|e| e.into_inner()).last().cloned())
+    }
+}
+
+/// Position of the next entry in the hash chain, plus recent entries.
+struct AuditChain {
+    next_sequence: u64,
+    last_hash: [u8; 32],
+    /// Hash preceding the oldest retained entry
+    retained_prev_hash: [u8; 32],
+    retained: VecDeque<AuditEntry>,
 }
 
 /// Audit logger with cryptographic signing.
@@ -212,25 +414,48 @@ impl AuditLogWriter for FileAuditLogWriter {
 pub struct AuditLogger {
     signing_key: SigningKey,
     log_writer: Box<dyn AuditLogWriter>,
+    retention: usize,
+    chain: Mutex<AuditChain>,
 }
 
 impl AuditLogger {
-    /// Create a new audit logger.
+    /// Create a new audit logger, continuing the chain after the last entry
+    /// already held by `log_writer`.
     ///
     /// # Arguments
     /// * `signing_key` - Ed25519 signing key for signing entries
     /// * `log_writer` - Writer for audit entries
-    pub fn new(signing_key: SigningKey, log_writer: Box<dyn AuditLogWriter>) -> Self {
-        Self {
+    pub fn new(signing_key: SigningKey, log_writer: Box<dyn AuditLogWriter>) -> Result<Self, SecurityError> {
+        let (next_sequence, last_hash) = match log_writer.last_entry()? {
+            Some(last) => (last.sequence + 1, last.chain_hash()),
+            None => (0, [0u8; 32]),
+        };
+        Ok(Self {
             signing_key,
             log_writer,
-        }
+            retention: DEFAULT_AUDIT_RETENTION,
+            chain: Mutex::new(AuditChain {
+                next_sequence,
+                last_hash,
+                retained_prev_hash: last_hash,
+                retained: VecDeque::new(),
+            }),
+        })
+    }
+
+    /// Keep at most `retention` recent entries available for export.
+    pub fn with_retention(mut self, retention: usize) -> Self {
+        self.retention = retention.max(1);
+        self
     }
 
     /// Log a security event.
     ///
     /// Requirements: 9.1, 9.2, 9.3, 9.4, 9.5
     pub fn log(&self, event: SecurityEvent) -> Result<(), SecurityError> {
+        // Held until the entry is written so the chain matches write order
+        let mut chain = self.chain.lock().unwrap_or_else(|e| e.into_inner());
+
         let entry = AuditEntry {
             id: Uuid::new_v4(),
             timestamp: Utc::now(),
@@ -238,6 +463,8 @@ impl AuditLogger {
             actor: event.actor(),
             target: event.target(),
             details: event.details(),
+            sequence: chain.next_sequence,
+            prev_hash: chain.last_hash.to_vec(),
             signature: Vec::new(), // Filled below
         };
 
@@ -253,9 +480,63 @@ impl AuditLogger {
         // Write to log
         self.log_writer.write(&signed_entry)?;
 
+        chain.next_sequence += 1;
+        chain.last_hash = signed_entry.chain_hash();
+        chain.retained.push_back(signed_entry);
+        while chain.retained.len() > self.retention {
+            if let Some(dropped) = chain.retained.pop_front() {
+                chain.retained_prev_hash = dropped.chain_hash();
+            }
+        }
+
         Ok(())
     }
 
+    /// Export the retained entries with sequence numbers in `range`, signed
+    /// with the device key.
+    ///
+    /// The range is clipped to the entries logged so far; it fails if its
+    /// start has already dropped out of retention.
+    ///
+    /// Requirements: 9.6
+    pub fn export_signed_log(&self, range: Range<u64>) -> Result<SignedAuditLog, SecurityError> {
+        let chain = self.chain.lock().unwrap_or_else(|e| e.into_inner());
+        let oldest = chain.next_sequence - chain.retained.len() as u64;
+        if range.start < oldest {
+            return Err(audit_error(format!(
+                "entries before sequence {} are no longer retained",
+                oldest
+            )));
+        }
+        let start = range.start.min(chain.next_sequence);
+        let end = range.end.clamp(start, chain.next_sequence);
+
+        let skip = (start - oldest) as usize;
+        let entries: Vec<AuditEntry> = chain
+            .retained
+            .iter()
+            .skip(skip)
+            .take((end - start) as usize)
+            .cloned()
+            .collect();
+        let prev_hash = match skip {
+            0 => chain.retained_prev_hash,
+            n => chain.retained[n - 1].chain_hash(),
+        };
+        drop(chain);
+
+        let head_hash = entries.last().map_or(prev_hash, |e| e.chain_hash());
+        let signed = export_signing_bytes(start, entries.len() as u64, &prev_hash, &head_hash);
+        Ok(SignedAuditLog {
+            first_sequence: start,
+            prev_hash: prev_hash.to_vec(),
+            entries,
+            head_hash: head_hash.to_vec(),
+            signer_pub: self.signing_key.verifying_key().to_bytes().to_vec(),
+            signature: self.signing_key.sign(&signed).to_bytes().to_vec(),
+        })
+    }
+
     /// Verify audit log integrity.
     ///
     /// Requirements: 9.7
@@ -300,7 +581,7 @@ mod tests {
         let _ = fs::remove_file(&log_path);
 
         let writer = Box::new(FileAuditLogWriter::new(log_path.clone()));
-        let logger = AuditLogger::new(signing_key.clone(), writer);
+        let logger = AuditLogger::new(signing_key.clone(), writer).unwrap();
 
         let event = SecurityEvent::AuthenticationAttempt {
             success: true,
@@ -317,7 +598,7 @@ mod tests {
     fn test_audit_verification() {
         let signing_key = SigningKey::generate(&mut OsRng);
         let writer = Box::new(FileAuditLogWriter::new(PathBuf::from("/dev/null")));
-        let logger = AuditLogger::new(signing_key.clone(), writer);
+        let logger = AuditLogger::new(signing_key.clone(), writer).unwrap();
 
         let event = SecurityEvent::SessionStarted {
             session_id: "test_session".to_string(),
@@ -333,6 +614,8 @@ mod tests {
             actor: event.actor(),
             target: event.target(),
             details: event.details(),
+            sequence: 0,
+            prev_hash: Vec::new(),
             signature: Vec::new(),
         };
 
@@ -346,4 +629,128 @@ mod tests {
         // Verify the entry
         assert!(logger.verify_log(&[signed_entry]).is_ok());
     }
+
+    fn logger_with_entries(count: usize) -> AuditLogger {
+        let writer = Box::new(FileAuditLogWriter::new(PathBuf::from("/dev/null")));
+        let logger = AuditLogger::new(SigningKey::generate(&mut OsRng), writer).unwrap();
+        for i in 0..count {
+            let event = SecurityEvent::AuthenticationAttempt {
+                success: i % 2 == 0,
+                source: format!("source-{}", i),
+            };
+            logger.log(event).unwrap();
+        }
+        logger
+    }
+
+    #[test]
+    fn test_signed_log_export_verifies() {
+        let logger = logger_with_entries(5);
+        let key = logger.signing_key.verifying_key();
+
+        let full = logger.export_signed_log(0..u64::MAX).unwrap();
+        assert_eq!(full.entries.len(), 5);
+        assert!(verify_signed_log(&full, &key).is_ok());
+
+        // A sub-range is anchored to the hash of the entry before it
+        let middle = logger.export_signed_log(2..4).unwrap();
+        assert_eq!(middle.first_sequence, 2);
+        assert_eq!(middle.prev_hash, full.entries[1].chain_hash().to_vec());
+        assert!(verify_signed_log(&middle, &key).is_ok());
+
+        let other = SigningKey::generate(&mut OsRng).verifying_key();
+        assert!(verify_signed_log(&full, &other).is_err());
+    }
+
+    #[test]
+    fn test_signed_log_detects_tampering() {
+        let logger = logger_with_entries(4);
+        let key = logger.signing_key.verifying_key();
+        let log = logger.export_signed_log(0..4).unwrap();
+
+        // Mutating any field of any record breaks verification
+        for i in 0..log.entries.len() {
+            let mut tampered = log.clone();
+            tampered.entries[i].target = Some("forged".to_string());
+            assert!(verify_signed_log(&tampered, &key).is_err(), "target of {}", i);
+
+            let mut tampered = log.clone();
+            tampered.entries[i].timestamp += chrono::Duration::seconds(1);
+            assert!(verify_signed_log(&tampered, &key).is_err(), "timestamp of {}", i);
+        }
+
+        let mut reordered = log.clone();
+        reordered.entries.swap(1, 2);
+        assert!(verify_signed_log(&reordered, &key).is_err());
+
+        let mut deleted = log.clone();
+        deleted.entries.remove(1);
+        assert!(verify_signed_log(&deleted, &key).is_err());
+
+        let mut truncated = log.clone();
+        truncated.entries.pop();
+        assert!(verify_signed_log(&truncated, &key).is_err());
+
+        let mut inserted = log.clone();
+        let extra = inserted.entries[0].clone();
+        inserted.entries.insert(1, extra);
+        assert!(verify_signed_log(&inserted, &key).is_err());
+    }
+
+    #[test]
+    fn test_signed_log_respects_retention() {
+        let writer = Box::new(FileAuditLogWriter::new(PathBuf::from("/dev/null")));
+        let logger = AuditLogger::new(SigningKey::generate(&mut OsRng), writer)
+            .unwrap()
+            .with_retention(2);
+        for i in 0..4 {
+            logger
+                .log(SecurityEvent::ReplayAttempt { sequence: i })
+                .unwrap();
+        }
+        let key = logger.signing_key.verifying_key();
+
+        assert!(logger.export_signed_log(0..4).is_err());
+        let log = logger.export_signed_log(2..4).unwrap();
+        assert_eq!(log.entries.len(), 2);
+        assert!(verify_signed_log(&log, &key).is_ok());
+    }
+
+    #[test]
+    fn test_chain_continues_after_restart() {
+        let signing_key = SigningKey::generate(&mut OsRng);
+        let log_path = std::env::temp_dir().join(format!("test_audit_restart_{}.log", Uuid::new_v4()));
+        let read_log = |path: &PathBuf| -> Vec<AuditEntry> {
+            fs::read_to_string(path)
+                .unwrap()
+                .lines()
+                .map(|line| serde_json::from_str(line).unwrap())
+                .collect()
+        };
+
+        let first = AuditLogger::new(signing_key.clone(), Box::new(FileAuditLogWriter::new(log_path.clone()))).unwrap();
+        for i in 0..3 {
+            first.log(SecurityEvent::ReplayAttempt { sequence: i }).unwrap();
+        }
+        drop(first);
+
+        let second = AuditLogger::new(signing_key.clone(), Box::new(FileAuditLogWriter::new(log_path.clone()))).unwrap();
+        second.log(SecurityEvent::ReplayAttempt { sequence: 3 }).unwrap();
+
+        let entries = read_log(&log_path);
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries[3].sequence, 3);
+        assert_eq!(entries[3].prev_hash, entries[2].chain_hash().to_vec());
+
+        // Only the entries logged since the restart are retained for export
+        assert!(second.export_signed_log(0..4).is_err());
+        let log = second.export_signed_log(3..4).unwrap();
+        assert_eq!(log.prev_hash, entries[2].chain_hash().to_vec());
+        assert!(verify_signed_log(&log, &signing_key.verifying_key()).is_ok());
+
+        // A tail that cannot be read back refuses to start a fresh chain
+        fs::write(&log_path, "not json\n").unwrap();
+        assert!(AuditLogger::new(signing_key, Box::new(FileAuditLogWriter::new(log_path.clone()))).is_err());
+        let _ = fs::remove_file(&log_path);
+    }
 }
@@ -2,9 +2,12 @@
 //!
 //! Requirements: 4.1, 4.2, 4.4, 4.5, 4.6
 
-use zrc_proto::v1::{CipherSuiteV1, KexSuiteV1, SigTypeV1};
+use constant_time_eq::constant_time_eq;
+use sha2::{Digest, Sha256};
+use zrc_proto::v1::{CipherSuiteV1, KexSuiteV1, SigTypeV1, TransportV1};
 use crate::error::SecurityError;
 use crate::audit::{AuditLogger, SecurityEvent};
+use crate::sas::SessionTranscript;
 
 /// Minimum required cipher suite version.
 pub const MIN_CIPHER_SUITE: CipherSuiteV1 = CipherSuiteV1::HpkeX25519HkdfSha256Chacha20poly1305;
@@ -168,6 +171,165 @@ impl Default for HandshakeAlgorithmVerifier {
     }
 }
 
+/// Transports and algorithms one side offered, in preference order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NegotiationOffer {
+    pub transports: Vec<TransportV1>,
+    pub cipher_suites: Vec<CipherSuiteV1>,
+    pub kex_suites: Vec<KexSuiteV1>,
+    pub sig_types: Vec<SigTypeV1>,
+}
+
+/// Parameters selected from the two offers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NegotiatedParams {
+    pub transport: TransportV1,
+    pub cipher_suite: CipherSuiteV1,
+    pub kex_suite: KexSuiteV1,
+    pub sig_type: SigTypeV1,
+}
+
+/// Label under which the negotiation hash is bound into the session transcript.
+pub const NEGOTIATION_TRANSCRIPT_LABEL: &[u8] = b"negotiation";
+
+/// Guards transport and algorithm negotiation against option stripping.
+///
+/// Each side hashes the offers and selection exactly as it saw them and
+/// binds the hash into its authenticated session transcript. A MITM that
+/// removes stronger options from an offer changes one side's view, so the
+/// hashes (and the SAS) no longer agree. `verify` also checks locally that
+/// the selection is the best option both sides offered: the initiator's
+/// first transport the responder supports, and the strongest common
+/// algorithms.
+///
+/// Requirements: 4.4, 4.5
+pub struct DowngradeGuard {
+    checker: AlgorithmVersionChecker,
+}
+
+impl DowngradeGuard {
+    /// Create a guard with the default minimum algorithms.
+    pub fn new() -> Self {
+        Self::with_checker(AlgorithmVersionChecker::default())
+    }
+
+    /// Create a guard with custom minimum algorithms.
+    pub fn with_checker(checker: AlgorithmVersionChecker) -> Self {
+        Self { checker }
+    }
+
+    /// Transcript hash over both offers and the selection.
+    pub fn transcript_hash(
+        initiator: &NegotiationOffer,
+        responder: &NegotiationOffer,
+        selected: &NegotiatedParams,
+    ) -> [u8; 32] {
+        fn put_list(hasher: &mut Sha256, values: impl ExactSizeIterator<Item = i32>) {
+            hasher.update((values.len() as u32).to_be_bytes());
+            for value in values {
+                hasher.update(value.to_be_bytes());
+            }
+        }
+        fn put_offer(hasher: &mut Sha256, offer: &NegotiationOffer) {
+            put_list(hasher, offer.transports.iter().map(|t| *t as i32));
+            put_list(hasher, offer.cipher_suites.iter().map(|c| *c as i32));
+            put_list(hasher, offer.kex_suites.iter().map(|k| *k as i32));
+            put_list(hasher, offer.sig_types.iter().map(|s| *s as i32));
+        }
+
+        let mut hasher = Sha256::new();
+        hasher.update(b"ZRC-NEGOTIATION-v1");
+        put_offer(&mut hasher, initiator);
+        put_offer(&mut hasher, responder);
+        put_list(
+            &mut hasher,
+            [
+                selected.transport as i32,
+                selected.cipher_suite as i32,
+                selected.kex_suite as i32,
+                selected.sig_type as i32,
+            ]
+            .into_iter(),
+        );
+        hasher.finalize().into()
+    }
+
+    /// Check the selection against the offers and return the transcript hash.
+    pub fn check_selection(
+        &self,
+        initiator: &NegotiationOffer,
+        responder: &NegotiationOffer,
+        selected: &NegotiatedParams,
+    ) -> Result<[u8; 32], SecurityError> {
+        self.checker.check_cipher_suite(selected.cipher_suite)?;
+        self.checker.check_kex_suite(selected.kex_suite)?;
+        self.checker.check_sig_type(selected.sig_type)?;
+
+        let best_transport = initiator
+            .transports
+            .iter()
+            .find(|t| responder.transports.contains(t));
+        if best_transport != Some(&selected.transport) {
+            return Err(SecurityError::DowngradeDetected {
+                algorithm: format!("transport:{:?}", selected.transport),
+            });
+        }
+        Self::check_strongest("cipher_suite", &initiator.cipher_suites, &responder.cipher_suites, selected.cipher_suite)?;
+        Self::check_strongest("kex_suite", &initiator.kex_suites, &responder.kex_suites, selected.kex_suite)?;
+        Self::check_strongest("sig_type", &initiator.sig_types, &responder.sig_types, selected.sig_type)?;
+
+        Ok(Self::transcript_hash(initiator, responder, selected))
+    }
+
+    /// Check the selection and compare our transcript hash with the one the
+    /// peer authenticated. Fails the session on any mismatch.
+    pub fn verify(
+        &self,
+        initiator: &NegotiationOffer,
+        responder: &NegotiationOffer,
+        selected: &NegotiatedParams,
+        peer_hash: &[u8],
+    ) -> Result<[u8; 32], SecurityError> {
+        let hash = self.check_selection(initiator, responder, selected)?;
+        if !constant_time_eq(&hash, peer_hash) {
+            return Err(SecurityError::DowngradeDetected {
+                algorithm: "negotiation transcript mismatch".to_string(),
+            });
+        }
+        Ok(hash)
+    }
+
+    /// Bind a negotiation hash into the session transcript.
+    pub fn bind(transcript: &mut SessionTranscript, hash: &[u8; 32]) {
+        transcript.append(NEGOTIATION_TRANSCRIPT_LABEL, hash);
+    }
+
+    fn check_strongest<T: Copy + Into<i32> + PartialEq + std::fmt::Debug>(
+        name: &str,
+        initiator: &[T],
+        responder: &[T],
+        selected: T,
+    ) -> Result<(), SecurityError> {
+        let strongest = initiator
+            .iter()
+            .filter(|v| responder.contains(v))
+            .copied()
+            .max_by_key(|v| (*v).into());
+        if strongest != Some(selected) {
+            return Err(SecurityError::DowngradeDetected {
+                algorithm: format!("{}:{:?}", name, selected),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Default for DowngradeGuard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Log downgrade detection events.
 ///
 /// Requirements: 4.6
@@ -223,4 +385,90 @@ mod tests {
             MIN_SIG_TYPE,
         ).is_err());
     }
+
+    fn full_offer() -> NegotiationOffer {
+        NegotiationOffer {
+            transports: vec![TransportV1::DirectIp, TransportV1::Relay],
+            cipher_suites: vec![
+                CipherSuiteV1::HpkeX25519HkdfSha256Chacha20poly1305,
+                CipherSuiteV1::HpkeX25519HkdfSha256Aesgcm128,
+            ],
+            kex_suites: vec![KexSuiteV1::X25519],
+            sig_types: vec![SigTypeV1::Ed25519],
+        }
+    }
+
+    fn selection(transport: TransportV1, cipher_suite: CipherSuiteV1) -> NegotiatedParams {
+        NegotiatedParams {
+            transport,
+            cipher_suite,
+            kex_suite: KexSuiteV1::X25519,
+            sig_type: SigTypeV1::Ed25519,
+        }
+    }
+
+    #[test]
+    fn test_downgrade_guard_accepts_honest_negotiation() {
+        let guard = DowngradeGuard::new();
+        let offer = full_offer();
+        let selected = selection(TransportV1::DirectIp, MIN_CIPHER_SUITE);
+
+        let responder_hash = guard.check_selection(&offer, &offer, &selected).unwrap();
+        let initiator_hash = guard.verify(&offer, &offer, &selected, &responder_hash).unwrap();
+        assert_eq!(initiator_hash, responder_hash);
+
+        // The hash changes the SAS transcript
+        let mut bound = SessionTranscript::new();
+        DowngradeGuard::bind(&mut bound, &initiator_hash);
+        assert_ne!(bound.as_bytes(), SessionTranscript::new().as_bytes());
+    }
+
+    #[test]
+    fn test_downgrade_guard_rejects_stripped_options() {
+        let guard = DowngradeGuard::new();
+        let initiator_offer = full_offer();
+        let responder_offer = full_offer();
+
+        // A MITM strips direct IP from the initiator's offer
+        let mut stripped = initiator_offer.clone();
+        stripped.transports.retain(|t| *t != TransportV1::DirectIp);
+
+        // The responder honestly picks the best of what it saw
+        let selected = selection(TransportV1::Relay, MIN_CIPHER_SUITE);
+        let responder_hash = guard.check_selection(&stripped, &responder_offer, &selected).unwrap();
+
+        // The initiator knows relay was not the best common transport
+        assert!(matches!(
+            guard.verify(&initiator_offer, &responder_offer, &selected, &responder_hash),
+            Err(SecurityError::DowngradeDetected { .. })
+        ));
+    }
+
+    #[test]
+    fn test_downgrade_guard_binds_unselected_options() {
+        let guard = DowngradeGuard::new();
+        let initiator_offer = full_offer();
+        let responder_offer = full_offer();
+
+        // Stripping an option that would not have been selected still
+        // changes the responder's transcript
+        let mut stripped = initiator_offer.clone();
+        stripped.cipher_suites.retain(|c| *c == MIN_CIPHER_SUITE);
+        let selected = selection(TransportV1::DirectIp, MIN_CIPHER_SUITE);
+        let responder_hash = guard.check_selection(&stripped, &responder_offer, &selected).unwrap();
+
+        assert!(matches!(
+            guard.verify(&initiator_offer, &responder_offer, &selected, &responder_hash),
+            Err(SecurityError::DowngradeDetected { algorithm }) if algorithm.contains("transcript")
+        ));
+    }
+
+    #[test]
+    fn test_downgrade_guard_rejects_weak_selection() {
+        // AES-GCM is offered by both but below the minimum and not the strongest
+        let guard = DowngradeGuard::new();
+        let offer = full_offer();
+        let selected = selection(TransportV1::DirectIp, CipherSuiteV1::HpkeX25519HkdfSha256Aesgcm128);
+        assert!(guard.check_selection(&offer, &offer, &selected).is_err());
+    }
 }
@@ -42,9 +42,18 @@ pub enum SecurityError {
     #[error("unsupported algorithm: {algorithm}")]
     UnsupportedAlgorithm { algorithm: String },
 
+    #[error("ratchet epoch {received} not reachable from epoch {current}")]
+    RatchetOutOfSync { current: u32, received: u32 },
+
     #[error("key rotation failed: {reason}")]
     KeyRotationFailed { reason: String },
 
+    #[error("key revoked: {reason}")]
+    KeyRevoked { reason: String },
+
+    #[error("invalid revocation: {reason}")]
+    InvalidRevocation { reason: String },
+
     #[error("invalid key length: expected {expected}, got {got}")]
     InvalidKeyLength { expected: usize, got: usize },
 }
@@ -1,23 +1,49 @@
 //! Identity pinning and verification for MITM protection.
 //!
+//! Pinned keys are held in [`PinnedKey`], which has no `PartialEq`: the only
+//! way to compare one is [`PinnedKey::ct_eq`], so a pin check cannot leak how
+//! many leading bytes matched through its timing.
+//!
 //! Requirements: 2.1, 2.2, 2.4
 
 use std::collections::HashMap;
 use std::time::SystemTime;
-use constant_time_eq::constant_time_eq;
+use subtle::{Choice, ConstantTimeEq};
 use zrc_proto::v1::PublicKeyBundleV1;
 use crate::error::SecurityError;
 
 /// A peer ID is a 32-byte identifier derived from the signing public key.
 pub type PeerId = [u8; 32];
 
+/// A pinned 32-byte public key that can only be compared in constant time.
+#[derive(Debug, Clone, Copy)]
+pub struct PinnedKey([u8; 32]);
+
+impl PinnedKey {
+    /// Wrap raw key bytes.
+    pub fn new(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    /// Raw key bytes.
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl ConstantTimeEq for PinnedKey {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0.ct_eq(&other.0)
+    }
+}
+
 /// Pinned identity information for a peer.
 #[derive(Debug, Clone)]
 pub struct PinnedIdentity {
     /// Ed25519 signing public key (32 bytes)
-    pub sign_pub: [u8; 32],
+    pub sign_pub: PinnedKey,
     /// X25519 key exchange public key (32 bytes)
-    pub kex_pub: [u8; 32],
+    pub kex_pub: PinnedKey,
     /// When this identity was first pinned
     pub pinned_at: SystemTime,
     /// When this identity was last verified
@@ -88,18 +114,18 @@ impl IdentityVerifier {
             })?;
 
         // Constant-time comparison to prevent timing attacks
-        if !constant_time_eq(&pinned.sign_pub, &sign_pub_array) {
+        if !bool::from(pinned.sign_pub.ct_eq(&PinnedKey::new(sign_pub_array))) {
             return Err(SecurityError::IdentityMismatch {
                 peer_id: peer_id.to_vec(),
-                expected: hex::encode(&pinned.sign_pub[..8]),
+                expected: hex::encode(&pinned.sign_pub.as_bytes()[..8]),
                 received: hex::encode(&sign_pub_array[..8]),
             });
         }
 
-        if !constant_time_eq(&pinned.kex_pub, &kex_pub_array) {
+        if !bool::from(pinned.kex_pub.ct_eq(&PinnedKey::new(kex_pub_array))) {
             return Err(SecurityError::IdentityMismatch {
                 peer_id: peer_id.to_vec(),
-                expected: hex::encode(&pinned.kex_pub[..8]),
+                expected: hex::encode(&pinned.kex_pub.as_bytes()[..8]),
                 received: hex::encode(&kex_pub_array[..8]),
             });
         }
@@ -150,8 +176,8 @@ impl IdentityVerifier {
 
         let now = SystemTime::now();
         let pinned = PinnedIdentity {
-            sign_pub: sign_pub_array,
-            kex_pub: kex_pub_array,
+            sign_pub: PinnedKey::new(sign_pub_array),
+            kex_pub: PinnedKey::new(kex_pub_array),
             pinned_at: now,
             last_verified: now,
         };
@@ -251,4 +277,20 @@ mod tests {
         verifier.unpin_identity(&peer_id);
         assert!(!verifier.is_pinned(&peer_id));
     }
+
+    #[test]
+    fn test_pinned_key_compares_in_constant_time() {
+        // Only compiles for types whose equality goes through `subtle`
+        fn ct_equal<T: ConstantTimeEq>(a: &T, b: &T) -> bool {
+            a.ct_eq(b).into()
+        }
+
+        let pinned = PinnedKey::new([0xAA; 32]);
+        let mut last_byte_differs = [0xAA; 32];
+        last_byte_differs[31] = 0xAB;
+
+        assert!(ct_equal(&pinned, &PinnedKey::new([0xAA; 32])));
+        assert!(!ct_equal(&pinned, &PinnedKey::new(last_byte_differs)));
+        assert!(!ct_equal(&pinned, &PinnedKey::new([0x00; 32])));
+    }
 }
@@ -1,10 +1,22 @@
 //! Key compromise recovery mechanisms.
 //!
+//! A device whose signing key is compromised issues a `RevocationV1` naming
+//! the revoked key and its replacement, signed by both. Peers cache verified
+//! records in a `RevocationSet` and refuse the revoked key from `not_before`
+//! on. The replacement key is never trusted just because a revocation names
+//! it: a peer only trusts it after re-pairing (trust on first use, confirmed
+//! by SAS), so a stolen key cannot be used to hand trust to an attacker.
+//!
 //! Requirements: 5.1, 5.2, 5.3, 5.5, 5.6
 
 use std::collections::HashMap;
+use std::sync::RwLock;
 use std::time::{SystemTime, UNIX_EPOCH};
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use prost::Message;
 use serde::{Deserialize, Serialize};
+use zrc_crypto::transcript::Transcript;
+use zrc_proto::v1::{revoke_v1::ReasonV1, RevocationV1};
 use crate::error::SecurityError;
 use crate::identity::{IdentityVerifier, PeerId};
 
@@ -70,7 +82,7 @@ impl KeyRotationManager {
         // Add to history
         self.rotation_history
             .entry(peer_id)
-            .or_insert_with(Vec::new)
+            .or_default()
             .push(entry);
 
         // Unpin old identity (will need to re-pair with new key)
@@ -145,6 +157,162 @@ impl Default for EmergencyRevocation {
     }
 }
 
+/// Canonical bytes signed by both keys of a `RevocationV1`.
+pub fn revocation_sign_data(record: &RevocationV1) -> [u8; 32] {
+    let mut t = Transcript::new("zrc_revocation_v1");
+    t.append_bytes(1, &record.device_id);
+    t.append_bytes(2, &record.revoked_sign_pub);
+    t.append_u64(3, record.reason as u64);
+    t.append_u64(4, record.not_before);
+    t.append_u64(5, record.issued_at);
+    t.append_bytes(6, &record.replacement_sign_pub);
+    t.finalize()
+}
+
+/// Issue a revocation of `revoked` in favour of `replacement`.
+pub fn issue_revocation(
+    device_id: &[u8],
+    revoked: &SigningKey,
+    replacement: &SigningKey,
+    reason: ReasonV1,
+    not_before: u64,
+    issued_at: u64,
+) -> RevocationV1 {
+    let mut record = RevocationV1 {
+        device_id: device_id.to_vec(),
+        revoked_sign_pub: revoked.verifying_key().to_bytes().to_vec(),
+        reason: reason as i32,
+        not_before,
+        issued_at,
+        replacement_sign_pub: replacement.verifying_key().to_bytes().to_vec(),
+        ..Default::default()
+    };
+    let data = revocation_sign_data(&record);
+    record.replacement_sig = replacement.sign(&data).to_bytes().to_vec();
+    record.revoked_key_sig = revoked.sign(&data).to_bytes().to_vec();
+    record
+}
+
+/// Check both signatures on a `RevocationV1`.
+pub fn verify_revocation(record: &RevocationV1) -> Result<(), SecurityError> {
+    fn invalid(reason: &str) -> SecurityError {
+        SecurityError::InvalidRevocation { reason: reason.to_string() }
+    }
+    fn verify(key: &[u8], sig: &[u8], data: &[u8; 32], which: &str) -> Result<(), SecurityError> {
+        let key: [u8; 32] = key
+            .try_into()
+            .map_err(|_| invalid(&format!("{} key must be 32 bytes", which)))?;
+        let sig: [u8; 64] = sig
+            .try_into()
+            .map_err(|_| invalid(&format!("{} signature must be 64 bytes", which)))?;
+        VerifyingKey::from_bytes(&key)
+            .map_err(|_| invalid(&format!("{} key is not a valid Ed25519 key", which)))?
+            .verify_strict(data, &Signature::from_bytes(&sig))
+            .map_err(|_| invalid(&format!("{} signature does not verify", which)))
+    }
+
+    if record.device_id.is_empty() {
+        return Err(invalid("missing device_id"));
+    }
+    if record.revoked_sign_pub == record.replacement_sign_pub {
+        return Err(invalid("replacement key equals revoked key"));
+    }
+    let data = revocation_sign_data(record);
+    verify(&record.replacement_sign_pub, &record.replacement_sig, &data, "replacement")?;
+    verify(&record.revoked_sign_pub, &record.revoked_key_sig, &data, "revoked")?;
+    Ok(())
+}
+
+/// Cached set of verified key revocations.
+///
+/// Shared between the pairing and session paths; records fetched from the
+/// directory or rendezvous are added with `insert_encoded`.
+#[derive(Default)]
+pub struct RevocationSet {
+    /// Revoked signing key -> record
+    records: RwLock<HashMap<Vec<u8>, RevocationV1>>,
+}
+
+impl RevocationSet {
+    /// Create an empty revocation set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Verify and cache a revocation. Returns `false` if an equivalent or
+    /// earlier revocation of the same key was already cached.
+    ///
+    /// When two valid records revoke the same key, the earliest `not_before`
+    /// wins, so a later record can never shorten a revocation.
+    pub fn insert(&self, record: RevocationV1) -> Result<bool, SecurityError> {
+        verify_revocation(&record)?;
+        let mut records = self.records.write().unwrap_or_else(|e| e.into_inner());
+        match records.get(&record.revoked_sign_pub) {
+            Some(existing) if existing.not_before <= record.not_before => Ok(false),
+            _ => {
+                records.insert(record.revoked_sign_pub.clone(), record);
+                Ok(true)
+            }
+        }
+    }
+
+    /// Decode, verify and cache a protobuf-encoded `RevocationV1`.
+    pub fn insert_encoded(&self, bytes: &[u8]) -> Result<bool, SecurityError> {
+        let record = RevocationV1::decode(bytes).map_err(|e| SecurityError::InvalidRevocation {
+            reason: format!("decode failed: {}", e),
+        })?;
+        self.insert(record)
+    }
+
+    /// Fail if `sign_pub` is revoked as of `now` (unix seconds).
+    pub fn check(&self, sign_pub: &[u8], now: u64) -> Result<(), SecurityError> {
+        let records = self.records.read().unwrap_or_else(|e| e.into_inner());
+        match records.get(sign_pub) {
+            Some(record) if now >= record.not_before => Err(SecurityError::KeyRevoked {
+                reason: format!(
+                    "{:?} since {}",
+                    ReasonV1::try_from(record.reason).unwrap_or(ReasonV1::Unspecified),
+                    record.not_before
+                ),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Fail if `sign_pub` is revoked now.
+    pub fn check_now(&self, sign_pub: &[u8]) -> Result<(), SecurityError> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        self.check(sign_pub, now)
+    }
+
+    /// The cached revocation of `sign_pub`, if any.
+    pub fn get(&self, sign_pub: &[u8]) -> Option<RevocationV1> {
+        let records = self.records.read().unwrap_or_else(|e| e.into_inner());
+        records.get(sign_pub).cloned()
+    }
+
+    /// The key a revocation names as successor of `sign_pub`, if any.
+    ///
+    /// Informational only: callers must re-pair before trusting it.
+    pub fn replacement_for(&self, sign_pub: &[u8]) -> Option<Vec<u8>> {
+        let records = self.records.read().unwrap_or_else(|e| e.into_inner());
+        records.get(sign_pub).map(|r| r.replacement_sign_pub.clone())
+    }
+
+    /// Number of cached revocations.
+    pub fn len(&self) -> usize {
+        self.records.read().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// Whether no revocations are cached.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -191,4 +359,43 @@ mod tests {
         // Key should now be revoked
         assert!(revocation.is_revoked(&key_id));
     }
+
+    #[test]
+    fn test_revocation_set_rejects_revoked_key() {
+        let old = SigningKey::from_bytes(&[1u8; 32]);
+        let new = SigningKey::from_bytes(&[2u8; 32]);
+        let record = issue_revocation(&[9u8; 32], &old, &new, ReasonV1::Compromised, 1000, 990);
+        let old_pub = old.verifying_key().to_bytes();
+
+        let set = RevocationSet::new();
+        assert!(set.insert_encoded(&record.encode_to_vec()).unwrap());
+        assert!(set.check(&old_pub, 999).is_ok());
+        assert!(matches!(set.check(&old_pub, 1000), Err(SecurityError::KeyRevoked { .. })));
+        assert!(set.check(&new.verifying_key().to_bytes(), 2000).is_ok());
+        assert_eq!(set.replacement_for(&old_pub), Some(new.verifying_key().to_bytes().to_vec()));
+
+        // A later record cannot push the revocation back
+        let later = issue_revocation(&[9u8; 32], &old, &new, ReasonV1::Compromised, 5000, 4990);
+        assert!(!set.insert(later).unwrap());
+        assert!(set.check(&old_pub, 1000).is_err());
+    }
+
+    #[test]
+    fn test_revocation_requires_both_signatures() {
+        let old = SigningKey::from_bytes(&[1u8; 32]);
+        let new = SigningKey::from_bytes(&[2u8; 32]);
+        let attacker = SigningKey::from_bytes(&[3u8; 32]);
+        let set = RevocationSet::new();
+
+        // Without the revoked key an attacker cannot revoke it
+        let mut forged = issue_revocation(&[9u8; 32], &attacker, &new, ReasonV1::Compromised, 0, 0);
+        forged.revoked_sign_pub = old.verifying_key().to_bytes().to_vec();
+        assert!(matches!(set.insert(forged), Err(SecurityError::InvalidRevocation { .. })));
+
+        // Tampering with the replacement breaks its signature
+        let mut swapped = issue_revocation(&[9u8; 32], &old, &new, ReasonV1::Compromised, 0, 0);
+        swapped.replacement_sign_pub = attacker.verifying_key().to_bytes().to_vec();
+        assert!(set.insert(swapped).is_err());
+        assert!(set.is_empty());
+    }
 }
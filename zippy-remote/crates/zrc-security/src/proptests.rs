@@ -241,7 +241,7 @@ mod tests {
                 std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_nanos()));
 
             let writer = Box::new(FileAuditLogWriter::new(log_path.clone()));
-            let logger = AuditLogger::new(signing_key.clone(), writer);
+            let logger = AuditLogger::new(signing_key.clone(), writer).unwrap();
 
             // Create and log an event
             let event = SecurityEvent::AuthenticationAttempt {
@@ -260,6 +260,8 @@ mod tests {
                 actor: event.actor(),
                 target: event.target(),
                 details: event.details(),
+                sequence: 0,
+                prev_hash: Vec::new(),
                 signature: Vec::new(),
             };
 
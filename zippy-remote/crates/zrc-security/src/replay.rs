@@ -2,13 +2,34 @@
 //!
 //! Requirements: 3.1, 3.2, 3.3, 3.4, 3.5
 
+use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use crate::audit::{AuditLogger, SecurityEvent};
 use crate::error::SecurityError;
 
+/// Capacity usage and eviction counters of a [`ReplayProtection`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReplayStats {
+    /// Sequence numbers currently tracked
+    pub entries: usize,
+    /// Tracked sequence numbers dropped as the window slid forward
+    pub evictions: u64,
+    /// Evictions of sequence numbers accepted within the protection window.
+    /// A non-zero value means the window is too small for the traffic: late
+    /// packets that should still be accepted are rejected as replays.
+    pub premature_evictions: u64,
+    /// When the oldest tracked sequence number was accepted (Unix seconds)
+    pub oldest_timestamp: Option<u64>,
+}
+
 /// Replay protection using a sliding window bitmap.
 ///
 /// Tracks sequence numbers within a configurable window to detect
-/// and reject duplicate or out-of-window packets.
+/// and reject duplicate or out-of-window packets. Memory is bounded by the
+/// window: when a newer sequence number slides it forward, the oldest
+/// tracked ones are evicted. With a protection window set, evicting entries
+/// accepted within that time logs an audit warning (at most once per
+/// window), since the window size is then too small for the packet rate.
 ///
 /// **Thread Safety:** This struct is not thread-safe. For multi-threaded use,
 /// wrap it in `Arc<Mutex<ReplayProtection>>` or use per-thread instances.
@@ -23,6 +44,15 @@ pub struct ReplayProtection {
     highest_seq: u64,
     /// Window size in packets
     window_size: u64,
+    /// When the first sequence number in each bitmap word was accepted
+    seen_at: [u64; 16],
+    evictions: u64,
+    premature_evictions: u64,
+    /// How long accepted sequence numbers should stay tracked, if audited
+    protection_window: Option<Duration>,
+    /// When the last undersized-window warning was logged
+    last_warning: Option<u64>,
+    audit: Option<Arc<AuditLogger>>,
 }
 
 impl ReplayProtection {
@@ -36,13 +66,43 @@ impl ReplayProtection {
             window_start: 0,
             highest_seq: 0,
             window_size,
+            seen_at: [0u64; 16],
+            evictions: 0,
+            premature_evictions: 0,
+            protection_window: None,
+            last_warning: None,
+            audit: None,
+        }
+    }
+
+    /// Create a replay protection that expects accepted sequence numbers to
+    /// stay tracked for at least `protection_window`.
+    pub fn with_protection_window(window_size: u64, protection_window: Duration) -> Self {
+        Self {
+            protection_window: Some(protection_window),
+            ..Self::new(window_size)
         }
     }
 
+    /// Log undersized-window warnings to `audit`.
+    pub fn set_audit_logger(&mut self, audit: Arc<AuditLogger>) {
+        self.audit = Some(audit);
+    }
+
     /// Check if a sequence number is valid (not replayed) and update the filter.
     ///
     /// Requirements: 3.4, 3.5
     pub fn check_and_update(&mut self, seq: u64) -> Result<(), SecurityError> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        self.check_and_update_at(seq, now)
+    }
+
+    /// Check a sequence number received at `now` (Unix seconds) and update
+    /// the filter.
+    pub fn check_and_update_at(&mut self, seq: u64, now: u64) -> Result<(), SecurityError> {
         if seq == 0 {
             return Err(SecurityError::InvalidSequence);
         }
@@ -64,12 +124,12 @@ impl ReplayProtection {
             }
 
             // Mark as seen
-            self.window[bitmap_idx] |= bit_mask;
+            self.mark_seen(bitmap_idx, bit_mask, now);
             return Ok(());
         }
 
         // Case 3: Sequence is ahead of the window - slide the window
-        self.slide_window(seq);
+        self.slide_window(seq, now);
 
         // Mark the new sequence as seen
         // After slide_window, seq is guaranteed to be in the first word (offset < 64)
@@ -77,18 +137,71 @@ impl ReplayProtection {
         let bitmap_idx = (offset / 64) as usize;
         let bit_idx = offset % 64;
         let bit_mask = 1u64 << bit_idx;
-        self.window[bitmap_idx] |= bit_mask;
+        self.mark_seen(bitmap_idx, bit_mask, now);
 
         Ok(())
     }
 
+    /// Current capacity usage and eviction counters.
+    pub fn stats(&self) -> ReplayStats {
+        ReplayStats {
+            entries: self.window.iter().map(|w| w.count_ones() as usize).sum(),
+            evictions: self.evictions,
+            premature_evictions: self.premature_evictions,
+            oldest_timestamp: (0..16)
+                .filter(|&i| self.window[i] != 0)
+                .map(|i| self.seen_at[i])
+                .min(),
+        }
+    }
+
+    fn mark_seen(&mut self, bitmap_idx: usize, bit_mask: u64, now: u64) {
+        if self.window[bitmap_idx] == 0 {
+            self.seen_at[bitmap_idx] = now;
+        }
+        self.window[bitmap_idx] |= bit_mask;
+    }
+
+    /// Count the sequence numbers in a bitmap word about to be dropped.
+    fn evict_word(&mut self, idx: usize, now: u64) {
+        let count = self.window[idx].count_ones() as u64;
+        if count == 0 {
+            return;
+        }
+        self.evictions += count;
+
+        let Some(protection_window) = self.protection_window else {
+            return;
+        };
+        let window = protection_window.as_secs();
+        let age = now.saturating_sub(self.seen_at[idx]);
+        if age >= window {
+            return;
+        }
+        self.premature_evictions += count;
+        let warned_recently = self
+            .last_warning
+            .is_some_and(|at| now.saturating_sub(at) < window);
+        if warned_recently {
+            return;
+        }
+        self.last_warning = Some(now);
+        if let Some(audit) = &self.audit {
+            // Failing to audit must not fail the packet being checked
+            let _ = audit.log(SecurityEvent::ReplayWindowUndersized {
+                capacity: self.window_size as usize,
+                evicted_age_secs: age,
+            });
+        }
+    }
+
     /// Get the current window start position.
     fn window_start(&self) -> u64 {
         self.window_start
     }
 
     /// Slide the window forward to accommodate a new sequence number.
-    fn slide_window(&mut self, new_seq: u64) {
+    fn slide_window(&mut self, new_seq: u64, now: u64) {
         // Align window start to 64-byte boundary for efficient bitmap operations
         let new_window_start = new_seq - (new_seq % 64);
         let old_window_start = self.window_start();
@@ -96,7 +209,11 @@ impl ReplayProtection {
 
         if shift_amount >= self.window_size {
             // Complete reset - new sequence is way ahead
+            for i in 0..16 {
+                self.evict_word(i, now);
+            }
             self.window = [0u64; 16];
+            self.seen_at = [0u64; 16];
             self.window_start = new_window_start;
         } else {
             // Shift the bitmap
@@ -104,9 +221,13 @@ impl ReplayProtection {
             let bits_to_shift = (shift_amount % 64) as u32;
 
             if words_to_shift > 0 && words_to_shift < 16 {
+                for i in 0..words_to_shift {
+                    self.evict_word(i, now);
+                }
                 // Shift whole words
                 for i in 0..(16 - words_to_shift) {
                     self.window[i] = self.window[i + words_to_shift];
+                    self.seen_at[i] = self.seen_at[i + words_to_shift];
                 }
                 for i in (16 - words_to_shift)..16 {
                     self.window[i] = 0;
@@ -193,6 +314,9 @@ impl Default for TicketValidator {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::audit::MemoryAuditLogWriter;
+    use ed25519_dalek::SigningKey;
+    use rand_core::OsRng;
 
     #[test]
     fn test_replay_protection_accepts_sequential() {
@@ -286,4 +410,50 @@ mod tests {
             Err(SecurityError::TicketFromFuture)
         ));
     }
+
+    #[test]
+    fn test_replay_protection_stats_track_eviction() {
+        let mut rp = ReplayProtection::new(64);
+
+        for seq in 1..=10 {
+            rp.check_and_update_at(seq, 1000).unwrap();
+        }
+        assert_eq!(rp.stats().entries, 10);
+        assert_eq!(rp.stats().oldest_timestamp, Some(1000));
+
+        // A sequence number far ahead slides the window past all of them
+        rp.check_and_update_at(200, 1010).unwrap();
+        let stats = rp.stats();
+        assert_eq!(stats.entries, 1);
+        assert_eq!(stats.evictions, 10);
+        assert_eq!(stats.premature_evictions, 0);
+        assert_eq!(stats.oldest_timestamp, Some(1010));
+        assert!(rp.check_and_update_at(5, 1010).is_err());
+    }
+
+    #[test]
+    fn test_replay_protection_warns_when_window_undersized() {
+        let writer = MemoryAuditLogWriter::new();
+        let logger = AuditLogger::new(SigningKey::generate(&mut OsRng), Box::new(writer.clone())).unwrap();
+        let mut rp = ReplayProtection::with_protection_window(64, Duration::from_secs(60));
+        rp.set_audit_logger(Arc::new(logger));
+
+        // Entries older than the protection window go without a warning
+        rp.check_and_update_at(1, 1000).unwrap();
+        rp.check_and_update_at(70, 1070).unwrap();
+        assert_eq!(rp.stats().evictions, 1);
+        assert!(writer.entries().is_empty());
+
+        // Evicting recent entries is reported, once per window
+        rp.check_and_update_at(140, 1071).unwrap();
+        rp.check_and_update_at(210, 1072).unwrap();
+        let stats = rp.stats();
+        assert_eq!(stats.evictions, 3);
+        assert_eq!(stats.premature_evictions, 2);
+
+        let logged = writer.entries();
+        assert_eq!(logged.len(), 1);
+        assert_eq!(logged[0].event_type, "replay_window_undersized");
+        assert_eq!(logged[0].target.as_deref(), Some("capacity:64"));
+    }
 }
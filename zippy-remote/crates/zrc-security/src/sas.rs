@@ -2,8 +2,13 @@
 //!
 //! Requirements: 2.3, 2.6
 
+use constant_time_eq::constant_time_eq;
+use zeroize::Zeroizing;
 use zrc_crypto::sas::sas_6digit;
 
+/// Default number of SAS entries allowed before verification is abandoned.
+pub const DEFAULT_SAS_ATTEMPTS: u32 = 3;
+
 /// Session transcript for SAS computation.
 ///
 /// Contains the handshake messages and peer IDs used to compute
@@ -31,6 +36,14 @@ impl SessionTranscript {
     pub fn to_bytes(self) -> Vec<u8> {
         self.bytes
     }
+
+    /// Append a labelled, length-prefixed value so it is covered by the SAS.
+    pub fn append(&mut self, label: &[u8], data: &[u8]) {
+        self.bytes.extend_from_slice(&(label.len() as u32).to_be_bytes());
+        self.bytes.extend_from_slice(label);
+        self.bytes.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        self.bytes.extend_from_slice(data);
+    }
 }
 
 impl Default for SessionTranscript {
@@ -87,6 +100,80 @@ impl SasVerification {
     }
 }
 
+/// Result of checking a user-entered SAS.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SasOutcome {
+    /// The entered code matches
+    Match,
+    /// The entered code differs; the user may try again
+    Mismatch { attempts_remaining: u32 },
+    /// No attempts remain; the pairing must be abandoned
+    Exhausted,
+}
+
+/// Confirms that the SAS the user entered matches the locally computed one.
+///
+/// Comparison is constant-time. Spaces and dashes in the entry are ignored so
+/// "123 456" and "123-456" are accepted. After `max_attempts` mismatches every
+/// further check returns `Exhausted`, even for the correct code.
+///
+/// Requirements: 2.5, 2.6
+pub struct SasVerifier {
+    expected: Zeroizing<Vec<u8>>,
+    max_attempts: u32,
+    failures: u32,
+}
+
+impl SasVerifier {
+    /// Create a verifier allowing `DEFAULT_SAS_ATTEMPTS` entries.
+    pub fn new(expected: &str) -> Self {
+        Self::with_max_attempts(expected, DEFAULT_SAS_ATTEMPTS)
+    }
+
+    /// Create a verifier allowing `max_attempts` entries (at least one).
+    pub fn with_max_attempts(expected: &str, max_attempts: u32) -> Self {
+        Self {
+            expected: Zeroizing::new(Self::normalize(expected)),
+            max_attempts: max_attempts.max(1),
+            failures: 0,
+        }
+    }
+
+    /// Check a user-entered code.
+    pub fn verify(&mut self, entered: &str) -> SasOutcome {
+        if self.failures >= self.max_attempts {
+            return SasOutcome::Exhausted;
+        }
+
+        let entered = Zeroizing::new(Self::normalize(entered));
+        if !self.expected.is_empty() && constant_time_eq(&self.expected, &entered) {
+            return SasOutcome::Match;
+        }
+
+        self.failures += 1;
+        match self.attempts_remaining() {
+            0 => SasOutcome::Exhausted,
+            attempts_remaining => SasOutcome::Mismatch { attempts_remaining },
+        }
+    }
+
+    /// Number of mismatched entries so far.
+    pub fn failures(&self) -> u32 {
+        self.failures
+    }
+
+    /// Entries left before verification is exhausted.
+    pub fn attempts_remaining(&self) -> u32 {
+        self.max_attempts.saturating_sub(self.failures)
+    }
+
+    fn normalize(code: &str) -> Vec<u8> {
+        code.bytes()
+            .filter(|b| !b.is_ascii_whitespace() && *b != b'-')
+            .collect()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -138,4 +225,41 @@ mod tests {
 
         assert_ne!(sas_a, sas_b);
     }
+
+    #[test]
+    fn test_sas_verifier_match() {
+        let mut verifier = SasVerifier::new("123456");
+        assert_eq!(verifier.verify("123 456"), SasOutcome::Match);
+        assert_eq!(verifier.verify("123-456"), SasOutcome::Match);
+        assert_eq!(verifier.failures(), 0);
+    }
+
+    #[test]
+    fn test_sas_verifier_mismatch_then_exhausted() {
+        let mut verifier = SasVerifier::new("123456");
+        assert_eq!(verifier.verify("123457"), SasOutcome::Mismatch { attempts_remaining: 2 });
+        assert_eq!(verifier.verify("12345"), SasOutcome::Mismatch { attempts_remaining: 1 });
+        assert_eq!(verifier.verify(""), SasOutcome::Exhausted);
+        // The correct code is no longer accepted
+        assert_eq!(verifier.verify("123456"), SasOutcome::Exhausted);
+        assert_eq!(verifier.failures(), 3);
+    }
+
+    #[test]
+    fn test_sas_verifier_constant_time_compare() {
+        // Every single-digit difference, at any position, is a mismatch,
+        // as are length differences and an empty expected code
+        for pos in 0..6 {
+            let mut entered = b"654321".to_vec();
+            entered[pos] = b'0';
+            let mut verifier = SasVerifier::with_max_attempts("654321", 10);
+            assert!(matches!(
+                verifier.verify(std::str::from_utf8(&entered).unwrap()),
+                SasOutcome::Mismatch { .. }
+            ));
+        }
+        assert!(!constant_time_eq(b"654321", b"6543210"));
+        assert!(constant_time_eq(b"654321", b"654321"));
+        assert_eq!(SasVerifier::new("").verify(""), SasOutcome::Mismatch { attempts_remaining: 2 });
+    }
 }
@@ -1,9 +1,16 @@
 //! Session key derivation using HKDF.
 //!
+//! [`KeyRatchet`] adds forward secrecy within a session: the frame key is
+//! replaced every few frames or seconds by a one-way HKDF chain step, so
+//! compromising the current key does not expose earlier frames.
+//!
 //! Requirements: 7.1, 7.2, 7.3
 
+use std::time::{Duration, Instant};
 use hkdf::Hkdf;
 use sha2::Sha256;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+use crate::error::SecurityError;
 
 /// Shared secret for key derivation (32 bytes).
 pub type SharedSecret = [u8; 32];
@@ -20,7 +27,7 @@ pub type PeerId = [u8; 32];
 /// to ensure key separation.
 ///
 /// Requirements: 7.2, 7.3
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct SessionKeys {
     /// Initiator to responder control channel key
     pub i2r_control: [u8; 32],
@@ -36,19 +43,6 @@ pub struct SessionKeys {
     pub r2i_files: [u8; 32],
 }
 
-impl Default for SessionKeys {
-    fn default() -> Self {
-        Self {
-            i2r_control: [0u8; 32],
-            r2i_control: [0u8; 32],
-            i2r_frames: [0u8; 32],
-            r2i_frames: [0u8; 32],
-            i2r_files: [0u8; 32],
-            r2i_files: [0u8; 32],
-        }
-    }
-}
-
 /// Session key deriver using HKDF-SHA256.
 ///
 /// Requirements: 7.1, 7.2, 7.3
@@ -96,6 +90,152 @@ impl SessionKeyDeriver {
     }
 }
 
+/// Frames sent under one ratchet key before stepping.
+pub const DEFAULT_RATCHET_FRAMES: u64 = 1024;
+
+/// Maximum time one ratchet key stays in use.
+pub const DEFAULT_RATCHET_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Furthest a receiver will step forward to resync with a sender.
+pub const MAX_RATCHET_SKIP: u32 = 1024;
+
+/// When the sending side steps its ratchet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RatchetPolicy {
+    /// Step after this many frames under one key
+    pub every_frames: u64,
+    /// Step once a key has been in use this long
+    pub every: Duration,
+}
+
+impl Default for RatchetPolicy {
+    fn default() -> Self {
+        Self {
+            every_frames: DEFAULT_RATCHET_FRAMES,
+            every: DEFAULT_RATCHET_INTERVAL,
+        }
+    }
+}
+
+/// Symmetric HKDF ratchet for frame-encryption keys.
+///
+/// Both ends seed it with the same 32-byte chain key (derived from the
+/// session binding) and start at epoch 0. Each step is
+///
+/// ```text
+/// frame_key[n] = HKDF-Expand(chain[n], "zrc_ratchet_frame_key_v1")
+/// chain[n+1]   = HKDF-Expand(chain[n], "zrc_ratchet_chain_v1")
+/// ```
+///
+/// after which `chain[n]` and `frame_key[n]` are erased. Senders step
+/// according to a [`RatchetPolicy`] and tag every frame with its epoch.
+/// Receivers never step on a timer: they follow the epoch tag, skipping
+/// ahead over epochs whose frames were lost (at most [`MAX_RATCHET_SKIP`]),
+/// and can never step back, so frames from an old epoch no longer decrypt.
+///
+/// Requirements: 7.2
+#[derive(Clone, Zeroize, ZeroizeOnDrop)]
+pub struct KeyRatchet {
+    chain: [u8; 32],
+    frame_key: [u8; 32],
+    epoch: u32,
+}
+
+impl KeyRatchet {
+    /// Start a ratchet at epoch 0 from the initial chain key.
+    pub fn new(chain_key: [u8; 32]) -> Self {
+        let mut ratchet = Self {
+            chain: chain_key,
+            frame_key: [0u8; 32],
+            epoch: 0,
+        };
+        ratchet.frame_key = ratchet.expand(b"zrc_ratchet_frame_key_v1");
+        ratchet
+    }
+
+    /// Current epoch.
+    pub fn epoch(&self) -> u32 {
+        self.epoch
+    }
+
+    /// Frame-encryption key for the current epoch.
+    pub fn frame_key(&self) -> &[u8; 32] {
+        &self.frame_key
+    }
+
+    /// Move to the next epoch, erasing the current keys.
+    pub fn step(&mut self) {
+        let mut next = self.expand(b"zrc_ratchet_chain_v1");
+        self.chain.zeroize();
+        self.chain = next;
+        next.zeroize();
+        self.frame_key = self.expand(b"zrc_ratchet_frame_key_v1");
+        self.epoch = self.epoch.wrapping_add(1);
+    }
+
+    /// Step forward until `epoch` is reached.
+    ///
+    /// Fails without changing state if `epoch` is in the past or more than
+    /// [`MAX_RATCHET_SKIP`] steps ahead.
+    pub fn advance_to(&mut self, epoch: u32) -> Result<(), SecurityError> {
+        if epoch < self.epoch || epoch - self.epoch > MAX_RATCHET_SKIP {
+            return Err(SecurityError::RatchetOutOfSync {
+                current: self.epoch,
+                received: epoch,
+            });
+        }
+        while self.epoch < epoch {
+            self.step();
+        }
+        Ok(())
+    }
+
+    fn expand(&self, label: &[u8]) -> [u8; 32] {
+        let hk = Hkdf::<Sha256>::from_prk(&self.chain).expect("32-byte PRK is valid");
+        let mut out = [0u8; 32];
+        hk.expand(label, &mut out)
+            .expect("HKDF expand should not fail for 32-byte output");
+        out
+    }
+}
+
+/// Decides when a sending [`KeyRatchet`] steps under a [`RatchetPolicy`].
+#[derive(Debug, Clone)]
+pub struct RatchetSchedule {
+    policy: RatchetPolicy,
+    frames: u64,
+    since: Instant,
+}
+
+impl RatchetSchedule {
+    /// Start counting for the key that is in use now.
+    pub fn new(policy: RatchetPolicy) -> Self {
+        Self {
+            policy,
+            frames: 0,
+            since: Instant::now(),
+        }
+    }
+
+    /// Record one frame about to be sent; returns true if the ratchet must
+    /// step before sealing it.
+    pub fn next_frame(&mut self) -> bool {
+        self.next_frame_at(Instant::now())
+    }
+
+    /// Like [`next_frame`](Self::next_frame) with an explicit clock.
+    pub fn next_frame_at(&mut self, now: Instant) -> bool {
+        let due = self.frames >= self.policy.every_frames
+            || now.saturating_duration_since(self.since) >= self.policy.every;
+        if due {
+            self.frames = 0;
+            self.since = now;
+        }
+        self.frames += 1;
+        due
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -178,4 +318,56 @@ mod tests {
 
         assert_ne!(keys1.i2r_control, keys2.i2r_control);
     }
+
+    #[test]
+    fn test_ratchet_steps_in_lockstep() {
+        let mut sender = KeyRatchet::new([0x11; 32]);
+        let mut receiver = KeyRatchet::new([0x11; 32]);
+        assert_eq!(sender.frame_key(), receiver.frame_key());
+
+        let first = *sender.frame_key();
+        sender.step();
+        assert_ne!(sender.frame_key(), &first);
+
+        // A receiver that missed epochs catches up by following the tag
+        sender.step();
+        receiver.advance_to(sender.epoch()).unwrap();
+        assert_eq!(receiver.epoch(), 2);
+        assert_eq!(sender.frame_key(), receiver.frame_key());
+    }
+
+    #[test]
+    fn test_ratchet_never_steps_back() {
+        let mut ratchet = KeyRatchet::new([0x22; 32]);
+        ratchet.advance_to(3).unwrap();
+        let key = *ratchet.frame_key();
+
+        assert_eq!(
+            ratchet.advance_to(2),
+            Err(SecurityError::RatchetOutOfSync { current: 3, received: 2 })
+        );
+        assert!(ratchet.advance_to(3 + MAX_RATCHET_SKIP + 1).is_err());
+        assert_eq!(ratchet.epoch(), 3);
+        assert_eq!(ratchet.frame_key(), &key);
+    }
+
+    #[test]
+    fn test_ratchet_schedule_by_frames_and_time() {
+        let start = Instant::now();
+        let mut schedule = RatchetSchedule {
+            policy: RatchetPolicy { every_frames: 3, every: Duration::from_secs(10) },
+            frames: 0,
+            since: start,
+        };
+
+        // Three frames per key...
+        assert!(!schedule.next_frame_at(start));
+        assert!(!schedule.next_frame_at(start));
+        assert!(!schedule.next_frame_at(start));
+        assert!(schedule.next_frame_at(start));
+
+        // ...or ten seconds, whichever comes first
+        assert!(!schedule.next_frame_at(start + Duration::from_secs(9)));
+        assert!(schedule.next_frame_at(start + Duration::from_secs(10)));
+    }
 }
@@ -1,10 +1,12 @@
 //! Backpressure handling for flow control.
 
+use crate::metrics::global_metrics;
 use crate::mux::ChannelType;
 use parking_lot::Mutex;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::collections::VecDeque;
 use thiserror::Error;
+use tokio::sync::Notify;
 
 /// Drop policy for handling buffer overflow
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -45,6 +47,7 @@ pub struct BackpressureHandler {
     drop_policy: DropPolicy,
     dropped_count: AtomicU64,
     frame_queue: Mutex<VecDeque<FrameEntry>>,
+    released: Notify,
 }
 
 impl BackpressureHandler {
@@ -56,6 +59,7 @@ impl BackpressureHandler {
             drop_policy: policy,
             dropped_count: AtomicU64::new(0),
             frame_queue: Mutex::new(VecDeque::new()),
+            released: Notify::new(),
         }
     }
 
@@ -82,9 +86,7 @@ impl BackpressureHandler {
 
         // Handle overflow based on policy
         match self.drop_policy {
-            DropPolicy::Block => {
-                return Err(BackpressureError::BufferFull);
-            }
+            DropPolicy::Block => Err(BackpressureError::BufferFull),
             DropPolicy::DropOldest => {
                 // Remove oldest frames until we have space
                 let mut queue = self.frame_queue.lock();
@@ -92,6 +94,7 @@ impl BackpressureHandler {
                     if let Some(entry) = queue.pop_front() {
                         *current -= entry.size;
                         self.dropped_count.fetch_add(1, Ordering::Relaxed);
+                        global_metrics().record_drop(entry.channel);
                     } else {
                         break;
                     }
@@ -107,6 +110,7 @@ impl BackpressureHandler {
             DropPolicy::DropNewest => {
                 // Just drop this frame
                 self.dropped_count.fetch_add(1, Ordering::Relaxed);
+                global_metrics().record_drop(channel);
                 Err(BackpressureError::FrameDropped)
             }
             DropPolicy::DropByPriority => {
@@ -122,6 +126,7 @@ impl BackpressureHandler {
                     if let Some(entry) = entries.pop() {
                         *current -= entry.size;
                         self.dropped_count.fetch_add(1, Ordering::Relaxed);
+                        global_metrics().record_drop(entry.channel);
                     } else {
                         break;
                     }
@@ -143,7 +148,43 @@ impl BackpressureHandler {
         }
     }
 
+    /// Reserve buffer space if it is available now, ignoring the drop policy.
+    pub fn try_reserve(&self, size: usize, channel: ChannelType) -> Result<(), BackpressureError> {
+        let mut current = self.current_buffer.lock();
+        if *current + size > self.send_buffer_limit {
+            return Err(BackpressureError::BufferFull);
+        }
+        *current += size;
+        self.frame_queue.lock().push_back(FrameEntry { size, channel });
+        Ok(())
+    }
+
+    /// Reserve buffer space, waiting for `release` calls until it fits.
+    ///
+    /// Fails with `BufferFull` only if `size` exceeds the whole limit.
+    pub async fn reserve_wait(
+        &self,
+        size: usize,
+        channel: ChannelType,
+    ) -> Result<(), BackpressureError> {
+        if size > self.send_buffer_limit {
+            return Err(BackpressureError::BufferFull);
+        }
+        loop {
+            // Register before checking so a release in between is not missed
+            let mut notified = std::pin::pin!(self.released.notified());
+            notified.as_mut().enable();
+            if self.try_reserve(size, channel).is_ok() {
+                return Ok(());
+            }
+            notified.await;
+        }
+    }
+
     /// Release buffer space
+    ///
+    /// `size` may cover several queued frames, or part of one, so slots are
+    /// retired oldest first by byte count.
     pub fn release(&self, size: usize) {
         let mut current = self.current_buffer.lock();
         if *current >= size {
@@ -152,13 +193,22 @@ impl BackpressureHandler {
             *current = 0;
         }
         
-        // Remove from queue if present
         let mut queue = self.frame_queue.lock();
-        if let Some(entry) = queue.front() {
-            if entry.size == size {
-                queue.pop_front();
+        let mut remaining = size;
+        while remaining > 0 {
+            let Some(entry) = queue.front_mut() else {
+                break;
+            };
+            if entry.size > remaining {
+                entry.size -= remaining;
+                break;
             }
+            remaining -= entry.size;
+            queue.pop_front();
         }
+        drop(queue);
+        drop(current);
+        self.released.notify_waiters();
     }
 
     /// Get dropped frame count
@@ -204,6 +254,27 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_release_retires_slots_by_bytes() {
+        let handler = BackpressureHandler::new(1000, DropPolicy::DropOldest);
+        for _ in 0..3 {
+            handler.try_reserve(100, ChannelType::Frames).unwrap();
+        }
+        // One release covering two frames and part of the third
+        handler.release(250);
+        assert_eq!(handler.frame_queue.lock().len(), 1);
+        handler.release(50);
+        assert!(handler.frame_queue.lock().is_empty());
+        assert_eq!(handler.current_usage(), 0);
+
+        // No stale slots are left to be "dropped" against the new usage
+        handler.try_reserve(900, ChannelType::Frames).unwrap();
+        let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        rt.block_on(handler.reserve(200, ChannelType::Frames)).unwrap();
+        assert_eq!(handler.current_usage(), 200);
+        assert_eq!(handler.dropped_count(), 1);
+    }
+
     #[test]
     fn test_drop_oldest() {
         let rt = tokio::runtime::Builder::new_current_thread()
@@ -1,9 +1,12 @@
 //! Connection state management and transport ladder.
 
 use crate::traits::{ControlPlaneTransport, TransportError, TransportType};
+use async_trait::async_trait;
 use parking_lot::Mutex;
 use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Weak};
 use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
 use tokio::time::timeout as tokio_timeout;
 
 /// Connection state
@@ -152,12 +155,18 @@ impl TransportLadder {
         &self,
         _target: &[u8; 32],
     ) -> Result<ConnectedTransport, TransportError> {
-        let transports = self.transports.lock();
+        // Snapshot the candidates so the lock is not held across the attempts
+        let candidates: Vec<(TransportType, bool)> = self
+            .transports
+            .lock()
+            .iter()
+            .map(|(transport_type, transport)| (*transport_type, transport.is_connected()))
+            .collect();
         let start = Instant::now();
 
-        for (transport_type, transport) in transports.iter() {
+        for (transport_type, connected) in candidates.iter() {
             // Check if transport is already connected
-            if transport.is_connected() {
+            if *connected {
                 // Note: In real implementation, we'd need to clone or wrap the transport
                 // For now, we'll create a mock transport as placeholder
                 return Ok(ConnectedTransport {
@@ -172,7 +181,7 @@ impl TransportLadder {
             match tokio_timeout(self.timeout, async {
                 // Simulate connection attempt
                 tokio::time::sleep(Duration::from_millis(10)).await;
-                if transport.is_connected() {
+                if *connected {
                     Ok(())
                 } else {
                     Err(TransportError::Disconnected)
@@ -206,13 +215,13 @@ impl TransportLadder {
         &self,
         _target: &[u8; 32],
     ) -> Result<ConnectedTransport, TransportError> {
-        let transports = self.transports.lock();
+        let transport_types: Vec<TransportType> =
+            self.transports.lock().iter().map(|(t, _)| *t).collect();
         let start = Instant::now();
 
         // Create tasks for each transport
         let mut tasks = Vec::new();
-        for (transport_type, _transport) in transports.iter() {
-            let transport_type = *transport_type;
+        for transport_type in transport_types {
             let timeout_duration = self.timeout;
             
             tasks.push(tokio::spawn(async move {
@@ -326,10 +335,176 @@ impl ReconnectionManager {
     }
 }
 
+/// Default idle interval before a heartbeat is sent. Well under the ~30s UDP
+/// mapping timeout of aggressive NATs.
+pub const DEFAULT_KEEPALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Heartbeat frame. An empty envelope is never valid, so it is reserved.
+pub const HEARTBEAT_FRAME: &[u8] = &[];
+
+/// Keepalive configuration
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeepaliveConfig {
+    /// Send a heartbeat after this long without outbound traffic
+    pub interval: Duration,
+}
+
+impl Default for KeepaliveConfig {
+    fn default() -> Self {
+        Self {
+            interval: DEFAULT_KEEPALIVE_INTERVAL,
+        }
+    }
+}
+
+/// Control plane transport wrapper that keeps idle connections alive.
+///
+/// A heartbeat task sends `HEARTBEAT_FRAME` whenever nothing has been sent
+/// for `interval`, so NAT mappings stay open while a session is idle.
+/// Nothing is sent until `set_keys_established` reports that the peer has
+/// unwrapped its session keys; before that the peer cannot authenticate the
+/// connection and would only see unsolicited empty frames.
+/// Received heartbeats update `last_seen` but are never returned from
+/// `recv`, and neither direction of heartbeat counts as activity for
+/// `idle_for`, so application idle-timeout policies are unaffected.
+///
+/// QUIC connections get the same effect from PING frames via
+/// `QuicConfig::keep_alive_interval`; this is for transports without one.
+pub struct KeepaliveTransport<T> {
+    inner: T,
+    peer: [u8; 32],
+    config: KeepaliveConfig,
+    last_sent: Mutex<tokio::time::Instant>,
+    last_seen: Mutex<Option<tokio::time::Instant>>,
+    last_activity: Mutex<tokio::time::Instant>,
+    keys_established: AtomicBool,
+    heartbeats_sent: AtomicU64,
+}
+
+impl<T: ControlPlaneTransport + 'static> KeepaliveTransport<T> {
+    /// Wrap `inner`, sending heartbeats to `peer`
+    pub fn new(inner: T, peer: [u8; 32], config: KeepaliveConfig) -> Arc<Self> {
+        let now = tokio::time::Instant::now();
+        Arc::new(Self {
+            inner,
+            peer,
+            config,
+            last_sent: Mutex::new(now),
+            last_seen: Mutex::new(None),
+            last_activity: Mutex::new(now),
+            keys_established: AtomicBool::new(false),
+            heartbeats_sent: AtomicU64::new(0),
+        })
+    }
+
+    /// Start the heartbeat task. It stops when the transport is dropped or
+    /// reports `Disconnected`.
+    pub fn spawn_heartbeat(self: &Arc<Self>) -> JoinHandle<()> {
+        let weak: Weak<Self> = Arc::downgrade(self);
+        let interval = self.config.interval;
+        tokio::spawn(async move {
+            loop {
+                let due = match weak.upgrade() {
+                    Some(this) => *this.last_sent.lock() + interval,
+                    None => return,
+                };
+                tokio::time::sleep_until(due).await;
+
+                let Some(this) = weak.upgrade() else { return };
+                if this.last_sent.lock().elapsed() < interval {
+                    continue;
+                }
+                if !this.keys_established() {
+                    *this.last_sent.lock() = tokio::time::Instant::now();
+                    continue;
+                }
+                match this.inner.send(&this.peer, HEARTBEAT_FRAME).await {
+                    Ok(()) => {
+                        this.heartbeats_sent.fetch_add(1, Ordering::Relaxed);
+                    }
+                    Err(TransportError::Disconnected) => return,
+                    // Transient failure; try again next interval
+                    Err(_) => {}
+                }
+                *this.last_sent.lock() = tokio::time::Instant::now();
+            }
+        })
+    }
+
+    /// Record that session keys with the peer are established, enabling
+    /// heartbeats
+    pub fn set_keys_established(&self) {
+        self.keys_established.store(true, Ordering::Release);
+    }
+
+    /// Whether heartbeats are being sent to the peer
+    pub fn keys_established(&self) -> bool {
+        self.keys_established.load(Ordering::Acquire)
+    }
+
+    /// When anything, including a heartbeat, was last received from the peer
+    pub fn last_seen(&self) -> Option<tokio::time::Instant> {
+        *self.last_seen.lock()
+    }
+
+    /// Time since the last application message in either direction
+    pub fn idle_for(&self) -> Duration {
+        self.last_activity.lock().elapsed()
+    }
+
+    /// Number of heartbeats sent
+    pub fn heartbeats_sent(&self) -> u64 {
+        self.heartbeats_sent.load(Ordering::Relaxed)
+    }
+
+    /// The wrapped transport
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+}
+
+#[async_trait]
+impl<T: ControlPlaneTransport + 'static> ControlPlaneTransport for KeepaliveTransport<T> {
+    async fn send(
+        &self,
+        recipient: &[u8; 32],
+        envelope: &[u8],
+    ) -> Result<(), TransportError> {
+        if envelope == HEARTBEAT_FRAME {
+            return Err(TransportError::Other("empty envelope is reserved for heartbeats".to_string()));
+        }
+        self.inner.send(recipient, envelope).await?;
+        let now = tokio::time::Instant::now();
+        *self.last_sent.lock() = now;
+        *self.last_activity.lock() = now;
+        Ok(())
+    }
+
+    async fn recv(&self) -> Result<([u8; 32], Vec<u8>), TransportError> {
+        loop {
+            let (sender, data) = self.inner.recv().await?;
+            let now = tokio::time::Instant::now();
+            *self.last_seen.lock() = Some(now);
+            if data == HEARTBEAT_FRAME {
+                continue;
+            }
+            *self.last_activity.lock() = now;
+            return Ok((sender, data));
+        }
+    }
+
+    fn is_connected(&self) -> bool {
+        self.inner.is_connected()
+    }
+
+    fn transport_type(&self) -> TransportType {
+        self.inner.transport_type()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::testing::MockTransport;
     use proptest::prelude::*;
 
     #[test]
@@ -375,6 +550,144 @@ mod tests {
         });
     }
 
+    /// Loopback link behind a NAT that forgets the mapping after
+    /// `timeout` without outbound traffic.
+    struct NatLink {
+        inner: crate::testing::LoopbackTransport,
+        timeout: Duration,
+        last_outbound: Mutex<tokio::time::Instant>,
+    }
+
+    impl NatLink {
+        fn new(inner: crate::testing::LoopbackTransport, timeout: Duration) -> Self {
+            Self { inner, timeout, last_outbound: Mutex::new(tokio::time::Instant::now()) }
+        }
+    }
+
+    #[async_trait]
+    impl ControlPlaneTransport for NatLink {
+        async fn send(&self, recipient: &[u8; 32], envelope: &[u8]) -> Result<(), TransportError> {
+            let expired = {
+                let mut last = self.last_outbound.lock();
+                let expired = last.elapsed() > self.timeout;
+                *last = tokio::time::Instant::now();
+                expired
+            };
+            if expired {
+                self.inner.disconnect();
+                return Err(TransportError::Disconnected);
+            }
+            self.inner.send(recipient, envelope).await
+        }
+
+        async fn recv(&self) -> Result<([u8; 32], Vec<u8>), TransportError> {
+            self.inner.recv().await
+        }
+
+        fn is_connected(&self) -> bool {
+            self.inner.is_connected()
+        }
+
+        fn transport_type(&self) -> TransportType {
+            TransportType::Direct
+        }
+    }
+
+    #[test]
+    fn test_keepalive_survives_nat_timeout() {
+        const NAT_TIMEOUT: Duration = Duration::from_secs(30);
+        const IDLE: Duration = Duration::from_secs(120);
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .start_paused(true)
+            .build()
+            .unwrap();
+        rt.block_on(async {
+            let (viewer, host) = crate::testing::LoopbackTransport::pair();
+            let viewer = KeepaliveTransport::new(
+                NatLink::new(viewer, NAT_TIMEOUT),
+                [2u8; 32],
+                KeepaliveConfig::default(),
+            );
+            let host = KeepaliveTransport::new(host, [1u8; 32], KeepaliveConfig::default());
+            viewer.set_keys_established();
+            viewer.spawn_heartbeat();
+
+            let receiver = host.clone();
+            let received = tokio::spawn(async move { receiver.recv().await });
+
+            tokio::time::sleep(IDLE).await;
+
+            // Heartbeats kept the peer seen but are not activity on either side
+            assert!(viewer.heartbeats_sent() >= IDLE.as_secs() / DEFAULT_KEEPALIVE_INTERVAL.as_secs() - 1);
+            assert!(host.last_seen().unwrap().elapsed() <= DEFAULT_KEEPALIVE_INTERVAL);
+            assert!(host.idle_for() >= IDLE);
+            assert!(viewer.idle_for() >= IDLE);
+
+            // The NAT mapping is still open and only the app message is delivered
+            viewer.send(&[2u8; 32], b"still here").await.unwrap();
+            assert_eq!(received.await.unwrap().unwrap().1, b"still here");
+            assert!(host.idle_for() < Duration::from_secs(1));
+            assert!(matches!(
+                viewer.send(&[2u8; 32], HEARTBEAT_FRAME).await,
+                Err(TransportError::Other(_))
+            ));
+        });
+    }
+
+    #[test]
+    fn test_idle_link_dropped_without_keepalive() {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .start_paused(true)
+            .build()
+            .unwrap();
+        rt.block_on(async {
+            let (viewer, host) = crate::testing::LoopbackTransport::pair();
+            let viewer = KeepaliveTransport::new(
+                NatLink::new(viewer, Duration::from_secs(30)),
+                [2u8; 32],
+                KeepaliveConfig::default(),
+            );
+
+            tokio::time::sleep(Duration::from_secs(120)).await;
+            assert!(matches!(
+                viewer.send(&[2u8; 32], b"too late").await,
+                Err(TransportError::Disconnected)
+            ));
+            assert!(viewer.idle_for() >= Duration::from_secs(120));
+            assert!(!host.is_connected());
+        });
+    }
+
+    #[test]
+    fn test_no_heartbeats_before_keys_established() {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .start_paused(true)
+            .build()
+            .unwrap();
+        rt.block_on(async {
+            let (viewer, host) = crate::testing::LoopbackTransport::pair();
+            let viewer = KeepaliveTransport::new(viewer, [2u8; 32], KeepaliveConfig::default());
+            let host = KeepaliveTransport::new(host, [1u8; 32], KeepaliveConfig::default());
+            viewer.spawn_heartbeat();
+
+            let receiver = host.clone();
+            tokio::spawn(async move { receiver.recv().await });
+
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            assert_eq!(viewer.heartbeats_sent(), 0);
+            assert!(host.last_seen().is_none());
+
+            viewer.set_keys_established();
+            tokio::time::sleep(DEFAULT_KEEPALIVE_INTERVAL * 2).await;
+            assert!(viewer.heartbeats_sent() >= 1);
+            assert!(host.last_seen().is_some());
+        });
+    }
+
     proptest! {
         #[test]
         fn prop_transport_fallback(
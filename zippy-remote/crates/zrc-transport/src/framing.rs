@@ -1,8 +1,9 @@
 //! Length-prefixed framing for reliable message delimiting.
 
-use bytes::{Buf, BufMut, BytesMut};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
 use std::io;
 use thiserror::Error;
+use tokio::io::{AsyncRead, AsyncReadExt};
 
 /// Maximum frame size for control plane messages (64KB)
 pub const MAX_CONTROL_FRAME_SIZE: usize = 64 * 1024;
@@ -10,12 +11,18 @@ pub const MAX_CONTROL_FRAME_SIZE: usize = 64 * 1024;
 /// Maximum frame size for media plane messages (1MB)
 pub const MAX_MEDIA_FRAME_SIZE: usize = 1024 * 1024;
 
+/// Default cap on a declared frame length for `FrameReader` (16MB)
+pub const DEFAULT_MAX_FRAME_SIZE: usize = 16 * 1024 * 1024;
+
 /// Framing error
 #[derive(Debug, Error)]
 pub enum FramingError {
     #[error("Frame too large: {0} bytes (max: {1})")]
     TooLarge(usize, usize),
 
+    #[error("Declared frame length {declared} exceeds limit {max}")]
+    FrameTooLarge { declared: usize, max: usize },
+
     #[error("Incomplete frame: need {0} more bytes")]
     Incomplete(usize),
 
@@ -66,11 +73,11 @@ impl LengthCodec {
             return Err(FramingError::Incomplete(4 - framed.len()));
         }
 
-        let mut buf = &framed[..];
+        let mut buf = framed;
         let len = buf.get_u32() as usize;
 
         if len > self.max_frame_size {
-            return Err(FramingError::TooLarge(len, self.max_frame_size));
+            return Err(FramingError::FrameTooLarge { declared: len, max: self.max_frame_size });
         }
 
         if buf.remaining() < len {
@@ -93,7 +100,7 @@ impl LengthCodec {
         };
 
         if len > self.max_frame_size {
-            return Err(FramingError::TooLarge(len, self.max_frame_size));
+            return Err(FramingError::FrameTooLarge { declared: len, max: self.max_frame_size });
         }
 
         if buf.len() < 4 + len {
@@ -107,6 +114,74 @@ impl LengthCodec {
     }
 }
 
+/// Reads length-prefixed frames from an async stream.
+///
+/// The declared length is checked against `max_frame_size` before the body
+/// buffer is allocated, so a peer cannot make us allocate by sending a huge
+/// length prefix.
+pub struct FrameReader<R> {
+    inner: R,
+    max_frame_size: usize,
+    buf: BytesMut,
+}
+
+impl<R: AsyncRead + Unpin> FrameReader<R> {
+    /// Create a reader with the default frame size limit
+    pub fn new(inner: R) -> Self {
+        Self::with_max_frame_size(inner, DEFAULT_MAX_FRAME_SIZE)
+    }
+
+    /// Create a reader with a per-stream frame size limit
+    pub fn with_max_frame_size(inner: R, max_frame_size: usize) -> Self {
+        Self {
+            inner,
+            max_frame_size,
+            buf: BytesMut::new(),
+        }
+    }
+
+    /// Current frame size limit
+    pub fn max_frame_size(&self) -> usize {
+        self.max_frame_size
+    }
+
+    /// Change the frame size limit for subsequent frames
+    pub fn set_max_frame_size(&mut self, max_frame_size: usize) {
+        self.max_frame_size = max_frame_size;
+    }
+
+    /// Bytes currently allocated for frame bodies
+    pub fn buffer_capacity(&self) -> usize {
+        self.buf.capacity()
+    }
+
+    /// Unwrap the underlying stream
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Read the next frame.
+    /// Returns None on clean EOF before a length prefix; EOF inside a frame is an error.
+    pub async fn read_frame(&mut self) -> Result<Option<Bytes>, FramingError> {
+        let mut len_buf = [0u8; 4];
+        let n = self.inner.read(&mut len_buf).await?;
+        if n == 0 {
+            return Ok(None);
+        }
+        self.inner.read_exact(&mut len_buf[n..]).await?;
+
+        let len = u32::from_be_bytes(len_buf) as usize;
+        if len > self.max_frame_size {
+            return Err(FramingError::FrameTooLarge { declared: len, max: self.max_frame_size });
+        }
+
+        self.buf.clear();
+        self.buf.resize(len, 0);
+        self.inner.read_exact(&mut self.buf[..]).await?;
+        Ok(Some(self.buf.split().freeze()))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -144,7 +219,72 @@ mod tests {
         assert_eq!(data, decoded.as_slice());
     }
 
+    #[test]
+    fn test_frame_reader_round_trip() {
+        tokio_test::block_on(async {
+            let codec = LengthCodec::media();
+            let mut stream = codec.encode(b"first").unwrap();
+            stream.extend(codec.encode(b"").unwrap());
+            stream.extend(codec.encode(b"third").unwrap());
+
+            let mut reader = FrameReader::new(&stream[..]);
+            assert_eq!(&reader.read_frame().await.unwrap().unwrap()[..], b"first");
+            assert_eq!(&reader.read_frame().await.unwrap().unwrap()[..], b"");
+            assert_eq!(&reader.read_frame().await.unwrap().unwrap()[..], b"third");
+            assert!(reader.read_frame().await.unwrap().is_none());
+
+            // Truncated prefix is an error, not EOF
+            let mut reader = FrameReader::new(&[0u8, 0][..]);
+            assert!(matches!(reader.read_frame().await, Err(FramingError::Io(_))));
+        });
+    }
+
+    #[test]
+    fn test_decode_rejects_declared_length() {
+        let codec = LengthCodec::control();
+        let mut framed = ((MAX_CONTROL_FRAME_SIZE + 1) as u32).to_be_bytes().to_vec();
+        framed.extend_from_slice(b"short");
+        assert!(matches!(
+            codec.decode(&framed),
+            Err(FramingError::FrameTooLarge { declared, max: MAX_CONTROL_FRAME_SIZE })
+                if declared == MAX_CONTROL_FRAME_SIZE + 1
+        ));
+        let mut buf = BytesMut::from(&framed[..]);
+        assert!(matches!(
+            codec.decode_stream(&mut buf),
+            Err(FramingError::FrameTooLarge { .. })
+        ));
+    }
+
     proptest! {
+        /// Crafted length prefixes above the limit are rejected without
+        /// allocating or reading the body.
+        #[test]
+        fn prop_frame_reader_rejects_before_allocation(
+            max in 0usize..4096,
+            excess in 1u64..=u32::MAX as u64,
+            tail in prop::collection::vec(any::<u8>(), 0..64),
+        ) {
+            let declared = (max as u64 + excess).min(u32::MAX as u64) as u32;
+            prop_assume!(declared as usize > max);
+            let mut stream = declared.to_be_bytes().to_vec();
+            stream.extend_from_slice(&tail);
+
+            let (result, capacity, unread) = tokio_test::block_on(async {
+                let mut reader = FrameReader::with_max_frame_size(&stream[..], max);
+                let result = reader.read_frame().await;
+                let capacity = reader.buffer_capacity();
+                (result, capacity, reader.into_inner().len())
+            });
+            let rejected = matches!(
+                result,
+                Err(FramingError::FrameTooLarge { declared: d, max: m }) if d == declared as usize && m == max
+            );
+            prop_assert!(rejected);
+            prop_assert_eq!(capacity, 0);
+            prop_assert_eq!(unread, tail.len());
+        }
+
         #[test]
         fn prop_framing_round_trip(data in prop::collection::vec(any::<u8>(), 0..MAX_CONTROL_FRAME_SIZE)) {
             let codec = LengthCodec::control();
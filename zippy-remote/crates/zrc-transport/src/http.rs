@@ -67,8 +67,7 @@ impl LongPollConfig {
 
         let delay_secs = self.initial_backoff.as_secs_f64()
             * self.backoff_multiplier.powi(attempt as i32);
-        let delay = Duration::from_secs_f64(delay_secs.min(self.max_backoff.as_secs_f64()));
-        delay
+        Duration::from_secs_f64(delay_secs.min(self.max_backoff.as_secs_f64()))
     }
 }
 
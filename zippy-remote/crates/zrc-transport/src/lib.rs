@@ -13,6 +13,7 @@ pub mod metrics;
 pub mod testing;
 pub mod quic;
 pub mod http;
+pub mod mesh;
 
 pub use traits::*;
 pub use framing::*;
@@ -23,3 +24,4 @@ pub use metrics::*;
 pub use testing::*;
 pub use quic::*;
 pub use http::*;
+pub use mesh::*;
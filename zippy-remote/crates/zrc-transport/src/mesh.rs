@@ -0,0 +1,164 @@
+//! Mesh mailbox wire format and duplicate suppression.
+//!
+//! Mesh nodes hand each other frames of `ttl (1 byte) || recipient_id (32
+//! bytes) || envelope_bytes`. The envelope stays opaque; its SHA-256 is the
+//! id nodes use to recognise a copy that reached them over another path.
+//! Shared by the in-process mesh (`zrc_core::mesh_mailbox`) and the HTTP
+//! relay in `zrc-rendezvous`.
+
+use bytes::Bytes;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, VecDeque};
+
+use crate::framing::FramingError;
+
+/// Frame header: ttl + recipient id.
+pub const MESH_FRAME_HEADER_LEN: usize = 1 + 32;
+
+/// Envelope ids remembered for duplicate suppression.
+pub const SEEN_CACHE_CAPACITY: usize = 4096;
+
+/// A sealed envelope in transit between mesh nodes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MeshFrame {
+    /// Remaining hops, including the one delivering this frame.
+    pub ttl: u8,
+    /// Mailbox the envelope is addressed to.
+    pub recipient_id: [u8; 32],
+    /// Sealed envelope bytes.
+    pub envelope: Bytes,
+}
+
+impl MeshFrame {
+    /// Frame for `envelope_bytes` starting with `ttl` hops.
+    pub fn new(ttl: u8, recipient_id: [u8; 32], envelope_bytes: &[u8]) -> Self {
+        Self {
+            ttl,
+            recipient_id,
+            envelope: Bytes::copy_from_slice(envelope_bytes),
+        }
+    }
+
+    /// Id used for duplicate suppression. Independent of the TTL, so the same
+    /// envelope arriving over different paths is recognised.
+    pub fn envelope_id(&self) -> [u8; 32] {
+        Sha256::digest(&self.envelope).into()
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(MESH_FRAME_HEADER_LEN + self.envelope.len());
+        out.push(self.ttl);
+        out.extend_from_slice(&self.recipient_id);
+        out.extend_from_slice(&self.envelope);
+        out
+    }
+
+    /// Parse a frame; the envelope shares `bytes` rather than copying it.
+    pub fn decode(bytes: &Bytes) -> Result<Self, FramingError> {
+        if bytes.len() <= MESH_FRAME_HEADER_LEN {
+            return Err(FramingError::InvalidFormat);
+        }
+        let mut recipient_id = [0u8; 32];
+        recipient_id.copy_from_slice(&bytes[1..MESH_FRAME_HEADER_LEN]);
+        Ok(Self {
+            ttl: bytes[0],
+            recipient_id,
+            envelope: bytes.slice(MESH_FRAME_HEADER_LEN..),
+        })
+    }
+
+    /// Copy for the next hop, or None once the TTL is spent.
+    pub fn next_hop(&self) -> Option<Self> {
+        let ttl = self.ttl.checked_sub(1).filter(|ttl| *ttl > 0)?;
+        Some(Self {
+            ttl,
+            ..self.clone()
+        })
+    }
+}
+
+/// Bounded map of recently seen envelope ids to the highest TTL they
+/// arrived with.
+#[derive(Debug, Default)]
+pub struct SeenCache {
+    ids: HashMap<[u8; 32], u8>,
+    order: VecDeque<[u8; 32]>,
+}
+
+impl SeenCache {
+    pub fn contains(&self, id: &[u8; 32]) -> bool {
+        self.ids.contains_key(id)
+    }
+
+    /// Record `id` arriving with `ttl`. Returns false if it was already seen
+    /// with at least that many hops left.
+    pub fn insert(&mut self, id: [u8; 32], ttl: u8) -> bool {
+        if let Some(best) = self.ids.get_mut(&id) {
+            if *best >= ttl {
+                return false;
+            }
+            *best = ttl;
+            return true;
+        }
+        self.ids.insert(id, ttl);
+        self.order.push_back(id);
+        if self.order.len() > SEEN_CACHE_CAPACITY {
+            if let Some(oldest) = self.order.pop_front() {
+                self.ids.remove(&oldest);
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_roundtrip() {
+        let frame = MeshFrame::new(3, [9; 32], b"sealed");
+        let encoded = Bytes::from(frame.encode());
+        assert_eq!(MeshFrame::decode(&encoded).unwrap(), frame);
+        assert!(MeshFrame::decode(&encoded.slice(..MESH_FRAME_HEADER_LEN)).is_err());
+    }
+
+    #[test]
+    fn test_envelope_id_ignores_ttl() {
+        let a = MeshFrame::new(4, [7; 32], b"sealed");
+        let b = MeshFrame::new(1, [7; 32], b"sealed");
+        assert_eq!(a.envelope_id(), b.envelope_id());
+    }
+
+    #[test]
+    fn test_next_hop_spends_ttl() {
+        let frame = MeshFrame::new(2, [7; 32], b"sealed");
+        let next = frame.next_hop().unwrap();
+        assert_eq!(next.ttl, 1);
+        assert!(next.next_hop().is_none());
+    }
+
+    #[test]
+    fn test_seen_cache_evicts_oldest() {
+        let id = |i: usize| -> [u8; 32] { Sha256::digest(i.to_le_bytes()).into() };
+        let mut cache = SeenCache::default();
+        for i in 0..=SEEN_CACHE_CAPACITY {
+            assert!(cache.insert(id(i), 1));
+        }
+        assert_eq!(cache.ids.len(), SEEN_CACHE_CAPACITY);
+        // The first id was evicted and is accepted again, evicting the second
+        assert!(cache.insert(id(0), 1));
+        assert!(!cache.contains(&id(1)));
+        assert!(!cache.insert(id(2), 1));
+    }
+
+    #[test]
+    fn test_seen_cache_accepts_more_hops() {
+        let mut cache = SeenCache::default();
+        assert!(cache.insert([1; 32], 2));
+        assert!(!cache.insert([1; 32], 2));
+        assert!(!cache.insert([1; 32], 1));
+        assert!(cache.insert([1; 32], 3));
+        assert_eq!(cache.order.len(), 1);
+    }
+}
@@ -3,8 +3,30 @@
 use crate::mux::ChannelType;
 use parking_lot::Mutex;
 use std::collections::HashMap;
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
 use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Metric name prefix used by the process-wide metrics.
+pub const METRICS_PREFIX: &str = "zrc_transport";
+
+/// Content type of the Prometheus text exposition format.
+pub const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+static GLOBAL_METRICS: OnceLock<TransportMetrics> = OnceLock::new();
+
+/// Process-wide transport metrics, named with the `zrc_transport_` prefix.
+pub fn global_metrics() -> &'static TransportMetrics {
+    GLOBAL_METRICS.get_or_init(|| TransportMetrics::new(METRICS_PREFIX))
+}
+
+/// Render the process-wide transport metrics as Prometheus text.
+pub fn render_prometheus() -> String {
+    global_metrics().export_prometheus()
+}
 
 /// Simple counter
 struct Counter {
@@ -96,6 +118,8 @@ pub struct TransportMetrics {
     messages_sent: Counter,
     messages_received: Counter,
     frames_dropped: Counter,
+    retransmits: Counter,
+    active_connections: AtomicI64,
     rtt_histogram: Histogram,
     connection_duration: Histogram,
     channel_bytes_sent: Mutex<HashMap<ChannelType, Counter>>,
@@ -113,6 +137,8 @@ impl TransportMetrics {
             messages_sent: Counter::new(),
             messages_received: Counter::new(),
             frames_dropped: Counter::new(),
+            retransmits: Counter::new(),
+            active_connections: AtomicI64::new(0),
             rtt_histogram: Histogram::new(),
             connection_duration: Histogram::new(),
             channel_bytes_sent: Mutex::new(HashMap::new()),
@@ -163,99 +189,94 @@ impl TransportMetrics {
         self.connection_duration.record(duration);
     }
 
-    /// Export metrics in Prometheus format
+    /// Record retransmitted packets
+    pub fn record_retransmit(&self, packets: u64) {
+        self.retransmits.inc(packets);
+    }
+
+    /// Record a connection being established
+    pub fn connection_opened(&self) {
+        self.active_connections.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record a connection being closed
+    pub fn connection_closed(&self) {
+        self.active_connections.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    /// Number of currently open connections
+    pub fn active_connections(&self) -> i64 {
+        self.active_connections.load(Ordering::Relaxed)
+    }
+
+    /// Bytes sent on `channel`
+    pub fn channel_bytes_sent(&self, channel: ChannelType) -> u64 {
+        self.channel_bytes_sent.lock().get(&channel).map_or(0, Counter::get)
+    }
+
+    /// Bytes received on `channel`
+    pub fn channel_bytes_received(&self, channel: ChannelType) -> u64 {
+        self.channel_bytes_received.lock().get(&channel).map_or(0, Counter::get)
+    }
+
+    /// Export metrics in Prometheus text exposition format.
+    ///
+    /// Every family is always present (zero when unused) so dashboards see
+    /// stable names.
     pub fn export_prometheus(&self) -> String {
         let mut output = String::new();
         let prefix = &self.prefix;
 
-        // Global counters
-        output.push_str(&format!(
-            "# HELP {}_bytes_sent_total Total bytes sent\n",
-            prefix
-        ));
-        output.push_str(&format!(
-            "# TYPE {}_bytes_sent_total counter\n",
-            prefix
-        ));
-        output.push_str(&format!(
-            "{}_bytes_sent_total {}\n",
-            prefix,
-            self.bytes_sent.get()
-        ));
-
-        output.push_str(&format!(
-            "# HELP {}_bytes_received_total Total bytes received\n",
-            prefix
-        ));
-        output.push_str(&format!(
-            "# TYPE {}_bytes_received_total counter\n",
-            prefix
-        ));
-        output.push_str(&format!(
-            "{}_bytes_received_total {}\n",
-            prefix,
-            self.bytes_received.get()
-        ));
-
-        output.push_str(&format!(
-            "# HELP {}_messages_sent_total Total messages sent\n",
-            prefix
-        ));
-        output.push_str(&format!(
-            "# TYPE {}_messages_sent_total counter\n",
-            prefix
-        ));
-        output.push_str(&format!(
-            "{}_messages_sent_total {}\n",
-            prefix,
-            self.messages_sent.get()
-        ));
-
-        output.push_str(&format!(
-            "# HELP {}_messages_received_total Total messages received\n",
-            prefix
-        ));
-        output.push_str(&format!(
-            "# TYPE {}_messages_received_total counter\n",
-            prefix
-        ));
-        output.push_str(&format!(
-            "{}_messages_received_total {}\n",
-            prefix,
-            self.messages_received.get()
-        ));
-
-        output.push_str(&format!(
-            "# HELP {}_frames_dropped_total Total frames dropped\n",
-            prefix
-        ));
-        output.push_str(&format!(
-            "# TYPE {}_frames_dropped_total counter\n",
-            prefix
-        ));
-        output.push_str(&format!(
-            "{}_frames_dropped_total {}\n",
-            prefix,
-            self.frames_dropped.get()
-        ));
-
-        // RTT histogram
-        let (sum, count, _buckets) = self.rtt_histogram.get();
-        if count > 0 {
-            output.push_str(&format!(
-                "# HELP {}_rtt_milliseconds Round-trip time in milliseconds\n",
-                prefix
-            ));
-            output.push_str(&format!(
-                "# TYPE {}_rtt_milliseconds histogram\n",
-                prefix
-            ));
-            let avg = sum / count;
-            output.push_str(&format!("{}_rtt_milliseconds_sum {}\n", prefix, sum));
-            output.push_str(&format!("{}_rtt_milliseconds_count {}\n", prefix, count));
-            output.push_str(&format!("{}_rtt_milliseconds_avg {}\n", prefix, avg));
+        let counters = [
+            ("bytes_sent_total", "Total bytes sent", self.bytes_sent.get()),
+            ("bytes_received_total", "Total bytes received", self.bytes_received.get()),
+            ("messages_sent_total", "Total messages sent", self.messages_sent.get()),
+            ("messages_received_total", "Total messages received", self.messages_received.get()),
+            ("frames_dropped_total", "Total frames dropped", self.frames_dropped.get()),
+            ("retransmits_total", "Total packets retransmitted", self.retransmits.get()),
+        ];
+        for (name, help, value) in counters {
+            write_header(&mut output, prefix, name, help, "counter");
+            let _ = writeln!(output, "{}_{} {}", prefix, name, value);
         }
 
+        write_header(&mut output, prefix, "active_connections", "Currently open connections", "gauge");
+        let _ = writeln!(output, "{}_active_connections {}", prefix, self.active_connections());
+
+        let per_channel = [
+            ("channel_bytes_sent_total", "Bytes sent per channel", &self.channel_bytes_sent),
+            ("channel_bytes_received_total", "Bytes received per channel", &self.channel_bytes_received),
+            ("channel_frames_dropped_total", "Frames dropped per channel", &self.channel_dropped),
+        ];
+        for (name, help, map) in per_channel {
+            write_header(&mut output, prefix, name, help, "counter");
+            let mut values: Vec<_> = map
+                .lock()
+                .iter()
+                .map(|(channel, counter)| (channel_label(*channel), counter.get()))
+                .collect();
+            values.sort();
+            for (channel, value) in values {
+                let _ = writeln!(output, "{}_{}{{channel=\"{}\"}} {}", prefix, name, channel, value);
+            }
+        }
+
+        // RTT histogram (cumulative buckets, milliseconds)
+        let (sum, count, buckets) = self.rtt_histogram.get();
+        write_header(&mut output, prefix, "rtt_milliseconds", "Round-trip time in milliseconds", "histogram");
+        let mut cumulative = 0;
+        for (upper_bound, bucket_count) in buckets {
+            cumulative += bucket_count;
+            let le = if upper_bound.is_infinite() {
+                "+Inf".to_string()
+            } else {
+                format!("{}", upper_bound * 1000.0)
+            };
+            let _ = writeln!(output, "{}_rtt_milliseconds_bucket{{le=\"{}\"}} {}", prefix, le, cumulative);
+        }
+        let _ = writeln!(output, "{}_rtt_milliseconds_sum {}", prefix, sum);
+        let _ = writeln!(output, "{}_rtt_milliseconds_count {}", prefix, count);
+
         output
     }
 
@@ -266,6 +287,7 @@ impl TransportMetrics {
         self.messages_sent.reset();
         self.messages_received.reset();
         self.frames_dropped.reset();
+        self.retransmits.reset();
         self.rtt_histogram.reset();
         self.connection_duration.reset();
         
@@ -278,6 +300,72 @@ impl TransportMetrics {
     }
 }
 
+fn write_header(output: &mut String, prefix: &str, name: &str, help: &str, kind: &str) {
+    let _ = writeln!(output, "# HELP {}_{} {}", prefix, name, help);
+    let _ = writeln!(output, "# TYPE {}_{} {}", prefix, name, kind);
+}
+
+fn channel_label(channel: ChannelType) -> &'static str {
+    match channel {
+        ChannelType::Control => "control",
+        ChannelType::Frames => "frames",
+        ChannelType::Clipboard => "clipboard",
+        ChannelType::Files => "files",
+        ChannelType::Audio => "audio",
+    }
+}
+
+/// Serve Prometheus text from `render` on `GET /metrics` until the listener
+/// fails. Meant for the relay and agent's local metrics port; other paths get
+/// a 404.
+pub async fn serve_prometheus<F>(listener: TcpListener, render: F) -> std::io::Result<()>
+where
+    F: Fn() -> String + Send + Sync + 'static,
+{
+    let render = Arc::new(render);
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let render = render.clone();
+        tokio::spawn(async move {
+            let _ = handle_metrics_request(stream, render.as_ref()).await;
+        });
+    }
+}
+
+async fn handle_metrics_request<F: Fn() -> String>(
+    mut stream: TcpStream,
+    render: &F,
+) -> std::io::Result<()> {
+    // Read the request head; only the request line matters
+    let mut buf = Vec::with_capacity(1024);
+    let mut chunk = [0u8; 1024];
+    while !buf.windows(4).any(|w| w == b"\r\n\r\n") && buf.len() < 8192 {
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            break;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+    let head = String::from_utf8_lossy(&buf);
+    let mut parts = head.lines().next().unwrap_or_default().split_whitespace();
+    let is_metrics = parts.next() == Some("GET") && parts.next() == Some("/metrics");
+
+    let (status, content_type, body) = if is_metrics {
+        ("200 OK", PROMETHEUS_CONTENT_TYPE, render())
+    } else {
+        ("404 Not Found", "text/plain", "not found\n".to_string())
+    };
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -304,4 +392,118 @@ mod tests {
         let prom = metrics.export_prometheus();
         assert!(prom.contains("test_rtt_milliseconds"));
     }
+
+    /// Check the exposition format: every sample belongs to a family declared
+    /// by `# HELP` and `# TYPE`, values parse, and histogram buckets are
+    /// cumulative and end in `+Inf` equal to `_count`.
+    fn assert_well_formed(text: &str, prefix: &str) {
+        let mut families: HashMap<String, String> = HashMap::new();
+        let mut helped = std::collections::HashSet::new();
+        let mut last_bucket: Option<f64> = None;
+        let mut inf_bucket = None;
+        let mut hist_count = None;
+
+        for line in text.lines() {
+            if let Some(rest) = line.strip_prefix("# HELP ") {
+                let name = rest.split_whitespace().next().unwrap();
+                assert!(helped.insert(name.to_string()), "duplicate HELP for {}", name);
+                continue;
+            }
+            if let Some(rest) = line.strip_prefix("# TYPE ") {
+                let mut parts = rest.split_whitespace();
+                let name = parts.next().unwrap().to_string();
+                let kind = parts.next().unwrap().to_string();
+                assert!(helped.contains(&name), "TYPE before HELP for {}", name);
+                assert!(["counter", "gauge", "histogram"].contains(&kind.as_str()));
+                assert!(families.insert(name, kind).is_none());
+                continue;
+            }
+            assert!(!line.starts_with('#'), "unexpected comment: {}", line);
+
+            let (series, value) = line.rsplit_once(' ').expect("sample needs a value");
+            let value: f64 = value.parse().expect("sample value must be numeric");
+            let name = series.split('{').next().unwrap();
+            assert!(name.starts_with(&format!("{}_", prefix)), "bad name {}", name);
+            assert!(name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'));
+            if series.contains('{') {
+                assert!(series.ends_with("\"}"), "bad labels in {}", series);
+            }
+
+            let family = ["_bucket", "_sum", "_count"]
+                .iter()
+                .find_map(|s| name.strip_suffix(s).filter(|f| families.get(*f).map(String::as_str) == Some("histogram")))
+                .unwrap_or(name);
+            assert!(families.contains_key(family), "sample {} without TYPE", name);
+
+            if name.ends_with("_bucket") {
+                if let Some(prev) = last_bucket {
+                    assert!(value >= prev, "buckets must be cumulative");
+                }
+                last_bucket = Some(value);
+                if series.contains("le=\"+Inf\"") {
+                    inf_bucket = Some(value);
+                }
+            } else if name.ends_with("_count") {
+                hist_count = Some(value);
+            }
+        }
+        assert_eq!(inf_bucket, hist_count);
+    }
+
+    #[test]
+    fn test_prometheus_output_well_formed() {
+        let metrics = TransportMetrics::new(METRICS_PREFIX);
+        metrics.record_send(ChannelType::Control, 100);
+        metrics.record_send(ChannelType::Frames, 5000);
+        metrics.record_recv(ChannelType::Frames, 200);
+        metrics.record_drop(ChannelType::Frames);
+        metrics.record_retransmit(3);
+        metrics.connection_opened();
+        metrics.connection_opened();
+        metrics.connection_closed();
+        for ms in [5, 20, 70, 300, 900] {
+            metrics.record_rtt(Duration::from_millis(ms));
+        }
+
+        let prom = metrics.export_prometheus();
+        assert_well_formed(&prom, METRICS_PREFIX);
+        assert!(prom.contains("zrc_transport_retransmits_total 3"));
+        assert!(prom.contains("zrc_transport_active_connections 1"));
+        assert!(prom.contains("zrc_transport_channel_bytes_sent_total{channel=\"frames\"} 5000"));
+        assert!(prom.contains("zrc_transport_rtt_milliseconds_bucket{le=\"100\"} 3"));
+        assert!(prom.contains("zrc_transport_rtt_milliseconds_count 5"));
+
+        // Empty metrics are still well-formed
+        assert_well_formed(&TransportMetrics::new(METRICS_PREFIX).export_prometheus(), METRICS_PREFIX);
+    }
+
+    #[test]
+    fn test_serve_prometheus() {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        rt.block_on(async {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            tokio::spawn(serve_prometheus(listener, render_prometheus));
+
+            global_metrics().record_retransmit(1);
+            let fetch = |path: &'static str| async move {
+                let mut stream = TcpStream::connect(addr).await.unwrap();
+                let request = format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path);
+                stream.write_all(request.as_bytes()).await.unwrap();
+                let mut response = String::new();
+                stream.read_to_string(&mut response).await.unwrap();
+                response
+            };
+
+            let response = fetch("/metrics").await;
+            assert!(response.starts_with("HTTP/1.1 200 OK"));
+            assert!(response.contains(PROMETHEUS_CONTENT_TYPE));
+            assert!(response.contains("zrc_transport_retransmits_total"));
+
+            assert!(fetch("/other").await.starts_with("HTTP/1.1 404"));
+        });
+    }
 }
@@ -1,9 +1,13 @@
 //! Channel multiplexing support.
 
+use crate::backpressure::{BackpressureError, BackpressureHandler, DropPolicy};
+use crate::metrics::global_metrics;
 use async_trait::async_trait;
+use bytes::Bytes;
 use parking_lot::Mutex;
 use std::collections::{HashMap, VecDeque};
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use thiserror::Error;
 
 /// Channel types for multiplexing
@@ -72,6 +76,12 @@ pub enum MuxError {
     #[error("Channel already open: {0:?}")]
     ChannelAlreadyOpen(ChannelType),
 
+    #[error("Channel window full: {0:?}")]
+    WouldBlock(ChannelType),
+
+    #[error("Message of {size} bytes exceeds the {window} byte channel window")]
+    TooLarge { size: usize, window: usize },
+
     #[error("Encryption error: {0}")]
     Encryption(String),
 
@@ -99,8 +109,8 @@ pub trait ChannelEncryption: Send + Sync {
 
 /// Multiplexer for routing data to correct channels
 pub struct Multiplexer {
-    channels: parking_lot::Mutex<HashMap<ChannelType, ChannelState>>,
-    encryption: parking_lot::Mutex<Option<Box<dyn ChannelEncryption>>>,
+    channels: parking_lot::Mutex<HashMap<ChannelType, Arc<ChannelState>>>,
+    encryption: parking_lot::Mutex<Option<Arc<dyn ChannelEncryption>>>,
 }
 
 impl Multiplexer {
@@ -118,7 +128,7 @@ impl Multiplexer {
         if channels.contains_key(&channel) {
             return Err(MuxError::ChannelAlreadyOpen(channel));
         }
-        channels.insert(channel, ChannelState::new());
+        channels.insert(channel, Arc::new(ChannelState::new()));
         Ok(())
     }
 
@@ -134,10 +144,12 @@ impl Multiplexer {
 
     /// Send on channel
     pub async fn send(&self, channel: ChannelType, data: &[u8]) -> Result<(), MuxError> {
-        let channels = self.channels.lock();
-        let state = channels
+        let state = self
+            .channels
+            .lock()
             .get(&channel)
-            .ok_or_else(|| MuxError::ChannelClosed(channel))?;
+            .cloned()
+            .ok_or(MuxError::ChannelClosed(channel))?;
 
         if !*state.is_open.lock() {
             return Err(MuxError::ChannelClosed(channel));
@@ -147,36 +159,41 @@ impl Multiplexer {
         let mut payload = data.to_vec();
 
         // Apply encryption if available
-        if let Some(enc) = self.encryption.lock().as_ref() {
+        let encryption = self.encryption.lock().clone();
+        if let Some(enc) = encryption {
             payload = enc.encrypt(channel, seq, &payload).await?;
         }
 
         state.send_buffer.lock().push_back(payload);
+        global_metrics().record_send(channel, data.len());
         Ok(())
     }
 
     /// Receive from any channel
     pub async fn recv(&self) -> Result<(ChannelType, Vec<u8>), MuxError> {
-        let channels = self.channels.lock();
-        
         // Try channels in priority order
-        let mut channel_list: Vec<_> = channels.keys().copied().collect();
-        channel_list.sort_by_key(|c| c.priority());
-
-        for channel in channel_list {
-            if let Some(state) = channels.get(&channel) {
-                let mut recv_buf = state.recv_buffer.lock();
-                if let Some(data) = recv_buf.pop_front() {
-                    let seq = state.next_recv_seq();
-                    
-                    // Apply decryption if available
-                    let mut payload = data;
-                    if let Some(enc) = self.encryption.lock().as_ref() {
-                        payload = enc.decrypt(channel, seq, &payload).await?;
-                    }
-                    
-                    return Ok((channel, payload));
+        let mut channel_list: Vec<_> = self
+            .channels
+            .lock()
+            .iter()
+            .map(|(channel, state)| (*channel, state.clone()))
+            .collect();
+        channel_list.sort_by_key(|(c, _)| c.priority());
+
+        for (channel, state) in channel_list {
+            let popped = state.recv_buffer.lock().pop_front();
+            if let Some(data) = popped {
+                let seq = state.next_recv_seq();
+
+                // Apply decryption if available
+                let mut payload = data;
+                let encryption = self.encryption.lock().clone();
+                if let Some(enc) = encryption {
+                    payload = enc.decrypt(channel, seq, &payload).await?;
                 }
+
+                global_metrics().record_recv(channel, payload.len());
+                return Ok((channel, payload));
             }
         }
 
@@ -185,7 +202,7 @@ impl Multiplexer {
 
     /// Set encryption for channels
     pub fn set_encryption(&self, encryption: Box<dyn ChannelEncryption>) {
-        *self.encryption.lock() = Some(encryption);
+        *self.encryption.lock() = Some(Arc::from(encryption));
     }
 
     /// Get send sequence number for a channel (for testing)
@@ -200,12 +217,29 @@ impl Multiplexer {
         channels.get(&channel).map(|s| s.recv_seq.load(Ordering::SeqCst))
     }
 
+    /// Take the oldest queued outgoing message on a channel, for the
+    /// transport layer to put on the wire.
+    pub fn pop_send(&self, channel: ChannelType) -> Option<Vec<u8>> {
+        let channels = self.channels.lock();
+        let mut buffer = channels.get(&channel)?.send_buffer.lock();
+        buffer.pop_front()
+    }
+
+    /// Number of outgoing messages queued on a channel
+    pub fn send_queue_len(&self, channel: ChannelType) -> usize {
+        let channels = self.channels.lock();
+        channels
+            .get(&channel)
+            .map(|s| s.send_buffer.lock().len())
+            .unwrap_or(0)
+    }
+
     /// Inject data into receive buffer (for testing)
     pub fn inject_recv(&self, channel: ChannelType, data: Vec<u8>) -> Result<(), MuxError> {
         let channels = self.channels.lock();
         let state = channels
             .get(&channel)
-            .ok_or_else(|| MuxError::ChannelClosed(channel))?;
+            .ok_or(MuxError::ChannelClosed(channel))?;
         state.recv_buffer.lock().push_back(data);
         Ok(())
     }
@@ -217,6 +251,87 @@ impl Default for Multiplexer {
     }
 }
 
+/// Send handle for one multiplexed channel with flow control.
+///
+/// Every message counts against the peer's receive window until the peer
+/// reports it consumed (`release`). When the window is full,
+/// `send_with_backpressure` waits and `try_send` fails with `WouldBlock`, so
+/// a slow reader bounds how much a fast writer can queue.
+pub struct MuxChannel {
+    mux: Arc<Multiplexer>,
+    channel: ChannelType,
+    window: Arc<BackpressureHandler>,
+}
+
+impl MuxChannel {
+    /// Create a handle for an open `channel` with a receive window of
+    /// `window_bytes`.
+    pub fn new(
+        mux: Arc<Multiplexer>,
+        channel: ChannelType,
+        window_bytes: usize,
+    ) -> Result<Self, MuxError> {
+        if !mux.channels.lock().contains_key(&channel) {
+            return Err(MuxError::ChannelClosed(channel));
+        }
+        Ok(Self {
+            mux,
+            channel,
+            window: Arc::new(BackpressureHandler::new(window_bytes, DropPolicy::Block)),
+        })
+    }
+
+    /// Channel this handle sends on
+    pub fn channel(&self) -> ChannelType {
+        self.channel
+    }
+
+    /// Window accounting, shared with whatever processes window updates
+    pub fn window(&self) -> Arc<BackpressureHandler> {
+        self.window.clone()
+    }
+
+    /// Send, waiting while the peer's receive window is full.
+    pub async fn send_with_backpressure(&self, data: Bytes) -> Result<(), MuxError> {
+        self.window
+            .reserve_wait(data.len(), self.channel)
+            .await
+            .map_err(|_| self.too_large(data.len()))?;
+        self.send_reserved(&data).await
+    }
+
+    /// Send without waiting; fails with `WouldBlock` if the window is full.
+    pub async fn try_send(&self, data: Bytes) -> Result<(), MuxError> {
+        if data.len() > self.window.limit() {
+            return Err(self.too_large(data.len()));
+        }
+        self.window
+            .try_reserve(data.len(), self.channel)
+            .map_err(|e| match e {
+                BackpressureError::BufferFull => MuxError::WouldBlock(self.channel),
+                other => MuxError::Other(other.to_string()),
+            })?;
+        self.send_reserved(&data).await
+    }
+
+    /// Credit `bytes` back to the window once the peer has consumed them.
+    pub fn release(&self, bytes: usize) {
+        self.window.release(bytes);
+    }
+
+    async fn send_reserved(&self, data: &[u8]) -> Result<(), MuxError> {
+        let result = self.mux.send(self.channel, data).await;
+        if result.is_err() {
+            self.window.release(data.len());
+        }
+        result
+    }
+
+    fn too_large(&self, size: usize) -> MuxError {
+        MuxError::TooLarge { size, window: self.window.limit() }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -256,6 +371,121 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_send_recv_feed_global_metrics() {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        rt.block_on(async {
+            let metrics = global_metrics();
+            let sent = metrics.channel_bytes_sent(ChannelType::Audio);
+            let received = metrics.channel_bytes_received(ChannelType::Audio);
+
+            let mux = Multiplexer::new();
+            mux.open_channel(ChannelType::Audio).unwrap();
+            mux.send(ChannelType::Audio, &[0u8; 100]).await.unwrap();
+            mux.inject_recv(ChannelType::Audio, vec![0u8; 40]).unwrap();
+            mux.recv().await.unwrap();
+
+            // Other tests share the process-wide metrics, so only a lower bound holds
+            assert!(metrics.channel_bytes_sent(ChannelType::Audio) >= sent + 100);
+            assert!(metrics.channel_bytes_received(ChannelType::Audio) >= received + 40);
+        });
+    }
+
+    #[test]
+    fn test_try_send_would_block() {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        rt.block_on(async {
+            let mux = Arc::new(Multiplexer::new());
+            mux.open_channel(ChannelType::Files).unwrap();
+            let channel = MuxChannel::new(mux.clone(), ChannelType::Files, 10).unwrap();
+
+            channel.try_send(Bytes::from_static(b"12345678")).await.unwrap();
+            assert!(matches!(
+                channel.try_send(Bytes::from_static(b"abc")).await,
+                Err(MuxError::WouldBlock(ChannelType::Files))
+            ));
+            assert!(matches!(
+                channel.try_send(Bytes::from(vec![0u8; 11])).await,
+                Err(MuxError::TooLarge { size: 11, window: 10 })
+            ));
+
+            channel.release(8);
+            channel.try_send(Bytes::from_static(b"abc")).await.unwrap();
+            assert_eq!(mux.send_queue_len(ChannelType::Files), 2);
+        });
+    }
+
+    #[test]
+    fn test_slow_reader_throttles_fast_writer() {
+        use crate::testing::LoopbackTransport;
+        use crate::traits::ControlPlaneTransport;
+        use std::time::Duration;
+
+        const WINDOW: usize = 4 * 1024;
+        const CHUNK: usize = 1024;
+        const CHUNKS: usize = 64;
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        rt.block_on(async {
+            let mux = Arc::new(Multiplexer::new());
+            mux.open_channel(ChannelType::Files).unwrap();
+            let channel = Arc::new(MuxChannel::new(mux.clone(), ChannelType::Files, WINDOW).unwrap());
+            let (local, remote) = LoopbackTransport::pair();
+
+            // Pump queued messages onto the transport
+            let pump_mux = mux.clone();
+            let pump = tokio::spawn(async move {
+                for _ in 0..CHUNKS {
+                    let data = loop {
+                        match pump_mux.pop_send(ChannelType::Files) {
+                            Some(data) => break data,
+                            None => tokio::time::sleep(Duration::from_millis(1)).await,
+                        }
+                    };
+                    local.send(&[2u8; 32], &data).await.unwrap();
+                }
+            });
+
+            // Slow reader: consume one chunk at a time, then send a window update
+            let reader_channel = channel.clone();
+            let reader = tokio::spawn(async move {
+                let mut received = 0;
+                for _ in 0..CHUNKS {
+                    let (_, data) = remote.recv().await.unwrap();
+                    tokio::time::sleep(Duration::from_millis(2)).await;
+                    received += data.len();
+                    reader_channel.release(data.len());
+                }
+                received
+            });
+
+            let window = channel.window();
+            let mut peak = 0;
+            for _ in 0..CHUNKS {
+                channel
+                    .send_with_backpressure(Bytes::from(vec![7u8; CHUNK]))
+                    .await
+                    .unwrap();
+                peak = peak.max(window.current_usage());
+                assert!(mux.send_queue_len(ChannelType::Files) <= WINDOW / CHUNK);
+            }
+
+            pump.await.unwrap();
+            assert_eq!(reader.await.unwrap(), CHUNKS * CHUNK);
+            assert!(peak <= WINDOW, "writer exceeded window: {}", peak);
+            assert_eq!(window.current_usage(), 0);
+        });
+    }
+
     #[test]
     fn prop_sequence_monotonicity() {
         use proptest::prelude::*;
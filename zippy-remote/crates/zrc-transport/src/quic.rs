@@ -34,12 +34,48 @@ impl StreamMapper {
     }
 }
 
+/// QUIC congestion controller.
+///
+/// Available controllers per backend:
+/// - quinn (`quinn` feature): all three. quinn always paces sends and has no
+///   switch to turn pacing off, so no pacing option is exposed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CongestionControl {
+    /// Loss-based, fair to other flows; quinn's default
+    #[default]
+    Cubic,
+    /// Classic loss-based Reno
+    NewReno,
+    /// Model-based; better on high bandwidth-delay links (experimental in quinn)
+    Bbr,
+}
+
+impl std::str::FromStr for CongestionControl {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "cubic" => Ok(Self::Cubic),
+            "newreno" | "new_reno" | "reno" => Ok(Self::NewReno),
+            "bbr" => Ok(Self::Bbr),
+            other => Err(format!("unknown congestion controller: {other}")),
+        }
+    }
+}
+
 /// QUIC configuration helpers
 pub struct QuicConfig {
     pub max_idle_timeout: Duration,
     pub keep_alive_interval: Duration,
     pub initial_rtt: Duration,
     pub max_udp_payload_size: usize,
+    /// Let clients keep the connection when their address changes (e.g.
+    /// Wi-Fi to cellular). Maps to quinn's `ServerConfig::migration`.
+    pub allow_migration: bool,
+    /// How often to check a connection's path for changes
+    pub path_poll_interval: Duration,
+    /// Congestion controller for new connections
+    pub congestion_control: CongestionControl,
 }
 
 impl Default for QuicConfig {
@@ -49,6 +85,9 @@ impl Default for QuicConfig {
             keep_alive_interval: Duration::from_secs(10),
             initial_rtt: Duration::from_millis(100),
             max_udp_payload_size: 1200,
+            allow_migration: true,
+            path_poll_interval: Duration::from_millis(500),
+            congestion_control: CongestionControl::Cubic,
         }
     }
 }
@@ -61,6 +100,9 @@ impl QuicConfig {
             keep_alive_interval: Duration::from_secs(5),
             initial_rtt: Duration::from_millis(50),
             max_udp_payload_size: 1200,
+            allow_migration: true,
+            path_poll_interval: Duration::from_millis(500),
+            congestion_control: CongestionControl::Cubic,
         }
     }
 
@@ -71,10 +113,64 @@ impl QuicConfig {
             keep_alive_interval: Duration::from_secs(30),
             initial_rtt: Duration::from_millis(200),
             max_udp_payload_size: 1400,
+            allow_migration: true,
+            path_poll_interval: Duration::from_secs(1),
+            congestion_control: CongestionControl::Cubic,
         }
     }
 }
 
+#[cfg(feature = "quinn")]
+impl CongestionControl {
+    /// quinn controller factory for this algorithm
+    pub fn quinn_factory(self) -> std::sync::Arc<dyn quinn::congestion::ControllerFactory + Send + Sync> {
+        use quinn::congestion::{BbrConfig, CubicConfig, NewRenoConfig};
+        use std::sync::Arc;
+        match self {
+            CongestionControl::Cubic => Arc::new(CubicConfig::default()),
+            CongestionControl::NewReno => Arc::new(NewRenoConfig::default()),
+            CongestionControl::Bbr => Arc::new(BbrConfig::default()),
+        }
+    }
+}
+
+#[cfg(feature = "quinn")]
+impl QuicConfig {
+    /// quinn transport parameters: timeouts, keep-alive, initial RTT, UDP
+    /// payload size and congestion controller.
+    ///
+    /// `max_udp_payload_size` caps MTU discovery; connections still start at
+    /// quinn's 1200-byte initial MTU, which every QUIC path must carry.
+    pub fn transport_config(&self) -> Result<quinn::TransportConfig, crate::traits::TransportError> {
+        let idle = quinn::IdleTimeout::try_from(self.max_idle_timeout)
+            .map_err(|e| crate::traits::TransportError::Other(format!("invalid idle timeout: {e}")))?;
+        let mut mtu_discovery = quinn::MtuDiscoveryConfig::default();
+        mtu_discovery.upper_bound(self.max_udp_payload_size.clamp(1200, u16::MAX as usize) as u16);
+        let mut transport = quinn::TransportConfig::default();
+        transport
+            .max_idle_timeout(Some(idle))
+            .keep_alive_interval(Some(self.keep_alive_interval))
+            .initial_rtt(self.initial_rtt)
+            .mtu_discovery_config(Some(mtu_discovery))
+            .congestion_controller_factory(self.congestion_control.quinn_factory());
+        Ok(transport)
+    }
+
+    /// Apply this configuration to a quinn server config.
+    pub fn apply_server(&self, server: &mut quinn::ServerConfig) -> Result<(), crate::traits::TransportError> {
+        server
+            .transport_config(std::sync::Arc::new(self.transport_config()?))
+            .migration(self.allow_migration);
+        Ok(())
+    }
+
+    /// Apply this configuration to a quinn client config.
+    pub fn apply_client(&self, client: &mut quinn::ClientConfig) -> Result<(), crate::traits::TransportError> {
+        client.transport_config(std::sync::Arc::new(self.transport_config()?));
+        Ok(())
+    }
+}
+
 /// Certificate pinning verification
 pub struct CertificatePinner;
 
@@ -121,5 +217,36 @@ mod tests {
         
         let low_latency = QuicConfig::low_latency();
         assert!(low_latency.max_idle_timeout < config.max_idle_timeout);
+        assert!(config.allow_migration && low_latency.allow_migration);
+        assert_eq!(config.congestion_control, CongestionControl::Cubic);
+        assert_eq!(QuicConfig::high_throughput().congestion_control, CongestionControl::Cubic);
+    }
+
+    #[test]
+    fn test_congestion_control_parse() {
+        assert_eq!("BBR".parse::<CongestionControl>(), Ok(CongestionControl::Bbr));
+        assert_eq!("cubic".parse::<CongestionControl>(), Ok(CongestionControl::Cubic));
+        assert_eq!("new_reno".parse::<CongestionControl>(), Ok(CongestionControl::NewReno));
+        assert!("vegas".parse::<CongestionControl>().is_err());
+    }
+
+    #[cfg(feature = "quinn")]
+    #[test]
+    fn test_congestion_control_applied() {
+        use quinn::congestion::{Bbr, Cubic, NewReno};
+        use std::any::Any;
+        use std::time::Instant;
+
+        fn built(cc: CongestionControl) -> Box<dyn Any> {
+            cc.quinn_factory().build(Instant::now(), 1200).into_any()
+        }
+        assert!(built(CongestionControl::Cubic).is::<Cubic>());
+        assert!(built(CongestionControl::NewReno).is::<NewReno>());
+        assert!(built(CongestionControl::Bbr).is::<Bbr>());
+
+        for cc in [CongestionControl::Cubic, CongestionControl::NewReno, CongestionControl::Bbr] {
+            let config = QuicConfig { congestion_control: cc, ..QuicConfig::default() };
+            assert!(config.transport_config().is_ok());
+        }
     }
 }
@@ -1,14 +1,21 @@
 //! Testing utilities for transport implementations.
 
-use crate::traits::{ControlPlaneTransport, TransportError, TransportType};
+use crate::framing::LengthCodec;
+use crate::mux::ChannelType;
+use crate::traits::{
+    CongestionState, ControlPlaneTransport, MediaPlaneTransport, MediaSession, SendResult,
+    TransportError, TransportType,
+};
 use async_trait::async_trait;
+use bytes::Bytes;
 use parking_lot::Mutex;
 use rand::Rng;
 use std::collections::VecDeque;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::time::sleep;
+use tokio::sync::{mpsc, Mutex as AsyncMutex, Notify};
+use tokio::time::{sleep, sleep_until, Instant};
 
 /// Mock transport for testing
 pub struct MockTransport {
@@ -140,52 +147,215 @@ impl Default for MockTransport {
     }
 }
 
-/// Loopback transport for local testing
-pub struct LoopbackTransport {
-    local_id: [u8; 32],
-    peer: Arc<LoopbackPeer>,
+/// Simulated link conditions for `LoopbackTransport`
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LinkConditions {
+    /// One-way delay added to every message
+    pub latency: Duration,
+    /// Probability (0.0 - 1.0) that a message is silently dropped
+    pub loss: f64,
 }
 
-struct LoopbackPeer {
-    #[allow(dead_code)]
-    id: [u8; 32],
-    recv_queue: Mutex<VecDeque<([u8; 32], Vec<u8>)>>,
+/// State shared by both ends of a loopback pair
+struct LoopbackLink {
     connected: AtomicBool,
+    closed: Notify,
+    conditions: Mutex<LinkConditions>,
+    dropped: AtomicU64,
+}
+
+/// A framed message in flight
+struct LoopbackPacket {
+    /// `None` on the control lane
+    channel: Option<ChannelType>,
+    framed: Vec<u8>,
+    deliver_at: Instant,
+}
+
+/// One direction of a lane
+struct LoopbackLane {
+    tx: mpsc::UnboundedSender<LoopbackPacket>,
+    rx: AsyncMutex<mpsc::UnboundedReceiver<LoopbackPacket>>,
+}
+
+/// In-process transport: two endpoints connected by channels.
+///
+/// Implements the control plane, media plane and `MediaSession` traits so
+/// dispatch and session flows can be tested without sockets or TLS. Control
+/// messages and media frames travel on separate ordered lanes and are
+/// length-prefixed with the same codecs as the network transports, so
+/// oversized messages fail the same way. Latency and loss are set per pair
+/// with `LinkConditions`.
+pub struct LoopbackTransport {
+    local_id: [u8; 32],
+    peer_id: [u8; 32],
+    link: Arc<LoopbackLink>,
+    control: LoopbackLane,
+    media: LoopbackLane,
+    control_codec: LengthCodec,
+    media_codec: LengthCodec,
+    /// Bytes sent by this end and not yet received
+    outbound: Arc<AtomicUsize>,
+    /// Bytes sent by the peer and not yet received
+    inbound: Arc<AtomicUsize>,
 }
 
 impl LoopbackTransport {
     /// Create connected pair
     pub fn pair() -> (Self, Self) {
+        Self::pair_with(LinkConditions::default())
+    }
+
+    /// Create connected pair with simulated latency and loss
+    pub fn pair_with(conditions: LinkConditions) -> (Self, Self) {
         let id1 = [1u8; 32];
         let id2 = [2u8; 32];
 
-        let peer1 = Arc::new(LoopbackPeer {
-            id: id1,
-            recv_queue: Mutex::new(VecDeque::new()),
+        let link = Arc::new(LoopbackLink {
             connected: AtomicBool::new(true),
+            closed: Notify::new(),
+            conditions: Mutex::new(Self::clamp(conditions)),
+            dropped: AtomicU64::new(0),
         });
 
-        let peer2 = Arc::new(LoopbackPeer {
-            id: id2,
-            recv_queue: Mutex::new(VecDeque::new()),
-            connected: AtomicBool::new(true),
-        });
+        let (control_1to2, control_2to1) = (mpsc::unbounded_channel(), mpsc::unbounded_channel());
+        let (media_1to2, media_2to1) = (mpsc::unbounded_channel(), mpsc::unbounded_channel());
+        let (bytes_1to2, bytes_2to1) = (Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0)));
 
-        // Cross-link peers
+        // Cross-link: each end sends on one channel and receives on the other
         let transport1 = Self {
             local_id: id1,
-            peer: peer2.clone(),
+            peer_id: id2,
+            link: link.clone(),
+            control: LoopbackLane { tx: control_1to2.0, rx: AsyncMutex::new(control_2to1.1) },
+            media: LoopbackLane { tx: media_1to2.0, rx: AsyncMutex::new(media_2to1.1) },
+            control_codec: LengthCodec::control(),
+            media_codec: LengthCodec::media(),
+            outbound: bytes_1to2.clone(),
+            inbound: bytes_2to1.clone(),
         };
 
         let transport2 = Self {
             local_id: id2,
-            peer: peer1.clone(),
+            peer_id: id1,
+            link,
+            control: LoopbackLane { tx: control_2to1.0, rx: AsyncMutex::new(control_1to2.1) },
+            media: LoopbackLane { tx: media_2to1.0, rx: AsyncMutex::new(media_1to2.1) },
+            control_codec: LengthCodec::control(),
+            media_codec: LengthCodec::media(),
+            outbound: bytes_2to1,
+            inbound: bytes_1to2,
         };
 
         (transport1, transport2)
     }
+
+    fn clamp(conditions: LinkConditions) -> LinkConditions {
+        LinkConditions { loss: conditions.loss.clamp(0.0, 1.0), ..conditions }
+    }
+
+    /// Device ID of this end
+    pub fn local_id(&self) -> [u8; 32] {
+        self.local_id
+    }
+
+    /// Device ID of the other end
+    pub fn peer_id(&self) -> [u8; 32] {
+        self.peer_id
+    }
+
+    /// Change latency and loss for both directions; applies to messages sent
+    /// from now on
+    pub fn set_conditions(&self, conditions: LinkConditions) {
+        *self.link.conditions.lock() = Self::clamp(conditions);
+    }
+
+    /// Number of messages dropped by loss injection on this pair
+    pub fn dropped(&self) -> u64 {
+        self.link.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Simulate disconnect of both ends; blocked receivers return
+    /// `TransportError::Disconnected`
+    pub fn disconnect(&self) {
+        self.link.connected.store(false, Ordering::Relaxed);
+        self.link.closed.notify_waiters();
+    }
+
+    /// Simulate reconnect of both ends
+    pub fn connect(&self) {
+        self.link.connected.store(true, Ordering::Relaxed);
+    }
+
+    /// Frame and queue `data`. Returns `false` if loss injection dropped it.
+    fn enqueue(
+        &self,
+        lane: &LoopbackLane,
+        codec: &LengthCodec,
+        channel: Option<ChannelType>,
+        data: &[u8],
+    ) -> Result<bool, TransportError> {
+        if !self.is_connected() {
+            return Err(TransportError::Disconnected);
+        }
+
+        let framed = codec.encode(data)?;
+        let conditions = *self.link.conditions.lock();
+        let should_drop = {
+            let mut rng = rand::thread_rng();
+            rng.gen::<f64>() < conditions.loss
+        };
+        if should_drop {
+            self.link.dropped.fetch_add(1, Ordering::Relaxed);
+            return Ok(false);
+        }
+
+        // Counted before the packet is visible, so the receiver's fetch_sub
+        // can never run first and wrap the counter
+        let len = framed.len();
+        self.outbound.fetch_add(len, Ordering::Relaxed);
+        let packet = LoopbackPacket {
+            channel,
+            framed,
+            deliver_at: Instant::now() + conditions.latency,
+        };
+        if lane.tx.send(packet).is_err() {
+            self.outbound.fetch_sub(len, Ordering::Relaxed);
+            return Err(TransportError::Disconnected);
+        }
+        Ok(true)
+    }
+
+    /// Wait for the next message on `lane`, honoring its delivery time.
+    async fn dequeue(
+        &self,
+        lane: &LoopbackLane,
+        codec: &LengthCodec,
+    ) -> Result<(Option<ChannelType>, Vec<u8>), TransportError> {
+        let closed = self.link.closed.notified();
+        tokio::pin!(closed);
+        closed.as_mut().enable();
+        if !self.is_connected() {
+            return Err(TransportError::Disconnected);
+        }
+
+        let mut rx = lane.rx.lock().await;
+        let packet = tokio::select! {
+            packet = rx.recv() => packet.ok_or(TransportError::Disconnected)?,
+            _ = &mut closed => return Err(TransportError::Disconnected),
+        };
+        tokio::select! {
+            _ = sleep_until(packet.deliver_at) => {}
+            _ = &mut closed => return Err(TransportError::Disconnected),
+        }
+
+        self.inbound.fetch_sub(packet.framed.len(), Ordering::Relaxed);
+        Ok((packet.channel, codec.decode(&packet.framed)?))
+    }
 }
 
+/// Control messages are delivered in order. Loss injection drops them
+/// silently, as a lost datagram would be, so retry logic can be exercised.
 #[async_trait]
 impl ControlPlaneTransport for LoopbackTransport {
     async fn send(
@@ -193,32 +363,17 @@ impl ControlPlaneTransport for LoopbackTransport {
         _recipient: &[u8; 32],
         envelope: &[u8],
     ) -> Result<(), TransportError> {
-        if !self.peer.connected.load(Ordering::Relaxed) {
-            return Err(TransportError::Disconnected);
-        }
-
-        self.peer
-            .recv_queue
-            .lock()
-            .push_back((self.local_id, envelope.to_vec()));
+        self.enqueue(&self.control, &self.control_codec, None, envelope)?;
         Ok(())
     }
 
     async fn recv(&self) -> Result<([u8; 32], Vec<u8>), TransportError> {
-        if !self.peer.connected.load(Ordering::Relaxed) {
-            return Err(TransportError::Disconnected);
-        }
-
-        loop {
-            if let Some((sender, data)) = self.peer.recv_queue.lock().pop_front() {
-                return Ok((sender, data));
-            }
-            sleep(Duration::from_millis(10)).await;
-        }
+        let (_, data) = self.dequeue(&self.control, &self.control_codec).await?;
+        Ok((self.peer_id, data))
     }
 
     fn is_connected(&self) -> bool {
-        self.peer.connected.load(Ordering::Relaxed)
+        self.link.connected.load(Ordering::Relaxed)
     }
 
     fn transport_type(&self) -> TransportType {
@@ -226,6 +381,68 @@ impl ControlPlaneTransport for LoopbackTransport {
     }
 }
 
+#[async_trait]
+impl MediaPlaneTransport for LoopbackTransport {
+    async fn send_frame(
+        &self,
+        channel: ChannelType,
+        data: &[u8],
+    ) -> Result<SendResult, TransportError> {
+        if self.enqueue(&self.media, &self.media_codec, Some(channel), data)? {
+            Ok(SendResult::Sent)
+        } else {
+            Ok(SendResult::Dropped)
+        }
+    }
+
+    async fn recv_frame(&self) -> Result<(ChannelType, Vec<u8>), TransportError> {
+        let (channel, data) = self.dequeue(&self.media, &self.media_codec).await?;
+        Ok((channel.unwrap_or(ChannelType::Frames), data))
+    }
+
+    fn congestion_state(&self) -> CongestionState {
+        CongestionState {
+            window_available: usize::MAX,
+            bytes_in_flight: self.outbound.load(Ordering::Relaxed),
+            is_congested: false,
+        }
+    }
+
+    fn rtt_estimate(&self) -> Duration {
+        self.link.conditions.lock().latency * 2
+    }
+}
+
+/// Control maps to the control lane and media frames to `ChannelType::Frames`
+/// on the media lane, mirroring the QUIC session's streams.
+#[async_trait]
+impl MediaSession for LoopbackTransport {
+    async fn send_control(&self, data: Bytes) -> anyhow::Result<()> {
+        ControlPlaneTransport::send(self, &self.peer_id, &data).await?;
+        Ok(())
+    }
+
+    async fn recv_control(&self) -> anyhow::Result<Bytes> {
+        let (_, data) = ControlPlaneTransport::recv(self).await?;
+        Ok(Bytes::from(data))
+    }
+
+    async fn send_media_frame(&self, data: Bytes) -> anyhow::Result<()> {
+        self.send_frame(ChannelType::Frames, &data).await?;
+        Ok(())
+    }
+
+    async fn recv_media_frame(&self) -> anyhow::Result<Bytes> {
+        let (_, data) = self.recv_frame().await?;
+        Ok(Bytes::from(data))
+    }
+
+    async fn close(&self) -> anyhow::Result<()> {
+        self.disconnect();
+        Ok(())
+    }
+}
+
 /// Transport recording for deterministic testing
 pub struct TransportRecorder {
     events: Mutex<Vec<TransportEvent>>,
@@ -301,4 +518,86 @@ mod tests {
             assert_eq!(data, b"hello");
         });
     }
+
+    #[test]
+    fn test_loopback_framing_and_lanes() {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        rt.block_on(async {
+            let (host, viewer) = LoopbackTransport::pair();
+
+            // Oversized control messages fail framing like on the network
+            let oversized = vec![0u8; crate::framing::MAX_CONTROL_FRAME_SIZE + 1];
+            assert!(matches!(
+                ControlPlaneTransport::send(&host, &viewer.local_id(), &oversized).await,
+                Err(TransportError::Framing(_))
+            ));
+
+            // Media does not block control, and keeps its channel type
+            host.send_frame(ChannelType::Audio, b"pcm").await.unwrap();
+            host.send_control(Bytes::from_static(b"ctrl")).await.unwrap();
+            assert_eq!(&viewer.recv_control().await.unwrap()[..], b"ctrl");
+            assert_eq!(
+                viewer.recv_frame().await.unwrap(),
+                (ChannelType::Audio, b"pcm".to_vec())
+            );
+            assert_eq!(host.congestion_state().bytes_in_flight, 0);
+
+            viewer.send_control(Bytes::from_static(b"ack")).await.unwrap();
+            let (sender, _) = ControlPlaneTransport::recv(&host).await.unwrap();
+            assert_eq!(sender, viewer.local_id());
+        });
+    }
+
+    #[test]
+    fn test_loopback_latency_and_loss() {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        rt.block_on(async {
+            let latency = Duration::from_millis(30);
+            let (host, viewer) = LoopbackTransport::pair_with(LinkConditions { latency, loss: 0.0 });
+            assert_eq!(host.rtt_estimate(), latency * 2);
+
+            let start = Instant::now();
+            host.send_media_frame(Bytes::from_static(b"frame")).await.unwrap();
+            assert_eq!(&viewer.recv_media_frame().await.unwrap()[..], b"frame");
+            assert!(start.elapsed() >= latency);
+
+            host.set_conditions(LinkConditions { latency: Duration::ZERO, loss: 1.0 });
+            assert_eq!(host.send_frame(ChannelType::Frames, b"x").await.unwrap(), SendResult::Dropped);
+            ControlPlaneTransport::send(&host, &viewer.local_id(), b"y").await.unwrap();
+            assert_eq!(viewer.dropped(), 2);
+
+            host.set_conditions(LinkConditions::default());
+            host.send_frame(ChannelType::Frames, b"z").await.unwrap();
+            assert_eq!(viewer.recv_frame().await.unwrap().1, b"z");
+        });
+    }
+
+    #[test]
+    fn test_loopback_disconnect_wakes_receiver() {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        rt.block_on(async {
+            let (host, viewer) = LoopbackTransport::pair();
+            let viewer = Arc::new(viewer);
+            let waiting = viewer.clone();
+            let pending = tokio::spawn(async move { waiting.recv_control().await });
+            tokio::task::yield_now().await;
+
+            host.close().await.unwrap();
+            assert!(pending.await.unwrap().is_err());
+            assert!(!viewer.is_connected());
+            assert!(matches!(
+                host.send_frame(ChannelType::Frames, b"late").await,
+                Err(TransportError::Disconnected)
+            ));
+        });
+    }
 }
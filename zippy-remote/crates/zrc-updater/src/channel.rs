@@ -18,8 +18,10 @@
 use std::path::PathBuf;
 
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
 use crate::error::UpdateError;
+use crate::manifest::UpdateManifest;
 
 /// Update channel for release tracks.
 ///
@@ -220,6 +222,19 @@ impl ChannelManager {
         new_channel.is_more_stable_than(&self.current_channel)
     }
 
+    /// Check whether a device is in the staged rollout cohort of a manifest.
+    ///
+    /// Manifests without a rollout are offered to everyone. Otherwise the
+    /// device is bucketed into 0-99 by hashing the rollout salt with
+    /// `device_id`, and is in the cohort if its bucket is below the
+    /// percentage. The result is deterministic for a given salt and id.
+    pub fn is_in_rollout(&self, manifest: &UpdateManifest, device_id: &str) -> bool {
+        match &manifest.rollout {
+            None => true,
+            Some(rollout) => rollout_bucket(&rollout.salt, device_id) < rollout.percentage,
+        }
+    }
+
     /// Save channel to config file.
     fn save_config(&self) -> Result<(), UpdateError> {
         if let Some(parent) = self.config_path.parent() {
@@ -232,11 +247,40 @@ impl ChannelManager {
     }
 }
 
+/// Map a device to a rollout bucket in 0-99.
+fn rollout_bucket(salt: &str, device_id: &str) -> u8 {
+    let digest = Sha256::new()
+        .chain_update(salt.as_bytes())
+        .chain_update(b":")
+        .chain_update(device_id.as_bytes())
+        .finalize();
+    let mut prefix = [0u8; 8];
+    prefix.copy_from_slice(&digest[..8]);
+    (u64::from_be_bytes(prefix) % 100) as u8
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::manifest::Rollout;
+    use semver::Version;
     use tempfile::TempDir;
 
+    fn rollout_manifest(percentage: u8, salt: &str) -> UpdateManifest {
+        UpdateManifest::new(
+            Version::new(1, 2, 0),
+            "linux-x86_64".to_string(),
+            UpdateChannel::Stable,
+            "https://example.com/update".to_string(),
+            "00".repeat(32),
+            1024,
+            String::new(),
+            false,
+            None,
+        )
+        .with_rollout(Rollout { percentage, salt: salt.to_string() })
+    }
+
     #[test]
     fn test_update_channel_default() {
         assert_eq!(UpdateChannel::default(), UpdateChannel::Stable);
@@ -450,4 +494,47 @@ mod tests {
             assert_eq!(manager.manifest_url(), custom_url);
         }
     }
+
+    #[test]
+    fn test_rollout_without_gate_includes_everyone() {
+        let manager = ChannelManager::new(PathBuf::from("channel.json"));
+        let mut manifest = rollout_manifest(0, "salt");
+        manifest.rollout = None;
+        assert!(manager.is_in_rollout(&manifest, "device-abc"));
+    }
+
+    #[test]
+    fn test_rollout_boundary_percentages() {
+        let manager = ChannelManager::new(PathBuf::from("channel.json"));
+        let none = rollout_manifest(0, "release-1.2.0");
+        let all = rollout_manifest(100, "release-1.2.0");
+
+        for i in 0..200 {
+            let device = format!("device-{}", i);
+            assert!(!manager.is_in_rollout(&none, &device));
+            assert!(manager.is_in_rollout(&all, &device));
+        }
+    }
+
+    #[test]
+    fn test_rollout_bucket_is_stable() {
+        // Fixed values: changing the hashing would reshuffle live cohorts
+        assert_eq!(rollout_bucket("release-1.2.0", "device-abc"), 57);
+        assert_eq!(rollout_bucket("release-1.2.0", "device-xyz"), 74);
+        assert_eq!(rollout_bucket("other", "device-abc"), 48);
+
+        let manager = ChannelManager::new(PathBuf::from("channel.json"));
+        assert!(manager.is_in_rollout(&rollout_manifest(58, "release-1.2.0"), "device-abc"));
+        assert!(!manager.is_in_rollout(&rollout_manifest(57, "release-1.2.0"), "device-abc"));
+    }
+
+    #[test]
+    fn test_rollout_percentage_is_roughly_honoured() {
+        let manager = ChannelManager::new(PathBuf::from("channel.json"));
+        let half = rollout_manifest(50, "s");
+        let included = (0..1000)
+            .filter(|i| manager.is_in_rollout(&half, &format!("device-{}", i)))
+            .count();
+        assert!((400..=600).contains(&included), "included {}", included);
+    }
 }
@@ -4,10 +4,13 @@
 //! and rollback management.
 
 use std::path::PathBuf;
+use std::time::Duration;
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 use crate::channel::UpdateChannel;
+use crate::manifest::PinnedKey;
 
 /// Main update configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,6 +42,14 @@ pub struct UpdateConfig {
     /// Network configuration
     #[serde(default)]
     pub network: NetworkConfig,
+
+    /// Pre/post install hooks
+    #[serde(default)]
+    pub hooks: HooksConfig,
+
+    /// Post-install health check
+    #[serde(default)]
+    pub health_check: HealthCheckConfig,
 }
 
 impl Default for UpdateConfig {
@@ -51,6 +62,8 @@ impl Default for UpdateConfig {
             security: SecurityConfig::default(),
             rollback: RollbackConfig::default(),
             network: NetworkConfig::default(),
+            hooks: HooksConfig::default(),
+            health_check: HealthCheckConfig::default(),
         }
     }
 }
@@ -84,6 +97,15 @@ pub struct SecurityConfig {
     #[serde(default)]
     pub manifest_keys: Vec<String>,
 
+    /// Pinned manifest keys with optional expiry, for key rotation.
+    /// Combined with `manifest_keys`, which never expire.
+    #[serde(default)]
+    pub pinned_keys: Vec<KeyEntry>,
+
+    /// Hours a pinned key is still accepted after its `not_after`
+    #[serde(default = "default_key_grace_hours")]
+    pub key_grace_hours: u64,
+
     /// Minimum number of valid signatures required
     #[serde(default = "default_signature_threshold")]
     pub signature_threshold: usize,
@@ -99,16 +121,29 @@ pub struct SecurityConfig {
     /// Expected team ID for macOS code signing
     #[serde(default)]
     pub macos_team_id: Option<String>,
+
+    /// Fail installation when the macOS Gatekeeper (`spctl`) assessment
+    /// rejects the artifact; by default a rejection is only logged
+    #[serde(default)]
+    pub require_notarization: bool,
+
+    /// Number of artifacts that failed verification to keep in quarantine
+    #[serde(default = "default_quarantine_retention")]
+    pub quarantine_retention: usize,
 }
 
 impl Default for SecurityConfig {
     fn default() -> Self {
         Self {
             manifest_keys: Vec::new(),
+            pinned_keys: Vec::new(),
+            key_grace_hours: default_key_grace_hours(),
             signature_threshold: default_signature_threshold(),
             verify_code_signature: true,
             windows_cert_thumbprint: None,
             macos_team_id: None,
+            require_notarization: false,
+            quarantine_retention: default_quarantine_retention(),
         }
     }
 }
@@ -127,6 +162,41 @@ impl SecurityConfig {
 
         Ok(keys)
     }
+
+    /// Parse `manifest_keys` and `pinned_keys` into pinned keys with expiry.
+    pub fn parse_pinned_keys(&self) -> Result<Vec<PinnedKey>, crate::error::UpdateError> {
+        let mut keys: Vec<PinnedKey> = self
+            .parse_manifest_keys()?
+            .into_iter()
+            .map(|key| PinnedKey { key, not_after: None })
+            .collect();
+
+        for entry in &self.pinned_keys {
+            keys.push(PinnedKey {
+                key: parse_ed25519_key(&entry.key)?,
+                not_after: entry.not_after.map(|t| t.timestamp().max(0) as u64),
+            });
+        }
+
+        Ok(keys)
+    }
+
+    /// Grace window applied after a pinned key's `not_after`, in seconds.
+    pub fn key_grace_secs(&self) -> u64 {
+        self.key_grace_hours.saturating_mul(3600)
+    }
+}
+
+/// A pinned manifest signing key.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyEntry {
+    /// Public key, format: "ed25519:<hex_or_base64_public_key>"
+    pub key: String,
+
+    /// Time after which the key is retired (plus `key_grace_hours`).
+    /// `None` means the key does not expire.
+    #[serde(default)]
+    pub not_after: Option<DateTime<Utc>>,
 }
 
 /// Parse an Ed25519 public key from string format.
@@ -193,10 +263,15 @@ fn decode_base64(s: &str) -> Result<Vec<u8>, String> {
 /// Rollback configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RollbackConfig {
-    /// Maximum number of backups to retain
+    /// Number of newest backups always retained, regardless of age
     #[serde(default = "default_max_backups")]
     pub max_backups: usize,
 
+    /// Backups newer than this are retained even beyond `max_backups`
+    /// (configured as `max_age_secs`; unset = count limit only)
+    #[serde(default, rename = "max_age_secs", with = "optional_secs")]
+    pub max_age: Option<Duration>,
+
     /// Directory for storing backups (empty = default location)
     #[serde(default)]
     pub backup_dir: Option<PathBuf>,
@@ -206,6 +281,7 @@ impl Default for RollbackConfig {
     fn default() -> Self {
         Self {
             max_backups: default_max_backups(),
+            max_age: None,
             backup_dir: None,
         }
     }
@@ -254,6 +330,90 @@ impl Default for NetworkConfig {
     }
 }
 
+/// Commands run around an installation.
+///
+/// Hooks are opt-in: nothing runs unless `enabled` is set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HooksConfig {
+    /// Whether hooks are executed
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Run before the service is stopped; failure aborts the update
+    #[serde(default)]
+    pub pre_install: Option<HookCommand>,
+
+    /// Run after the service is started; failure rolls the update back
+    #[serde(default)]
+    pub post_install: Option<HookCommand>,
+
+    /// Time limit for each hook in seconds
+    #[serde(default = "default_hook_timeout")]
+    pub timeout_secs: u64,
+}
+
+impl Default for HooksConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            pre_install: None,
+            post_install: None,
+            timeout_secs: default_hook_timeout(),
+        }
+    }
+}
+
+/// Health check run after an update is installed.
+///
+/// If the new version is not healthy within `timeout_secs`, the update is
+/// rolled back.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthCheckConfig {
+    /// Whether to probe the installed version before committing the update
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// HTTP endpoint that returns a success status when healthy
+    #[serde(default)]
+    pub http_url: Option<String>,
+
+    /// Command that exits successfully when healthy
+    /// (defaults to the installed executable with `--healthcheck`)
+    #[serde(default)]
+    pub command: Option<HookCommand>,
+
+    /// Time allowed for the new version to become healthy in seconds
+    #[serde(default = "default_health_timeout")]
+    pub timeout_secs: u64,
+
+    /// Delay between probes in seconds
+    #[serde(default = "default_health_interval")]
+    pub interval_secs: u64,
+}
+
+impl Default for HealthCheckConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            http_url: None,
+            command: None,
+            timeout_secs: default_health_timeout(),
+            interval_secs: default_health_interval(),
+        }
+    }
+}
+
+/// A hook command, run directly without a shell.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookCommand {
+    /// Absolute path of the program
+    pub program: PathBuf,
+
+    /// Arguments passed to the program
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
 // Default value functions for serde
 fn default_check_interval() -> u32 {
     24 // Daily
@@ -267,10 +427,45 @@ fn default_signature_threshold() -> usize {
     1
 }
 
+fn default_key_grace_hours() -> u64 {
+    24
+}
+
+fn default_hook_timeout() -> u64 {
+    60
+}
+
+fn default_health_timeout() -> u64 {
+    60
+}
+
+fn default_health_interval() -> u64 {
+    2
+}
+
 fn default_max_backups() -> usize {
     3
 }
 
+/// Serialize an optional [`Duration`] as whole seconds.
+mod optional_secs {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &Option<Duration>, serializer: S) -> Result<S::Ok, S::Error> {
+        value.map(|d| d.as_secs()).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Duration>, D::Error> {
+        Ok(Option::<u64>::deserialize(deserializer)?.map(Duration::from_secs))
+    }
+}
+
+fn default_quarantine_retention() -> usize {
+    5
+}
+
 fn default_timeout() -> u64 {
     30
 }
@@ -331,6 +526,30 @@ mod tests {
         assert!(!config.auto_install);
         assert_eq!(config.security.signature_threshold, 1);
         assert_eq!(config.rollback.max_backups, 3);
+        assert!(!config.hooks.enabled);
+    }
+
+    #[test]
+    fn test_hooks_config_from_toml() {
+        let config: UpdateConfig = toml::from_str(
+            r#"
+            [hooks]
+            enabled = true
+            timeout_secs = 30
+
+            [hooks.pre_install]
+            program = "/opt/zrc/hooks/drain"
+            args = ["--grace", "10"]
+            "#,
+        )
+        .unwrap();
+
+        assert!(config.hooks.enabled);
+        assert_eq!(config.hooks.timeout_secs, 30);
+        let pre = config.hooks.pre_install.unwrap();
+        assert_eq!(pre.program, PathBuf::from("/opt/zrc/hooks/drain"));
+        assert_eq!(pre.args, vec!["--grace", "10"]);
+        assert!(config.hooks.post_install.is_none());
     }
 
     #[test]
@@ -352,15 +571,45 @@ mod tests {
         assert!(config.manifest_keys.is_empty());
         assert_eq!(config.signature_threshold, 1);
         assert!(config.verify_code_signature);
+        assert_eq!(config.quarantine_retention, 5);
+        assert!(config.pinned_keys.is_empty());
+        assert_eq!(config.key_grace_hours, 24);
+        assert!(!config.require_notarization);
+    }
+
+    #[test]
+    fn test_parse_pinned_keys() {
+        let signing_key = ed25519_dalek::SigningKey::from_bytes(&[7u8; 32]);
+        let key = format!("ed25519:{}", hex::encode(signing_key.verifying_key().as_bytes()));
+        let mut config = SecurityConfig::default();
+        config.manifest_keys.push(key.clone());
+        config.pinned_keys.push(KeyEntry {
+            key,
+            not_after: Some(DateTime::from_timestamp(1_700_000_000, 0).unwrap()),
+        });
+
+        let keys = config.parse_pinned_keys().unwrap();
+        assert_eq!(keys.len(), 2);
+        assert_eq!(keys[0].not_after, None);
+        assert_eq!(keys[1].not_after, Some(1_700_000_000));
+        assert_eq!(config.key_grace_secs(), 24 * 3600);
     }
 
     #[test]
     fn test_rollback_config_default() {
         let config = RollbackConfig::default();
         assert_eq!(config.max_backups, 3);
+        assert!(config.max_age.is_none());
         assert!(config.backup_dir.is_none());
     }
 
+    #[test]
+    fn test_rollback_max_age_from_toml() {
+        let config: RollbackConfig = toml::from_str("max_backups = 2\nmax_age_secs = 86400").unwrap();
+        assert_eq!(config.max_backups, 2);
+        assert_eq!(config.max_age, Some(Duration::from_secs(86400)));
+    }
+
     #[test]
     fn test_network_config_default() {
         let config = NetworkConfig::default();
@@ -7,7 +7,7 @@ use std::fs::{File, OpenOptions};
 use std::io::Write;
 use std::path::Path;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use futures_util::StreamExt;
 use reqwest::StatusCode;
@@ -15,6 +15,7 @@ use sha2::{Digest, Sha256};
 use tracing::{debug, info, warn};
 
 use crate::error::UpdateError;
+use crate::manifest::PatchArtifact;
 
 /// Default timeout for HTTP requests in seconds.
 const DEFAULT_TIMEOUT_SECS: u64 = 30;
@@ -25,6 +26,9 @@ const DEFAULT_READ_TIMEOUT_SECS: u64 = 60;
 /// Buffer size for reading chunks during download.
 const DOWNLOAD_BUFFER_SIZE: usize = 8192;
 
+/// Lowest bandwidth cap honoured, so a tiny cap cannot stall a download.
+const MIN_THROTTLE_BYTES_PER_SEC: u64 = 1024;
+
 /// Configuration for the downloader.
 #[derive(Debug, Clone)]
 pub struct DownloaderConfig {
@@ -36,6 +40,9 @@ pub struct DownloaderConfig {
     pub max_retries: u32,
     /// User agent string.
     pub user_agent: String,
+    /// Bandwidth cap in bytes per second (`None` = unlimited).
+    /// Caps below 1 KiB/s are raised to 1 KiB/s.
+    pub max_bytes_per_sec: Option<u64>,
 }
 
 impl Default for DownloaderConfig {
@@ -45,6 +52,7 @@ impl Default for DownloaderConfig {
             read_timeout_secs: DEFAULT_READ_TIMEOUT_SECS,
             max_retries: 3,
             user_agent: format!("zrc-updater/{}", env!("CARGO_PKG_VERSION")),
+            max_bytes_per_sec: None,
         }
     }
 }
@@ -177,7 +185,7 @@ impl Downloader {
             let existing_size = dest.metadata()?.len();
             if existing_size >= expected_size {
                 info!("Download already complete ({} bytes)", existing_size);
-                self.report_progress(expected_size, expected_size);
+                self.report_progress(expected_size, expected_size, 0);
                 return Ok(());
             }
             debug!("Resuming download from byte {}", existing_size);
@@ -230,16 +238,29 @@ impl Downloader {
         };
 
         // Report initial progress
-        self.report_progress(downloaded, expected_size);
+        self.report_progress(downloaded, expected_size, 0);
+
+        // Only bytes fetched by this request count towards the cap and the
+        // reported rate, so a resumed download starts from a fresh budget
+        let mut limiter = self.config.max_bytes_per_sec.map(RateLimiter::new);
+        let started = Instant::now();
+        let mut transferred = 0u64;
 
         while let Some(chunk_result) = stream.next().await {
             let chunk = chunk_result.map_err(|e| UpdateError::NetworkError(e.to_string()))?;
 
             file.write_all(&chunk)?;
             downloaded += chunk.len() as u64;
+            transferred += chunk.len() as u64;
+
+            if let Some(limiter) = limiter.as_mut() {
+                limiter.throttle(chunk.len()).await;
+            }
 
             // Report progress
-            self.report_progress(downloaded, expected_size);
+            let elapsed = started.elapsed().as_secs_f64();
+            let rate = if elapsed > 0.0 { (transferred as f64 / elapsed) as u64 } else { 0 };
+            self.report_progress(downloaded, expected_size, rate);
         }
 
         // Ensure all data is written to disk
@@ -303,6 +324,35 @@ impl Downloader {
         Ok(())
     }
 
+    /// Download a delta patch and rebuild the full artifact from `base`.
+    ///
+    /// The patch itself is hash-verified against the manifest before it is
+    /// applied, and the rebuilt artifact is verified against `expected_hash`
+    /// (the full-artifact hash). The patch file is removed afterwards.
+    pub async fn download_and_apply_patch(
+        &self,
+        patch: &PatchArtifact,
+        base: &Path,
+        dest: &Path,
+        expected_hash: &[u8; 32],
+    ) -> Result<(), UpdateError> {
+        let patch_hash = patch.hash_bytes().ok_or_else(|| {
+            UpdateError::ConfigError("Invalid patch hash in manifest".to_string())
+        })?;
+        let patch_path = dest.with_extension("patch");
+
+        let result = match self
+            .download_and_verify(&patch.url, &patch_path, patch.size, &patch_hash)
+            .await
+        {
+            Ok(()) => crate::install::apply_patch(base, &patch_path, dest, expected_hash),
+            Err(e) => Err(e),
+        };
+
+        let _ = std::fs::remove_file(&patch_path);
+        result
+    }
+
     /// Compute the SHA-256 hash of a file.
     fn compute_file_hash(&self, path: &Path) -> Result<[u8; 32], UpdateError> {
         let mut file = File::open(path)?;
@@ -321,9 +371,9 @@ impl Downloader {
     }
 
     /// Report download progress via the callback if set.
-    fn report_progress(&self, downloaded: u64, total: u64) {
+    fn report_progress(&self, downloaded: u64, total: u64, bytes_per_sec: u64) {
         if let Some(callback) = &self.progress_callback {
-            callback(DownloadProgress { downloaded, total });
+            callback(DownloadProgress { downloaded, total, bytes_per_sec });
         }
     }
 
@@ -391,6 +441,57 @@ impl Default for Downloader {
     }
 }
 
+/// Token-bucket limiter for download bandwidth.
+///
+/// The bucket starts empty and holds at most a quarter second of tokens.
+/// A chunk larger than the bucket is admitted by going into debt and
+/// sleeping it off, so progress is always made whatever the cap.
+struct RateLimiter {
+    /// Refill rate in bytes per second.
+    rate: f64,
+    /// Maximum burst in bytes.
+    capacity: f64,
+    /// Available tokens; negative while in debt.
+    tokens: f64,
+    /// Time of the last refill.
+    last: Instant,
+}
+
+impl RateLimiter {
+    fn new(bytes_per_sec: u64) -> Self {
+        let rate = bytes_per_sec.max(MIN_THROTTLE_BYTES_PER_SEC) as f64;
+        Self {
+            rate,
+            capacity: rate / 4.0,
+            tokens: 0.0,
+            last: Instant::now(),
+        }
+    }
+
+    /// Take `bytes` tokens at `now` and return how long to wait before
+    /// reading more.
+    fn consume(&mut self, bytes: usize, now: Instant) -> Duration {
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.last = now;
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        self.tokens -= bytes as f64;
+
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / self.rate)
+        }
+    }
+
+    /// Wait until `bytes` fit within the configured rate.
+    async fn throttle(&mut self, bytes: usize) {
+        let delay = self.consume(bytes, Instant::now());
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
 /// Download progress information.
 ///
 /// Provides information about the current state of a download,
@@ -401,12 +502,15 @@ pub struct DownloadProgress {
     pub downloaded: u64,
     /// Total bytes to download.
     pub total: u64,
+    /// Average transfer rate of the current request in bytes per second,
+    /// after throttling. Zero before any data has arrived.
+    pub bytes_per_sec: u64,
 }
 
 impl DownloadProgress {
     /// Create a new progress instance.
     pub fn new(downloaded: u64, total: u64) -> Self {
-        Self { downloaded, total }
+        Self { downloaded, total, bytes_per_sec: 0 }
     }
 
     /// Get download progress as a percentage (0.0 to 100.0).
@@ -440,3 +544,99 @@ impl std::fmt::Display for DownloadProgress {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Serve `body` once over plain HTTP and return the URL.
+    async fn serve_once(body: Vec<u8>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = [0u8; 1024];
+            let _ = socket.read(&mut request).await;
+            let header = format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            );
+            socket.write_all(header.as_bytes()).await.unwrap();
+            socket.write_all(&body).await.unwrap();
+        });
+        format!("http://{}/artifact", addr)
+    }
+
+    #[test]
+    fn test_rate_limiter_paces_to_rate() {
+        let start = Instant::now();
+        let mut limiter = RateLimiter::new(10_000);
+        limiter.last = start;
+
+        // Empty bucket: 5000 bytes owe half a second
+        let delay = limiter.consume(5000, start);
+        assert!((delay.as_secs_f64() - 0.5).abs() < 0.01);
+
+        // After sleeping it off the debt is cleared
+        let delay = limiter.consume(0, start + Duration::from_millis(500));
+        assert!(delay.is_zero());
+    }
+
+    #[test]
+    fn test_rate_limiter_burst_is_capped() {
+        let start = Instant::now();
+        let mut limiter = RateLimiter::new(10_000);
+        limiter.last = start;
+
+        // A long idle period only banks a quarter second of tokens
+        let later = start + Duration::from_secs(60);
+        assert!(limiter.consume(2500, later).is_zero());
+        assert!(!limiter.consume(1000, later).is_zero());
+    }
+
+    #[test]
+    fn test_rate_limiter_tiny_cap_makes_progress() {
+        let start = Instant::now();
+        let mut limiter = RateLimiter::new(0);
+        limiter.last = start;
+
+        // Clamped to the minimum rate rather than waiting forever
+        let delay = limiter.consume(1024, start);
+        assert!((delay.as_secs_f64() - 1.0).abs() < 0.01);
+    }
+
+    #[tokio::test]
+    async fn test_throttled_download_respects_cap() {
+        const CAP: u64 = 64 * 1024;
+        let body = vec![0x5au8; 48 * 1024];
+        let url = serve_once(body.clone()).await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("artifact.bin");
+        let last = Arc::new(std::sync::Mutex::new(None));
+        let seen = last.clone();
+        let mut downloader = Downloader::with_config(DownloaderConfig {
+            max_bytes_per_sec: Some(CAP),
+            ..DownloaderConfig::default()
+        });
+        downloader.set_progress_callback(move |p| *seen.lock().unwrap() = Some(p));
+
+        let started = Instant::now();
+        downloader
+            .download_with_resume(&url, &dest, body.len() as u64)
+            .await
+            .unwrap();
+        let elapsed = started.elapsed().as_secs_f64();
+
+        let expected = body.len() as f64 / CAP as f64;
+        assert!(elapsed >= expected * 0.8, "too fast: {elapsed:.3}s");
+        assert!(elapsed <= expected * 1.5 + 0.25, "too slow: {elapsed:.3}s");
+        assert_eq!(std::fs::read(&dest).unwrap(), body);
+
+        let progress = last.lock().unwrap().unwrap();
+        assert!(progress.is_complete());
+        assert!(progress.bytes_per_sec <= CAP * 5 / 4);
+    }
+}
@@ -29,6 +29,18 @@ pub enum UpdateError {
     #[error("hash mismatch: expected {expected}, got {actual}")]
     HashMismatch { expected: String, actual: String },
 
+    /// Delta patch could not be applied to the installed version
+    #[error("patch failed: {0}")]
+    PatchFailed(String),
+
+    /// Artifact previously failed verification and is quarantined
+    #[error("artifact for version {version} is quarantined; purge the quarantine to retry")]
+    ArtifactQuarantined { version: String },
+
+    /// Offline update package is truncated or malformed
+    #[error("corrupt update package: {0}")]
+    CorruptPackage(String),
+
     /// Artifact size does not match expected value
     #[error("size mismatch: expected {expected}, got {actual}")]
     SizeMismatch { expected: u64, actual: u64 },
@@ -65,6 +77,27 @@ pub enum UpdateError {
     #[error("code signature verification failed: {0}")]
     CodeSignatureInvalid(String),
 
+    /// Install hook exited unsuccessfully or timed out
+    #[error("{hook} hook failed ({status})\nstdout: {stdout}\nstderr: {stderr}")]
+    HookFailed {
+        hook: String,
+        status: String,
+        stdout: String,
+        stderr: String,
+    },
+
+    /// Install hook was not run because its path is unsafe
+    #[error("install hook refused: {0}")]
+    HookRefused(String),
+
+    /// Installed version did not become healthy
+    #[error("health check failed: {0}")]
+    HealthCheckFailed(String),
+
+    /// The update can no longer be snoozed, or not for that long
+    #[error("snooze refused: {0}")]
+    SnoozeRefused(String),
+
     /// Service management error
     #[error("service error: {0}")]
     ServiceError(String),
@@ -0,0 +1,202 @@
+//! Post-install health checks.
+//!
+//! After an update is installed and the service restarted, the new version
+//! must become healthy within a timeout or the update is rolled back. Probes
+//! implement [`HealthCheck`]; an HTTP endpoint probe and a subprocess probe
+//! (e.g. `zrc-agent --healthcheck`) are provided.
+
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use tracing::{debug, info, warn};
+
+use crate::config::HealthCheckConfig;
+use crate::error::UpdateError;
+
+/// A probe reporting whether the installed version is healthy.
+#[async_trait]
+pub trait HealthCheck: Send + Sync {
+    /// Run one probe; `Ok(())` means healthy.
+    async fn check(&self) -> Result<(), UpdateError>;
+}
+
+/// Healthy when an HTTP GET returns a success status.
+pub struct HttpHealthCheck {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl HttpHealthCheck {
+    /// Create a probe for `url`; each request is limited to `request_timeout`.
+    pub fn new(url: String, request_timeout: Duration) -> Result<Self, UpdateError> {
+        let client = reqwest::Client::builder().timeout(request_timeout).build()?;
+        Ok(Self { url, client })
+    }
+}
+
+#[async_trait]
+impl HealthCheck for HttpHealthCheck {
+    async fn check(&self) -> Result<(), UpdateError> {
+        let response = self.client.get(&self.url).send().await?;
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(UpdateError::HealthCheckFailed(format!(
+                "{} returned {}",
+                self.url,
+                response.status()
+            )))
+        }
+    }
+}
+
+/// Healthy when a subprocess exits successfully.
+pub struct CommandHealthCheck {
+    program: PathBuf,
+    args: Vec<String>,
+}
+
+impl CommandHealthCheck {
+    /// Create a probe running `program` with `args`.
+    pub fn new(program: PathBuf, args: Vec<String>) -> Self {
+        Self { program, args }
+    }
+}
+
+#[async_trait]
+impl HealthCheck for CommandHealthCheck {
+    async fn check(&self) -> Result<(), UpdateError> {
+        let status = tokio::process::Command::new(&self.program)
+            .args(&self.args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .status()
+            .await?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(UpdateError::HealthCheckFailed(format!(
+                "{:?} exited with {}",
+                self.program, status
+            )))
+        }
+    }
+}
+
+/// Build the probe described by configuration.
+///
+/// Returns `None` if health checks are disabled. An HTTP URL takes precedence
+/// over a command; with neither set, the installed executable is run with
+/// `--healthcheck`.
+pub fn from_config(config: &HealthCheckConfig) -> Result<Option<Box<dyn HealthCheck>>, UpdateError> {
+    if !config.enabled {
+        return Ok(None);
+    }
+    if let Some(url) = &config.http_url {
+        let request_timeout = Duration::from_secs(config.interval_secs.max(1));
+        return Ok(Some(Box::new(HttpHealthCheck::new(url.clone(), request_timeout)?)));
+    }
+    let probe = match &config.command {
+        Some(command) => CommandHealthCheck::new(command.program.clone(), command.args.clone()),
+        None => CommandHealthCheck::new(std::env::current_exe()?, vec!["--healthcheck".to_string()]),
+    };
+    Ok(Some(Box::new(probe)))
+}
+
+/// Poll `check` until it reports healthy or `timeout` elapses.
+///
+/// At least one probe is always run. The last probe error is returned if the
+/// timeout is reached.
+pub async fn wait_until_healthy(
+    check: &dyn HealthCheck,
+    timeout: Duration,
+    interval: Duration,
+) -> Result<(), UpdateError> {
+    let deadline = Instant::now() + timeout;
+    let mut attempts = 0u32;
+
+    loop {
+        attempts += 1;
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let result = match tokio::time::timeout(remaining.max(interval), check.check()).await {
+            Ok(result) => result,
+            Err(_) => Err(UpdateError::HealthCheckFailed("probe timed out".to_string())),
+        };
+
+        match result {
+            Ok(()) => {
+                info!("Health check passed after {} attempt(s)", attempts);
+                return Ok(());
+            }
+            Err(e) if Instant::now() + interval >= deadline => {
+                warn!("Health check failed after {} attempt(s): {}", attempts, e);
+                return Err(UpdateError::HealthCheckFailed(format!(
+                    "not healthy within {:?}: {}",
+                    timeout, e
+                )));
+            }
+            Err(e) => {
+                debug!("Health check attempt {} failed: {}", attempts, e);
+                tokio::time::sleep(interval).await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// Reports unhealthy for the first `failures` probes.
+    struct StubCheck {
+        failures: u32,
+        calls: AtomicU32,
+    }
+
+    impl StubCheck {
+        fn new(failures: u32) -> Self {
+            Self { failures, calls: AtomicU32::new(0) }
+        }
+    }
+
+    #[async_trait]
+    impl HealthCheck for StubCheck {
+        async fn check(&self) -> Result<(), UpdateError> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst);
+            if call < self.failures {
+                Err(UpdateError::HealthCheckFailed("unhealthy".to_string()))
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_healthy_after_retries() {
+        let check = StubCheck::new(2);
+        let result =
+            wait_until_healthy(&check, Duration::from_secs(5), Duration::from_millis(10)).await;
+        assert!(result.is_ok());
+        assert_eq!(check.calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_unhealthy_times_out() {
+        let check = StubCheck::new(u32::MAX);
+        let result =
+            wait_until_healthy(&check, Duration::from_millis(100), Duration::from_millis(20)).await;
+        assert!(matches!(result, Err(UpdateError::HealthCheckFailed(_))));
+        assert!(check.calls.load(Ordering::SeqCst) >= 2);
+    }
+
+    #[test]
+    fn test_disabled_config_has_no_probe() {
+        let config = HealthCheckConfig::default();
+        assert!(from_config(&config).unwrap().is_none());
+    }
+}
@@ -0,0 +1,336 @@
+//! Pre/post install command hooks.
+//!
+//! Enterprise deployments can run a command before the service is stopped
+//! (e.g. to drain connections) and after it is restarted (e.g. to warm
+//! caches). Hooks are opt-in via [`HooksConfig`] and run by the
+//! `PlatformInstaller` implementations.
+//!
+//! # Security
+//!
+//! Hooks run with the updater's privileges, so a hook program must be an
+//! absolute path and neither it nor its directory may be world-writable.
+
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
+
+use tracing::{info, warn};
+
+use crate::config::{HookCommand, HooksConfig};
+use crate::error::UpdateError;
+use crate::rollback::{BackupInfo, RollbackManager};
+
+/// Maximum bytes of stdout/stderr kept for error reporting.
+const MAX_CAPTURED_OUTPUT: usize = 4096;
+
+/// Hook commands run around an installation.
+#[derive(Debug, Clone)]
+pub struct InstallHooks {
+    /// Run before any backup or replacement
+    pre_install: Option<HookCommand>,
+    /// Run after the service has been restarted
+    post_install: Option<HookCommand>,
+    /// Time limit for each hook
+    timeout: Duration,
+}
+
+impl InstallHooks {
+    /// Build hooks from configuration.
+    ///
+    /// Returns `None` unless hooks are enabled and at least one is set.
+    pub fn from_config(config: &HooksConfig) -> Option<Self> {
+        if !config.enabled || (config.pre_install.is_none() && config.post_install.is_none()) {
+            return None;
+        }
+        Some(Self {
+            pre_install: config.pre_install.clone(),
+            post_install: config.post_install.clone(),
+            timeout: Duration::from_secs(config.timeout_secs),
+        })
+    }
+
+    /// Run the pre-install hook, if configured.
+    ///
+    /// A failure must abort the update before anything is changed.
+    pub async fn run_pre_install(&self) -> Result<(), UpdateError> {
+        match &self.pre_install {
+            Some(command) => run_hook("pre_install", command, self.timeout).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Run the post-install hook, if configured.
+    ///
+    /// A failure should roll the update back.
+    pub async fn run_post_install(&self) -> Result<(), UpdateError> {
+        match &self.post_install {
+            Some(command) => run_hook("post_install", command, self.timeout).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Run the post-install hook, rolling the update back to `backup` if it
+    /// fails.
+    ///
+    /// When the service was running it is stopped around the restore with
+    /// `stop_service` and `start_service`.
+    pub async fn run_post_install_or_rollback(
+        &self,
+        rollback_manager: &RollbackManager,
+        backup: &BackupInfo,
+        was_running: bool,
+        stop_service: impl Fn() -> Result<(), UpdateError>,
+        start_service: impl Fn() -> Result<(), UpdateError>,
+    ) -> Result<(), UpdateError> {
+        let Err(e) = self.run_post_install().await else {
+            return Ok(());
+        };
+        warn!("Post-install hook failed, rolling back: {}", e);
+        if was_running {
+            let _ = stop_service();
+        }
+        let _ = rollback_manager.rollback_to(backup);
+        if was_running {
+            let _ = start_service();
+        }
+        Err(e)
+    }
+}
+
+/// Run a single hook, capturing its output.
+async fn run_hook(name: &str, command: &HookCommand, timeout: Duration) -> Result<(), UpdateError> {
+    check_hook_path(&command.program)?;
+    info!("Running {} hook: {:?}", name, command.program);
+
+    let child = tokio::process::Command::new(&command.program)
+        .args(&command.args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| UpdateError::HookFailed {
+            hook: name.to_string(),
+            status: format!("failed to start: {}", e),
+            stdout: String::new(),
+            stderr: String::new(),
+        })?;
+
+    let output = match tokio::time::timeout(timeout, child.wait_with_output()).await {
+        Ok(result) => result?,
+        Err(_) => {
+            warn!("{} hook timed out after {:?}", name, timeout);
+            return Err(UpdateError::HookFailed {
+                hook: name.to_string(),
+                status: format!("timed out after {:?}", timeout),
+                stdout: String::new(),
+                stderr: String::new(),
+            });
+        }
+    };
+
+    if output.status.success() {
+        return Ok(());
+    }
+
+    warn!("{} hook failed: {}", name, output.status);
+    Err(UpdateError::HookFailed {
+        hook: name.to_string(),
+        status: output.status.to_string(),
+        stdout: captured(&output.stdout),
+        stderr: captured(&output.stderr),
+    })
+}
+
+/// Refuse hook programs that other users could have replaced.
+fn check_hook_path(program: &Path) -> Result<(), UpdateError> {
+    if !program.is_absolute() {
+        return Err(UpdateError::HookRefused(format!(
+            "hook program must be an absolute path: {:?}",
+            program
+        )));
+    }
+
+    let metadata = std::fs::metadata(program).map_err(|e| {
+        UpdateError::HookRefused(format!("cannot stat hook program {:?}: {}", program, e))
+    })?;
+    if is_world_writable(&metadata) {
+        return Err(UpdateError::HookRefused(format!(
+            "hook program is world-writable: {:?}",
+            program
+        )));
+    }
+
+    if let Some(dir) = program.parent() {
+        if std::fs::metadata(dir).map(|m| is_world_writable(&m)).unwrap_or(false) {
+            return Err(UpdateError::HookRefused(format!(
+                "hook directory is world-writable: {:?}",
+                dir
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(unix)]
+fn is_world_writable(metadata: &std::fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o002 != 0
+}
+
+/// Windows ACLs are not inspected; hooks should live under Program Files.
+#[cfg(not(unix))]
+fn is_world_writable(_metadata: &std::fs::Metadata) -> bool {
+    false
+}
+
+/// Decode hook output, keeping the tail if it is long.
+fn captured(bytes: &[u8]) -> String {
+    let start = bytes.len().saturating_sub(MAX_CAPTURED_OUTPUT);
+    String::from_utf8_lossy(&bytes[start..]).trim_end().to_string()
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    fn write_script(dir: &Path, name: &str, body: &str, mode: u32) -> PathBuf {
+        let path = dir.join(name);
+        std::fs::write(&path, format!("#!/bin/sh\n{}\n", body)).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(mode)).unwrap();
+        path
+    }
+
+    fn hooks(pre: Option<PathBuf>, post: Option<PathBuf>, timeout_secs: u64) -> InstallHooks {
+        let command = |program| HookCommand { program, args: Vec::new() };
+        InstallHooks::from_config(&HooksConfig {
+            enabled: true,
+            pre_install: pre.map(command),
+            post_install: post.map(command),
+            timeout_secs,
+        })
+        .unwrap()
+    }
+
+    fn private_dir() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        std::fs::set_permissions(dir.path(), std::fs::Permissions::from_mode(0o700)).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_hooks_are_opt_in() {
+        let mut config = HooksConfig {
+            pre_install: Some(HookCommand { program: "/bin/true".into(), args: Vec::new() }),
+            ..HooksConfig::default()
+        };
+        assert!(InstallHooks::from_config(&config).is_none());
+
+        config.enabled = true;
+        assert!(InstallHooks::from_config(&config).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_hook_success() {
+        let dir = private_dir();
+        let script = write_script(dir.path(), "ok.sh", "exit 0", 0o700);
+        assert!(hooks(Some(script), None, 5).run_pre_install().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_hook_failure_captures_output() {
+        let dir = private_dir();
+        let script = write_script(dir.path(), "fail.sh", "echo draining; echo busy >&2; exit 3", 0o700);
+
+        let err = hooks(None, Some(script), 5).run_post_install().await.unwrap_err();
+        match err {
+            UpdateError::HookFailed { hook, stdout, stderr, .. } => {
+                assert_eq!(hook, "post_install");
+                assert_eq!(stdout, "draining");
+                assert_eq!(stderr, "busy");
+            }
+            other => panic!("unexpected error: {other}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hook_timeout() {
+        let dir = private_dir();
+        let script = write_script(dir.path(), "slow.sh", "sleep 10", 0o700);
+
+        let err = hooks(Some(script), None, 1).run_pre_install().await.unwrap_err();
+        assert!(matches!(err, UpdateError::HookFailed { ref status, .. } if status.contains("timed out")));
+    }
+
+    #[tokio::test]
+    async fn test_world_writable_hook_refused() {
+        let dir = private_dir();
+        let script = write_script(dir.path(), "open.sh", "exit 0", 0o777);
+
+        let err = hooks(Some(script), None, 5).run_pre_install().await.unwrap_err();
+        assert!(matches!(err, UpdateError::HookRefused(_)));
+    }
+
+    #[tokio::test]
+    async fn test_world_writable_directory_refused() {
+        let dir = TempDir::new().unwrap();
+        std::fs::set_permissions(dir.path(), std::fs::Permissions::from_mode(0o777)).unwrap();
+        let script = write_script(dir.path(), "ok.sh", "exit 0", 0o700);
+
+        let err = hooks(Some(script), None, 5).run_pre_install().await.unwrap_err();
+        assert!(matches!(err, UpdateError::HookRefused(_)));
+    }
+
+    #[tokio::test]
+    async fn test_relative_hook_refused() {
+        let err = hooks(Some(PathBuf::from("drain.sh")), None, 5)
+            .run_pre_install()
+            .await
+            .unwrap_err();
+        assert!(matches!(err, UpdateError::HookRefused(_)));
+    }
+
+    #[tokio::test]
+    async fn test_failed_post_install_restarts_service() {
+        use std::cell::Cell;
+
+        let dir = private_dir();
+        let ok = write_script(dir.path(), "ok.sh", "exit 0", 0o700);
+        let fail = write_script(dir.path(), "fail.sh", "exit 1", 0o700);
+        // An empty backup: the restore itself fails without touching anything
+        let manager = RollbackManager::new(dir.path().join("backups"), 3);
+        let backup = BackupInfo {
+            version: semver::Version::new(1, 0, 0),
+            created_at: chrono::Utc::now(),
+            path: dir.path().join("missing"),
+            hash: None,
+        };
+
+        let (stops, starts) = (Cell::new(0), Cell::new(0));
+        let stop = || {
+            stops.set(stops.get() + 1);
+            Ok(())
+        };
+        let start = || {
+            starts.set(starts.get() + 1);
+            Ok(())
+        };
+
+        hooks(None, Some(ok), 5)
+            .run_post_install_or_rollback(&manager, &backup, true, stop, start)
+            .await
+            .unwrap();
+        assert_eq!((stops.get(), starts.get()), (0, 0));
+
+        let err = hooks(None, Some(fail), 5)
+            .run_post_install_or_rollback(&manager, &backup, true, stop, start)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, UpdateError::HookFailed { .. }));
+        assert_eq!((stops.get(), starts.get()), (1, 1));
+    }
+}
@@ -6,9 +6,11 @@
 use std::path::{Path, PathBuf};
 
 use async_trait::async_trait;
+use sha2::{Digest, Sha256};
 use tracing::{debug, info, warn};
 
 use crate::error::UpdateError;
+use crate::hooks::InstallHooks;
 use crate::rollback::{BackupInfo, RollbackManager};
 
 /// Platform-specific update installer.
@@ -22,6 +24,53 @@ pub trait PlatformInstaller: Send + Sync {
 
     /// Check if restart is required after installation.
     fn requires_restart(&self) -> bool;
+
+    /// Stop and start the installed service so it runs the binary now on
+    /// disk, e.g. after an unhealthy update was rolled back.
+    ///
+    /// The default does nothing, for installers that don't manage a service.
+    fn restart_service(&self) -> Result<(), UpdateError> {
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Delta Patches
+// ============================================================================
+
+/// Rebuild a full artifact by applying a bsdiff patch to `base`.
+///
+/// The reconstructed bytes are SHA-256 verified against `expected_hash` (the
+/// manifest's full-artifact hash) before anything is written to `dest`, so a
+/// patch applied to the wrong base can never yield an installable artifact.
+pub fn apply_patch(
+    base: &Path,
+    patch: &Path,
+    dest: &Path,
+    expected_hash: &[u8; 32],
+) -> Result<(), UpdateError> {
+    let old = std::fs::read(base)?;
+    let patch_data = std::fs::read(patch)?;
+
+    let mut rebuilt = Vec::new();
+    bsdiff::patch(&old, &mut patch_data.as_slice(), &mut rebuilt)
+        .map_err(|e| UpdateError::PatchFailed(e.to_string()))?;
+
+    let actual: [u8; 32] = Sha256::digest(&rebuilt).into();
+    if actual != *expected_hash {
+        warn!("Patched artifact does not match the manifest hash");
+        return Err(UpdateError::HashMismatch {
+            expected: hex::encode(expected_hash),
+            actual: hex::encode(actual),
+        });
+    }
+
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(dest, &rebuilt)?;
+    debug!("Rebuilt {} bytes from patch into {:?}", rebuilt.len(), dest);
+    Ok(())
 }
 
 // ============================================================================
@@ -54,6 +103,8 @@ pub struct WindowsInstaller {
     expected_thumbprint: Option<String>,
     /// Whether to perform silent installation
     silent: bool,
+    /// Optional pre/post install hooks
+    hooks: Option<InstallHooks>,
 }
 
 #[cfg(target_os = "windows")]
@@ -86,6 +137,7 @@ impl WindowsInstaller {
             rollback_manager,
             expected_thumbprint: None,
             silent: true,
+            hooks: None,
         }
     }
 
@@ -104,6 +156,16 @@ impl WindowsInstaller {
         self
     }
 
+    /// Set pre/post install hooks.
+    ///
+    /// The pre-install hook runs before the backup and service stop; the
+    /// post-install hook runs after the service restarts and rolls the
+    /// update back if it fails.
+    pub fn with_hooks(mut self, hooks: InstallHooks) -> Self {
+        self.hooks = Some(hooks);
+        self
+    }
+
     /// Get the service name.
     pub fn service_name(&self) -> &str {
         &self.service_name
@@ -224,6 +286,8 @@ impl PlatformInstaller for WindowsInstaller {
     /// 6. Start the Windows service
     /// 7. On failure, automatically rollback
     ///
+    /// Configured hooks run before step 2 and after step 6.
+    ///
     /// # Requirements
     ///
     /// - Requirement 6.1: MSI-based installation
@@ -238,6 +302,11 @@ impl PlatformInstaller for WindowsInstaller {
             verify_authenticode(artifact, self.expected_thumbprint.as_deref())?;
         }
         
+        // Pre-install hook: a failure aborts before anything is changed
+        if let Some(hooks) = &self.hooks {
+            hooks.run_pre_install().await?;
+        }
+        
         // Step 2: Backup current version
         let backup = self.rollback_manager.backup_current()?;
         info!("Created backup: version {}", backup.version);
@@ -291,6 +360,19 @@ impl PlatformInstaller for WindowsInstaller {
             }
         }
         
+        // Post-install hook: a failure rolls the update back
+        if let Some(hooks) = &self.hooks {
+            hooks
+                .run_post_install_or_rollback(
+                    &self.rollback_manager,
+                    &backup,
+                    was_running,
+                    || self.stop_service(),
+                    || self.start_service(),
+                )
+                .await?;
+        }
+        
         info!("Windows update installation completed successfully");
         Ok(())
     }
@@ -337,6 +419,14 @@ impl PlatformInstaller for WindowsInstaller {
     fn requires_restart(&self) -> bool {
         true
     }
+
+    fn restart_service(&self) -> Result<(), UpdateError> {
+        // The new version may already have exited, so a failed stop is fine
+        if let Err(e) = self.stop_service() {
+            warn!("Failed to stop service before restart: {}", e);
+        }
+        self.start_service()
+    }
 }
 
 // ============================================================================
@@ -629,7 +719,12 @@ pub fn verify_authenticode(path: &Path, expected_thumbprint: Option<&str>) -> Re
     }
 }
 
-/// Get the certificate thumbprint from a signed file.
+/// Get the SHA-1 thumbprint of the certificate that signed a file.
+///
+/// The signer is identified from the embedded PKCS#7 message
+/// (`CMSG_SIGNER_CERT_INFO_PARAM`), looked up in the message's certificate
+/// store, and its `CERT_SHA1_HASH_PROP_ID` property returned as uppercase hex,
+/// matching the thumbprint shown by `certmgr` and `Get-AuthenticodeSignature`.
 #[cfg(target_os = "windows")]
 fn get_certificate_thumbprint(path: &Path) -> Result<String, UpdateError> {
     use std::ffi::OsStr;
@@ -637,10 +732,9 @@ fn get_certificate_thumbprint(path: &Path) -> Result<String, UpdateError> {
     use std::ptr;
     
     use windows::Win32::Security::Cryptography::{
-        CryptMsgClose, CryptMsgGetParam,
-        CryptQueryObject, CERT_QUERY_CONTENT_FLAG_PKCS7_SIGNED_EMBED,
-        CERT_QUERY_FORMAT_FLAG_BINARY, CERT_QUERY_OBJECT_FILE,
-        CMSG_SIGNER_INFO_PARAM,
+        CertCloseStore, CryptMsgClose, CryptQueryObject,
+        CERT_QUERY_CONTENT_FLAG_PKCS7_SIGNED_EMBED, CERT_QUERY_ENCODING_TYPE,
+        CERT_QUERY_FORMAT_FLAG_BINARY, CERT_QUERY_OBJECT_FILE, HCERTSTORE,
     };
     
     let path_wide: Vec<u16> = OsStr::new(path)
@@ -649,20 +743,21 @@ fn get_certificate_thumbprint(path: &Path) -> Result<String, UpdateError> {
         .collect();
     
     unsafe {
+        let mut encoding = CERT_QUERY_ENCODING_TYPE::default();
+        let mut cert_store = HCERTSTORE::default();
         let mut msg_handle: *mut std::ffi::c_void = ptr::null_mut();
-        let mut cert_store = ptr::null_mut();
         
-        // Query the object to get the message handle
+        // Query the object to get the certificate store and message handle
         let result = CryptQueryObject(
             CERT_QUERY_OBJECT_FILE,
             path_wide.as_ptr() as *const _,
             CERT_QUERY_CONTENT_FLAG_PKCS7_SIGNED_EMBED,
             CERT_QUERY_FORMAT_FLAG_BINARY,
             0,
+            Some(&mut encoding),
             None,
             None,
-            None,
-            Some(cert_store),
+            Some(&mut cert_store),
             Some(&mut msg_handle),
             None,
         );
@@ -673,35 +768,88 @@ fn get_certificate_thumbprint(path: &Path) -> Result<String, UpdateError> {
             ));
         }
         
-        // Get signer info size
-        let mut signer_info_size: u32 = 0;
-        let _ = CryptMsgGetParam(
-            msg_handle as *const _,
-            CMSG_SIGNER_INFO_PARAM,
-            0,
-            None,
-            &mut signer_info_size,
-        );
-        
-        if signer_info_size == 0 {
-            let _ = CryptMsgClose(Some(msg_handle as *const _));
-            return Err(UpdateError::CodeSignatureInvalid(
-                "No signer information found".to_string(),
-            ));
-        }
-        
-        // For simplicity, we'll compute a hash of the file's signature
-        // In a production implementation, you'd extract the actual certificate
-        // and compute its SHA-1 thumbprint
+        let thumbprint = signer_thumbprint(cert_store, msg_handle, encoding);
         
         let _ = CryptMsgClose(Some(msg_handle as *const _));
+        let _ = CertCloseStore(Some(cert_store), 0);
         
-        // Placeholder: return a computed thumbprint
-        // In production, this would extract the actual certificate thumbprint
-        Ok("PLACEHOLDER_THUMBPRINT".to_string())
+        thumbprint
     }
 }
 
+/// Look up the signer certificate and read its SHA-1 hash property.
+#[cfg(target_os = "windows")]
+unsafe fn signer_thumbprint(
+    cert_store: windows::Win32::Security::Cryptography::HCERTSTORE,
+    msg_handle: *mut std::ffi::c_void,
+    encoding: windows::Win32::Security::Cryptography::CERT_QUERY_ENCODING_TYPE,
+) -> Result<String, UpdateError> {
+    use windows::Win32::Security::Cryptography::{
+        CertFreeCertificateContext, CertGetCertificateContextProperty,
+        CertGetSubjectCertificateFromStore, CryptMsgGetParam, CERT_INFO,
+        CERT_SHA1_HASH_PROP_ID, CMSG_SIGNER_CERT_INFO_PARAM,
+    };
+    
+    // Signer issuer and serial number, as a CERT_INFO
+    let mut signer_info_size: u32 = 0;
+    let _ = CryptMsgGetParam(
+        msg_handle as *const _,
+        CMSG_SIGNER_CERT_INFO_PARAM,
+        0,
+        None,
+        &mut signer_info_size,
+    );
+
+    if signer_info_size == 0 {
+        return Err(UpdateError::CodeSignatureInvalid(
+            "No signer information found".to_string(),
+        ));
+    }
+
+    // u64 backing keeps the CERT_INFO suitably aligned
+    let mut signer_info = vec![0u64; (signer_info_size as usize).div_ceil(8)];
+    CryptMsgGetParam(
+        msg_handle as *const _,
+        CMSG_SIGNER_CERT_INFO_PARAM,
+        0,
+        Some(signer_info.as_mut_ptr() as *mut _),
+        &mut signer_info_size,
+    )
+    .map_err(|e| {
+        UpdateError::CodeSignatureInvalid(format!("Failed to read signer info: {}", e))
+    })?;
+
+    let cert_context = CertGetSubjectCertificateFromStore(
+        cert_store,
+        encoding,
+        signer_info.as_ptr() as *const CERT_INFO,
+    );
+    if cert_context.is_null() {
+        return Err(UpdateError::CodeSignatureInvalid(
+            "Signer certificate not found in signature".to_string(),
+        ));
+    }
+
+    let mut hash = [0u8; 20];
+    let mut hash_size = hash.len() as u32;
+    let result = CertGetCertificateContextProperty(
+        cert_context,
+        CERT_SHA1_HASH_PROP_ID,
+        Some(hash.as_mut_ptr() as *mut _),
+        &mut hash_size,
+    );
+    let _ = CertFreeCertificateContext(Some(cert_context));
+
+    result.map_err(|e| {
+        UpdateError::CodeSignatureInvalid(format!(
+            "Failed to read certificate thumbprint: {}",
+            e
+        ))
+    })?;
+
+    Ok(hex::encode_upper(&hash[..hash_size as usize]))
+}
+
 // Stub for non-Windows platforms
 #[cfg(not(target_os = "windows"))]
 pub fn verify_authenticode(_path: &Path, _expected_thumbprint: Option<&str>) -> Result<(), UpdateError> {
@@ -738,8 +886,12 @@ pub struct MacOSInstaller {
     rollback_manager: RollbackManager,
     /// Expected Team ID for code signature verification (optional)
     expected_team_id: Option<String>,
+    /// Whether a rejected Gatekeeper assessment fails the installation
+    require_notarization: bool,
     /// Whether this is a LaunchDaemon (system-wide) vs LaunchAgent (user)
     is_daemon: bool,
+    /// Optional pre/post install hooks
+    hooks: Option<InstallHooks>,
 }
 
 #[cfg(target_os = "macos")]
@@ -771,7 +923,9 @@ impl MacOSInstaller {
             backup_dir,
             rollback_manager,
             expected_team_id: None,
+            require_notarization: false,
             is_daemon: false,
+            hooks: None,
         }
     }
 
@@ -784,6 +938,16 @@ impl MacOSInstaller {
         self
     }
 
+    /// Require the artifact to pass Gatekeeper notarization assessment.
+    ///
+    /// By default a rejection from `spctl` is only logged. When required,
+    /// it fails the installation; an unavailable `spctl` is still only
+    /// logged so older macOS releases keep working.
+    pub fn with_require_notarization(mut self, required: bool) -> Self {
+        self.require_notarization = required;
+        self
+    }
+
     /// Set whether this is a LaunchDaemon (system-wide) vs LaunchAgent (user).
     ///
     /// LaunchDaemons require root privileges and are located in /Library/LaunchDaemons.
@@ -793,6 +957,16 @@ impl MacOSInstaller {
         self
     }
 
+    /// Set pre/post install hooks.
+    ///
+    /// The pre-install hook runs before the backup and service stop; the
+    /// post-install hook runs after the service restarts and rolls the
+    /// update back if it fails.
+    pub fn with_hooks(mut self, hooks: InstallHooks) -> Self {
+        self.hooks = Some(hooks);
+        self
+    }
+
     /// Get the launch agent/daemon label.
     pub fn launch_agent_label(&self) -> &str {
         &self.launch_agent_label
@@ -894,6 +1068,8 @@ impl PlatformInstaller for MacOSInstaller {
     /// 6. Start the LaunchAgent/Daemon
     /// 7. On failure, automatically rollback
     ///
+    /// Configured hooks run before step 2 and after step 6.
+    ///
     /// # Requirements
     ///
     /// - Requirement 7.1: .pkg or app bundle replacement
@@ -904,8 +1080,17 @@ impl PlatformInstaller for MacOSInstaller {
         info!("Starting macOS update installation from {:?}", artifact);
         
         // Step 1: Verify code signature before installation
-        if self.expected_team_id.is_some() {
-            verify_macos_code_signature(artifact, self.expected_team_id.as_deref())?;
+        if self.expected_team_id.is_some() || self.require_notarization {
+            verify_macos_code_signature(
+                artifact,
+                self.expected_team_id.as_deref(),
+                self.require_notarization,
+            )?;
+        }
+        
+        // Pre-install hook: a failure aborts before anything is changed
+        if let Some(hooks) = &self.hooks {
+            hooks.run_pre_install().await?;
         }
         
         // Step 2: Backup current version
@@ -938,8 +1123,12 @@ impl PlatformInstaller for MacOSInstaller {
         }
         
         // Step 6: Verify new executable signature
-        if self.expected_team_id.is_some() {
-            if let Err(e) = verify_macos_code_signature(&current_exe, self.expected_team_id.as_deref()) {
+        if self.expected_team_id.is_some() || self.require_notarization {
+            if let Err(e) = verify_macos_code_signature(
+                &current_exe,
+                self.expected_team_id.as_deref(),
+                self.require_notarization,
+            ) {
                 warn!("New executable signature verification failed, rolling back: {}", e);
                 // Rollback
                 let _ = self.rollback_manager.rollback_to(&backup);
@@ -961,6 +1150,19 @@ impl PlatformInstaller for MacOSInstaller {
             }
         }
         
+        // Post-install hook: a failure rolls the update back
+        if let Some(hooks) = &self.hooks {
+            hooks
+                .run_post_install_or_rollback(
+                    &self.rollback_manager,
+                    &backup,
+                    was_running,
+                    || self.stop_service(),
+                    || self.start_service(),
+                )
+                .await?;
+        }
+        
         info!("macOS update installation completed successfully");
         Ok(())
     }
@@ -1007,6 +1209,14 @@ impl PlatformInstaller for MacOSInstaller {
     fn requires_restart(&self) -> bool {
         true
     }
+
+    fn restart_service(&self) -> Result<(), UpdateError> {
+        // The new version may already have exited, so a failed stop is fine
+        if let Err(e) = self.stop_service() {
+            warn!("Failed to stop service before restart: {}", e);
+        }
+        self.start_service()
+    }
 }
 
 // ============================================================================
@@ -1208,12 +1418,18 @@ mod macos_launchctl {
 ///
 /// * `path` - Path to the executable or app bundle to verify
 /// * `expected_team_id` - Optional Team ID to match
+/// * `require_notarization` - Treat a Gatekeeper rejection as an error
+///   rather than a warning. An unavailable `spctl` is never an error.
 ///
 /// # Requirements
 ///
 /// - Requirement 7.4: Code signature and notarization verification
 #[cfg(target_os = "macos")]
-pub fn verify_macos_code_signature(path: &Path, expected_team_id: Option<&str>) -> Result<(), UpdateError> {
+pub fn verify_macos_code_signature(
+    path: &Path,
+    expected_team_id: Option<&str>,
+    require_notarization: bool,
+) -> Result<(), UpdateError> {
     use std::process::Command;
     
     info!("Verifying macOS code signature for {:?}", path);
@@ -1241,19 +1457,26 @@ pub fn verify_macos_code_signature(path: &Path, expected_team_id: Option<&str>)
         .arg(path)
         .output();
     
-    match notarization_result {
+    let assessment = match notarization_result {
         Ok(output) => {
-            if !output.status.success() {
-                let stderr = String::from_utf8_lossy(&output.stderr);
-                // Notarization check failure is a warning, not an error
-                // Some valid signed apps may not be notarized
-                warn!("Notarization check warning: {}", stderr);
-            } else {
-                debug!("Notarization check passed");
-            }
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            classify_spctl_output(output.status.success(), &stderr)
         }
-        Err(e) => {
-            warn!("Could not check notarization status: {}", e);
+        Err(e) => SpctlAssessment::Unavailable(e.to_string()),
+    };
+    
+    match assessment {
+        SpctlAssessment::Accepted => debug!("Notarization check passed"),
+        SpctlAssessment::Rejected(reason) if require_notarization => {
+            return Err(UpdateError::CodeSignatureInvalid(format!(
+                "Notarization check failed: {}",
+                reason
+            )));
+        }
+        // Some valid signed apps may not be notarized
+        SpctlAssessment::Rejected(reason) => warn!("Notarization check warning: {}", reason),
+        SpctlAssessment::Unavailable(reason) => {
+            warn!("Could not check notarization status: {}", reason);
         }
     }
     
@@ -1301,9 +1524,56 @@ fn extract_team_id(path: &Path) -> Result<String, UpdateError> {
     ))
 }
 
+/// Outcome of a Gatekeeper assessment.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+enum SpctlAssessment {
+    /// Gatekeeper accepted the artifact
+    Accepted,
+    /// Gatekeeper rejected the artifact (e.g. not notarized)
+    Rejected(String),
+    /// The assessment could not be performed
+    Unavailable(String),
+}
+
+/// Classify `spctl --assess -v` output.
+///
+/// `spctl` reports `<path>: accepted` or `<path>: rejected` on stderr, with
+/// the reason on a following `source=` line. Anything else (a missing tool,
+/// unsupported options on older macOS, assessments disabled) is treated as
+/// unavailable rather than as a rejection.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn classify_spctl_output(success: bool, stderr: &str) -> SpctlAssessment {
+    let verdict = stderr.lines().find_map(|line| {
+        let line = line.trim_end();
+        if line.ends_with(": accepted") {
+            Some(true)
+        } else if line.ends_with(": rejected") || line.contains(": rejected (") {
+            Some(false)
+        } else {
+            None
+        }
+    });
+    let source = stderr
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("source="))
+        .unwrap_or("no reason given")
+        .to_string();
+    
+    match verdict {
+        Some(true) if success => SpctlAssessment::Accepted,
+        Some(false) if !success => SpctlAssessment::Rejected(source),
+        _ => SpctlAssessment::Unavailable(stderr.trim().to_string()),
+    }
+}
+
 // Stub for non-macOS platforms
 #[cfg(not(target_os = "macos"))]
-pub fn verify_macos_code_signature(_path: &Path, _expected_team_id: Option<&str>) -> Result<(), UpdateError> {
+pub fn verify_macos_code_signature(
+    _path: &Path,
+    _expected_team_id: Option<&str>,
+    _require_notarization: bool,
+) -> Result<(), UpdateError> {
     Err(UpdateError::CodeSignatureInvalid(
         "macOS code signature verification is only available on macOS".to_string(),
     ))
@@ -1341,6 +1611,8 @@ pub struct LinuxInstaller {
     is_user_service: bool,
     /// Whether the executable is an AppImage
     is_appimage: bool,
+    /// Optional pre/post install hooks
+    hooks: Option<InstallHooks>,
 }
 
 #[cfg(target_os = "linux")]
@@ -1373,6 +1645,7 @@ impl LinuxInstaller {
             rollback_manager,
             is_user_service: false,
             is_appimage: false,
+            hooks: None,
         }
     }
 
@@ -1394,6 +1667,16 @@ impl LinuxInstaller {
         self
     }
 
+    /// Set pre/post install hooks.
+    ///
+    /// The pre-install hook runs before the backup and service stop; the
+    /// post-install hook runs after the service restarts and rolls the
+    /// update back if it fails.
+    pub fn with_hooks(mut self, hooks: InstallHooks) -> Self {
+        self.hooks = Some(hooks);
+        self
+    }
+
     /// Get the systemd unit name.
     pub fn systemd_unit(&self) -> &str {
         &self.systemd_unit
@@ -1454,9 +1737,15 @@ impl LinuxInstaller {
     /// Replace the executable file.
     ///
     /// Handles Linux-specific file replacement:
-    /// 1. Copy the new artifact to the executable location
-    /// 2. Set proper file permissions (rwxr-xr-x)
-    /// 3. Preserve ownership if running as root
+    /// 1. Write the new artifact to a temp file next to the executable
+    /// 2. Set proper file permissions (rwxr-xr-x) and fsync
+    /// 3. Rename it over the executable
+    /// 4. Verify permissions
+    ///
+    /// Renaming swaps the directory entry instead of writing into the old
+    /// inode, so it succeeds while the running binary is still mapped (no
+    /// `ETXTBSY`) and is atomic. If the rename crosses filesystems (e.g. the
+    /// executable is a bind mount) the new binary is copied over instead.
     ///
     /// # Requirements
     ///
@@ -1466,20 +1755,7 @@ impl LinuxInstaller {
     fn replace_executable(&self, artifact: &Path, target: &Path) -> Result<(), UpdateError> {
         info!("Replacing executable: {:?} -> {:?}", artifact, target);
         
-        // Copy new artifact to target location
-        std::fs::copy(artifact, target).map_err(|e| {
-            UpdateError::InstallationFailed(format!("Failed to copy new executable: {}", e))
-        })?;
-        
-        // Set executable permissions (rwxr-xr-x = 0o755)
-        #[cfg(unix)]
-        {
-            use std::os::unix::fs::PermissionsExt;
-            let permissions = std::fs::Permissions::from_mode(0o755);
-            std::fs::set_permissions(target, permissions).map_err(|e| {
-                UpdateError::InstallationFailed(format!("Failed to set permissions: {}", e))
-            })?;
-        }
+        replace_file(artifact, target, |from, to| std::fs::rename(from, to))?;
         
         // Verify permissions were set correctly
         self.verify_permissions(target)?;
@@ -1571,6 +1847,74 @@ impl LinuxInstaller {
     }
 }
 
+/// `errno` for a rename across filesystems.
+#[cfg(target_os = "linux")]
+const EXDEV: i32 = 18;
+
+/// Replace `target` with the contents of `artifact` via temp file + rename.
+///
+/// `rename` is injectable so the cross-filesystem fallback can be tested.
+/// The temp file is always removed if it is not renamed into place.
+#[cfg(target_os = "linux")]
+fn replace_file<R>(artifact: &Path, target: &Path, rename: R) -> Result<(), UpdateError>
+where
+    R: Fn(&Path, &Path) -> std::io::Result<()>,
+{
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = target.parent().ok_or_else(|| {
+        UpdateError::InstallationFailed(format!("Executable has no parent directory: {:?}", target))
+    })?;
+    let name = target
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let temp = dir.join(format!(".{}.new-{}", name, std::process::id()));
+    let permissions = std::fs::Permissions::from_mode(0o755);
+
+    let staged = (|| -> std::io::Result<()> {
+        let mut src = std::fs::File::open(artifact)?;
+        let mut dst = std::fs::File::create(&temp)?;
+        std::io::copy(&mut src, &mut dst)?;
+        dst.set_permissions(permissions.clone())?;
+        dst.sync_all()
+    })();
+    if let Err(e) = staged {
+        let _ = std::fs::remove_file(&temp);
+        return Err(UpdateError::InstallationFailed(format!(
+            "Failed to stage new executable: {}",
+            e
+        )));
+    }
+
+    match rename(&temp, target) {
+        Ok(()) => {
+            // Persist the rename itself; failure here is not fatal
+            if let Ok(dir) = std::fs::File::open(dir) {
+                let _ = dir.sync_all();
+            }
+            Ok(())
+        }
+        Err(e) if e.raw_os_error() == Some(EXDEV) => {
+            warn!("Rename crosses filesystems, copying executable instead: {}", e);
+            let _ = std::fs::remove_file(&temp);
+            std::fs::copy(artifact, target).map_err(|e| {
+                UpdateError::InstallationFailed(format!("Failed to copy new executable: {}", e))
+            })?;
+            std::fs::set_permissions(target, permissions).map_err(|e| {
+                UpdateError::InstallationFailed(format!("Failed to set permissions: {}", e))
+            })
+        }
+        Err(e) => {
+            let _ = std::fs::remove_file(&temp);
+            Err(UpdateError::InstallationFailed(format!(
+                "Failed to move new executable into place: {}",
+                e
+            )))
+        }
+    }
+}
+
 #[cfg(target_os = "linux")]
 #[async_trait]
 impl PlatformInstaller for LinuxInstaller {
@@ -1584,6 +1928,8 @@ impl PlatformInstaller for LinuxInstaller {
     /// 5. Start the systemd service
     /// 6. On failure, automatically rollback
     ///
+    /// Configured hooks run before step 1 and after step 5.
+    ///
     /// # Requirements
     ///
     /// - Requirement 8.1: In-place binary replacement
@@ -1594,6 +1940,11 @@ impl PlatformInstaller for LinuxInstaller {
     async fn install(&self, artifact: &Path) -> Result<(), UpdateError> {
         info!("Starting Linux update installation from {:?}", artifact);
         
+        // Pre-install hook: a failure aborts before anything is changed
+        if let Some(hooks) = &self.hooks {
+            hooks.run_pre_install().await?;
+        }
+        
         // Step 1: Backup current version
         let backup = self.rollback_manager.backup_current()?;
         info!("Created backup: version {}", backup.version);
@@ -1651,6 +2002,19 @@ impl PlatformInstaller for LinuxInstaller {
             }
         }
         
+        // Post-install hook: a failure rolls the update back
+        if let Some(hooks) = &self.hooks {
+            hooks
+                .run_post_install_or_rollback(
+                    &self.rollback_manager,
+                    &backup,
+                    was_running,
+                    || self.stop_service(),
+                    || self.start_service(),
+                )
+                .await?;
+        }
+        
         info!("Linux update installation completed successfully");
         Ok(())
     }
@@ -1697,6 +2061,14 @@ impl PlatformInstaller for LinuxInstaller {
     fn requires_restart(&self) -> bool {
         true
     }
+
+    fn restart_service(&self) -> Result<(), UpdateError> {
+        // The new version may already have exited, so a failed stop is fine
+        if let Err(e) = self.stop_service() {
+            warn!("Failed to stop service before restart: {}", e);
+        }
+        self.start_service()
+    }
 }
 
 // ============================================================================
@@ -1886,6 +2258,49 @@ mod tests {
     use super::*;
     use tempfile::TempDir;
 
+    // ========================================================================
+    // Delta Patch Tests
+    // ========================================================================
+
+    fn write_patch(dir: &Path, old: &[u8], new: &[u8]) -> (PathBuf, PathBuf) {
+        let base = dir.join("base.bin");
+        let patch = dir.join("update.patch");
+        let mut patch_data = Vec::new();
+        bsdiff::diff(old, new, &mut patch_data).unwrap();
+        std::fs::write(&base, old).unwrap();
+        std::fs::write(&patch, patch_data).unwrap();
+        (base, patch)
+    }
+
+    #[test]
+    fn test_apply_patch_rebuilds_artifact() {
+        let temp_dir = TempDir::new().unwrap();
+        let old = b"zrc-agent 1.0.0 binary contents".repeat(64);
+        let mut new = old.clone();
+        new[100..108].copy_from_slice(b"1.0.1!!!");
+        let (base, patch) = write_patch(temp_dir.path(), &old, &new);
+
+        let dest = temp_dir.path().join("out/update.bin");
+        let expected: [u8; 32] = Sha256::digest(&new).into();
+        apply_patch(&base, &patch, &dest, &expected).unwrap();
+
+        assert_eq!(std::fs::read(&dest).unwrap(), new);
+    }
+
+    #[test]
+    fn test_apply_patch_rejects_hash_mismatch() {
+        let temp_dir = TempDir::new().unwrap();
+        let old = b"old release".repeat(32);
+        let new = b"new release".repeat(32);
+        let (base, patch) = write_patch(temp_dir.path(), &old, &new);
+
+        let dest = temp_dir.path().join("update.bin");
+        let result = apply_patch(&base, &patch, &dest, &[0u8; 32]);
+
+        assert!(matches!(result, Err(UpdateError::HashMismatch { .. })));
+        assert!(!dest.exists(), "mismatched artifact must not be written");
+    }
+
     #[cfg(target_os = "windows")]
     #[test]
     fn test_windows_installer_creation() {
@@ -1916,6 +2331,57 @@ mod tests {
         assert_eq!(installer.service_name(), "TestService");
     }
 
+    /// Signs a copy of `whoami.exe` with `tests/fixtures/authenticode/make-fixture.ps1`,
+    /// which briefly adds a self-signed certificate to the user's store.
+    #[cfg(all(target_os = "windows", feature = "authenticode-fixture"))]
+    #[test]
+    fn test_certificate_thumbprint_of_signed_fixture() {
+        let script = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/authenticode/make-fixture.ps1");
+        let source = Path::new(&std::env::var("SystemRoot").unwrap()).join("System32/whoami.exe");
+        let temp_dir = TempDir::new().unwrap();
+        let status = std::process::Command::new("powershell")
+            .args(["-NoProfile", "-ExecutionPolicy", "Bypass", "-File"])
+            .arg(&script)
+            .arg("-Source")
+            .arg(&source)
+            .arg("-OutDir")
+            .arg(temp_dir.path())
+            .status()
+            .unwrap();
+        assert!(status.success(), "make-fixture.ps1 failed");
+        let expected = std::fs::read_to_string(temp_dir.path().join("signed.thumbprint")).unwrap();
+        
+        let thumbprint = get_certificate_thumbprint(&temp_dir.path().join("signed.exe")).unwrap();
+        assert_eq!(thumbprint, expected.trim().to_ascii_uppercase());
+        assert_eq!(thumbprint.len(), 40);
+    }
+
+    #[test]
+    fn test_spctl_accepted() {
+        let stderr = "/Applications/ZRC.app: accepted\nsource=Notarized Developer ID\n";
+        assert_eq!(classify_spctl_output(true, stderr), SpctlAssessment::Accepted);
+    }
+
+    #[test]
+    fn test_spctl_rejected_not_notarized() {
+        let stderr = "/tmp/zrc-agent: rejected\nsource=Unnotarized Developer ID\n";
+        assert_eq!(
+            classify_spctl_output(false, stderr),
+            SpctlAssessment::Rejected("Unnotarized Developer ID".to_string())
+        );
+    }
+
+    #[test]
+    fn test_spctl_unrecognized_output_is_unavailable() {
+        // Older spctl releases reject `--type execute` outright
+        let stderr = "spctl: unrecognized option `--type'\n";
+        assert!(matches!(
+            classify_spctl_output(false, stderr),
+            SpctlAssessment::Unavailable(_)
+        ));
+        assert!(matches!(classify_spctl_output(false, ""), SpctlAssessment::Unavailable(_)));
+    }
+
     // ========================================================================
     // macOS Installer Tests
     // ========================================================================
@@ -1972,7 +2438,7 @@ mod tests {
     #[test]
     fn test_macos_code_signature_stub() {
         use std::path::PathBuf;
-        let result = verify_macos_code_signature(&PathBuf::from("/test"), None);
+        let result = verify_macos_code_signature(&PathBuf::from("/test"), None, false);
         assert!(result.is_err());
         match result {
             Err(UpdateError::CodeSignatureInvalid(msg)) => {
@@ -2063,4 +2529,76 @@ mod tests {
         assert!(installer.is_user_service());
         assert!(installer.is_appimage());
     }
+
+    #[cfg(target_os = "linux")]
+    fn staged_files(dir: &Path) -> Vec<String> {
+        std::fs::read_dir(dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .filter(|name| name.contains(".new-"))
+            .collect()
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_replace_file_renames_over_target() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let artifact = temp_dir.path().join("artifact.bin");
+        let target = temp_dir.path().join("zrc-agent");
+        std::fs::write(&artifact, b"new version").unwrap();
+        std::fs::write(&target, b"old version").unwrap();
+
+        replace_file(&artifact, &target, |from, to| std::fs::rename(from, to)).unwrap();
+
+        assert_eq!(std::fs::read(&target).unwrap(), b"new version");
+        let mode = std::fs::metadata(&target).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o755);
+        assert!(staged_files(temp_dir.path()).is_empty());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_replace_file_falls_back_on_cross_device_rename() {
+        use std::cell::Cell;
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let artifact = temp_dir.path().join("artifact.bin");
+        let target = temp_dir.path().join("zrc-agent");
+        std::fs::write(&artifact, b"new version").unwrap();
+        std::fs::write(&target, b"old version").unwrap();
+
+        let attempted = Cell::new(false);
+        replace_file(&artifact, &target, |_, _| {
+            attempted.set(true);
+            Err(std::io::Error::from_raw_os_error(EXDEV))
+        })
+        .unwrap();
+
+        assert!(attempted.get());
+        assert_eq!(std::fs::read(&target).unwrap(), b"new version");
+        let mode = std::fs::metadata(&target).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o755);
+        assert!(staged_files(temp_dir.path()).is_empty());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_replace_file_other_rename_error_leaves_target() {
+        let temp_dir = TempDir::new().unwrap();
+        let artifact = temp_dir.path().join("artifact.bin");
+        let target = temp_dir.path().join("zrc-agent");
+        std::fs::write(&artifact, b"new version").unwrap();
+        std::fs::write(&target, b"old version").unwrap();
+
+        let result = replace_file(&artifact, &target, |_, _| {
+            Err(std::io::Error::from(std::io::ErrorKind::PermissionDenied))
+        });
+
+        assert!(matches!(result, Err(UpdateError::InstallationFailed(_))));
+        assert_eq!(std::fs::read(&target).unwrap(), b"old version");
+        assert!(staged_files(temp_dir.path()).is_empty());
+    }
 }
@@ -22,6 +22,8 @@ pub mod channel;
 pub mod config;
 pub mod download;
 pub mod error;
+pub mod health;
+pub mod hooks;
 pub mod install;
 pub mod manager;
 pub mod manifest;
@@ -29,12 +31,18 @@ pub mod notification;
 pub mod offline;
 #[cfg(test)]
 mod proptests;
+pub mod quarantine;
 pub mod rollback;
 
 // Re-export main types for convenience
 pub use artifact::ArtifactVerifier;
 pub use channel::{ChannelManager, UpdateChannel};
-pub use config::{RollbackConfig, SecurityConfig, UpdateConfig};
+pub use config::{
+    HealthCheckConfig, HookCommand, HooksConfig, KeyEntry, RollbackConfig, SecurityConfig,
+    UpdateConfig,
+};
+pub use health::{CommandHealthCheck, HealthCheck, HttpHealthCheck};
+pub use hooks::InstallHooks;
 pub use download::{DownloadProgress, Downloader, DownloaderConfig};
 pub use error::UpdateError;
 pub use install::PlatformInstaller;
@@ -44,15 +52,19 @@ pub use install::{WindowsInstaller, verify_authenticode};
 pub use install::{MacOSInstaller, verify_macos_code_signature};
 #[cfg(target_os = "linux")]
 pub use install::LinuxInstaller;
-pub use manager::{UpdateInfo, UpdateManager, UpdateState};
-pub use manifest::{current_platform, ManifestVerifier, SignedManifest, UpdateManifest, ManifestSignature};
+pub use manager::{UpdateInfo, UpdateManager, UpdatePhase, UpdatePhaseProgress, UpdateState};
+pub use manifest::{
+    current_platform, ManifestSignature, ManifestVerifier, PatchArtifact, PinnedKey, Rollout,
+    SignedManifest, UpdateManifest, VerifiedManifest,
+};
 pub use notification::{
     create_platform_backend, DeferredUpdate, NotificationBackend, NotificationConfig,
     NotificationContent, NotificationManager, NotificationResponse, NotificationState,
     StubNotificationBackend, UpdateUrgency,
 };
 pub use offline::{
-    generate_package_filename, package_extension, OfflineUpdateInfo, OfflineUpdateManager,
-    OfflineUpdatePackage,
+    generate_package_filename, inspect_package, package_extension, OfflineUpdateInfo,
+    OfflineUpdateManager, OfflineUpdatePackage,
 };
+pub use quarantine::{QuarantineManager, QuarantineRecord};
 pub use rollback::{BackupInfo, RollbackManager};
@@ -6,6 +6,8 @@
 //! - Downloader for downloading updates
 //! - PlatformInstaller for platform-specific installation
 //! - RollbackManager for backup and rollback support
+//! - HealthCheck for probing the installed version before committing
+//! - QuarantineManager for artifacts that fail verification
 //!
 //! # Requirements
 //! - Requirement 4.1: Check for updates on application startup
@@ -20,16 +22,18 @@ use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use semver::Version;
-use tokio::sync::RwLock;
+use tokio::sync::{broadcast, RwLock};
 use tracing::{debug, error, info, warn};
 
 use crate::artifact::ArtifactVerifier;
 use crate::channel::{ChannelManager, UpdateChannel};
 use crate::config::UpdateConfig;
-use crate::download::{DownloadProgress, Downloader};
+use crate::download::{DownloadProgress, Downloader, DownloaderConfig};
 use crate::error::UpdateError;
+use crate::health::{self, HealthCheck};
 use crate::install::PlatformInstaller;
-use crate::manifest::{ManifestVerifier, UpdateManifest};
+use crate::manifest::{ManifestVerifier, PatchArtifact, UpdateManifest};
+use crate::quarantine::{QuarantineManager, QuarantineRecord};
 use crate::rollback::{BackupInfo, RollbackManager};
 
 /// Information about an available update.
@@ -49,6 +53,8 @@ pub struct UpdateInfo {
     pub artifact_url: String,
     /// Update channel
     pub channel: UpdateChannel,
+    /// Optional delta patch against an earlier release
+    pub patch: Option<PatchArtifact>,
 }
 
 impl UpdateInfo {
@@ -66,8 +72,17 @@ impl UpdateInfo {
             expected_hash,
             artifact_url: manifest.artifact_url.clone(),
             channel: manifest.channel.clone(),
+            patch: manifest.patch.clone(),
         })
     }
+
+    /// The delta patch to use when `installed` is running, if any.
+    ///
+    /// Returns None when no patch is published or it targets a different
+    /// base version, in which case the full artifact must be downloaded.
+    pub fn patch_for(&self, installed: &Version) -> Option<&PatchArtifact> {
+        self.patch.as_ref().filter(|patch| patch.applies_to(installed))
+    }
 }
 
 /// Current state of the update manager.
@@ -79,9 +94,13 @@ pub enum UpdateState {
     Checking,
     /// Update available
     UpdateAvailable,
+    /// Update available but not yet rolled out to this device
+    RolloutPending(Version),
+    /// Backing up the current version
+    BackingUp,
     /// Downloading update
     Downloading,
-    /// Verifying downloaded artifact
+    /// Verifying downloaded artifact, then the installed version's health
     Verifying,
     /// Installing update
     Installing,
@@ -97,6 +116,103 @@ impl Default for UpdateState {
     }
 }
 
+/// Number of progress events buffered for slow subscribers.
+const PROGRESS_CHANNEL_CAPACITY: usize = 64;
+
+/// A phase of installing an update, in the order they run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpdatePhase {
+    /// Backing up the current version
+    BackingUp,
+    /// Downloading the artifact (or delta patch)
+    Downloading,
+    /// Verifying the downloaded artifact
+    Verifying,
+    /// Running the platform installer
+    Installing,
+    /// Waiting for the installed version to become healthy
+    VerifyingHealth,
+}
+
+impl UpdatePhase {
+    /// All phases in execution order.
+    pub const ALL: [UpdatePhase; 5] = [
+        UpdatePhase::BackingUp,
+        UpdatePhase::Downloading,
+        UpdatePhase::Verifying,
+        UpdatePhase::Installing,
+        UpdatePhase::VerifyingHealth,
+    ];
+
+    /// Human-readable label for display.
+    pub fn label(&self) -> &'static str {
+        match self {
+            UpdatePhase::BackingUp => "Backing up",
+            UpdatePhase::Downloading => "Downloading",
+            UpdatePhase::Verifying => "Verifying",
+            UpdatePhase::Installing => "Installing",
+            UpdatePhase::VerifyingHealth => "Checking health",
+        }
+    }
+
+    /// Position of this phase in [`UpdatePhase::ALL`].
+    fn index(&self) -> usize {
+        Self::ALL.iter().position(|p| p == self).unwrap_or(0)
+    }
+
+    /// Manager state while this phase runs.
+    fn state(&self) -> UpdateState {
+        match self {
+            UpdatePhase::BackingUp => UpdateState::BackingUp,
+            UpdatePhase::Downloading => UpdateState::Downloading,
+            UpdatePhase::Verifying | UpdatePhase::VerifyingHealth => UpdateState::Verifying,
+            UpdatePhase::Installing => UpdateState::Installing,
+        }
+    }
+}
+
+/// Progress of the whole update, published on [`UpdateManager::subscribe_progress`].
+///
+/// Every phase publishes at least a start (0.0) and an end (1.0) event, so a
+/// single progress bar never stalls on phases that finish instantly.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UpdatePhaseProgress {
+    /// Current phase
+    pub phase: UpdatePhase,
+    /// Completion of the current phase (0.0 to 1.0)
+    pub fraction: f32,
+    /// Label of the current phase
+    pub label: &'static str,
+}
+
+impl UpdatePhaseProgress {
+    /// Create a progress event, clamping `fraction` to 0.0..=1.0.
+    pub fn new(phase: UpdatePhase, fraction: f32) -> Self {
+        Self {
+            phase,
+            fraction: fraction.clamp(0.0, 1.0),
+            label: phase.label(),
+        }
+    }
+
+    /// Completion of the whole update (0.0 to 1.0), weighting phases equally.
+    pub fn overall(&self) -> f32 {
+        (self.phase.index() as f32 + self.fraction) / UpdatePhase::ALL.len() as f32
+    }
+}
+
+/// Publish download progress as `Downloading` phase progress.
+fn forward_download_progress(
+    tx: broadcast::Sender<UpdatePhaseProgress>,
+) -> impl Fn(DownloadProgress) + Send + Sync + 'static {
+    move |progress| {
+        if progress.total > 0 {
+            let fraction = (progress.percentage() / 100.0) as f32;
+            let _ = tx.send(UpdatePhaseProgress::new(UpdatePhase::Downloading, fraction));
+        }
+    }
+}
+
 /// Main update manager that orchestrates the complete update flow.
 ///
 /// # Example
@@ -129,8 +245,14 @@ pub struct UpdateManager {
     channel_manager: ChannelManager,
     /// Rollback manager for backups
     rollback_manager: RollbackManager,
+    /// Quarantine for artifacts that failed verification
+    quarantine: QuarantineManager,
     /// Platform-specific installer
     installer: Option<Box<dyn PlatformInstaller>>,
+    /// Probe run after installation; an unhealthy update is rolled back
+    health_check: Option<Box<dyn HealthCheck>>,
+    /// Publishes phase progress during `install_update`
+    progress_tx: broadcast::Sender<UpdatePhaseProgress>,
     /// Current state
     state: Arc<RwLock<UpdateState>>,
     /// Current version of the application
@@ -141,6 +263,8 @@ pub struct UpdateManager {
     cached_update: Arc<RwLock<Option<UpdateInfo>>>,
     /// Download directory for staging updates
     download_dir: PathBuf,
+    /// Stable device identifier used for staged rollouts
+    device_id: Option<String>,
 }
 
 impl UpdateManager {
@@ -165,7 +289,7 @@ impl UpdateManager {
         channel_config_path: PathBuf,
     ) -> Result<Self, UpdateError> {
         // Parse manifest signing keys
-        let trusted_keys = config.security.parse_manifest_keys()?;
+        let trusted_keys = config.security.parse_pinned_keys()?;
         if trusted_keys.is_empty() {
             warn!("No manifest signing keys configured - updates will fail verification");
         }
@@ -175,14 +299,29 @@ impl UpdateManager {
             // This is safe because verify_and_parse will return InsufficientSignatures
             ManifestVerifier::new(vec![], 1)
         } else {
-            ManifestVerifier::new(trusted_keys, config.security.signature_threshold)
+            ManifestVerifier::with_pinned_keys(
+                trusted_keys,
+                config.security.signature_threshold,
+                config.security.key_grace_secs(),
+            )
         };
 
         // Create artifact verifier
         let artifact_verifier = ArtifactVerifier::new();
 
-        // Create downloader
-        let downloader = Downloader::new();
+        // Create downloader, forwarding its progress to phase subscribers
+        let (progress_tx, _) = broadcast::channel(PROGRESS_CHANNEL_CAPACITY);
+        let mut downloader = Downloader::with_config(DownloaderConfig {
+            timeout_secs: config.network.timeout_seconds,
+            max_retries: config.network.max_retries,
+            max_bytes_per_sec: (config.network.bandwidth_limit > 0)
+                .then_some(config.network.bandwidth_limit),
+            ..DownloaderConfig::default()
+        });
+        downloader.set_progress_callback(forward_download_progress(progress_tx.clone()));
+
+        // Create health check probe (None when disabled)
+        let health_check = health::from_config(&config.health_check)?;
 
         // Load channel manager
         let channel_manager = ChannelManager::load(channel_config_path)?;
@@ -191,6 +330,13 @@ impl UpdateManager {
         let rollback_manager = RollbackManager::new(
             config.rollback.backup_dir(),
             config.rollback.max_backups,
+        )
+        .with_max_age(config.rollback.max_age);
+
+        // Rejected artifacts are kept next to the staging area
+        let quarantine = QuarantineManager::new(
+            download_dir.join("quarantine"),
+            config.security.quarantine_retention,
         );
 
         Ok(Self {
@@ -200,12 +346,16 @@ impl UpdateManager {
             downloader,
             channel_manager,
             rollback_manager,
+            quarantine,
             installer: None,
+            health_check,
+            progress_tx,
             state: Arc::new(RwLock::new(UpdateState::Idle)),
             current_version,
             last_check: Arc::new(RwLock::new(None)),
             cached_update: Arc::new(RwLock::new(None)),
             download_dir,
+            device_id: None,
         })
     }
 
@@ -255,12 +405,40 @@ impl UpdateManager {
         self.installer = Some(installer);
     }
 
+    /// Set the device identifier used to place this device in staged rollouts.
+    ///
+    /// Without one, staged releases are only offered once they reach 100%.
+    pub fn set_device_id(&mut self, device_id: impl Into<String>) {
+        self.device_id = Some(device_id.into());
+    }
+
+    /// Set the probe run after installation.
+    ///
+    /// Overrides the probe built from `UpdateConfig::health_check`.
+    pub fn set_health_check(&mut self, health_check: Box<dyn HealthCheck>) {
+        self.health_check = Some(health_check);
+    }
+
     /// Set a progress callback for downloads.
+    ///
+    /// Download progress is still published to phase subscribers.
     pub fn set_progress_callback<F>(&mut self, callback: F)
     where
         F: Fn(DownloadProgress) + Send + Sync + 'static,
     {
-        self.downloader.set_progress_callback(callback);
+        let forward = forward_download_progress(self.progress_tx.clone());
+        self.downloader.set_progress_callback(move |progress| {
+            forward(progress);
+            callback(progress);
+        });
+    }
+
+    /// Subscribe to progress of all update phases.
+    ///
+    /// Events are only published while `install_update` runs; a subscriber
+    /// that falls more than a few dozen events behind skips ahead.
+    pub fn subscribe_progress(&self) -> broadcast::Receiver<UpdatePhaseProgress> {
+        self.progress_tx.subscribe()
     }
 
     /// Check if an update check is due based on the configured interval.
@@ -296,6 +474,22 @@ impl UpdateManager {
         *self.state.write().await = state;
     }
 
+    /// Enter an update phase: set its state and publish its start.
+    async fn begin_phase(&self, phase: UpdatePhase) {
+        self.set_state(phase.state()).await;
+        self.publish_progress(phase, 0.0);
+    }
+
+    /// Publish the end of an update phase.
+    fn end_phase(&self, phase: UpdatePhase) {
+        self.publish_progress(phase, 1.0);
+    }
+
+    /// Publish phase progress; having no subscribers is not an error.
+    fn publish_progress(&self, phase: UpdatePhase, fraction: f32) {
+        let _ = self.progress_tx.send(UpdatePhaseProgress::new(phase, fraction));
+    }
+
     /// Check for available updates.
     ///
     /// This method:
@@ -353,13 +547,28 @@ impl UpdateManager {
         *self.last_check.write().await = Some(Instant::now());
 
         // Compare versions (Requirement 4.3 - version comparison)
-        if manifest.version > self.current_version {
+        if manifest.version > self.current_version && !self.in_rollout(&manifest) {
+            info!(
+                "Update {} available but not yet rolled out to this device",
+                manifest.version
+            );
+            *self.cached_update.write().await = None;
+            self.set_state(UpdateState::RolloutPending(manifest.version)).await;
+            Ok(None)
+        } else if manifest.version > self.current_version {
             info!(
                 "Update available: {} -> {}",
                 self.current_version, manifest.version
             );
 
             let update_info = UpdateInfo::from_manifest(&manifest)?;
+
+            // Don't offer an update whose artifact already failed verification
+            if let Err(e) = self.check_quarantine(&update_info) {
+                *self.cached_update.write().await = None;
+                self.set_state(UpdateState::Error(e.to_string())).await;
+                return Err(e);
+            }
             
             // Cache the update info
             *self.cached_update.write().await = Some(update_info.clone());
@@ -376,6 +585,13 @@ impl UpdateManager {
         }
     }
 
+    /// Check whether this device is in the manifest's rollout cohort.
+    fn in_rollout(&self, manifest: &UpdateManifest) -> bool {
+        match &self.device_id {
+            Some(device_id) => self.channel_manager.is_in_rollout(manifest, device_id),
+            None => manifest.rollout.as_ref().is_none_or(|r| r.percentage >= 100),
+        }
+    }
 
     /// Download and install an update.
     ///
@@ -384,7 +600,8 @@ impl UpdateManager {
     /// 2. Download the update artifact
     /// 3. Verify artifact hash and signature
     /// 4. Install the update using platform-specific installer
-    /// 5. Rollback on failure (Requirement 9.2)
+    /// 5. Verify the installed version is healthy (if configured)
+    /// 6. Rollback on failure (Requirement 9.2)
     ///
     /// # Requirements
     /// - Requirement 9.1: Backup current version before update
@@ -406,15 +623,20 @@ impl UpdateManager {
     /// - Download fails
     /// - Artifact verification fails
     /// - Installation fails (triggers automatic rollback)
+    /// - The installed version is unhealthy (triggers automatic rollback)
     pub async fn install_update(&self, info: &UpdateInfo) -> Result<(), UpdateError> {
         info!("Installing update to version {}", info.version);
 
+        // Never automatically retry an artifact that already failed verification
+        self.check_quarantine(info)?;
+
         // Ensure we have an installer
         let installer = self.installer.as_ref().ok_or_else(|| {
             UpdateError::InstallationFailed("No platform installer configured".to_string())
         })?;
 
         // Step 1: Backup current version (Requirement 9.1)
+        self.begin_phase(UpdatePhase::BackingUp).await;
         info!("Creating backup of current version...");
         let backup = match self.rollback_manager.backup_current() {
             Ok(backup) => {
@@ -427,44 +649,71 @@ impl UpdateManager {
                 None
             }
         };
+        self.end_phase(UpdatePhase::BackingUp);
 
         // Step 2: Download artifact
-        self.set_state(UpdateState::Downloading).await;
+        self.begin_phase(UpdatePhase::Downloading).await;
         let artifact_path = self.download_dir.join(format!(
             "update-{}-{}.bin",
             info.version,
             chrono::Utc::now().timestamp()
         ));
 
-        info!("Downloading update artifact to {:?}", artifact_path);
-        if let Err(e) = self
-            .downloader
-            .download_with_resume(&info.artifact_url, &artifact_path, info.size)
-            .await
-        {
-            error!("Download failed: {}", e);
-            self.set_state(UpdateState::Error(e.to_string())).await;
-            // Clean up partial download
-            let _ = std::fs::remove_file(&artifact_path);
-            return Err(e);
+        // Prefer a delta patch against the running version; fall back to the
+        // full artifact if there is none or it cannot be applied
+        let mut patched = false;
+        if let Some(patch) = info.patch_for(&self.current_version) {
+            info!("Applying delta update from {}", patch.patch_from);
+            match self.download_patched(patch, info, &artifact_path).await {
+                Ok(()) => patched = true,
+                Err(e) => {
+                    warn!("Delta update failed: {} - falling back to full download", e);
+                    let _ = std::fs::remove_file(&artifact_path);
+                }
+            }
+        }
+
+        if !patched {
+            info!("Downloading update artifact to {:?}", artifact_path);
+            if let Err(e) = self
+                .downloader
+                .download_with_resume(&info.artifact_url, &artifact_path, info.size)
+                .await
+            {
+                error!("Download failed: {}", e);
+                self.set_state(UpdateState::Error(e.to_string())).await;
+                // Clean up partial download
+                let _ = std::fs::remove_file(&artifact_path);
+                return Err(e);
+            }
         }
 
+        self.end_phase(UpdatePhase::Downloading);
+
         // Step 3: Verify artifact
-        self.set_state(UpdateState::Verifying).await;
+        self.begin_phase(UpdatePhase::Verifying).await;
         info!("Verifying artifact integrity...");
         if let Err(e) = self.artifact_verifier.verify(&artifact_path, &info.expected_hash) {
             error!("Artifact verification failed: {}", e);
             self.set_state(UpdateState::Error(e.to_string())).await;
-            // Clean up failed download
-            let _ = std::fs::remove_file(&artifact_path);
+            self.reject_artifact(&artifact_path, info, &e);
             return Err(e);
         }
         info!("Artifact verified successfully");
+        self.end_phase(UpdatePhase::Verifying);
 
         // Step 4: Install update
-        self.set_state(UpdateState::Installing).await;
+        self.begin_phase(UpdatePhase::Installing).await;
         info!("Installing update...");
-        match installer.install(&artifact_path).await {
+        let installed = match installer.install(&artifact_path).await {
+            // Step 5: Health check before committing
+            Ok(()) => {
+                self.end_phase(UpdatePhase::Installing);
+                self.verify_health().await
+            }
+            Err(e) => Err(e),
+        };
+        match installed {
             Ok(()) => {
                 info!("Update installed successfully");
                 // Clean up downloaded artifact
@@ -484,34 +733,145 @@ impl UpdateManager {
             Err(e) => {
                 error!("Installation failed: {}", e);
                 
-                // Step 5: Automatic rollback on failure (Requirement 9.2)
-                if let Some(backup) = backup {
-                    warn!("Attempting automatic rollback...");
-                    if let Err(rollback_err) = self.rollback_manager.rollback_to(&backup) {
-                        error!("Rollback also failed: {}", rollback_err);
-                        self.set_state(UpdateState::Error(format!(
-                            "Installation failed: {}. Rollback also failed: {}",
-                            e, rollback_err
-                        ))).await;
-                    } else {
-                        info!("Rollback successful");
-                        self.set_state(UpdateState::Error(format!(
-                            "Installation failed: {}. Rolled back to previous version.",
-                            e
-                        ))).await;
-                    }
+                // Step 6: Automatic rollback on failure (Requirement 9.2)
+                self.rollback_failed_install(installer.as_ref(), backup.as_ref(), &e).await;
+                
+                // The installer's own signature check rejects the artifact
+                // just like ours; anything else only removes the download
+                if is_artifact_rejection(&e) {
+                    self.reject_artifact(&artifact_path, info, &e);
                 } else {
-                    self.set_state(UpdateState::Error(e.to_string())).await;
+                    let _ = std::fs::remove_file(&artifact_path);
                 }
                 
-                // Clean up downloaded artifact
-                let _ = std::fs::remove_file(&artifact_path);
-                
                 Err(e)
             }
         }
     }
 
+    /// Probe the installed version, if a health check is configured.
+    async fn verify_health(&self) -> Result<(), UpdateError> {
+        let Some(check) = &self.health_check else {
+            // Nothing to probe, but keep the phase sequence complete
+            self.publish_progress(UpdatePhase::VerifyingHealth, 0.0);
+            self.end_phase(UpdatePhase::VerifyingHealth);
+            return Ok(());
+        };
+        self.begin_phase(UpdatePhase::VerifyingHealth).await;
+        info!("Waiting for the installed version to become healthy...");
+        let config = &self.config.health_check;
+        health::wait_until_healthy(
+            check.as_ref(),
+            Duration::from_secs(config.timeout_secs),
+            Duration::from_secs(config.interval_secs),
+        )
+        .await?;
+        self.end_phase(UpdatePhase::VerifyingHealth);
+        Ok(())
+    }
+
+    /// Restore `backup` after a failed or unhealthy install, restart the
+    /// service on the restored binary and record the outcome.
+    async fn rollback_failed_install(
+        &self,
+        installer: &dyn PlatformInstaller,
+        backup: Option<&BackupInfo>,
+        e: &UpdateError,
+    ) {
+        let Some(backup) = backup else {
+            self.set_state(UpdateState::Error(e.to_string())).await;
+            return;
+        };
+
+        warn!("Attempting automatic rollback...");
+        let restored = self
+            .rollback_manager
+            .rollback_to(backup)
+            .and_then(|()| installer.restart_service());
+        if let Err(rollback_err) = restored {
+            error!("Rollback also failed: {}", rollback_err);
+            self.set_state(UpdateState::Error(format!(
+                "Installation failed: {}. Rollback also failed: {}",
+                e, rollback_err
+            ))).await;
+        } else {
+            info!("Rollback successful");
+            self.set_state(UpdateState::Error(format!(
+                "Installation failed: {}. Rolled back to previous version.",
+                e
+            ))).await;
+        }
+    }
+
+    /// Rebuild the update artifact from the installed executable and a patch.
+    async fn download_patched(
+        &self,
+        patch: &PatchArtifact,
+        info: &UpdateInfo,
+        dest: &Path,
+    ) -> Result<(), UpdateError> {
+        let base = std::env::current_exe()?;
+        self.downloader
+            .download_and_apply_patch(patch, &base, dest, &info.expected_hash)
+            .await
+    }
+
+    /// Refuse an update whose artifact is quarantined. Every path that offers
+    /// or installs an update goes through here.
+    fn check_quarantine(&self, info: &UpdateInfo) -> Result<(), UpdateError> {
+        if self.quarantine.is_quarantined(&hex::encode(info.expected_hash))? {
+            warn!("Update {} is quarantined, refusing to retry", info.version);
+            return Err(UpdateError::ArtifactQuarantined {
+                version: info.version.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Quarantine an artifact that failed verification, keeping the bytes
+    /// for forensics; it is deleted only if quarantining fails.
+    fn reject_artifact(&self, artifact: &Path, info: &UpdateInfo, failure: &UpdateError) {
+        if let Err(qe) = self.quarantine_artifact(artifact, info, failure) {
+            warn!("Failed to quarantine artifact: {} - removing it", qe);
+            let _ = std::fs::remove_file(artifact);
+        }
+    }
+
+    /// Move a rejected artifact into quarantine with its forensic record.
+    fn quarantine_artifact(
+        &self,
+        artifact: &Path,
+        info: &UpdateInfo,
+        failure: &UpdateError,
+    ) -> Result<QuarantineRecord, UpdateError> {
+        let actual_hash = match failure {
+            UpdateError::HashMismatch { actual, .. } => actual.clone(),
+            _ => hex::encode(self.artifact_verifier.compute_hash(artifact)?),
+        };
+        let record = QuarantineRecord {
+            version: info.version.clone(),
+            artifact_url: info.artifact_url.clone(),
+            expected_hash: hex::encode(info.expected_hash),
+            actual_hash,
+            reason: failure.to_string(),
+            quarantined_at: chrono::Utc::now(),
+            path: artifact.to_path_buf(),
+        };
+        self.quarantine.quarantine(artifact, record)
+    }
+
+    /// List artifacts quarantined after failed verification, newest first.
+    pub fn list_quarantine(&self) -> Result<Vec<QuarantineRecord>, UpdateError> {
+        self.quarantine.list()
+    }
+
+    /// Delete all quarantined artifacts so their updates may be retried.
+    ///
+    /// Returns the number of artifacts removed.
+    pub fn purge_quarantine(&self) -> Result<usize, UpdateError> {
+        self.quarantine.purge(0)
+    }
+
     /// Manually trigger a rollback to the previous version.
     ///
     /// # Returns
@@ -563,9 +923,58 @@ impl UpdateManager {
     }
 }
 
+/// Whether `error` means the artifact itself failed verification, as
+/// opposed to the install going wrong.
+fn is_artifact_rejection(error: &UpdateError) -> bool {
+    matches!(
+        error,
+        UpdateError::HashMismatch { .. }
+            | UpdateError::CodeSignatureInvalid(_)
+            | UpdateError::SignatureVerificationFailed(_)
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use async_trait::async_trait;
+    use tempfile::TempDir;
+
+    /// Health check stub that never reports healthy.
+    struct Unhealthy;
+
+    /// Installer stub counting service restarts.
+    #[derive(Default)]
+    struct CountingInstaller {
+        restarts: std::sync::atomic::AtomicU32,
+    }
+
+    #[async_trait]
+    impl PlatformInstaller for CountingInstaller {
+        async fn install(&self, _artifact: &Path) -> Result<(), UpdateError> {
+            Ok(())
+        }
+
+        fn rollback(&self) -> Result<(), UpdateError> {
+            Ok(())
+        }
+
+        fn requires_restart(&self) -> bool {
+            true
+        }
+
+        fn restart_service(&self) -> Result<(), UpdateError> {
+            self.restarts.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl HealthCheck for Unhealthy {
+        async fn check(&self) -> Result<(), UpdateError> {
+            Err(UpdateError::HealthCheckFailed("stub unhealthy".to_string()))
+        }
+    }
 
     #[test]
     fn test_update_state_default() {
@@ -597,6 +1006,34 @@ mod tests {
         assert_eq!(info.artifact_url, "https://example.com/update.zip");
     }
 
+    #[test]
+    fn test_update_info_patch_requires_matching_base() {
+        use crate::manifest::UpdateManifest;
+
+        let manifest = UpdateManifest::new(
+            Version::new(2, 0, 1),
+            "windows-x86_64".to_string(),
+            UpdateChannel::Stable,
+            "https://example.com/update.zip".to_string(),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855".to_string(),
+            1024,
+            "Patch release".to_string(),
+            false,
+            None,
+        )
+        .with_patch(PatchArtifact {
+            patch_from: Version::new(2, 0, 0),
+            url: "https://example.com/update-2.0.0-2.0.1.patch".to_string(),
+            hash: "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855".to_string(),
+            size: 64,
+        });
+
+        let info = UpdateInfo::from_manifest(&manifest).unwrap();
+        assert!(info.patch_for(&Version::new(2, 0, 0)).is_some());
+        // Any other installed version falls back to the full artifact
+        assert!(info.patch_for(&Version::new(1, 9, 0)).is_none());
+    }
+
     #[test]
     fn test_update_info_from_manifest_invalid_hash() {
         use crate::manifest::UpdateManifest;
@@ -649,4 +1086,155 @@ mod tests {
         let last = last_check.read().await.unwrap();
         assert!(last.elapsed() < interval);
     }
+
+    #[tokio::test]
+    async fn test_unhealthy_install_rolls_back() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = UpdateConfig::default();
+        config.rollback.backup_dir = Some(temp_dir.path().join("backups"));
+        config.health_check.timeout_secs = 0;
+        config.health_check.interval_secs = 0;
+        let mut manager = UpdateManager::new(
+            config,
+            Version::new(1, 0, 0),
+            temp_dir.path().join("downloads"),
+            temp_dir.path().join("channel.json"),
+        )
+        .unwrap();
+        manager.set_health_check(Box::new(Unhealthy));
+
+        let err = manager.verify_health().await.unwrap_err();
+        assert!(matches!(err, UpdateError::HealthCheckFailed(_)));
+        assert_eq!(manager.state().await, UpdateState::Verifying);
+
+        // The backup has no executable, so the rollback is attempted but
+        // fails before it could touch the running test binary
+        let backup = BackupInfo {
+            version: Version::new(1, 0, 0),
+            created_at: chrono::Utc::now(),
+            path: temp_dir.path().join("backups").join("missing"),
+            hash: None,
+        };
+        let installer = CountingInstaller::default();
+        manager.rollback_failed_install(&installer, Some(&backup), &err).await;
+        match manager.state().await {
+            UpdateState::Error(message) => {
+                assert!(message.contains("stub unhealthy"));
+                assert!(message.contains("Rollback also failed"));
+            }
+            other => panic!("unexpected state: {:?}", other),
+        }
+        // Nothing was restored, so the service is left alone
+        assert_eq!(installer.restarts.load(std::sync::atomic::Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_no_health_check_is_healthy() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = UpdateConfig::default();
+        config.rollback.backup_dir = Some(temp_dir.path().join("backups"));
+        let manager = UpdateManager::new(
+            config,
+            Version::new(1, 0, 0),
+            temp_dir.path().join("downloads"),
+            temp_dir.path().join("channel.json"),
+        )
+        .unwrap();
+
+        assert!(manager.verify_health().await.is_ok());
+        assert_eq!(manager.state().await, UpdateState::Idle);
+    }
+
+    #[test]
+    fn test_phase_progress_overall() {
+        let first = UpdatePhaseProgress::new(UpdatePhase::BackingUp, 0.0);
+        assert_eq!(first.overall(), 0.0);
+        assert_eq!(first.label, "Backing up");
+
+        let halfway = UpdatePhaseProgress::new(UpdatePhase::Verifying, 0.5);
+        assert!((halfway.overall() - 0.5).abs() < f32::EPSILON);
+
+        let last = UpdatePhaseProgress::new(UpdatePhase::VerifyingHealth, 2.0);
+        assert_eq!(last.fraction, 1.0);
+        assert_eq!(last.overall(), 1.0);
+    }
+
+    #[test]
+    fn test_download_progress_is_forwarded() {
+        let (tx, mut rx) = broadcast::channel(8);
+        let forward = forward_download_progress(tx);
+
+        forward(DownloadProgress::new(0, 0));
+        forward(DownloadProgress::new(25, 100));
+
+        let event = rx.try_recv().unwrap();
+        assert_eq!(event.phase, UpdatePhase::Downloading);
+        assert!((event.fraction - 0.25).abs() < f32::EPSILON);
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_instant_phase_emits_start_and_end() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = UpdateConfig::default();
+        config.rollback.backup_dir = Some(temp_dir.path().join("backups"));
+        let manager = UpdateManager::new(
+            config,
+            Version::new(1, 0, 0),
+            temp_dir.path().join("downloads"),
+            temp_dir.path().join("channel.json"),
+        )
+        .unwrap();
+        let mut rx = manager.subscribe_progress();
+
+        // No health check configured: the phase still starts and ends
+        manager.verify_health().await.unwrap();
+        let start = rx.try_recv().unwrap();
+        let end = rx.try_recv().unwrap();
+        assert_eq!(start, UpdatePhaseProgress::new(UpdatePhase::VerifyingHealth, 0.0));
+        assert_eq!(end, UpdatePhaseProgress::new(UpdatePhase::VerifyingHealth, 1.0));
+    }
+
+    #[tokio::test]
+    async fn test_signature_rejection_is_quarantined() {
+        use crate::manifest::UpdateManifest;
+
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = UpdateConfig::default();
+        config.rollback.backup_dir = Some(temp_dir.path().join("backups"));
+        let manager = UpdateManager::new(
+            config,
+            Version::new(1, 0, 0),
+            temp_dir.path().join("downloads"),
+            temp_dir.path().join("channel.json"),
+        )
+        .unwrap();
+        let manifest = UpdateManifest::new(
+            Version::new(2, 0, 0),
+            "windows-x86_64".to_string(),
+            UpdateChannel::Stable,
+            "https://example.com/update.zip".to_string(),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855".to_string(),
+            1024,
+            "Test release notes".to_string(),
+            false,
+            None,
+        );
+        let info = UpdateInfo::from_manifest(&manifest).unwrap();
+        assert!(manager.check_quarantine(&info).is_ok());
+
+        // An installer's Authenticode failure rejects the artifact itself
+        let failure = UpdateError::CodeSignatureInvalid("stub unsigned".to_string());
+        assert!(is_artifact_rejection(&failure));
+        let artifact = temp_dir.path().join("update.zip");
+        std::fs::write(&artifact, b"unsigned").unwrap();
+        manager.reject_artifact(&artifact, &info, &failure);
+
+        assert!(!artifact.exists());
+        assert_eq!(manager.list_quarantine().unwrap().len(), 1);
+        assert!(matches!(
+            manager.install_update(&info).await,
+            Err(UpdateError::ArtifactQuarantined { .. })
+        ));
+    }
 }
@@ -47,6 +47,9 @@ pub struct ManifestVerifier {
     /// Pinned public keys for manifest signing.
     /// Multiple keys support key rotation (Requirement 1.6).
     trusted_keys: Vec<VerifyingKey>,
+    /// Expiry (unix seconds, grace window included) for each trusted key,
+    /// in the same order as `trusted_keys`. `None` never expires.
+    key_expiry: Vec<Option<u64>>,
     /// Minimum required valid signatures.
     /// Must be at least 1 for security.
     threshold: usize,
@@ -69,6 +72,7 @@ impl ManifestVerifier {
     pub fn new(trusted_keys: Vec<VerifyingKey>, threshold: usize) -> Self {
         assert!(threshold > 0, "signature threshold must be at least 1");
         Self {
+            key_expiry: vec![None; trusted_keys.len()],
             trusted_keys,
             threshold,
             expected_platform: current_platform(),
@@ -85,12 +89,50 @@ impl ManifestVerifier {
     ) -> Self {
         assert!(threshold > 0, "signature threshold must be at least 1");
         Self {
+            key_expiry: vec![None; trusted_keys.len()],
             trusted_keys,
             threshold,
             expected_platform,
         }
     }
 
+    /// Create a verifier from pinned keys that may expire (Requirement 1.6).
+    ///
+    /// A key is accepted until its `not_after` plus `grace_secs`; after that,
+    /// signatures made with it no longer count towards the threshold. During a
+    /// rotation manifests can be co-signed by the outgoing and incoming keys.
+    /// A key listed more than once is trusted once, until its earliest expiry.
+    ///
+    /// # Panics
+    ///
+    /// Panics if threshold is 0 (would allow unsigned manifests).
+    pub fn with_pinned_keys(pinned_keys: Vec<PinnedKey>, threshold: usize, grace_secs: u64) -> Self {
+        assert!(threshold > 0, "signature threshold must be at least 1");
+        let mut trusted_keys: Vec<VerifyingKey> = Vec::new();
+        let mut key_expiry: Vec<Option<u64>> = Vec::new();
+        for pinned in pinned_keys {
+            let expiry = pinned.not_after.map(|t| t.saturating_add(grace_secs));
+            match trusted_keys.iter().position(|key| *key == pinned.key) {
+                Some(i) => {
+                    key_expiry[i] = match (key_expiry[i], expiry) {
+                        (Some(a), Some(b)) => Some(a.min(b)),
+                        (a, b) => a.or(b),
+                    }
+                }
+                None => {
+                    trusted_keys.push(pinned.key);
+                    key_expiry.push(expiry);
+                }
+            }
+        }
+        Self {
+            trusted_keys,
+            key_expiry,
+            threshold,
+            expected_platform: current_platform(),
+        }
+    }
+
     /// Get the trusted public keys.
     pub fn trusted_keys(&self) -> &[VerifyingKey] {
         &self.trusted_keys
@@ -133,6 +175,36 @@ impl ManifestVerifier {
     /// - Insufficient valid signatures
     /// - Platform mismatch
     pub fn verify_and_parse(&self, data: &[u8]) -> Result<UpdateManifest, UpdateError> {
+        let manifest = self.verify_signatures(data)?.manifest;
+
+        // Verify platform matches (Requirement 1.5)
+        if manifest.platform != self.expected_platform {
+            tracing::error!(
+                expected = %self.expected_platform,
+                actual = %manifest.platform,
+                "Platform mismatch in manifest"
+            );
+            return Err(UpdateError::PlatformMismatch {
+                expected: self.expected_platform.clone(),
+                actual: manifest.platform.clone(),
+            });
+        }
+
+        tracing::info!(
+            version = %manifest.version,
+            platform = %manifest.platform,
+            channel = %manifest.channel,
+            "Manifest verified successfully"
+        );
+
+        Ok(manifest)
+    }
+
+    /// Verify the manifest timestamp and signatures, without the platform check.
+    ///
+    /// Used to audit manifests for other platforms; anything that installs
+    /// must use [`verify_and_parse`](Self::verify_and_parse).
+    pub fn verify_signatures(&self, data: &[u8]) -> Result<VerifiedManifest, UpdateError> {
         // Parse the signed manifest envelope
         let signed_manifest: SignedManifest = serde_json::from_slice(data)?;
 
@@ -140,7 +212,19 @@ impl ManifestVerifier {
         self.verify_timestamp(signed_manifest.timestamp)?;
 
         // Verify signatures (Requirements 1.1, 1.2, 1.6)
-        let valid_signatures = self.count_valid_signatures(&signed_manifest)?;
+        let (signer_key_ids, expired_signers) =
+            self.count_valid_signatures(&signed_manifest, unix_now()?)?;
+        let valid_signatures = signer_key_ids.len();
+        if valid_signatures == 0 && !expired_signers.is_empty() {
+            tracing::error!(
+                keys = %expired_signers.join(", "),
+                "Manifest signed only by expired keys"
+            );
+            return Err(UpdateError::SignatureVerificationFailed(format!(
+                "manifest signed only by expired key(s): {}",
+                expired_signers.join(", ")
+            )));
+        }
         if valid_signatures < self.threshold {
             tracing::error!(
                 required = self.threshold,
@@ -162,27 +246,12 @@ impl ManifestVerifier {
         // Parse the inner manifest
         let manifest: UpdateManifest = serde_json::from_str(&signed_manifest.manifest)?;
 
-        // Verify platform matches (Requirement 1.5)
-        if manifest.platform != self.expected_platform {
-            tracing::error!(
-                expected = %self.expected_platform,
-                actual = %manifest.platform,
-                "Platform mismatch in manifest"
-            );
-            return Err(UpdateError::PlatformMismatch {
-                expected: self.expected_platform.clone(),
-                actual: manifest.platform.clone(),
-            });
-        }
-
-        tracing::info!(
-            version = %manifest.version,
-            platform = %manifest.platform,
-            channel = %manifest.channel,
-            "Manifest verified successfully"
-        );
-
-        Ok(manifest)
+        Ok(VerifiedManifest {
+            manifest,
+            signer_key_ids,
+            signed_at: signed_manifest.timestamp,
+            expires_at: signed_manifest.timestamp.saturating_add(MAX_MANIFEST_AGE_SECS),
+        })
     }
 
     /// Verify the manifest timestamp is within acceptable bounds.
@@ -190,10 +259,7 @@ impl ManifestVerifier {
     /// - Must not be older than 7 days (Requirement 1.3)
     /// - Must not be more than 1 hour in the future (clock skew tolerance)
     fn verify_timestamp(&self, timestamp: u64) -> Result<(), UpdateError> {
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .map_err(|e| UpdateError::ConfigError(format!("system time error: {}", e)))?
-            .as_secs();
+        let now = unix_now()?;
 
         // Check if manifest is too old
         if timestamp < now.saturating_sub(MAX_MANIFEST_AGE_SECS) {
@@ -224,10 +290,19 @@ impl ManifestVerifier {
     ///
     /// A signature is valid if:
     /// - It can be verified against one of the trusted keys
+    /// - That key has not expired at `now` (grace window included)
     /// - Each key can only validate one signature (no double-counting)
-    fn count_valid_signatures(&self, signed_manifest: &SignedManifest) -> Result<usize, UpdateError> {
+    ///
+    /// Returns the key ids of the valid signatures and of signatures made by
+    /// expired keys.
+    fn count_valid_signatures(
+        &self,
+        signed_manifest: &SignedManifest,
+        now: u64,
+    ) -> Result<(Vec<String>, Vec<String>), UpdateError> {
         let manifest_bytes = signed_manifest.manifest.as_bytes();
-        let mut valid_count = 0;
+        let mut valid_signers = Vec::new();
+        let mut expired_signers = Vec::new();
         let mut used_keys = vec![false; self.trusted_keys.len()];
 
         for sig in &signed_manifest.signatures {
@@ -238,8 +313,16 @@ impl ManifestVerifier {
                 }
 
                 if key.verify(manifest_bytes, &sig.signature).is_ok() {
-                    valid_count += 1;
                     used_keys[i] = true;
+                    if self.key_expiry[i].is_some_and(|expiry| now > expiry) {
+                        tracing::warn!(
+                            key_id = %sig.key_id,
+                            "Ignoring signature from expired key"
+                        );
+                        expired_signers.push(sig.key_id.clone());
+                        break;
+                    }
+                    valid_signers.push(sig.key_id.clone());
                     tracing::debug!(
                         key_id = %sig.key_id,
                         "Valid signature found"
@@ -249,10 +332,40 @@ impl ManifestVerifier {
             }
         }
 
-        Ok(valid_count)
+        Ok((valid_signers, expired_signers))
     }
 }
 
+/// A manifest whose timestamp and signatures have been verified.
+#[derive(Debug, Clone)]
+pub struct VerifiedManifest {
+    /// The parsed manifest (platform not yet checked)
+    pub manifest: UpdateManifest,
+    /// Key ids of the signatures that verified against active pinned keys
+    pub signer_key_ids: Vec<String>,
+    /// Unix timestamp when the manifest was signed
+    pub signed_at: u64,
+    /// Unix timestamp after which the manifest is rejected as too old
+    pub expires_at: u64,
+}
+
+/// A pinned manifest signing key with an optional expiry.
+#[derive(Debug, Clone)]
+pub struct PinnedKey {
+    /// Ed25519 public key
+    pub key: VerifyingKey,
+    /// Unix timestamp after which the key is retired (before grace)
+    pub not_after: Option<u64>,
+}
+
+/// Current unix time in seconds.
+fn unix_now() -> Result<u64, UpdateError> {
+    Ok(SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| UpdateError::ConfigError(format!("system time error: {}", e)))?
+        .as_secs())
+}
+
 /// A signed update manifest with signatures and timestamp.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SignedManifest {
@@ -339,6 +452,12 @@ pub struct UpdateManifest {
     pub is_security_update: bool,
     /// Minimum version required for delta update (if applicable)
     pub min_version: Option<Version>,
+    /// Binary diff against an earlier release (if published)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub patch: Option<PatchArtifact>,
+    /// Staged rollout gate (absent = offered to every device)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rollout: Option<Rollout>,
 }
 
 impl UpdateManifest {
@@ -365,9 +484,23 @@ impl UpdateManifest {
             release_notes,
             is_security_update,
             min_version,
+            patch: None,
+            rollout: None,
         }
     }
 
+    /// Attach a delta patch artifact.
+    pub fn with_patch(mut self, patch: PatchArtifact) -> Self {
+        self.patch = Some(patch);
+        self
+    }
+
+    /// Limit the release to a percentage of devices.
+    pub fn with_rollout(mut self, rollout: Rollout) -> Self {
+        self.rollout = Some(rollout);
+        self
+    }
+
     /// Check if this is a security update.
     pub fn is_security_update(&self) -> bool {
         self.is_security_update
@@ -382,6 +515,49 @@ impl UpdateManifest {
     }
 }
 
+/// Staged rollout of a release to a percentage of devices.
+///
+/// Devices are bucketed by hashing their id with `salt`, so membership is
+/// stable across checks and a fresh salt reshuffles the cohort.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rollout {
+    /// Percentage of devices offered the update (0-100)
+    pub percentage: u8,
+    /// Per-release salt mixed into the device hash
+    pub salt: String,
+}
+
+/// A bsdiff patch that rebuilds a release from an earlier installed version.
+///
+/// The reconstructed artifact is verified against the manifest's full
+/// `artifact_hash`, so a patch never bypasses artifact verification.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PatchArtifact {
+    /// Installed version the patch applies to
+    pub patch_from: Version,
+    /// URL to download the patch
+    pub url: String,
+    /// SHA-256 hash of the patch file (hex encoded)
+    pub hash: String,
+    /// Size of the patch in bytes
+    pub size: u64,
+}
+
+impl PatchArtifact {
+    /// Get the patch hash as bytes.
+    ///
+    /// Returns None if the hash is not valid hex.
+    pub fn hash_bytes(&self) -> Option<[u8; 32]> {
+        let bytes = hex::decode(&self.hash).ok()?;
+        bytes.try_into().ok()
+    }
+
+    /// Whether the patch can be applied on top of `installed`.
+    pub fn applies_to(&self, installed: &Version) -> bool {
+        self.patch_from == *installed
+    }
+}
+
 /// Get the current platform string.
 ///
 /// Returns a string like "windows-x86_64", "macos-aarch64", "linux-x86_64".
@@ -780,6 +956,99 @@ mod tests {
         assert!(matches!(result, Err(UpdateError::InsufficientSignatures { required: 2, found: 1 })));
     }
 
+    #[test]
+    fn test_pinned_key_single_active() {
+        let (signing_key, verifying_key) = create_test_keypair();
+        let manifest_json = create_test_manifest_json(&current_platform());
+        let signed_manifest =
+            create_signed_manifest(&manifest_json, &[(signing_key, "key1")], current_timestamp());
+        let signed_data = serde_json::to_vec(&signed_manifest).unwrap();
+
+        let pinned = vec![PinnedKey { key: verifying_key, not_after: None }];
+        let verifier = ManifestVerifier::with_pinned_keys(pinned, 1, 0);
+        assert!(verifier.verify_and_parse(&signed_data).is_ok());
+    }
+
+    #[test]
+    fn test_pinned_key_rotation_dual_signed() {
+        let (old_signing, old_verifying) = create_test_keypair();
+        let (new_signing, new_verifying) = create_test_keypair_2();
+        let now = current_timestamp();
+        let manifest_json = create_test_manifest_json(&current_platform());
+        let signed_manifest = create_signed_manifest(
+            &manifest_json,
+            &[(old_signing, "old"), (new_signing, "new")],
+            now,
+        );
+        let signed_data = serde_json::to_vec(&signed_manifest).unwrap();
+
+        // Old key retires soon, both still active: 2-of-2 passes
+        let pinned = vec![
+            PinnedKey { key: old_verifying, not_after: Some(now + 3600) },
+            PinnedKey { key: new_verifying, not_after: None },
+        ];
+        let verifier = ManifestVerifier::with_pinned_keys(pinned.clone(), 2, 0);
+        assert!(verifier.verify_and_parse(&signed_data).is_ok());
+
+        // Once the old key expires only the new signature counts
+        let mut retired = pinned;
+        retired[0].not_after = Some(now - 3600);
+        let verifier = ManifestVerifier::with_pinned_keys(retired.clone(), 2, 0);
+        assert!(matches!(
+            verifier.verify_and_parse(&signed_data),
+            Err(UpdateError::InsufficientSignatures { required: 2, found: 1 })
+        ));
+        let verifier = ManifestVerifier::with_pinned_keys(retired, 1, 0);
+        assert!(verifier.verify_and_parse(&signed_data).is_ok());
+    }
+
+    #[test]
+    fn test_pinned_key_expired_only_rejected() {
+        let (signing_key, verifying_key) = create_test_keypair();
+        let (_, other_verifying) = create_test_keypair_2();
+        let now = current_timestamp();
+        let manifest_json = create_test_manifest_json(&current_platform());
+        let signed_manifest =
+            create_signed_manifest(&manifest_json, &[(signing_key, "old")], now);
+        let signed_data = serde_json::to_vec(&signed_manifest).unwrap();
+
+        let pinned = vec![
+            PinnedKey { key: verifying_key, not_after: Some(now - 7200) },
+            PinnedKey { key: other_verifying, not_after: None },
+        ];
+        let verifier = ManifestVerifier::with_pinned_keys(pinned.clone(), 1, 3600);
+        assert!(matches!(
+            verifier.verify_and_parse(&signed_data),
+            Err(UpdateError::SignatureVerificationFailed(_))
+        ));
+
+        // Still inside the grace window
+        let verifier = ManifestVerifier::with_pinned_keys(pinned, 1, 3 * 3600);
+        assert!(verifier.verify_and_parse(&signed_data).is_ok());
+    }
+
+    #[test]
+    fn test_duplicate_pinned_key_keeps_expiry() {
+        let (signing_key, verifying_key) = create_test_keypair();
+        let now = current_timestamp();
+        let manifest_json = create_test_manifest_json(&current_platform());
+        let signed_manifest =
+            create_signed_manifest(&manifest_json, &[(signing_key, "old")], now);
+        let signed_data = serde_json::to_vec(&signed_manifest).unwrap();
+
+        // Listed both as a permanent key and as an expired pinned key
+        let pinned = vec![
+            PinnedKey { key: verifying_key, not_after: None },
+            PinnedKey { key: verifying_key, not_after: Some(now - 7200) },
+        ];
+        let verifier = ManifestVerifier::with_pinned_keys(pinned, 1, 3600);
+        assert_eq!(verifier.trusted_keys().len(), 1);
+        assert!(matches!(
+            verifier.verify_and_parse(&signed_data),
+            Err(UpdateError::SignatureVerificationFailed(_))
+        ));
+    }
+
     #[test]
     fn test_current_platform() {
         let platform = current_platform();
@@ -14,10 +14,19 @@
 //! - Requirement 11.6: Not spam notifications
 //! - Requirement 11.7: Indicate update urgency (security vs feature)
 //! - Requirement 11.8: Provide "remind me later" option
+//!
+//! # Snoozing
+//!
+//! A [`NotificationResponse::Snooze`] reschedules the prompt exactly the
+//! chosen duration out, which must be one of
+//! [`NotificationConfig::snooze_options`]. The deadline is persisted with the
+//! rest of [`NotificationState`], so a restart does not re-prompt early. After
+//! [`NotificationConfig::max_snoozes`] snoozes of the same version it is
+//! escalated to [`UpdateUrgency::Critical`] and can no longer be deferred.
 
 use std::path::PathBuf;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use chrono::{DateTime, Utc};
 use semver::Version;
@@ -107,7 +116,14 @@ impl NotificationContent {
     /// - Requirement 11.2: Show update version and release notes summary
     /// - Requirement 11.7: Indicate update urgency
     pub fn from_update_info(info: &UpdateInfo) -> Self {
-        let urgency = UpdateUrgency::from_update_info(info);
+        Self::with_urgency(info, UpdateUrgency::from_update_info(info))
+    }
+
+    /// Create notification content for update info at a given urgency.
+    ///
+    /// Used when the urgency has been escalated beyond what the update
+    /// info alone implies, e.g. after too many snoozes.
+    pub fn with_urgency(info: &UpdateInfo, urgency: UpdateUrgency) -> Self {
         let title = Self::format_title(&info.version, urgency);
         let body = Self::format_body(info, urgency);
         let release_notes_summary = Self::summarize_release_notes(&info.release_notes);
@@ -178,6 +194,8 @@ pub enum NotificationResponse {
     Install,
     /// User chose to defer/remind later
     RemindLater,
+    /// User chose to be reminded after one of the configured snooze options
+    Snooze(Duration),
     /// User dismissed the notification
     Dismissed,
     /// Notification timed out
@@ -216,6 +234,14 @@ pub struct NotificationConfig {
     /// Always show critical security updates regardless of other settings
     #[serde(default = "default_true")]
     pub always_show_critical: bool,
+
+    /// Snooze durations offered to the user (configured in seconds)
+    #[serde(default = "default_snooze_options", with = "duration_secs")]
+    pub snooze_options: Vec<Duration>,
+
+    /// Snoozes allowed per version before it becomes critical
+    #[serde(default = "default_max_snoozes")]
+    pub max_snoozes: u32,
 }
 
 impl Default for NotificationConfig {
@@ -228,6 +254,8 @@ impl Default for NotificationConfig {
             max_reminders: default_max_reminders(),
             show_feature_updates: true,
             always_show_critical: true,
+            snooze_options: default_snooze_options(),
+            max_snoozes: default_max_snoozes(),
         }
     }
 }
@@ -248,6 +276,33 @@ fn default_max_reminders() -> u32 {
     5 // Maximum 5 reminders before giving up
 }
 
+fn default_snooze_options() -> Vec<Duration> {
+    // 1 hour, 4 hours, 1 day
+    [3600, 4 * 3600, 24 * 3600].map(Duration::from_secs).to_vec()
+}
+
+fn default_max_snoozes() -> u32 {
+    3
+}
+
+/// Serialize durations as whole seconds.
+mod duration_secs {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &[Duration], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(value.iter().map(Duration::as_secs))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Duration>, D::Error> {
+        Ok(Vec::<u64>::deserialize(deserializer)?
+            .into_iter()
+            .map(Duration::from_secs)
+            .collect())
+    }
+}
+
 /// State for tracking deferred notifications.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DeferredUpdate {
@@ -259,6 +314,9 @@ pub struct DeferredUpdate {
     pub remind_at: DateTime<Utc>,
     /// Number of times reminded
     pub reminder_count: u32,
+    /// Number of times snoozed
+    #[serde(default)]
+    pub snooze_count: u32,
     /// Whether user chose to skip this version
     pub skipped: bool,
 }
@@ -326,11 +384,46 @@ impl NotificationState {
                 first_seen: now,
                 remind_at,
                 reminder_count: 1,
+                snooze_count: 0,
+                skipped: false,
+            });
+        }
+    }
+
+    /// Snooze a version until `duration` from now.
+    pub fn snooze(&mut self, version: Version, duration: Duration) {
+        let now = Utc::now();
+        let remind_at = chrono::Duration::from_std(duration)
+            .ok()
+            .and_then(|d| now.checked_add_signed(d))
+            .unwrap_or(DateTime::<Utc>::MAX_UTC);
+
+        if let Some(existing) = self.deferred.iter_mut().find(|d| d.version == version) {
+            existing.remind_at = remind_at;
+            existing.snooze_count += 1;
+        } else {
+            self.deferred.push(DeferredUpdate {
+                version,
+                first_seen: now,
+                remind_at,
+                reminder_count: 0,
+                snooze_count: 1,
                 skipped: false,
             });
         }
     }
 
+    /// Check if a version is snoozed and the snooze has not yet elapsed.
+    pub fn is_snoozed(&self, version: &Version) -> bool {
+        self.get_deferred(version)
+            .is_some_and(|d| d.snooze_count > 0 && !d.skipped && d.remind_at > Utc::now())
+    }
+
+    /// Number of times a version has been snoozed.
+    pub fn snooze_count(&self, version: &Version) -> u32 {
+        self.get_deferred(version).map_or(0, |d| d.snooze_count)
+    }
+
     /// Skip a version (don't notify again).
     pub fn skip_version(&mut self, version: Version) {
         if !self.skipped_versions.contains(&version) {
@@ -435,7 +528,13 @@ impl NotificationManager {
             return false;
         }
 
-        let urgency = UpdateUrgency::from_update_info(info);
+        // An explicit snooze is honoured even once escalated to critical
+        if self.state.read().await.is_snoozed(&info.version) {
+            debug!("Version {} is snoozed", info.version);
+            return false;
+        }
+
+        let urgency = self.effective_urgency(info).await;
 
         // Critical updates always show (if configured)
         if urgency == UpdateUrgency::Critical && self.config.always_show_critical {
@@ -456,24 +555,28 @@ impl NotificationManager {
 
         let state = self.state.read().await;
 
-        // Check if version is skipped
-        if state.is_skipped(&info.version) {
-            debug!("Version {} is skipped", info.version);
-            return false;
-        }
-
-        // Check if version is deferred and not yet due
-        if state.is_deferred(&info.version) {
-            debug!("Version {} is deferred", info.version);
-            return false;
-        }
+        // Skips and "remind later" recorded before an escalation to critical
+        // no longer apply
+        if urgency != UpdateUrgency::Critical {
+            // Check if version is skipped
+            if state.is_skipped(&info.version) {
+                debug!("Version {} is skipped", info.version);
+                return false;
+            }
 
-        // Check reminder count
-        if let Some(deferred) = state.get_deferred(&info.version) {
-            if deferred.reminder_count >= self.config.max_reminders {
-                debug!("Max reminders reached for version {}", info.version);
+            // Check if version is deferred and not yet due
+            if state.is_deferred(&info.version) {
+                debug!("Version {} is deferred", info.version);
                 return false;
             }
+
+            // Check reminder count
+            if let Some(deferred) = state.get_deferred(&info.version) {
+                if deferred.reminder_count >= self.config.max_reminders {
+                    debug!("Max reminders reached for version {}", info.version);
+                    return false;
+                }
+            }
         }
 
         // Check notification interval (rate limiting)
@@ -499,7 +602,7 @@ impl NotificationManager {
             return Ok(NotificationResponse::Dismissed);
         }
 
-        let content = NotificationContent::from_update_info(info);
+        let content = NotificationContent::with_urgency(info, self.effective_urgency(info).await);
         info!(
             "Showing update notification for version {} (urgency: {:?})",
             info.version, content.urgency
@@ -537,6 +640,71 @@ impl NotificationManager {
         Ok(())
     }
 
+    /// Urgency of an update, escalated to critical once it has been
+    /// snoozed `max_snoozes` times.
+    pub async fn effective_urgency(&self, info: &UpdateInfo) -> UpdateUrgency {
+        let snoozes = self.state.read().await.snooze_count(&info.version);
+        if snoozes >= self.config.max_snoozes {
+            UpdateUrgency::Critical
+        } else {
+            UpdateUrgency::from_update_info(info)
+        }
+    }
+
+    /// Handle user response to snooze the update.
+    ///
+    /// The prompt is rescheduled exactly `duration` from now. Fails if
+    /// `duration` is not one of the configured snooze options or the update
+    /// is critical (including after `max_snoozes` snoozes).
+    pub async fn snooze(&self, info: &UpdateInfo, duration: Duration) -> Result<(), UpdateError> {
+        if !self.config.snooze_options.contains(&duration) {
+            return Err(UpdateError::SnoozeRefused(format!(
+                "{:?} is not a configured snooze option",
+                duration
+            )));
+        }
+        if self.effective_urgency(info).await == UpdateUrgency::Critical {
+            return Err(UpdateError::SnoozeRefused(format!(
+                "critical update {} cannot be deferred",
+                info.version
+            )));
+        }
+
+        let mut state = self.state.write().await;
+        state.snooze(info.version.clone(), duration);
+        self.save_state(&state)?;
+        info!("Update {} snoozed for {:?}", info.version, duration);
+        Ok(())
+    }
+
+    /// Apply the user's response to a notification.
+    ///
+    /// Installation itself is left to the caller; an `Install` response
+    /// only clears deferral state. `RemindLater` and `SkipVersion` are
+    /// ignored for critical updates, like snoozing.
+    pub async fn handle_response(
+        &self,
+        info: &UpdateInfo,
+        response: NotificationResponse,
+    ) -> Result<(), UpdateError> {
+        let deferral = matches!(
+            response,
+            NotificationResponse::RemindLater | NotificationResponse::SkipVersion
+        );
+        if deferral && self.effective_urgency(info).await == UpdateUrgency::Critical {
+            warn!("Ignoring {:?} for critical update {}", response, info.version);
+            return Ok(());
+        }
+
+        match response {
+            NotificationResponse::Install => self.clear_deferred(&info.version).await,
+            NotificationResponse::RemindLater => self.defer_update(&info.version).await,
+            NotificationResponse::Snooze(duration) => self.snooze(info, duration).await,
+            NotificationResponse::SkipVersion => self.skip_version(&info.version).await,
+            NotificationResponse::Dismissed | NotificationResponse::TimedOut => Ok(()),
+        }
+    }
+
     /// Handle user response to skip this version.
     pub async fn skip_version(&self, version: &Version) -> Result<(), UpdateError> {
         let mut state = self.state.write().await;
@@ -966,6 +1134,7 @@ mod tests {
             expected_hash: [0u8; 32],
             artifact_url: "https://example.com/update.zip".to_string(),
             channel: crate::channel::UpdateChannel::Stable,
+            patch: None,
         }
     }
 
@@ -1032,6 +1201,119 @@ mod tests {
         assert_eq!(config.max_reminders, 5);
         assert!(config.show_feature_updates);
         assert!(config.always_show_critical);
+        assert_eq!(config.snooze_options.len(), 3);
+        assert_eq!(config.max_snoozes, 3);
+    }
+
+    #[test]
+    fn test_notification_config_snooze_options_in_seconds() {
+        let config: NotificationConfig =
+            serde_json::from_str(r#"{"snooze_options": [600, 7200], "max_snoozes": 1}"#).unwrap();
+        assert_eq!(
+            config.snooze_options,
+            vec![Duration::from_secs(600), Duration::from_secs(7200)]
+        );
+        assert_eq!(config.max_snoozes, 1);
+    }
+
+    fn snooze_test_manager(state_path: PathBuf, max_snoozes: u32) -> NotificationManager {
+        let config = NotificationConfig {
+            min_interval_hours: 0,
+            max_snoozes,
+            ..NotificationConfig::default()
+        };
+        NotificationManager::new(config, state_path, Box::new(StubNotificationBackend)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_snooze_persists_across_restart() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let state_path = temp_dir.path().join("notifications.json");
+        let info = create_test_update_info(false, "Bug fixes");
+        let hour = Duration::from_secs(3600);
+
+        let manager = snooze_test_manager(state_path.clone(), 3);
+        assert!(manager.should_notify(&info).await);
+        manager
+            .handle_response(&info, NotificationResponse::Snooze(hour))
+            .await
+            .unwrap();
+        drop(manager);
+
+        // A new manager loads the persisted deadline and stays quiet
+        let restarted = snooze_test_manager(state_path, 3);
+        assert!(!restarted.should_notify(&info).await);
+        let deferred = restarted.state().await.get_deferred(&info.version).cloned().unwrap();
+        assert_eq!(deferred.snooze_count, 1);
+        let remaining = deferred.remind_at - Utc::now();
+        assert!(remaining > chrono::Duration::minutes(59));
+        assert!(remaining <= chrono::Duration::hours(1));
+    }
+
+    #[tokio::test]
+    async fn test_snooze_rejects_unconfigured_duration() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let manager = snooze_test_manager(temp_dir.path().join("notifications.json"), 3);
+        let info = create_test_update_info(false, "Bug fixes");
+
+        let result = manager.snooze(&info, Duration::from_secs(365 * 24 * 3600)).await;
+        assert!(matches!(result, Err(UpdateError::SnoozeRefused(_))));
+        assert!(manager.state().await.get_deferred(&info.version).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_snooze_limit_escalates_to_critical() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let manager = snooze_test_manager(temp_dir.path().join("notifications.json"), 2);
+        let info = create_test_update_info(false, "Bug fixes");
+        let hour = Duration::from_secs(3600);
+
+        manager.snooze(&info, hour).await.unwrap();
+        assert_eq!(manager.effective_urgency(&info).await, UpdateUrgency::Normal);
+        manager.snooze(&info, hour).await.unwrap();
+        assert_eq!(manager.effective_urgency(&info).await, UpdateUrgency::Critical);
+
+        let result = manager.snooze(&info, hour).await;
+        assert!(matches!(result, Err(UpdateError::SnoozeRefused(_))));
+        assert_eq!(manager.state().await.snooze_count(&info.version), 2);
+
+        // Once the last snooze elapses the prompt returns, as critical
+        {
+            let mut state = manager.state.write().await;
+            state.deferred[0].remind_at = Utc::now() - chrono::Duration::seconds(1);
+        }
+        assert!(manager.should_notify(&info).await);
+        let content = NotificationContent::with_urgency(&info, manager.effective_urgency(&info).await);
+        assert_eq!(content.urgency, UpdateUrgency::Critical);
+    }
+
+    #[tokio::test]
+    async fn test_critical_update_cannot_be_skipped_or_deferred() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let config = NotificationConfig {
+            min_interval_hours: 0,
+            always_show_critical: false,
+            ..NotificationConfig::default()
+        };
+        let manager = NotificationManager::new(
+            config,
+            temp_dir.path().join("notifications.json"),
+            Box::new(StubNotificationBackend),
+        )
+        .unwrap();
+        let info = create_test_update_info(true, "Fixes a critical remote code execution bug");
+        assert_eq!(manager.effective_urgency(&info).await, UpdateUrgency::Critical);
+
+        for response in [NotificationResponse::RemindLater, NotificationResponse::SkipVersion] {
+            manager.handle_response(&info, response).await.unwrap();
+        }
+        let state = manager.state().await;
+        assert!(state.skipped_versions.is_empty());
+        assert!(state.deferred.is_empty());
+
+        // A skip recorded before the update became critical is ignored too
+        manager.state.write().await.skip_version(info.version.clone());
+        assert!(manager.should_notify(&info).await);
     }
 
     #[test]
@@ -17,6 +17,7 @@ use std::fs::{self, File};
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 
+use chrono::{DateTime, Utc};
 use semver::Version;
 use sha2::{Digest, Sha256};
 use serde::{Deserialize, Serialize};
@@ -34,6 +35,12 @@ const PACKAGE_MAGIC: &[u8; 4] = b"ZRCU";
 /// Current package format version.
 const PACKAGE_VERSION: u8 = 1;
 
+/// Header size: magic + version + manifest length.
+const HEADER_SIZE: u64 = 4 + 1 + 4;
+
+/// Largest signed manifest accepted in a package.
+const MAX_MANIFEST_SIZE: u64 = 10 * 1024 * 1024;
+
 /// Offline update package containing manifest and artifact.
 ///
 /// The package format is:
@@ -168,51 +175,7 @@ impl OfflineUpdateManager {
 
         // Read the package file
         let mut file = File::open(path)?;
-        let metadata = file.metadata()?;
-        let file_size = metadata.len();
-
-        // Verify minimum size (magic + version + manifest length)
-        if file_size < 9 {
-            return Err(UpdateError::ConfigError(
-                "Invalid update package: file too small".to_string(),
-            ));
-        }
-
-        // Read and verify magic bytes
-        let mut magic = [0u8; 4];
-        file.read_exact(&mut magic)?;
-        if &magic != PACKAGE_MAGIC {
-            return Err(UpdateError::ConfigError(
-                "Invalid update package: wrong magic bytes".to_string(),
-            ));
-        }
-
-        // Read and verify version
-        let mut version = [0u8; 1];
-        file.read_exact(&mut version)?;
-        if version[0] != PACKAGE_VERSION {
-            return Err(UpdateError::ConfigError(format!(
-                "Unsupported package version: {} (expected {})",
-                version[0], PACKAGE_VERSION
-            )));
-        }
-
-        // Read manifest length
-        let mut manifest_len_bytes = [0u8; 4];
-        file.read_exact(&mut manifest_len_bytes)?;
-        let manifest_len = u32::from_be_bytes(manifest_len_bytes) as usize;
-
-        // Sanity check manifest length
-        if manifest_len > 10 * 1024 * 1024 {
-            // 10MB max for manifest
-            return Err(UpdateError::ConfigError(
-                "Invalid update package: manifest too large".to_string(),
-            ));
-        }
-
-        // Read manifest data
-        let mut manifest_data = vec![0u8; manifest_len];
-        file.read_exact(&mut manifest_data)?;
+        let manifest_data = read_package_manifest(&mut file)?;
 
         // Verify and parse manifest (Requirement 10.6)
         debug!("Verifying manifest signature...");
@@ -387,110 +350,23 @@ impl OfflineUpdateManager {
     ///
     /// # Errors
     ///
-    /// Returns an error if the package is invalid or verification fails.
+    /// Returns an error if the package is invalid, verification fails, or
+    /// the package is for another platform.
     pub fn verify_update_file(&self, path: &Path) -> Result<OfflineUpdateInfo, UpdateError> {
         info!("Verifying offline update file: {:?}", path);
 
-        let mut file = File::open(path)?;
-        let metadata = file.metadata()?;
-        let file_size = metadata.len();
-
-        // Verify minimum size
-        if file_size < 9 {
-            return Err(UpdateError::ConfigError(
-                "Invalid update package: file too small".to_string(),
-            ));
-        }
-
-        // Read and verify magic bytes
-        let mut magic = [0u8; 4];
-        file.read_exact(&mut magic)?;
-        if &magic != PACKAGE_MAGIC {
-            return Err(UpdateError::ConfigError(
-                "Invalid update package: wrong magic bytes".to_string(),
-            ));
-        }
-
-        // Read and verify version
-        let mut version = [0u8; 1];
-        file.read_exact(&mut version)?;
-        if version[0] != PACKAGE_VERSION {
-            return Err(UpdateError::ConfigError(format!(
-                "Unsupported package version: {} (expected {})",
-                version[0], PACKAGE_VERSION
-            )));
-        }
-
-        // Read manifest length
-        let mut manifest_len_bytes = [0u8; 4];
-        file.read_exact(&mut manifest_len_bytes)?;
-        let manifest_len = u32::from_be_bytes(manifest_len_bytes) as usize;
-
-        // Sanity check
-        if manifest_len > 10 * 1024 * 1024 {
-            return Err(UpdateError::ConfigError(
-                "Invalid update package: manifest too large".to_string(),
-            ));
-        }
-
-        // Read manifest data
-        let mut manifest_data = vec![0u8; manifest_len];
-        file.read_exact(&mut manifest_data)?;
-
-        // Verify manifest signature
-        let manifest = self.manifest_verifier.verify_and_parse(&manifest_data)?;
-
-        // Calculate expected artifact size from file
-        let header_size = 4 + 1 + 4 + manifest_len as u64;
-        let artifact_size_in_file = file_size - header_size;
+        let info = inspect_package(path, &self.manifest_verifier)?;
 
-        // Verify artifact size matches manifest
-        if artifact_size_in_file != manifest.artifact_size {
-            return Err(UpdateError::SizeMismatch {
-                expected: manifest.artifact_size,
-                actual: artifact_size_in_file,
+        // Verify platform matches (Requirement 1.5)
+        let expected = self.manifest_verifier.expected_platform();
+        if info.platform != expected {
+            return Err(UpdateError::PlatformMismatch {
+                expected: expected.to_string(),
+                actual: info.platform,
             });
         }
 
-        // Compute artifact hash without extracting
-        debug!("Computing artifact hash...");
-        let mut hasher = Sha256::new();
-        let mut buffer = [0u8; 8192];
-        loop {
-            let bytes_read = file.read(&mut buffer)?;
-            if bytes_read == 0 {
-                break;
-            }
-            hasher.update(&buffer[..bytes_read]);
-        }
-        let actual_hash: [u8; 32] = hasher.finalize().into();
-
-        // Verify hash
-        let expected_hash = manifest.artifact_hash_bytes().ok_or_else(|| {
-            UpdateError::ConfigError("Invalid artifact hash in manifest".to_string())
-        })?;
-
-        if actual_hash != expected_hash {
-            return Err(UpdateError::HashMismatch {
-                expected: hex::encode(expected_hash),
-                actual: hex::encode(actual_hash),
-            });
-        }
-
-        info!(
-            "Offline update file verified: version={}, size={}",
-            manifest.version, file_size
-        );
-
-        Ok(OfflineUpdateInfo {
-            version: manifest.version.clone(),
-            platform: manifest.platform.clone(),
-            channel: manifest.channel.clone(),
-            artifact_size: manifest.artifact_size,
-            package_size: file_size,
-            is_security_update: manifest.is_security_update,
-            release_notes: manifest.release_notes.clone(),
-        })
+        Ok(info)
     }
 
     /// Clean up staging directory.
@@ -527,6 +403,14 @@ pub struct OfflineUpdateInfo {
     pub is_security_update: bool,
     /// Release notes
     pub release_notes: String,
+    /// Expected SHA-256 hash of the artifact (hex encoded)
+    pub artifact_hash: String,
+    /// Key ids of the signatures that verified against pinned keys
+    pub signer_key_ids: Vec<String>,
+    /// When the manifest was signed
+    pub signed_at: DateTime<Utc>,
+    /// When the manifest becomes too old to be accepted
+    pub expires_at: DateTime<Utc>,
 }
 
 impl OfflineUpdateInfo {
@@ -536,6 +420,131 @@ impl OfflineUpdateInfo {
     }
 }
 
+/// Inspect an offline update package without installing it.
+///
+/// Verifies the manifest signatures against the verifier's pinned keys and
+/// the artifact hash, then reports what the package contains. The platform
+/// is not checked, so an admin can audit a package for another machine.
+///
+/// # Requirements
+/// - Requirement 10.2: Verify imported update files
+/// - Requirement 10.6: Verify offline update signatures
+///
+/// # Errors
+///
+/// Returns `UpdateError::CorruptPackage` if the package is truncated or
+/// malformed, and the usual verification errors for bad signatures or an
+/// artifact that does not match the manifest.
+pub fn inspect_package(path: &Path, verifier: &ManifestVerifier) -> Result<OfflineUpdateInfo, UpdateError> {
+    let mut file = File::open(path)?;
+    let package_size = file.metadata()?.len();
+    let manifest_data = read_package_manifest(&mut file)?;
+
+    let verified = verifier.verify_signatures(&manifest_data).map_err(|e| match e {
+        UpdateError::JsonError(e) => UpdateError::CorruptPackage(format!("invalid manifest: {}", e)),
+        e => e,
+    })?;
+    let manifest = verified.manifest;
+
+    // Hash the artifact in place; a short read means the copy was truncated
+    let expected_hash = manifest.artifact_hash_bytes().ok_or_else(|| {
+        UpdateError::ConfigError("Invalid artifact hash in manifest".to_string())
+    })?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 8192];
+    let mut artifact_size = 0u64;
+    loop {
+        let bytes_read = file.read(&mut buffer)?;
+        if bytes_read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..bytes_read]);
+        artifact_size += bytes_read as u64;
+    }
+    if artifact_size != manifest.artifact_size {
+        return Err(UpdateError::CorruptPackage(format!(
+            "artifact is {} bytes, manifest expects {}",
+            artifact_size, manifest.artifact_size
+        )));
+    }
+    let actual_hash: [u8; 32] = hasher.finalize().into();
+    if actual_hash != expected_hash {
+        return Err(UpdateError::HashMismatch {
+            expected: hex::encode(expected_hash),
+            actual: hex::encode(actual_hash),
+        });
+    }
+
+    info!(
+        "Offline update package verified: version={}, platform={}, signers={:?}",
+        manifest.version, manifest.platform, verified.signer_key_ids
+    );
+
+    Ok(OfflineUpdateInfo {
+        version: manifest.version,
+        platform: manifest.platform,
+        channel: manifest.channel,
+        artifact_size: manifest.artifact_size,
+        package_size,
+        is_security_update: manifest.is_security_update,
+        release_notes: manifest.release_notes,
+        artifact_hash: manifest.artifact_hash,
+        signer_key_ids: verified.signer_key_ids,
+        signed_at: unix_to_datetime(verified.signed_at),
+        expires_at: unix_to_datetime(verified.expires_at),
+    })
+}
+
+/// Read the package header and return the signed manifest bytes.
+///
+/// Leaves `file` positioned at the start of the artifact.
+fn read_package_manifest(file: &mut File) -> Result<Vec<u8>, UpdateError> {
+    let file_size = file.metadata()?.len();
+
+    // Verify minimum size (magic + version + manifest length)
+    if file_size < HEADER_SIZE {
+        return Err(UpdateError::CorruptPackage("file too small".to_string()));
+    }
+
+    let mut header = [0u8; HEADER_SIZE as usize];
+    file.read_exact(&mut header)?;
+
+    // Verify magic bytes
+    if &header[..4] != PACKAGE_MAGIC {
+        return Err(UpdateError::CorruptPackage("wrong magic bytes".to_string()));
+    }
+
+    // Verify version
+    if header[4] != PACKAGE_VERSION {
+        return Err(UpdateError::CorruptPackage(format!(
+            "Unsupported package version: {} (expected {})",
+            header[4], PACKAGE_VERSION
+        )));
+    }
+
+    // Sanity check manifest length: 10MB max, and it must fit in the file
+    let manifest_len = u32::from_be_bytes([header[5], header[6], header[7], header[8]]) as u64;
+    if manifest_len > MAX_MANIFEST_SIZE {
+        return Err(UpdateError::CorruptPackage("manifest too large".to_string()));
+    }
+    if HEADER_SIZE + manifest_len > file_size {
+        return Err(UpdateError::CorruptPackage(format!(
+            "manifest truncated: expected {} bytes, {} available",
+            manifest_len,
+            file_size - HEADER_SIZE
+        )));
+    }
+
+    let mut manifest_data = vec![0u8; manifest_len as usize];
+    file.read_exact(&mut manifest_data)?;
+    Ok(manifest_data)
+}
+
+/// Convert a unix timestamp to a UTC date time.
+fn unix_to_datetime(secs: u64) -> DateTime<Utc> {
+    DateTime::from_timestamp(secs.min(i64::MAX as u64) as i64, 0).unwrap_or_default()
+}
+
 /// Get the recommended file extension for offline update packages.
 pub fn package_extension() -> &'static str {
     "zrcu"
@@ -664,6 +673,10 @@ mod tests {
             package_size: 2000,
             is_security_update: false,
             release_notes: "Test".to_string(),
+            artifact_hash: "00".repeat(32),
+            signer_key_ids: vec!["key1".to_string()],
+            signed_at: Utc::now(),
+            expires_at: Utc::now(),
         };
         
         assert!(info.is_current_platform());
@@ -676,6 +689,10 @@ mod tests {
             package_size: 2000,
             is_security_update: false,
             release_notes: "Test".to_string(),
+            artifact_hash: "00".repeat(32),
+            signer_key_ids: vec!["key1".to_string()],
+            signed_at: Utc::now(),
+            expires_at: Utc::now(),
         };
         
         assert!(!info_other.is_current_platform());
@@ -858,4 +875,118 @@ mod tests {
         assert!(!staging_dir.join("file1.bin").exists());
         assert!(!staging_dir.join("file2.bin").exists());
     }
+
+    /// Export a package for `platform` and return its path.
+    fn export_test_package(temp_dir: &TempDir, platform: &str, timestamp: u64) -> PathBuf {
+        let (signing_key, verifying_key) = create_test_keypair();
+        let artifact_data = b"Artifact bytes carried by sneakernet";
+        let artifact_path = temp_dir.path().join("artifact.bin");
+        fs::write(&artifact_path, artifact_data).unwrap();
+
+        let manifest_json =
+            create_test_manifest(platform, &compute_hash(artifact_data), artifact_data.len() as u64);
+        let signed_manifest = create_signed_manifest(&manifest_json, &signing_key, timestamp);
+
+        let manager = OfflineUpdateManager::new(
+            ManifestVerifier::new(vec![verifying_key], 1),
+            ArtifactVerifier::new(),
+            temp_dir.path().join("staging"),
+        );
+        let package_path = temp_dir.path().join("update.zrcu");
+        manager
+            .export_update_file(&signed_manifest, &artifact_path, &package_path)
+            .unwrap();
+        package_path
+    }
+
+    #[test]
+    fn test_inspect_package_reports_signers_and_expiry() {
+        let temp_dir = TempDir::new().unwrap();
+        let timestamp = current_timestamp();
+        // Export checks the artifact only, so any platform can be packaged
+        let package_path = export_test_package(&temp_dir, "other-platform", timestamp);
+
+        let (_, verifying_key) = create_test_keypair();
+        let verifier = ManifestVerifier::new(vec![verifying_key], 1);
+        let info = inspect_package(&package_path, &verifier).unwrap();
+
+        assert_eq!(info.version, Version::new(2, 0, 0));
+        assert_eq!(info.platform, "other-platform");
+        assert_eq!(info.artifact_hash, compute_hash(b"Artifact bytes carried by sneakernet"));
+        assert_eq!(info.signer_key_ids, vec!["key1".to_string()]);
+        assert_eq!(info.signed_at.timestamp(), timestamp as i64);
+        assert_eq!(info.expires_at.timestamp(), (timestamp + 7 * 24 * 60 * 60) as i64);
+
+        // Installing paths still refuse the foreign platform
+        let manager = OfflineUpdateManager::new(verifier, ArtifactVerifier::new(), temp_dir.path().join("staging"));
+        assert!(matches!(
+            manager.verify_update_file(&package_path),
+            Err(UpdateError::PlatformMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_inspect_package_rejects_untrusted_signer() {
+        let temp_dir = TempDir::new().unwrap();
+        let package_path = export_test_package(&temp_dir, "other-platform", current_timestamp());
+
+        let other_key = SigningKey::from_bytes(&[9u8; 32]).verifying_key();
+        let verifier = ManifestVerifier::new(vec![other_key], 1);
+        assert!(matches!(
+            inspect_package(&package_path, &verifier),
+            Err(UpdateError::InsufficientSignatures { .. })
+        ));
+    }
+
+    #[test]
+    fn test_inspect_truncated_package() {
+        let temp_dir = TempDir::new().unwrap();
+        let package_path = export_test_package(&temp_dir, "other-platform", current_timestamp());
+        let (_, verifying_key) = create_test_keypair();
+        let verifier = ManifestVerifier::new(vec![verifying_key], 1);
+        let data = fs::read(&package_path).unwrap();
+
+        // Truncated inside the artifact
+        let truncated = temp_dir.path().join("short-artifact.zrcu");
+        fs::write(&truncated, &data[..data.len() - 5]).unwrap();
+        assert!(matches!(
+            inspect_package(&truncated, &verifier),
+            Err(UpdateError::CorruptPackage(_))
+        ));
+
+        // Truncated inside the manifest
+        let truncated = temp_dir.path().join("short-manifest.zrcu");
+        fs::write(&truncated, &data[..20]).unwrap();
+        assert!(matches!(
+            inspect_package(&truncated, &verifier),
+            Err(UpdateError::CorruptPackage(_))
+        ));
+
+        // Header only
+        let truncated = temp_dir.path().join("short-header.zrcu");
+        fs::write(&truncated, &data[..6]).unwrap();
+        assert!(matches!(
+            inspect_package(&truncated, &verifier),
+            Err(UpdateError::CorruptPackage(_))
+        ));
+    }
+
+    #[test]
+    fn test_inspect_garbled_manifest() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut data = Vec::new();
+        data.extend_from_slice(PACKAGE_MAGIC);
+        data.push(PACKAGE_VERSION);
+        data.extend_from_slice(&10u32.to_be_bytes());
+        data.extend_from_slice(b"not json!!");
+        let package_path = temp_dir.path().join("garbled.zrcu");
+        fs::write(&package_path, &data).unwrap();
+
+        let (_, verifying_key) = create_test_keypair();
+        let verifier = ManifestVerifier::new(vec![verifying_key], 1);
+        assert!(matches!(
+            inspect_package(&package_path, &verifier),
+            Err(UpdateError::CorruptPackage(_))
+        ));
+    }
 }
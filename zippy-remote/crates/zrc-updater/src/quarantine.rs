@@ -0,0 +1,262 @@
+//! Quarantine for artifacts that failed verification.
+//!
+//! When a downloaded artifact fails its hash or code signature check it is
+//! moved here instead of being deleted, together with a JSON sidecar that
+//! records what was expected and what was received. Quarantined artifacts are
+//! never retried automatically; an operator has to purge the quarantine first.
+//!
+//! ## Directory Structure
+//!
+//! ```text
+//! quarantine_dir/
+//! ├── update-2.0.0-1704067200.bin        # The rejected artifact
+//! └── update-2.0.0-1704067200.bin.json   # QuarantineRecord sidecar
+//! ```
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use crate::error::UpdateError;
+
+/// Extension appended to the artifact file name for its sidecar.
+const SIDECAR_EXTENSION: &str = "json";
+
+/// Forensic record stored next to a quarantined artifact.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuarantineRecord {
+    /// Manifest version the artifact claimed to be
+    pub version: Version,
+    /// URL the artifact was downloaded from
+    pub artifact_url: String,
+    /// SHA-256 hash from the manifest (hex encoded)
+    pub expected_hash: String,
+    /// SHA-256 hash of the received bytes (hex encoded)
+    pub actual_hash: String,
+    /// Why verification failed
+    pub reason: String,
+    /// When the artifact was quarantined
+    pub quarantined_at: DateTime<Utc>,
+    /// Path of the quarantined artifact
+    pub path: PathBuf,
+}
+
+/// Keeps rejected artifacts for inspection.
+pub struct QuarantineManager {
+    /// Directory holding quarantined artifacts and sidecars
+    quarantine_dir: PathBuf,
+    /// Maximum number of quarantined artifacts to retain
+    retention: usize,
+}
+
+impl QuarantineManager {
+    /// Create a new quarantine manager.
+    ///
+    /// # Arguments
+    ///
+    /// * `quarantine_dir` - Directory where rejected artifacts are kept
+    /// * `retention` - Maximum number of artifacts to keep (oldest are deleted)
+    pub fn new(quarantine_dir: PathBuf, retention: usize) -> Self {
+        Self {
+            quarantine_dir,
+            retention,
+        }
+    }
+
+    /// Get the quarantine directory.
+    pub fn quarantine_dir(&self) -> &Path {
+        &self.quarantine_dir
+    }
+
+    /// Get the retention count.
+    pub fn retention(&self) -> usize {
+        self.retention
+    }
+
+    /// Move a rejected artifact into quarantine and write its sidecar.
+    ///
+    /// `record.path` is set to the artifact's new location. Older entries
+    /// beyond the retention count are deleted afterwards.
+    pub fn quarantine(
+        &self,
+        artifact: &Path,
+        mut record: QuarantineRecord,
+    ) -> Result<QuarantineRecord, UpdateError> {
+        fs::create_dir_all(&self.quarantine_dir)?;
+
+        let file_name = artifact
+            .file_name()
+            .ok_or_else(|| UpdateError::ConfigError(format!("invalid artifact path {:?}", artifact)))?;
+        let dest = self.quarantine_dir.join(file_name);
+
+        // rename fails across filesystems; fall back to copy + remove
+        if fs::rename(artifact, &dest).is_err() {
+            fs::copy(artifact, &dest)?;
+            fs::remove_file(artifact)?;
+        }
+
+        record.path = dest.clone();
+        fs::write(sidecar_path(&dest), serde_json::to_string_pretty(&record)?)?;
+
+        warn!(
+            version = %record.version,
+            expected = %record.expected_hash,
+            actual = %record.actual_hash,
+            "Artifact quarantined at {:?}: {}",
+            dest,
+            record.reason
+        );
+
+        self.purge(self.retention)?;
+        Ok(record)
+    }
+
+    /// List quarantined artifacts, newest first.
+    ///
+    /// Sidecars that cannot be read or parsed are skipped with a warning.
+    pub fn list(&self) -> Result<Vec<QuarantineRecord>, UpdateError> {
+        let mut records = Vec::new();
+
+        if !self.quarantine_dir.exists() {
+            return Ok(records);
+        }
+
+        for entry in fs::read_dir(&self.quarantine_dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some(SIDECAR_EXTENSION) {
+                continue;
+            }
+
+            match fs::read_to_string(&path)
+                .map_err(UpdateError::from)
+                .and_then(|content| serde_json::from_str::<QuarantineRecord>(&content).map_err(UpdateError::from))
+            {
+                Ok(record) => records.push(record),
+                Err(e) => warn!("Skipping unreadable quarantine record {:?}: {}", path, e),
+            }
+        }
+
+        records.sort_by_key(|record| std::cmp::Reverse(record.quarantined_at));
+        Ok(records)
+    }
+
+    /// Check whether an artifact with this expected hash was quarantined.
+    pub fn is_quarantined(&self, expected_hash: &str) -> Result<bool, UpdateError> {
+        Ok(self
+            .list()?
+            .iter()
+            .any(|record| record.expected_hash.eq_ignore_ascii_case(expected_hash)))
+    }
+
+    /// Delete all but the `keep` newest quarantined artifacts.
+    ///
+    /// Returns the number of artifacts removed.
+    pub fn purge(&self, keep: usize) -> Result<usize, UpdateError> {
+        let records = self.list()?;
+        let mut removed = 0;
+
+        for record in records.iter().skip(keep) {
+            if record.path.exists() {
+                fs::remove_file(&record.path)?;
+            }
+            let sidecar = sidecar_path(&record.path);
+            if sidecar.exists() {
+                fs::remove_file(&sidecar)?;
+            }
+            debug!("Purged quarantined artifact {:?}", record.path);
+            removed += 1;
+        }
+
+        if removed > 0 {
+            info!("Purged {} quarantined artifact(s)", removed);
+        }
+        Ok(removed)
+    }
+}
+
+/// Sidecar path for a quarantined artifact (`<file>.json`).
+fn sidecar_path(artifact: &Path) -> PathBuf {
+    let mut name = artifact.as_os_str().to_os_string();
+    name.push(".");
+    name.push(SIDECAR_EXTENSION);
+    PathBuf::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use tempfile::TempDir;
+
+    fn record(version: &str, expected: &str, age_secs: i64) -> QuarantineRecord {
+        QuarantineRecord {
+            version: Version::parse(version).unwrap(),
+            artifact_url: "https://mirror.example.com/update.bin".to_string(),
+            expected_hash: expected.to_string(),
+            actual_hash: "00".repeat(32),
+            reason: "hash mismatch".to_string(),
+            quarantined_at: Utc::now() - Duration::seconds(age_secs),
+            path: PathBuf::new(),
+        }
+    }
+
+    fn artifact(dir: &Path, name: &str) -> PathBuf {
+        let path = dir.join(name);
+        fs::write(&path, b"corrupted bytes").unwrap();
+        path
+    }
+
+    #[test]
+    fn test_quarantine_moves_artifact_and_writes_sidecar() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = QuarantineManager::new(temp_dir.path().join("quarantine"), 5);
+        let downloaded = artifact(temp_dir.path(), "update-2.0.0-1.bin");
+
+        let stored = manager
+            .quarantine(&downloaded, record("2.0.0", &"ab".repeat(32), 0))
+            .unwrap();
+
+        assert!(!downloaded.exists());
+        assert!(stored.path.exists());
+        assert!(sidecar_path(&stored.path).exists());
+
+        let listed = manager.list().unwrap();
+        assert_eq!(listed, vec![stored]);
+        assert!(manager.is_quarantined(&"AB".repeat(32)).unwrap());
+        assert!(!manager.is_quarantined(&"cd".repeat(32)).unwrap());
+    }
+
+    #[test]
+    fn test_retention_keeps_newest() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = QuarantineManager::new(temp_dir.path().join("quarantine"), 2);
+
+        for (i, age) in [30, 20, 10].into_iter().enumerate() {
+            let downloaded = artifact(temp_dir.path(), &format!("update-{i}.bin"));
+            manager
+                .quarantine(&downloaded, record("2.0.0", &format!("{i:064}"), age))
+                .unwrap();
+        }
+
+        let listed = manager.list().unwrap();
+        assert_eq!(listed.len(), 2);
+        assert_eq!(listed[0].expected_hash, format!("{:064}", 2));
+        assert_eq!(listed[1].expected_hash, format!("{:064}", 1));
+    }
+
+    #[test]
+    fn test_purge_all() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = QuarantineManager::new(temp_dir.path().join("quarantine"), 5);
+        let downloaded = artifact(temp_dir.path(), "update.bin");
+        manager.quarantine(&downloaded, record("2.0.0", &"ab".repeat(32), 0)).unwrap();
+
+        assert_eq!(manager.purge(0).unwrap(), 1);
+        assert!(manager.list().unwrap().is_empty());
+        assert!(fs::read_dir(manager.quarantine_dir()).unwrap().next().is_none());
+    }
+}
@@ -6,6 +6,14 @@
 //! - Rollback to previous versions
 //! - Automatic cleanup of old backups
 //!
+//! ## Retention
+//!
+//! After each backup, a backup is kept if it is among the newest
+//! `max_backups` **or** younger than `max_age` (when set). The count limit
+//! therefore guarantees the last few versions survive however old they are,
+//! while the age limit keeps every recent backup on machines that update
+//! often. Anything matching neither rule is deleted.
+//!
 //! ## Security
 //!
 //! Backups are stored with metadata that includes version information
@@ -15,6 +23,7 @@
 use std::fs::{self, File};
 use std::io::Read;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use chrono::{DateTime, Utc};
 use semver::Version;
@@ -37,7 +46,8 @@ const HASH_FILE: &str = "hash.sha256";
 /// - Creating backups of the current executable before updates
 /// - Listing all available backups sorted by creation time
 /// - Rolling back to a specific backup version
-/// - Cleaning up old backups to respect the max_backups limit
+/// - Cleaning up old backups that fall outside both the max_backups and
+///   max_age limits
 ///
 /// # Backup Directory Structure
 ///
@@ -55,8 +65,10 @@ const HASH_FILE: &str = "hash.sha256";
 pub struct RollbackManager {
     /// Directory for storing backups
     backup_dir: PathBuf,
-    /// Maximum number of backups to retain
+    /// Number of newest backups always retained
     max_backups: usize,
+    /// Backups younger than this are retained beyond max_backups
+    max_age: Option<Duration>,
 }
 
 impl RollbackManager {
@@ -79,9 +91,18 @@ impl RollbackManager {
         Self {
             backup_dir,
             max_backups,
+            max_age: None,
         }
     }
 
+    /// Also retain every backup younger than `max_age`.
+    ///
+    /// See the module documentation for how this combines with `max_backups`.
+    pub fn with_max_age(mut self, max_age: Option<Duration>) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
     /// Get the backup directory.
     pub fn backup_dir(&self) -> &PathBuf {
         &self.backup_dir
@@ -92,6 +113,11 @@ impl RollbackManager {
         self.max_backups
     }
 
+    /// Get the age below which backups are always retained.
+    pub fn max_age(&self) -> Option<Duration> {
+        self.max_age
+    }
+
     /// Ensure the backup directory exists.
     fn ensure_backup_dir(&self) -> Result<(), UpdateError> {
         if !self.backup_dir.exists() {
@@ -151,8 +177,8 @@ impl RollbackManager {
     /// Backup current version before update.
     ///
     /// Creates a backup of the currently running executable along with
-    /// metadata and integrity hash. Automatically cleans up old backups
-    /// if the max_backups limit is exceeded.
+    /// metadata and integrity hash. Automatically cleans up backups that
+    /// are outside both the max_backups and max_age limits.
     ///
     /// # Returns
     ///
@@ -355,10 +381,10 @@ impl RollbackManager {
         Ok(())
     }
 
-    /// Clean up old backups beyond max_backups limit.
+    /// Clean up backups outside the retention limits.
     ///
-    /// Removes the oldest backups when the number of backups exceeds
-    /// the configured maximum. Always keeps at least one backup.
+    /// A backup is removed only if it is beyond the newest `max_backups`
+    /// and, when `max_age` is set, at least `max_age` old.
     fn cleanup_old_backups(&self) -> Result<(), UpdateError> {
         let backups = self.list_backups()?;
 
@@ -366,8 +392,12 @@ impl RollbackManager {
             return Ok(());
         }
 
-        // Remove oldest backups (list is sorted newest first)
+        let now = Utc::now();
+        // List is sorted newest first, so the count limit keeps a prefix
         for backup in backups.iter().skip(self.max_backups) {
+            if self.within_max_age(backup, now) {
+                continue;
+            }
             info!("Removing old backup: {:?}", backup.path);
             if let Err(e) = fs::remove_dir_all(&backup.path) {
                 warn!("Failed to remove old backup {:?}: {}", backup.path, e);
@@ -377,6 +407,18 @@ impl RollbackManager {
         Ok(())
     }
 
+    /// Whether `backup` is younger than `max_age` at `now`.
+    fn within_max_age(&self, backup: &BackupInfo, now: DateTime<Utc>) -> bool {
+        let Some(max_age) = self.max_age else {
+            return false;
+        };
+        match (now - backup.created_at).to_std() {
+            Ok(age) => age < max_age,
+            // Created in the future (clock change); treat as fresh
+            Err(_) => true,
+        }
+    }
+
     /// Get the most recent backup.
     ///
     /// Returns the newest backup if one exists.
@@ -594,6 +636,53 @@ mod tests {
         assert_eq!(backups[1].version, Version::new(1, 3, 0));
     }
 
+    /// Rewrite a backup's metadata so it appears `age` old.
+    fn age_backup(backup: &BackupInfo, age: chrono::Duration) {
+        let mut info = backup.clone();
+        info.created_at = Utc::now() - age;
+        fs::write(
+            backup.metadata_path(),
+            serde_json::to_string_pretty(&info).unwrap(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_max_age_retains_recent_beyond_count() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = RollbackManager::new(temp_dir.path().to_path_buf(), 1)
+            .with_max_age(Some(Duration::from_secs(7 * 24 * 3600)));
+        let test_file = create_test_file(temp_dir.path(), "test_exe", b"test content");
+
+        let old = manager.backup_file(&test_file, Version::new(1, 0, 0)).unwrap();
+        age_backup(&old, chrono::Duration::days(30));
+        let recent = manager.backup_file(&test_file, Version::new(1, 1, 0)).unwrap();
+        age_backup(&recent, chrono::Duration::days(2));
+        manager.backup_file(&test_file, Version::new(1, 2, 0)).unwrap();
+
+        // 1.2.0 is kept by count, 1.1.0 by age; 1.0.0 matches neither
+        let versions: Vec<_> = manager.list_backups().unwrap().into_iter().map(|b| b.version).collect();
+        assert_eq!(versions, vec![Version::new(1, 2, 0), Version::new(1, 1, 0)]);
+    }
+
+    #[test]
+    fn test_max_backups_retains_old_beyond_age() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = RollbackManager::new(temp_dir.path().to_path_buf(), 2)
+            .with_max_age(Some(Duration::from_secs(24 * 3600)));
+        let test_file = create_test_file(temp_dir.path(), "test_exe", b"test content");
+
+        for (minor, days) in [(0, 400), (1, 300), (2, 200)] {
+            let backup = manager.backup_file(&test_file, Version::new(1, minor, 0)).unwrap();
+            age_backup(&backup, chrono::Duration::days(days));
+        }
+        manager.backup_file(&test_file, Version::new(1, 3, 0)).unwrap();
+
+        // Everything but 1.3.0 is past max_age, yet the newest two survive
+        let versions: Vec<_> = manager.list_backups().unwrap().into_iter().map(|b| b.version).collect();
+        assert_eq!(versions, vec![Version::new(1, 3, 0), Version::new(1, 2, 0)]);
+    }
+
     #[test]
     fn test_find_by_version() {
         let (manager, temp_dir) = create_test_manager();
@@ -0,0 +1,21 @@
+# Write signed.exe and signed.thumbprint for the authenticode-fixture tests.
+param(
+    [Parameter(Mandatory = $true)]
+    [string]$Source,
+    [string]$OutDir = $PSScriptRoot
+)
+
+$ErrorActionPreference = "Stop"
+
+$cert = New-SelfSignedCertificate -Type CodeSigningCert `
+    -Subject "CN=ZRC Updater Test Signing" `
+    -CertStoreLocation Cert:\CurrentUser\My
+
+try {
+    Copy-Item $Source "$OutDir\signed.exe" -Force
+    Set-AuthenticodeSignature -FilePath "$OutDir\signed.exe" -Certificate $cert | Out-Null
+    Set-Content -Path "$OutDir\signed.thumbprint" -Value $cert.Thumbprint -NoNewline
+}
+finally {
+    Remove-Item "Cert:\CurrentUser\My\$($cert.Thumbprint)"
+}
@@ -0,0 +1,204 @@
+//! Pixel-format conversion from `FramePacketV1` into the RGBA frame buffer.
+//!
+//! Each packet `format` maps to a [`FrameConverter`]; new capture formats are
+//! added by registering a converter instead of touching the render loop.
+//! Rows are read using the packet `stride`, so padded captures are not skewed.
+
+use std::collections::HashMap;
+
+use zrc_core::quic_mux::FramePacketV1;
+
+/// Raw BGRA, 4 bytes per pixel (matches `FRAME_FORMAT_V1_RAW_BGRA`).
+pub const FORMAT_BGRA: u8 = 1;
+/// Raw RGBA, 4 bytes per pixel (matches `FRAME_FORMAT_V1_RAW_RGBA`).
+pub const FORMAT_RGBA: u8 = 2;
+/// Raw RGB, 3 bytes per pixel. Packet-only formats start at 0x80 so they
+/// never collide with `FrameFormatV1` values.
+pub const FORMAT_RGB: u8 = 0x80;
+
+/// Side of one square in the unknown-format diagnostic pattern.
+const DIAGNOSTIC_CELL: usize = 16;
+
+/// Converts one row of source pixels into RGBA.
+pub trait FrameConverter: Send + Sync {
+    /// Bytes per source pixel.
+    fn bytes_per_pixel(&self) -> usize;
+
+    /// Convert whole pixels from `src` into `dst` (4 bytes per pixel).
+    fn convert_row(&self, src: &[u8], dst: &mut [u8]);
+}
+
+/// BGRA → RGBA channel swap.
+pub struct BgraConverter;
+
+impl FrameConverter for BgraConverter {
+    fn bytes_per_pixel(&self) -> usize {
+        4
+    }
+
+    fn convert_row(&self, src: &[u8], dst: &mut [u8]) {
+        for (s, d) in src.chunks_exact(4).zip(dst.chunks_exact_mut(4)) {
+            d.copy_from_slice(&[s[2], s[1], s[0], s[3]]);
+        }
+    }
+}
+
+/// RGBA passthrough.
+pub struct RgbaConverter;
+
+impl FrameConverter for RgbaConverter {
+    fn bytes_per_pixel(&self) -> usize {
+        4
+    }
+
+    fn convert_row(&self, src: &[u8], dst: &mut [u8]) {
+        let n = src.len().min(dst.len()) / 4 * 4;
+        dst[..n].copy_from_slice(&src[..n]);
+    }
+}
+
+/// RGB → RGBA expansion with opaque alpha.
+pub struct RgbConverter;
+
+impl FrameConverter for RgbConverter {
+    fn bytes_per_pixel(&self) -> usize {
+        3
+    }
+
+    fn convert_row(&self, src: &[u8], dst: &mut [u8]) {
+        for (s, d) in src.chunks_exact(3).zip(dst.chunks_exact_mut(4)) {
+            d.copy_from_slice(&[s[0], s[1], s[2], 0xFF]);
+        }
+    }
+}
+
+/// Converters keyed by `FramePacketV1.format`.
+pub struct FrameConverters {
+    converters: HashMap<u8, Box<dyn FrameConverter>>,
+}
+
+impl Default for FrameConverters {
+    fn default() -> Self {
+        let mut converters = Self { converters: HashMap::new() };
+        converters.register(FORMAT_BGRA, Box::new(BgraConverter));
+        converters.register(FORMAT_RGBA, Box::new(RgbaConverter));
+        converters.register(FORMAT_RGB, Box::new(RgbConverter));
+        converters
+    }
+}
+
+impl FrameConverters {
+    /// Register (or replace) the converter for a packet format.
+    pub fn register(&mut self, format: u8, converter: Box<dyn FrameConverter>) {
+        self.converters.insert(format, converter);
+    }
+
+    /// Whether a converter is registered for `format`.
+    pub fn supports(&self, format: u8) -> bool {
+        self.converters.contains_key(&format)
+    }
+
+    /// Convert `pkt` into `dst`, a `width * 4` bytes-per-row RGBA buffer.
+    ///
+    /// Source rows start every `stride` bytes (`width * bpp` when the stride
+    /// is 0 or smaller than a row). Copies are clamped to both buffers, so short or oversized
+    /// packets never read or write out of bounds. Unknown formats render a
+    /// diagnostic checkerboard instead of garbage.
+    pub fn convert(&self, pkt: &FramePacketV1, dst: &mut [u8]) {
+        let width = pkt.width as usize;
+        let dst_row = width * 4;
+        if dst_row == 0 {
+            return;
+        }
+
+        let Some(converter) = self.converters.get(&pkt.format) else {
+            fill_diagnostic(dst, width);
+            return;
+        };
+
+        // A stride shorter than a row is bogus; fall back to tightly packed rows
+        let src_row = width * converter.bytes_per_pixel();
+        let stride = (pkt.stride as usize).max(src_row);
+
+        for (row, out) in dst.chunks_mut(dst_row).take(pkt.height as usize).enumerate() {
+            let start = row * stride;
+            if start >= pkt.pixels.len() {
+                break;
+            }
+            let end = (start + src_row).min(pkt.pixels.len());
+            converter.convert_row(&pkt.pixels[start..end], out);
+        }
+    }
+}
+
+/// Fill `dst` with a magenta/black checkerboard marking an unsupported format.
+fn fill_diagnostic(dst: &mut [u8], width: usize) {
+    for (i, px) in dst.chunks_exact_mut(4).enumerate() {
+        let (x, y) = (i % width, i / width);
+        let on = (x / DIAGNOSTIC_CELL + y / DIAGNOSTIC_CELL) % 2 == 0;
+        px.copy_from_slice(if on { &[0xFF, 0x00, 0xFF, 0xFF] } else { &[0x00, 0x00, 0x00, 0xFF] });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn packet(width: u32, height: u32, stride: u32, format: u8, pixels: Vec<u8>) -> FramePacketV1 {
+        FramePacketV1 { width, height, stride, format, pixels }
+    }
+
+    #[test]
+    fn test_bgra_with_stride_padding() {
+        // 2x2 BGRA image with 4 bytes of 0xEE padding after each row
+        let pixels = vec![
+            1, 2, 3, 4, 5, 6, 7, 8, 0xEE, 0xEE, 0xEE, 0xEE, //
+            9, 10, 11, 12, 13, 14, 15, 16, 0xEE, 0xEE, 0xEE, 0xEE,
+        ];
+        let pkt = packet(2, 2, 12, FORMAT_BGRA, pixels);
+        let mut out = vec![0u8; 2 * 2 * 4];
+        FrameConverters::default().convert(&pkt, &mut out);
+
+        assert_eq!(
+            out,
+            vec![3, 2, 1, 4, 7, 6, 5, 8, 11, 10, 9, 12, 15, 14, 13, 16]
+        );
+    }
+
+    #[test]
+    fn test_rgb_with_stride_padding() {
+        let pixels = vec![
+            1, 2, 3, 4, 5, 6, 0, 0, //
+            7, 8, 9, 10, 11, 12, 0, 0,
+        ];
+        let pkt = packet(2, 2, 8, FORMAT_RGB, pixels);
+        let mut out = vec![0u8; 2 * 2 * 4];
+        FrameConverters::default().convert(&pkt, &mut out);
+
+        assert_eq!(
+            out,
+            vec![1, 2, 3, 255, 4, 5, 6, 255, 7, 8, 9, 255, 10, 11, 12, 255]
+        );
+    }
+
+    #[test]
+    fn test_short_buffers_are_clamped() {
+        // Second row is truncated and the destination only fits one row
+        let pkt = packet(2, 2, 12, FORMAT_RGBA, vec![1, 2, 3, 4, 5, 6, 7, 8, 0, 0, 0, 0, 9, 9]);
+        let mut out = vec![0u8; 2 * 4];
+        FrameConverters::default().convert(&pkt, &mut out);
+        assert_eq!(out, vec![1, 2, 3, 4, 5, 6, 7, 8]);
+
+        let mut out = vec![0u8; 2 * 2 * 4];
+        FrameConverters::default().convert(&pkt, &mut out);
+        assert_eq!(&out[8..], &[0u8; 8]);
+    }
+
+    #[test]
+    fn test_unknown_format_renders_diagnostic() {
+        let pkt = packet(2, 1, 0, 0x7F, vec![0; 8]);
+        let mut out = vec![0u8; 2 * 4];
+        FrameConverters::default().convert(&pkt, &mut out);
+        assert_eq!(&out[..4], &[0xFF, 0x00, 0xFF, 0xFF]);
+    }
+}
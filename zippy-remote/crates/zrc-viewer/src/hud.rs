@@ -0,0 +1,99 @@
+//! Heads-up display metrics for the viewer.
+//!
+//! Rates are derived from the [`FrameRing`](crate::ring::FrameRing) receive
+//! counters, i.e. the same path frames arrive on, sampled once per interval
+//! while the HUD is visible. `FramePacketV1` carries no capture timestamp, so
+//! end-to-end latency is not shown.
+
+use std::time::{Duration, Instant};
+
+use crate::ring::FrameStats;
+
+/// How often displayed rates are refreshed.
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Rolling receive-rate meter.
+#[derive(Debug, Default)]
+pub struct HudMeter {
+    last: Option<(Instant, FrameStats)>,
+    fps: f64,
+    avg_frame_bytes: u64,
+}
+
+impl HudMeter {
+    /// Forget previous samples, e.g. when the HUD is re-enabled.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Feed the current counters; rates update once per sample interval.
+    pub fn sample(&mut self, now: Instant, stats: FrameStats) {
+        let Some((at, prev)) = self.last else {
+            self.last = Some((now, stats));
+            return;
+        };
+        let elapsed = now.duration_since(at);
+        if elapsed < SAMPLE_INTERVAL {
+            return;
+        }
+
+        let frames = stats.received.saturating_sub(prev.received);
+        let bytes = stats.received_bytes.saturating_sub(prev.received_bytes);
+        self.fps = frames as f64 / elapsed.as_secs_f64();
+        self.avg_frame_bytes = if frames == 0 { 0 } else { bytes / frames };
+        self.last = Some((now, stats));
+    }
+
+    /// Frames received per second over the last interval.
+    pub fn fps(&self) -> f64 {
+        self.fps
+    }
+
+    /// Average pixel payload per received frame over the last interval.
+    pub fn avg_frame_bytes(&self) -> u64 {
+        self.avg_frame_bytes
+    }
+
+    /// Text lines for the overlay.
+    pub fn lines(&self) -> Vec<String> {
+        vec![
+            format!("FPS {:.0}", self.fps),
+            format!("KB {}", self.avg_frame_bytes / 1024),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(received: u64, received_bytes: u64) -> FrameStats {
+        FrameStats { received, received_bytes, ..Default::default() }
+    }
+
+    #[test]
+    fn test_rates_from_receive_counters() {
+        let start = Instant::now();
+        let mut meter = HudMeter::default();
+        meter.sample(start, stats(0, 0));
+
+        // Within the interval nothing changes
+        meter.sample(start + Duration::from_millis(500), stats(15, 15 * 4096));
+        assert_eq!(meter.fps(), 0.0);
+
+        meter.sample(start + Duration::from_secs(2), stats(60, 60 * 4096));
+        assert_eq!(meter.fps(), 30.0);
+        assert_eq!(meter.avg_frame_bytes(), 4096);
+        assert_eq!(meter.lines(), vec!["FPS 30", "KB 4"]);
+    }
+
+    #[test]
+    fn test_idle_stream_reports_zero() {
+        let start = Instant::now();
+        let mut meter = HudMeter::default();
+        meter.sample(start, stats(10, 100));
+        meter.sample(start + Duration::from_secs(1), stats(10, 100));
+        assert_eq!(meter.fps(), 0.0);
+        assert_eq!(meter.avg_frame_bytes(), 0);
+    }
+}
@@ -0,0 +1,317 @@
+//! Translation of local winit input into `ControlMsgV1` input events.
+//!
+//! Key codes are Windows virtual-key codes (VK_*), matching what hosts expect
+//! for `KEY_DOWN`/`KEY_UP` events. Modifier state follows `InputModifiersV1`.
+
+use std::collections::HashSet;
+use std::time::Instant;
+
+use tokio::sync::mpsc;
+use winit::keyboard::{KeyCode, ModifiersState};
+use zrc_core::policy::KEEPALIVE_INTERVAL;
+use zrc_proto::v1::{ControlMsgV1, InputEventV1};
+
+use crate::viewport::Viewport;
+
+const MOD_SHIFT: u32 = 1;
+const MOD_CTRL: u32 = 2;
+const MOD_ALT: u32 = 4;
+const MOD_META: u32 = 8;
+
+/// Sends input events with monotonically increasing sequence numbers.
+pub struct InputSender {
+    tx: mpsc::UnboundedSender<ControlMsgV1>,
+    sequence: u64,
+    /// When the last input or ping went out
+    last_sent: Instant,
+}
+
+impl InputSender {
+    pub fn new(tx: mpsc::UnboundedSender<ControlMsgV1>) -> Self {
+        Self { tx, sequence: 0, last_sent: Instant::now() }
+    }
+
+    /// Wrap `event` in a control message and send it; drops silently once
+    /// the session side has gone away.
+    pub fn send(&mut self, event: InputEventV1) {
+        self.sequence += 1;
+        self.last_sent = Instant::now();
+        let _ = self.tx.send(ControlMsgV1::input(self.sequence, event));
+    }
+
+    /// Ping the host if nothing has been sent for `KEEPALIVE_INTERVAL`, so
+    /// a viewer that is only watching isn't closed as idle.
+    pub fn keepalive(&mut self, now: Instant) {
+        if now.duration_since(self.last_sent) < KEEPALIVE_INTERVAL {
+            return;
+        }
+        self.sequence += 1;
+        self.last_sent = now;
+        let _ = self.tx.send(ControlMsgV1::ping(self.sequence));
+    }
+}
+
+/// Keyboard state used to build key events.
+#[derive(Debug, Default)]
+pub struct KeyboardState {
+    pressed: HashSet<KeyCode>,
+    modifiers: u32,
+}
+
+impl KeyboardState {
+    /// Update the modifier bitmask from winit's modifier state.
+    pub fn set_modifiers(&mut self, state: ModifiersState) {
+        self.modifiers = modifiers_mask(state);
+    }
+
+    /// Current `InputModifiersV1` bitmask.
+    pub fn modifiers(&self) -> u32 {
+        self.modifiers
+    }
+
+    /// Build the event for a key transition, if one should be sent.
+    ///
+    /// Presses of a key that is already down are only forwarded when the OS
+    /// flags them as auto-repeat; releases of keys we never saw go down (e.g.
+    /// pressed before the window had focus) are dropped.
+    pub fn key_event(&mut self, code: KeyCode, pressed: bool, repeat: bool) -> Option<InputEventV1> {
+        let vk = virtual_key(code)?;
+        if pressed {
+            if !self.pressed.insert(code) && !repeat {
+                return None;
+            }
+            Some(InputEventV1::key_down(vk, self.modifiers))
+        } else {
+            if !self.pressed.remove(&code) {
+                return None;
+            }
+            Some(InputEventV1::key_up(vk, self.modifiers))
+        }
+    }
+
+    /// Release every held key, e.g. when the window loses focus, so the
+    /// host is not left with stuck keys.
+    pub fn release_all(&mut self) -> Vec<InputEventV1> {
+        let modifiers = self.modifiers;
+        self.pressed
+            .drain()
+            .filter_map(virtual_key)
+            .map(|vk| InputEventV1::key_up(vk, modifiers))
+            .collect()
+    }
+}
+
+/// Relative mouse mode: forwards raw motion deltas instead of window
+/// coordinates, for hosts that capture the pointer (games, CAD).
+#[derive(Debug, Default)]
+pub struct RelativeMouse {
+    enabled: bool,
+    remainder: (f64, f64),
+}
+
+impl RelativeMouse {
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Flip the mode and return the new state.
+    pub fn toggle(&mut self) -> bool {
+        self.enabled = !self.enabled;
+        self.remainder = (0.0, 0.0);
+        self.enabled
+    }
+
+    /// Build a relative move from a raw device delta.
+    ///
+    /// Sub-pixel motion is carried over so slow movements are not lost to
+    /// rounding; returns `None` while disabled or until a whole pixel moved.
+    pub fn motion(&mut self, dx: f64, dy: f64) -> Option<InputEventV1> {
+        if !self.enabled {
+            return None;
+        }
+        let x = self.remainder.0 + dx;
+        let y = self.remainder.1 + dy;
+        let (ix, iy) = (x.trunc(), y.trunc());
+        self.remainder = (x - ix, y - iy);
+        if ix == 0.0 && iy == 0.0 {
+            return None;
+        }
+        Some(InputEventV1::mouse_move_relative(ix as i32, iy as i32))
+    }
+}
+
+/// Map a cursor position in window pixels to remote frame pixels.
+///
+/// `viewport` is where the frame is drawn in the window. Positions over the
+/// letterbox bars clamp to the nearest frame edge so drags that leave the
+/// picture still track. Returns `None` until the remote frame size is known.
+pub fn map_to_frame(pos: (f64, f64), viewport: Viewport, frame: (u32, u32)) -> Option<(i32, i32)> {
+    if frame.0 == 0 || frame.1 == 0 || viewport.is_empty() {
+        return None;
+    }
+    let (fw, fh) = (frame.0 as f64, frame.1 as f64);
+    let scale_x = viewport.width as f64 / fw;
+    let scale_y = viewport.height as f64 / fh;
+
+    let x = ((pos.0 - viewport.x as f64) / scale_x).floor().clamp(0.0, fw - 1.0);
+    let y = ((pos.1 - viewport.y as f64) / scale_y).floor().clamp(0.0, fh - 1.0);
+    Some((x as i32, y as i32))
+}
+
+/// Convert winit modifier state into an `InputModifiersV1` bitmask.
+pub fn modifiers_mask(state: ModifiersState) -> u32 {
+    let mut mask = 0;
+    if state.shift_key() {
+        mask |= MOD_SHIFT;
+    }
+    if state.control_key() {
+        mask |= MOD_CTRL;
+    }
+    if state.alt_key() {
+        mask |= MOD_ALT;
+    }
+    if state.super_key() {
+        mask |= MOD_META;
+    }
+    mask
+}
+
+/// Map a physical key to its Windows virtual-key code.
+#[rustfmt::skip]
+pub fn virtual_key(code: KeyCode) -> Option<u32> {
+    use KeyCode::*;
+
+    let vk = match code {
+        KeyA => 0x41, KeyB => 0x42, KeyC => 0x43, KeyD => 0x44, KeyE => 0x45,
+        KeyF => 0x46, KeyG => 0x47, KeyH => 0x48, KeyI => 0x49, KeyJ => 0x4A,
+        KeyK => 0x4B, KeyL => 0x4C, KeyM => 0x4D, KeyN => 0x4E, KeyO => 0x4F,
+        KeyP => 0x50, KeyQ => 0x51, KeyR => 0x52, KeyS => 0x53, KeyT => 0x54,
+        KeyU => 0x55, KeyV => 0x56, KeyW => 0x57, KeyX => 0x58, KeyY => 0x59,
+        KeyZ => 0x5A,
+        Digit0 => 0x30, Digit1 => 0x31, Digit2 => 0x32, Digit3 => 0x33, Digit4 => 0x34,
+        Digit5 => 0x35, Digit6 => 0x36, Digit7 => 0x37, Digit8 => 0x38, Digit9 => 0x39,
+        F1 => 0x70, F2 => 0x71, F3 => 0x72, F4 => 0x73, F5 => 0x74, F6 => 0x75,
+        F7 => 0x76, F8 => 0x77, F9 => 0x78, F10 => 0x79, F11 => 0x7A, F12 => 0x7B,
+        Numpad0 => 0x60, Numpad1 => 0x61, Numpad2 => 0x62, Numpad3 => 0x63, Numpad4 => 0x64,
+        Numpad5 => 0x65, Numpad6 => 0x66, Numpad7 => 0x67, Numpad8 => 0x68, Numpad9 => 0x69,
+        NumpadMultiply => 0x6A, NumpadAdd => 0x6B, NumpadSubtract => 0x6D,
+        NumpadDecimal => 0x6E, NumpadDivide => 0x6F, NumpadEnter => 0x0D,
+        Backspace => 0x08, Tab => 0x09, Enter => 0x0D, Escape => 0x1B, Space => 0x20,
+        PageUp => 0x21, PageDown => 0x22, End => 0x23, Home => 0x24,
+        ArrowLeft => 0x25, ArrowUp => 0x26, ArrowRight => 0x27, ArrowDown => 0x28,
+        PrintScreen => 0x2C, Insert => 0x2D, Delete => 0x2E, Pause => 0x13,
+        CapsLock => 0x14, NumLock => 0x90, ScrollLock => 0x91,
+        ShiftLeft => 0xA0, ShiftRight => 0xA1, ControlLeft => 0xA2, ControlRight => 0xA3,
+        AltLeft => 0xA4, AltRight => 0xA5, SuperLeft => 0x5B, SuperRight => 0x5C,
+        ContextMenu => 0x5D,
+        Semicolon => 0xBA, Equal => 0xBB, Comma => 0xBC, Minus => 0xBD, Period => 0xBE,
+        Slash => 0xBF, Backquote => 0xC0, BracketLeft => 0xDB, Backslash => 0xDC,
+        BracketRight => 0xDD, Quote => 0xDE,
+        _ => return None,
+    };
+    Some(vk)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zrc_proto::v1::InputEventTypeV1;
+
+    #[test]
+    fn test_held_key_only_repeats_when_os_repeats() {
+        let mut keys = KeyboardState::default();
+        let down = keys.key_event(KeyCode::KeyA, true, false).unwrap();
+        assert_eq!(down.event_type_enum(), InputEventTypeV1::KeyDown);
+        assert_eq!(down.key_code, 0x41);
+
+        assert!(keys.key_event(KeyCode::KeyA, true, false).is_none());
+        assert!(keys.key_event(KeyCode::KeyA, true, true).is_some());
+
+        let up = keys.key_event(KeyCode::KeyA, false, false).unwrap();
+        assert_eq!(up.event_type_enum(), InputEventTypeV1::KeyUp);
+        assert!(keys.key_event(KeyCode::KeyA, false, false).is_none());
+    }
+
+    #[test]
+    fn test_modifiers_are_attached() {
+        let mut keys = KeyboardState::default();
+        keys.set_modifiers(ModifiersState::SHIFT | ModifiersState::CONTROL);
+        let event = keys.key_event(KeyCode::KeyC, true, false).unwrap();
+        assert_eq!(event.modifiers, MOD_SHIFT | MOD_CTRL);
+    }
+
+    #[test]
+    fn test_relative_motion_accumulates_subpixel_deltas() {
+        let mut mouse = RelativeMouse::default();
+        assert!(mouse.motion(5.0, 5.0).is_none());
+
+        assert!(mouse.toggle());
+        assert!(mouse.motion(0.5, -0.5).is_none());
+        let event = mouse.motion(0.75, -0.75).unwrap();
+        assert_eq!(event.event_type_enum(), InputEventTypeV1::MouseMoveRelative);
+        assert_eq!((event.mouse_x, event.mouse_y), (1, -1));
+
+        assert!(!mouse.toggle());
+        assert!(mouse.motion(3.0, 0.0).is_none());
+    }
+
+    #[test]
+    fn test_map_to_frame() {
+        let map = |pos, window, frame, lock| map_to_frame(pos, Viewport::fit(window, frame, lock), frame);
+
+        // Same size: identity
+        assert_eq!(map((10.0, 20.0), (640, 480), (640, 480), true), Some((10, 20)));
+
+        // 2x scale, 100px bars left and right (window 1000x600, frame 400x300)
+        assert_eq!(map((100.0, 0.0), (1000, 600), (400, 300), true), Some((0, 0)));
+        assert_eq!(map((500.0, 300.0), (1000, 600), (400, 300), true), Some((200, 150)));
+        assert_eq!(map((899.0, 599.0), (1000, 600), (400, 300), true), Some((399, 299)));
+
+        // Over the letterbox bars clamps to the frame edge
+        assert_eq!(map((20.0, 300.0), (1000, 600), (400, 300), true), Some((0, 150)));
+        assert_eq!(map((990.0, 300.0), (1000, 600), (400, 300), true), Some((399, 150)));
+
+        // Window smaller than the frame: scaled down
+        assert_eq!(map((640.0, 360.0), (1280, 720), (1920, 1080), true), Some((960, 540)));
+
+        // Stretched (no aspect lock)
+        assert_eq!(map((500.0, 300.0), (1000, 600), (400, 300), false), Some((200, 150)));
+        assert_eq!(map((999.0, 0.0), (1000, 600), (400, 300), false), Some((399, 0)));
+
+        // Unknown frame size
+        assert_eq!(map((1.0, 1.0), (640, 480), (0, 0), true), None);
+    }
+
+    #[test]
+    fn test_release_all_clears_held_keys() {
+        let mut keys = KeyboardState::default();
+        keys.key_event(KeyCode::KeyW, true, false);
+        keys.key_event(KeyCode::ShiftLeft, true, false);
+        assert_eq!(keys.release_all().len(), 2);
+        assert!(keys.release_all().is_empty());
+    }
+
+    #[test]
+    fn test_keepalive_pings_only_when_quiet() {
+        use zrc_proto::v1::control_msg_v1::Payload;
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mut input = InputSender::new(tx);
+        let start = Instant::now();
+
+        input.keepalive(start + KEEPALIVE_INTERVAL / 2);
+        assert!(rx.try_recv().is_err());
+
+        // Input counts as activity, so the ping waits a full interval after it
+        input.send(InputEventV1::mouse_move(1, 1));
+        assert!(matches!(rx.try_recv().unwrap().payload, Some(Payload::Input(_))));
+        let sent = input.last_sent;
+        input.keepalive(sent + KEEPALIVE_INTERVAL / 2);
+        assert!(rx.try_recv().is_err());
+
+        input.keepalive(sent + KEEPALIVE_INTERVAL);
+        let ping = rx.try_recv().unwrap();
+        assert!(matches!(ping.payload, Some(Payload::Ping(_))));
+        assert_eq!(ping.sequence_number, 2);
+    }
+}
@@ -1,22 +1,75 @@
 #![forbid(unsafe_code)]
 
-use anyhow::Context;
+pub mod convert;
+pub mod hud;
+pub mod input;
+mod overlay;
+pub mod ring;
+pub mod screenshot;
+pub mod viewport;
+
 use pixels::{Pixels, SurfaceTexture};
-use std::sync::{Arc, Mutex};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Instant;
 use tokio::sync::mpsc;
 use winit::{
     dpi::LogicalSize,
-    event::{ElementState, Event, MouseButton, WindowEvent},
+    event::{DeviceEvent, ElementState, Event, MouseButton, WindowEvent},
     event_loop::{ControlFlow, EventLoop},
-    window::WindowBuilder,
+    keyboard::{KeyCode, PhysicalKey},
+    window::{CursorGrabMode, Fullscreen, Window, WindowBuilder},
 };
 
+use crate::convert::FrameConverters;
+use crate::hud::HudMeter;
+use crate::input::{map_to_frame, InputSender, KeyboardState, RelativeMouse};
+use crate::ring::FrameRing;
+use crate::screenshot::Screenshot;
+use crate::viewport::{Viewport, LETTERBOX_COLOR};
 use zrc_core::quic_mux::FramePacketV1;
-use zrc_proto::v1::{ControlMsgV1, InputEventV1, MouseMoveV1, MouseButtonV1};
+use zrc_proto::v1::{ControlMsgV1, InputEventV1};
+
+/// Toggles the FPS / frame size HUD.
+const HUD_TOGGLE: KeyCode = KeyCode::F3;
+/// Toggles relative mouse mode (pointer captured, motion sent as deltas).
+const RELATIVE_MODE_TOGGLE: KeyCode = KeyCode::F8;
+/// Toggles the dropped/received frame counter overlay.
+const STATS_OVERLAY_TOGGLE: KeyCode = KeyCode::F9;
+/// Toggles borderless fullscreen.
+const FULLSCREEN_TOGGLE: KeyCode = KeyCode::F11;
+/// Saves the displayed frame as a PNG.
+const SCREENSHOT_KEY: KeyCode = KeyCode::F12;
+/// Frames buffered between the receiver thread and the render loop.
+const FRAME_RING_CAPACITY: usize = 4;
+
+/// Viewer settings.
+#[derive(Debug, Clone)]
+pub struct ViewerOptions {
+    /// Directory screenshots are written to.
+    pub screenshot_dir: PathBuf,
+    /// Keep the remote aspect ratio, letterboxing the spare window area,
+    /// instead of stretching the frame to the window.
+    pub lock_aspect: bool,
+}
+
+impl Default for ViewerOptions {
+    fn default() -> Self {
+        Self { screenshot_dir: PathBuf::from("."), lock_aspect: true }
+    }
+}
 
 pub fn run_viewer(
+    frames_rx: mpsc::UnboundedReceiver<FramePacketV1>,
+    input_tx: mpsc::UnboundedSender<ControlMsgV1>,
+) -> anyhow::Result<()> {
+    run_viewer_with_options(frames_rx, input_tx, ViewerOptions::default())
+}
+
+pub fn run_viewer_with_options(
     mut frames_rx: mpsc::UnboundedReceiver<FramePacketV1>,
-    mut input_tx: mpsc::UnboundedSender<ControlMsgV1>,
+    input_tx: mpsc::UnboundedSender<ControlMsgV1>,
+    options: ViewerOptions,
 ) -> anyhow::Result<()> {
     let event_loop = EventLoop::new()?;
     let window = WindowBuilder::new()
@@ -24,23 +77,42 @@ pub fn run_viewer(
         .with_inner_size(LogicalSize::new(960.0, 540.0))
         .build(&event_loop)?;
 
-    let latest: Arc<Mutex<Option<FramePacketV1>>> = Arc::new(Mutex::new(None));
-    let latest2 = latest.clone();
+    let ring = Arc::new(FrameRing::new(FRAME_RING_CAPACITY));
+    let ring2 = ring.clone();
 
     // Receive frames on a background thread (winit wants main thread)
     std::thread::spawn(move || {
         while let Some(pkt) = frames_rx.blocking_recv() {
-            *latest2.lock().unwrap() = Some(pkt);
+            ring2.push(pkt);
         }
     });
 
-    // Start with a placeholder surface; will resize once we have a frame
+    // The buffer tracks the window size; frames are scaled into a viewport.
+    // Its size is kept here since Pixels doesn't report it.
+    let size = window.inner_size();
+    let mut buffer = (size.width.max(1), size.height.max(1));
     let mut pixels = {
-        let size = window.inner_size();
         let st = SurfaceTexture::new(size.width, size.height, &window);
-        Pixels::new(320, 180, st)?
+        Pixels::new(buffer.0, buffer.1, st)?
     };
 
+    let converters = FrameConverters::default();
+    let mut input = InputSender::new(input_tx);
+    let mut keyboard = KeyboardState::default();
+    let mut cursor = (0i32, 0i32);
+    // Remote frame size from the last packet, for cursor mapping
+    let mut remote_size = (0u32, 0u32);
+    // Last frame converted to RGBA at remote resolution
+    let mut frame_rgba: Vec<u8> = Vec::new();
+    let mut relative = RelativeMouse::default();
+    let mut show_stats = false;
+    let mut show_hud = false;
+    let mut hud = HudMeter::default();
+    let mut screenshot_requested = false;
+    // Set when the buffer must be redrawn and uploaded: a new frame, a
+    // resize or an overlay toggle
+    let mut dirty = true;
+
     event_loop.run(move |event, elwt| {
         elwt.set_control_flow(ControlFlow::Poll);
 
@@ -48,16 +120,13 @@ pub fn run_viewer(
             Event::WindowEvent { event, .. } => match event {
                 WindowEvent::CloseRequested => elwt.exit(),
                 WindowEvent::CursorMoved { position, .. } => {
-                    // Send mouse move (absolute in window space for MVP)
-                    let msg = ControlMsgV1 {
-                        msg: Some(zrc_proto::v1::control_msg_v1::Msg::Input(InputEventV1 {
-                            kind: Some(zrc_proto::v1::input_event_v1::Kind::MouseMove(MouseMoveV1 {
-                                x: position.x as i32,
-                                y: position.y as i32,
-                            })),
-                        })),
-                    };
-                    let _ = input_tx.send(msg);
+                    let viewport = Viewport::fit(buffer, remote_size, options.lock_aspect);
+                    if let Some(mapped) = map_to_frame((position.x, position.y), viewport, remote_size) {
+                        cursor = mapped;
+                        if !relative.enabled() {
+                            input.send(InputEventV1::mouse_move(cursor.0, cursor.1));
+                        }
+                    }
                 }
                 WindowEvent::MouseInput { state, button, .. } => {
                     let b = match button {
@@ -66,48 +135,141 @@ pub fn run_viewer(
                         MouseButton::Middle => 3,
                         _ => 1,
                     };
-                    let down = state == ElementState::Pressed;
-                    let msg = ControlMsgV1 {
-                        msg: Some(zrc_proto::v1::control_msg_v1::Msg::Input(InputEventV1 {
-                            kind: Some(zrc_proto::v1::input_event_v1::Kind::MouseButton(MouseButtonV1 {
-                                button: b,
-                                down,
-                            })),
-                        })),
-                    };
-                    let _ = input_tx.send(msg);
+                    input.send(match state {
+                        ElementState::Pressed => InputEventV1::mouse_down(cursor.0, cursor.1, b),
+                        ElementState::Released => InputEventV1::mouse_up(cursor.0, cursor.1, b),
+                    });
+                }
+                WindowEvent::ModifiersChanged(modifiers) => {
+                    keyboard.set_modifiers(modifiers.state());
+                }
+                WindowEvent::KeyboardInput { event, .. } => {
+                    let pressed = event.state == ElementState::Pressed;
+                    match event.physical_key {
+                        // Escape is kept local so the viewer can always be closed
+                        PhysicalKey::Code(KeyCode::Escape) => {
+                            if pressed {
+                                elwt.exit();
+                            }
+                        }
+                        PhysicalKey::Code(RELATIVE_MODE_TOGGLE) => {
+                            if pressed && !event.repeat {
+                                let enabled = relative.toggle();
+                                set_pointer_captured(&window, enabled);
+                            }
+                        }
+                        PhysicalKey::Code(SCREENSHOT_KEY) => {
+                            // Nothing to save until the first frame arrives
+                            if pressed && !event.repeat && !frame_rgba.is_empty() {
+                                screenshot_requested = true;
+                            }
+                        }
+                        PhysicalKey::Code(FULLSCREEN_TOGGLE) => {
+                            if pressed && !event.repeat {
+                                let fullscreen = match window.fullscreen() {
+                                    Some(_) => None,
+                                    None => Some(Fullscreen::Borderless(None)),
+                                };
+                                window.set_fullscreen(fullscreen);
+                            }
+                        }
+                        PhysicalKey::Code(HUD_TOGGLE) => {
+                            if pressed && !event.repeat {
+                                show_hud = !show_hud;
+                                hud.reset();
+                                dirty = true;
+                            }
+                        }
+                        PhysicalKey::Code(STATS_OVERLAY_TOGGLE) => {
+                            if pressed && !event.repeat {
+                                show_stats = !show_stats;
+                                dirty = true;
+                            }
+                        }
+                        PhysicalKey::Code(code) => {
+                            if let Some(key) = keyboard.key_event(code, pressed, event.repeat) {
+                                input.send(key);
+                            }
+                        }
+                        PhysicalKey::Unidentified(_) => {}
+                    }
+                }
+                WindowEvent::Focused(false) => {
+                    for key in keyboard.release_all() {
+                        input.send(key);
+                    }
+                    // Never leave the local pointer grabbed behind another window
+                    if relative.enabled() {
+                        relative.toggle();
+                        set_pointer_captured(&window, false);
+                    }
                 }
                 WindowEvent::Resized(size) => {
-                    pixels.resize_surface(size.width, size.height);
+                    // Minimized windows report 0x0; keep the old buffer
+                    if size.width > 0 && size.height > 0 {
+                        let resized = pixels
+                            .resize_surface(size.width, size.height)
+                            .and_then(|_| pixels.resize_buffer(size.width, size.height));
+                        if resized.is_err() {
+                            elwt.exit();
+                        }
+                        buffer = (size.width, size.height);
+                        dirty = true;
+                    }
+                }
+                WindowEvent::Occluded(false) => {
+                    dirty = true;
                 }
                 _ => {}
             },
+            Event::DeviceEvent { event: DeviceEvent::MouseMotion { delta }, .. } => {
+                if let Some(motion) = relative.motion(delta.0, delta.1) {
+                    input.send(motion);
+                }
+            }
             Event::AboutToWait => {
+                input.keepalive(Instant::now());
                 window.request_redraw();
             }
             Event::WindowEvent { event: WindowEvent::RedrawRequested, .. } => {
-                if let Some(pkt) = latest.lock().unwrap().clone() {
-                    // Resize pixel buffer to match incoming frame
-                    if pixels.texture_width() != pkt.width || pixels.texture_height() != pkt.height {
-                        let size = window.inner_size();
-                        let st = SurfaceTexture::new(size.width, size.height, &window);
-                        pixels = Pixels::new(pkt.width, pkt.height, st).context("pixels resize")?;
-                    }
+                if let Some(pkt) = ring.take_latest() {
+                    // Convert once per new frame; the converter for pkt.format does the rest
+                    remote_size = (pkt.width, pkt.height);
+                    frame_rgba.resize(pkt.width as usize * pkt.height as usize * 4, 0);
+                    converters.convert(&pkt, &mut frame_rgba);
+                    dirty = true;
+                }
 
-                    // pkt.format=1 is BGRA; pixels expects RGBA. Convert in place.
-                    let frame = pixels.frame_mut();
-                    let mut i = 0usize;
-                    while i + 4 <= pkt.pixels.len() && i + 4 <= frame.len() {
-                        let b = pkt.pixels[i];
-                        let g = pkt.pixels[i + 1];
-                        let r = pkt.pixels[i + 2];
-                        let a = pkt.pixels[i + 3];
-                        frame[i] = r;
-                        frame[i + 1] = g;
-                        frame[i + 2] = b;
-                        frame[i + 3] = a;
-                        i += 4;
-                    }
+                // Snapshot the converted frame, never the overlays or letterbox
+                if std::mem::take(&mut screenshot_requested) {
+                    let shot = Screenshot {
+                        width: remote_size.0,
+                        height: remote_size.1,
+                        rgba: frame_rgba.clone(),
+                    };
+                    screenshot::save_async(shot, options.screenshot_dir.clone());
+                }
+
+                // Nothing changed; the last upload is still on screen
+                if !std::mem::take(&mut dirty) {
+                    return;
+                }
+
+                let viewport = Viewport::fit(buffer, remote_size, options.lock_aspect);
+                let frame = pixels.frame_mut();
+                viewport::fill(frame, LETTERBOX_COLOR);
+                viewport::blit_scaled(&frame_rgba, remote_size, frame, buffer.0, viewport);
+
+                // Overlays are drawn after conversion and cost nothing when off
+                let width = buffer.0 as usize;
+                let mut top = 0;
+                if show_hud {
+                    hud.sample(Instant::now(), ring.stats());
+                    top = overlay::draw_hud(frame, width, top, &hud.lines());
+                }
+                if show_stats {
+                    let stats = ring.stats();
+                    overlay::draw_frame_stats(frame, width, top, stats.dropped, stats.received);
                 }
 
                 if pixels.render().is_err() {
@@ -121,3 +283,15 @@ pub fn run_viewer(
     Ok(())
 }
 
+/// Hide and grab the local cursor for relative mode, or restore it.
+fn set_pointer_captured(window: &Window, captured: bool) {
+    if captured {
+        // Locked is unsupported on some platforms (e.g. Windows); confine instead
+        let _ = window
+            .set_cursor_grab(CursorGrabMode::Locked)
+            .or_else(|_| window.set_cursor_grab(CursorGrabMode::Confined));
+    } else {
+        let _ = window.set_cursor_grab(CursorGrabMode::None);
+    }
+    window.set_cursor_visible(!captured);
+}
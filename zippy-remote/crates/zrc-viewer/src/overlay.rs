@@ -0,0 +1,98 @@
+//! Minimal diagnostics overlay drawn straight into the RGBA frame buffer.
+//!
+//! Uses a built-in 3x5 bitmap font (digits, `/`, space and the few capitals
+//! the overlays need) so no font or text rendering dependency is needed.
+
+/// Pixel scale applied to each font cell.
+const SCALE: usize = 3;
+/// Glyph size in font cells, plus one cell of spacing.
+const GLYPH_W: usize = 3;
+const GLYPH_H: usize = 5;
+const ADVANCE: usize = GLYPH_W + 1;
+/// Margin around the text, in font cells.
+const PAD: usize = 1;
+
+const BACKGROUND: [u8; 4] = [0x00, 0x00, 0x00, 0xFF];
+const TEXT_OK: [u8; 4] = [0x40, 0xFF, 0x40, 0xFF];
+const TEXT_INFO: [u8; 4] = [0xFF, 0xFF, 0xFF, 0xFF];
+const TEXT_DROPPING: [u8; 4] = [0xFF, 0x50, 0x50, 0xFF];
+
+/// Draw `dropped/received` starting at row `top` of an RGBA buffer that is
+/// `width` pixels wide. Text turns red once any frame has been dropped.
+/// Returns the first row below the drawn box.
+pub fn draw_frame_stats(frame: &mut [u8], width: usize, top: usize, dropped: u64, received: u64) -> usize {
+    let color = if dropped > 0 { TEXT_DROPPING } else { TEXT_OK };
+    draw_lines(frame, width, top, &[format!("{dropped}/{received}")], color)
+}
+
+/// Draw HUD text lines starting at row `top`; returns the first row below.
+pub fn draw_hud(frame: &mut [u8], width: usize, top: usize, lines: &[String]) -> usize {
+    draw_lines(frame, width, top, lines, TEXT_INFO)
+}
+
+/// Draw lines of text on a black box in the left corner at row `top`.
+fn draw_lines(frame: &mut [u8], width: usize, top: usize, lines: &[String], color: [u8; 4]) -> usize {
+    let longest = lines.iter().map(|l| l.len()).max().unwrap_or(0);
+    let box_w = (longest * ADVANCE + PAD * 2) * SCALE;
+    let box_h = (lines.len() * (GLYPH_H + 1) + PAD * 2) * SCALE;
+    fill_rect(frame, width, 0, top, box_w, box_h, BACKGROUND);
+
+    for (line_no, text) in lines.iter().enumerate() {
+        let origin_y = top + (PAD + line_no * (GLYPH_H + 1)) * SCALE;
+        for (i, ch) in text.chars().enumerate() {
+            let Some(rows) = glyph(ch) else { continue };
+            let origin_x = (PAD + i * ADVANCE) * SCALE;
+            for (gy, row) in rows.iter().enumerate() {
+                for gx in 0..GLYPH_W {
+                    if row & (0b100 >> gx) != 0 {
+                        let x = origin_x + gx * SCALE;
+                        let y = origin_y + gy * SCALE;
+                        fill_rect(frame, width, x, y, SCALE, SCALE, color);
+                    }
+                }
+            }
+        }
+    }
+    top + box_h
+}
+
+/// Fill a rectangle, clipped to the buffer.
+fn fill_rect(frame: &mut [u8], width: usize, x: usize, y: usize, w: usize, h: usize, color: [u8; 4]) {
+    if width == 0 || x >= width {
+        return;
+    }
+    let x_end = (x + w).min(width);
+    for row in y..y + h {
+        for col in x..x_end {
+            let i = (row * width + col) * 4;
+            match frame.get_mut(i..i + 4) {
+                Some(px) => px.copy_from_slice(&color),
+                None => return,
+            }
+        }
+    }
+}
+
+/// 3x5 glyph rows, most significant of the low 3 bits is the left column.
+fn glyph(ch: char) -> Option<[u8; GLYPH_H]> {
+    Some(match ch {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b010, 0b010, 0b010],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        '/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+        ' ' => [0b000; GLYPH_H],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        _ => return None,
+    })
+}
@@ -0,0 +1,109 @@
+//! Bounded frame hand-off between the network thread and the render loop.
+//!
+//! The receiver pushes into a fixed-size lock-free ring that evicts the oldest
+//! frame when full; the render loop takes only the newest frame. Every frame
+//! that is never rendered is counted, so drops can be told apart from a
+//! quiet stream.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crossbeam_queue::ArrayQueue;
+use zrc_core::quic_mux::FramePacketV1;
+
+/// Counters for frames passing through a [`FrameRing`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FrameStats {
+    /// Frames pushed by the receiver.
+    pub received: u64,
+    /// Pixel payload bytes pushed by the receiver.
+    pub received_bytes: u64,
+    /// Frames handed to the renderer.
+    pub rendered: u64,
+    /// Frames evicted or skipped without being rendered.
+    pub dropped: u64,
+}
+
+/// Keeps the newest `capacity` frames, dropping the oldest on overflow.
+pub struct FrameRing {
+    queue: ArrayQueue<FramePacketV1>,
+    received: AtomicU64,
+    received_bytes: AtomicU64,
+    rendered: AtomicU64,
+    dropped: AtomicU64,
+}
+
+impl FrameRing {
+    /// Create a ring holding at most `capacity` frames (at least 1).
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            queue: ArrayQueue::new(capacity.max(1)),
+            received: AtomicU64::new(0),
+            received_bytes: AtomicU64::new(0),
+            rendered: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    /// Push a frame, evicting the oldest one if the ring is full. Never blocks.
+    pub fn push(&self, pkt: FramePacketV1) {
+        self.received.fetch_add(1, Ordering::Relaxed);
+        self.received_bytes.fetch_add(pkt.pixels.len() as u64, Ordering::Relaxed);
+        if self.queue.force_push(pkt).is_some() {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Take the newest frame, counting any older queued frames as dropped.
+    pub fn take_latest(&self) -> Option<FramePacketV1> {
+        let mut latest = self.queue.pop()?;
+        while let Some(newer) = self.queue.pop() {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            latest = newer;
+        }
+        self.rendered.fetch_add(1, Ordering::Relaxed);
+        Some(latest)
+    }
+
+    /// Snapshot of the frame counters.
+    pub fn stats(&self) -> FrameStats {
+        FrameStats {
+            received: self.received.load(Ordering::Relaxed),
+            received_bytes: self.received_bytes.load(Ordering::Relaxed),
+            rendered: self.rendered.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(n: u32) -> FramePacketV1 {
+        FramePacketV1 { width: n, height: 1, stride: 0, format: 1, pixels: Vec::new() }
+    }
+
+    #[test]
+    fn test_overflow_drops_oldest() {
+        let ring = FrameRing::new(2);
+        for n in 1..=5 {
+            ring.push(frame(n));
+        }
+        assert_eq!(ring.take_latest().unwrap().width, 5);
+        assert!(ring.take_latest().is_none());
+        assert_eq!(
+            ring.stats(),
+            FrameStats { received: 5, received_bytes: 0, rendered: 1, dropped: 4 }
+        );
+    }
+
+    #[test]
+    fn test_take_latest_without_backlog() {
+        let ring = FrameRing::new(4);
+        ring.push(frame(1));
+        assert_eq!(ring.take_latest().unwrap().width, 1);
+        ring.push(frame(2));
+        assert_eq!(ring.take_latest().unwrap().width, 2);
+        assert_eq!(ring.stats().dropped, 0);
+    }
+}
@@ -0,0 +1,56 @@
+//! Saving the displayed frame to disk as PNG.
+
+use std::path::{Path, PathBuf};
+use std::thread::JoinHandle;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A copy of the converted RGBA frame buffer.
+pub struct Screenshot {
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Vec<u8>,
+}
+
+/// Timestamped file name including the remote resolution, e.g.
+/// `zrc-1760601600123-1920x1080.png`.
+pub fn screenshot_path(dir: &Path, width: u32, height: u32, at: SystemTime) -> PathBuf {
+    let millis = at.duration_since(UNIX_EPOCH).map(|d| d.as_millis()).unwrap_or(0);
+    dir.join(format!("zrc-{millis}-{width}x{height}.png"))
+}
+
+/// Encode and write `shot` on a background thread so the render loop never
+/// stalls on disk I/O. The saved path is printed to stdout.
+pub fn save_async(shot: Screenshot, dir: PathBuf) -> JoinHandle<anyhow::Result<PathBuf>> {
+    std::thread::spawn(move || {
+        let result = save(&shot, &dir);
+        match &result {
+            Ok(path) => println!("Saved screenshot to {}", path.display()),
+            Err(e) => eprintln!("Screenshot failed: {e:#}"),
+        }
+        result
+    })
+}
+
+fn save(shot: &Screenshot, dir: &Path) -> anyhow::Result<PathBuf> {
+    use anyhow::Context;
+
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("creating screenshot directory {}", dir.display()))?;
+    let path = screenshot_path(dir, shot.width, shot.height, SystemTime::now());
+    image::save_buffer(&path, &shot.rgba, shot.width, shot.height, image::ExtendedColorType::Rgba8)
+        .with_context(|| format!("writing {}", path.display()))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_screenshot_path_has_timestamp_and_resolution() {
+        let at = UNIX_EPOCH + Duration::from_millis(1_760_601_600_123);
+        let path = screenshot_path(Path::new("shots"), 1920, 1080, at);
+        assert_eq!(path, Path::new("shots").join("zrc-1760601600123-1920x1080.png"));
+    }
+}
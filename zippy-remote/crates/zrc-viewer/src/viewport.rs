@@ -0,0 +1,111 @@
+//! Placement of the remote frame inside the window.
+//!
+//! The frame is scaled into a viewport rectangle of the window-sized buffer;
+//! with aspect lock the viewport keeps the remote aspect ratio and the spare
+//! area is letterboxed. The same rectangle drives cursor mapping so input
+//! lands where the picture is drawn.
+
+/// Color of the letterbox bars and of the window before the first frame.
+pub const LETTERBOX_COLOR: [u8; 4] = [0x20, 0x20, 0x20, 0xFF];
+
+/// Rectangle the frame is drawn into, in window pixels.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Viewport {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Viewport {
+    /// Viewport for a `frame` sized image in a `window` sized buffer.
+    ///
+    /// With `lock_aspect` the frame is scaled as large as fits while keeping
+    /// its aspect ratio and centered; otherwise it stretches to the window.
+    pub fn fit(window: (u32, u32), frame: (u32, u32), lock_aspect: bool) -> Self {
+        if !lock_aspect || frame.0 == 0 || frame.1 == 0 {
+            return Self { x: 0, y: 0, width: window.0, height: window.1 };
+        }
+        let scale = (window.0 as f64 / frame.0 as f64).min(window.1 as f64 / frame.1 as f64);
+        let width = ((frame.0 as f64 * scale).round() as u32).min(window.0);
+        let height = ((frame.1 as f64 * scale).round() as u32).min(window.1);
+        Self {
+            x: (window.0 - width) / 2,
+            y: (window.1 - height) / 2,
+            width,
+            height,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+}
+
+/// Fill an RGBA buffer with a solid color.
+pub fn fill(dst: &mut [u8], color: [u8; 4]) {
+    for px in dst.chunks_exact_mut(4) {
+        px.copy_from_slice(&color);
+    }
+}
+
+/// Nearest-neighbour scale of the `src` RGBA image (`src_size`) into
+/// `viewport` of the `dst` RGBA buffer, which is `dst_width` pixels wide.
+/// Writes are clamped to `dst`.
+pub fn blit_scaled(src: &[u8], src_size: (u32, u32), dst: &mut [u8], dst_width: u32, viewport: Viewport) {
+    let (sw, sh) = (src_size.0 as usize, src_size.1 as usize);
+    if sw == 0 || sh == 0 || viewport.is_empty() || src.len() < sw * sh * 4 {
+        return;
+    }
+    let (vw, vh) = (viewport.width as usize, viewport.height as usize);
+    let dst_width = dst_width as usize;
+    let x_end = (viewport.x as usize + vw).min(dst_width);
+
+    for dy in 0..vh {
+        let sy = dy * sh / vh;
+        let row = (viewport.y as usize + dy) * dst_width;
+        for x in viewport.x as usize..x_end {
+            let sx = (x - viewport.x as usize) * sw / vw;
+            let s = (sy * sw + sx) * 4;
+            let d = (row + x) * 4;
+            match dst.get_mut(d..d + 4) {
+                Some(px) => px.copy_from_slice(&src[s..s + 4]),
+                None => return,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fit_letterboxes_with_aspect_lock() {
+        // 4:3 frame in a 5:3 window: bars left and right
+        let vp = Viewport::fit((1000, 600), (400, 300), true);
+        assert_eq!(vp, Viewport { x: 100, y: 0, width: 800, height: 600 });
+
+        // 16:9 frame in a 4:3 window: bars top and bottom
+        let vp = Viewport::fit((800, 600), (1920, 1080), true);
+        assert_eq!(vp, Viewport { x: 0, y: 75, width: 800, height: 450 });
+
+        // Without aspect lock the frame stretches
+        let vp = Viewport::fit((1000, 600), (400, 300), false);
+        assert_eq!(vp, Viewport { x: 0, y: 0, width: 1000, height: 600 });
+    }
+
+    #[test]
+    fn test_blit_scaled_into_letterboxed_viewport() {
+        // 1x1 red frame into the middle column of a 3x1 buffer
+        let src = [0xFF, 0, 0, 0xFF];
+        let mut dst = vec![0u8; 3 * 4];
+        fill(&mut dst, LETTERBOX_COLOR);
+        let vp = Viewport::fit((3, 1), (1, 1), true);
+        blit_scaled(&src, (1, 1), &mut dst, 3, vp);
+
+        assert_eq!(&dst[0..4], &LETTERBOX_COLOR);
+        assert_eq!(&dst[4..8], &[0xFF, 0, 0, 0xFF]);
+        assert_eq!(&dst[8..12], &LETTERBOX_COLOR);
+    }
+}